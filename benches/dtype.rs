@@ -0,0 +1,73 @@
+//! Measures the translation of a C file of 200 structs of 20 fields each into the IR, and the
+//! cloning of the IR it is translated into, e.g., by `cargo bench --bench dtype`, to compare the
+//! representations of the types across commits.
+
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use kecc::*;
+
+const STRUCTS: usize = 200;
+const FIELDS: usize = 20;
+const ROUNDS: usize = 20;
+
+fn main() {
+    let dir = tempfile::tempdir().expect("temp dir creation failed");
+    let path = dir.path().join("structs.c");
+    std::fs::write(&path, source()).unwrap();
+
+    let mut translate = Duration::ZERO;
+    let mut clone = Duration::ZERO;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        let unit = Parse
+            .translate(&path.as_path())
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+        let ir = Irgen::default()
+            .translate(&unit)
+            .unwrap_or_else(|e| panic!("{}", e));
+        translate += start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            drop(ir.clone());
+        }
+        clone += start.elapsed();
+    }
+
+    println!("translate: {:>8.2?} per round", translate / ROUNDS as u32);
+    println!(
+        "clone:     {:>8.2?} per round",
+        clone / (ROUNDS * ROUNDS) as u32
+    );
+}
+
+/// Returns the source of the structs, each of which refers to the previous ones by pointers and
+/// function pointers, and the declarations using them.
+fn source() -> String {
+    let mut source = "struct base { int x; char y; };\n".to_string();
+    for i in 0..STRUCTS {
+        writeln!(source, "struct s{i} {{").unwrap();
+        for j in 0..FIELDS {
+            let prev = i.saturating_sub(1 + j % 4);
+            let field = match j % 6 {
+                0 => format!("int f{j};"),
+                1 => format!("struct s{i} *f{j};"),
+                2 => format!("long f{j}[4][4];"),
+                3 => format!("int (*f{j})(struct s{prev} *, char *);"),
+                4 => format!("struct base f{j};"),
+                _ => format!("const char *const *f{j}[2];"),
+            };
+            writeln!(source, "    {field}").unwrap();
+        }
+        writeln!(source, "}};").unwrap();
+        writeln!(source, "struct s{i} g{i}[2];").unwrap();
+        writeln!(
+            source,
+            "struct s{i} *h{i}(struct s{i} *, struct s{prev} (*)[4]);",
+            prev = i.saturating_sub(1)
+        )
+        .unwrap();
+    }
+    source
+}
@@ -0,0 +1,79 @@
+//! Counts the heap allocations of parsing, optimizing and lowering an IR file of about 20k lines,
+//! e.g., by `cargo bench --bench memory`, to compare the representations of the IR across commits.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use kecc::*;
+
+const FUNCTIONS: usize = 400;
+
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+static CURRENT_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = COUNT.fetch_add(1, Ordering::Relaxed);
+        let _ = BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        let current = CURRENT_BYTES.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        let _ = PEAK_BYTES.fetch_max(current, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let _ = CURRENT_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn main() {
+    let path = Path::new("examples/ir0/fibonacci.ir");
+    let unit = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let unit = replicate_function(&unit, "fibonacci", FUNCTIONS);
+
+    let dir = tempfile::tempdir().expect("temp dir creation failed");
+    let path = dir.path().join("functions.ir");
+    let mut source = Vec::new();
+    write(&unit, &mut source).unwrap();
+    let lines = source.iter().filter(|byte| **byte == b'\n').count();
+    std::fs::write(&path, source).unwrap();
+    drop(unit);
+    println!("{lines} lines");
+
+    let mut unit = measure("parse", || {
+        IrParse::default()
+            .translate(&path.as_path())
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()))
+    });
+    measure("optimize", || {
+        let _ = O1::default().optimize(&mut unit);
+    });
+    let _ = measure("asmgen", || Asmgen::default().translate(&unit).unwrap());
+}
+
+/// Runs `f`, printing the heap allocations it makes.
+fn measure<T, F: FnOnce() -> T>(name: &str, f: F) -> T {
+    let count = COUNT.load(Ordering::Relaxed);
+    let bytes = BYTES.load(Ordering::Relaxed);
+    let current = CURRENT_BYTES.load(Ordering::Relaxed);
+    PEAK_BYTES.store(current, Ordering::Relaxed);
+
+    let result = f();
+    println!(
+        "{name:<8} {:>10} allocations of {:>12} bytes, peak {:>12} bytes",
+        COUNT.load(Ordering::Relaxed) - count,
+        BYTES.load(Ordering::Relaxed) - bytes,
+        PEAK_BYTES.load(Ordering::Relaxed) - current
+    );
+    result
+}
@@ -0,0 +1,52 @@
+//! Measures the optimization of a translation unit of 200 functions on 1, 2 and 4 threads, e.g.,
+//! by `cargo bench --features parallel --bench parallel`.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use kecc::*;
+
+const FUNCTIONS: usize = 200;
+const ROUNDS: usize = 20;
+
+fn main() {
+    let path = Path::new("examples/ir0/fibonacci.ir");
+    let unit = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let unit = replicate_function(&unit, "fibonacci", FUNCTIONS);
+
+    let mut baseline = None;
+    for threads in [1, 2, 4] {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("thread pool creation failed");
+        let elapsed = pool.install(|| compile(&unit));
+        let first = *baseline.get_or_insert(elapsed);
+        println!(
+            "{threads} threads: {:>8.2?} per round, {:.2}x",
+            elapsed / ROUNDS as u32,
+            first.as_secs_f64() / elapsed.as_secs_f64()
+        );
+    }
+}
+
+/// Returns the time spent optimizing `ROUNDS` copies of `unit`.
+fn compile(unit: &ir::TranslationUnit) -> Duration {
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..ROUNDS {
+        let mut unit = unit.clone();
+        let start = Instant::now();
+        let _ = ParallelFunctionPass::new((
+            SimplifyArith::default(),
+            (
+                Sink::default(),
+                (LoopRotate::default(), TailMerge::default()),
+            ),
+        ))
+        .optimize(&mut unit);
+        elapsed += start.elapsed();
+    }
+    elapsed
+}
@@ -1,17 +1,27 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 
 use std::ffi::OsStr;
 use std::io::Write;
 use std::os::unix::io::{FromRawFd, IntoRawFd};
-use std::path::Path;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::time::Duration;
 
 use lang_c::ast::TranslationUnit;
 use tempfile::tempdir;
 
 use kecc::{
-    ir, ok_or_exit, write, Asmgen, Deadcode, Gvn, IrParse, IrVisualizer, Irgen, Mem2reg, Optimize,
-    Parse, SimplifyCfg, Translate, O1,
+    asm, check_language_std, check_restrict_aliasing, check_sequence_points, install_crash_handler,
+    ir, ok_or_exit, optimizer_out_of_time, set_max_nesting_depth, set_optimizer_budget, set_stage,
+    set_target_data, some_or, source_stamp, take_budget_diagnostics, target, write, ArtifactKind,
+    Asmgen, Cache, Cgen, CharSignedness, CompileArtifacts, CompileOptions, CrashReport,
+    DeadFunction, DeadStore, Deadcode, FoldConstLoad, ForwardPointers, Gvn, Instrumentation,
+    IrParse, IrVisualizer, Irgen, LanguageStd, LineMap, LoopRotate, LowerSwitch, LowerSwitchInner,
+    Mem2reg, MemoryStats, MinMax, Normalized, Optimize, OptimizerBudget, OverflowPolicy,
+    ParallelFunctionPass, Parse, PassRegistry, Report, Severity, SimplifyArith, SimplifyArithInner,
+    SimplifyCfg, Sink, SplitMisaligned, StatementCoverage, StrcmpDispatch, SwitchThresholds,
+    TailMerge, TargetData, Translate, DEFAULT_MAX_NESTING_DEPTH, O1,
 };
 
 #[derive(Debug, Parser)]
@@ -25,6 +35,11 @@ struct KeccCli {
     #[clap(short, long)]
     print: bool,
 
+    /// Prints the input AST normalized for diffing, with one declarator per declaration, fully
+    /// parenthesized operands and braces around every body
+    #[clap(long)]
+    normalize: bool,
+
     /// Generates IR
     #[clap(short, long)]
     irgen: bool,
@@ -33,6 +48,11 @@ struct KeccCli {
     #[clap(long)]
     irparse: bool,
 
+    /// Fixes the mechanical mistakes of the input IR file, e.g., duplicate block labels and
+    /// blocks without exits, instead of refusing it
+    #[clap(long)]
+    fix: bool,
+
     /// Prints the input IR AST
     #[clap(long)]
     irprint: bool,
@@ -41,6 +61,11 @@ struct KeccCli {
     #[clap(long)]
     irrun: bool,
 
+    /// Executes the input file even if it references functions or `extern` variables defined in
+    /// another unit, whose calls fail and whose values are undefined
+    #[clap(long = "allow-undefined")]
+    allow_undefined: bool,
+
     /// Visualizes IR
     #[clap(long, value_name = "FILE")]
     irviz: Option<String>,
@@ -53,6 +78,10 @@ struct KeccCli {
     #[clap(long = "simplify-cfg")]
     simplify_cfg: bool,
 
+    /// Forwards the accesses through local pointers always pointing to the same local variable
+    #[clap(long = "forward-pointers")]
+    forward_pointers: bool,
+
     /// Performs mem2reg
     #[clap(long)]
     mem2reg: bool,
@@ -65,10 +94,190 @@ struct KeccCli {
     #[clap(long)]
     gvn: bool,
 
+    /// Performs constant folding and algebraic simplification
+    #[clap(long = "simplify-arith")]
+    simplify_arith: bool,
+
+    /// Folds loads from `const` global variables
+    #[clap(long = "fold-const-load")]
+    fold_const_load: bool,
+
+    /// Sinks computations into the branch using them
+    #[clap(long)]
+    sink: bool,
+
+    /// Replaces the conditionals selecting the minimum, the maximum or the absolute value
+    #[clap(long = "min-max")]
+    min_max: bool,
+
+    /// Rotates loops into the bottom-test form
+    #[clap(long = "loop-rotate")]
+    loop_rotate: bool,
+
+    /// Lowers switches into comparisons, keeping the dense ones for jump tables
+    #[clap(long = "lower-switch")]
+    lower_switch: bool,
+
+    /// Merges the identical tails of the blocks jumping to the same block
+    #[clap(long = "tail-merge")]
+    tail_merge: bool,
+
+    /// Removes the stores overwritten before they are read, telling apart the pointers by
+    /// `restrict`
+    #[clap(long = "dead-store")]
+    dead_store: bool,
+
+    /// Dispatches the chains of `strcmp` of a string with literals by its characters
+    #[clap(long = "strcmp-dispatch")]
+    strcmp_dispatch: bool,
+
+    /// Runs the passes PASSES, separated by commas and named after their flags, e.g.,
+    /// `mem2reg,gvn,simplify-cfg`, in turn after the other passes
+    #[clap(long, value_name = "PASSES")]
+    passes: Option<String>,
+
+    /// Stops optimizing once SECONDS of wall-clock time elapsed, keeping the IR optimized so far
+    #[clap(long = "time-limit", value_name = "SECONDS")]
+    time_limit: Option<f64>,
+
+    /// Stops repeating a group of passes after N iterations without converging
+    #[clap(long = "max-iterations", value_name = "N", default_value_t = OptimizerBudget::DEFAULT_MAX_ITERATIONS)]
+    max_iterations: usize,
+
+    /// Stops repeating a group of passes once the IR grew beyond PERCENT of its size
+    #[clap(long = "max-ir-growth", value_name = "PERCENT", default_value_t = OptimizerBudget::DEFAULT_MAX_GROWTH_PERCENT)]
+    max_ir_growth: usize,
+
+    /// Prints the changes the pass PASS, e.g., `gvn` or `O1`, makes to the IR to stderr
+    #[clap(long = "print-ir-diff-after", value_name = "PASS")]
+    print_ir_diff_after: Option<String>,
+
+    /// Compares the value of a switch with at most N cases in turn
+    #[clap(long = "switch-max-linear", value_name = "N", default_value_t = SwitchThresholds::DEFAULT_MAX_LINEAR_CASES)]
+    switch_max_linear: usize,
+
+    /// Keeps a switch whose cases are at least PERCENT dense for a jump table
+    #[clap(long = "switch-min-density", value_name = "PERCENT", default_value_t = SwitchThresholds::DEFAULT_MIN_TABLE_DENSITY)]
+    switch_min_density: usize,
+
+    /// Removes functions unreachable from `main` and the functions given by `--keep`
+    #[clap(long = "dead-function")]
+    dead_function: bool,
+
+    /// Keeps the function from dead function elimination
+    #[clap(long, value_name = "NAME")]
+    keep: Vec<String>,
+
+    /// Compiles a library without `main` to be linked with other units, keeping every defined
+    /// function from dead function elimination and exporting them all
+    #[clap(long, conflicts_with = "irrun")]
+    lib: bool,
+
+    /// Sets the semantics of signed integer overflow (wrap, trap, or ub)
+    #[clap(long, value_name = "POLICY", default_value = "wrap")]
+    overflow: OverflowPolicy,
+
+    /// Instruments the generated code for debugging (none or stack-protect)
+    #[clap(long, value_name = "KIND", default_value = "none")]
+    instrument: Instrumentation,
+
+    /// Checks the input against the revision STD of the C standard (c89, c99, c11, or c17)
+    #[clap(long, value_name = "STD", default_value = "c11")]
+    std: LanguageStd,
+
+    /// Reports the constructs the revision of the C standard given by `--std` does not allow as
+    /// errors instead of warnings
+    #[clap(long = "pedantic-errors")]
+    pedantic_errors: bool,
+
+    /// Sets the signedness of plain `char` (signed, or unsigned as on RISC-V Linux)
+    #[clap(
+        long = "char-signedness",
+        value_name = "SIGNEDNESS",
+        default_value = "signed"
+    )]
+    char_signedness: CharSignedness,
+
+    /// Emits the line tables of the functions for debuggers
+    #[clap(short = 'g')]
+    debug: bool,
+
+    /// Moves the blocks on the way to the functions never returning, e.g., `abort`, into the
+    /// `.text.unlikely` section
+    #[clap(long = "split-cold")]
+    split_cold: bool,
+
+    /// Selects the target of the code generation (riscv64 or riscv32)
+    #[clap(long, value_name = "TARGET", default_value = "riscv64")]
+    target: Target,
+
+    /// Targets RISC-V with the given extension, e.g., `+c` for the compressed instructions
+    #[clap(long, value_name = "EXTENSION")]
+    march: Option<Extension>,
+
+    /// Splits the misaligned loads and stores, for the targets trapping on them
+    #[clap(long = "strict-align")]
+    strict_align: bool,
+
+    /// Counts the executions of each statement in the global array `__kecc_coverage`
+    #[clap(long)]
+    coverage: bool,
+
     /// Prints the output IR
     #[clap(long)]
     iroutput: bool,
 
+    /// Keeps compiling the other functions when a function fails to generate IR
+    #[clap(long = "keep-going")]
+    keep_going: bool,
+
+    /// Reuses the optimized IR of the functions unchanged since the last compilation, cached in
+    /// DIR. The IR printed by `--irgen` is not optimized, and is thus always generated anew
+    #[clap(long = "cache-dir", value_name = "DIR")]
+    cache_dir: Option<String>,
+
+    /// Prints the number of instructions of the optimized IR by opcode to stderr, along with the
+    /// statistics of the incremental cache if any, the memory usage and how many instructions of
+    /// the assembly have compressed forms
+    #[clap(long)]
+    stats: bool,
+
+    /// Prints the given artifact instead of compiling, or writes the artifacts of a single
+    /// compilation, e.g., `--emit=ir,opt-ir,asm`, next to the input or into `--out-dir`
+    #[clap(long, value_name = "KIND", value_delimiter = ',')]
+    emit: Vec<Emit>,
+
+    /// Writes the artifacts selected by `--emit` into DIR instead of next to the input
+    #[clap(long = "out-dir", value_name = "DIR")]
+    out_dir: Option<String>,
+
+    /// Writes `_Static_assert`s of the layout of the interface structs into FILE for other
+    /// compilers to check, and adds the layout to the assembly as `__kecc_layout_*` symbols
+    #[clap(long = "emit-layout-asserts", value_name = "FILE")]
+    emit_layout_asserts: Option<String>,
+
+    /// Checks the offsets of the struct members computed by kecc against those found by the
+    /// interpreter, by gcc and by `qemu-riscv64-static` running a probe program, if available
+    #[clap(long = "verify-layout")]
+    verify_layout: bool,
+
+    /// Prints a report of the functions after optimization instead of compiling
+    #[clap(long, value_name = "KIND")]
+    report: Option<ReportKind>,
+
+    /// Reports the types and expressions nested more than DEPTH levels as too complex
+    #[clap(long = "max-nesting-depth", value_name = "DEPTH", default_value_t = DEFAULT_MAX_NESTING_DEPTH)]
+    max_nesting_depth: usize,
+
+    /// Optimizes up to N functions at a time, by default as many as the CPUs
+    #[cfg(feature = "parallel")]
+    #[clap(short = 'j', long, value_name = "N")]
+    jobs: Option<usize>,
+
+    /// Panics deliberately after parsing, to test crash reports
+    #[clap(long = "debug-panic", hide = true)]
+    debug_panic: bool,
+
     /// Sets the output file to use
     #[clap(short, long, value_name = "FILE")]
     output: Option<String>,
@@ -77,9 +286,159 @@ struct KeccCli {
     input: String,
 }
 
+/// Artifacts printed or written by `--emit`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Emit {
+    /// Memory layout of the structs
+    Layout,
+    /// Portable C generated from the IR after optimization
+    C,
+    /// IR generated from the input, written to `<input>.ir`
+    Ir,
+    /// IR after optimization, written to `<input>.opt.ir`
+    OptIr,
+    /// Assembly, written to `<input>.s`
+    Asm,
+}
+
+impl Emit {
+    /// Returns the kind of the artifact written to a file, if it is.
+    fn artifact_kind(self) -> Option<ArtifactKind> {
+        match self {
+            Self::Layout | Self::C => None,
+            Self::Ir => Some(ArtifactKind::Ir),
+            Self::OptIr => Some(ArtifactKind::OptIr),
+            Self::Asm => Some(ArtifactKind::Asm),
+        }
+    }
+}
+
+/// Targets selected by `--target`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Target {
+    /// 64-bit RISC-V with the F and D extensions
+    Riscv64,
+    /// 32-bit RISC-V with the F extension only
+    Riscv32,
+}
+
+/// Extensions selected by `--march`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Extension {
+    /// The compressed instructions
+    #[value(name = "+c")]
+    C,
+}
+
+/// Formats printed by `--report`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportKind {
+    /// Size and stack usage of each function
+    Functions,
+    /// `functions` in JSON
+    Json,
+}
+
 fn main() {
     let matches = KeccCli::parse();
     let input = Path::new(&matches.input);
+    set_max_nesting_depth(matches.max_nesting_depth);
+    // E.g., pointers and `long` are 4 bytes on RV32, which the whole pipeline follows.
+    set_target_data(compile_options(&matches).target_data);
+    #[cfg(feature = "parallel")]
+    if let Some(jobs) = matches.jobs {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(jobs);
+        ok_or_exit!(pool.build_global(), 1);
+    }
+
+    // Panics on user input are reported in `kecc-crash-<timestamp>.txt` in the current directory.
+    install_crash_handler(
+        PathBuf::from(&matches.input),
+        compile_options(&matches),
+        PathBuf::from("."),
+    );
+    if panic::catch_unwind(AssertUnwindSafe(|| compile(input, &matches))).is_err() {
+        ::std::process::exit(CrashReport::EXIT_CODE);
+    }
+    if matches.stats {
+        eprintln!("{}", MemoryStats::current());
+    }
+}
+
+fn compile_options(matches: &KeccCli) -> CompileOptions {
+    CompileOptions {
+        overflow_policy: matches.overflow,
+        instrumentation: matches.instrument,
+        switch_thresholds: SwitchThresholds {
+            max_linear_cases: matches.switch_max_linear,
+            min_table_density: matches.switch_min_density,
+        },
+        strict_align: matches.strict_align,
+        language_std: matches.std,
+        target_data: TargetData {
+            char_signedness: matches.char_signedness,
+            ..match matches.target {
+                Target::Riscv64 => TargetData::RV64,
+                Target::Riscv32 => TargetData::RV32,
+            }
+        },
+    }
+}
+
+/// Everything but the source the optimized IR of a function depends on, to key the incremental
+/// cache.
+fn cache_context(matches: &KeccCli) -> String {
+    let passes = [
+        matches.optimize,
+        matches.simplify_cfg,
+        matches.forward_pointers,
+        matches.mem2reg,
+        matches.deadcode,
+        matches.gvn,
+        matches.simplify_arith,
+        matches.fold_const_load,
+        matches.sink,
+        matches.min_max,
+        matches.loop_rotate,
+        matches.lower_switch,
+        matches.tail_merge,
+        matches.dead_store,
+        matches.strcmp_dispatch,
+    ];
+    format!(
+        "{} {passes:?} {:?} coverage: {} keep-going: {}",
+        compile_options(matches).to_json(),
+        matches.passes,
+        matches.coverage,
+        matches.keep_going
+    )
+}
+
+fn compile(input: &Path, matches: &KeccCli) {
+    // Only the artifacts written to files are emitted together.
+    let printed = matches
+        .emit
+        .iter()
+        .filter(|emit| emit.artifact_kind().is_none())
+        .count();
+    if printed > 0 && matches.emit.len() > 1 {
+        eprintln!("error: `--emit=layout` and `--emit=c` are not emitted with others");
+        ::std::process::exit(1);
+    }
+
+    // The stamp is of the source as read before it is compiled.
+    let source = if matches
+        .emit
+        .iter()
+        .any(|emit| emit.artifact_kind().is_some())
+    {
+        Some(ok_or_exit!(::std::fs::read(input), 1))
+    } else {
+        None
+    };
+    let mut artifacts = source
+        .as_ref()
+        .map(|source| CompileArtifacts::new(source_stamp(source, &compile_options(matches))));
 
     let output = matches.output.clone().unwrap_or_else(|| "-".to_string());
 
@@ -90,18 +449,60 @@ fn main() {
     };
 
     let ext = input.extension();
-    if ext == Some(OsStr::new("c")) {
-        let input = ok_or_exit!(Parse::default().translate(&input), 1);
-        compile_c(&input, &mut output, &matches);
+    if ext == Some(OsStr::new("c")) || ext == Some(OsStr::new("i")) {
+        set_stage("parse");
+        let (unit, line_map) = ok_or_exit!(Parse::default().translate_with_line_map(&input), 1);
+        check_unchanged(input, source.as_deref());
+        compile_c(&unit, line_map, &mut output, matches, artifacts.as_mut());
     } else if ext == Some(OsStr::new("ir")) {
-        let mut input = ok_or_exit!(IrParse::default().translate(&input), 1);
-        compile_ir(&mut input, &mut output, &matches);
+        set_stage("irparse");
+        let parse = if matches.fix {
+            IrParse::default().fix()
+        } else {
+            IrParse::default()
+        };
+        let (mut unit, fixes) = parse.translate_with_fixes(&input).unwrap_or_else(|error| {
+            for line in error.to_string().lines() {
+                eprintln!("{}:{line}", input.display());
+            }
+            ::std::process::exit(1);
+        });
+        for fix in fixes {
+            eprintln!("{}:{fix} (fixed)", input.display());
+        }
+        check_unchanged(input, source.as_deref());
+        // Those of the C sources are found by the IR generator, see `print_bounds_warnings`.
+        for warning in ir::check_bounds(&unit) {
+            eprintln!("{warning}");
+        }
+        compile_ir(&mut unit, &mut output, matches, None, artifacts.as_mut());
     } else {
         panic!("Unsupported file extension: {ext:?}");
     }
+
+    if let Some(artifacts) = artifacts {
+        let out_dir = matches.out_dir.as_deref().map(Path::new);
+        let _unused = ok_or_exit!(artifacts.write_files(input, out_dir), 1);
+    }
 }
 
-fn compile_c(input: &TranslationUnit, output: &mut dyn ::std::io::Write, matches: &KeccCli) {
+/// Exits if the input is no longer `source`, as read before being parsed, so that the artifacts
+/// are stamped with the source they are compiled from.
+fn check_unchanged(input: &Path, source: Option<&[u8]>) {
+    let source = some_or!(source, return);
+    if ok_or_exit!(::std::fs::read(input), 1) != source {
+        eprintln!("error: {} changed while being compiled", input.display());
+        ::std::process::exit(1);
+    }
+}
+
+fn compile_c(
+    input: &TranslationUnit,
+    line_map: LineMap,
+    output: &mut dyn ::std::io::Write,
+    matches: &KeccCli,
+    artifacts: Option<&mut CompileArtifacts>,
+) {
     if matches.parse {
         return;
     }
@@ -111,7 +512,74 @@ fn compile_c(input: &TranslationUnit, output: &mut dyn ::std::io::Write, matches
         return;
     }
 
-    let mut ir = match Irgen::default().translate(input) {
+    if matches.normalize {
+        write(&Normalized(input), output).unwrap();
+        return;
+    }
+
+    for warning in check_sequence_points(input) {
+        eprintln!("{warning}");
+    }
+    for warning in check_restrict_aliasing(input) {
+        eprintln!("{warning}");
+    }
+
+    let severity = if matches.pedantic_errors {
+        Severity::Error
+    } else {
+        Severity::Warning
+    };
+    let diagnostics = check_language_std(input, matches.std, severity);
+    for diagnostic in &diagnostics {
+        eprintln!("{diagnostic}");
+    }
+    if severity == Severity::Error && !diagnostics.is_empty() {
+        ::std::process::exit(1);
+    }
+
+    set_stage("irgen");
+    let mut irgen = irgen(matches, line_map);
+    // The cache holds the optimized IR only.
+    let mut cache = matches
+        .cache_dir
+        .as_ref()
+        .filter(|_| !matches.irgen)
+        .map(|dir| {
+            let cache = ok_or_exit!(Cache::new(Path::new(dir), cache_context(matches)), 1);
+            // The counters of the statements are numbered across the unit.
+            if matches.coverage {
+                cache.with_unit_keys()
+            } else {
+                cache
+            }
+        });
+    if let Some(cache) = cache.as_mut() {
+        irgen = cache.skip_cached(input, irgen);
+    }
+
+    if matches.keep_going {
+        let (mut ir, errors) = irgen.translate_keep_going(input);
+        print_bounds_warnings(&mut irgen);
+        for irgen_error in &errors {
+            eprintln!("{irgen_error}");
+        }
+
+        if matches.irgen {
+            write(&ir, output).unwrap();
+        } else {
+            compile_ir(&mut ir, output, matches, cache.as_mut(), artifacts);
+            print_cache_stats(cache.as_ref(), matches);
+        }
+
+        if !errors.is_empty() {
+            ::std::process::exit(1);
+        }
+        return;
+    }
+
+    let result = irgen.translate(input);
+    print_bounds_warnings(&mut irgen);
+    let mut ir = match result {
         Ok(ir) => ir,
         Err(irgen_error) => {
             println!("{irgen_error}");
@@ -124,14 +592,107 @@ fn compile_c(input: &TranslationUnit, output: &mut dyn ::std::io::Write, matches
         return;
     }
 
-    compile_ir(&mut ir, output, matches)
+    compile_ir(&mut ir, output, matches, cache.as_mut(), artifacts);
+    print_cache_stats(cache.as_ref(), matches);
+}
+
+/// Prints the statistics of the incremental cache, if any, with `--stats`.
+fn print_cache_stats(cache: Option<&Cache>, matches: &KeccCli) {
+    if let Some(cache) = cache.filter(|_| matches.stats) {
+        eprintln!("{}", cache.stats());
+    }
+}
+
+/// Prints the accesses and the initializers out of the bounds of arrays `irgen` found.
+fn print_bounds_warnings(irgen: &mut Irgen) {
+    for warning in irgen.take_bounds_warnings() {
+        eprintln!("{warning}");
+    }
+}
+
+fn irgen(matches: &KeccCli, line_map: LineMap) -> Irgen {
+    let irgen = Irgen::default().with_line_map(line_map);
+    if matches.coverage {
+        irgen.with_hooks(Box::<StatementCoverage>::default())
+    } else {
+        irgen
+    }
+}
+
+/// Runs the optimization `name` by `pass`, printing the changes it makes to the IR if requested
+/// by `--print-ir-diff-after`.
+fn run_pass<F>(name: &str, input: &mut ir::TranslationUnit, matches: &KeccCli, pass: F)
+where
+    F: FnOnce(&mut ir::TranslationUnit) -> bool,
+{
+    // The IR is valid after each pass, so the remaining ones are skipped if out of time.
+    if optimizer_out_of_time() {
+        return;
+    }
+
+    set_stage(&format!("opt:{name}"));
+    if matches.print_ir_diff_after.as_deref() != Some(name) {
+        let _ = pass(input);
+        return;
+    }
+
+    let before = input.clone();
+    let _ = pass(input);
+    eprint!("{}", ir::diff(&before, input));
+}
+
+/// Runs the function pass `pass` named `name` on each function of `input`, in parallel with the
+/// `parallel` feature (see [`run_pass`]).
+fn run_function_pass<T>(name: &str, input: &mut ir::TranslationUnit, matches: &KeccCli, pass: T)
+where
+    T: Optimize<ir::FunctionDefinition> + Clone + Sync,
+{
+    run_pass(name, input, matches, |input| {
+        ParallelFunctionPass::new(pass).optimize(input)
+    });
 }
 
 fn compile_ir(
     input: &mut ir::TranslationUnit,
     output: &mut dyn ::std::io::Write,
     matches: &KeccCli,
+    cache: Option<&mut Cache>,
+    mut artifacts: Option<&mut CompileArtifacts>,
 ) {
+    let options = compile_options(matches);
+
+    if matches.debug_panic {
+        panic!("deliberate panic requested by `--debug-panic`");
+    }
+
+    // Types the target cannot handle are reported before spending time on the optimizations.
+    set_stage("target-check");
+    let unsupported = match matches.target {
+        Target::Riscv32 => target::check_target(input, &target::Riscv32),
+        Target::Riscv64 => target::check_target(input, &target::Riscv64),
+    };
+    if !unsupported.is_empty() {
+        for error in unsupported {
+            eprintln!("{error}");
+        }
+        ::std::process::exit(1);
+    }
+
+    if let Some(path) = &matches.emit_layout_asserts {
+        let static_asserts = ir::Layout::new(input).static_asserts();
+        ok_or_exit!(::std::fs::write(path, static_asserts), 1);
+    }
+
+    if matches.verify_layout {
+        set_stage("verify-layout");
+        verify_layout(input);
+    }
+
+    if matches.emit.contains(&Emit::Layout) {
+        write(&ir::Layout::new(input), output).unwrap();
+        return;
+    }
+
     if matches.irparse {
         return;
     }
@@ -141,26 +702,169 @@ fn compile_ir(
         return;
     }
 
+    if let Some(artifacts) = artifacts.as_deref_mut() {
+        if matches.emit.contains(&Emit::Ir) {
+            ok_or_exit!(artifacts.insert(ArtifactKind::Ir, input), 1);
+        }
+    }
+
+    set_optimizer_budget(OptimizerBudget {
+        time_limit: matches.time_limit.map(Duration::from_secs_f64),
+        max_iterations: Some(matches.max_iterations),
+        max_growth_percent: Some(matches.max_ir_growth),
+    });
     if matches.optimize {
-        O1::default().optimize(input);
+        run_function_pass("O1", input, matches, O1::default());
     } else {
         if matches.simplify_cfg {
-            SimplifyCfg::default().optimize(input);
+            run_function_pass("simplify-cfg", input, matches, SimplifyCfg::default());
+        }
+
+        if matches.forward_pointers {
+            run_function_pass(
+                "forward-pointers",
+                input,
+                matches,
+                ForwardPointers::default(),
+            );
         }
 
         if matches.mem2reg {
-            Mem2reg::default().optimize(input);
+            run_function_pass("mem2reg", input, matches, Mem2reg::default());
         }
 
         if matches.deadcode {
-            Deadcode::default().optimize(input);
+            run_function_pass("deadcode", input, matches, Deadcode::default());
         }
 
         if matches.gvn {
-            Gvn::default().optimize(input);
+            run_function_pass("gvn", input, matches, Gvn::default());
+        }
+
+        if matches.simplify_arith {
+            run_function_pass(
+                "simplify-arith",
+                input,
+                matches,
+                SimplifyArith::new(SimplifyArithInner::new(options)),
+            );
+        }
+
+        if matches.fold_const_load {
+            run_pass("fold-const-load", input, matches, |input| {
+                FoldConstLoad::default().optimize(input)
+            });
+        }
+
+        if matches.sink {
+            run_function_pass("sink", input, matches, Sink::default());
+        }
+
+        if matches.min_max {
+            run_function_pass("min-max", input, matches, MinMax::default());
+        }
+
+        if matches.loop_rotate {
+            run_function_pass("loop-rotate", input, matches, LoopRotate::default());
+        }
+
+        if matches.lower_switch {
+            run_function_pass(
+                "lower-switch",
+                input,
+                matches,
+                LowerSwitch::new(LowerSwitchInner::new(options)),
+            );
+        }
+
+        if matches.tail_merge {
+            run_function_pass("tail-merge", input, matches, TailMerge::default());
+        }
+
+        if matches.dead_store {
+            run_function_pass("dead-store", input, matches, DeadStore::default());
+        }
+
+        if matches.strcmp_dispatch {
+            run_pass("strcmp-dispatch", input, matches, |input| {
+                StrcmpDispatch::default().optimize(input)
+            });
+        }
+    }
+
+    if let Some(pipeline) = &matches.passes {
+        let mut registry = PassRegistry::new(options);
+        let names = pipeline
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>();
+        for name in &names {
+            if let Err(error) = registry.get_mut(name) {
+                eprintln!("error: {error}");
+                ::std::process::exit(1);
+            }
+        }
+        for name in names {
+            run_pass(name, input, matches, |input| {
+                registry
+                    .get_mut(name)
+                    .expect("`name` must be registered")
+                    .optimize(input)
+            });
         }
     }
 
+    for diagnostic in take_budget_diagnostics() {
+        eprintln!("{diagnostic}");
+    }
+
+    // Interprocedural passes need the functions from the cache.
+    if let Some(cache) = cache {
+        set_stage("cache");
+        ok_or_exit!(cache.complete(input), 1);
+    }
+
+    if matches.dead_function {
+        run_pass("dead-function", input, matches, |input| {
+            let keep = matches.keep.clone();
+            if matches.lib {
+                DeadFunction::library(keep).optimize(input)
+            } else {
+                DeadFunction::new(keep).optimize(input)
+            }
+        });
+    }
+
+    if options.strict_align {
+        set_stage("lower:strict-align");
+        ParallelFunctionPass::new(SplitMisaligned::default()).optimize(input);
+    }
+
+    if matches.stats {
+        for (opcode, count) in input.opcode_histogram() {
+            eprintln!("{count:>8} {opcode}");
+        }
+    }
+
+    if let Some(artifacts) = artifacts.as_deref_mut() {
+        if matches.emit.contains(&Emit::OptIr) {
+            ok_or_exit!(artifacts.insert(ArtifactKind::OptIr, input), 1);
+        }
+        if !matches.emit.contains(&Emit::Asm) {
+            return;
+        }
+    }
+
+    if let Some(kind) = matches.report {
+        let report = Report::new(input);
+        match kind {
+            ReportKind::Functions => write!(output, "{report}").unwrap(),
+            ReportKind::Json => write!(output, "{}", report.to_json()).unwrap(),
+        }
+        return;
+    }
+
     if let Some(path) = &matches.irviz {
         assert_eq!(
             Path::new(&path).extension(),
@@ -207,7 +911,23 @@ fn compile_ir(
     }
 
     if matches.irrun {
-        let result = ir::interp(input, Vec::new()).unwrap();
+        set_stage("irrun");
+        // Unlike the assembly, the program is not linked with the units defining these symbols,
+        // except for the functions reading the standard input, which the interpreter evaluates.
+        let undefined = input
+            .undefined_symbols()
+            .into_iter()
+            .filter(|name| ir::StdinFunction::from_name(name).is_none())
+            .collect::<Vec<_>>();
+        if !undefined.is_empty() && !matches.allow_undefined {
+            for name in undefined {
+                eprintln!("error: undefined reference to `{name}`");
+            }
+            ::std::process::exit(1);
+        }
+
+        let bindings = ir::Bindings::default().stdin(::std::io::stdin());
+        let result = ir::interp_with_bindings(input, Vec::new(), options, bindings).unwrap();
         let (value, width, is_signed) = result.get_int().expect("non-integer value occurs");
         assert_eq!(width, 32);
         assert!(is_signed);
@@ -218,6 +938,120 @@ fn compile_ir(
         return;
     }
 
-    let asm = ok_or_exit!(Asmgen::default().translate(input), 1);
-    write(&asm, output).unwrap();
+    if matches.emit.contains(&Emit::C) {
+        set_stage("cgen");
+        let c = Cgen::default().translate(input).unwrap_or_else(|error| {
+            eprintln!("{error}");
+            ::std::process::exit(1);
+        });
+        output.write_all(c.as_bytes()).unwrap();
+        return;
+    }
+
+    set_stage("asmgen");
+    let mut asm = ok_or_exit!(Asmgen::default().translate(input), 1);
+    if matches.lib {
+        asm.unit.export_functions();
+    }
+    if matches.march == Some(Extension::C) {
+        asm.unit.enable_compression();
+    }
+    if matches.emit_layout_asserts.is_some() {
+        let layouts = ir::Layout::new(input).interface_structs();
+        asm.unit
+            .variables
+            .extend(layouts.iter().map(asm::Section::layout_assert));
+    }
+    if options.instrumentation == Instrumentation::StackProtect {
+        asm.unit.functions.push(asm::Section::stack_fail());
+        asm.unit.variables.push(asm::Section::stack_fail_message());
+    }
+    if matches.split_cold {
+        asm::SplitCold::default().optimize(&mut asm.unit);
+    }
+    // The locations of the functions are only known from a C source.
+    let ext = Path::new(&matches.input).extension();
+    if matches.debug && (ext == Some(OsStr::new("c")) || ext == Some(OsStr::new("i"))) {
+        let locations = ok_or_exit!(Parse::function_locations(&matches.input), 1);
+        asm::DebugLines::new(locations).optimize(&mut asm.unit);
+    }
+    if matches.stats {
+        eprintln!("{}", asm.unit.compression_estimate());
+    }
+    match artifacts {
+        Some(artifacts) => ok_or_exit!(artifacts.insert(ArtifactKind::Asm, &asm), 1),
+        None => write(&asm, output).unwrap(),
+    }
+}
+
+/// Exits if the offsets of the struct members in `input` differ between kecc, the interpreter and
+/// the probe program compiled by gcc and by the RISC-V cross compiler, which are skipped if absent.
+fn verify_layout(input: &ir::TranslationUnit) {
+    let layout = ir::Layout::new(input);
+    let recorded = layout.member_offsets();
+    let interpreted = layout.interpreted_offsets().unwrap_or_else(|error| {
+        eprintln!("error: the interpreter fails to place the structs: {error:?}");
+        ::std::process::exit(1);
+    });
+    let probe = layout.offset_probe();
+    let native = run_offset_probe(&probe, "gcc", &[], None);
+    let qemu = run_offset_probe(
+        &probe,
+        "riscv64-linux-gnu-gcc",
+        &["-static"],
+        Some("qemu-riscv64-static"),
+    );
+
+    let mut sources = vec![("interpreter", interpreted.as_slice())];
+    for (source, offsets) in [("gcc", &native), ("qemu", &qemu)] {
+        match offsets {
+            Some(offsets) => sources.push((source, offsets.as_slice())),
+            None => {
+                eprintln!("warning: the layout is not checked by {source}, which is unavailable")
+            }
+        }
+    }
+    let mismatches = ir::LayoutMismatch::between(&recorded, &sources);
+    if !mismatches.is_empty() {
+        for mismatch in mismatches {
+            eprintln!("{mismatch}");
+        }
+        ::std::process::exit(1);
+    }
+}
+
+/// Compiles the C program `probe` of [`ir::Layout::offset_probe`] with `compiler` and `flags`, and
+/// returns the offsets it prints when run, by `runner` if any, or `None` if any of them fails.
+fn run_offset_probe(
+    probe: &str,
+    compiler: &str,
+    flags: &[&str],
+    runner: Option<&str>,
+) -> Option<Vec<ir::MemberOffset>> {
+    let temp_dir = tempdir().expect("temp dir creation failed");
+    let source_path = temp_dir.path().join("probe.c");
+    let bin_path = temp_dir.path().join("probe");
+    ::std::fs::write(&source_path, probe).expect("failed to write the probe");
+
+    let compiled = Command::new(compiler)
+        .args(flags)
+        .arg(&source_path)
+        .arg("-o")
+        .arg(&bin_path)
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    if !compiled {
+        return None;
+    }
+
+    let output = match runner {
+        Some(runner) => Command::new(runner).arg(&bin_path).output(),
+        None => Command::new(&bin_path).output(),
+    }
+    .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    ir::MemberOffset::parse_probe_output(&String::from_utf8_lossy(&output.stdout))
 }
@@ -0,0 +1,4 @@
+_Alignas(3) char odd[4];
+_Alignas(-8) char negative[4];
+_Alignas(2) int weaker[4];
+_Alignas(8) int scalar;
@@ -0,0 +1,13 @@
+typedef _Alignas(16) char block_t[16];
+
+struct packet {
+    char tag;
+    block_t payload;
+};
+
+_Alignas(16) char buf[64];
+_Alignas(double) char scratch[3];
+_Alignas(0) short plain[3];
+_Alignas(4) _Alignas(32) int table[4];
+block_t blocks[2];
+struct packet packet;
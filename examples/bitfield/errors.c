@@ -0,0 +1,15 @@
+struct Wide {
+    unsigned x : 33;
+};
+
+struct Negative {
+    int y : -1;
+};
+
+struct Floating {
+    double z : 2;
+};
+
+struct Empty {
+    int w : 0;
+};
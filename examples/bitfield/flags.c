@@ -0,0 +1,15 @@
+struct Flags {
+    unsigned ready : 1;
+    unsigned mode : 3;
+};
+
+struct Mixed {
+    char c;
+    unsigned a : 4;
+    unsigned : 0;
+    unsigned b : 30;
+    unsigned d : 4;
+};
+
+struct Flags flags;
+struct Mixed mixed;
@@ -0,0 +1,31 @@
+typedef int quad[4];
+struct pair { int arr[2]; int x; };
+enum { N = 4 };
+
+quad table = {1, 2, 3, 4, 5};
+
+int sum(int arr[4], int *p) {
+    return arr[7] + p[9];
+}
+
+int main() {
+    quad buf;
+    struct pair pr;
+    char name[4] = "hello!";
+    char fits[4] = "abcd";
+    int *decayed = buf;
+    int n = 9;
+
+    buf[N - 1] = 1;
+    buf[N] = 2;
+    pr.arr[2] = buf[-1];
+    decayed[5] = buf[n];
+    __builtin_memcpy(buf, decayed, 20);
+    __builtin_memset(pr.arr, 0, 8);
+    {
+        int N = 8;
+        buf[N] = 3;
+    }
+    static char tag[2] = "abc";
+    return table[4] + fits[0] + name[0] + tag[0];
+}
@@ -0,0 +1 @@
+int __builtin_bogus(int x);
@@ -0,0 +1,19 @@
+struct Flags {
+    unsigned ready : 1;
+    unsigned mode : 3;
+    int x : 3;
+};
+
+int main() {
+    struct Flags flags;
+    flags.ready = 1;
+    flags.mode = 6;
+    flags.x = 7;
+
+    // Stores to adjacent fields keep each other.
+    flags.mode = flags.mode - 1;
+    flags.ready = flags.ready + 1;
+
+    struct Flags copy = flags;
+    return sizeof(struct Flags) == 4 && copy.ready == 0 && copy.mode == 5 && copy.x == -1;
+}
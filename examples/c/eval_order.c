@@ -0,0 +1,30 @@
+// kecc: order-dependent, returns 1
+
+int trace = 0;
+
+int record(int digit) {
+    trace = trace * 10 + digit;
+    return digit;
+}
+
+int sum(int a, int b, int c) {
+    return a + b + c;
+}
+
+int main() {
+    int ok = 1;
+
+    trace = 0;
+    sum(record(1), record(2), record(3));
+    ok = ok && trace == 123;
+
+    trace = 0;
+    record(4) - record(5);
+    ok = ok && trace == 45;
+
+    trace = 0;
+    int a[3] = {record(6), record(7), record(8)};
+    ok = ok && trace == 678;
+
+    return ok;
+}
@@ -0,0 +1,13 @@
+int g = 3;
+
+int add(int a, int b) {
+    return a + b;
+}
+
+int square(int x) {
+    return x * x;
+}
+
+int main() {
+    return add(square(g), 4);
+}
@@ -0,0 +1,7 @@
+char stored = 200;
+int greater = (char)200 > 100;
+int all_ones = (char)-1 == 255;
+signed char explicit_signed = 200;
+unsigned char explicit_unsigned = 200;
+int negative = (char)0x80 < 0;
+int explicit_negative = (signed char)0x80 < 0;
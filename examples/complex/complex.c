@@ -0,0 +1,4 @@
+double real;
+_Complex double z;
+float _Complex w;
+int count;
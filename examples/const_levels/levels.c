@@ -0,0 +1,50 @@
+typedef int *IP;
+typedef const int *CIP;
+typedef int *const IPC;
+typedef const int *const CIPC;
+typedef int A[3];
+typedef const int CA[3];
+typedef IP AP[2];
+typedef A AA[2];
+typedef CIP T;
+typedef const IP CIP2;
+
+const IP d1;
+const CIP d2;
+const IPC d3;
+IPC *d4;
+const IPC *const d5;
+const T *const d6[3];
+const A d7;
+const CA d8;
+const AA d9;
+const AP d10;
+const A *d11;
+A *const d12;
+const CIPC d13[2];
+CIP d14[2][3];
+const IP *d15[4];
+const T (*d16)[2];
+const A *const *d17;
+int (*const d18)(const IP p);
+const CA *d19[2];
+const int *const *const d20;
+CIP2 *d21;
+
+struct Box {
+    A values;
+};
+
+const struct Box box;
+
+void writable(void) {
+    *d1 = 1;
+    d4 = 0;
+    **d4 = 1;
+    d11 = 0;
+    (*d12)[0] = 1;
+    d14[1][2] = 0;
+    d16 = 0;
+    d17 = 0;
+    d21 = 0;
+}
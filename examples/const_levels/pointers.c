@@ -0,0 +1,6 @@
+const int *p1;
+int *const p2;
+const int *const p3;
+int **const p4;
+int *const *p5;
+int const *const *q;
@@ -0,0 +1,58 @@
+typedef int *IP;
+typedef const int *CIP;
+typedef int A[3];
+typedef A AA[2];
+typedef IP AP[2];
+
+const IP p;
+const A a;
+const AA aa;
+const AP ap;
+const CIP *const r[3];
+const A *pa;
+
+struct Box {
+    A values;
+};
+
+const struct Box box;
+
+void write_const_pointer(void) {
+    p = 0;
+}
+
+void write_array_element(void) {
+    a[1] = 1;
+}
+
+void write_nested_array_element(void) {
+    aa[1][2] = 1;
+}
+
+void write_pointer_array_element(void) {
+    ap[0] = 0;
+}
+
+void write_array(void) {
+    r = 0;
+}
+
+void write_element(void) {
+    r[0] = 0;
+}
+
+void write_first_pointee(void) {
+    *r[0] = 0;
+}
+
+void write_second_pointee(void) {
+    **r[0] = 1;
+}
+
+void write_pointed_array_element(void) {
+    (*pa)[0] = 1;
+}
+
+void write_member_element(void) {
+    box.values[0] = 1;
+}
@@ -0,0 +1,11 @@
+const int size = 4;
+
+int shift = 1 << 4;
+int negated = -(3 * 5);
+int scaled = sizeof(int) * size;
+long big = 1L << 40;
+double half = 1 / 2.0;
+char narrowed = (char)300;
+int logical = size && 0;
+int chosen = size > 2 ? 10 : 1 / 0;
+int table[3] = {size, size * 2, ~size};
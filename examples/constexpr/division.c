@@ -0,0 +1,2 @@
+int quotients[5] = {-7 / 2, 7 / -2, -7 / -2, (-2147483647 - 1) / 2, (char)-128 / 3};
+int remainders[5] = {-7 % 2, 7 % -2, -7 % -2, (-2147483647 - 1) % 2, (char)-128 % 3};
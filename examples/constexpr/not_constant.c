@@ -0,0 +1,5 @@
+int f(void);
+int counter;
+
+int called = f();
+int loaded = counter + 1;
@@ -0,0 +1,5 @@
+int negated = -(unsigned short)1;
+int complemented = ~(unsigned char)0;
+int shifted = (char)-1 >> 1;
+int widened = (unsigned char)255 << 8;
+int compared = (unsigned char)200 < -1;
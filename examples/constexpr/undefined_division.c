@@ -0,0 +1,4 @@
+int quotient_by_zero = 7 / 0;
+int remainder_by_zero = 7 % 0;
+int quotient_overflow = (-2147483647 - 1) / -1;
+int remainder_overflow = (-2147483647 - 1) % -1;
@@ -0,0 +1,11 @@
+int main() {
+    int sum = 0;
+    for (int i = 0; i < 10; i++) {
+        if (i % 2 == 0) {
+            sum += i;
+        } else {
+            continue;
+        }
+    }
+    return sum;
+}
@@ -0,0 +1,41 @@
+//! Runs the passes of kecc and [`StripNames`], registered by this driver, on an IR file, e.g.,
+//!
+//! ```sh
+//! cargo run --example custom_pass -- examples/tail_merge/if_else.input.ir tail-merge,strip-names
+//! ```
+
+mod pass;
+
+use std::env;
+use std::io;
+use std::process;
+
+use kecc::{write, CompileOptions, IrParse, PassRegistry, Translate};
+
+use pass::StripNames;
+
+fn main() {
+    let args = env::args().collect::<Vec<_>>();
+    if args.len() != 3 {
+        eprintln!("usage: {} <IR file> <passes>", args[0]);
+        process::exit(1);
+    }
+
+    let mut unit = IrParse::default()
+        .translate(&args[1])
+        .unwrap_or_else(|error| {
+            eprintln!("error: failed to parse {}: {error:?}", args[1]);
+            process::exit(1);
+        });
+
+    let mut registry = PassRegistry::new(CompileOptions::default());
+    registry
+        .register("strip-names", Box::<StripNames>::default())
+        .expect("`strip-names` must not be built in");
+    if let Err(error) = registry.run(&args[2], &mut unit) {
+        eprintln!("error: {error}");
+        process::exit(1);
+    }
+
+    write(&unit, &mut io::stdout()).unwrap();
+}
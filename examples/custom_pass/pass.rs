@@ -0,0 +1,41 @@
+//! A pass defined outside of kecc, only through its public API.
+
+use core::ops::Deref;
+
+use kecc::ir::{FunctionDefinition, Named};
+use kecc::{FunctionPass, Optimize};
+
+pub type StripNames = FunctionPass<StripNamesInner>;
+
+/// Removes the names of the allocations, the phinodes and the instructions, e.g., `x` of
+/// `%l0:i32:x`, which only help reading the IR.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct StripNamesInner {}
+
+impl Optimize<FunctionDefinition> for StripNamesInner {
+    fn optimize(&mut self, code: &mut FunctionDefinition) -> bool {
+        let mut changed = false;
+        for allocation in &mut code.allocations {
+            changed |= strip(allocation);
+        }
+        for block in code.blocks.values_mut() {
+            for phinode in &mut block.phinodes {
+                changed |= strip(phinode);
+            }
+            for instruction in &mut block.instructions {
+                changed |= strip(instruction);
+            }
+        }
+        changed
+    }
+}
+
+/// Removes the name of `named`, and returns whether it had one.
+fn strip<T: Clone>(named: &mut Named<T>) -> bool {
+    if named.name().is_none() {
+        return false;
+    }
+
+    *named = Named::new(None, named.deref().clone());
+    true
+}
@@ -0,0 +1,12 @@
+int square(int x) {
+    return x * x;
+}
+
+#line 100 "generated.c"
+int cube(int x) {
+    return x * x * x;
+}
+
+int main() {
+    return square(2) + cube(2) - 12;
+}
@@ -0,0 +1,29 @@
+    .file	1 "examples/debug_lines/lines.c"
+    .globl	square
+    .section	.text
+    .type	square, @function
+square:
+    .cfi_startproc
+    .loc	1 1 1
+    li	a0,4
+    ret
+    .cfi_endproc
+    .file	2 "generated.c"
+    .globl	cube
+    .section	.text
+    .type	cube, @function
+cube:
+    .cfi_startproc
+    .loc	2 100 1
+    li	a0,8
+    ret
+    .cfi_endproc
+    .globl	main
+    .section	.text
+    .type	main, @function
+main:
+    .cfi_startproc
+    .loc	2 104 1
+    li	a0,0
+    ret
+    .cfi_endproc
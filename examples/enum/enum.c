@@ -0,0 +1,7 @@
+enum Color { RED, GREEN = 5, BLUE };
+enum { MINUS = -2, MINUS_ONE };
+
+enum Color red = RED;
+enum Color blue = BLUE;
+const enum Color green = GREEN;
+int sum = GREEN + BLUE + MINUS + MINUS_ONE;
@@ -0,0 +1,2 @@
+extern int shared_counter;
+extern long shared_counter;
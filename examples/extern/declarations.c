@@ -0,0 +1,11 @@
+extern int helper(int);
+extern int shared_counter;
+extern int shared_counter;
+
+int defined = 1;
+extern int defined;
+
+extern struct Handle *open_handle(int id);
+extern struct Handle current;
+
+extern int errno_shadow;
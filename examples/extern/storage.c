@@ -0,0 +1,3 @@
+extern typedef int handle_t;
+typedef extern int *handle_ptr;
+extern extern int twice;
@@ -0,0 +1,17 @@
+struct point {
+    int x;
+};
+
+const struct point *(*get)(void);
+
+void through_global(void) {
+    get()->x = 1;
+}
+
+void through_dereference(const struct point *(*getter)(void)) {
+    (*getter)()->x = 2;
+}
+
+void through_parameter(const struct point *find(int)) {
+    find(0)->x = 3;
+}
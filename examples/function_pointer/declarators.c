@@ -0,0 +1,10 @@
+int (*cmp)(int, int);
+int (*table[4])(int);
+int (*(*getter)(void))(int);
+
+typedef int unary(int);
+
+int sort(int *v, int n, int (*less)(int, int));
+int apply(int f(int), int x);
+int apply_typedef(unary f, int x);
+int apply_abstract(int (int), int x);
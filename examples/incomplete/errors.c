@@ -0,0 +1,16 @@
+struct Handle;
+
+long handle_size = sizeof(struct Handle);
+
+struct Wrapper {
+    struct Handle inner;
+};
+
+struct Handle current;
+
+int use_handle(struct Handle handle) {
+    return 0;
+}
+
+struct Handle make_handle(void) {
+}
@@ -0,0 +1,15 @@
+struct Handle;
+typedef struct Handle Handle;
+
+struct Handle *open_handle(int id);
+void close_handle(struct Handle *handle);
+int read_handle(Handle *handle, void *buffer, long size);
+
+struct Node {
+    struct Node *next;
+    Handle *handle;
+};
+
+Handle *handles[4];
+void *raw;
+long pointer_size = sizeof(struct Handle *);
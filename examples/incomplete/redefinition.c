@@ -0,0 +1,11 @@
+struct Node {
+    int value;
+    struct Node *next;
+};
+
+struct Node {
+    long value;
+};
+
+struct Node *head;
+long node_size = sizeof(struct Node);
@@ -0,0 +1,28 @@
+struct pair {
+    const int id;
+    int value;
+};
+
+void shadowed(void) {
+    struct pair outer;
+    outer.value = 1;
+    {
+        struct pair {
+            int id;
+            const int value;
+        } inner;
+        inner.id = 2;
+        inner.value = 3;
+    }
+}
+
+void restored(void) {
+    {
+        struct pair {
+            int id;
+        } inner;
+        inner.id = 4;
+    }
+    struct pair outer;
+    outer.id = 5;
+}
@@ -0,0 +1,9 @@
+int a[] = {1, 2, 3};
+int m[][2] = {{1, 2}, {3, 4}, {5, 6}};
+
+extern char table[];
+
+extern int late[];
+int late[4];
+
+int f(int v[]);
@@ -0,0 +1,2 @@
+int bad[3][];
+int missing[];
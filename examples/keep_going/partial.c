@@ -0,0 +1,11 @@
+int g = 3;
+
+_Static_assert(sizeof(int) == 4, "int is 4 bytes");
+_Static_assert(sizeof(long) == 4, "long is 4 bytes");
+
+struct Missing;
+struct Missing m;
+
+int h = g + 1;
+
+int last = 7;
@@ -0,0 +1,12 @@
+int before = 1;
+
+struct Missing;
+struct Missing bad(void) {
+    return before;
+}
+
+int get(void) {
+    return before;
+}
+
+int after = 2;
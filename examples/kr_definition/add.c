@@ -0,0 +1,21 @@
+int add(int a, int b);
+long scale(int x, long factor);
+
+int add(a, b)
+int a;
+int b;
+{
+    return a + b;
+}
+
+// `x` is not declared, so it is an `int`.
+long scale(x, factor)
+long factor;
+{
+    return x * factor;
+}
+
+int main()
+{
+    return add(1, 2) + scale(3, 4) == 15;
+}
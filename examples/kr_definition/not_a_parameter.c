@@ -0,0 +1,11 @@
+int add(a, b)
+int a;
+int c;
+{
+    return a;
+}
+
+int main()
+{
+    return add(1, 2) == 1;
+}
@@ -0,0 +1,30 @@
+int sum(int n) {
+    int total = 0;
+    int i;
+
+    for (i = 0; i < n; i++) {
+        int square = i * i;
+        total += square;
+    }
+
+    return total;
+}
+
+int main() {
+    int x = 1;
+    x = sum(4);
+    int y = x + 1;
+
+    for (int i = 0; i < 2; i++) {
+        y += i;
+        int z = y;
+        x += z;
+    }
+
+    {
+        int w = x;
+        y += w;
+    }
+
+    return x + y;
+}
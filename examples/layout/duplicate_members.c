@@ -0,0 +1,10 @@
+struct pair {
+    union {
+        struct {
+            int x;
+            int y;
+        };
+        long xy;
+    };
+    int x;
+};
@@ -0,0 +1,27 @@
+struct inner {
+    char c;
+    int i;
+};
+
+struct outer {
+    char tag;
+    struct inner inner;
+    long l;
+    short s;
+};
+
+struct tail {
+    double d;
+    char c[3];
+};
+
+struct padded {
+    char c;
+    int i;
+    char d;
+};
+
+struct chars {
+    char a;
+    char b;
+};
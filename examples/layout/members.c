@@ -0,0 +1,28 @@
+struct point {
+    int tag;
+    struct {
+        char c;
+        long l;
+    };
+    union {
+        short s;
+        double d;
+    };
+};
+
+int main() {
+    struct point p;
+    p.l = 1;
+    return p.l;
+}
+
+struct word {
+    char c;
+    union {
+        struct {
+            short lo;
+            short hi;
+        };
+        int value;
+    };
+};
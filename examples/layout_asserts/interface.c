@@ -0,0 +1,16 @@
+struct inner {
+    char c;
+    int i;
+};
+
+struct outer {
+    char tag;
+    struct inner inner;
+    long l;
+};
+
+struct hidden {
+    double d;
+};
+
+struct outer *current;
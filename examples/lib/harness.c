@@ -0,0 +1,6 @@
+int fib(int n);
+int square(int x);
+
+int main() {
+    return fib(10) + square(3);
+}
@@ -0,0 +1,8 @@
+# 1 "main.c"
+# 1 "config.h" 1
+int limit = 4;
+
+int bad = limit;
+# 2 "main.c" 2
+
+int table[3];
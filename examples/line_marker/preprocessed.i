@@ -0,0 +1,13 @@
+# 1 "main.c"
+# 1 "<built-in>"
+# 1 "<command-line>"
+# 1 "main.c"
+# 1 "util.h" 1 3 4
+int add(int a, int b);
+
+int broken(int a) { return a + ; }
+# 2 "main.c" 2
+
+int main() {
+  return 0;
+}
@@ -0,0 +1,30 @@
+void local_const(void) {
+    typedef const int cint;
+    cint a = 1;
+    a = 2;
+}
+
+void local_struct(void) {
+    typedef struct { const int id; int value; } entry;
+    entry e;
+    e.value = 0;
+    e.id = 1;
+}
+
+void local_cast(void) {
+    typedef const int cint;
+    int x = 0;
+    *(cint *)&x = 1;
+}
+
+typedef const int level;
+
+void restored(void) {
+    {
+        typedef int level;
+        level inner = 0;
+        inner = 1;
+    }
+    level outer = 0;
+    outer = 1;
+}
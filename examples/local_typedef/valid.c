@@ -0,0 +1,23 @@
+typedef int count;
+
+void valid(void) {
+    typedef struct { int x, y; } pt;
+    pt a;
+    a.x = (int)sizeof(pt);
+    ((pt *)&a)->y = 2;
+    {
+        typedef const int count;
+        int limit = sizeof(count);
+        limit = (count)1;
+    }
+    count c = 0;
+    c = 3;
+    {
+        typedef struct { const int x; int y; } pt;
+        pt b = {1, 2};
+        b.y = (int)sizeof(pt);
+    }
+    a = a;
+}
+
+struct { int z; } after;
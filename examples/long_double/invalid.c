@@ -0,0 +1,4 @@
+long float a;
+long long double b;
+short double c;
+unsigned long double d;
@@ -0,0 +1,10 @@
+long double scale;
+const long double pi = 3.14;
+double long ratio;
+
+struct sample {
+    char tag;
+    long double value;
+};
+
+struct sample samples[2];
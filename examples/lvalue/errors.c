@@ -0,0 +1,48 @@
+struct Pair {
+    const int key;
+    int value;
+};
+
+int x;
+int a, b;
+int values[4];
+const int limit = 10;
+
+int next_value(void);
+
+void assign_constant(void) {
+    5 = x;
+}
+
+void assign_sum(void) {
+    (a + b) = 3;
+}
+
+void assign_string_literal(void) {
+    "str"[0] = 'c';
+}
+
+void assign_through_const_pointer(const int *p) {
+    *p = 1;
+}
+
+void assign_array(void) {
+    int copy[4];
+    copy = values;
+}
+
+void assign_struct_with_const_member(struct Pair *p, struct Pair q) {
+    *p = q;
+}
+
+void increment_const(void) {
+    limit++;
+}
+
+void compound_assign_call(void) {
+    next_value() += 1;
+}
+
+int *address_of_sum(void) {
+    return &(a + 1);
+}
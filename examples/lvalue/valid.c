@@ -0,0 +1,35 @@
+struct Point {
+    int x;
+    int y;
+};
+
+int counter;
+int values[4];
+const int limit = 10;
+
+int valid(int *p, const int *q, struct Point point) {
+    int local = 0;
+    int *const fixed = p;
+    struct Point *ptr = &point;
+
+    counter = 1;
+    counter += local;
+    counter++;
+    --local;
+    *p = *q;
+    *fixed = 2;
+    p = &values[1];
+    values[0] = 3;
+    p[1] = 4;
+    point.x = 5;
+    ptr->y = 6;
+    q = p;
+
+    {
+        int limit = 0;
+        limit = 1;
+        local = limit;
+    }
+
+    return local;
+}
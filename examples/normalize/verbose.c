@@ -0,0 +1,67 @@
+struct point
+{
+    int x;
+    int y;
+} origin;
+struct point unit = { 1, 1 };
+
+const int limit = 10;
+
+long unsigned int total;
+long int unsigned * cursor;
+
+int sum(int n)
+{
+    int i;
+    int s = 0;
+
+    for (i = 0; (i < n); i += 1)
+    {
+        s += ((i * 2) + 1);
+    }
+
+    return (s);
+}
+
+int pick(int c)
+{
+    int r;
+
+    switch (c)
+    {
+        case 0:
+        {
+            r = 1;
+            break;
+        }
+        default:
+        {
+            r = (-1);
+            break;
+        }
+    }
+
+    if ((c > 0))
+    {
+        r = (r + c);
+    }
+    else
+    {
+        if (c < 0)
+        {
+            r = (r - c);
+        }
+    }
+
+    while (r > limit)
+    {
+        r -= limit;
+    }
+
+    return (r) ? (r) : ((r = 1), r);
+}
+
+int main()
+{
+    return ((sum(3) + pick(2)) - origin.x) + unit.y;
+}
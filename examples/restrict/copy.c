@@ -0,0 +1,6 @@
+typedef int *intptr;
+
+void copy(int * restrict dst, const int * restrict src, int n);
+
+int * const restrict first;
+restrict intptr second;
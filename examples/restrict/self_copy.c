@@ -0,0 +1,27 @@
+void *memcpy(void *restrict dst, const void *restrict src, unsigned long n);
+void copy(int *restrict dst, const int *src, int n);
+
+void copy(int *restrict dst, const int *src, int n) {
+    for (int i = 0; i < n; i++) {
+        dst[i] = src[i];
+    }
+}
+
+int main() {
+    int a[4] = {1, 2, 3, 4};
+    int b[4] = {0, 0, 0, 0};
+    int *p = a;
+    int *q = b;
+
+    memcpy(a, a, sizeof(a));
+    memcpy((void *)p, &a[0], sizeof(a));
+    copy(b, (int *)b, 4);
+
+    // Distinct buffers, or a pointer variable modified since its initialization.
+    memcpy(b, a, sizeof(a));
+    copy(b, a, 4);
+    q = a;
+    copy(q, b, 4);
+
+    return b[0];
+}
@@ -0,0 +1,30 @@
+struct inner {
+    char tag;
+    long value;
+};
+
+struct outer {
+    int id;
+    struct inner a;
+    struct inner b;
+};
+
+struct outer make(int id) {
+    struct outer o = {id, {1, id * 10}, {2, id * 100}};
+    return o;
+}
+
+long value_of(struct inner in) {
+    return in.value + in.tag;
+}
+
+struct inner second(struct outer o) {
+    return o.b;
+}
+
+int main() {
+    struct outer s = make(3);
+    long sum = value_of(s.a) + value_of(make(4).b) + second(s).tag + make(5).a.value;
+    sum += make(6).b.tag;
+    return sum == 487;
+}
@@ -0,0 +1,23 @@
+int f(int a, int b) {
+    return a + b;
+}
+
+int main() {
+    int i = 0;
+    int j = 0;
+    int k = 0;
+    int a[4] = {0, 1, 2, 3};
+
+    i = i++;
+    j = f(j++, j++);
+    a[k] = k++;
+
+    // Sequenced by the operators, or accessing different variables.
+    i = i + 1;
+    j++ && j++;
+    k = (k++, k);
+    a[i] = j++;
+    i = j ? j++ : j--;
+
+    return i + j + k;
+}
@@ -0,0 +1,20 @@
+    .globl	check
+    .section	.text
+    .type	check, @function
+check:
+    beq	a0,zero, .check_L1.far
+.check_L2:
+    li	a0,0
+    beq	t5,t6, .check_L3
+    j	.check_split2
+.check_L3:
+    ret
+.check_L1.far:
+    j	.check_L1
+    .pushsection	.text.unlikely
+.check_split2:
+    call	__kecc_stack_fail
+.check_L1:
+    li	a0,1
+    call	abort
+    .popsection
@@ -0,0 +1,14 @@
+int visible;
+static int visible;
+
+static int hidden;
+int hidden;
+
+void loop(void) {
+    for (static int i = 0; i < 1; i++) {
+    }
+}
+
+void nested(void) {
+    static int helper(void);
+}
@@ -0,0 +1,22 @@
+static int counter = 0;
+static int table[3] = {1, 2, 3};
+int shared;
+
+static int declared_twice;
+static int declared_twice = 4;
+extern int declared_twice;
+
+int next(void) {
+    static int hits = 0;
+    return ++hits;
+}
+
+int reset(int n) {
+    static int hits = 10;
+    if (n) {
+        static int hits;
+        hits = n;
+        return hits;
+    }
+    return hits;
+}
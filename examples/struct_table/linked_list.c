@@ -0,0 +1,22 @@
+struct node {
+    struct node *next;
+    int val;
+};
+
+// `struct node` is contained by value, in an array.
+struct pool {
+    struct node nodes[8];
+    struct node *free;
+};
+
+struct list {
+    struct node *head;
+    struct pool *pool;
+    int len;
+};
+
+struct list lists[4];
+struct pool pool;
+
+struct node *push(struct list *list, int val);
+int pop(struct list *list);
@@ -0,0 +1,12 @@
+// `struct S` is referred to before and after its definition.
+struct S *early;
+typedef struct S T;
+
+struct S {
+    int x;
+    struct S *next;
+};
+
+struct S *late;
+typedef struct S T;
+T value;
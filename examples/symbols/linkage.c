@@ -0,0 +1,12 @@
+int shared = 2;
+static int counter = 1;
+static const int table[4] = {1, 2, 3, 4};
+int zeros[8];
+
+static int helper(void);
+
+int get(void) { return helper() + shared; }
+
+int helper(void) { return counter + table[0] + zeros[0]; }
+
+int main(void) { return get() - 4; }
@@ -0,0 +1,46 @@
+    .globl	get
+    .section	.text
+    .p2align	2
+    .type	get, @function
+get:
+    ret
+    .size	get, .-get
+    .section	.text
+    .p2align	2
+    .type	helper, @function
+helper:
+    ret
+    .size	helper, .-helper
+    .globl	main
+    .section	.text
+    .p2align	2
+    .type	main, @function
+main:
+    ret
+    .size	main, .-main
+    .section	.data
+    .align	2
+    .type	counter, @object
+    .size	counter, 4
+counter:
+    .zero	0x4
+    .globl	shared
+    .section	.data
+    .align	2
+    .type	shared, @object
+    .size	shared, 4
+shared:
+    .zero	0x4
+    .section	.rodata
+    .align	2
+    .type	table, @object
+    .size	table, 16
+table:
+    .zero	0x10
+    .globl	zeros
+    .section	.bss
+    .align	2
+    .type	zeros, @object
+    .size	zeros, 32
+zeros:
+    .zero	0x20
@@ -0,0 +1,9 @@
+struct node {
+  char tag;
+  struct node *next;
+  long count;
+};
+
+unsigned long size_of_node = sizeof(struct node);
+unsigned long align_of_node = _Alignof(struct node);
+int long_is_pointer = sizeof(long) == sizeof(void *);
@@ -0,0 +1,13 @@
+int table[4][4];
+
+int main() {
+    void *p = table;
+    int (*row)[4] = (int (*)[4])p;
+    void (*callback)(int, char *) = (void (*)(int, char *))0;
+    const char *const *names = (const char *const *)p;
+    long (*(*lookup)(int))[2] = (long (*(*)(int))[2])p;
+    int *columns[3] = {(int *)row, (int *)names, (int *)lookup};
+
+    return (int)_Alignof(int *[3]) + (row == (int (*)[4])table) + (callback == 0) +
+           (columns[0] != 0);
+}
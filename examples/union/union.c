@@ -0,0 +1,7 @@
+union U {
+    int a;
+    char b[7];
+    double d;
+};
+
+union U u;
@@ -0,0 +1,29 @@
+struct point {
+    char tag;
+    int x;
+    double y;
+};
+
+union number {
+    int i;
+    float f;
+    char bytes[8];
+};
+
+struct flags {
+    char kind;
+    unsigned ready : 1;
+    unsigned mode : 3;
+    short count;
+};
+
+struct node {
+    struct point point;
+    union number value;
+    struct node *next;
+    char name[3];
+    long id;
+};
+
+struct node *head;
+struct flags flags;
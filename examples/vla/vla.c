@@ -0,0 +1,9 @@
+int n = 3;
+int buf[n];
+
+void g(int n, int a[*]);
+
+int f(int n) {
+    static int counts[n];
+    return 0;
+}
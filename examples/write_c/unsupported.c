@@ -0,0 +1,12 @@
+struct point {
+    int x;
+    int y;
+};
+
+int area(struct point *p) {
+    return sizeof(struct point) + p->x * p->y;
+}
+
+int main() {
+    return 0;
+}
@@ -0,0 +1,8 @@
+static int big[100000];
+int zeros[4] = {0, 0};
+int tail[4] = {1, 0, 0};
+struct point {
+    char c;
+    long l;
+} origin = {0}, p = {1, 2};
+double negative_zero = -0.0;
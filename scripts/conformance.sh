@@ -0,0 +1,29 @@
+#!/usr/bin/env bash
+
+# Runs the conformance suite checked out at <suite>, e.g., `tests/single-exec` of c-testsuite or
+# `gcc.c-torture/execute` of GCC, against `examples/conformance/manifest.txt`.
+#
+# Usage: scripts/conformance.sh [--qemu] [--update] <suite>
+#
+#   --qemu    Also run the assembly of the tests on QEMU.
+#   --update  Regenerate the manifest from the results instead of checking them.
+
+# Exit when any command fails.
+set -e
+
+while [[ $# -gt 1 ]]; do
+    case "$1" in
+        --qemu) export KECC_CONFORMANCE_QEMU=1 ;;
+        --update) export KECC_CONFORMANCE_UPDATE=1 ;;
+        *) echo "unknown option: $1" >&2; exit 1 ;;
+    esac
+    shift
+done
+
+if [[ $# -ne 1 ]]; then
+    echo "usage: $0 [--qemu] [--update] <suite>" >&2
+    exit 1
+fi
+
+KECC_CONFORMANCE_DIR="$(realpath "$1")" RUST_MIN_STACK=33554432 \
+    cargo test --release --test test_examples test_conformance -- --exact --nocapture
@@ -0,0 +1,231 @@
+//! The artifacts of a single compilation, e.g., `foo.ir`, `foo.opt.ir` and `foo.s` for `foo.c`,
+//! which correspond to each other as they are generated from the same source at once.
+//!
+//! Each artifact starts with a stamp, a hash of the source and the [`CompileOptions`] it is
+//! compiled with, e.g., `/* kecc-stamp: 0123456789abcdef */` in the IR and `# kecc-stamp: ...` in
+//! the assembly, so that the artifacts of different compilations are told apart by
+//! [`read_stamp`].
+
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::str::FromStr;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::write_base::WriteLine;
+use crate::*;
+
+/// The kind of an artifact, selected by `--emit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ArtifactKind {
+    /// The IR generated from the source
+    Ir,
+    /// The IR after optimization
+    OptIr,
+    /// The assembly generated from the optimized IR
+    Asm,
+}
+
+impl ArtifactKind {
+    pub const ALL: [Self; 3] = [Self::Ir, Self::OptIr, Self::Asm];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ir => "ir",
+            Self::OptIr => "opt-ir",
+            Self::Asm => "asm",
+        }
+    }
+
+    /// Returns the extension of the files of the artifact, e.g., `opt.ir` for `foo.opt.ir`.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Ir => "ir",
+            Self::OptIr => "opt.ir",
+            Self::Asm => "s",
+        }
+    }
+
+    /// Returns the path of the artifact of `input`, next to it or in `out_dir` if any, e.g.,
+    /// `out/foo.opt.ir` for `src/foo.c`.
+    pub fn path(self, input: &Path, out_dir: Option<&Path>) -> PathBuf {
+        let stem = input.file_stem().unwrap_or(input.as_os_str());
+        let mut file_name = stem.to_os_string();
+        file_name.push(".");
+        file_name.push(self.extension());
+
+        match out_dir {
+            Some(out_dir) => out_dir.join(file_name),
+            None => input.with_file_name(file_name),
+        }
+    }
+
+    /// Returns the line stamping an artifact of this kind with `stamp`, in a comment.
+    fn stamp_line(self, stamp: &str) -> String {
+        match self {
+            Self::Ir | Self::OptIr => format!("/* {STAMP_PREFIX}{stamp} */\n"),
+            Self::Asm => format!("# {STAMP_PREFIX}{stamp}\n"),
+        }
+    }
+}
+
+impl fmt::Display for ArtifactKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for ArtifactKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name() == s)
+            .ok_or(())
+    }
+}
+
+const STAMP_PREFIX: &str = "kecc-stamp: ";
+
+/// Returns the stamp of the artifacts compiled from `source` with `options`.
+pub fn source_stamp(source: &[u8], options: &CompileOptions) -> String {
+    let mut hasher = DefaultHasher::new();
+    (env!("CARGO_PKG_VERSION"), source, options.to_json()).hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Returns the stamp `artifact` starts with, if any.
+pub fn read_stamp(artifact: &str) -> Option<&str> {
+    let line = artifact.lines().next()?;
+    let comment = line
+        .strip_prefix("/* ")
+        .and_then(|comment| comment.strip_suffix(" */"))
+        .or_else(|| line.strip_prefix("# "))?;
+    comment.strip_prefix(STAMP_PREFIX)
+}
+
+/// An error in compiling the artifacts.
+#[derive(Debug, Error)]
+pub enum ArtifactError {
+    #[error("failed to read the source: {0}")]
+    Io(#[from] io::Error),
+    #[error("the source changed while being compiled")]
+    SourceChanged,
+    #[error("failed to parse the source: {0:?}")]
+    Parse(ParseError),
+    #[error("{0}")]
+    Irgen(IrgenError),
+    #[error("failed to generate the assembly")]
+    Asmgen,
+}
+
+/// The artifacts of a single compilation, stamped with the same [`CompileArtifacts::stamp`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileArtifacts {
+    /// The stamp of the source and the options (see [`source_stamp`])
+    pub stamp: String,
+    pub ir: Option<String>,
+    pub opt_ir: Option<String>,
+    pub asm: Option<String>,
+}
+
+impl CompileArtifacts {
+    /// Returns the artifacts stamped with `stamp`, none of which is added yet.
+    pub fn new(stamp: String) -> Self {
+        Self {
+            stamp,
+            ir: None,
+            opt_ir: None,
+            asm: None,
+        }
+    }
+
+    pub fn get(&self, kind: ArtifactKind) -> Option<&str> {
+        match kind {
+            ArtifactKind::Ir => self.ir.as_deref(),
+            ArtifactKind::OptIr => self.opt_ir.as_deref(),
+            ArtifactKind::Asm => self.asm.as_deref(),
+        }
+    }
+
+    /// Adds the artifact `kind` written from `t`, after its stamp.
+    pub fn insert<T: WriteLine>(&mut self, kind: ArtifactKind, t: &T) -> io::Result<()> {
+        let mut buffer = kind.stamp_line(&self.stamp).into_bytes();
+        write(t, &mut buffer)?;
+        let text = String::from_utf8(buffer).expect("artifacts must be written in UTF-8");
+
+        let artifact = match kind {
+            ArtifactKind::Ir => &mut self.ir,
+            ArtifactKind::OptIr => &mut self.opt_ir,
+            ArtifactKind::Asm => &mut self.asm,
+        };
+        *artifact = Some(text);
+        Ok(())
+    }
+
+    /// Writes the artifacts of `input` next to it or into `out_dir` (see [`ArtifactKind::path`]),
+    /// and returns their paths.
+    pub fn write_files(&self, input: &Path, out_dir: Option<&Path>) -> io::Result<Vec<PathBuf>> {
+        if let Some(out_dir) = out_dir {
+            fs::create_dir_all(out_dir)?;
+        }
+
+        let mut paths = Vec::new();
+        for kind in ArtifactKind::ALL {
+            let artifact = some_or!(self.get(kind), continue);
+            let path = kind.path(input, out_dir);
+            fs::write(&path, artifact)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
+
+/// Compiles the C source at `path` with `options` once into the artifacts of `kinds`, optimizing
+/// the IR by `optimizer`.
+///
+/// The source is read before and after being parsed, and an edit in between is reported as
+/// [`ArtifactError::SourceChanged`], so that the stamp is that of the source compiled. The current
+/// thread is set to the target data of `options` (see [`set_target_data`]).
+pub fn compile_artifacts<O: Optimize<ir::TranslationUnit>>(
+    path: &Path,
+    options: &CompileOptions,
+    optimizer: &mut O,
+    kinds: &[ArtifactKind],
+) -> Result<CompileArtifacts, ArtifactError> {
+    let source = fs::read(path)?;
+    let (unit, line_map) = Parse
+        .translate_with_line_map(&path)
+        .map_err(ArtifactError::Parse)?;
+    if fs::read(path)? != source {
+        return Err(ArtifactError::SourceChanged);
+    }
+
+    let mut artifacts = CompileArtifacts::new(source_stamp(&source, options));
+    set_target_data(options.target_data);
+    let mut ir = Irgen::default()
+        .with_line_map(line_map)
+        .translate(&unit)
+        .map_err(ArtifactError::Irgen)?;
+    if kinds.contains(&ArtifactKind::Ir) {
+        artifacts.insert(ArtifactKind::Ir, &ir)?;
+    }
+
+    let _unused = optimizer.optimize(&mut ir);
+    if kinds.contains(&ArtifactKind::OptIr) {
+        artifacts.insert(ArtifactKind::OptIr, &ir)?;
+    }
+
+    if kinds.contains(&ArtifactKind::Asm) {
+        let asm = Asmgen::default()
+            .translate(&ir)
+            .map_err(|()| ArtifactError::Asmgen)?;
+        artifacts.insert(ArtifactKind::Asm, &asm)?;
+    }
+    Ok(artifacts)
+}
@@ -0,0 +1,289 @@
+//! The compressed instructions of the C extension, which encode common instructions in 2 bytes
+//! instead of 4 when their operands fit the compressed forms.
+//!
+//! Most compressed forms only name the 8 registers `x8`-`x15` (`s0`, `s1` and `a0`-`a5`, and
+//! likewise for the floating-point registers), require the destination to be the first source,
+//! and have immediates and offsets of a few bits. The code generation prefers such operands under
+//! `--march=+c` (see [`Register::allocation_order`]), and [`CompressionEstimate`] tells how much
+//! of the code the assembler may compress.
+
+use core::fmt;
+
+use crate::asm::*;
+use crate::some_or;
+
+impl Register {
+    /// Returns `true` if the register is one of `x8`-`x15` or `f8`-`f15`, which the compressed
+    /// instructions with 3-bit register fields name.
+    pub fn is_compressible(&self) -> bool {
+        match self {
+            Self::Saved(_, id) => *id <= 1,
+            Self::Arg(_, id) => *id <= 5,
+            _ => false,
+        }
+    }
+
+    /// Returns the registers of `register_type` to be allocated to the values, in the order they
+    /// are preferred. The temporaries come first, as they need not be saved. If `is_compressed`,
+    /// the compressible registers come first instead, so that more instructions have compressed
+    /// forms.
+    pub fn allocation_order(register_type: RegisterType, is_compressed: bool) -> Vec<Self> {
+        let temps = match register_type {
+            RegisterType::Integer => 0..=6,
+            RegisterType::FloatingPoint => 0..=11,
+        };
+        let mut order = temps
+            .map(|id| Self::temp(register_type, id))
+            .chain((0..=7).map(|id| Self::arg(register_type, id)))
+            .chain((0..=11).map(|id| Self::saved(register_type, id)))
+            .collect::<Vec<_>>();
+        if is_compressed {
+            // The sort is stable, so that the order is otherwise kept.
+            order.sort_by_key(|register| !register.is_compressible());
+        }
+        order
+    }
+}
+
+impl Instruction {
+    /// Returns `true` if the instruction has a compressed form on RV64C, into which the assembler
+    /// turns it under `.option rvc`. The branches and jumps are assumed to be in the range of
+    /// their compressed forms.
+    pub fn is_compressible(&self) -> bool {
+        match self {
+            Self::RType {
+                instr,
+                rd,
+                rs1,
+                rs2: Some(rs2),
+            } => {
+                let is_narrow = rd == rs1 && rd.is_compressible() && rs2.is_compressible();
+                match instr {
+                    // c.add, or c.mv from `add rd, zero, rs2`
+                    RType::Add(DataSize::Double) => {
+                        *rd != Register::Zero
+                            && *rs2 != Register::Zero
+                            && (rd == rs1 || *rs1 == Register::Zero)
+                    }
+                    RType::Add(DataSize::Word)
+                    | RType::Sub(DataSize::Double | DataSize::Word)
+                    | RType::And
+                    | RType::Or
+                    | RType::Xor => is_narrow,
+                    _ => false,
+                }
+            }
+            Self::RType { rs2: None, .. } => false,
+            Self::IType {
+                instr,
+                rd,
+                rs1,
+                imm,
+            } => {
+                let imm = some_or!(value(imm), return false);
+                match instr {
+                    // c.li
+                    IType::Addi(DataSize::Double) if *rs1 == Register::Zero => {
+                        *rd != Register::Zero && fits(imm, 6)
+                    }
+                    // c.addi16sp
+                    IType::Addi(DataSize::Double)
+                        if *rd == Register::Sp && *rs1 == Register::Sp =>
+                    {
+                        imm != 0 && imm % 16 == 0 && fits(imm, 10)
+                    }
+                    // c.addi4spn
+                    IType::Addi(DataSize::Double) if *rs1 == Register::Sp => {
+                        rd.is_compressible() && imm > 0 && imm % 4 == 0 && imm < 1024
+                    }
+                    // c.addi
+                    IType::Addi(DataSize::Double) => {
+                        rd == rs1 && *rd != Register::Zero && imm != 0 && fits(imm, 6)
+                    }
+                    // c.addiw
+                    IType::Addi(DataSize::Word) => {
+                        rd == rs1 && *rd != Register::Zero && fits(imm, 6)
+                    }
+                    IType::Andi => rd == rs1 && rd.is_compressible() && fits(imm, 6),
+                    IType::Slli(DataSize::Double) => {
+                        rd == rs1 && *rd != Register::Zero && (1..64).contains(&imm)
+                    }
+                    IType::Srli(DataSize::Double) | IType::Srai(DataSize::Double) => {
+                        rd == rs1 && rd.is_compressible() && (1..64).contains(&imm)
+                    }
+                    // `lwu` and the like have no compressed forms.
+                    IType::Load {
+                        data_size,
+                        is_signed,
+                    } if *is_signed || data_size.is_floating_point() => {
+                        let size = some_or!(memory_access_size(*data_size), return false);
+                        if *rs1 == Register::Sp {
+                            *rd != Register::Zero && fits_sp_offset(imm, size)
+                        } else {
+                            rd.is_compressible() && rs1.is_compressible() && fits_offset(imm, size)
+                        }
+                    }
+                    _ => false,
+                }
+            }
+            Self::SType {
+                instr: SType::Store(data_size),
+                rs1,
+                rs2,
+                imm,
+            } => {
+                let imm = some_or!(value(imm), return false);
+                let size = some_or!(memory_access_size(*data_size), return false);
+                if *rs1 == Register::Sp {
+                    fits_sp_offset(imm, size)
+                } else {
+                    rs1.is_compressible() && rs2.is_compressible() && fits_offset(imm, size)
+                }
+            }
+            // c.beqz and c.bnez
+            Self::BType {
+                instr: BType::Beq | BType::Bne,
+                rs1,
+                rs2,
+                ..
+            } => rs1.is_compressible() && *rs2 == Register::Zero,
+            Self::BType { .. } => false,
+            Self::UType {
+                instr: UType::Lui,
+                rd,
+                imm,
+            } => {
+                let imm = some_or!(value(imm), return false);
+                *rd != Register::Zero
+                    && *rd != Register::Sp
+                    && ((1..32).contains(&imm) || (0xfffe0..=0xfffff).contains(&imm))
+            }
+            Self::Pseudo(pseudo) => match pseudo {
+                Pseudo::Li { rd, imm } => *rd != Register::Zero && fits(*imm as i64, 6),
+                Pseudo::Mv { rd, rs } => *rd != Register::Zero && *rs != Register::Zero,
+                // c.addiw rd, 0
+                Pseudo::SextW { rd, rs } => rd == rs && *rd != Register::Zero,
+                Pseudo::J { .. } | Pseudo::Jr { .. } | Pseudo::Jalr { .. } | Pseudo::Ret => true,
+                Pseudo::La { .. }
+                | Pseudo::Fmv { .. }
+                | Pseudo::Neg { .. }
+                | Pseudo::Seqz { .. }
+                | Pseudo::Snez { .. }
+                | Pseudo::Fneg { .. }
+                | Pseudo::Call { .. } => false,
+            },
+        }
+    }
+}
+
+/// Returns the value of `imm`, or `None` for a relocation, which the linker may not fit.
+fn value(imm: &Immediate) -> Option<i64> {
+    match imm {
+        Immediate::Value(value) => Some(*value as i64),
+        Immediate::Relocation { .. } => None,
+    }
+}
+
+/// Returns `true` if `value` is a signed integer of `bits` bits.
+fn fits(value: i64, bits: u32) -> bool {
+    let bound = 1 << (bits - 1);
+    (-bound..bound).contains(&value)
+}
+
+/// Returns the size of the memory accessed by the compressed loads and stores of `data_size`, or
+/// `None` if they have no compressed form on RV64C, e.g., bytes.
+fn memory_access_size(data_size: DataSize) -> Option<i64> {
+    match data_size {
+        DataSize::Word => Some(4),
+        DataSize::Double | DataSize::DoublePrecision => Some(8),
+        DataSize::Byte | DataSize::Half | DataSize::SinglePrecision => None,
+    }
+}
+
+/// Returns `true` if `offset` fits the 5-bit offsets, scaled by `size`, of `c.lw` and the like.
+fn fits_offset(offset: i64, size: i64) -> bool {
+    offset % size == 0 && (0..32 * size).contains(&offset)
+}
+
+/// Returns `true` if `offset` fits the 6-bit offsets, scaled by `size`, of `c.lwsp` and the like.
+fn fits_sp_offset(offset: i64, size: i64) -> bool {
+    offset % size == 0 && (0..64 * size).contains(&offset)
+}
+
+/// The number of instructions of some code with compressed forms (see
+/// [`Instruction::is_compressible`]).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionEstimate {
+    pub compressible: usize,
+    pub total: usize,
+}
+
+impl CompressionEstimate {
+    /// Returns the estimate of `instructions`.
+    pub fn new<'a>(instructions: impl IntoIterator<Item = &'a Instruction>) -> Self {
+        let mut estimate = Self::default();
+        for instruction in instructions {
+            estimate.total += 1;
+            if instruction.is_compressible() {
+                estimate.compressible += 1;
+            }
+        }
+        estimate
+    }
+
+    /// Returns the percentage of the instructions with compressed forms.
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.compressible as f64 * 100.0 / self.total as f64
+    }
+
+    /// Returns the number of bytes the compressed forms save, 2 for each of them.
+    pub fn saved_bytes(&self) -> usize {
+        self.compressible * 2
+    }
+}
+
+impl fmt::Display for CompressionEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} instructions compressible ({:.1}%), saving {} bytes",
+            self.compressible,
+            self.total,
+            self.percent(),
+            self.saved_bytes()
+        )
+    }
+}
+
+impl TranslationUnit {
+    /// Returns the estimate of the instructions of the functions.
+    pub fn compression_estimate(&self) -> CompressionEstimate {
+        CompressionEstimate::new(self.functions.iter().flat_map(|function| {
+            function
+                .body
+                .blocks
+                .iter()
+                .chain(&function.body.cold_blocks)
+                .flat_map(|block| &block.instructions)
+        }))
+    }
+
+    /// Marks the functions as compressed, as [`Section::function`] does if `is_compressed`: they
+    /// are assembled under `.option rvc` and aligned to 2 bytes.
+    pub fn enable_compression(&mut self) {
+        for function in &mut self.functions {
+            for directive in &mut function.header {
+                if *directive == Directive::P2align(2) {
+                    *directive = Directive::P2align(1);
+                }
+            }
+            let option = Directive::Option(AsmOption::Rvc);
+            if !function.header.contains(&option) {
+                function.header.insert(0, option);
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use crate::asm::*;
+use crate::{some_or, Location, Optimize};
+
+/// Emits the line tables of the functions, so that a debugger shows the source of the code.
+///
+/// Each function whose location is known, e.g., by [`Parse::function_locations`], is given a
+/// `.loc` of its definition and is enclosed by `.cfi_startproc` and `.cfi_endproc`, so that the
+/// assembler generates the call frame information for backtraces. The files of the locations are
+/// numbered by `.file` before the first function in each of them.
+///
+/// [`Parse::function_locations`]: crate::Parse::function_locations
+#[derive(Default, Clone, Debug)]
+pub struct DebugLines {
+    locations: HashMap<String, Location>,
+}
+
+impl DebugLines {
+    pub fn new(locations: HashMap<String, Location>) -> Self {
+        Self { locations }
+    }
+}
+
+impl Optimize<TranslationUnit> for DebugLines {
+    fn optimize(&mut self, code: &mut TranslationUnit) -> bool {
+        let mut files = Vec::<&str>::new();
+        let mut changed = false;
+
+        for function in &mut code.functions {
+            let entry = some_or!(function.body.blocks.first(), continue);
+            let name = some_or!(&entry.label, continue);
+            let location = some_or!(self.locations.get(&name.0), continue);
+            if function
+                .body
+                .entry_directives
+                .contains(&Directive::CfiStartproc)
+            {
+                continue;
+            }
+
+            let file = if let Some(index) = files.iter().position(|file| *file == location.file) {
+                index + 1
+            } else {
+                files.push(&location.file);
+                function
+                    .header
+                    .insert(0, Directive::File(files.len(), location.file.clone()));
+                files.len()
+            };
+
+            function.body.entry_directives.extend([
+                Directive::CfiStartproc,
+                Directive::Loc {
+                    file,
+                    line: location.line,
+                    column: location.column,
+                },
+            ]);
+            // The call frame information ends before the size of the function, if any.
+            function
+                .body
+                .exit_directives
+                .insert(0, Directive::CfiEndproc);
+            changed = true;
+        }
+
+        changed
+    }
+}
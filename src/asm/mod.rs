@@ -1,10 +1,22 @@
+mod compressed;
+mod debug_lines;
+mod split_cold;
 mod write_asm;
 
+use lang_c::ast;
+
 use crate::ir;
 use crate::write_base::*;
+use crate::{some_or, target_data, Instrumentation};
 
 use core::convert::TryFrom;
 use core::fmt;
+use core::ops::Deref;
+use std::collections::HashMap;
+
+pub use compressed::CompressionEstimate;
+pub use debug_lines::DebugLines;
+pub use split_cold::SplitCold;
 
 /// TODO
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -18,6 +30,28 @@ pub struct TranslationUnit {
     pub variables: Vec<Section<Variable>>,
 }
 
+impl TranslationUnit {
+    /// Declares every function `.globl`, so that a library compiled with `--lib` exports them all
+    /// to the units it is linked with.
+    pub fn export_functions(&mut self) {
+        for function in &mut self.functions {
+            let label = function
+                .header
+                .iter()
+                .find_map(|directive| match directive {
+                    Directive::Type(label, SymbolType::Function) => Some(label.clone()),
+                    _ => None,
+                });
+            if let Some(label) = label {
+                let globl = Directive::Globl(label);
+                if !function.header.contains(&globl) {
+                    function.header.insert(0, globl);
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Section<T> {
     /// Section Headers provide size, offset, type, alignment and flags of the sections
@@ -40,11 +74,143 @@ impl<T> Section<T> {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Function {
     pub blocks: Vec<Block>,
+    /// The blocks rarely executed, e.g., on the way to `abort`, placed in
+    /// [`SectionType::TextUnlikely`] apart from `blocks` (see [`SplitCold`]).
+    pub cold_blocks: Vec<Block>,
+    /// The directives following the label of the entry block, e.g., the start of the call frame
+    /// information and the source line of the function (see [`DebugLines`]).
+    pub entry_directives: Vec<Directive>,
+    /// The directives following `blocks`, before `cold_blocks`.
+    pub exit_directives: Vec<Directive>,
 }
 
 impl Function {
     pub fn new(blocks: Vec<Block>) -> Self {
-        Self { blocks }
+        Self {
+            blocks,
+            cold_blocks: Vec::new(),
+            entry_directives: Vec::new(),
+            exit_directives: Vec::new(),
+        }
+    }
+
+    /// Returns the numbers of instructions in `blocks` and `cold_blocks`. The fewer instructions
+    /// the hot part has, the more of it fits in the instruction cache.
+    pub fn instruction_counts(&self) -> (usize, usize) {
+        let count = |blocks: &[Block]| {
+            blocks
+                .iter()
+                .map(|block| block.instructions.len())
+                .sum::<usize>()
+        };
+        (count(&self.blocks), count(&self.cold_blocks))
+    }
+}
+
+impl Function {
+    /// The function called when a stack canary is found corrupted.
+    pub const STACK_FAIL: &'static str = "__kecc_stack_fail";
+
+    /// Returns the instructions storing the stack canary at `offset` from `sp` in the prologue.
+    pub fn canary_store(offset: u64) -> Vec<Instruction> {
+        vec![
+            Instruction::Pseudo(Pseudo::Li {
+                rd: Register::T6,
+                imm: Instrumentation::STACK_CANARY,
+            }),
+            Instruction::SType {
+                instr: SType::SD,
+                rs1: Register::Sp,
+                rs2: Register::T6,
+                imm: Immediate::Value(offset),
+            },
+        ]
+    }
+
+    /// Returns the instructions checking the stack canary at `offset` from `sp` in the epilogue,
+    /// jumping to `ok` if it is intact and calling [`Function::STACK_FAIL`] otherwise.
+    ///
+    /// Only temporary registers are clobbered, so that the return value is preserved.
+    pub fn canary_check(offset: u64, ok: Label) -> Vec<Instruction> {
+        vec![
+            Instruction::IType {
+                instr: IType::LD,
+                rd: Register::T5,
+                rs1: Register::Sp,
+                imm: Immediate::Value(offset),
+            },
+            Instruction::Pseudo(Pseudo::Li {
+                rd: Register::T6,
+                imm: Instrumentation::STACK_CANARY,
+            }),
+            Instruction::BType {
+                instr: BType::Beq,
+                rs1: Register::T5,
+                rs2: Register::T6,
+                imm: ok,
+            },
+            Instruction::Pseudo(Pseudo::Call {
+                offset: Label(Self::STACK_FAIL.to_string()),
+            }),
+        ]
+    }
+}
+
+impl Section<Function> {
+    /// Creates the function `label` with `body`, whose first block must be labelled `label`, with
+    /// the directives describing its symbol: `.globl` unless `is_static`, so that a function with
+    /// internal linkage stays local, its alignment of 2 bytes for the compressed instructions and
+    /// of 4 bytes otherwise, its type, and its size, following `body`. If `is_compressed`, it is
+    /// assembled under `.option rvc`.
+    pub fn function(
+        label: Label,
+        is_static: bool,
+        is_compressed: bool,
+        mut body: Function,
+    ) -> Self {
+        let mut header = symbol_header(&label, is_static, SectionType::Text);
+        header.insert(
+            header.len() - 1,
+            Directive::P2align(if is_compressed { 1 } else { 2 }),
+        );
+        if is_compressed {
+            header.insert(0, Directive::Option(AsmOption::Rvc));
+        }
+        body.exit_directives
+            .push(Directive::Size(label, SymbolSize::UpToHere));
+
+        Self::new(header, body)
+    }
+
+    /// Creates [`Function::STACK_FAIL`], which writes [`Instrumentation::STACK_FAIL_MESSAGE`]
+    /// to the standard error and aborts. It must be emitted once with
+    /// [`Section::stack_fail_message`] in a program instrumented by
+    /// `Instrumentation::StackProtect`.
+    pub fn stack_fail() -> Self {
+        let label = Label(Function::STACK_FAIL.to_string());
+        let instructions = vec![
+            Instruction::Pseudo(Pseudo::Li {
+                rd: Register::A0,
+                imm: 2,
+            }),
+            Instruction::Pseudo(Pseudo::La {
+                rd: Register::A1,
+                symbol: Label(format!("{}_message", Function::STACK_FAIL)),
+            }),
+            Instruction::Pseudo(Pseudo::Li {
+                rd: Register::A2,
+                imm: Instrumentation::STACK_FAIL_MESSAGE.len() as u64 + 1,
+            }),
+            Instruction::Pseudo(Pseudo::Call {
+                offset: Label("write".to_string()),
+            }),
+            Instruction::Pseudo(Pseudo::Call {
+                offset: Label("abort".to_string()),
+            }),
+        ];
+
+        let body = Function::new(vec![Block::new(Some(label.clone()), instructions)]);
+        Self::function(label, false, false, body)
     }
 }
 
@@ -58,6 +224,239 @@ impl Variable {
     pub fn new(label: Label, directives: Vec<Directive>) -> Self {
         Self { label, directives }
     }
+
+    /// Creates a zero-initialized variable of `size` bytes, emitted as a single `.zero`.
+    pub fn zero(label: Label, size: usize) -> Self {
+        Self::new(label, vec![Directive::Zero(size)])
+    }
+
+    /// Returns the number of bytes of the variable, i.e., emitted by its directives.
+    pub fn size(&self) -> usize {
+        self.directives.iter().map(Directive::data_size).sum()
+    }
+}
+
+impl Section<Variable> {
+    /// Creates `variable` placed in `section`, with the directives describing its symbol:
+    /// `.globl` unless `is_static`, so that a variable with internal linkage stays local, its
+    /// alignment given by `.align`, its type, and its size.
+    pub fn object(variable: Variable, is_static: bool, section: SectionType, align: usize) -> Self {
+        let mut header = symbol_header(&variable.label, is_static, section);
+        header.insert(header.len() - 1, Directive::Align(align));
+        header.push(Directive::Size(
+            variable.label.clone(),
+            SymbolSize::Bytes(variable.size()),
+        ));
+
+        Self::new(header, variable)
+    }
+
+    /// Creates a zero-initialized variable of `size` bytes placed in the `.bss` section.
+    pub fn bss(label: Label, is_static: bool, size: usize, align: usize) -> Self {
+        Self::object(
+            Variable::zero(label, size),
+            is_static,
+            SectionType::Bss,
+            align,
+        )
+    }
+
+    /// Creates the global variable `decl` labeled `label` in the section given by
+    /// [`SectionType::of_variable`], or returns `None` if `decl` is not a variable defined in
+    /// this unit or its initializer has no data directives, e.g., a string literal or a struct
+    /// with bit-fields.
+    ///
+    /// A zero-initialized variable is a single `.zero` in `.bss`, however large it is, and the
+    /// items missing from an initializer list are a single `.zero` for the `ZeroInit` tail, e.g.,
+    /// `.word 1; .zero 12` for `int a[4] = {1};`.
+    pub fn global(
+        label: Label,
+        decl: &ir::Declaration,
+        structs: &HashMap<String, Option<ir::Dtype>>,
+    ) -> Option<Self> {
+        let (dtype, initializer) = decl.get_variable()?;
+        let section = SectionType::of_variable(decl)?;
+        let (size, align) = dtype.size_align_of(structs).ok()?;
+        let align = align.trailing_zeros() as usize;
+        if section == SectionType::Bss {
+            return Some(Self::bss(label, decl.is_static(), size, align));
+        }
+
+        let mut directives = Vec::new();
+        push_initializer(&mut directives, initializer.as_ref(), dtype, structs)?;
+        Some(Self::object(
+            Variable::new(label, directives),
+            decl.is_static(),
+            section,
+            align,
+        ))
+    }
+
+    /// Creates the message written by [`Function::STACK_FAIL`], followed by a newline.
+    pub fn stack_fail_message() -> Self {
+        let label = Label(format!("{}_message", Function::STACK_FAIL));
+        let directives = Instrumentation::STACK_FAIL_MESSAGE
+            .bytes()
+            .chain([b'\n'])
+            .map(Directive::Byte)
+            .collect();
+
+        Self::object(
+            Variable::new(label, directives),
+            true,
+            SectionType::Rodata,
+            0,
+        )
+    }
+
+    /// Creates a variable holding the size and alignment of a struct as computed by the
+    /// compiler, to be compared with those computed by other compilers, e.g.,
+    /// `__kecc_layout_foo: .quad 16; .quad 8` for `struct foo`.
+    pub fn layout_assert(layout: &ir::StructLayout) -> Self {
+        let label = Label(format!("__kecc_layout_{}", layout.tag));
+        let directives = vec![
+            Directive::Quad(layout.size_of as u64),
+            Directive::Quad(layout.align_of as u64),
+        ];
+
+        Self::object(
+            Variable::new(label, directives),
+            false,
+            SectionType::Rodata,
+            3,
+        )
+    }
+}
+
+/// Pushes the data directives of `initializer` for a value of `dtype` into `directives`, where no
+/// initializer is the zero value of `dtype`.
+fn push_initializer(
+    directives: &mut Vec<Directive>,
+    initializer: Option<&ast::Initializer>,
+    dtype: &ir::Dtype,
+    structs: &HashMap<String, Option<ir::Dtype>>,
+) -> Option<()> {
+    let (size, _) = dtype.size_align_of(structs).ok()?;
+    let initializer = some_or!(initializer, {
+        push_zero(directives, size);
+        return Some(());
+    });
+
+    match (initializer, dtype) {
+        (
+            ast::Initializer::Expression(expr),
+            ir::Dtype::Int { .. } | ir::Dtype::Float { .. } | ir::Dtype::Pointer { .. },
+        ) => {
+            let constant = ir::Constant::try_from(&expr.node).ok()?;
+            let constant = if dtype.get_pointer_inner().is_some() {
+                constant
+            } else {
+                constant.typecast(dtype.clone())
+            };
+            if constant.is_zero() {
+                push_zero(directives, size);
+                return Some(());
+            }
+            let bits = match constant {
+                ir::Constant::Int { value, .. } => value as u64,
+                ir::Constant::Float { .. } => constant.get_float_bits()?,
+                _ => return None,
+            };
+            directives.push(match size {
+                1 => Directive::Byte(bits as u8),
+                2 => Directive::Half(bits as u16),
+                4 => Directive::Word(bits as u32),
+                8 => Directive::Quad(bits),
+                _ => return None,
+            });
+        }
+        (
+            ast::Initializer::List(items),
+            ir::Dtype::Array {
+                inner,
+                size: Some(len),
+                ..
+            },
+        ) => {
+            if items.len() > *len || items.iter().any(|item| !item.node.designation.is_empty()) {
+                return None;
+            }
+            let (size_of_inner, align_of_inner) = inner.size_align_of(structs).ok()?;
+            let stride = size_of_inner.max(align_of_inner);
+            for item in items {
+                push_initializer(
+                    directives,
+                    Some(&item.node.initializer.node),
+                    inner.deref(),
+                    structs,
+                )?;
+                push_zero(directives, stride - size_of_inner);
+            }
+            // The `ZeroInit` tail of the items missing from the list
+            push_zero(directives, (len - items.len()) * stride);
+        }
+        (ast::Initializer::List(items), ir::Dtype::Struct { name, .. }) => {
+            let struct_type = structs.get(name.as_ref()?)?.as_ref()?;
+            let fields = struct_type.get_struct_fields()?.as_ref()?;
+            let (_, _, offsets) = struct_type.get_struct_size_align_offsets()?.as_ref()?;
+            if items.len() > fields.len()
+                || items.iter().any(|item| !item.node.designation.is_empty())
+                || (0..fields.len()).any(|i| struct_type.get_struct_bit_field(i).is_some())
+            {
+                return None;
+            }
+
+            // The directives of the struct, whose sizes give the offset of the next field
+            let mut data = Vec::new();
+            for (i, (field, offset)) in fields.iter().zip(offsets).enumerate() {
+                let written = data.iter().map(Directive::data_size).sum::<usize>();
+                push_zero(&mut data, offset - written);
+                let item = items.get(i).map(|item| &item.node.initializer.node);
+                push_initializer(&mut data, item, field.deref(), structs)?;
+            }
+            let written = data.iter().map(Directive::data_size).sum::<usize>();
+            push_zero(&mut data, size - written);
+            for directive in data {
+                if let Directive::Zero(bytes) = directive {
+                    push_zero(directives, bytes);
+                } else {
+                    directives.push(directive);
+                }
+            }
+        }
+        _ => return None,
+    }
+
+    Some(())
+}
+
+/// Pushes `.zero bytes` into `directives`, merged into the last one if it is also a `.zero`.
+fn push_zero(directives: &mut Vec<Directive>, bytes: usize) {
+    if bytes == 0 {
+        return;
+    }
+    if let Some(Directive::Zero(last)) = directives.last_mut() {
+        *last += bytes;
+    } else {
+        directives.push(Directive::Zero(bytes));
+    }
+}
+
+/// Returns the directives declaring the symbol `label` placed in `section`, i.e., `.globl` unless
+/// `is_static`, the section and the type of the symbol.
+fn symbol_header(label: &Label, is_static: bool, section: SectionType) -> Vec<Directive> {
+    let symbol_type = match section {
+        SectionType::Text | SectionType::TextUnlikely => SymbolType::Function,
+        SectionType::Data | SectionType::Rodata | SectionType::Bss => SymbolType::Object,
+    };
+    let mut header = vec![
+        Directive::Section(section),
+        Directive::Type(label.clone(), symbol_type),
+    ];
+    if !is_static {
+        header.insert(0, Directive::Globl(label.clone()));
+    }
+    header
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -83,12 +482,22 @@ impl Block {
 pub enum Directive {
     /// .align integer
     Align(usize),
+    /// .p2align integer
+    P2align(usize),
+    /// .option option
+    Option(AsmOption),
     /// .globl symbol
     Globl(Label),
     /// .section section_type
     Section(SectionType),
+    /// .pushsection section_type
+    PushSection(SectionType),
+    /// .popsection
+    PopSection,
     /// .type symbol, symbol_type
     Type(Label, SymbolType),
+    /// .size symbol, symbol_size
+    Size(Label, SymbolSize),
     /// .byte value
     Byte(u8),
     /// .half value
@@ -99,6 +508,18 @@ pub enum Directive {
     Quad(u64),
     /// .zero bytes
     Zero(usize),
+    /// .file file_number "file_name"
+    File(usize, String),
+    /// .loc file_number line column
+    Loc {
+        file: usize,
+        line: usize,
+        column: usize,
+    },
+    /// .cfi_startproc
+    CfiStartproc,
+    /// .cfi_endproc
+    CfiEndproc,
 }
 
 impl Directive {
@@ -112,22 +533,45 @@ impl Directive {
             DataSize::DoublePrecision => Self::Quad(value),
         }
     }
+
+    /// Returns the number of bytes emitted by the directive, e.g., 8 for `.quad`, or 0 if it does
+    /// not emit data.
+    pub fn data_size(&self) -> usize {
+        match self {
+            Self::Byte(_) => 1,
+            Self::Half(_) => 2,
+            Self::Word(_) => 4,
+            Self::Quad(_) => 8,
+            Self::Zero(bytes) => *bytes,
+            _ => 0,
+        }
+    }
 }
 
 impl fmt::Display for Directive {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Align(value) => write!(f, ".align\t{value}"),
+            Self::P2align(value) => write!(f, ".p2align\t{value}"),
+            Self::Option(option) => write!(f, ".option\t{option}"),
             Self::Globl(label) => write!(f, ".globl\t{label}"),
             Self::Type(symbol, symbol_type) => {
                 write!(f, ".type\t{symbol}, {symbol_type}")
             }
+            Self::Size(symbol, SymbolSize::Bytes(bytes)) => write!(f, ".size\t{symbol}, {bytes}"),
+            Self::Size(symbol, SymbolSize::UpToHere) => write!(f, ".size\t{symbol}, .-{symbol}"),
             Self::Section(section_type) => write!(f, ".section\t{section_type}"),
+            Self::PushSection(section_type) => write!(f, ".pushsection\t{section_type}"),
+            Self::PopSection => write!(f, ".popsection"),
             Self::Byte(value) => write!(f, ".byte\t{value:#x?}"),
             Self::Half(value) => write!(f, ".half\t{value:#x?}"),
             Self::Word(value) => write!(f, ".word\t{value:#x?}"),
             Self::Quad(value) => write!(f, ".quad\t{value:#x?}"),
             Self::Zero(bytes) => write!(f, ".zero\t{bytes:#x?}"),
+            Self::File(number, name) => write!(f, ".file\t{number} {name:?}"),
+            Self::Loc { file, line, column } => write!(f, ".loc\t{file} {line} {column}"),
+            Self::CfiStartproc => write!(f, ".cfi_startproc"),
+            Self::CfiEndproc => write!(f, ".cfi_endproc"),
         }
     }
 }
@@ -135,11 +579,30 @@ impl fmt::Display for Directive {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SectionType {
     Text,
+    TextUnlikely,
     Data,
     Rodata,
     Bss,
 }
 
+impl SectionType {
+    /// Returns the section where the global variable `decl` is placed, or `None` if `decl` is not a
+    /// variable defined in this unit, e.g., it is declared `extern`.
+    pub fn of_variable(decl: &ir::Declaration) -> Option<Self> {
+        if decl.get_variable().is_none() || decl.is_extern() {
+            return None;
+        }
+
+        Some(if decl.is_constant() {
+            Self::Rodata
+        } else if decl.is_zero_initialized() {
+            Self::Bss
+        } else {
+            Self::Data
+        })
+    }
+}
+
 impl fmt::Display for SectionType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -147,6 +610,7 @@ impl fmt::Display for SectionType {
             "{}",
             match self {
                 Self::Text => ".text",
+                Self::TextUnlikely => ".text.unlikely",
                 Self::Data => ".data",
                 Self::Rodata => ".rodata",
                 Self::Bss => ".bss",
@@ -174,6 +638,34 @@ impl fmt::Display for SymbolType {
     }
 }
 
+/// The options of the assembler set by `.option`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsmOption {
+    /// Assembles the instructions into their compressed forms if they have one.
+    Rvc,
+    /// Assembles the instructions uncompressed.
+    NoRvc,
+}
+
+impl fmt::Display for AsmOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rvc => write!(f, "rvc"),
+            Self::NoRvc => write!(f, "norvc"),
+        }
+    }
+}
+
+/// The size of a symbol given by `.size`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolSize {
+    /// The number of bytes, e.g., of a variable.
+    Bytes(usize),
+    /// The bytes from the symbol up to the directive, i.e., `.-symbol`, e.g., after the body of a
+    /// function.
+    UpToHere,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Instruction {
     /// R-type instruction format
@@ -954,6 +1446,86 @@ impl fmt::Display for RelocationFunction {
     }
 }
 
+/// Runtime helpers in the style of compiler-rt implementing 64-bit integer operations which have
+/// no single-instruction counterpart on RV32.
+///
+/// The code generation for RV32 is to call them for the operations on 64-bit integers, and to emit
+/// their bodies, defined in `asmgen::runtime`, once per unit.
+///
+/// For more details: <https://gcc.gnu.org/onlinedocs/gccint/Integer-library-routines.html>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RuntimeHelper {
+    /// __muldi3
+    Mul,
+    /// __divdi3
+    Div,
+    /// __udivdi3
+    Udiv,
+    /// __moddi3
+    Mod,
+    /// __umoddi3
+    Umod,
+    /// __ashldi3
+    Shl,
+    /// __ashrdi3
+    Ashr,
+    /// __lshrdi3
+    Lshr,
+}
+
+impl RuntimeHelper {
+    /// Returns the helper implementing `op` on operands of `dtype` on RV32, if any.
+    ///
+    /// Only 64-bit integer multiplication, division, remainder and shifts need a helper. Additions,
+    /// subtractions, bitwise operations and comparisons are expanded inline on register pairs.
+    pub fn of_binary_operator(op: &ast::BinaryOperator, dtype: &ir::Dtype) -> Option<Self> {
+        let is_signed = if let ir::Dtype::Int {
+            width: 64,
+            is_signed,
+            ..
+        } = dtype
+        {
+            *is_signed
+        } else {
+            return None;
+        };
+
+        let helper = match (op, is_signed) {
+            (ast::BinaryOperator::Multiply, _) => Self::Mul,
+            (ast::BinaryOperator::Divide, true) => Self::Div,
+            (ast::BinaryOperator::Divide, false) => Self::Udiv,
+            (ast::BinaryOperator::Modulo, true) => Self::Mod,
+            (ast::BinaryOperator::Modulo, false) => Self::Umod,
+            (ast::BinaryOperator::ShiftLeft, _) => Self::Shl,
+            (ast::BinaryOperator::ShiftRight, true) => Self::Ashr,
+            (ast::BinaryOperator::ShiftRight, false) => Self::Lshr,
+            _ => return None,
+        };
+
+        Some(helper)
+    }
+
+    /// Returns the symbol of the helper.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Mul => "__muldi3",
+            Self::Div => "__divdi3",
+            Self::Udiv => "__udivdi3",
+            Self::Mod => "__moddi3",
+            Self::Umod => "__umoddi3",
+            Self::Shl => "__ashldi3",
+            Self::Ashr => "__ashrdi3",
+            Self::Lshr => "__lshrdi3",
+        }
+    }
+}
+
+impl fmt::Display for RuntimeHelper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
 /// `Label` is used as branch, unconditional jump targets and symbol offsets.
 ///
 /// For more details: <https://github.com/michaeljclark/michaeljclark.github.io/blob/master/asm.md#labels>
@@ -996,7 +1568,7 @@ impl TryFrom<ir::Dtype> for DataSize {
                 let size = (width - 1) / ir::Dtype::BITS_OF_BYTE + 1;
                 (size, true)
             }
-            ir::Dtype::Pointer { .. } => (ir::Dtype::SIZE_OF_POINTER, false),
+            ir::Dtype::Pointer { .. } => (target_data().pointer_size, false),
             _ => todo!("DataSize::try_from: support dtype: {:?}", dtype),
         };
 
@@ -1004,7 +1576,7 @@ impl TryFrom<ir::Dtype> for DataSize {
             (ir::Dtype::SIZE_OF_CHAR, false) => Self::Byte,
             (ir::Dtype::SIZE_OF_SHORT, false) => Self::Half,
             (ir::Dtype::SIZE_OF_INT, false) => Self::Word,
-            (ir::Dtype::SIZE_OF_LONG, false) => Self::Double,
+            (ir::Dtype::SIZE_OF_LONGLONG, false) => Self::Double,
             (ir::Dtype::SIZE_OF_FLOAT, true) => Self::SinglePrecision,
             (ir::Dtype::SIZE_OF_DOUBLE, true) => Self::DoublePrecision,
             _ => panic!("there is no other possible case"),
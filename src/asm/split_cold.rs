@@ -0,0 +1,220 @@
+use core::mem;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::asm::*;
+use crate::{some_or, Optimize};
+
+/// Moves the cold blocks of each function to [`SectionType::TextUnlikely`], so that the hot blocks
+/// are packed closer together in the instruction cache.
+///
+/// A block is cold if it calls a function that never returns, e.g., `abort` or the functions in
+/// `noreturn`, or if all of its successors are cold. The entry block is never cold, and neither is
+/// a block with an unknown successor, e.g., by `jr`. The moved blocks keep their labels. A
+/// fallthrough across the sections becomes a `j`, and a conditional branch across the sections is
+/// redirected to a stub `j` in its own section, as the other section may be out of its range.
+#[derive(Default, Clone, Debug)]
+pub struct SplitCold {
+    noreturn: Vec<String>,
+}
+
+impl SplitCold {
+    /// The functions known not to return.
+    const NORETURN: [&'static str; 5] = [
+        "abort",
+        "exit",
+        "_exit",
+        "__assert_fail",
+        Function::STACK_FAIL,
+    ];
+
+    pub fn new(noreturn: Vec<String>) -> Self {
+        Self { noreturn }
+    }
+
+    fn is_noreturn(&self, instruction: &Instruction) -> bool {
+        match instruction {
+            Instruction::Pseudo(Pseudo::Call { offset }) => {
+                Self::NORETURN.contains(&offset.0.as_str()) || self.noreturn.contains(&offset.0)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the control flows from the end of `block` to the next block.
+    fn falls_through(&self, block: &Block) -> bool {
+        match block.instructions.last() {
+            Some(Instruction::Pseudo(Pseudo::J { .. } | Pseudo::Jr { .. } | Pseudo::Ret)) => false,
+            Some(instruction) => !self.is_noreturn(instruction),
+            None => true,
+        }
+    }
+
+    /// Returns the indices of the successors of each block, or `None` if some of them are unknown.
+    fn successors(&self, blocks: &[Block]) -> Vec<Option<Vec<usize>>> {
+        let indices = blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, block)| Some((block.label.as_ref()?, index)))
+            .collect::<BTreeMap<_, _>>();
+
+        blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block)| {
+                let mut successors = Vec::new();
+                for instruction in &block.instructions {
+                    match instruction {
+                        Instruction::BType { imm: target, .. }
+                        | Instruction::Pseudo(Pseudo::J { offset: target }) => {
+                            successors.push(*indices.get(target)?)
+                        }
+                        Instruction::Pseudo(Pseudo::Jr { .. }) => return None,
+                        _ => {}
+                    }
+                }
+                if self.falls_through(block) && index + 1 < blocks.len() {
+                    successors.push(index + 1);
+                }
+                Some(successors)
+            })
+            .collect()
+    }
+
+    fn split(&self, function: &mut Function) -> bool {
+        let mut blocks = split_at_branches(function.blocks.clone());
+        let successors = self.successors(&blocks);
+
+        let mut is_cold = blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block)| {
+                index != 0
+                    && block
+                        .instructions
+                        .iter()
+                        .any(|instruction| self.is_noreturn(instruction))
+            })
+            .collect::<Vec<_>>();
+        loop {
+            let mut changed = false;
+            for index in 1..blocks.len() {
+                let successors = some_or!(&successors[index], continue);
+                if !is_cold[index]
+                    && !successors.is_empty()
+                    && successors.iter().all(|successor| is_cold[*successor])
+                {
+                    is_cold[index] = true;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        if !is_cold.contains(&true) {
+            return false;
+        }
+
+        // Makes the fallthroughs across the sections explicit.
+        let name = blocks[0]
+            .label
+            .as_ref()
+            .map_or("function", |label| label.0.as_str())
+            .to_string();
+        for index in 1..blocks.len() {
+            if is_cold[index - 1] == is_cold[index] || !self.falls_through(&blocks[index - 1]) {
+                continue;
+            }
+            let label = blocks[index]
+                .label
+                .get_or_insert_with(|| Label(format!(".{name}_split{index}")))
+                .clone();
+            blocks[index - 1]
+                .instructions
+                .push(Instruction::Pseudo(Pseudo::J { offset: label }));
+        }
+
+        // Redirects the conditional branches across the sections to the stubs.
+        let is_cold_label = blocks
+            .iter()
+            .zip(&is_cold)
+            .filter_map(|(block, is_cold)| Some((block.label.clone()?, *is_cold)))
+            .collect::<BTreeMap<_, _>>();
+        let mut hot_stubs = BTreeSet::new();
+        let mut cold_stubs = BTreeSet::new();
+        for (block, is_cold) in blocks.iter_mut().zip(&is_cold) {
+            for instruction in &mut block.instructions {
+                if let Instruction::BType { imm: target, .. } = instruction {
+                    if is_cold_label.get(&*target) != Some(&!is_cold) {
+                        continue;
+                    }
+                    let stubs = if *is_cold {
+                        &mut cold_stubs
+                    } else {
+                        &mut hot_stubs
+                    };
+                    let _ = stubs.insert(target.clone());
+                    *target = stub_label(target);
+                }
+            }
+        }
+
+        let (cold, hot): (Vec<_>, Vec<_>) = blocks
+            .into_iter()
+            .zip(is_cold)
+            .partition(|(_, is_cold)| *is_cold);
+        function.blocks = hot.into_iter().map(|(block, _)| block).collect();
+        function
+            .blocks
+            .extend(hot_stubs.into_iter().map(stub_block));
+        function
+            .cold_blocks
+            .extend(cold.into_iter().map(|(block, _)| block));
+        function
+            .cold_blocks
+            .extend(cold_stubs.into_iter().map(stub_block));
+
+        true
+    }
+}
+
+impl Optimize<TranslationUnit> for SplitCold {
+    fn optimize(&mut self, code: &mut TranslationUnit) -> bool {
+        code.functions
+            .iter_mut()
+            .map(|function| self.split(&mut function.body))
+            .fold(false, |l, r| l | r)
+    }
+}
+
+/// Splits the blocks after the conditional branches in the middle, e.g., of the epilogue checking
+/// the stack canary, so that the rest can be moved on its own.
+fn split_at_branches(blocks: Vec<Block>) -> Vec<Block> {
+    let mut result = Vec::new();
+    for block in blocks {
+        let mut current = Block::new(block.label, Vec::new());
+        for instruction in block.instructions {
+            let is_branch = matches!(instruction, Instruction::BType { .. });
+            current.instructions.push(instruction);
+            if is_branch {
+                result.push(mem::replace(&mut current, Block::new(None, Vec::new())));
+            }
+        }
+        if current.label.is_some() || !current.instructions.is_empty() {
+            result.push(current);
+        }
+    }
+    result
+}
+
+fn stub_label(target: &Label) -> Label {
+    Label(format!("{}.far", target.0))
+}
+
+fn stub_block(target: Label) -> Block {
+    Block::new(
+        Some(stub_label(&target)),
+        vec![Instruction::Pseudo(Pseudo::J { offset: target })],
+    )
+}
@@ -39,8 +39,31 @@ impl<T: WriteLine> WriteLine for Section<T> {
 
 impl WriteLine for Function {
     fn write_line(&self, indent: usize, write: &mut dyn Write) -> Result<()> {
-        for block in &self.blocks {
-            block.write_line(indent, write)?;
+        for (index, block) in self.blocks.iter().enumerate() {
+            let directives = if index == 0 {
+                self.entry_directives.as_slice()
+            } else {
+                &[]
+            };
+            write_block(block, directives, indent, write)?;
+        }
+        for directive in &self.exit_directives {
+            write_indent(indent + INDENT, write)?;
+            writeln!(write, "{directive}")?;
+        }
+
+        if !self.cold_blocks.is_empty() {
+            write_indent(indent + INDENT, write)?;
+            writeln!(
+                write,
+                "{}",
+                Directive::PushSection(SectionType::TextUnlikely)
+            )?;
+            for block in &self.cold_blocks {
+                block.write_line(indent, write)?;
+            }
+            write_indent(indent + INDENT, write)?;
+            writeln!(write, "{}", Directive::PopSection)?;
         }
 
         Ok(())
@@ -61,17 +84,32 @@ impl WriteLine for Variable {
 
 impl WriteLine for Block {
     fn write_line(&self, indent: usize, write: &mut dyn Write) -> Result<()> {
-        if let Some(label) = &self.label {
-            writeln!(write, "{}:", label.0)?;
-        }
+        write_block(self, &[], indent, write)
+    }
+}
 
-        for instruction in &self.instructions {
-            write_indent(indent + INDENT, write)?;
-            writeln!(write, "{instruction}")?;
-        }
+/// Writes `block` with `directives` between its label and its instructions.
+fn write_block(
+    block: &Block,
+    directives: &[Directive],
+    indent: usize,
+    write: &mut dyn Write,
+) -> Result<()> {
+    if let Some(label) = &block.label {
+        writeln!(write, "{}:", label.0)?;
+    }
 
-        Ok(())
+    for directive in directives {
+        write_indent(indent + INDENT, write)?;
+        writeln!(write, "{directive}")?;
+    }
+
+    for instruction in &block.instructions {
+        write_indent(indent + INDENT, write)?;
+        writeln!(write, "{instruction}")?;
     }
+
+    Ok(())
 }
 
 impl WriteString for Directive {
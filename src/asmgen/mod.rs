@@ -1,3 +1,6 @@
+pub mod runtime;
+pub mod target;
+
 use crate::asm;
 use crate::ir;
 use crate::Translate;
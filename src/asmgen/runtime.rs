@@ -0,0 +1,447 @@
+//! Definitions of the runtime helpers of 64-bit integer operations on RV32 (see
+//! [`RuntimeHelper`]).
+//!
+//! The helpers are defined in the IR on 32-bit words, so that they are lowered as any other
+//! function and emitted once for the functions calling them. Unlike those of compiler-rt, they
+//! take the 64-bit integers by address, since they do not fit in a register on RV32, and write
+//! the result to the address given first, e.g., `__muldi3(dst, lhs, rhs)`. The shifts take their
+//! amount as a word, e.g., `__ashldi3(dst, lhs, amount)`. A 64-bit integer is a pair of words,
+//! the low word first.
+
+use core::mem;
+use std::collections::BTreeMap;
+
+use lang_c::ast;
+
+use crate::asm::RuntimeHelper;
+use crate::ir::{self, HasDtype, Named};
+
+/// Returns the definition of `helper`, with internal linkage so that the units lowered separately
+/// do not clash.
+pub fn declaration(helper: RuntimeHelper) -> ir::Declaration {
+    let word = word();
+    let pointer = ir::Dtype::pointer(word.clone());
+    let amount = if is_shift(helper) {
+        word
+    } else {
+        pointer.clone()
+    };
+    let params = vec![pointer.clone(), pointer, amount];
+
+    let mut builder = Builder::new(params.clone());
+    let (dst, lhs, rhs) = (builder.param(0), builder.param(1), builder.param(2));
+    let value = builder.load_pair(&lhs);
+    let result = match helper {
+        RuntimeHelper::Mul => {
+            let rhs = builder.load_pair(&rhs);
+            builder.mul(value, rhs)
+        }
+        RuntimeHelper::Shl => builder.shift_left(value, rhs),
+        RuntimeHelper::Ashr => builder.shift_right(value, rhs, true),
+        RuntimeHelper::Lshr => builder.shift_right(value, rhs, false),
+        RuntimeHelper::Div | RuntimeHelper::Udiv | RuntimeHelper::Mod | RuntimeHelper::Umod => {
+            let rhs = builder.load_pair(&rhs);
+            builder.div_rem(helper, value, rhs)
+        }
+    };
+    builder.store_pair(&dst, result);
+    builder.end_block(ir::BlockExit::Return {
+        value: ir::Operand::constant(ir::Constant::unit()),
+    });
+
+    ir::Declaration::Function {
+        signature: ir::FunctionSignature {
+            ret: ir::Dtype::unit(),
+            params,
+        },
+        definition: Some(ir::FunctionDefinition {
+            allocations: Vec::new(),
+            blocks: builder.blocks,
+            bid_init: ir::BlockId(0),
+        }),
+        is_static: true,
+    }
+}
+
+fn is_shift(helper: RuntimeHelper) -> bool {
+    matches!(
+        helper,
+        RuntimeHelper::Shl | RuntimeHelper::Ashr | RuntimeHelper::Lshr
+    )
+}
+
+/// The dtype of the words the helpers compute on.
+fn word() -> ir::Dtype {
+    ir::Dtype::INT.set_signed(false)
+}
+
+/// A 64-bit integer as its low and high words.
+type Pair = (ir::Operand, ir::Operand);
+
+/// Builds the blocks of a helper, starting with the block `b0` taking the parameters.
+#[derive(Debug)]
+struct Builder {
+    blocks: BTreeMap<ir::BlockId, ir::Block>,
+    /// The block being built
+    bid: ir::BlockId,
+    phinodes: Vec<Named<ir::Dtype>>,
+    instructions: Vec<Named<ir::Instruction>>,
+}
+
+impl Builder {
+    fn new(params: Vec<ir::Dtype>) -> Self {
+        Self {
+            blocks: BTreeMap::new(),
+            bid: ir::BlockId(0),
+            phinodes: params
+                .into_iter()
+                .map(|dtype| Named::new(None, dtype))
+                .collect(),
+            instructions: Vec::new(),
+        }
+    }
+
+    fn param(&self, aid: usize) -> ir::Operand {
+        ir::Operand::register(
+            ir::RegisterId::arg(self.bid, aid),
+            (*self.phinodes[aid]).clone(),
+        )
+    }
+
+    /// Ends the block being built with `exit`, and starts building the block `bid` taking
+    /// `phinodes` words.
+    fn switch_block(&mut self, exit: ir::BlockExit, bid: ir::BlockId, phinodes: usize) {
+        self.end_block(exit);
+        self.bid = bid;
+        self.phinodes = (0..phinodes).map(|_| Named::new(None, word())).collect();
+    }
+
+    fn end_block(&mut self, exit: ir::BlockExit) {
+        let block = ir::Block {
+            phinodes: mem::take(&mut self.phinodes),
+            instructions: mem::take(&mut self.instructions),
+            exit,
+        };
+        let _unused = self.blocks.insert(self.bid, block);
+    }
+
+    fn insert(&mut self, instruction: ir::Instruction) -> ir::Operand {
+        let dtype = instruction.dtype();
+        self.instructions.push(Named::new(None, instruction));
+        ir::Operand::register(
+            ir::RegisterId::temp(self.bid, self.instructions.len() - 1),
+            dtype,
+        )
+    }
+
+    fn binary(
+        &mut self,
+        op: ast::BinaryOperator,
+        lhs: &ir::Operand,
+        rhs: &ir::Operand,
+    ) -> ir::Operand {
+        self.insert(ir::Instruction::BinOp {
+            op,
+            lhs: lhs.clone(),
+            rhs: rhs.clone(),
+            dtype: word(),
+        })
+    }
+
+    /// Returns the comparison `lhs op rhs` as a word, i.e., 0 or 1.
+    fn compare(
+        &mut self,
+        op: ast::BinaryOperator,
+        lhs: &ir::Operand,
+        rhs: &ir::Operand,
+    ) -> ir::Operand {
+        let condition = self.insert(ir::Instruction::BinOp {
+            op,
+            lhs: lhs.clone(),
+            rhs: rhs.clone(),
+            dtype: ir::Dtype::BOOL,
+        });
+        self.insert(ir::Instruction::TypeCast {
+            value: condition,
+            target_dtype: word(),
+        })
+    }
+
+    fn constant(value: u32) -> ir::Operand {
+        ir::Operand::constant(ir::Constant::int(value as u128, word()))
+    }
+
+    fn load_pair(&mut self, ptr: &ir::Operand) -> Pair {
+        let lo = self.insert(ir::Instruction::Load { ptr: ptr.clone() });
+        let ptr = self.high_word(ptr);
+        let hi = self.insert(ir::Instruction::Load { ptr });
+        (lo, hi)
+    }
+
+    fn store_pair(&mut self, ptr: &ir::Operand, (lo, hi): Pair) {
+        let _unused = self.insert(ir::Instruction::Store {
+            ptr: ptr.clone(),
+            value: lo,
+        });
+        let ptr = self.high_word(ptr);
+        let _unused = self.insert(ir::Instruction::Store { ptr, value: hi });
+    }
+
+    fn high_word(&mut self, ptr: &ir::Operand) -> ir::Operand {
+        self.insert(ir::Instruction::GetElementPtr {
+            ptr: ptr.clone(),
+            offset: ir::Operand::constant(ir::Constant::int(4, ir::Dtype::LONG)),
+            dtype: ir::Dtype::pointer(word()),
+        })
+    }
+
+    /// Returns the mask of all ones if `bit` is 1, and of all zeros if it is 0.
+    fn mask(&mut self, bit: &ir::Operand) -> ir::Operand {
+        self.binary(ast::BinaryOperator::Minus, &Self::constant(0), bit)
+    }
+
+    /// Returns `on` where `mask` is set and `off` elsewhere.
+    fn select(&mut self, mask: &ir::Operand, on: &ir::Operand, off: &ir::Operand) -> ir::Operand {
+        let on = self.binary(ast::BinaryOperator::BitwiseAnd, on, mask);
+        let inverse = self.binary(ast::BinaryOperator::BitwiseXor, mask, &Self::constant(!0));
+        let off = self.binary(ast::BinaryOperator::BitwiseAnd, off, &inverse);
+        self.binary(ast::BinaryOperator::BitwiseOr, &on, &off)
+    }
+
+    /// Returns `lhs - rhs`.
+    fn sub(&mut self, (lhs_lo, lhs_hi): &Pair, (rhs_lo, rhs_hi): &Pair) -> Pair {
+        let lo = self.binary(ast::BinaryOperator::Minus, lhs_lo, rhs_lo);
+        let borrow = self.compare(ast::BinaryOperator::Less, lhs_lo, rhs_lo);
+        let hi = self.binary(ast::BinaryOperator::Minus, lhs_hi, rhs_hi);
+        let hi = self.binary(ast::BinaryOperator::Minus, &hi, &borrow);
+        (lo, hi)
+    }
+
+    /// Returns `-value` if `mask` is all ones, and `value` if it is all zeros, i.e.,
+    /// `(value ^ mask) - mask`.
+    fn negate_if(&mut self, (lo, hi): Pair, mask: &ir::Operand) -> Pair {
+        let lo = self.binary(ast::BinaryOperator::BitwiseXor, &lo, mask);
+        let hi = self.binary(ast::BinaryOperator::BitwiseXor, &hi, mask);
+        self.sub(&(lo, hi), &(mask.clone(), mask.clone()))
+    }
+
+    /// Returns the high word of the 64-bit product of the words `lhs` and `rhs`, from the
+    /// products of their 16-bit halves, since RV32I has no `mulhu`.
+    fn mul_high(&mut self, lhs: &ir::Operand, rhs: &ir::Operand) -> ir::Operand {
+        let half = Self::constant(16);
+        let low_half = Self::constant(0xffff);
+        let split = |builder: &mut Self, value| {
+            (
+                builder.binary(ast::BinaryOperator::BitwiseAnd, value, &low_half),
+                builder.binary(ast::BinaryOperator::ShiftRight, value, &half),
+            )
+        };
+        let (lhs0, lhs1) = split(self, lhs);
+        let (rhs0, rhs1) = split(self, rhs);
+
+        let p00 = self.binary(ast::BinaryOperator::Multiply, &lhs0, &rhs0);
+        let p01 = self.binary(ast::BinaryOperator::Multiply, &lhs0, &rhs1);
+        let p10 = self.binary(ast::BinaryOperator::Multiply, &lhs1, &rhs0);
+        let p11 = self.binary(ast::BinaryOperator::Multiply, &lhs1, &rhs1);
+
+        // The carry into the high word sums three values below 2^16 + 2^16 + 2^16.
+        let p00_high = self.binary(ast::BinaryOperator::ShiftRight, &p00, &half);
+        let p01_low = self.binary(ast::BinaryOperator::BitwiseAnd, &p01, &low_half);
+        let p10_low = self.binary(ast::BinaryOperator::BitwiseAnd, &p10, &low_half);
+        let middle = self.binary(ast::BinaryOperator::Plus, &p00_high, &p01_low);
+        let middle = self.binary(ast::BinaryOperator::Plus, &middle, &p10_low);
+        let carry = self.binary(ast::BinaryOperator::ShiftRight, &middle, &half);
+
+        let p01_high = self.binary(ast::BinaryOperator::ShiftRight, &p01, &half);
+        let p10_high = self.binary(ast::BinaryOperator::ShiftRight, &p10, &half);
+        let high = self.binary(ast::BinaryOperator::Plus, &p11, &p01_high);
+        let high = self.binary(ast::BinaryOperator::Plus, &high, &p10_high);
+        self.binary(ast::BinaryOperator::Plus, &high, &carry)
+    }
+
+    /// Returns `lhs * rhs`, i.e., the low 64 bits of the product.
+    fn mul(&mut self, (lhs_lo, lhs_hi): Pair, (rhs_lo, rhs_hi): Pair) -> Pair {
+        let lo = self.binary(ast::BinaryOperator::Multiply, &lhs_lo, &rhs_lo);
+        let hi = self.mul_high(&lhs_lo, &rhs_lo);
+        let cross = self.binary(ast::BinaryOperator::Multiply, &lhs_lo, &rhs_hi);
+        let hi = self.binary(ast::BinaryOperator::Plus, &hi, &cross);
+        let cross = self.binary(ast::BinaryOperator::Multiply, &lhs_hi, &rhs_lo);
+        let hi = self.binary(ast::BinaryOperator::Plus, &hi, &cross);
+        (lo, hi)
+    }
+
+    /// Returns the amount modulo 32, and the mask of all ones if the amount modulo 64 is at least
+    /// 32.
+    fn split_amount(&mut self, amount: &ir::Operand) -> (ir::Operand, ir::Operand) {
+        let shift = self.binary(ast::BinaryOperator::BitwiseAnd, amount, &Self::constant(31));
+        let is_wide = self.binary(ast::BinaryOperator::ShiftRight, amount, &Self::constant(5));
+        let is_wide = self.binary(
+            ast::BinaryOperator::BitwiseAnd,
+            &is_wide,
+            &Self::constant(1),
+        );
+        let is_wide = self.mask(&is_wide);
+        (shift, is_wide)
+    }
+
+    /// Returns the bits of `value` shifted out by the shift of `shift` bits towards the other
+    /// word, i.e., `value << (32 - shift)` for a right shift, and 0 if `shift` is 0.
+    ///
+    /// The shift is split in two, since shifting a word by 32 masks the amount to 0.
+    fn shifted_out(
+        &mut self,
+        op: ast::BinaryOperator,
+        value: &ir::Operand,
+        shift: &ir::Operand,
+    ) -> ir::Operand {
+        let value = self.binary(op.clone(), value, &Self::constant(1));
+        let rest = self.binary(ast::BinaryOperator::Minus, &Self::constant(31), shift);
+        self.binary(op, &value, &rest)
+    }
+
+    /// Returns `value << amount`, without branches.
+    fn shift_left(&mut self, (lo, hi): Pair, amount: ir::Operand) -> Pair {
+        let (shift, is_wide) = self.split_amount(&amount);
+        let lo_shifted = self.binary(ast::BinaryOperator::ShiftLeft, &lo, &shift);
+        let hi_shifted = self.binary(ast::BinaryOperator::ShiftLeft, &hi, &shift);
+        let carry = self.shifted_out(ast::BinaryOperator::ShiftRight, &lo, &shift);
+        let hi_shifted = self.binary(ast::BinaryOperator::BitwiseOr, &hi_shifted, &carry);
+
+        let zero = Self::constant(0);
+        let lo = self.select(&is_wide, &zero, &lo_shifted);
+        let hi = self.select(&is_wide, &lo_shifted, &hi_shifted);
+        (lo, hi)
+    }
+
+    /// Returns `value >> amount`, filling the vacated bits with the sign bit if `is_arithmetic`,
+    /// without branches.
+    fn shift_right(&mut self, (lo, hi): Pair, amount: ir::Operand, is_arithmetic: bool) -> Pair {
+        let (shift, is_wide) = self.split_amount(&amount);
+        let sign = if is_arithmetic {
+            let sign = self.binary(ast::BinaryOperator::ShiftRight, &hi, &Self::constant(31));
+            self.mask(&sign)
+        } else {
+            Self::constant(0)
+        };
+
+        // The words are unsigned, so the arithmetic shift of the high word fills the bits shifted
+        // in with those of the sign mask.
+        let hi_shifted = self.binary(ast::BinaryOperator::ShiftRight, &hi, &shift);
+        let hi_shifted = if is_arithmetic {
+            let fill = self.shifted_out(ast::BinaryOperator::ShiftLeft, &sign, &shift);
+            self.binary(ast::BinaryOperator::BitwiseOr, &hi_shifted, &fill)
+        } else {
+            hi_shifted
+        };
+        let lo_shifted = self.binary(ast::BinaryOperator::ShiftRight, &lo, &shift);
+        let carry = self.shifted_out(ast::BinaryOperator::ShiftLeft, &hi, &shift);
+        let lo_shifted = self.binary(ast::BinaryOperator::BitwiseOr, &lo_shifted, &carry);
+
+        let lo = self.select(&is_wide, &hi_shifted, &lo_shifted);
+        let hi = self.select(&is_wide, &sign, &hi_shifted);
+        (lo, hi)
+    }
+
+    /// Returns the quotient or the remainder of `lhs` by `rhs` for `helper`, by the restoring
+    /// division of their magnitudes one bit per iteration of the loop `b1`. The quotient takes
+    /// the sign of `lhs * rhs`, and the remainder that of `lhs`, as in C.
+    fn div_rem(&mut self, helper: RuntimeHelper, lhs: Pair, rhs: Pair) -> Pair {
+        let is_signed = matches!(helper, RuntimeHelper::Div | RuntimeHelper::Mod);
+        let (lhs, rhs, lhs_sign, rhs_sign) = if is_signed {
+            let lhs_sign =
+                self.binary(ast::BinaryOperator::ShiftRight, &lhs.1, &Self::constant(31));
+            let lhs_sign = self.mask(&lhs_sign);
+            let rhs_sign =
+                self.binary(ast::BinaryOperator::ShiftRight, &rhs.1, &Self::constant(31));
+            let rhs_sign = self.mask(&rhs_sign);
+            let lhs = self.negate_if(lhs, &lhs_sign);
+            let rhs = self.negate_if(rhs, &rhs_sign);
+            (lhs, rhs, lhs_sign, rhs_sign)
+        } else {
+            (lhs, rhs, Self::constant(0), Self::constant(0))
+        };
+
+        // The loop shifts the bits of the dividend from the quotient into the remainder, and
+        // subtracts the divisor from the remainder if it fits, setting the bit of the quotient.
+        let (bid_loop, bid_exit) = (ir::BlockId(1), ir::BlockId(2));
+        let zero = Self::constant(0);
+        self.switch_block(
+            ir::BlockExit::Jump {
+                arg: ir::JumpArg::new(
+                    bid_loop,
+                    vec![lhs.0, lhs.1, zero.clone(), zero, Self::constant(64)],
+                ),
+            },
+            bid_loop,
+            5,
+        );
+        let (quotient_lo, quotient_hi) = (self.param(0), self.param(1));
+        let (remainder_lo, remainder_hi) = (self.param(2), self.param(3));
+        let count = self.param(4);
+
+        let one = Self::constant(1);
+        let top = Self::constant(31);
+        let shift_in = |builder: &mut Self, high: &ir::Operand, low: &ir::Operand| {
+            let high = builder.binary(ast::BinaryOperator::ShiftLeft, high, &one);
+            let low = builder.binary(ast::BinaryOperator::ShiftRight, low, &top);
+            builder.binary(ast::BinaryOperator::BitwiseOr, &high, &low)
+        };
+        // The bit shifted out of the remainder makes it exceed any divisor.
+        let overflow = self.binary(ast::BinaryOperator::ShiftRight, &remainder_hi, &top);
+        let remainder_hi = shift_in(self, &remainder_hi, &remainder_lo);
+        let remainder_lo = shift_in(self, &remainder_lo, &quotient_hi);
+        let quotient_hi = shift_in(self, &quotient_hi, &quotient_lo);
+        let quotient_lo = self.binary(ast::BinaryOperator::ShiftLeft, &quotient_lo, &one);
+
+        let greater = self.compare(ast::BinaryOperator::Greater, &remainder_hi, &rhs.1);
+        let equal = self.compare(ast::BinaryOperator::Equals, &remainder_hi, &rhs.1);
+        let fits = self.compare(ast::BinaryOperator::GreaterOrEqual, &remainder_lo, &rhs.0);
+        let fits = self.binary(ast::BinaryOperator::BitwiseAnd, &equal, &fits);
+        let fits = self.binary(ast::BinaryOperator::BitwiseOr, &greater, &fits);
+        let fits = self.binary(ast::BinaryOperator::BitwiseOr, &overflow, &fits);
+
+        let mask = self.mask(&fits);
+        let subtrahend_lo = self.binary(ast::BinaryOperator::BitwiseAnd, &rhs.0, &mask);
+        let subtrahend_hi = self.binary(ast::BinaryOperator::BitwiseAnd, &rhs.1, &mask);
+        let (remainder_lo, remainder_hi) = self.sub(
+            &(remainder_lo, remainder_hi),
+            &(subtrahend_lo, subtrahend_hi),
+        );
+        let quotient_lo = self.binary(ast::BinaryOperator::BitwiseOr, &quotient_lo, &fits);
+
+        let count = self.binary(ast::BinaryOperator::Minus, &count, &one);
+        let is_done = self.insert(ir::Instruction::BinOp {
+            op: ast::BinaryOperator::Equals,
+            lhs: count.clone(),
+            rhs: Self::constant(0),
+            dtype: ir::Dtype::BOOL,
+        });
+        let args = vec![quotient_lo, quotient_hi, remainder_lo, remainder_hi];
+        let mut loop_args = args.clone();
+        loop_args.push(count);
+        self.switch_block(
+            ir::BlockExit::ConditionalJump {
+                condition: is_done,
+                arg_then: ir::JumpArg::new(bid_exit, args),
+                arg_else: ir::JumpArg::new(bid_loop, loop_args),
+            },
+            bid_exit,
+            4,
+        );
+
+        match helper {
+            RuntimeHelper::Udiv => (self.param(0), self.param(1)),
+            RuntimeHelper::Umod => (self.param(2), self.param(3)),
+            RuntimeHelper::Div => {
+                let sign = self.binary(ast::BinaryOperator::BitwiseXor, &lhs_sign, &rhs_sign);
+                let quotient = (self.param(0), self.param(1));
+                self.negate_if(quotient, &sign)
+            }
+            RuntimeHelper::Mod => {
+                let remainder = (self.param(2), self.param(3));
+                self.negate_if(remainder, &lhs_sign)
+            }
+            _ => panic!("`{helper}` must be a division"),
+        }
+    }
+}
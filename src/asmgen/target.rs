@@ -0,0 +1,151 @@
+//! Targets of the code generation and the types they support.
+
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::ir::{self, HasDtype};
+use crate::some_or;
+
+/// The widest types a target supports, e.g., by its ABI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// The width of the widest integers, in bits
+    pub int_width: usize,
+    /// The width of the widest floating-point numbers, in bits
+    pub float_width: usize,
+}
+
+/// Properties of a target of the code generation.
+pub trait TargetMachine {
+    /// The name of the target, e.g., in diagnostics.
+    fn name(&self) -> &'static str;
+
+    /// The types the target supports.
+    fn capabilities(&self) -> Capabilities;
+
+    /// Returns `true` if the target supports values of `dtype`. Pointers are supported whatever
+    /// they point to, as it is checked where the pointee is accessed.
+    fn supports(&self, dtype: &ir::Dtype, structs: &HashMap<String, Option<ir::Dtype>>) -> bool {
+        let capabilities = self.capabilities();
+        match dtype {
+            ir::Dtype::Int { width, .. } => *width <= capabilities.int_width,
+            ir::Dtype::Float { width, .. } => *width <= capabilities.float_width,
+            ir::Dtype::Unit { .. }
+            | ir::Dtype::Pointer { .. }
+            | ir::Dtype::Typedef { .. }
+            | ir::Dtype::Enum { .. } => true,
+            ir::Dtype::Array { inner, .. } => self.supports(inner, structs),
+            ir::Dtype::Struct { name, fields, .. } => {
+                let fields = fields.as_ref().or_else(|| {
+                    structs
+                        .get(name.as_ref()?)?
+                        .as_ref()?
+                        .get_struct_fields()?
+                        .as_ref()
+                });
+                fields.map_or(true, |fields| {
+                    fields.iter().all(|field| self.supports(field, structs))
+                })
+            }
+            ir::Dtype::Function { ret, params } => {
+                self.supports(ret, structs)
+                    && params.iter().all(|param| self.supports(param, structs))
+            }
+        }
+    }
+}
+
+/// 64-bit RISC-V with the single- and double-precision floating-point extensions.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Riscv64;
+
+impl TargetMachine for Riscv64 {
+    fn name(&self) -> &'static str {
+        "riscv64"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            int_width: 64,
+            float_width: 64,
+        }
+    }
+}
+
+/// 32-bit RISC-V with the single-precision floating-point extension only, whose ABI (`ilp32f`)
+/// has no `double`. Integers wider than a register are held in pairs of registers.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Riscv32;
+
+impl TargetMachine for Riscv32 {
+    fn name(&self) -> &'static str {
+        "riscv32"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            int_width: 64,
+            float_width: 32,
+        }
+    }
+}
+
+/// A use of a type the target does not support, found by [`check_target`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedType {
+    /// The declaration using the type, e.g., `@f` for its signature, or `f:b0:3` and `f:%l0`
+    /// for an instruction and a local allocation of `f`
+    pub site: String,
+    pub dtype: ir::Dtype,
+    /// The name of the target
+    pub target: String,
+}
+
+impl fmt::Display for UnsupportedType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: error: `{}` is not supported by the target `{}`",
+            self.site, self.dtype, self.target
+        )
+    }
+}
+
+/// Returns the uses of the types in `unit` that `target` does not support, so that they are
+/// reported before optimizing and lowering `unit`.
+///
+/// The signatures and the types of the global variables, the local allocations and the results
+/// of the instructions are checked. Each type is reported once per declaration, at its first use.
+pub fn check_target<T: TargetMachine>(
+    unit: &ir::TranslationUnit,
+    target: &T,
+) -> Vec<UnsupportedType> {
+    let mut unsupported = Vec::new();
+    for (name, decl) in &unit.decls {
+        let mut reported = Vec::new();
+        let mut check = |site: String, dtype: ir::Dtype| {
+            if target.supports(&dtype, &unit.structs) || reported.contains(&dtype) {
+                return;
+            }
+            reported.push(dtype.clone());
+            unsupported.push(UnsupportedType {
+                site,
+                dtype,
+                target: target.name().to_string(),
+            });
+        };
+
+        check(format!("@{name}"), decl.dtype());
+        let (_, definition) = some_or!(decl.get_function(), continue);
+        let definition = some_or!(definition, continue);
+        for (aid, allocation) in definition.allocations.iter().enumerate() {
+            check(format!("{name}:%l{aid}"), (**allocation).clone());
+        }
+        for (bid, block) in &definition.blocks {
+            for (iid, instruction) in block.instructions.iter().enumerate() {
+                check(format!("{name}:{bid}:{iid}"), instruction.dtype());
+            }
+        }
+    }
+    unsupported
+}
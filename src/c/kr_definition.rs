@@ -0,0 +1,151 @@
+//! K&R function definitions, e.g., `int add(a, b) int a; int b; { return a + b; }`, which list the
+//! names of their parameters in the declarator and declare them between the declarator and the
+//! body.
+
+use std::collections::HashMap;
+use std::mem;
+
+use lang_c::ast::*;
+use lang_c::span::Node;
+
+use crate::c::name_of_declarator;
+use crate::c::parse::Error;
+use crate::some_or;
+
+/// Rewrites the K&R function definitions of `unit` into prototype form, e.g., `int add(int a, int
+/// b) { return a + b; }`, so that they have the same types as the prototypes.
+///
+/// A parameter not declared is `int`, as in C89. A declaration of a name not in the list, or a
+/// parameter listed or declared twice, is an error.
+pub(crate) fn prototype_kr_definitions(unit: &mut TranslationUnit) -> Result<(), Error> {
+    for decl in &mut unit.0 {
+        if let ExternalDeclaration::FunctionDefinition(definition) = &mut decl.node {
+            prototype_kr_definition(&mut definition.node).map_err(|message| {
+                Error::KRDefinition {
+                    function: name_of_declarator(&definition.node.declarator.node),
+                    message,
+                }
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+fn prototype_kr_definition(definition: &mut FunctionDefinition) -> Result<(), String> {
+    let derived = some_or!(
+        function_declarator_mut(&mut definition.declarator.node),
+        return Ok(())
+    );
+    let identifiers = match &derived.node {
+        DerivedDeclarator::KRFunction(identifiers) => identifiers.clone(),
+        _ if definition.declarations.is_empty() => return Ok(()),
+        _ => {
+            return Err(
+                "the parameters are declared, but the declarator does not list their names"
+                    .to_string(),
+            )
+        }
+    };
+    // `int f() { ... }` is kept without a prototype.
+    if identifiers.is_empty() && definition.declarations.is_empty() {
+        return Ok(());
+    }
+
+    let mut declared = HashMap::new();
+    for declaration in mem::take(&mut definition.declarations) {
+        let Declaration {
+            specifiers,
+            declarators,
+        } = declaration.node;
+        if declarators.is_empty() {
+            return Err(
+                "a declaration between the declarator and the body declares no parameter"
+                    .to_string(),
+            );
+        }
+
+        for init_declarator in declarators {
+            let InitDeclarator {
+                declarator,
+                initializer,
+            } = init_declarator.node;
+            let name = name_of_declarator(&declarator.node);
+            if !identifiers
+                .iter()
+                .any(|identifier| identifier.node.name == name)
+            {
+                return Err(format!("`{name}` is declared, but is not a parameter"));
+            }
+            if initializer.is_some() {
+                return Err(format!("parameter `{name}` is initialized"));
+            }
+
+            let parameter = ParameterDeclaration {
+                specifiers: specifiers.clone(),
+                declarator: Some(declarator),
+                extensions: Vec::new(),
+            };
+            if declared.insert(name.clone(), parameter).is_some() {
+                return Err(format!("parameter `{name}` is declared twice"));
+            }
+        }
+    }
+
+    let mut parameters = Vec::new();
+    for (i, identifier) in identifiers.iter().enumerate() {
+        let span = identifier.span;
+        let name = &identifier.node.name;
+        if identifiers[..i]
+            .iter()
+            .any(|other| other.node.name == *name)
+        {
+            return Err(format!("parameter `{name}` is listed twice"));
+        }
+
+        let parameter = declared
+            .remove(name)
+            .unwrap_or_else(|| ParameterDeclaration {
+                specifiers: vec![Node::new(
+                    DeclarationSpecifier::TypeSpecifier(Node::new(TypeSpecifier::Int, span)),
+                    span,
+                )],
+                declarator: Some(Node::new(
+                    Declarator {
+                        kind: Node::new(DeclaratorKind::Identifier(identifier.clone()), span),
+                        derived: Vec::new(),
+                        extensions: Vec::new(),
+                    },
+                    span,
+                )),
+                extensions: Vec::new(),
+            });
+        parameters.push(Node::new(parameter, span));
+    }
+
+    derived.node = DerivedDeclarator::Function(Node::new(
+        FunctionDeclarator {
+            parameters,
+            ellipsis: Ellipsis::None,
+        },
+        derived.span,
+    ));
+    Ok(())
+}
+
+/// Returns the derived declarator giving the parameters of the function `declarator` declares, as
+/// the names of the parameters are found in the IR generator.
+fn function_declarator_mut(declarator: &mut Declarator) -> Option<&mut Node<DerivedDeclarator>> {
+    if let DeclaratorKind::Declarator(inner) = &mut declarator.kind.node {
+        if let Some(derived) = function_declarator_mut(&mut inner.node) {
+            return Some(derived);
+        }
+    }
+
+    declarator.derived.iter_mut().find(|derived| {
+        matches!(
+            derived.node,
+            DerivedDeclarator::Function(_) | DerivedDeclarator::KRFunction(_)
+        )
+    })
+}
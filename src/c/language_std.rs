@@ -0,0 +1,159 @@
+//! Diagnose the constructs the selected revision of the C standard does not have, e.g., a
+//! declaration after a statement in a block or a declaration in the first clause of a `for`
+//! statement under C89.
+//!
+//! The checks only consult [`LanguageStd`] for whether a construct is allowed, so that a revision
+//! is described in one place. A construct is reported as a warning, as a pedantic compiler does,
+//! or as an error, by the severity the caller chooses.
+
+use core::fmt;
+
+use lang_c::ast::*;
+use lang_c::span::Span;
+
+use crate::c::name_of_declarator;
+use crate::LanguageStd;
+
+/// How a [`StdDiagnostic`] is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    /// The compilation fails, e.g., with `--pedantic-errors`.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Warning => "warning",
+                Self::Error => "error",
+            }
+        )
+    }
+}
+
+/// A construct the selected revision of the C standard does not have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StdViolation {
+    /// A declaration after a statement in the same block.
+    MixedDeclaration,
+    /// A declaration in the first clause of a `for` statement.
+    ForDeclaration,
+}
+
+/// A construct of the source not allowed by the selected revision of the C standard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StdDiagnostic {
+    /// The function containing the construct
+    pub function: String,
+    pub violation: StdViolation,
+    pub severity: Severity,
+    pub std: LanguageStd,
+    /// The span of the offending declaration
+    pub span: Span,
+}
+
+impl fmt::Display for StdDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let construct = match self.violation {
+            StdViolation::MixedDeclaration => "mixed declarations and statements",
+            StdViolation::ForDeclaration => "declaration in `for` loop initializer",
+        };
+        write!(
+            f,
+            "{}: {}: {construct} not allowed in {}",
+            self.function, self.severity, self.std
+        )
+    }
+}
+
+/// Returns the diagnostics for the constructs in the function bodies of `unit` that `std` does
+/// not allow, reported with `severity`.
+pub fn check_language_std(
+    unit: &TranslationUnit,
+    std: LanguageStd,
+    severity: Severity,
+) -> Vec<StdDiagnostic> {
+    let mut checker = Checker {
+        function: String::new(),
+        std,
+        severity,
+        diagnostics: Vec::new(),
+    };
+    for decl in &unit.0 {
+        if let ExternalDeclaration::FunctionDefinition(definition) = &decl.node {
+            checker.function = name_of_declarator(&definition.node.declarator.node);
+            checker.check_stmt(&definition.node.statement.node);
+        }
+    }
+
+    checker.diagnostics
+}
+
+struct Checker {
+    function: String,
+    std: LanguageStd,
+    severity: Severity,
+    diagnostics: Vec<StdDiagnostic>,
+}
+
+impl Checker {
+    fn check_stmt(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Labeled(stmt) => self.check_stmt(&stmt.node.statement.node),
+            Statement::Compound(items) => {
+                let mut after_statement = false;
+                for item in items {
+                    match &item.node {
+                        BlockItem::Declaration(_) | BlockItem::StaticAssert(_) => {
+                            if after_statement && !self.std.allows_mixed_declarations() {
+                                self.report(StdViolation::MixedDeclaration, item.span);
+                            }
+                        }
+                        BlockItem::Statement(stmt) => {
+                            after_statement = true;
+                            self.check_stmt(&stmt.node);
+                        }
+                    }
+                }
+            }
+            Statement::If(stmt) => {
+                self.check_stmt(&stmt.node.then_statement.node);
+                if let Some(stmt) = &stmt.node.else_statement {
+                    self.check_stmt(&stmt.node);
+                }
+            }
+            Statement::Switch(stmt) => self.check_stmt(&stmt.node.statement.node),
+            Statement::While(stmt) => self.check_stmt(&stmt.node.statement.node),
+            Statement::DoWhile(stmt) => self.check_stmt(&stmt.node.statement.node),
+            Statement::For(stmt) => {
+                let initializer = &stmt.node.initializer;
+                if matches!(initializer.node, ForInitializer::Declaration(_))
+                    && !self.std.allows_for_declarations()
+                {
+                    self.report(StdViolation::ForDeclaration, initializer.span);
+                }
+                self.check_stmt(&stmt.node.statement.node);
+            }
+            Statement::Expression(_)
+            | Statement::Return(_)
+            | Statement::Goto(_)
+            | Statement::Continue
+            | Statement::Break
+            | Statement::Asm(_) => (),
+        }
+    }
+
+    fn report(&mut self, violation: StdViolation, span: Span) {
+        self.diagnostics.push(StdDiagnostic {
+            function: self.function.clone(),
+            violation,
+            severity: self.severity,
+            std: self.std,
+            span,
+        });
+    }
+}
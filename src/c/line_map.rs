@@ -0,0 +1,139 @@
+//! Map offsets in preprocessed C sources back to the original files and lines.
+
+use core::fmt;
+
+use crate::some_or;
+
+/// A location in an original source file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Location {
+    pub file: String,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column number
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// Line markers of a preprocessed source.
+///
+/// The preprocessor emits line markers of the form `# 42 "foo.h" 1 3 4` (optionally with
+/// trailing flags) or `#line 42 "foo.h"`, meaning that the next line is line 42 of `foo.h`.
+/// For more details: <https://gcc.gnu.org/onlinedocs/cpp/Preprocessor-Output.html>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineMap {
+    /// The file a source without markers comes from
+    file: String,
+    /// Offset of the line following each marker, with its file and line number
+    markers: Vec<(usize, String, usize)>,
+    /// Offset of the beginning of each line in the preprocessed source
+    line_starts: Vec<usize>,
+}
+
+impl LineMap {
+    pub fn new(file: &str, source: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(source.match_indices('\n').map(|(offset, _)| offset + 1));
+
+        let mut markers = Vec::new();
+        let mut current_file = file.to_string();
+        for (index, start) in line_starts.iter().enumerate() {
+            let end = line_starts
+                .get(index + 1)
+                .map(|next| next - 1)
+                .unwrap_or(source.len());
+            let (line, marker_file) = some_or!(parse_line_marker(&source[*start..end]), continue);
+            if let Some(marker_file) = marker_file {
+                current_file = marker_file;
+            }
+
+            // The marker describes the line following it.
+            let next = line_starts.get(index + 1).copied().unwrap_or(source.len());
+            markers.push((next, current_file.clone(), line));
+        }
+
+        Self {
+            file: file.to_string(),
+            markers,
+            line_starts,
+        }
+    }
+
+    /// Returns `source` with its line markers replaced by empty lines.
+    ///
+    /// The lines and offsets of the result are the same as those of `source`, so a `LineMap` of
+    /// `source` applies to the result.
+    pub fn strip(source: &str) -> String {
+        source
+            .split_inclusive('\n')
+            .map(|line| {
+                let content = line.trim_end_matches(['\n', '\r']);
+                if parse_line_marker(content).is_some() {
+                    &line[content.len()..]
+                } else {
+                    line
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the original location of `offset` in the preprocessed source.
+    pub fn location(&self, offset: usize) -> Location {
+        let index = self
+            .line_starts
+            .partition_point(|start| *start <= offset)
+            .saturating_sub(1);
+        let column = offset - self.line_starts[index] + 1;
+
+        let marker = self
+            .markers
+            .iter()
+            .rev()
+            .find(|(start, _, _)| *start <= self.line_starts[index]);
+        let (file, line) = if let Some((start, file, line)) = marker {
+            let marker_index = self.line_starts.partition_point(|s| s < start);
+            (file.clone(), line + index - marker_index)
+        } else {
+            (self.file.clone(), index + 1)
+        };
+
+        Location { file, line, column }
+    }
+}
+
+/// Parses a line marker, returning the line number and the file name if present.
+fn parse_line_marker(line: &str) -> Option<(usize, Option<String>)> {
+    let rest = line.trim_start().strip_prefix('#')?.trim_start();
+    let rest = rest.strip_prefix("line").unwrap_or(rest).trim_start();
+    if !rest.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let digits = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let number = rest[..digits].parse().ok()?;
+    let rest = rest[digits..].trim_start();
+
+    if rest.is_empty() {
+        return Some((number, None));
+    }
+
+    // The file name is a string literal, followed by flags.
+    let rest = rest.strip_prefix('"')?;
+    let end = rest.find('"')?;
+    let flags = &rest[end + 1..];
+    if !flags
+        .split_ascii_whitespace()
+        .all(|flag| flag.chars().all(|c| c.is_ascii_digit()))
+    {
+        return None;
+    }
+
+    Some((number, Some(rest[..end].to_string())))
+}
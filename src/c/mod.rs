@@ -1,6 +1,29 @@
 mod ast_equiv;
+mod kr_definition;
+mod language_std;
+mod line_map;
+mod normalize;
 mod parse;
+mod restrict;
+mod sequence;
 mod write_c;
 
+use lang_c::ast::{Declarator, DeclaratorKind};
+
 pub use ast_equiv::assert_ast_equiv;
-pub use parse::Parse;
+pub use language_std::{check_language_std, Severity, StdDiagnostic, StdViolation};
+pub use line_map::{LineMap, Location};
+pub use normalize::Normalized;
+pub use parse::{Error as ParseError, Parse};
+pub use restrict::{check_restrict_aliasing, RestrictWarning};
+pub use sequence::{check_sequence_points, SequenceWarning};
+
+#[inline]
+pub(crate) fn name_of_declarator(declarator: &Declarator) -> String {
+    let declarator_kind = &declarator.kind;
+    match &declarator_kind.node {
+        DeclaratorKind::Abstract => panic!("DeclaratorKind::Abstract is unsupported"),
+        DeclaratorKind::Identifier(identifier) => identifier.node.name.clone(),
+        DeclaratorKind::Declarator(declarator) => name_of_declarator(&declarator.node),
+    }
+}
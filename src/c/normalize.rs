@@ -0,0 +1,856 @@
+//! Normalized C, in which structurally identical programs are written identically whatever their
+//! style, e.g., to diff submissions for grading.
+//!
+//! The normal form has:
+//!
+//! - one declarator per declaration, except in the initializer of a `for` and for the declarators
+//!   of an anonymous struct, which cannot be split without duplicating the struct;
+//! - the declaration specifiers in the order of the storage class, the function specifiers, the
+//!   qualifiers and the type specifiers, e.g., `static const unsigned long int`;
+//! - every operand parenthesized unless it is a primary or a postfix expression, and every
+//!   operator, including the compound assignments, surrounded by single spaces;
+//! - braces around the body of every `if`, `else`, `switch`, `while`, `do` and `for`;
+//! - one statement per line, indented by two spaces per level, and no blank lines.
+//!
+//! The GNU extensions and the constructs that only exist at compile time, e.g., `_Generic` and
+//! `_Static_assert`, cannot be normalized.
+
+use std::io::{Result, Write};
+
+use itertools::Itertools;
+use lang_c::ast::*;
+use lang_c::span::Node;
+
+use crate::write_base::{write_indent, WriteLine};
+
+/// Writes a translation unit in the normal form, e.g., by `write(&Normalized(&unit), &mut out)`.
+#[derive(Debug, Clone, Copy)]
+pub struct Normalized<'a>(pub &'a TranslationUnit);
+
+impl WriteLine for Normalized<'_> {
+    fn write_line(&self, indent: usize, write: &mut dyn Write) -> Result<()> {
+        for decl in &self.0 .0 {
+            match &decl.node {
+                ExternalDeclaration::Declaration(decl) => {
+                    write_declaration(&decl.node, indent, write)?
+                }
+                ExternalDeclaration::FunctionDefinition(fdef) => {
+                    write_function_definition(&fdef.node, indent, write)?
+                }
+                ExternalDeclaration::StaticAssert(_) => unsupported("`_Static_assert`"),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn unsupported(construct: &str) -> ! {
+    panic!("{construct} cannot be normalized")
+}
+
+/// A declaration specifier or a specifier qualifier.
+#[derive(Clone, Copy)]
+enum Specifier<'a> {
+    StorageClass(&'a StorageClassSpecifier),
+    Function(&'a FunctionSpecifier),
+    Alignment(&'a AlignmentSpecifier),
+    Qualifier(&'a TypeQualifier),
+    Type(&'a TypeSpecifier),
+}
+
+impl<'a> Specifier<'a> {
+    fn from_declaration(specifier: &'a DeclarationSpecifier) -> Self {
+        match specifier {
+            DeclarationSpecifier::StorageClass(storage_class) => {
+                Self::StorageClass(&storage_class.node)
+            }
+            DeclarationSpecifier::TypeSpecifier(type_specifier) => Self::Type(&type_specifier.node),
+            DeclarationSpecifier::TypeQualifier(qualifier) => Self::Qualifier(&qualifier.node),
+            DeclarationSpecifier::Function(function) => Self::Function(&function.node),
+            DeclarationSpecifier::Alignment(alignment) => Self::Alignment(&alignment.node),
+            DeclarationSpecifier::Extension(_) => unsupported("an extension"),
+        }
+    }
+
+    fn from_qualifier(specifier: &'a SpecifierQualifier) -> Self {
+        match specifier {
+            SpecifierQualifier::TypeSpecifier(type_specifier) => Self::Type(&type_specifier.node),
+            SpecifierQualifier::TypeQualifier(qualifier) => Self::Qualifier(&qualifier.node),
+            SpecifierQualifier::Extension(_) => unsupported("an extension"),
+        }
+    }
+
+    /// The position of the specifier in the canonical order.
+    fn rank(&self) -> (usize, usize) {
+        match self {
+            Self::StorageClass(storage_class) => (
+                0,
+                match storage_class {
+                    StorageClassSpecifier::Typedef => 0,
+                    StorageClassSpecifier::Extern => 1,
+                    StorageClassSpecifier::Static => 2,
+                    StorageClassSpecifier::ThreadLocal => 3,
+                    StorageClassSpecifier::Auto => 4,
+                    StorageClassSpecifier::Register => 5,
+                },
+            ),
+            Self::Function(_) => (1, 0),
+            Self::Alignment(_) => (2, 0),
+            Self::Qualifier(qualifier) => (
+                3,
+                match qualifier {
+                    TypeQualifier::Const => 0,
+                    TypeQualifier::Volatile => 1,
+                    TypeQualifier::Restrict => 2,
+                    TypeQualifier::Atomic => 3,
+                    _ => 4,
+                },
+            ),
+            Self::Type(type_specifier) => (
+                4,
+                match type_specifier {
+                    TypeSpecifier::Signed | TypeSpecifier::Unsigned => 0,
+                    TypeSpecifier::Short | TypeSpecifier::Long => 1,
+                    TypeSpecifier::Complex => 3,
+                    _ => 2,
+                },
+            ),
+        }
+    }
+
+    /// Writes the specifier, with the members of the struct it defines if `with_body`.
+    fn write(&self, indent: usize, with_body: bool) -> String {
+        match self {
+            Self::StorageClass(storage_class) => match storage_class {
+                StorageClassSpecifier::Typedef => "typedef",
+                StorageClassSpecifier::Extern => "extern",
+                StorageClassSpecifier::Static => "static",
+                StorageClassSpecifier::ThreadLocal => "_Thread_local",
+                StorageClassSpecifier::Auto => "auto",
+                StorageClassSpecifier::Register => "register",
+            }
+            .to_string(),
+            Self::Function(function) => match function {
+                FunctionSpecifier::Inline => "inline",
+                FunctionSpecifier::Noreturn => "_Noreturn",
+            }
+            .to_string(),
+            Self::Alignment(alignment) => match alignment {
+                AlignmentSpecifier::Type(type_name) => {
+                    format!("_Alignas({})", write_type_name(&type_name.node))
+                }
+                AlignmentSpecifier::Constant(constant) => {
+                    format!("_Alignas({})", write_expression(&constant.node))
+                }
+            },
+            Self::Qualifier(qualifier) => write_qualifier(qualifier).to_string(),
+            Self::Type(type_specifier) => write_type_specifier(type_specifier, indent, with_body),
+        }
+    }
+
+    /// Whether the specifier defines an anonymous struct, which cannot be referred to again.
+    fn defines_anonymous(&self) -> bool {
+        match self {
+            Self::Type(TypeSpecifier::Struct(struct_type)) => {
+                struct_type.node.identifier.is_none() && struct_type.node.declarations.is_some()
+            }
+            Self::Type(TypeSpecifier::Enum(enum_type)) => enum_type.node.identifier.is_none(),
+            _ => false,
+        }
+    }
+}
+
+/// Writes `specifiers` in the canonical order.
+fn write_specifiers(specifiers: &[Specifier<'_>], indent: usize, with_body: bool) -> String {
+    specifiers
+        .iter()
+        .sorted_by_key(|specifier| specifier.rank())
+        .map(|specifier| specifier.write(indent, with_body))
+        .join(" ")
+}
+
+/// Writes a declaration for each of `declarators` with `specifiers`. Only the first one defines
+/// the struct the specifiers define, and the others refer to it by its tag.
+fn split_declarators(
+    specifiers: &[Specifier<'_>],
+    declarators: Vec<String>,
+    indent: usize,
+) -> Vec<String> {
+    let defining = write_specifiers(specifiers, indent, true);
+    if declarators.is_empty() {
+        return vec![defining];
+    }
+    if specifiers.iter().any(Specifier::defines_anonymous) {
+        return vec![format!("{defining} {}", declarators.join(", "))];
+    }
+
+    let referring = write_specifiers(specifiers, indent, false);
+    declarators
+        .iter()
+        .enumerate()
+        .map(|(index, declarator)| {
+            let specifiers = if index == 0 { &defining } else { &referring };
+            join_declarator(specifiers, declarator)
+        })
+        .collect()
+}
+
+fn join_declarator(specifiers: &str, declarator: &str) -> String {
+    if declarator.is_empty() {
+        specifiers.to_string()
+    } else {
+        format!("{specifiers} {declarator}")
+    }
+}
+
+fn declaration_specifiers(specifiers: &[Node<DeclarationSpecifier>]) -> Vec<Specifier<'_>> {
+    specifiers
+        .iter()
+        .map(|specifier| Specifier::from_declaration(&specifier.node))
+        .collect()
+}
+
+fn qualifier_specifiers(specifiers: &[Node<SpecifierQualifier>]) -> Vec<Specifier<'_>> {
+    specifiers
+        .iter()
+        .map(|specifier| Specifier::from_qualifier(&specifier.node))
+        .collect()
+}
+
+fn write_declaration(decl: &Declaration, indent: usize, write: &mut dyn Write) -> Result<()> {
+    let declarators = decl
+        .declarators
+        .iter()
+        .map(|declarator| write_init_declarator(&declarator.node))
+        .collect();
+    for line in split_declarators(
+        &declaration_specifiers(&decl.specifiers),
+        declarators,
+        indent,
+    ) {
+        write_indent(indent, write)?;
+        writeln!(write, "{line};")?;
+    }
+    Ok(())
+}
+
+fn write_function_definition(
+    fdef: &FunctionDefinition,
+    indent: usize,
+    write: &mut dyn Write,
+) -> Result<()> {
+    write_indent(indent, write)?;
+    writeln!(
+        write,
+        "{}",
+        join_declarator(
+            &write_specifiers(&declaration_specifiers(&fdef.specifiers), indent, true),
+            &write_declarator(&fdef.declarator.node)
+        )
+    )?;
+    for decl in &fdef.declarations {
+        write_declaration(&decl.node, indent, write)?;
+    }
+    write_statement(&fdef.statement.node, indent, write)
+}
+
+fn write_init_declarator(declarator: &InitDeclarator) -> String {
+    let mut string = write_declarator(&declarator.declarator.node);
+    if let Some(initializer) = &declarator.initializer {
+        string.push_str(" = ");
+        string.push_str(&write_initializer(&initializer.node));
+    }
+    string
+}
+
+fn write_initializer(initializer: &Initializer) -> String {
+    match initializer {
+        Initializer::Expression(expr) => write_assignment(&expr.node),
+        Initializer::List(items) => format!(
+            "{{{}}}",
+            items
+                .iter()
+                .map(|item| {
+                    let designation = item
+                        .node
+                        .designation
+                        .iter()
+                        .map(|designator| match &designator.node {
+                            Designator::Index(index) => {
+                                format!("[{}]", write_expression(&index.node))
+                            }
+                            Designator::Member(member) => format!(".{}", member.node.name),
+                            Designator::Range(range) => format!(
+                                "[{} ... {}]",
+                                write_expression(&range.node.from.node),
+                                write_expression(&range.node.to.node)
+                            ),
+                        })
+                        .join("");
+                    let initializer = write_initializer(&item.node.initializer.node);
+                    if designation.is_empty() {
+                        initializer
+                    } else {
+                        format!("{designation} = {initializer}")
+                    }
+                })
+                .join(", ")
+        ),
+    }
+}
+
+fn write_qualifier(qualifier: &TypeQualifier) -> &'static str {
+    match qualifier {
+        TypeQualifier::Const => "const",
+        TypeQualifier::Restrict => "restrict",
+        TypeQualifier::Volatile => "volatile",
+        TypeQualifier::Nonnull => "_Nonnull",
+        TypeQualifier::NullUnspecified => "_Null_unspecified",
+        TypeQualifier::Nullable => "_Nullable",
+        TypeQualifier::Atomic => "_Atomic",
+    }
+}
+
+fn write_type_specifier(type_specifier: &TypeSpecifier, indent: usize, with_body: bool) -> String {
+    match type_specifier {
+        TypeSpecifier::Void => "void".to_string(),
+        TypeSpecifier::Char => "char".to_string(),
+        TypeSpecifier::Short => "short".to_string(),
+        TypeSpecifier::Int => "int".to_string(),
+        TypeSpecifier::Long => "long".to_string(),
+        TypeSpecifier::Float => "float".to_string(),
+        TypeSpecifier::Double => "double".to_string(),
+        TypeSpecifier::Signed => "signed".to_string(),
+        TypeSpecifier::Unsigned => "unsigned".to_string(),
+        TypeSpecifier::Bool => "_Bool".to_string(),
+        TypeSpecifier::Complex => "_Complex".to_string(),
+        TypeSpecifier::Struct(struct_type) => {
+            write_struct_type(&struct_type.node, indent, with_body)
+        }
+        TypeSpecifier::Enum(enum_type) => write_enum_type(&enum_type.node, with_body),
+        TypeSpecifier::TypedefName(name) => name.node.name.clone(),
+        TypeSpecifier::Atomic(_) => unsupported("`_Atomic(...)`"),
+        TypeSpecifier::TypeOf(_) => unsupported("`typeof`"),
+        TypeSpecifier::TS18661Float(_) => unsupported("a TS 18661 floating type"),
+    }
+}
+
+fn write_struct_type(struct_type: &StructType, indent: usize, with_body: bool) -> String {
+    let mut string = match struct_type.kind.node {
+        StructKind::Struct => "struct",
+        StructKind::Union => "union",
+    }
+    .to_string();
+    if let Some(identifier) = &struct_type.identifier {
+        string.push(' ');
+        string.push_str(&identifier.node.name);
+    }
+
+    let declarations = match &struct_type.declarations {
+        Some(declarations) if with_body || struct_type.identifier.is_none() => declarations,
+        _ => return string,
+    };
+    string.push_str(" {\n");
+    for declaration in declarations {
+        let field = match &declaration.node {
+            StructDeclaration::Field(field) => &field.node,
+            StructDeclaration::StaticAssert(_) => unsupported("`_Static_assert`"),
+        };
+        let declarators = field
+            .declarators
+            .iter()
+            .map(|declarator| {
+                let mut string = declarator
+                    .node
+                    .declarator
+                    .as_ref()
+                    .map_or(String::new(), |declarator| {
+                        write_declarator(&declarator.node)
+                    });
+                if let Some(bit_width) = &declarator.node.bit_width {
+                    if !string.is_empty() {
+                        string.push(' ');
+                    }
+                    string.push_str(": ");
+                    string.push_str(&write_expression(&bit_width.node));
+                }
+                string
+            })
+            .collect();
+        for line in split_declarators(
+            &qualifier_specifiers(&field.specifiers),
+            declarators,
+            indent + 1,
+        ) {
+            string.push_str(&"  ".repeat(indent + 1));
+            string.push_str(&line);
+            string.push_str(";\n");
+        }
+    }
+    string.push_str(&"  ".repeat(indent));
+    string.push('}');
+    string
+}
+
+fn write_enum_type(enum_type: &EnumType, with_body: bool) -> String {
+    let mut string = "enum".to_string();
+    if let Some(identifier) = &enum_type.identifier {
+        string.push(' ');
+        string.push_str(&identifier.node.name);
+    }
+
+    if enum_type.enumerators.is_empty() || !(with_body || enum_type.identifier.is_none()) {
+        return string;
+    }
+    let enumerators = enum_type
+        .enumerators
+        .iter()
+        .map(|enumerator| {
+            let name = &enumerator.node.identifier.node.name;
+            match &enumerator.node.expression {
+                Some(expr) => format!("{name} = {}", write_assignment(&expr.node)),
+                None => name.clone(),
+            }
+        })
+        .join(", ");
+    format!("{string} {{ {enumerators} }}")
+}
+
+fn write_type_name(type_name: &TypeName) -> String {
+    join_declarator(
+        &write_specifiers(&qualifier_specifiers(&type_name.specifiers), 0, true),
+        &type_name
+            .declarator
+            .as_ref()
+            .map_or(String::new(), |declarator| {
+                write_declarator(&declarator.node)
+            }),
+    )
+}
+
+fn write_declarator(declarator: &Declarator) -> String {
+    let mut prefix = String::new();
+    let mut suffix = String::new();
+    for derived in &declarator.derived {
+        match &derived.node {
+            DerivedDeclarator::Pointer(qualifiers) => {
+                prefix.push('*');
+                for qualifier in qualifiers {
+                    match &qualifier.node {
+                        PointerQualifier::TypeQualifier(qualifier) => {
+                            prefix.push_str(write_qualifier(&qualifier.node));
+                            prefix.push(' ');
+                        }
+                        PointerQualifier::Extension(_) => unsupported("an extension"),
+                    }
+                }
+            }
+            DerivedDeclarator::Array(array) => {
+                let qualifiers = array
+                    .node
+                    .qualifiers
+                    .iter()
+                    .map(|qualifier| write_qualifier(&qualifier.node));
+                let size = match &array.node.size {
+                    ArraySize::Unknown => String::new(),
+                    ArraySize::VariableUnknown => "*".to_string(),
+                    ArraySize::VariableExpression(expr) => write_assignment(&expr.node),
+                    ArraySize::StaticExpression(expr) => {
+                        format!("static {}", write_assignment(&expr.node))
+                    }
+                };
+                let inner = qualifiers
+                    .map(str::to_string)
+                    .chain((!size.is_empty()).then_some(size))
+                    .join(" ");
+                suffix.push_str(&format!("[{inner}]"));
+            }
+            DerivedDeclarator::Function(function) => {
+                let mut parameters = function
+                    .node
+                    .parameters
+                    .iter()
+                    .map(|parameter| {
+                        join_declarator(
+                            &write_specifiers(
+                                &declaration_specifiers(&parameter.node.specifiers),
+                                0,
+                                true,
+                            ),
+                            &parameter
+                                .node
+                                .declarator
+                                .as_ref()
+                                .map_or(String::new(), |declarator| {
+                                    write_declarator(&declarator.node)
+                                }),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                if function.node.ellipsis == Ellipsis::Some {
+                    parameters.push("...".to_string());
+                }
+                suffix.push_str(&format!("({})", parameters.join(", ")));
+            }
+            DerivedDeclarator::KRFunction(identifiers) => {
+                suffix.push_str(&format!(
+                    "({})",
+                    identifiers
+                        .iter()
+                        .map(|identifier| identifier.node.name.as_str())
+                        .join(", ")
+                ));
+            }
+            DerivedDeclarator::Block(_) => unsupported("a block pointer"),
+        }
+    }
+
+    let kind = match &declarator.kind.node {
+        DeclaratorKind::Abstract => String::new(),
+        DeclaratorKind::Identifier(identifier) => identifier.node.name.clone(),
+        DeclaratorKind::Declarator(inner) => format!("({})", write_declarator(&inner.node)),
+    };
+    if kind.is_empty() {
+        format!("{}{suffix}", prefix.trim_end())
+    } else {
+        format!("{prefix}{kind}{suffix}")
+    }
+}
+
+/// Writes `expr` as an operand, parenthesized unless it is a primary or a postfix expression other
+/// than an increment or a decrement.
+fn write_operand(expr: &Expression) -> String {
+    match expr {
+        Expression::Identifier(_)
+        | Expression::Constant(_)
+        | Expression::StringLiteral(_)
+        | Expression::Member(_)
+        | Expression::Call(_)
+        | Expression::SizeOfTy(_)
+        | Expression::SizeOfVal(_)
+        | Expression::AlignOf(_) => write_expression(expr),
+        Expression::BinaryOperator(binary)
+            if binary.node.operator.node == BinaryOperator::Index =>
+        {
+            write_expression(expr)
+        }
+        _ => format!("({})", write_expression(expr)),
+    }
+}
+
+/// Writes `expr` where an assignment expression is expected, e.g., as an argument, which is
+/// parenthesized if it is a comma expression.
+fn write_assignment(expr: &Expression) -> String {
+    if let Expression::Comma(_) = expr {
+        format!("({})", write_expression(expr))
+    } else {
+        write_expression(expr)
+    }
+}
+
+/// Writes `expr` as a whole, e.g., as an expression statement.
+fn write_expression(expr: &Expression) -> String {
+    match expr {
+        Expression::Identifier(identifier) => identifier.node.name.clone(),
+        Expression::Constant(constant) => write_constant(&constant.node),
+        Expression::StringLiteral(literal) => literal.node.join(" "),
+        Expression::Member(member) => format!(
+            "{}{}{}",
+            write_operand(&member.node.expression.node),
+            match member.node.operator.node {
+                MemberOperator::Direct => ".",
+                MemberOperator::Indirect => "->",
+            },
+            member.node.identifier.node.name
+        ),
+        Expression::Call(call) => format!(
+            "{}({})",
+            write_operand(&call.node.callee.node),
+            call.node
+                .arguments
+                .iter()
+                .map(|argument| write_assignment(&argument.node))
+                .join(", ")
+        ),
+        Expression::SizeOfTy(size_of) => {
+            format!("sizeof({})", write_type_name(&size_of.node.0.node))
+        }
+        Expression::SizeOfVal(size_of) => {
+            format!("sizeof({})", write_expression(&size_of.node.0.node))
+        }
+        Expression::AlignOf(align_of) => {
+            format!("_Alignof({})", write_type_name(&align_of.node.0.node))
+        }
+        Expression::UnaryOperator(unary) => {
+            let operand = write_operand(&unary.node.operand.node);
+            match unary.node.operator.node {
+                UnaryOperator::PostIncrement => format!("{operand}++"),
+                UnaryOperator::PostDecrement => format!("{operand}--"),
+                UnaryOperator::PreIncrement => format!("++{operand}"),
+                UnaryOperator::PreDecrement => format!("--{operand}"),
+                UnaryOperator::Address => format!("&{operand}"),
+                UnaryOperator::Indirection => format!("*{operand}"),
+                UnaryOperator::Plus => format!("+{operand}"),
+                UnaryOperator::Minus => format!("-{operand}"),
+                UnaryOperator::Complement => format!("~{operand}"),
+                UnaryOperator::Negate => format!("!{operand}"),
+            }
+        }
+        Expression::Cast(cast) => format!(
+            "({}){}",
+            write_type_name(&cast.node.type_name.node),
+            write_operand(&cast.node.expression.node)
+        ),
+        Expression::BinaryOperator(binary) => {
+            let lhs = write_operand(&binary.node.lhs.node);
+            if binary.node.operator.node == BinaryOperator::Index {
+                return format!("{lhs}[{}]", write_expression(&binary.node.rhs.node));
+            }
+            format!(
+                "{lhs} {} {}",
+                write_binary_operator(&binary.node.operator.node),
+                write_operand(&binary.node.rhs.node)
+            )
+        }
+        Expression::Conditional(conditional) => format!(
+            "{} ? {} : {}",
+            write_operand(&conditional.node.condition.node),
+            write_operand(&conditional.node.then_expression.node),
+            write_operand(&conditional.node.else_expression.node)
+        ),
+        Expression::Comma(exprs) => exprs
+            .iter()
+            .map(|expr| write_assignment(&expr.node))
+            .join(", "),
+        Expression::GenericSelection(_) => unsupported("`_Generic`"),
+        Expression::CompoundLiteral(_) => unsupported("a compound literal"),
+        Expression::OffsetOf(_) => unsupported("`offsetof`"),
+        Expression::VaArg(_) => unsupported("`va_arg`"),
+        Expression::Statement(_) => unsupported("a statement expression"),
+    }
+}
+
+fn write_binary_operator(operator: &BinaryOperator) -> &'static str {
+    match operator {
+        BinaryOperator::Index => "[]",
+        BinaryOperator::Multiply => "*",
+        BinaryOperator::Divide => "/",
+        BinaryOperator::Modulo => "%",
+        BinaryOperator::Plus => "+",
+        BinaryOperator::Minus => "-",
+        BinaryOperator::ShiftLeft => "<<",
+        BinaryOperator::ShiftRight => ">>",
+        BinaryOperator::Less => "<",
+        BinaryOperator::Greater => ">",
+        BinaryOperator::LessOrEqual => "<=",
+        BinaryOperator::GreaterOrEqual => ">=",
+        BinaryOperator::Equals => "==",
+        BinaryOperator::NotEquals => "!=",
+        BinaryOperator::BitwiseAnd => "&",
+        BinaryOperator::BitwiseXor => "^",
+        BinaryOperator::BitwiseOr => "|",
+        BinaryOperator::LogicalAnd => "&&",
+        BinaryOperator::LogicalOr => "||",
+        BinaryOperator::Assign => "=",
+        BinaryOperator::AssignMultiply => "*=",
+        BinaryOperator::AssignDivide => "/=",
+        BinaryOperator::AssignModulo => "%=",
+        BinaryOperator::AssignPlus => "+=",
+        BinaryOperator::AssignMinus => "-=",
+        BinaryOperator::AssignShiftLeft => "<<=",
+        BinaryOperator::AssignShiftRight => ">>=",
+        BinaryOperator::AssignBitwiseAnd => "&=",
+        BinaryOperator::AssignBitwiseXor => "^=",
+        BinaryOperator::AssignBitwiseOr => "|=",
+    }
+}
+
+fn write_constant(constant: &Constant) -> String {
+    match constant {
+        Constant::Integer(integer) => {
+            let (base, number) = match integer.base {
+                IntegerBase::Decimal => ("", integer.number.to_string()),
+                IntegerBase::Octal => ("0", integer.number.to_string()),
+                IntegerBase::Hexadecimal => ("0x", integer.number.to_lowercase()),
+                IntegerBase::Binary => ("0b", integer.number.to_string()),
+            };
+            let unsigned = if integer.suffix.unsigned { "U" } else { "" };
+            let size = match integer.suffix.size {
+                IntegerSize::Int => "",
+                IntegerSize::Long => "L",
+                IntegerSize::LongLong => "LL",
+            };
+            format!("{base}{number}{unsigned}{size}")
+        }
+        Constant::Float(float) => {
+            let (base, number) = match float.base {
+                FloatBase::Decimal => ("", float.number.to_lowercase()),
+                FloatBase::Hexadecimal => ("0x", float.number.to_lowercase()),
+            };
+            let format = match float.suffix.format {
+                FloatFormat::Float => "F",
+                FloatFormat::Double => "",
+                FloatFormat::LongDouble => "L",
+                FloatFormat::TS18661Format(_) => unsupported("a TS 18661 floating constant"),
+            };
+            format!("{base}{number}{format}")
+        }
+        Constant::Character(character) => character.clone(),
+    }
+}
+
+fn write_block_item(item: &BlockItem, indent: usize, write: &mut dyn Write) -> Result<()> {
+    match item {
+        BlockItem::Declaration(decl) => write_declaration(&decl.node, indent, write),
+        BlockItem::Statement(stmt) => write_statement(&stmt.node, indent, write),
+        BlockItem::StaticAssert(_) => unsupported("`_Static_assert`"),
+    }
+}
+
+/// Writes `stmt` as the body of a statement, in braces even if it is not a compound statement.
+fn write_body(stmt: &Statement, indent: usize, write: &mut dyn Write) -> Result<()> {
+    if let Statement::Compound(_) = stmt {
+        return write_statement(stmt, indent, write);
+    }
+
+    write_indent(indent, write)?;
+    writeln!(write, "{{")?;
+    write_statement(stmt, indent + 1, write)?;
+    write_indent(indent, write)?;
+    writeln!(write, "}}")
+}
+
+fn write_statement(stmt: &Statement, indent: usize, write: &mut dyn Write) -> Result<()> {
+    match stmt {
+        Statement::Labeled(labeled) => {
+            write_indent(indent, write)?;
+            match &labeled.node.label.node {
+                Label::Identifier(identifier) => writeln!(write, "{}:", identifier.node.name)?,
+                Label::Case(expr) => writeln!(write, "case {}:", write_expression(&expr.node))?,
+                Label::CaseRange(range) => writeln!(
+                    write,
+                    "case {} ... {}:",
+                    write_expression(&range.node.low.node),
+                    write_expression(&range.node.high.node)
+                )?,
+                Label::Default => writeln!(write, "default:")?,
+            }
+            write_statement(&labeled.node.statement.node, indent + 1, write)
+        }
+        Statement::Compound(items) => {
+            write_indent(indent, write)?;
+            writeln!(write, "{{")?;
+            for item in items {
+                write_block_item(&item.node, indent + 1, write)?;
+            }
+            write_indent(indent, write)?;
+            writeln!(write, "}}")
+        }
+        Statement::Expression(expr) => {
+            write_indent(indent, write)?;
+            match expr {
+                Some(expr) => writeln!(write, "{};", write_expression(&expr.node)),
+                None => writeln!(write, ";"),
+            }
+        }
+        Statement::If(stmt) => {
+            write_indent(indent, write)?;
+            writeln!(
+                write,
+                "if ({})",
+                write_expression(&stmt.node.condition.node)
+            )?;
+            write_body(&stmt.node.then_statement.node, indent, write)?;
+            if let Some(else_statement) = &stmt.node.else_statement {
+                write_indent(indent, write)?;
+                writeln!(write, "else")?;
+                write_body(&else_statement.node, indent, write)?;
+            }
+            Ok(())
+        }
+        Statement::Switch(stmt) => {
+            write_indent(indent, write)?;
+            writeln!(
+                write,
+                "switch ({})",
+                write_expression(&stmt.node.expression.node)
+            )?;
+            write_body(&stmt.node.statement.node, indent, write)
+        }
+        Statement::While(stmt) => {
+            write_indent(indent, write)?;
+            writeln!(
+                write,
+                "while ({})",
+                write_expression(&stmt.node.expression.node)
+            )?;
+            write_body(&stmt.node.statement.node, indent, write)
+        }
+        Statement::DoWhile(stmt) => {
+            write_indent(indent, write)?;
+            writeln!(write, "do")?;
+            write_body(&stmt.node.statement.node, indent, write)?;
+            write_indent(indent, write)?;
+            writeln!(
+                write,
+                "while ({});",
+                write_expression(&stmt.node.expression.node)
+            )
+        }
+        Statement::For(stmt) => {
+            let initializer = match &stmt.node.initializer.node {
+                ForInitializer::Empty => String::new(),
+                ForInitializer::Expression(expr) => write_expression(&expr.node),
+                ForInitializer::Declaration(decl) => {
+                    let declarators = decl
+                        .node
+                        .declarators
+                        .iter()
+                        .map(|declarator| write_init_declarator(&declarator.node))
+                        .join(", ");
+                    join_declarator(
+                        &write_specifiers(
+                            &declaration_specifiers(&decl.node.specifiers),
+                            indent,
+                            true,
+                        ),
+                        &declarators,
+                    )
+                }
+                ForInitializer::StaticAssert(_) => unsupported("`_Static_assert`"),
+            };
+            let optional = |expr: &Option<Box<Node<Expression>>>| {
+                expr.as_ref()
+                    .map_or(String::new(), |expr| write_expression(&expr.node))
+            };
+            write_indent(indent, write)?;
+            writeln!(
+                write,
+                "for ({initializer}; {}; {})",
+                optional(&stmt.node.condition),
+                optional(&stmt.node.step)
+            )?;
+            write_body(&stmt.node.statement.node, indent, write)
+        }
+        Statement::Goto(label) => {
+            write_indent(indent, write)?;
+            writeln!(write, "goto {};", label.node.name)
+        }
+        Statement::Continue => {
+            write_indent(indent, write)?;
+            writeln!(write, "continue;")
+        }
+        Statement::Break => {
+            write_indent(indent, write)?;
+            writeln!(write, "break;")
+        }
+        Statement::Return(expr) => {
+            write_indent(indent, write)?;
+            match expr {
+                Some(expr) => writeln!(write, "return {};", write_expression(&expr.node)),
+                None => writeln!(write, "return;"),
+            }
+        }
+        Statement::Asm(_) => unsupported("`asm`"),
+    }
+}
@@ -1,10 +1,16 @@
 use core::ops::Deref;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
 use std::path::Path;
 
 use lang_c::ast::*;
-use lang_c::driver::{parse, Config, Error as ParseError};
+use lang_c::driver::{parse, parse_preprocessed, Config, Error as ParseError, SyntaxError};
 use lang_c::span::Node;
 
+use crate::c::kr_definition::prototype_kr_definitions;
+use crate::c::line_map::{LineMap, Location};
+use crate::c::name_of_declarator;
 use crate::utils::AssertSupported;
 use crate::Translate;
 
@@ -12,6 +18,18 @@ use crate::Translate;
 #[derive(Debug)]
 pub enum Error {
     ParseError(ParseError),
+    /// Syntax error at `location` of the original source, following the line markers of the
+    /// preprocessed source.
+    SyntaxError {
+        location: Location,
+        error: SyntaxError,
+    },
+    /// A K&R function definition whose declarations do not match its list of parameter names, e.g.,
+    /// declaring a name not in the list.
+    KRDefinition {
+        function: String,
+        message: String,
+    },
     Unsupported,
 }
 
@@ -24,13 +42,72 @@ impl<P: AsRef<Path>> Translate<P> for Parse {
     type Error = Error;
 
     fn translate(&mut self, source: &P) -> Result<Self::Target, Self::Error> {
-        let config = Config::default();
-        let ast = parse(&config, source).map_err(Error::ParseError)?;
-        let unit = ast.unit;
+        let (unit, _) = self.translate_with_line_map(source)?;
+        Ok(unit)
+    }
+}
 
+impl Parse {
+    /// Translates the C source at `path`, returning its AST with the line map of the preprocessed
+    /// source the spans of the AST are in, e.g., to locate the errors of [`crate::Irgen`] by
+    /// [`crate::Irgen::with_line_map`].
+    pub fn translate_with_line_map<P: AsRef<Path>>(
+        &mut self,
+        path: &P,
+    ) -> Result<(TranslationUnit, LineMap), Error> {
+        let (mut unit, line_map) = parse_file(path.as_ref())?;
+
+        prototype_kr_definitions(&mut unit)?;
         unit.assert_supported();
-        Ok(unit)
+        Ok((unit, line_map))
     }
+
+    /// Returns the locations of the function definitions in the C source at `path`, following
+    /// the line markers of the preprocessed source.
+    pub fn function_locations<P: AsRef<Path>>(path: P) -> Result<HashMap<String, Location>, Error> {
+        let (unit, line_map) = parse_file(path.as_ref())?;
+
+        Ok(unit
+            .0
+            .iter()
+            .filter_map(|decl| match &decl.node {
+                ExternalDeclaration::FunctionDefinition(definition) => Some((
+                    name_of_declarator(&definition.node.declarator.node),
+                    line_map.location(definition.span.start),
+                )),
+                _ => None,
+            })
+            .collect())
+    }
+}
+
+/// Parses the C source at `path`, returning its AST and the line map of the preprocessed source
+/// the spans of the AST are in.
+fn parse_file(path: &Path) -> Result<(TranslationUnit, LineMap), Error> {
+    let config = Config::default();
+    let file = path.to_string_lossy();
+
+    // Already preprocessed sources (`.i`) are parsed without running the preprocessor.
+    if path.extension() == Some(OsStr::new("i")) {
+        let text = fs::read_to_string(path)
+            .map_err(|e| Error::ParseError(ParseError::PreprocessorError(e)))?;
+        let line_map = LineMap::new(&file, &text);
+        let ast = parse_preprocessed(&config, LineMap::strip(&text)).map_err(|error| {
+            let location = line_map.location(error.offset);
+            Error::SyntaxError { location, error }
+        })?;
+        return Ok((ast.unit, line_map));
+    }
+
+    let ast = parse(&config, path).map_err(|e| match e {
+        ParseError::SyntaxError(error) => {
+            let location = LineMap::new(&file, &error.source).location(error.offset);
+            Error::SyntaxError { location, error }
+        }
+        e => Error::ParseError(e),
+    })?;
+    let line_map = LineMap::new(&file, &ast.source);
+    Ok((ast.unit, line_map))
 }
 
 impl<T: AssertSupported> AssertSupported for Node<T> {
@@ -78,7 +155,7 @@ impl AssertSupported for ExternalDeclaration {
                 assert!(is_valid_global_variable_declaration(&decl.node));
                 decl.assert_supported()
             }
-            Self::StaticAssert(_) => panic!("ExternalDeclaration::StaticAssert"),
+            Self::StaticAssert(assert) => assert.node.expression.assert_supported(),
             Self::FunctionDefinition(fdef) => fdef.assert_supported(),
         }
     }
@@ -107,7 +184,7 @@ impl AssertSupported for DeclarationSpecifier {
             Self::TypeSpecifier(type_specifier) => type_specifier.assert_supported(),
             Self::TypeQualifier(type_qualifier) => type_qualifier.assert_supported(),
             Self::Function(_) => panic!("DeclarationSpecifier::Function"),
-            Self::Alignment(_) => panic!("DeclarationSpecifier::Alignment"),
+            Self::Alignment(alignment) => alignment.assert_supported(),
             Self::Extension(_) => panic!("DeclarationSpecifier::Extension"),
         }
     }
@@ -115,7 +192,10 @@ impl AssertSupported for DeclarationSpecifier {
 
 impl AssertSupported for StorageClassSpecifier {
     fn assert_supported(&self) {
-        assert_eq!(*self, Self::Typedef)
+        assert!(
+            matches!(self, Self::Typedef | Self::Extern | Self::Static),
+            "{self:?}"
+        )
     }
 }
 
@@ -132,10 +212,11 @@ impl AssertSupported for TypeSpecifier {
             Self::Signed => (),
             Self::Unsigned => (),
             Self::Bool => (),
-            Self::Complex => panic!("TypeSpecifier::Complex"),
+            // Rejected by irgen with a diagnostic.
+            Self::Complex => (),
             Self::Atomic(_) => panic!("TypeSpecifier::Atomic"),
             Self::Struct(struct_type) => struct_type.assert_supported(),
-            Self::Enum(_) => panic!("TypeSpecifier::Enum"),
+            Self::Enum(enum_type) => enum_type.assert_supported(),
             Self::TypedefName(_) => (),
             Self::TypeOf(_) => panic!("TypeSpecifier::TypeOf"),
             Self::TS18661Float(_) => panic!("TypeSpecifier::TS18661Float"),
@@ -169,24 +250,35 @@ impl AssertSupported for StructField {
 impl AssertSupported for StructDeclarator {
     fn assert_supported(&self) {
         self.declarator.assert_supported();
-        assert!(self.bit_width.is_none());
+        self.bit_width.assert_supported();
     }
 }
 
 impl AssertSupported for StructKind {
     fn assert_supported(&self) {
         match self {
-            Self::Struct => (),
-            Self::Union => panic!("StructKind::Union"),
+            Self::Struct | Self::Union => (),
         }
     }
 }
 
+impl AssertSupported for EnumType {
+    fn assert_supported(&self) {
+        self.enumerators.assert_supported();
+    }
+}
+
+impl AssertSupported for Enumerator {
+    fn assert_supported(&self) {
+        self.expression.assert_supported();
+    }
+}
+
 impl AssertSupported for AlignmentSpecifier {
     fn assert_supported(&self) {
         match self {
             Self::Type(typename) => typename.assert_supported(),
-            Self::Constant(_) => std::panic::panic_any(AlignmentSpecifier::Constant),
+            Self::Constant(expr) => expr.assert_supported(),
         }
     }
 }
@@ -228,7 +320,7 @@ impl AssertSupported for DerivedDeclarator {
             Self::Pointer(pointer_qualifiers) => pointer_qualifiers.assert_supported(),
             Self::Array(array_decl) => array_decl.assert_supported(),
             Self::Function(func_decl) => func_decl.assert_supported(),
-            // Support when K&R function has no parameter
+            // The K&R functions with parameters are rewritten into prototypes.
             Self::KRFunction(kr_func_decl) => assert!(kr_func_decl.is_empty()),
             Self::Block(_) => panic!("DerivedDeclarator::Block"),
         }
@@ -258,7 +350,7 @@ impl AssertSupported for ArrayDeclarator {
 impl AssertSupported for TypeQualifier {
     fn assert_supported(&self) {
         match self {
-            Self::Const => (),
+            Self::Const | Self::Restrict => (),
             _ => panic!("TypeQualifier::_"),
         }
     }
@@ -267,6 +359,7 @@ impl AssertSupported for TypeQualifier {
 impl AssertSupported for ArraySize {
     fn assert_supported(&self) {
         match self {
+            Self::Unknown | Self::VariableUnknown => (),
             Self::VariableExpression(expr) => expr.assert_supported(),
             _ => panic!("ArraySize::_"),
         }
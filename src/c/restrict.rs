@@ -0,0 +1,432 @@
+//! Warn about the calls passing the same pointer to two parameters one of which is
+//! `restrict`-qualified, e.g., `memcpy(p, p, n)`, whose behavior is undefined if the callee
+//! modifies the object it points to (see C17 6.7.3.1).
+//!
+//! The parameters are `restrict`-qualified by the declarations of the callee in the unit, or by
+//! [`Builtin::restrict_params`] for the builtins and the library functions they stand for, e.g.,
+//! `memcpy` for `__builtin_memcpy`. Two arguments are the same pointer if they are the same
+//! expression without side effects after looking through the casts, `&a[0]` for `a`, and the
+//! local pointer variables never modified after their initialization, e.g., `q` in `int *q = p;`
+//! if neither `q` nor `p` is modified.
+
+use core::fmt;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use lang_c::ast::*;
+use lang_c::span::Span;
+
+use crate::c::name_of_declarator;
+use crate::c::sequence::is_assignment;
+use crate::ir::{Builtin, Dtype};
+use crate::some_or;
+use crate::utils::IsEquiv;
+
+/// A call passing the same pointer to two parameters one of which is `restrict`-qualified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestrictWarning {
+    /// The function containing the call
+    pub function: String,
+    pub callee: String,
+    /// The indices of the arguments, in increasing order
+    pub args: [usize; 2],
+    /// The spans of the arguments
+    pub spans: [Span; 2],
+}
+
+impl fmt::Display for RestrictWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: warning: arguments {} and {} of `{}` are the same pointer, but a parameter is \
+             `restrict`-qualified",
+            self.function,
+            self.args[0] + 1,
+            self.args[1] + 1,
+            self.callee
+        )
+    }
+}
+
+/// Returns the warnings for the calls in the function bodies of `unit` passing the same pointer to
+/// two parameters one of which is `restrict`-qualified.
+pub fn check_restrict_aliasing(unit: &TranslationUnit) -> Vec<RestrictWarning> {
+    let mut restrict_params = HashMap::<String, BTreeSet<usize>>::new();
+    for decl in &unit.0 {
+        let declarators = match &decl.node {
+            ExternalDeclaration::Declaration(decl) => decl
+                .node
+                .declarators
+                .iter()
+                .map(|init_decl| &init_decl.node.declarator.node)
+                .collect(),
+            ExternalDeclaration::FunctionDefinition(definition) => {
+                vec![&definition.node.declarator.node]
+            }
+            ExternalDeclaration::StaticAssert(_) => Vec::new(),
+        };
+        for declarator in declarators {
+            let params = some_or!(restrict_params_of(declarator), continue);
+            restrict_params
+                .entry(name_of_declarator(declarator))
+                .or_default()
+                .extend(params);
+        }
+    }
+
+    let mut warnings = Vec::new();
+    for decl in &unit.0 {
+        let definition = match &decl.node {
+            ExternalDeclaration::FunctionDefinition(definition) => &definition.node,
+            _ => continue,
+        };
+        let mut body = Body::default();
+        body.declare_params(&definition.declarator.node);
+        body.stmt(&definition.statement.node);
+        let aliases = body.aliases();
+
+        for call in &body.calls {
+            let callee = match &call.callee.node {
+                Expression::Identifier(identifier) => &identifier.node.name,
+                _ => continue,
+            };
+            let mut params = restrict_params.get(callee).cloned().unwrap_or_default();
+            if let Some(builtin) = Builtin::from_library_name(callee) {
+                params.extend(builtin.restrict_params());
+            }
+
+            for (i, lhs) in call.arguments.iter().enumerate() {
+                for (j, rhs) in call.arguments.iter().enumerate().skip(i + 1) {
+                    if (params.contains(&i) || params.contains(&j))
+                        && is_same_pointer(&lhs.node, &rhs.node, &aliases)
+                    {
+                        warnings.push(RestrictWarning {
+                            function: name_of_declarator(&definition.declarator.node),
+                            callee: callee.clone(),
+                            args: [i, j],
+                            spans: [lhs.span, rhs.span],
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Returns the indices of the `restrict`-qualified parameters of the function `declarator`
+/// declares, or `None` if it does not declare a function.
+fn restrict_params_of(declarator: &Declarator) -> Option<Vec<usize>> {
+    if !matches!(declarator.kind.node, DeclaratorKind::Identifier(_)) {
+        return None;
+    }
+    let function = declarator
+        .derived
+        .iter()
+        .find_map(|derived| match &derived.node {
+            DerivedDeclarator::Function(function) => Some(&function.node),
+            _ => None,
+        })?;
+
+    Some(
+        function
+            .parameters
+            .iter()
+            .enumerate()
+            .filter(|(_, param)| {
+                Dtype::try_from(&param.node).map_or(false, |dtype| {
+                    dtype.is_restrict()
+                        || matches!(
+                            dtype,
+                            Dtype::Typedef {
+                                is_restrict: true,
+                                ..
+                            }
+                        )
+                })
+            })
+            .map(|(i, _)| i)
+            .collect(),
+    )
+}
+
+/// The variables and the calls of a function body.
+#[derive(Default)]
+struct Body<'a> {
+    /// The number of the declarations of each variable, including the parameters
+    declarations: HashMap<&'a str, usize>,
+    /// The initializer of each variable declared with an expression
+    initializers: HashMap<&'a str, &'a Expression>,
+    /// The variables assigned, incremented, decremented or whose address is taken
+    modified: HashSet<&'a str>,
+    calls: Vec<&'a CallExpression>,
+}
+
+impl<'a> Body<'a> {
+    fn declare_params(&mut self, declarator: &'a Declarator) {
+        for derived in &declarator.derived {
+            if let DerivedDeclarator::Function(function) = &derived.node {
+                for param in &function.node.parameters {
+                    if let Some(declarator) = &param.node.declarator {
+                        self.declare(&declarator.node);
+                    }
+                }
+                return;
+            }
+        }
+    }
+
+    fn declare(&mut self, declarator: &'a Declarator) {
+        let mut kind = &declarator.kind.node;
+        let name = loop {
+            match kind {
+                DeclaratorKind::Abstract => return,
+                DeclaratorKind::Identifier(identifier) => break identifier.node.name.as_str(),
+                DeclaratorKind::Declarator(declarator) => kind = &declarator.node.kind.node,
+            }
+        };
+        *self.declarations.entry(name).or_default() += 1;
+    }
+
+    fn stmt(&mut self, stmt: &'a Statement) {
+        match stmt {
+            Statement::Labeled(stmt) => self.stmt(&stmt.node.statement.node),
+            Statement::Compound(items) => {
+                for item in items {
+                    match &item.node {
+                        BlockItem::Declaration(decl) => self.declaration(&decl.node),
+                        BlockItem::StaticAssert(_) => (),
+                        BlockItem::Statement(stmt) => self.stmt(&stmt.node),
+                    }
+                }
+            }
+            Statement::Expression(expr) | Statement::Return(expr) => {
+                if let Some(expr) = expr {
+                    self.expr(&expr.node);
+                }
+            }
+            Statement::If(stmt) => {
+                self.expr(&stmt.node.condition.node);
+                self.stmt(&stmt.node.then_statement.node);
+                if let Some(stmt) = &stmt.node.else_statement {
+                    self.stmt(&stmt.node);
+                }
+            }
+            Statement::Switch(stmt) => {
+                self.expr(&stmt.node.expression.node);
+                self.stmt(&stmt.node.statement.node);
+            }
+            Statement::While(stmt) => {
+                self.expr(&stmt.node.expression.node);
+                self.stmt(&stmt.node.statement.node);
+            }
+            Statement::DoWhile(stmt) => {
+                self.stmt(&stmt.node.statement.node);
+                self.expr(&stmt.node.expression.node);
+            }
+            Statement::For(stmt) => {
+                match &stmt.node.initializer.node {
+                    ForInitializer::Expression(expr) => self.expr(&expr.node),
+                    ForInitializer::Declaration(decl) => self.declaration(&decl.node),
+                    ForInitializer::Empty | ForInitializer::StaticAssert(_) => (),
+                }
+                for expr in [&stmt.node.condition, &stmt.node.step]
+                    .into_iter()
+                    .flatten()
+                {
+                    self.expr(&expr.node);
+                }
+                self.stmt(&stmt.node.statement.node);
+            }
+            Statement::Goto(_) | Statement::Continue | Statement::Break | Statement::Asm(_) => (),
+        }
+    }
+
+    fn declaration(&mut self, decl: &'a Declaration) {
+        for init_decl in &decl.declarators {
+            self.declare(&init_decl.node.declarator.node);
+            let initializer = some_or!(&init_decl.node.initializer, continue);
+            if let Initializer::Expression(expr) = &initializer.node {
+                if let DeclaratorKind::Identifier(identifier) =
+                    &init_decl.node.declarator.node.kind.node
+                {
+                    let _unused = self
+                        .initializers
+                        .insert(identifier.node.name.as_str(), &expr.node);
+                }
+            }
+            for expr in initializer_exprs(&initializer.node) {
+                self.expr(expr);
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &'a Expression) {
+        match expr {
+            Expression::Call(call) => self.calls.push(&call.node),
+            Expression::UnaryOperator(unary) => {
+                if let (
+                    UnaryOperator::PreIncrement
+                    | UnaryOperator::PostIncrement
+                    | UnaryOperator::PreDecrement
+                    | UnaryOperator::PostDecrement
+                    | UnaryOperator::Address,
+                    Expression::Identifier(identifier),
+                ) = (&unary.node.operator.node, &unary.node.operand.node)
+                {
+                    let _unused = self.modified.insert(identifier.node.name.as_str());
+                }
+            }
+            Expression::BinaryOperator(binary) if is_assignment(&binary.node.operator.node) => {
+                if let Expression::Identifier(identifier) = &binary.node.lhs.node {
+                    let _unused = self.modified.insert(identifier.node.name.as_str());
+                }
+            }
+            _ => (),
+        }
+
+        for operand in operands(expr) {
+            self.expr(operand);
+        }
+    }
+
+    /// Returns the initializers of the variables declared once, never modified, and initialized
+    /// without side effects by the variables declared once and never modified, which therefore
+    /// always hold the values of their initializers.
+    fn aliases(&self) -> HashMap<&'a str, &'a Expression> {
+        let is_unmodified =
+            |name: &str| self.declarations.get(name) == Some(&1) && !self.modified.contains(name);
+        self.initializers
+            .iter()
+            .filter(|(name, initializer)| {
+                is_unmodified(name)
+                    && !has_side_effects(initializer)
+                    && identifiers(initializer).all(is_unmodified)
+            })
+            .map(|(name, initializer)| (*name, *initializer))
+            .collect()
+    }
+}
+
+/// Returns the expressions of `initializer`.
+fn initializer_exprs(initializer: &Initializer) -> Vec<&Expression> {
+    match initializer {
+        Initializer::Expression(expr) => vec![&expr.node],
+        Initializer::List(items) => items
+            .iter()
+            .flat_map(|item| initializer_exprs(&item.node.initializer.node))
+            .collect(),
+    }
+}
+
+/// Returns the operands of `expr` it evaluates.
+fn operands(expr: &Expression) -> Vec<&Expression> {
+    match expr {
+        Expression::Member(member) => vec![&member.node.expression.node],
+        Expression::Call(call) => std::iter::once(call.node.callee.as_ref())
+            .chain(&call.node.arguments)
+            .map(|operand| &operand.node)
+            .collect(),
+        Expression::CompoundLiteral(literal) => literal
+            .node
+            .initializer_list
+            .iter()
+            .flat_map(|item| initializer_exprs(&item.node.initializer.node))
+            .collect(),
+        Expression::UnaryOperator(unary) => vec![&unary.node.operand.node],
+        Expression::Cast(cast) => vec![&cast.node.expression.node],
+        Expression::BinaryOperator(binary) => vec![&binary.node.lhs.node, &binary.node.rhs.node],
+        Expression::Conditional(conditional) => vec![
+            &conditional.node.condition.node,
+            &conditional.node.then_expression.node,
+            &conditional.node.else_expression.node,
+        ],
+        Expression::Comma(exprs) => exprs.iter().map(|expr| &expr.node).collect(),
+        // The operands of `sizeof` and `_Alignof` are not evaluated.
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the names of the variables `expr` reads.
+fn identifiers(expr: &Expression) -> impl Iterator<Item = &str> {
+    let mut names = Vec::new();
+    let mut exprs = vec![expr];
+    while let Some(expr) = exprs.pop() {
+        if let Expression::Identifier(identifier) = expr {
+            names.push(identifier.node.name.as_str());
+        }
+        exprs.extend(operands(expr));
+    }
+    names.into_iter()
+}
+
+/// Returns `true` if evaluating `expr` may have side effects, or different values each time.
+fn has_side_effects(expr: &Expression) -> bool {
+    let has_side_effect = match expr {
+        Expression::Call(_) => true,
+        Expression::UnaryOperator(unary) => matches!(
+            unary.node.operator.node,
+            UnaryOperator::PreIncrement
+                | UnaryOperator::PostIncrement
+                | UnaryOperator::PreDecrement
+                | UnaryOperator::PostDecrement
+        ),
+        Expression::BinaryOperator(binary) => is_assignment(&binary.node.operator.node),
+        _ => false,
+    };
+    has_side_effect || operands(expr).into_iter().any(has_side_effects)
+}
+
+/// Returns `true` if `lhs` and `rhs` evaluate to the same pointer, other than a null pointer.
+fn is_same_pointer(
+    lhs: &Expression,
+    rhs: &Expression,
+    aliases: &HashMap<&str, &Expression>,
+) -> bool {
+    if has_side_effects(lhs) || has_side_effects(rhs) {
+        return false;
+    }
+    let lhs = origin(lhs, aliases);
+    !matches!(lhs, Expression::Constant(_)) && lhs.is_equiv(origin(rhs, aliases))
+}
+
+/// Returns the expression `expr` evaluates to the same pointer as, looking through the casts,
+/// `&a[0]` for `a`, and the variables in `aliases` for their initializers.
+fn origin<'a>(mut expr: &'a Expression, aliases: &HashMap<&str, &'a Expression>) -> &'a Expression {
+    // The variables resolved so far, so that a variable initialized by itself is resolved once.
+    let mut resolved = HashSet::new();
+    loop {
+        let next = match expr {
+            Expression::Cast(cast) => Some(&cast.node.expression.node),
+            Expression::Identifier(identifier) => {
+                let name = identifier.node.name.as_str();
+                aliases.get(name).filter(|_| resolved.insert(name)).copied()
+            }
+            Expression::UnaryOperator(unary)
+                if unary.node.operator.node == UnaryOperator::Address =>
+            {
+                match &unary.node.operand.node {
+                    Expression::BinaryOperator(binary)
+                        if binary.node.operator.node == BinaryOperator::Index
+                            && is_zero(&binary.node.rhs.node) =>
+                    {
+                        Some(&binary.node.lhs.node)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        expr = some_or!(next, return expr);
+    }
+}
+
+fn is_zero(expr: &Expression) -> bool {
+    match expr {
+        Expression::Constant(constant) => match &constant.node {
+            Constant::Integer(integer) => integer.number.trim_start_matches('0').is_empty(),
+            _ => false,
+        },
+        _ => false,
+    }
+}
@@ -0,0 +1,333 @@
+//! Warn about the unsequenced modifications of a variable, e.g., `i++ + i++`, `f(i++, i++)` or
+//! `a[i] = i++`, whose behavior is undefined (see C17 6.5p2).
+//!
+//! Only the variables designated by their names are tracked, since whether two other lvalues,
+//! e.g., `*p` and `a[0]`, designate the same object is not known syntactically. The operands of
+//! `&&`, `||`, `,` and `?:` are sequenced, as well as the items of an initializer list with
+//! respect to each other, so that only the operands of the other operators and the arguments of a
+//! call are checked against each other. An assignment also conflicts with a modification of its
+//! target in its right operand unless a sequence point completes it first, e.g., `i = (i++, i)`.
+
+use core::{fmt, iter};
+
+use lang_c::ast::*;
+use lang_c::span::{Node, Span};
+
+use crate::c::name_of_declarator;
+
+/// A variable modified in a full expression without being sequenced with another access to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceWarning {
+    /// The function containing the full expression
+    pub function: String,
+    pub name: String,
+    /// The span of the full expression
+    pub span: Span,
+}
+
+impl fmt::Display for SequenceWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: warning: operation on `{}` may be undefined",
+            self.function, self.name
+        )
+    }
+}
+
+/// Returns the warnings for the unsequenced modifications in the function bodies of `unit`.
+pub fn check_sequence_points(unit: &TranslationUnit) -> Vec<SequenceWarning> {
+    let mut checker = Checker {
+        function: String::new(),
+        span: Span::none(),
+        warnings: Vec::new(),
+    };
+    for decl in &unit.0 {
+        if let ExternalDeclaration::FunctionDefinition(definition) = &decl.node {
+            checker.function = name_of_declarator(&definition.node.declarator.node);
+            checker.check_stmt(&definition.node.statement.node);
+        }
+    }
+
+    checker.warnings
+}
+
+/// The variables an expression reads and modifies.
+#[derive(Debug, Default)]
+struct Accesses {
+    read: Vec<String>,
+    modified: Vec<String>,
+    /// The modified variables whose side effects may not be complete when the value of the
+    /// expression is computed, e.g., `i` in `i++ + 1` but not in `(i++, 1)` or `f(i++)`
+    pending: Vec<String>,
+}
+
+impl Accesses {
+    fn extend(&mut self, other: Self) {
+        self.read.extend(other.read);
+        self.modified.extend(other.modified);
+        self.pending.extend(other.pending);
+    }
+
+    /// Returns the accesses whose side effects are complete, as after a sequence point.
+    fn complete(mut self) -> Self {
+        self.pending.clear();
+        self
+    }
+
+    /// Returns the variables modified by one of `self` and `other` and accessed by the other.
+    fn conflicts<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = &'a String> {
+        let modified_by_self = self
+            .modified
+            .iter()
+            .filter(|name| other.read.contains(name) || other.modified.contains(name));
+        let modified_by_other = other
+            .modified
+            .iter()
+            .filter(|name| self.read.contains(name));
+        modified_by_self.chain(modified_by_other)
+    }
+}
+
+struct Checker {
+    function: String,
+    /// The span of the current full expression
+    span: Span,
+    warnings: Vec<SequenceWarning>,
+}
+
+impl Checker {
+    fn check_stmt(&mut self, stmt: &Statement) {
+        match stmt {
+            Statement::Labeled(stmt) => self.check_stmt(&stmt.node.statement.node),
+            Statement::Compound(items) => {
+                for item in items {
+                    match &item.node {
+                        BlockItem::Declaration(decl) => self.check_declaration(&decl.node),
+                        BlockItem::StaticAssert(_) => (),
+                        BlockItem::Statement(stmt) => self.check_stmt(&stmt.node),
+                    }
+                }
+            }
+            Statement::Expression(expr) | Statement::Return(expr) => {
+                if let Some(expr) = expr {
+                    self.check_full_expr(expr);
+                }
+            }
+            Statement::If(stmt) => {
+                self.check_full_expr(&stmt.node.condition);
+                self.check_stmt(&stmt.node.then_statement.node);
+                if let Some(stmt) = &stmt.node.else_statement {
+                    self.check_stmt(&stmt.node);
+                }
+            }
+            Statement::Switch(stmt) => {
+                self.check_full_expr(&stmt.node.expression);
+                self.check_stmt(&stmt.node.statement.node);
+            }
+            Statement::While(stmt) => {
+                self.check_full_expr(&stmt.node.expression);
+                self.check_stmt(&stmt.node.statement.node);
+            }
+            Statement::DoWhile(stmt) => {
+                self.check_stmt(&stmt.node.statement.node);
+                self.check_full_expr(&stmt.node.expression);
+            }
+            Statement::For(stmt) => {
+                match &stmt.node.initializer.node {
+                    ForInitializer::Expression(expr) => self.check_full_expr(expr),
+                    ForInitializer::Declaration(decl) => self.check_declaration(&decl.node),
+                    ForInitializer::Empty | ForInitializer::StaticAssert(_) => (),
+                }
+                for expr in [&stmt.node.condition, &stmt.node.step]
+                    .into_iter()
+                    .flatten()
+                {
+                    self.check_full_expr(expr);
+                }
+                self.check_stmt(&stmt.node.statement.node);
+            }
+            Statement::Goto(_) | Statement::Continue | Statement::Break | Statement::Asm(_) => (),
+        }
+    }
+
+    fn check_declaration(&mut self, decl: &Declaration) {
+        for init_decl in &decl.declarators {
+            if let Some(initializer) = &init_decl.node.initializer {
+                self.span = initializer.span;
+                let _unused = self.initializer(&initializer.node);
+            }
+        }
+    }
+
+    fn check_full_expr(&mut self, expr: &Node<Expression>) {
+        self.span = expr.span;
+        let _unused = self.accesses(&expr.node);
+    }
+
+    fn initializer(&mut self, initializer: &Initializer) -> Accesses {
+        match initializer {
+            Initializer::Expression(expr) => self.accesses(&expr.node),
+            Initializer::List(items) => self.initializer_list(items),
+        }
+    }
+
+    fn initializer_list(&mut self, items: &[Node<InitializerListItem>]) -> Accesses {
+        let mut accesses = Accesses::default();
+        for item in items {
+            accesses.extend(self.initializer(&item.node.initializer.node));
+        }
+        accesses
+    }
+
+    /// Returns the variables `expr` accesses, warning about those its unsequenced operands
+    /// conflict on.
+    fn accesses(&mut self, expr: &Expression) -> Accesses {
+        match expr {
+            Expression::Identifier(identifier) => Accesses {
+                read: vec![identifier.node.name.clone()],
+                ..Accesses::default()
+            },
+            Expression::Member(member) => self.accesses(&member.node.expression.node),
+            Expression::Call(call) => {
+                let operands = iter::once(call.node.callee.as_ref())
+                    .chain(&call.node.arguments)
+                    .map(|operand| &operand.node)
+                    .collect::<Vec<_>>();
+                // There is a sequence point before the call.
+                self.unsequenced(&operands).complete()
+            }
+            Expression::CompoundLiteral(literal) => {
+                self.initializer_list(&literal.node.initializer_list)
+            }
+            Expression::UnaryOperator(unary) => {
+                let operand = &unary.node.operand.node;
+                match (&unary.node.operator.node, operand) {
+                    (
+                        UnaryOperator::PreIncrement
+                        | UnaryOperator::PostIncrement
+                        | UnaryOperator::PreDecrement
+                        | UnaryOperator::PostDecrement,
+                        Expression::Identifier(identifier),
+                    ) => Accesses {
+                        read: Vec::new(),
+                        modified: vec![identifier.node.name.clone()],
+                        pending: vec![identifier.node.name.clone()],
+                    },
+                    // Taking the address of a variable does not read it.
+                    (UnaryOperator::Address, Expression::Identifier(_)) => Accesses::default(),
+                    _ => self.accesses(operand),
+                }
+            }
+            Expression::Cast(cast) => self.accesses(&cast.node.expression.node),
+            Expression::BinaryOperator(binary) => {
+                let lhs = &binary.node.lhs.node;
+                let rhs = &binary.node.rhs.node;
+                match &binary.node.operator.node {
+                    BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr => {
+                        let mut accesses = self.accesses(lhs).complete();
+                        accesses.extend(self.accesses(rhs));
+                        accesses
+                    }
+                    operator if is_assignment(operator) => self.assignment(lhs, rhs),
+                    _ => self.unsequenced(&[lhs, rhs]),
+                }
+            }
+            Expression::Conditional(conditional) => {
+                let mut accesses = self.accesses(&conditional.node.condition.node).complete();
+                accesses.extend(self.accesses(&conditional.node.then_expression.node));
+                accesses.extend(self.accesses(&conditional.node.else_expression.node));
+                accesses
+            }
+            Expression::Comma(exprs) => {
+                let mut accesses = Accesses::default();
+                for expr in exprs {
+                    accesses = accesses.complete();
+                    accesses.extend(self.accesses(&expr.node));
+                }
+                accesses
+            }
+            // The operands of `sizeof` and `_Alignof` are not evaluated.
+            _ => Accesses::default(),
+        }
+    }
+
+    /// Returns the variables an assignment of `rhs` to `lhs` accesses. The store is sequenced
+    /// after the values of both operands are computed, but neither with each other nor with their
+    /// pending side effects.
+    fn assignment(&mut self, lhs: &Expression, rhs: &Expression) -> Accesses {
+        let (target, mut accesses) = if let Expression::Identifier(identifier) = lhs {
+            (Some(identifier.node.name.clone()), Accesses::default())
+        } else {
+            (None, self.accesses(lhs))
+        };
+        let rhs = self.accesses(rhs);
+        self.warn(accesses.conflicts(&rhs).cloned().collect());
+        if let Some(target) = target {
+            if rhs.pending.contains(&target) {
+                self.warn(vec![target.clone()]);
+            }
+            accesses.modified.push(target.clone());
+            accesses.pending.push(target);
+        }
+
+        accesses.extend(rhs);
+        accesses
+    }
+
+    /// Returns the variables the unsequenced `operands` access.
+    fn unsequenced(&mut self, operands: &[&Expression]) -> Accesses {
+        let operands = operands
+            .iter()
+            .map(|operand| self.accesses(operand))
+            .collect::<Vec<_>>();
+
+        let mut conflicts = Vec::new();
+        for (i, lhs) in operands.iter().enumerate() {
+            for rhs in &operands[i + 1..] {
+                conflicts.extend(lhs.conflicts(rhs).cloned());
+            }
+        }
+        self.warn(conflicts);
+
+        let mut accesses = Accesses::default();
+        for operand in operands {
+            accesses.extend(operand);
+        }
+        accesses
+    }
+
+    /// Warns about the modifications of `names` in the current full expression, once per name.
+    fn warn(&mut self, names: Vec<String>) {
+        for name in names {
+            let is_warned = self
+                .warnings
+                .iter()
+                .any(|warning| warning.span == self.span && warning.name == name);
+            if !is_warned {
+                self.warnings.push(SequenceWarning {
+                    function: self.function.clone(),
+                    name,
+                    span: self.span,
+                });
+            }
+        }
+    }
+}
+
+pub(super) fn is_assignment(operator: &BinaryOperator) -> bool {
+    matches!(
+        operator,
+        BinaryOperator::Assign
+            | BinaryOperator::AssignMultiply
+            | BinaryOperator::AssignDivide
+            | BinaryOperator::AssignModulo
+            | BinaryOperator::AssignPlus
+            | BinaryOperator::AssignMinus
+            | BinaryOperator::AssignShiftLeft
+            | BinaryOperator::AssignShiftRight
+            | BinaryOperator::AssignBitwiseAnd
+            | BinaryOperator::AssignBitwiseXor
+            | BinaryOperator::AssignBitwiseOr
+    )
+}
@@ -1,11 +1,19 @@
 use lang_c::ast::*;
 use lang_c::span::Node;
 
+use core::cell::Cell;
 use core::ops::Deref;
-use std::io::{Result, Write};
+use std::io::{Error, ErrorKind, Result, Write};
 
+use crate::max_nesting_depth;
 use crate::write_base::*;
 
+thread_local! {
+    /// The number of expressions being written, and whether an expression was nested too deeply to
+    /// be written since the translation unit started to be written.
+    static EXPRESSION_DEPTH: Cell<(usize, bool)> = const { Cell::new((0, false)) };
+}
+
 impl<T: WriteLine> WriteLine for Node<T> {
     fn write_line(&self, indent: usize, write: &mut dyn Write) -> Result<()> {
         self.node.write_line(indent, write)
@@ -240,122 +248,155 @@ impl WriteString for UnaryOperatorExpression {
 
 impl WriteString for Expression {
     fn write_string(&self) -> String {
-        match self {
-            Expression::Identifier(ident) => ident.node.name.clone(),
-            Expression::Constant(cst) => match &cst.node {
-                Constant::Integer(it) => {
-                    let s: String = match &it.base {
-                        IntegerBase::Decimal => {
-                            let str_slice: &str = &it.number;
-                            format!("{}", str_slice)
-                        }
-                        IntegerBase::Octal => {
-                            let str_slice: &str = &it.number;
-                            format!("{}", str_slice)
-                        }
-                        IntegerBase::Hexadecimal => {
-                            let str_slice: &str = &it.number;
-                            format!("0x{}", str_slice)
-                        }
-                        IntegerBase::Binary => {
-                            let str_slice: &str = &it.number;
-                            format!("{}", str_slice)
-                        }
-                    };
-                    match &it.suffix.size {
-                        IntegerSize::Int => s,
-                        IntegerSize::Long => {
-                            format!("{}L", s)
-                        }
-                        IntegerSize::LongLong => {
-                            format!("{}LL", s)
-                        }
+        // Expressions nested too deeply are skipped instead of overflowing the stack, and reported
+        // by the writer of the translation unit.
+        let (depth, too_deep) = EXPRESSION_DEPTH.with(Cell::get);
+        if too_deep || depth >= max_nesting_depth() {
+            EXPRESSION_DEPTH.with(|state| state.set((depth, true)));
+            return String::new();
+        }
+
+        EXPRESSION_DEPTH.with(|state| state.set((depth + 1, false)));
+        let string = write_expression(self);
+        EXPRESSION_DEPTH.with(|state| {
+            let (_, too_deep) = state.get();
+            state.set((depth, too_deep));
+        });
+        string
+    }
+}
+
+fn write_expression(expression: &Expression) -> String {
+    match expression {
+        Expression::Identifier(ident) => ident.node.name.clone(),
+        Expression::Constant(cst) => match &cst.node {
+            Constant::Integer(it) => {
+                let s: String = match &it.base {
+                    IntegerBase::Decimal => {
+                        let str_slice: &str = &it.number;
+                        format!("{}", str_slice)
                     }
-                }
-                Constant::Float(float) => match &float.base {
-                    FloatBase::Decimal => {
-                        let str_slice: &str = &float.number;
-                        str_slice.parse::<f64>().expect("error").to_string()
+                    IntegerBase::Octal => {
+                        let str_slice: &str = &it.number;
+                        format!("{}", str_slice)
                     }
-                    FloatBase::Hexadecimal => {
-                        let str_slice: &str = &float.number;
-                        let float_value = str_slice.parse::<f64>().expect("error");
-                        let integer_part = float_value.floor() as i64;
-                        let fractional_part = float_value - integer_part as f64;
-                        let hex_integer_part = format!("{:X}", integer_part);
-                        let fractional_part_hex = (fractional_part * 1_000_000.0).round() as i64;
-                        let hex_fractional_part = format!("{:X}", fractional_part_hex);
-                        format!("{}.{}", hex_integer_part, hex_fractional_part)
+                    IntegerBase::Hexadecimal => {
+                        let str_slice: &str = &it.number;
+                        format!("0x{}", str_slice)
+                    }
+                    IntegerBase::Binary => {
+                        let str_slice: &str = &it.number;
+                        format!("{}", str_slice)
+                    }
+                };
+                match &it.suffix.size {
+                    IntegerSize::Int => s,
+                    IntegerSize::Long => {
+                        format!("{}L", s)
+                    }
+                    IntegerSize::LongLong => {
+                        format!("{}LL", s)
                     }
-                },
-                Constant::Character(str) => {
-                    format!("{}", str.as_str())
                 }
-            },
-            Expression::StringLiteral(_) => todo!(),
-            Expression::GenericSelection(_) => todo!(),
-            Expression::Member(mem) => match mem.node.operator.node {
-                MemberOperator::Direct => {
-                    format!(
-                        "{}.{}",
-                        mem.node.expression.node.write_string(),
-                        mem.node.identifier.node.name
-                    )
+            }
+            Constant::Float(float) => match &float.base {
+                FloatBase::Decimal => {
+                    let str_slice: &str = &float.number;
+                    str_slice.parse::<f64>().expect("error").to_string()
                 }
-                MemberOperator::Indirect => todo!(),
-            },
-            Expression::Call(exp) => {
-                let mut s: Vec<String> = Vec::new();
-                for v in &exp.node.arguments {
-                    s.push(v.node.write_string());
+                FloatBase::Hexadecimal => {
+                    let str_slice: &str = &float.number;
+                    let float_value = str_slice.parse::<f64>().expect("error");
+                    let integer_part = float_value.floor() as i64;
+                    let fractional_part = float_value - integer_part as f64;
+                    let hex_integer_part = format!("{:X}", integer_part);
+                    let fractional_part_hex = (fractional_part * 1_000_000.0).round() as i64;
+                    let hex_fractional_part = format!("{:X}", fractional_part_hex);
+                    format!("{}.{}", hex_integer_part, hex_fractional_part)
                 }
-                format!("{}({})", exp.node.callee.write_string(), s.join(","))
-            }
-            Expression::CompoundLiteral(_) => todo!(),
-            Expression::SizeOfTy(_) => todo!(),
-            Expression::SizeOfVal(_) => todo!(),
-            Expression::AlignOf(ali) => {
-                format!("_Alignof({})", ali.node.0.node.write_string())
+            },
+            Constant::Character(str) => {
+                format!("{}", str.as_str())
             }
-            Expression::UnaryOperator(u_op) => u_op.node.write_string(),
-            Expression::Cast(cast) => {
+        },
+        Expression::StringLiteral(literal) => unsupported("string literal", Some(literal.span)),
+        Expression::GenericSelection(selection) => {
+            unsupported("generic selection", Some(selection.span))
+        }
+        Expression::Member(mem) => match mem.node.operator.node {
+            MemberOperator::Direct => {
                 format!(
-                    "({}){}",
-                    cast.node.type_name.node.write_string(),
-                    cast.node.expression.node.write_string()
+                    "{}.{}",
+                    mem.node.expression.node.write_string(),
+                    mem.node.identifier.node.name
                 )
             }
-            Expression::BinaryOperator(b_op) => b_op.node.write_string(),
-            Expression::Conditional(cond) => {
-                format!(
-                    "({} ? {} : {})",
-                    cond.node.condition.node.write_string(),
-                    cond.node.then_expression.node.write_string(),
-                    cond.node.else_expression.node.write_string()
-                )
+            MemberOperator::Indirect => unsupported("`->` member access", Some(mem.span)),
+        },
+        Expression::Call(exp) => {
+            let mut s: Vec<String> = Vec::new();
+            for v in &exp.node.arguments {
+                s.push(v.node.write_string());
             }
-            Expression::Comma(exp) => {
-                let mut s: Vec<String> = Vec::new();
-                let vec: &Vec<Node<Expression>> = &*exp;
-                for p in vec {
-                    s.push(p.node.write_string());
-                }
-                format!("({})", s.join(", "))
+            format!("{}({})", exp.node.callee.write_string(), s.join(","))
+        }
+        Expression::CompoundLiteral(literal) => unsupported("compound literal", Some(literal.span)),
+        Expression::SizeOfTy(size_of) => unsupported("`sizeof` of a type", Some(size_of.span)),
+        Expression::SizeOfVal(size_of) => {
+            unsupported("`sizeof` of an expression", Some(size_of.span))
+        }
+        Expression::AlignOf(ali) => {
+            format!("_Alignof({})", ali.node.0.node.write_string())
+        }
+        Expression::UnaryOperator(u_op) => u_op.node.write_string(),
+        Expression::Cast(cast) => {
+            format!(
+                "({}){}",
+                cast.node.type_name.node.write_string(),
+                cast.node.expression.node.write_string()
+            )
+        }
+        Expression::BinaryOperator(b_op) => b_op.node.write_string(),
+        Expression::Conditional(cond) => {
+            format!(
+                "({} ? {} : {})",
+                cond.node.condition.node.write_string(),
+                cond.node.then_expression.node.write_string(),
+                cond.node.else_expression.node.write_string()
+            )
+        }
+        Expression::Comma(exp) => {
+            let mut s: Vec<String> = Vec::new();
+            let vec: &Vec<Node<Expression>> = &*exp;
+            for p in vec {
+                s.push(p.node.write_string());
             }
-            Expression::OffsetOf(_) => todo!(),
-            Expression::VaArg(_) => todo!(),
-            Expression::Statement(_) => todo!(),
+            format!("({})", s.join(", "))
         }
+        Expression::OffsetOf(offset_of) => unsupported("`offsetof`", Some(offset_of.span)),
+        Expression::VaArg(va_arg) => unsupported("`va_arg`", Some(va_arg.span)),
+        Expression::Statement(stmt) => unsupported("statement expression", Some(stmt.span)),
     }
 }
 
 impl WriteLine for TranslationUnit {
     fn write_line(&self, _indent: usize, _write: &mut dyn Write) -> Result<()> {
+        EXPRESSION_DEPTH.with(|state| state.set((0, false)));
         for v in &self.0 {
             v.node.write_line(_indent, _write)?;
             writeln!(_write)?;
         }
 
+        if EXPRESSION_DEPTH.with(Cell::get).1 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "expression is too complex: nested more than {} levels",
+                    max_nesting_depth()
+                ),
+            ));
+        }
+
         Ok(())
     }
 }
@@ -365,7 +406,10 @@ impl WriteString for SpecifierQualifier {
         match self {
             SpecifierQualifier::TypeSpecifier(spec) => spec.node.write_string(),
             SpecifierQualifier::TypeQualifier(spec) => spec.node.write_string(),
-            SpecifierQualifier::Extension(_) => todo!(),
+            SpecifierQualifier::Extension(extensions) => unsupported(
+                "extension",
+                extensions.first().map(|extension| extension.span),
+            ),
         }
     }
 }
@@ -491,15 +535,27 @@ impl WriteString for Label {
 impl WriteString for DeclarationSpecifier {
     fn write_string(&self) -> String {
         match self {
-            DeclarationSpecifier::Alignment(_) => todo!(),
-            DeclarationSpecifier::StorageClass(_) => todo!(),
+            DeclarationSpecifier::Alignment(alignment) => match &alignment.node {
+                AlignmentSpecifier::Type(type_name) => {
+                    format!("_Alignas({})", type_name.node.write_string())
+                }
+                AlignmentSpecifier::Constant(expr) => {
+                    format!("_Alignas({})", expr.node.write_string())
+                }
+            },
+            DeclarationSpecifier::StorageClass(storage) => {
+                unsupported("storage class specifier", Some(storage.span))
+            }
             DeclarationSpecifier::TypeSpecifier(spec) => spec.node.write_string(),
             DeclarationSpecifier::TypeQualifier(qlf) => qlf.node.write_string(),
             DeclarationSpecifier::Function(spec) => match spec.node {
                 FunctionSpecifier::Inline => "__inline__".to_string(),
                 FunctionSpecifier::Noreturn => "_Noreturn".to_string(),
             },
-            DeclarationSpecifier::Extension(_) => todo!(),
+            DeclarationSpecifier::Extension(extensions) => unsupported(
+                "extension",
+                extensions.first().map(|extension| extension.span),
+            ),
         }
     }
 }
@@ -543,7 +599,9 @@ impl WriteString for TypeSpecifier {
                                 }
                                 s = format!("{{{}}}", str);
                             }
-                            StructDeclaration::StaticAssert(_) => todo!(),
+                            StructDeclaration::StaticAssert(assert) => {
+                                s.push_str(&unsupported("`_Static_assert`", Some(assert.span)))
+                            }
                         }
                     }
                 }
@@ -576,7 +634,8 @@ impl WriteString for DeclaratorKind {
         match self {
             DeclaratorKind::Abstract => "".to_string(),
             DeclaratorKind::Identifier(ident) => ident.node.name.to_string(),
-            DeclaratorKind::Declarator(decl) => decl.node.write_string(),
+            // E.g., `(*)` of `int (*)[4]`, whose derived declarators bind before the outer ones.
+            DeclaratorKind::Declarator(decl) => format!("({})", decl.node.write_string()),
         }
     }
 }
@@ -584,7 +643,10 @@ impl WriteString for PointerQualifier {
     fn write_string(&self) -> String {
         match self {
             PointerQualifier::TypeQualifier(qls) => qls.node.write_string(),
-            PointerQualifier::Extension(_) => todo!(),
+            PointerQualifier::Extension(extensions) => unsupported(
+                "extension",
+                extensions.first().map(|extension| extension.span),
+            ),
         }
     }
 }
@@ -616,9 +678,11 @@ impl WriteString for Declarator {
         for v in &self.derived {
             match &v.node {
                 DerivedDeclarator::Pointer(qlf) => {
+                    // The qualifiers of a pointer follow its asterisk, e.g., `*const p`.
                     s.push('*');
                     for p in qlf {
-                        str.push_str(p.node.write_string().as_str())
+                        s.push_str(p.node.write_string().as_str());
+                        s.push(' ');
                     }
                 }
                 DerivedDeclarator::Array(decl) => {
@@ -654,7 +718,9 @@ impl WriteString for Declarator {
                     }
                     str.push_str(&format!("({})", v));
                 }
-                DerivedDeclarator::Block(_) => todo!(),
+                DerivedDeclarator::Block(_) => {
+                    str.push_str(&unsupported("block pointer", Some(v.span)))
+                }
             }
         }
         s.push_str(self.kind.node.write_string().as_str());
@@ -673,7 +739,14 @@ impl WriteLine for BlockItem {
         match self {
             Self::Declaration(de) => de.write_line(indent, write),
             Self::Statement(fd) => fd.write_line(indent, write),
-            Self::StaticAssert(_) => panic!(),
+            Self::StaticAssert(assert) => {
+                write_indent(indent, write)?;
+                writeln!(
+                    write,
+                    "{}",
+                    unsupported("`_Static_assert`", Some(assert.span))
+                )
+            }
         }
     }
 }
@@ -683,7 +756,14 @@ impl WriteLine for ExternalDeclaration {
         match self {
             Self::Declaration(de) => de.write_line(indent, write),
             Self::FunctionDefinition(fd) => fd.write_line(indent, write),
-            Self::StaticAssert(_) => panic!(),
+            Self::StaticAssert(assert) => {
+                write_indent(indent, write)?;
+                writeln!(
+                    write,
+                    "{}",
+                    unsupported("`_Static_assert`", Some(assert.span))
+                )
+            }
         }
     }
 }
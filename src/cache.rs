@@ -0,0 +1,279 @@
+//! Incremental recompilation cache of the optimized IR of function definitions.
+
+use core::fmt;
+use core::mem;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use lang_c::ast;
+
+use crate::c::name_of_declarator;
+use crate::write_base::{WriteLine, WriteOpts, WriteString};
+use crate::*;
+
+/// Functions reused from and stored into the cache.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Functions whose optimized IR is reused from the cache
+    pub hits: usize,
+    /// Functions lowered and optimized anew, then stored into the cache
+    pub misses: usize,
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cache: {} hits, {} misses", self.hits, self.misses)
+    }
+}
+
+/// Cache of the optimized IR of each function definition, stored in a directory.
+///
+/// A function is keyed on its source, the external declarations of the translation unit without
+/// function bodies, the build of the compiler, and a context identifying everything else its
+/// optimized IR depends on, e.g., the [`CompileOptions`] and the passes to run. Editing a function
+/// body thus invalidates only the function, while editing a global variable, a struct, a typedef
+/// or a function signature invalidates every function, as it may change how their bodies are
+/// lowered. The keys are hashed with a hash stable across builds and hosts.
+///
+/// A compilation calls [`Cache::skip_cached`], so that the IR generator only lowers the functions
+/// missing from the cache, optimizes the result with function passes, and then calls [`Cache::complete`], which stores the
+/// missing functions into the cache and adds the cached ones. Interprocedural passes, e.g.,
+/// [`DeadFunction`], must run after [`Cache::complete`], as they need every function.
+#[derive(Debug)]
+pub struct Cache {
+    dir: PathBuf,
+    context: String,
+    /// Whether every function is keyed on the bodies of all the functions, see
+    /// [`Cache::with_unit_keys`]
+    unit_keys: bool,
+    /// The key of each function defined in the unit being compiled, if its source can be written
+    keys: BTreeMap<String, Option<String>>,
+    /// The cached entries of the functions of the unit being compiled
+    hits: BTreeMap<String, ir::TranslationUnit>,
+    stats: CacheStats,
+}
+
+impl Cache {
+    /// Opens the cache in `dir`, creating the directory if needed.
+    pub fn new(dir: &Path, context: String) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            context,
+            unit_keys: false,
+            keys: BTreeMap::new(),
+            hits: BTreeMap::new(),
+            stats: CacheStats::default(),
+        })
+    }
+
+    /// Keys every function on the bodies of all the functions of the unit, e.g., when the lowering
+    /// numbers the statements across the unit for [`StatementCoverage`]. The functions are then
+    /// reused only if the whole unit is unchanged.
+    pub fn with_unit_keys(mut self) -> Self {
+        self.unit_keys = true;
+        self
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Looks up the functions defined in `source` in the cache, and returns `irgen` set to only
+    /// declare those found, so that translating `source` by it lowers the missing functions only.
+    pub fn skip_cached(&mut self, source: &ast::TranslationUnit, irgen: Irgen) -> Irgen {
+        self.keys = keys(source, &self.context, self.unit_keys);
+        let hits = self
+            .keys
+            .iter()
+            .filter_map(|(name, key)| Some((name.clone(), self.load(name, key.as_ref()?)?)))
+            .collect::<BTreeMap<_, _>>();
+        self.hits = hits;
+
+        self.stats.hits += self.hits.len();
+        self.stats.misses += self.keys.len() - self.hits.len();
+
+        irgen.skip_definitions(self.hits.keys().cloned().collect())
+    }
+
+    /// Stores the optimized definitions of the functions missing from the cache, and adds the
+    /// cached definitions to `unit`.
+    ///
+    /// The declarations of a cached entry missing from `unit` are added too, e.g., the counters of
+    /// [`StatementCoverage`], which are only declared once their statements are lowered.
+    pub fn complete(&mut self, unit: &mut ir::TranslationUnit) -> io::Result<()> {
+        for (name, key) in &self.keys {
+            let key = some_or!(key, continue);
+            if !self.hits.contains_key(name) {
+                self.store(unit, name, key)?;
+            }
+        }
+
+        for (name, mut entry) in mem::take(&mut self.hits) {
+            let cached = entry
+                .decls
+                .remove(&name)
+                .and_then(|decl| decl.get_function()?.1.clone());
+            if let Some(ir::Declaration::Function { definition, .. }) = unit.decls.get_mut(&name) {
+                *definition = cached;
+            }
+            for (other, decl) in entry.decls {
+                let _unused = unit.decls.entry(other).or_insert(decl);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.ir"))
+    }
+
+    /// Returns the cached entry of `name`, if it defines `name`.
+    ///
+    /// An unreadable entry, e.g., one written by a concurrent compilation, is a miss.
+    fn load(&self, name: &str, key: &str) -> Option<ir::TranslationUnit> {
+        let path = self.path(key);
+        if !path.is_file() {
+            return None;
+        }
+
+        let unit = IrParse::default().translate(&path).ok()?;
+        let (_, definition) = unit.decls.get(name)?.get_function()?;
+        definition.as_ref()?;
+        Some(unit)
+    }
+
+    /// Stores the definition of `name` in `unit`, if any.
+    ///
+    /// The entry is a translation unit defining only `name`, so that it can be parsed on its own.
+    fn store(&self, unit: &ir::TranslationUnit, name: &str, key: &str) -> io::Result<()> {
+        let decls = unit
+            .decls
+            .iter()
+            .map(|(other, decl)| {
+                let mut decl = decl.clone();
+                if other != name {
+                    if let ir::Declaration::Function { definition, .. } = &mut decl {
+                        *definition = None;
+                    }
+                }
+                (other.clone(), decl)
+            })
+            .collect();
+        let entry = ir::TranslationUnit {
+            decls,
+            structs: unit.structs.clone(),
+        };
+
+        let mut buffer = Vec::new();
+        write(&entry, &mut buffer)?;
+        fs::write(self.path(key), buffer)
+    }
+}
+
+/// Returns the key of each function defined in `source`, or `None` for the functions whose source
+/// cannot be written, which are always lowered anew. With `unit_keys`, every key covers the bodies
+/// of all the functions.
+fn keys(
+    source: &ast::TranslationUnit,
+    context: &str,
+    unit_keys: bool,
+) -> BTreeMap<String, Option<String>> {
+    let opts = WriteOpts::default();
+    let functions = source
+        .0
+        .iter()
+        .filter_map(|ext_decl| match &ext_decl.node {
+            ast::ExternalDeclaration::FunctionDefinition(func) => Some(&func.node),
+            _ => None,
+        })
+        .map(|func| {
+            let mut body = Vec::new();
+            let body = func.try_write_line(0, &mut body, &opts).ok().map(|()| body);
+            (name_of_declarator(&func.declarator.node), body)
+        })
+        .collect::<Vec<_>>();
+
+    // Everything the lowering of a function body may depend on.
+    let environment = || -> Option<Vec<u8>> {
+        let mut environment = String::new();
+        for ext_decl in &source.0 {
+            match &ext_decl.node {
+                ast::ExternalDeclaration::Declaration(decl) => {
+                    environment.push_str(&decl.node.try_write_string(&opts).ok()?)
+                }
+                ast::ExternalDeclaration::FunctionDefinition(func) => {
+                    for specifier in &func.node.specifiers {
+                        environment.push_str(&specifier.node.try_write_string(&opts).ok()?);
+                        environment.push(' ');
+                    }
+                    environment.push_str(&func.node.declarator.node.try_write_string(&opts).ok()?);
+                }
+                ast::ExternalDeclaration::StaticAssert(_) => {}
+            }
+            environment.push('\n');
+        }
+
+        let mut environment = environment.into_bytes();
+        if unit_keys {
+            for (_, body) in &functions {
+                environment.extend_from_slice(body.as_ref()?);
+            }
+        }
+        Some(environment)
+    };
+    let environment = environment();
+    let build_id = build_id();
+
+    functions
+        .iter()
+        .map(|(name, body)| {
+            let key = environment
+                .as_ref()
+                .zip(body.as_ref())
+                .map(|(environment, body)| {
+                    let hash =
+                        stable_hash(&[build_id.as_bytes(), context.as_bytes(), environment, body]);
+                    format!("{hash:032x}")
+                });
+            (name.clone(), key)
+        })
+        .collect()
+}
+
+/// Identifies the build of the compiler, so that a rebuilt compiler does not reuse the functions
+/// optimized by another build of the same version: the version and the modification time of the
+/// running executable, if known.
+fn build_id() -> String {
+    let modified = env::current_exe()
+        .and_then(fs::metadata)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |modified| modified.as_nanos());
+    format!("{} {modified}", env!("CARGO_PKG_VERSION"))
+}
+
+/// Hashes `parts` with the 128-bit FNV-1a, which unlike `DefaultHasher` is the same across Rust
+/// releases and hosts. Each part is prefixed with its length, so that the parts are not confused.
+fn stable_hash(parts: &[&[u8]]) -> u128 {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    parts
+        .iter()
+        .flat_map(|part| {
+            (part.len() as u64)
+                .to_le_bytes()
+                .into_iter()
+                .chain(part.iter().copied())
+        })
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u128::from(byte)).wrapping_mul(PRIME)
+        })
+}
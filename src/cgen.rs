@@ -0,0 +1,753 @@
+//! Portable C generated from the IR, e.g., to run programs on hosts without a RISC-V toolchain.
+
+use core::ops::Deref;
+use std::collections::HashSet;
+
+use itertools::{izip, Itertools};
+use lang_c::ast;
+use thiserror::Error;
+
+use crate::ir::{self, HasDtype};
+use crate::write_base::WriteString;
+use crate::{some_or, Translate};
+
+/// Translates IR into C compiled by gcc with the same behavior as the interpreter.
+///
+/// - Integers are fixed-width, e.g., `int32_t`, and signed arithmetic wraps around. Booleans,
+///   i.e., `u1`, are `uint8_t` holding `0` or `1`.
+/// - Each function is a C function whose blocks are labels, starting with a `goto` to the initial
+///   block. The phinodes of the initial block are the parameters, and those of the other blocks
+///   are local variables assigned by the jumps to them, along with the registers.
+/// - Each struct is a C struct with explicit padding fields, so that its layout is the one
+///   computed by [`ir::Dtype::size_align_of`], which is checked by `_Static_assert`s.
+/// - Global variables are defined with their initializers, and functions without definitions are
+///   declared, except for builtins, which gcc provides.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct Cgen {}
+
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum CgenError {
+    #[error("{func_name}:{bid}:{iid} / `{instruction}` is not supported: {reason}")]
+    UnsupportedInstruction {
+        func_name: String,
+        bid: ir::BlockId,
+        iid: usize,
+        instruction: String,
+        reason: String,
+    },
+    #[error("{func_name}:{bid} / values of `{dtype}` are not supported")]
+    UnsupportedDtype {
+        func_name: String,
+        bid: ir::BlockId,
+        dtype: ir::Dtype,
+    },
+    #[error("the initializer of `@{name}` is not supported")]
+    UnsupportedInitializer { name: String },
+}
+
+impl Translate<ir::TranslationUnit> for Cgen {
+    type Target = String;
+    type Error = CgenError;
+
+    fn translate(&mut self, source: &ir::TranslationUnit) -> Result<Self::Target, Self::Error> {
+        let mut c = "/* Generated by kecc */\n#include <stdint.h>\n".to_string();
+
+        let mut names = source.structs.keys().collect::<Vec<_>>();
+        names.sort();
+        if !names.is_empty() {
+            c.push('\n');
+        }
+        // Structs are declared first, since fields may point to structs defined later.
+        for name in &names {
+            c.push_str(&format!(
+                "{} {};\n",
+                ir::c_keyword(&source.structs, name),
+                ir::c_tag(name)
+            ));
+        }
+        let mut defined = HashSet::new();
+        for name in &names {
+            define_struct(source, name, &mut defined, &mut c);
+        }
+
+        for (name, decl) in &source.decls {
+            let (dtype, initializer) = some_or!(decl.get_variable(), continue);
+
+            let declaration = object_declaration(dtype, name);
+            let declaration = if decl.is_static() {
+                format!("static {declaration}")
+            } else {
+                declaration
+            };
+            if decl.is_extern() {
+                c.push_str(&format!("\nextern {declaration};\n"));
+                continue;
+            }
+            let initializer = some_or!(initializer.as_ref(), {
+                c.push_str(&format!("\n{declaration};\n"));
+                continue;
+            });
+            let initializer =
+                match ir::Value::try_from_initializer(initializer, dtype, &source.structs) {
+                    Ok(value) => value_initializer(&value),
+                    // E.g., string literals, which are written as is unless the layout of a struct is
+                    // involved.
+                    Err(()) if !has_struct(dtype) => initializer.write_string(),
+                    Err(()) => {
+                        return Err(CgenError::UnsupportedInitializer { name: name.clone() });
+                    }
+                };
+            c.push_str(&format!("\n{declaration} = {initializer};\n"));
+        }
+
+        c.push('\n');
+        for (name, decl) in &source.decls {
+            let (signature, _) = some_or!(decl.get_function(), continue);
+            if ir::Builtin::is_reserved(name) {
+                continue;
+            }
+
+            let params = if signature.params.is_empty() {
+                "void".to_string()
+            } else {
+                signature.params.iter().map(type_name).join(", ")
+            };
+            // The definition keeps the internal linkage of the prototype.
+            c.push_str(&format!(
+                "{}{};\n",
+                if decl.is_static() { "static " } else { "" },
+                declaration(&signature.ret, &format!("{name}({params})"))
+            ));
+        }
+
+        for (name, decl) in &source.decls {
+            let (signature, definition) = some_or!(decl.get_function(), continue);
+            let definition = some_or!(definition.as_ref(), continue);
+            define_function(name, signature, definition, &mut c)?;
+        }
+
+        Ok(c)
+    }
+}
+
+/// Appends the C definition of the struct `name` to `c`, after those of the structs it contains.
+///
+/// Padding is explicit, so that the offsets of the fields do not depend on the C compiler.
+fn define_struct(
+    unit: &ir::TranslationUnit,
+    name: &str,
+    defined: &mut HashSet<String>,
+    c: &mut String,
+) {
+    let struct_type = some_or!(unit.structs[name].as_ref(), return);
+    if !defined.insert(name.to_string()) {
+        return;
+    }
+
+    let fields = struct_type
+        .get_struct_fields()
+        .expect("`struct_type` must be struct type")
+        .as_ref()
+        .expect("`fields` must be `Some`");
+    let (size_of, align_of, offsets) = struct_type
+        .get_struct_size_align_offsets()
+        .expect("`struct_type` must be struct type")
+        .as_ref()
+        .expect("`offsets` must be `Some`");
+
+    // Contained structs must be complete, unlike pointed ones.
+    for field in fields {
+        let mut dtype = field.deref();
+        while let ir::Dtype::Array { inner, .. } = dtype {
+            dtype = inner;
+        }
+        if let ir::Dtype::Struct {
+            name: Some(inner), ..
+        } = dtype
+        {
+            define_struct(unit, inner, defined, c);
+        }
+    }
+
+    let tag = format!("{} {}", ir::c_keyword(&unit.structs, name), ir::c_tag(name));
+    let mut paddings = 0;
+    let mut padding = |size: usize, c: &mut String| {
+        if size > 0 {
+            c.push_str(&format!("  uint8_t __kecc_pad{paddings}[{size}];\n"));
+            paddings += 1;
+        }
+    };
+
+    // The members of a union overlap, and its tail padding is a member as large as the union.
+    c.push_str(&format!("\n{tag} {{\n"));
+    let mut end = 0;
+    for (i, (field, offset)) in izip!(fields, offsets).enumerate() {
+        padding(offset.saturating_sub(end), c);
+
+        // Adjacent bit-fields are packed alike by the C compiler, and unnamed ones stay unnamed
+        // since they do not align the struct.
+        if let Some(ir::BitField { width, bit_offset }) = struct_type.get_struct_bit_field(i) {
+            let name = field.name().cloned().unwrap_or_default();
+            c.push_str(&format!(
+                "  {} : {width};\n",
+                declaration(field.deref(), &name)
+            ));
+            end = end.max(offset + (bit_offset + width).div_ceil(ir::Dtype::BITS_OF_BYTE));
+            continue;
+        }
+
+        c.push_str(&format!(
+            "  {};\n",
+            object_declaration(field.deref(), &field_name(field.name(), i))
+        ));
+
+        let (field_size_of, _) = field
+            .size_align_of(&unit.structs)
+            .expect("`field` must have its size");
+        end = end.max(offset + field_size_of);
+    }
+    padding(size_of - end, c);
+    c.push_str("};\n");
+    c.push_str(&format!(
+        "_Static_assert(sizeof({tag}) == {size_of}, \"size of `{name}`\");\n\
+         _Static_assert(_Alignof({tag}) == {align_of}, \"alignment of `{name}`\");\n"
+    ));
+}
+
+/// Appends the C definition of the function `name` to `c`.
+fn define_function(
+    name: &str,
+    signature: &ir::FunctionSignature,
+    definition: &ir::FunctionDefinition,
+    c: &mut String,
+) -> Result<(), CgenError> {
+    let bid_init = definition.bid_init;
+    let params = if signature.params.is_empty() {
+        "void".to_string()
+    } else {
+        signature
+            .params
+            .iter()
+            .enumerate()
+            .map(|(aid, dtype)| declaration(dtype, &register(&ir::RegisterId::arg(bid_init, aid))))
+            .join(", ")
+    };
+    c.push_str(&format!(
+        "\n{} {{\n",
+        declaration(&signature.ret, &format!("{name}({params})"))
+    ));
+
+    for (aid, allocation) in definition.allocations.iter().enumerate() {
+        c.push_str(&format!(
+            "  {};\n",
+            object_declaration(allocation.deref(), &format!("_l{aid}"))
+        ));
+    }
+
+    // Registers are declared upfront, since jumps must not skip their declarations.
+    for (bid, block) in &definition.blocks {
+        let phinodes = block
+            .phinodes
+            .iter()
+            .enumerate()
+            .filter(|_| *bid != bid_init)
+            .map(|(aid, dtype)| (ir::RegisterId::arg(*bid, aid), dtype.deref().clone()));
+        let instructions = block
+            .instructions
+            .iter()
+            .enumerate()
+            .map(|(iid, instruction)| (ir::RegisterId::temp(*bid, iid), instruction.dtype()));
+
+        for (rid, dtype) in phinodes.chain(instructions) {
+            match dtype {
+                ir::Dtype::Unit { .. } => continue,
+                ir::Dtype::Array { .. } | ir::Dtype::Function { .. } => {
+                    return Err(CgenError::UnsupportedDtype {
+                        func_name: name.to_string(),
+                        bid: *bid,
+                        dtype,
+                    })
+                }
+                _ => c.push_str(&format!("  {};\n", declaration(&dtype, &register(&rid)))),
+            }
+        }
+    }
+    c.push_str(&format!("  goto {bid_init};\n"));
+
+    for (bid, block) in &definition.blocks {
+        c.push_str(&format!("{bid}:\n"));
+        for (iid, instruction) in block.instructions.iter().enumerate() {
+            let statement =
+                statement(instruction, &ir::RegisterId::temp(*bid, iid)).map_err(|reason| {
+                    CgenError::UnsupportedInstruction {
+                        func_name: name.to_string(),
+                        bid: *bid,
+                        iid,
+                        instruction: instruction.deref().to_string(),
+                        reason,
+                    }
+                })?;
+            if let Some(statement) = statement {
+                c.push_str(&format!("  {statement}\n"));
+            }
+        }
+        c.push_str(&format!("  {}\n", exit(&block.exit)));
+    }
+
+    c.push_str("}\n");
+    Ok(())
+}
+
+/// Returns the C statement of `instruction` whose result is `rid`, if any.
+fn statement(
+    instruction: &ir::Instruction,
+    rid: &ir::RegisterId,
+) -> Result<Option<String>, String> {
+    let value = match instruction {
+        ir::Instruction::Nop => return Ok(None),
+        ir::Instruction::BinOp {
+            op,
+            lhs,
+            rhs,
+            dtype,
+        } => binary_operation(op, lhs, rhs, dtype)?,
+        ir::Instruction::UnaryOp { op, operand, dtype } => unary_operation(op, operand, dtype)?,
+        ir::Instruction::Store { ptr, value } => {
+            return Ok(Some(format!("*{} = {};", operand(ptr), operand(value))))
+        }
+        ir::Instruction::Load { ptr } => format!("*{}", operand(ptr)),
+        ir::Instruction::Call {
+            callee,
+            args,
+            return_type,
+        } => {
+            let callee =
+                if let ir::Operand::Constant(ir::Constant::GlobalVariable { name, .. }) = callee {
+                    name.clone()
+                } else {
+                    format!("(*{})", operand(callee))
+                };
+            let call = format!("{callee}({})", args.iter().map(operand).join(", "));
+            if let ir::Dtype::Unit { .. } = return_type {
+                return Ok(Some(format!("{call};")));
+            }
+            call
+        }
+        ir::Instruction::TypeCast {
+            value,
+            target_dtype,
+        } => match target_dtype {
+            ir::Dtype::Int { width: 1, .. } if value.dtype().get_int_width().is_some() => {
+                cast(target_dtype, operand(value))
+            }
+            ir::Dtype::Int { width: 1, .. } => {
+                cast(target_dtype, format!("(int64_t){}", operand(value)))
+            }
+            _ => format!("({}){}", type_name(target_dtype), operand(value)),
+        },
+        ir::Instruction::GetElementPtr { ptr, offset, dtype } => format!(
+            "({})((char *){} + {})",
+            type_name(dtype),
+            operand(ptr),
+            operand(offset)
+        ),
+        ir::Instruction::Min { lhs, rhs, .. } => {
+            let (lhs, rhs) = (operand(lhs), operand(rhs));
+            format!("{lhs} < {rhs} ? {lhs} : {rhs}")
+        }
+        ir::Instruction::Max { lhs, rhs, .. } => {
+            let (lhs, rhs) = (operand(lhs), operand(rhs));
+            format!("{lhs} < {rhs} ? {rhs} : {lhs}")
+        }
+        ir::Instruction::Abs {
+            operand: value,
+            dtype,
+        } => {
+            let value = operand(value);
+            let negation = wrapping_negation(dtype, &value);
+            format!("{value} < 0 ? {negation} : {value}")
+        }
+    };
+
+    Ok(Some(format!("{} = {value};", register(rid))))
+}
+
+fn binary_operation(
+    op: &ast::BinaryOperator,
+    lhs: &ir::Operand,
+    rhs: &ir::Operand,
+    dtype: &ir::Dtype,
+) -> Result<String, String> {
+    let token = match op {
+        ast::BinaryOperator::Multiply => "*",
+        ast::BinaryOperator::Divide => "/",
+        ast::BinaryOperator::Modulo => "%",
+        ast::BinaryOperator::Plus => "+",
+        ast::BinaryOperator::Minus => "-",
+        ast::BinaryOperator::ShiftLeft => "<<",
+        ast::BinaryOperator::ShiftRight => ">>",
+        ast::BinaryOperator::Less => "<",
+        ast::BinaryOperator::Greater => ">",
+        ast::BinaryOperator::LessOrEqual => "<=",
+        ast::BinaryOperator::GreaterOrEqual => ">=",
+        ast::BinaryOperator::Equals => "==",
+        ast::BinaryOperator::NotEquals => "!=",
+        ast::BinaryOperator::BitwiseAnd => "&",
+        ast::BinaryOperator::BitwiseXor => "^",
+        ast::BinaryOperator::BitwiseOr => "|",
+        _ => return Err(format!("unknown operator `{op:?}`")),
+    };
+    let is_comparison = matches!(
+        op,
+        ast::BinaryOperator::Less
+            | ast::BinaryOperator::Greater
+            | ast::BinaryOperator::LessOrEqual
+            | ast::BinaryOperator::GreaterOrEqual
+            | ast::BinaryOperator::Equals
+            | ast::BinaryOperator::NotEquals
+    );
+    let (lhs_c, rhs_c) = (operand(lhs), operand(rhs));
+
+    match lhs.dtype() {
+        ir::Dtype::Int { width, .. } => {
+            if is_comparison {
+                return Ok(format!("{lhs_c} {token} {rhs_c}"));
+            }
+
+            // Signed overflow wraps around, as it is computed in unsigned integers.
+            let wraps = matches!(
+                op,
+                ast::BinaryOperator::Plus
+                    | ast::BinaryOperator::Minus
+                    | ast::BinaryOperator::Multiply
+                    | ast::BinaryOperator::ShiftLeft
+            );
+            let expression = if wraps {
+                let unsigned = int_type(width, false);
+                format!("({unsigned}){lhs_c} {token} ({unsigned}){rhs_c}")
+            } else {
+                format!("{lhs_c} {token} {rhs_c}")
+            };
+            Ok(cast(dtype, expression))
+        }
+        ir::Dtype::Float { .. } if is_comparison => Ok(format!("{lhs_c} {token} {rhs_c}")),
+        ir::Dtype::Float { .. } if !matches!(op, ast::BinaryOperator::Modulo) => {
+            Ok(cast(dtype, format!("{lhs_c} {token} {rhs_c}")))
+        }
+        ir::Dtype::Pointer { .. } if is_comparison => {
+            Ok(format!("(char *){lhs_c} {token} (char *){rhs_c}"))
+        }
+        dtype => Err(format!("`{op:?}` on `{dtype}`")),
+    }
+}
+
+fn unary_operation(
+    op: &ast::UnaryOperator,
+    value: &ir::Operand,
+    dtype: &ir::Dtype,
+) -> Result<String, String> {
+    let value_c = operand(value);
+    match (op, value.dtype()) {
+        (ast::UnaryOperator::Plus, _) => Ok(value_c),
+        (ast::UnaryOperator::Minus, ir::Dtype::Int { .. }) => {
+            Ok(wrapping_negation(dtype, &value_c))
+        }
+        (ast::UnaryOperator::Minus, ir::Dtype::Float { .. }) => Ok(format!("-{value_c}")),
+        (ast::UnaryOperator::Negate, ir::Dtype::Int { .. }) => Ok(format!("!{value_c}")),
+        (ast::UnaryOperator::Complement, ir::Dtype::Int { width, .. }) => Ok(cast(
+            dtype,
+            format!("~({}){value_c}", int_type(width, false)),
+        )),
+        (op, dtype) => Err(format!("`{op:?}` on `{dtype}`")),
+    }
+}
+
+/// Returns `-value` of the integer `dtype`, wrapping around for the minimum integer.
+fn wrapping_negation(dtype: &ir::Dtype, value: &str) -> String {
+    let width = dtype.get_int_width().expect("`dtype` must be an integer");
+    cast(dtype, format!("0 - ({}){value}", int_type(width, false)))
+}
+
+/// Returns `expression` converted to the integer or float `dtype`, like the interpreter, i.e.,
+/// booleans keep the lowest bit.
+fn cast(dtype: &ir::Dtype, expression: String) -> String {
+    if let ir::Dtype::Int { width: 1, .. } = dtype {
+        format!("(uint8_t)(({expression}) & 1)")
+    } else {
+        format!("({})({expression})", type_name(dtype))
+    }
+}
+
+fn exit(exit: &ir::BlockExit) -> String {
+    match exit {
+        ir::BlockExit::Jump { arg } => jump(arg),
+        ir::BlockExit::ConditionalJump {
+            condition,
+            arg_then,
+            arg_else,
+        } => format!(
+            "if ({}) {} else {}",
+            operand(condition),
+            jump(arg_then),
+            jump(arg_else)
+        ),
+        ir::BlockExit::Switch {
+            value,
+            default,
+            cases,
+        } => {
+            let cases = cases
+                .iter()
+                .map(|(case, arg)| format!("    case {}: {}\n", constant(case), jump(arg)))
+                .join("");
+            format!(
+                "switch ({}) {{\n{cases}    default: {}\n  }}",
+                operand(value),
+                jump(default)
+            )
+        }
+        ir::BlockExit::Return { value } => {
+            if let ir::Dtype::Unit { .. } = value.dtype() {
+                "return;".to_string()
+            } else {
+                format!("return {};", operand(value))
+            }
+        }
+        ir::BlockExit::Unreachable => "__builtin_unreachable();".to_string(),
+    }
+}
+
+/// Returns the jump along `arg`, assigning the phinodes of the target.
+///
+/// The arguments are computed before any phinode is assigned, since they may read the phinodes.
+fn jump(arg: &ir::JumpArg) -> String {
+    if arg.args.is_empty() {
+        return format!("goto {};", arg.bid);
+    }
+
+    let temps = arg
+        .args
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            format!(
+                "{} = {}; ",
+                declaration(&value.dtype(), &format!("__kecc_a{i}")),
+                operand(value)
+            )
+        })
+        .join("");
+    let assigns = (0..arg.args.len())
+        .map(|i| {
+            format!(
+                "{} = __kecc_a{i}; ",
+                register(&ir::RegisterId::arg(arg.bid, i))
+            )
+        })
+        .join("");
+    format!("{{ {temps}{assigns}goto {}; }}", arg.bid)
+}
+
+fn register(rid: &ir::RegisterId) -> String {
+    match rid {
+        ir::RegisterId::Local { aid } => format!("_l{aid}"),
+        ir::RegisterId::Arg { bid, aid } => format!("_{bid}_p{aid}"),
+        ir::RegisterId::Temp { bid, iid } => format!("_{bid}_i{iid}"),
+    }
+}
+
+fn operand(operand: &ir::Operand) -> String {
+    match operand {
+        ir::Operand::Constant(value) => constant(value),
+        ir::Operand::Register {
+            rid: rid @ ir::RegisterId::Local { .. },
+            ..
+        } => format!("(&{})", register(rid)),
+        ir::Operand::Register { rid, .. } => register(rid),
+    }
+}
+
+fn constant(value: &ir::Constant) -> String {
+    match value {
+        ir::Constant::Undef { dtype } | ir::Constant::ZeroInit { dtype } => match dtype {
+            ir::Dtype::Unit { .. } => String::new(),
+            ir::Dtype::Array { .. } | ir::Dtype::Struct { .. } => {
+                format!("(({}){{0}})", type_name(dtype))
+            }
+            _ => format!("(({})0)", type_name(dtype)),
+        },
+        ir::Constant::Unit => String::new(),
+        ir::Constant::Int {
+            value,
+            width,
+            is_signed,
+            ..
+        } => integer(*value, *width, *is_signed),
+        ir::Constant::Float { value, width } => float(value.into_inner(), *width),
+        ir::Constant::GlobalVariable { name, dtype } => format!(
+            "(({})&{name})",
+            type_name(&ir::Dtype::pointer(dtype.clone()))
+        ),
+    }
+}
+
+fn integer(value: u128, width: usize, is_signed: bool) -> String {
+    let value = ir::trim_unnecessary_bits(value, width as u128);
+    if width == 1 {
+        return format!("((uint8_t){value})");
+    }
+    if !is_signed {
+        return format!("(({}){value}ULL)", int_type(width, false));
+    }
+
+    let value = ir::sign_extension(value, width as u128) as i128;
+    if value == i128::from(i64::MIN) {
+        // `9223372036854775808` does not fit in `long long`.
+        "INT64_MIN".to_string()
+    } else {
+        format!("(({})({value}LL))", int_type(width, true))
+    }
+}
+
+fn float(value: f64, width: usize) -> String {
+    let is_float = width == ir::Dtype::SIZE_OF_FLOAT * ir::Dtype::BITS_OF_BYTE;
+    let suffix = if is_float { "f" } else { "" };
+
+    if value.is_nan() {
+        format!("__builtin_nan{suffix}(\"\")")
+    } else if value.is_infinite() {
+        let sign = if value < 0.0 { "-" } else { "" };
+        format!("({sign}__builtin_inf{suffix}())")
+    } else if is_float {
+        // The shortest representation of the `f32`, not the `f64`, is exact.
+        format!("({:?}f)", value as f32)
+    } else {
+        format!("({value:?})")
+    }
+}
+
+/// Returns the initializer of a global variable holding `value`.
+fn value_initializer(value: &ir::Value) -> String {
+    match value {
+        ir::Value::Undef { dtype } => match dtype {
+            ir::Dtype::Array { .. } | ir::Dtype::Struct { .. } => "{0}".to_string(),
+            _ => "0".to_string(),
+        },
+        ir::Value::Unit | ir::Value::Pointer { .. } => "0".to_string(),
+        ir::Value::Int {
+            value,
+            width,
+            is_signed,
+        } => integer(*value, *width, *is_signed),
+        ir::Value::Float { value, width } => float(value.into_inner(), *width),
+        ir::Value::Array { values, .. } => {
+            format!("{{ {} }}", values.iter().map(value_initializer).join(", "))
+        }
+        // Designators skip the padding fields.
+        ir::Value::Struct { fields, .. } => format!(
+            "{{ {} }}",
+            fields
+                .iter()
+                .enumerate()
+                .map(|(i, field)| format!(
+                    ".{} = {}",
+                    field_name(field.name(), i),
+                    value_initializer(field.deref())
+                ))
+                .join(", ")
+        ),
+    }
+}
+
+/// Returns `true` if values of `dtype` contain a struct.
+fn has_struct(dtype: &ir::Dtype) -> bool {
+    match dtype {
+        ir::Dtype::Array { inner, .. } => has_struct(inner),
+        ir::Dtype::Struct { .. } => true,
+        _ => false,
+    }
+}
+
+/// Returns the name of the `i`-th field of a struct, naming anonymous members.
+fn field_name(name: Option<&String>, i: usize) -> String {
+    name.cloned().unwrap_or_else(|| format!("__kecc_anon{i}"))
+}
+
+fn int_type(width: usize, is_signed: bool) -> String {
+    if width == 1 {
+        "uint8_t".to_string()
+    } else if is_signed {
+        format!("int{width}_t")
+    } else {
+        format!("uint{width}_t")
+    }
+}
+
+/// Returns the C type name of `dtype`, e.g., for casts.
+fn type_name(dtype: &ir::Dtype) -> String {
+    declaration(dtype, "").trim_end().to_string()
+}
+
+/// Returns the C declaration of the object `declarator` of `dtype`, aligned with `_Alignas` if
+/// `dtype` is an array whose alignment is overridden, e.g., `_Alignas(16) uint8_t x[64]`.
+fn object_declaration(dtype: &ir::Dtype, declarator: &str) -> String {
+    let declaration = declaration(dtype, declarator);
+    if let Some(align) = dtype.get_array_align() {
+        format!("_Alignas({align}) {declaration}")
+    } else {
+        declaration
+    }
+}
+
+/// Returns the C declaration of `declarator` of `dtype`, e.g., `int32_t (*x)[4]`.
+///
+/// Qualifiers are omitted, since registers are assigned and stores to constants are rejected by
+/// the verifier.
+fn declaration(dtype: &ir::Dtype, declarator: &str) -> String {
+    match dtype {
+        ir::Dtype::Unit { .. } => format!("void {declarator}"),
+        ir::Dtype::Int {
+            width, is_signed, ..
+        } => format!("{} {declarator}", int_type(*width, *is_signed)),
+        ir::Dtype::Float { width, .. } => {
+            let base = if *width == ir::Dtype::SIZE_OF_FLOAT * ir::Dtype::BITS_OF_BYTE {
+                "float"
+            } else {
+                "double"
+            };
+            format!("{base} {declarator}")
+        }
+        ir::Dtype::Pointer { inner, .. } => {
+            let declarator = if matches!(
+                inner.deref(),
+                ir::Dtype::Array { .. } | ir::Dtype::Function { .. }
+            ) {
+                format!("(*{declarator})")
+            } else {
+                format!("*{declarator}")
+            };
+            declaration(inner, &declarator)
+        }
+        ir::Dtype::Array { inner, size, .. } => {
+            let size = size.map(|size| size.to_string()).unwrap_or_default();
+            declaration(inner, &format!("{declarator}[{size}]"))
+        }
+        ir::Dtype::Struct { name, .. } => {
+            let name = name.as_ref().expect("struct should have its name");
+            let keyword = dtype
+                .get_struct_keyword()
+                .expect("`dtype` must be a struct");
+            format!("{keyword} {} {declarator}", ir::c_tag(name))
+        }
+        ir::Dtype::Function { ret, params } => {
+            let params = if params.is_empty() {
+                "void".to_string()
+            } else {
+                params.iter().map(type_name).join(", ")
+            };
+            declaration(ret, &format!("{declarator}({params})"))
+        }
+        ir::Dtype::Typedef { .. } => panic!("typedef should be replaced by real dtype"),
+        ir::Dtype::Enum { .. } => panic!("enum should be replaced by int"),
+    }
+}
@@ -0,0 +1,370 @@
+//! Harness for external conformance suites, e.g., c-testsuite or the execute tests of the GCC
+//! torture suite, laid out as one C file per test whose `main` returns 0.
+//!
+//! The suites are not vendored. Instead, a [`Manifest`] in the repository records the status of
+//! each test, i.e., the stage of the pipeline it is known to fail at, so that a
+//! [`ConformanceReport`] tells the tests now passing a later stage than recorded, i.e., the
+//! progress, from those failing an earlier one, i.e., the regressions.
+
+use core::fmt;
+use core::str::FromStr;
+use core::time::Duration;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use tempfile::tempdir;
+use thiserror::Error;
+use wait_timeout::ChildExt;
+
+use crate::*;
+
+/// The number of steps the interpreter runs a test for, beyond which it is taken not to terminate.
+const MAX_STEPS: usize = 100_000_000;
+
+/// The time a test runs on QEMU for, beyond which it is taken not to terminate.
+const QEMU_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The status of a conformance test, by the stage of the pipeline it reaches, in increasing order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConformanceStatus {
+    /// The parser rejects the test.
+    XfailParse,
+    /// The IR generator rejects the test, or the interpreter does not return 0 from its IR.
+    XfailIrgen,
+    /// The assembly generator rejects the test, or its assembly does not exit with 0 on QEMU.
+    XfailAsm,
+    Pass,
+}
+
+impl ConformanceStatus {
+    const ALL: [Self; 4] = [
+        Self::XfailParse,
+        Self::XfailIrgen,
+        Self::XfailAsm,
+        Self::Pass,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::XfailParse => "xfail-parse",
+            Self::XfailIrgen => "xfail-irgen",
+            Self::XfailAsm => "xfail-asm",
+            Self::Pass => "pass",
+        }
+    }
+}
+
+impl fmt::Display for ConformanceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for ConformanceStatus {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.name() == s)
+            .ok_or(())
+    }
+}
+
+/// An error in parsing a [`Manifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ManifestError {
+    #[error("line {line}: expected `<test> <status>`")]
+    Malformed { line: usize },
+    #[error("line {line}: unknown status `{status}`")]
+    UnknownStatus { line: usize, status: String },
+    #[error("line {line}: duplicate test `{name}`")]
+    Duplicate { line: usize, name: String },
+}
+
+/// The recorded status of each test of a conformance suite, by its path relative to the suite.
+///
+/// It is written one test per line as `<test> <status>`, sorted by the tests, after a header of
+/// `#` comments, so that regenerating it from the same results gives the same file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    pub tests: BTreeMap<String, ConformanceStatus>,
+}
+
+impl Manifest {
+    const HEADER: &'static str = "\
+# The status of each test of the conformance suite: pass, xfail-parse, xfail-irgen or xfail-asm.
+# Regenerated by `scripts/conformance.sh --update <suite>`.
+";
+
+    pub fn parse(text: &str) -> Result<Self, ManifestError> {
+        let mut tests = BTreeMap::new();
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (name, status) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [name, status] => (name, status),
+                _ => return Err(ManifestError::Malformed { line: line_number }),
+            };
+            let status =
+                status
+                    .parse::<ConformanceStatus>()
+                    .map_err(|()| ManifestError::UnknownStatus {
+                        line: line_number,
+                        status: status.to_string(),
+                    })?;
+            if tests.insert(name.to_string(), status).is_some() {
+                return Err(ManifestError::Duplicate {
+                    line: line_number,
+                    name: name.to_string(),
+                });
+            }
+        }
+
+        Ok(Self { tests })
+    }
+
+    /// Returns the manifest recording `results`, the tests of the suite with their statuses.
+    ///
+    /// If the assembly is not checked, the tests recorded as `xfail-asm` passing the interpreter
+    /// are kept as they are.
+    pub fn updated(&self, results: &BTreeMap<String, ConformanceStatus>, checks_asm: bool) -> Self {
+        let tests = results
+            .iter()
+            .map(|(name, status)| {
+                let status = checked_status(*status, self.tests.get(name).copied(), checks_asm);
+                (name.clone(), status)
+            })
+            .collect();
+        Self { tests }
+    }
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::HEADER)?;
+        for (name, status) in &self.tests {
+            writeln!(f, "{name} {status}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns `status` of a test recorded as `recorded`, as far as it is checked: a test recorded as
+/// `xfail-asm` passing the interpreter is still `xfail-asm` if the assembly is not checked.
+fn checked_status(
+    status: ConformanceStatus,
+    recorded: Option<ConformanceStatus>,
+    checks_asm: bool,
+) -> ConformanceStatus {
+    if !checks_asm
+        && status == ConformanceStatus::Pass
+        && recorded == Some(ConformanceStatus::XfailAsm)
+    {
+        ConformanceStatus::XfailAsm
+    } else {
+        status
+    }
+}
+
+/// A change in the status of a test from its recorded one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusChange {
+    pub name: String,
+    pub recorded: ConformanceStatus,
+    pub status: ConformanceStatus,
+}
+
+impl fmt::Display for StatusChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({} -> {})", self.name, self.recorded, self.status)
+    }
+}
+
+/// The results of a conformance suite compared with its manifest.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConformanceReport {
+    /// The tests reaching a later stage than recorded
+    pub newly_passing: Vec<StatusChange>,
+    /// The tests failing at an earlier stage than recorded
+    pub newly_failing: Vec<StatusChange>,
+    /// The tests of the suite not in the manifest, with their statuses
+    pub untracked: Vec<(String, ConformanceStatus)>,
+    /// The tests of the manifest not in the suite
+    pub missing: Vec<String>,
+}
+
+impl ConformanceReport {
+    pub fn new(
+        manifest: &Manifest,
+        results: &BTreeMap<String, ConformanceStatus>,
+        checks_asm: bool,
+    ) -> Self {
+        let mut report = Self::default();
+        for (name, status) in results {
+            let recorded = some_or!(manifest.tests.get(name), {
+                report.untracked.push((name.clone(), *status));
+                continue;
+            });
+            let change = StatusChange {
+                name: name.clone(),
+                recorded: *recorded,
+                status: checked_status(*status, Some(*recorded), checks_asm),
+            };
+            if change.status > change.recorded {
+                report.newly_passing.push(change);
+            } else if change.status < change.recorded {
+                report.newly_failing.push(change);
+            }
+        }
+        report.missing = manifest
+            .tests
+            .keys()
+            .filter(|name| !results.contains_key(*name))
+            .cloned()
+            .collect();
+        report
+    }
+
+    /// Returns `true` if a test fails at an earlier stage than recorded.
+    pub fn has_regressions(&self) -> bool {
+        !self.newly_failing.is_empty()
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.newly_passing {
+            writeln!(f, "newly passing: {change}")?;
+        }
+        for change in &self.newly_failing {
+            writeln!(f, "newly failing: {change}")?;
+        }
+        for (name, status) in &self.untracked {
+            writeln!(f, "untracked: {name} ({status})")?;
+        }
+        for name in &self.missing {
+            writeln!(f, "missing: {name}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the tests of the conformance suite in `dir`, i.e., the C files in it, and returns their
+/// statuses by their paths relative to `dir`, e.g., `single-exec/00001.c`.
+///
+/// A test passes if the interpreter returns 0 from the IR generated from it, and, if
+/// `checks_asm`, if its assembly exits with 0 on QEMU.
+pub fn run_conformance_suite(
+    dir: &Path,
+    checks_asm: bool,
+) -> io::Result<BTreeMap<String, ConformanceStatus>> {
+    let mut tests = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(current) = dirs.pop() {
+        for entry in fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().map_or(false, |ext| ext == "c") {
+                let name = path
+                    .strip_prefix(dir)
+                    .expect("`path` must be in `dir`")
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                tests.push((name, path));
+            }
+        }
+    }
+
+    Ok(parallel_map(tests, |(name, path)| {
+        (name, run_conformance_test(&path, checks_asm))
+    })
+    .into_iter()
+    .collect())
+}
+
+/// Runs the conformance test `path` through the pipeline, returning the stage it reaches. A panic
+/// of a stage fails it like an error.
+pub fn run_conformance_test(path: &Path, checks_asm: bool) -> ConformanceStatus {
+    let unit = some_or!(
+        catch(|| Parse.translate(&path).ok()),
+        return ConformanceStatus::XfailParse
+    );
+    let ir = some_or!(
+        catch(|| Irgen::default().translate(&unit).ok()),
+        return ConformanceStatus::XfailIrgen
+    );
+    let status = catch(|| {
+        let execution =
+            ir::interp_bounded(&ir, Vec::new(), CompileOptions::default(), MAX_STEPS).ok()??;
+        execution.value.exit_status()
+    });
+    if status != Some(0) {
+        return ConformanceStatus::XfailIrgen;
+    }
+    if !checks_asm {
+        return ConformanceStatus::Pass;
+    }
+
+    let asm = some_or!(
+        catch(|| Asmgen::default().translate(&ir).ok()),
+        return ConformanceStatus::XfailAsm
+    );
+    if run_on_qemu(&asm) == Some(0) {
+        ConformanceStatus::Pass
+    } else {
+        ConformanceStatus::XfailAsm
+    }
+}
+
+/// Returns the result of `f`, or `None` if it panics.
+fn catch<T>(f: impl FnOnce() -> Option<T>) -> Option<T> {
+    panic::catch_unwind(AssertUnwindSafe(f)).ok().flatten()
+}
+
+/// Assembles `asm` and runs it on QEMU, returning its exit status, or `None` if it fails to
+/// assemble, is killed or times out.
+fn run_on_qemu(asm: &asm::Asm) -> Option<i32> {
+    let temp_dir = tempdir().ok()?;
+    let asm_path = temp_dir.path().join("test.S");
+    let bin_path = temp_dir.path().join("test");
+    let mut buffer = Vec::new();
+    write(asm, &mut buffer).ok()?;
+    fs::write(&asm_path, buffer).ok()?;
+
+    let assembled = Command::new("riscv64-linux-gnu-gcc")
+        .arg("-static")
+        .arg(&asm_path)
+        .arg("-o")
+        .arg(&bin_path)
+        .stderr(Stdio::null())
+        .status()
+        .ok()?;
+    if !assembled.success() {
+        return None;
+    }
+
+    let mut child = Command::new("qemu-riscv64-static")
+        .arg(&bin_path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let status = some_or!(child.wait_timeout(QEMU_TIMEOUT).ok()?, {
+        let _unused = child.kill();
+        let _unused = child.wait();
+        return None;
+    });
+    status.code()
+}
@@ -0,0 +1,138 @@
+//! Reproducible crash reports for panics on user input.
+
+use core::fmt;
+use std::backtrace::Backtrace;
+use std::fs;
+use std::io::{self, Write};
+use std::panic::{self, PanicInfo};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::CompileOptions;
+
+/// The stage of the compiler currently running, e.g., `irgen` or `opt:gvn`.
+static STAGE: Mutex<Option<String>> = Mutex::new(None);
+
+/// Records the stage of the compiler currently running, to be reported if it panics.
+pub fn set_stage(stage: &str) {
+    let mut current = STAGE.lock().unwrap_or_else(PoisonError::into_inner);
+    *current = Some(stage.to_string());
+}
+
+/// Returns the stage of the compiler currently running, if any.
+pub fn current_stage() -> Option<String> {
+    STAGE.lock().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// Self-contained report of a panic, with everything needed to reproduce it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashReport {
+    pub input_path: PathBuf,
+    /// Contents of the input file, or the error reading it
+    pub input: String,
+    pub options: CompileOptions,
+    pub stage: Option<String>,
+    pub message: String,
+    pub backtrace: String,
+}
+
+impl CrashReport {
+    /// The exit code of the compiler when it crashes.
+    pub const EXIT_CODE: i32 = 70;
+
+    pub fn new(
+        input_path: &Path,
+        options: CompileOptions,
+        message: String,
+        backtrace: String,
+    ) -> Self {
+        let input = fs::read_to_string(input_path)
+            .unwrap_or_else(|e| format!("<failed to read the input: {e}>"));
+
+        Self {
+            input_path: input_path.to_path_buf(),
+            input,
+            options,
+            stage: current_stage(),
+            message,
+            backtrace,
+        }
+    }
+
+    /// Writes the report into `kecc-crash-<timestamp>.txt` in `dir`, returning its path.
+    pub fn write(&self, dir: &Path) -> io::Result<PathBuf> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let path = dir.join(format!("kecc-crash-{timestamp}.txt"));
+        fs::write(&path, self.to_string())?;
+        Ok(path)
+    }
+}
+
+impl fmt::Display for CrashReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "== kecc crash report ==")?;
+        writeln!(f, "version: {}", env!("CARGO_PKG_VERSION"))?;
+        writeln!(f, "stage: {}", self.stage.as_deref().unwrap_or("<unknown>"))?;
+        writeln!(f, "options: {}", self.options.to_json())?;
+        writeln!(f)?;
+        writeln!(f, "== panic ==")?;
+        writeln!(f, "{}", self.message)?;
+        writeln!(f)?;
+        writeln!(f, "== input: {} ==", self.input_path.display())?;
+        writeln!(f, "{}", self.input)?;
+        writeln!(f)?;
+        writeln!(f, "== backtrace ==")?;
+        writeln!(f, "{}", self.backtrace)
+    }
+}
+
+/// Installs a panic hook writing a [`CrashReport`] of the compilation of `input_path` into `dir`.
+///
+/// The hook never panics itself: failures to write the report are printed instead.
+pub fn install_crash_handler(input_path: PathBuf, options: CompileOptions, dir: PathBuf) {
+    panic::set_hook(Box::new(move |info| {
+        let report = CrashReport::new(
+            &input_path,
+            options,
+            panic_message(info),
+            Backtrace::force_capture().to_string(),
+        );
+
+        // Unlike `eprintln!`, writing to `stderr` this way does not panic on failure.
+        let mut stderr = io::stderr();
+        let _unused = match report.write(&dir) {
+            Ok(path) => writeln!(
+                stderr,
+                "kecc crashed: {}\ncrash report written to {}",
+                report.message,
+                path.display()
+            ),
+            Err(e) => writeln!(
+                stderr,
+                "kecc crashed: {}\nfailed to write crash report: {e}",
+                report.message
+            ),
+        };
+    }));
+}
+
+fn panic_message(info: &PanicInfo<'_>) -> String {
+    let payload = info.payload();
+    let message = if let Some(message) = payload.downcast_ref::<&str>() {
+        *message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.as_str()
+    } else {
+        "<non-string panic payload>"
+    };
+
+    if let Some(location) = info.location() {
+        format!("{message} at {location}")
+    } else {
+        message.to_string()
+    }
+}
@@ -0,0 +1,327 @@
+//! Warn about out-of-bounds accesses to arrays at constant offsets.
+
+use core::fmt;
+use core::ops::Deref;
+use std::collections::HashMap;
+
+use lang_c::ast;
+use lang_c::span::Span;
+
+use crate::ir::*;
+use crate::some_or;
+
+/// An access out of the bounds of a variable or one of its array members.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundsWarning {
+    /// The access, e.g., `main:b0:3` or `foo.c:5:5`, or the variable for its initializer in the IR
+    pub site: String,
+    /// The accessed variable, e.g., `%l0:[4 x i32]:buf` or `@table`, or the location of its
+    /// declarator in the C source, e.g., `foo.c:2:9`
+    pub decl: String,
+    pub message: String,
+    /// The spans of the access and of the declarator of the variable, if found in the C source
+    pub spans: Option<(Span, Span)>,
+}
+
+impl fmt::Display for BoundsWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.spans.is_some() {
+            return write!(
+                f,
+                "{}: warning: {} (declared at {})",
+                self.site, self.message, self.decl
+            );
+        }
+        write!(
+            f,
+            "{}: warning: {} (`{}` is declared here)",
+            self.site, self.message, self.decl
+        )
+    }
+}
+
+/// A pointer into a variable at a constant offset.
+#[derive(Debug, Clone)]
+struct Pointer {
+    decl: String,
+    /// Bytes that may be accessed through the pointer, i.e., the variable or the innermost array
+    /// the pointer is derived from
+    bounds: (isize, isize),
+    offset: isize,
+}
+
+/// Returns the warnings for the accesses in `unit` that are out of bounds, and for the array
+/// initializers with too many elements or too long a string literal.
+///
+/// The accesses are checked by [`check_access_bounds`].
+pub fn check_bounds(unit: &TranslationUnit) -> Vec<BoundsWarning> {
+    let mut warnings = Vec::new();
+
+    for (name, decl) in &unit.decls {
+        if let Some((dtype, Some(initializer))) = decl.get_variable() {
+            let decl = format!("@{name}");
+            let mut messages = Vec::new();
+            initializer_overflows(dtype, initializer, &mut messages);
+            warnings.extend(messages.into_iter().map(|message| BoundsWarning {
+                site: decl.clone(),
+                decl: decl.clone(),
+                message,
+                spans: None,
+            }));
+        }
+    }
+
+    warnings.extend(check_access_bounds(unit));
+    warnings
+}
+
+/// Returns the warnings for the accesses in the functions of `unit` that are out of bounds.
+///
+/// The check is local: it follows `getelementptr` chains with constant offsets from local
+/// allocations and global variables, so accesses through pointers of unknown provenance (e.g.,
+/// function parameters) are never warned. Accessing an element of an array through the pointer
+/// it decays to must stay in the array even if it is a member of a larger variable. Also warned
+/// are `__builtin_memcpy` and `__builtin_memset` with a constant size exceeding the destination.
+pub fn check_access_bounds(unit: &TranslationUnit) -> Vec<BoundsWarning> {
+    let mut warnings = Vec::new();
+
+    for (func_name, decl) in &unit.decls {
+        let (_, definition) = some_or!(decl.get_function(), continue);
+        let definition = some_or!(definition, continue);
+        let pointers = pointers(unit, definition);
+
+        for (bid, block) in &definition.blocks {
+            for (iid, instruction) in block.instructions.iter().enumerate() {
+                let (ptr, size) = match instruction.deref() {
+                    Instruction::Load { ptr } | Instruction::Store { ptr, .. } => {
+                        let dtype = ptr.dtype();
+                        let inner = dtype.get_pointer_inner().expect("`ptr` must be a pointer");
+                        let (size_of, _) =
+                            some_or!(inner.size_align_of(&unit.structs).ok(), continue);
+                        (ptr, size_of as isize)
+                    }
+                    Instruction::Call { callee, args, .. } => {
+                        let callee = some_or!(
+                            callee
+                                .get_constant()
+                                .and_then(Constant::get_global_variable_name),
+                            continue
+                        );
+                        if !matches!(
+                            Builtin::from_name(&callee),
+                            Some(Builtin::Memcpy | Builtin::Memset)
+                        ) {
+                            continue;
+                        }
+                        let size = some_or!(
+                            args.get(2)
+                                .and_then(Operand::get_constant)
+                                .and_then(Constant::get_int),
+                            continue
+                        );
+                        (&args[0], size.0 as isize)
+                    }
+                    _ => continue,
+                };
+
+                let pointer = some_or!(pointer_of(ptr, unit, definition, &pointers), continue);
+                let (start, end) = pointer.bounds;
+                if pointer.offset < start || pointer.offset + size > end {
+                    warnings.push(BoundsWarning {
+                        site: format!("{func_name}:{bid}:{iid}"),
+                        decl: pointer.decl,
+                        message: format!(
+                            "access to bytes [{}, {}) is out of bounds [{start}, {end})",
+                            pointer.offset,
+                            pointer.offset + size
+                        ),
+                        spans: None,
+                    });
+                }
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Returns the pointers derived from local allocations and global variables by `getelementptr`
+/// chains with constant offsets.
+fn pointers(
+    unit: &TranslationUnit,
+    definition: &FunctionDefinition,
+) -> HashMap<RegisterId, Pointer> {
+    let mut pointers = HashMap::<RegisterId, Pointer>::new();
+
+    loop {
+        let mut changed = false;
+        for (bid, block) in &definition.blocks {
+            for (iid, instruction) in block.instructions.iter().enumerate() {
+                let rid = RegisterId::temp(*bid, iid);
+                if pointers.contains_key(&rid) {
+                    continue;
+                }
+
+                let (ptr, offset, dtype) = if let Instruction::GetElementPtr {
+                    ptr,
+                    offset,
+                    dtype,
+                } = instruction.deref()
+                {
+                    (ptr, offset, dtype)
+                } else {
+                    continue;
+                };
+
+                let mut pointer = some_or!(pointer_of(ptr, unit, definition, &pointers), continue);
+
+                // An array decays to the pointer to its first element, which may only access the
+                // array.
+                let ptr_dtype = ptr.dtype();
+                let array = ptr_dtype
+                    .get_pointer_inner()
+                    .and_then(Dtype::get_array_inner);
+                let element = dtype.get_pointer_inner();
+                if let (Some(array), Some(element)) = (array, element) {
+                    if array.clone().strip_outer_const() == element.clone().strip_outer_const() {
+                        let (size_of, _) = some_or!(
+                            ptr_dtype
+                                .get_pointer_inner()
+                                .expect("`ptr` must be a pointer")
+                                .size_align_of(&unit.structs)
+                                .ok(),
+                            continue
+                        );
+                        pointer.bounds = (pointer.offset, pointer.offset + size_of as isize);
+                    }
+                }
+
+                let (offset, _, is_signed) =
+                    some_or!(offset.get_constant().and_then(Constant::get_int), continue);
+                pointer.offset += if is_signed {
+                    offset as i128 as isize
+                } else {
+                    offset as isize
+                };
+
+                let _unused = pointers.insert(rid, pointer);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    pointers
+}
+
+/// Returns the pointer `operand` holds, if it points to a variable at a constant offset.
+fn pointer_of(
+    operand: &Operand,
+    unit: &TranslationUnit,
+    definition: &FunctionDefinition,
+    pointers: &HashMap<RegisterId, Pointer>,
+) -> Option<Pointer> {
+    let (decl, dtype) = match operand {
+        Operand::Constant(Constant::GlobalVariable { name, dtype }) => (format!("@{name}"), dtype),
+        Operand::Register {
+            rid: RegisterId::Local { aid },
+            ..
+        } => {
+            let allocation = definition.allocations.get(*aid)?;
+            let name = allocation
+                .name()
+                .map(|name| format!(":{name}"))
+                .unwrap_or_default();
+            let decl = format!("{}:{}{name}", RegisterId::local(*aid), allocation.deref());
+            (decl, allocation.deref())
+        }
+        Operand::Register { rid, .. } => return pointers.get(rid).cloned(),
+        _ => return None,
+    };
+
+    let (size_of, _) = dtype.size_align_of(&unit.structs).ok()?;
+    Some(Pointer {
+        decl,
+        bounds: (0, size_of as isize),
+        offset: 0,
+    })
+}
+
+/// Adds the messages about `initializer` of a variable of `dtype` and its nested initializers
+/// having too many elements or too long a string literal for their arrays to `messages`.
+pub(crate) fn initializer_overflows(
+    dtype: &Dtype,
+    initializer: &ast::Initializer,
+    messages: &mut Vec<String>,
+) {
+    let (inner, size) = if let Dtype::Array {
+        inner,
+        size: Some(size),
+        ..
+    } = dtype
+    {
+        (inner.deref(), *size)
+    } else {
+        return;
+    };
+
+    let len = match initializer {
+        ast::Initializer::List(items) => {
+            for item in items {
+                initializer_overflows(inner, &item.node.initializer.node, messages);
+            }
+            items.len()
+        }
+        ast::Initializer::Expression(expr) => {
+            if let ast::Expression::StringLiteral(literal) = &expr.node {
+                string_literal_len(&literal.node)
+            } else {
+                return;
+            }
+        }
+    };
+
+    if len > size {
+        messages.push(format!(
+            "initializer of {len} elements overflows the array of {size}"
+        ));
+    }
+}
+
+/// Returns the number of characters of a string literal, excluding the terminating null
+/// character.
+fn string_literal_len(literal: &[String]) -> usize {
+    literal
+        .iter()
+        .map(|piece| {
+            let content = piece
+                .split_once('"')
+                .map(|(_, content)| content.strip_suffix('"').unwrap_or(content))
+                .unwrap_or(piece);
+
+            let mut len = 0;
+            let mut chars = content.chars().peekable();
+            while let Some(c) = chars.next() {
+                len += 1;
+                if c != '\\' {
+                    continue;
+                }
+
+                // Escape sequences denote a single character.
+                match chars.next() {
+                    Some('x') => while chars.next_if(char::is_ascii_hexdigit).is_some() {},
+                    Some('0'..='7') => {
+                        for _ in 0..2 {
+                            let _unused = chars.next_if(|c| ('0'..='7').contains(c));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            len
+        })
+        .sum()
+}
@@ -0,0 +1,93 @@
+//! Compiler builtins.
+
+use core::fmt;
+
+/// Builtin functions recognized by the compiler.
+///
+/// A builtin is referenced as a function declared without a definition, e.g., `int
+/// __builtin_abs(int);`, and calls to it are evaluated by the compiler instead of being linked.
+/// For more details: <https://gcc.gnu.org/onlinedocs/gcc/Other-Builtins.html>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Builtin {
+    /// `int __builtin_abs(int)`
+    Abs,
+    /// `long __builtin_labs(long)`
+    Labs,
+    /// `double __builtin_fabs(double)`
+    Fabs,
+    /// `void *__builtin_memcpy(void *restrict, const void *restrict, unsigned long)`
+    Memcpy,
+    /// `void *__builtin_memset(void *, int, unsigned long)`
+    Memset,
+    /// `int __builtin_strcmp(const char *, const char *)`
+    Strcmp,
+    /// `void __builtin_unreachable(void)`
+    Unreachable,
+    /// `void __builtin_trap(void)`
+    Trap,
+}
+
+impl Builtin {
+    /// The prefix of the names reserved for builtins.
+    pub const PREFIX: &'static str = "__builtin_";
+
+    const ALL: [Self; 8] = [
+        Self::Abs,
+        Self::Labs,
+        Self::Fabs,
+        Self::Memcpy,
+        Self::Memset,
+        Self::Strcmp,
+        Self::Unreachable,
+        Self::Trap,
+    ];
+
+    /// Returns the builtin named `name`, if any.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|builtin| builtin.name() == name)
+    }
+
+    /// Returns the builtin named `name`, or the one of the library function `name`, e.g.,
+    /// [`Self::Strcmp`] for `strcmp`, which behaves the same.
+    pub fn from_library_name(name: &str) -> Option<Self> {
+        Self::from_name(name).or_else(|| Self::from_name(&format!("{}{name}", Self::PREFIX)))
+    }
+
+    /// Returns `true` if `name` is reserved for builtins, whether or not it is supported.
+    pub fn is_reserved(name: &str) -> bool {
+        name.starts_with(Self::PREFIX)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Abs => "__builtin_abs",
+            Self::Labs => "__builtin_labs",
+            Self::Fabs => "__builtin_fabs",
+            Self::Memcpy => "__builtin_memcpy",
+            Self::Memset => "__builtin_memset",
+            Self::Strcmp => "__builtin_strcmp",
+            Self::Unreachable => "__builtin_unreachable",
+            Self::Trap => "__builtin_trap",
+        }
+    }
+
+    /// Returns the indices of the `restrict`-qualified parameters of the builtin.
+    pub fn restrict_params(self) -> &'static [usize] {
+        match self {
+            Self::Memcpy => &[0, 1],
+            Self::Abs
+            | Self::Labs
+            | Self::Fabs
+            | Self::Memset
+            | Self::Strcmp
+            | Self::Unreachable
+            | Self::Trap => &[],
+        }
+    }
+}
+
+impl fmt::Display for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -0,0 +1,358 @@
+//! Report the changes between two versions of the IR, e.g., before and after a pass, at the
+//! granularity of instructions.
+//!
+//! The instructions of a block in both versions are aligned by a longest common subsequence of
+//! their shapes, i.e., their text with the registers of the instructions and allocations they use
+//! erased, so that an instruction keeps its match when the instructions before it are removed and
+//! the registers renumbered. The registers used by the matched instructions are then renamed to
+//! those of the new version, so that a matched instruction is only reported as changed if it
+//! differs otherwise, e.g., by a folded operand.
+
+use core::fmt::Write;
+use core::ops::Deref;
+use std::collections::{BTreeSet, HashMap};
+
+use itertools::izip;
+
+use crate::ir::*;
+use crate::some_or;
+
+/// Returns the changes from `before` to `after`, function by function, in the style of a unified
+/// diff without context lines. It is empty if no function changed.
+pub fn diff(before: &TranslationUnit, after: &TranslationUnit) -> String {
+    let mut report = String::new();
+    let names = before
+        .decls
+        .keys()
+        .chain(after.decls.keys())
+        .collect::<BTreeSet<_>>();
+    for name in names {
+        let definition = |unit: &TranslationUnit| {
+            unit.decls
+                .get(name)
+                .and_then(|decl| decl.get_function())
+                .and_then(|(_, definition)| definition.as_ref())
+        };
+        let lines = match (definition(before), definition(after)) {
+            (Some(before), Some(after)) => diff_function(before, after),
+            (Some(_), None) => vec![format!("-fun @{name}")],
+            (None, Some(_)) => vec![format!("+fun @{name}")],
+            (None, None) => continue,
+        };
+        if lines.is_empty() {
+            continue;
+        }
+
+        writeln!(report, "@@ @{name} @@").unwrap();
+        for line in lines {
+            writeln!(report, "{line}").unwrap();
+        }
+    }
+
+    report
+}
+
+/// Returns the lines of the changes from `before` to `after`.
+fn diff_function(before: &FunctionDefinition, after: &FunctionDefinition) -> Vec<String> {
+    let mut renames = HashMap::new();
+
+    let allocation = |aid: usize, allocation: &Named<Dtype>| {
+        format!(
+            "  {}:{}{}",
+            RegisterId::local(aid),
+            allocation.deref(),
+            allocation
+                .name()
+                .map_or(String::new(), |name| format!(":{name}"))
+        )
+    };
+    let allocation_shape =
+        |allocation: &Named<Dtype>| format!("{}:{:?}", allocation.deref(), allocation.name());
+    let allocations = align(
+        &before
+            .allocations
+            .iter()
+            .map(allocation_shape)
+            .collect::<Vec<_>>(),
+        &after
+            .allocations
+            .iter()
+            .map(allocation_shape)
+            .collect::<Vec<_>>(),
+    );
+    for (lhs, rhs) in &allocations {
+        if let (Some(lhs), Some(rhs)) = (lhs, rhs) {
+            let _unused = renames.insert(RegisterId::local(*lhs), RegisterId::local(*rhs));
+        }
+    }
+
+    // The instructions of each block in both versions, matched by their shapes or changed.
+    let mut alignments = HashMap::new();
+    for (bid, block) in &before.blocks {
+        let other = some_or!(after.blocks.get(bid), continue);
+        let shapes = |block: &Block| block.instructions.iter().map(shape).collect::<Vec<_>>();
+        let alignment = pair_changed(align(&shapes(block), &shapes(other)), |lhs, rhs| {
+            block.instructions[lhs].opcode_name() == other.instructions[rhs].opcode_name()
+        });
+        for (lhs, rhs) in &alignment {
+            if let (Some(lhs), Some(rhs)) = (lhs, rhs) {
+                let _unused =
+                    renames.insert(RegisterId::temp(*bid, *lhs), RegisterId::temp(*bid, *rhs));
+            }
+        }
+        let _unused = alignments.insert(*bid, alignment);
+    }
+
+    let mut lines = Vec::new();
+    let mut allocation_lines = Vec::new();
+    for (lhs, rhs) in allocations {
+        match (lhs, rhs) {
+            (Some(lhs), None) => {
+                allocation_lines.push(format!("-{}", allocation(lhs, &before.allocations[lhs])))
+            }
+            (None, Some(rhs)) => {
+                allocation_lines.push(format!("+{}", allocation(rhs, &after.allocations[rhs])))
+            }
+            _ => {}
+        }
+    }
+    if !allocation_lines.is_empty() {
+        lines.push(" allocations".to_string());
+        lines.extend(allocation_lines);
+    }
+
+    let bids = before
+        .blocks
+        .keys()
+        .chain(after.blocks.keys())
+        .collect::<BTreeSet<_>>();
+    for bid in bids {
+        match (before.blocks.get(bid), after.blocks.get(bid)) {
+            (Some(block), None) => {
+                lines.push(format!("-block {bid}"));
+                lines.extend(block_lines(*bid, block).map(|line| format!("-{line}")));
+            }
+            (None, Some(block)) => {
+                lines.push(format!("+block {bid}"));
+                lines.extend(block_lines(*bid, block).map(|line| format!("+{line}")));
+            }
+            (Some(block), Some(other)) => {
+                let block_lines = diff_block(*bid, block, other, &alignments[bid], &renames);
+                if !block_lines.is_empty() {
+                    lines.push(format!(" block {bid}"));
+                    lines.extend(block_lines);
+                }
+            }
+            (None, None) => unreachable!("`bid` must be a block of either version"),
+        }
+    }
+
+    lines
+}
+
+/// Returns the lines of the changes from `block` to `other`, whose instructions are aligned by
+/// `alignment`, the registers of `block` being renamed by `renames` to be compared.
+fn diff_block(
+    bid: BlockId,
+    block: &Block,
+    other: &Block,
+    alignment: &[(Option<usize>, Option<usize>)],
+    renames: &HashMap<RegisterId, RegisterId>,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    let phinodes = |block: &Block| {
+        block
+            .phinodes
+            .iter()
+            .enumerate()
+            .map(|(aid, phinode)| phinode_line(bid, aid, phinode))
+            .collect::<Vec<_>>()
+    };
+    let (lhs_phinodes, rhs_phinodes) = (phinodes(block), phinodes(other));
+    if lhs_phinodes != rhs_phinodes {
+        lines.extend(lhs_phinodes.into_iter().map(|line| format!("-{line}")));
+        lines.extend(rhs_phinodes.into_iter().map(|line| format!("+{line}")));
+    }
+
+    for (lhs, rhs) in alignment {
+        match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => {
+                let instruction = &block.instructions[*lhs];
+                let other_instruction = &other.instructions[*rhs];
+                let renamed = Named::new(
+                    instruction.name().cloned(),
+                    rename(instruction.deref(), renames),
+                );
+                if instruction_line(bid, *rhs, &renamed)
+                    != instruction_line(bid, *rhs, other_instruction)
+                {
+                    lines.push(format!("-{}", instruction_line(bid, *lhs, instruction)));
+                    lines.push(format!(
+                        "+{}",
+                        instruction_line(bid, *rhs, other_instruction)
+                    ));
+                }
+            }
+            (Some(lhs), None) => lines.push(format!(
+                "-{}",
+                instruction_line(bid, *lhs, &block.instructions[*lhs])
+            )),
+            (None, Some(rhs)) => lines.push(format!(
+                "+{}",
+                instruction_line(bid, *rhs, &other.instructions[*rhs])
+            )),
+            (None, None) => {}
+        }
+    }
+
+    let mut exit = block.exit.clone();
+    exit.walk_operands_mut(|operand| rename_operand(operand, renames));
+    if exit != other.exit {
+        lines.push(format!("-  {}", block.exit));
+        lines.push(format!("+  {}", other.exit));
+    }
+
+    lines
+}
+
+/// Returns the lines of `block`, as written in the IR.
+fn block_lines(bid: BlockId, block: &Block) -> impl Iterator<Item = String> + '_ {
+    let phinodes = block
+        .phinodes
+        .iter()
+        .enumerate()
+        .map(move |(aid, phinode)| phinode_line(bid, aid, phinode));
+    let instructions = block
+        .instructions
+        .iter()
+        .enumerate()
+        .map(move |(iid, instruction)| instruction_line(bid, iid, instruction));
+    phinodes
+        .chain(instructions)
+        .chain(std::iter::once(format!("  {}", block.exit)))
+}
+
+fn phinode_line(bid: BlockId, aid: usize, phinode: &Named<Dtype>) -> String {
+    format!(
+        "  {}:{}{}",
+        RegisterId::arg(bid, aid),
+        phinode.deref(),
+        phinode
+            .name()
+            .map_or(String::new(), |name| format!(":{name}"))
+    )
+}
+
+fn instruction_line(bid: BlockId, iid: usize, instruction: &Named<Instruction>) -> String {
+    format!(
+        "  {}:{}{} = {}",
+        RegisterId::temp(bid, iid),
+        instruction.dtype(),
+        instruction
+            .name()
+            .map_or(String::new(), |name| format!(":{name}")),
+        instruction.deref()
+    )
+}
+
+/// Returns the text of `instruction` with the registers of the instructions and allocations it
+/// uses erased.
+fn shape(instruction: &Named<Instruction>) -> String {
+    let mut instruction = instruction.deref().clone();
+    for operand in instruction.operands_mut() {
+        let (rid, _) = some_or!(operand.get_register_mut(), continue);
+        if matches!(rid, RegisterId::Temp { .. } | RegisterId::Local { .. }) {
+            *rid = RegisterId::local(usize::MAX);
+        }
+    }
+    format!("{}:{}", instruction.dtype(), instruction)
+}
+
+fn rename(instruction: &Instruction, renames: &HashMap<RegisterId, RegisterId>) -> Instruction {
+    let mut instruction = instruction.clone();
+    instruction.walk_operands_mut(|operand| rename_operand(operand, renames));
+    instruction
+}
+
+fn rename_operand(operand: &mut Operand, renames: &HashMap<RegisterId, RegisterId>) {
+    let (rid, _) = some_or!(operand.get_register_mut(), return);
+    if let Some(new_rid) = renames.get(rid) {
+        *rid = *new_rid;
+    }
+}
+
+/// Pairs the elements removed and added in place of each other in `alignment`, in order, as long
+/// as `is_changed` holds for them, so that they are reported as changed.
+fn pair_changed<F>(
+    alignment: Vec<(Option<usize>, Option<usize>)>,
+    is_changed: F,
+) -> Vec<(Option<usize>, Option<usize>)>
+where
+    F: Fn(usize, usize) -> bool,
+{
+    let mut paired = Vec::new();
+    let mut rest = alignment.as_slice();
+    while !rest.is_empty() {
+        let len = rest
+            .iter()
+            .position(|(lhs, rhs)| lhs.is_some() && rhs.is_some())
+            .unwrap_or(rest.len());
+        if len == 0 {
+            paired.push(rest[0]);
+            rest = &rest[1..];
+            continue;
+        }
+
+        let (run, next) = rest.split_at(len);
+        let removed = run.iter().filter_map(|(lhs, _)| *lhs).collect::<Vec<_>>();
+        let added = run.iter().filter_map(|(_, rhs)| *rhs).collect::<Vec<_>>();
+        let changed = izip!(&removed, &added)
+            .take_while(|(lhs, rhs)| is_changed(**lhs, **rhs))
+            .count();
+        paired.extend(
+            izip!(&removed, &added)
+                .take(changed)
+                .map(|(lhs, rhs)| (Some(*lhs), Some(*rhs))),
+        );
+        paired.extend(removed[changed..].iter().map(|lhs| (Some(*lhs), None)));
+        paired.extend(added[changed..].iter().map(|rhs| (None, Some(*rhs))));
+        rest = next;
+    }
+
+    paired
+}
+
+/// Aligns `lhs` and `rhs` by their longest common subsequence, returning the pairs of the indices
+/// of the equal elements and the indices of the others paired with `None`, in order.
+fn align(lhs: &[String], rhs: &[String]) -> Vec<(Option<usize>, Option<usize>)> {
+    // `lengths[i][j]` is the length of the longest common subsequence of `lhs[i..]` and `rhs[j..]`.
+    let mut lengths = vec![vec![0; rhs.len() + 1]; lhs.len() + 1];
+    for i in (0..lhs.len()).rev() {
+        for j in (0..rhs.len()).rev() {
+            lengths[i][j] = if lhs[i] == rhs[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut alignment = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < lhs.len() || j < rhs.len() {
+        if i < lhs.len() && j < rhs.len() && lhs[i] == rhs[j] {
+            alignment.push((Some(i), Some(j)));
+            i += 1;
+            j += 1;
+        } else if j == rhs.len() || (i < lhs.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            alignment.push((Some(i), None));
+            i += 1;
+        } else {
+            alignment.push((None, Some(j)));
+            j += 1;
+        }
+    }
+
+    alignment
+}
@@ -1,16 +1,18 @@
 use core::convert::TryFrom;
 use core::fmt;
+use core::iter;
 use core::ops::Deref;
 use lang_c::ast;
 use lang_c::span::Node;
 use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
+use std::sync::Arc;
 use thiserror::Error;
 
-use itertools::izip;
+use itertools::{izip, Itertools};
 
 use crate::ir::*;
-use crate::some_or;
+use crate::{char_signedness, max_nesting_depth, some_or, target_data, CharSignedness};
 
 /// TODO(document)
 #[derive(Debug, PartialEq, Eq, Error)]
@@ -21,6 +23,23 @@ pub enum DtypeError {
         /// TODO(document)
         message: String,
     },
+
+    /// A type whose size is required, e.g., the operand of `sizeof`, is incomplete.
+    #[error("incomplete type `{dtype}` used as {usage}")]
+    IncompleteType { dtype: Dtype, usage: String },
+
+    /// The type is nested more deeply than [`max_nesting_depth`](crate::max_nesting_depth).
+    #[error("type is too complex: nested more than {limit} levels")]
+    TooComplex { limit: usize },
+
+    /// The width of a bit-field is not an integer constant within the bits of its integer type.
+    #[error("invalid bit-field `{field}`: {reason}")]
+    InvalidBitField { field: String, reason: String },
+
+    /// A member is accessed by a name the struct type, including its anonymous members, does not
+    /// declare.
+    #[error("`{dtype}` has no member named `{field}`")]
+    NoSuchMember { dtype: Dtype, field: String },
 }
 
 /// TODO(document)
@@ -29,6 +48,21 @@ pub trait HasDtype {
     fn dtype(&self) -> Dtype;
 }
 
+/// The storage class given to a declaration by its specifiers.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum DeclarationStorage {
+    /// No storage class, e.g., `int x;`, which defines the variable
+    #[default]
+    Unspecified,
+    /// `typedef`, which declares type names instead of objects
+    Typedef,
+    /// `extern`, which declares variables defined in another unit unless they are initialized
+    Extern,
+    /// `static`, which gives internal linkage to the variables at file scope, and static storage
+    /// duration to those in function bodies
+    Static,
+}
+
 #[derive(Default)]
 struct BaseDtype {
     scalar: Option<ast::TypeSpecifier>,
@@ -36,8 +70,10 @@ struct BaseDtype {
     signed_option: Option<ast::TypeSpecifier>,
     typedef_name: Option<String>,
     struct_type: Option<ast::StructType>,
+    enum_type: Option<ast::EnumType>,
     is_const: bool,
-    is_typedef: bool,
+    is_restrict: bool,
+    storage: DeclarationStorage,
 }
 
 /// TODO(document)
@@ -69,21 +105,33 @@ pub enum Dtype {
     },
     /// TODO(document)
     Pointer {
-        /// TODO(document)
-        inner: Box<Dtype>,
+        /// The pointee, shared with the clones of the type, as are the elements of the arrays and
+        /// the return types of the functions.
+        inner: Arc<Dtype>,
 
         /// TODO(document)
         is_const: bool,
+
+        /// Whether the pointer is `restrict`-qualified. It is kept for the IR to round-trip, and
+        /// tells the optimizer that the parameters of this type do not alias the other pointers.
+        is_restrict: bool,
     },
     /// TODO(document)
     Array {
         /// TODO(document)
-        inner: Box<Dtype>,
+        inner: Arc<Dtype>,
 
-        /// TODO(document)
-        size: usize,
+        /// The number of elements, or `None` if the array type is incomplete, e.g., the type of
+        /// `table` in `extern char table[];`.
+        size: Option<usize>,
+
+        /// The alignment required by `_Alignas`, e.g., 16 for `_Alignas(16) char buf[64]`, if it
+        /// overrides the one of `inner`.
+        align: Option<usize>,
     },
-    /// TODO(document)
+    /// A struct or union. A tag names a single definition in a translation unit, kept in its
+    /// struct table, so that the types referring to it by its tag, without its fields, are
+    /// complete once it is defined. See [`Dtype::is_same_type`] for comparing them.
     Struct {
         /// TODO(document)
         name: Option<String>,
@@ -91,6 +139,12 @@ pub enum Dtype {
         /// TODO(document)
         fields: Option<Vec<Named<Dtype>>>,
 
+        /// Whether the type is a union, whose fields all start at offset 0.
+        is_union: bool,
+
+        /// The bit-fields among `fields` by their indices, or empty if there is none.
+        bit_fields: Vec<Option<BitField>>,
+
         /// TODO(document)
         is_const: bool,
 
@@ -100,7 +154,7 @@ pub enum Dtype {
     /// TODO(document)
     Function {
         /// TODO(document)
-        ret: Box<Dtype>,
+        ret: Arc<Dtype>,
 
         /// TODO(document)
         params: Vec<Dtype>,
@@ -112,9 +166,36 @@ pub enum Dtype {
 
         /// TODO(document)
         is_const: bool,
+
+        /// Whether the type is `restrict`-qualified, which is kept when the typedef is resolved to
+        /// a pointer type.
+        is_restrict: bool,
+    },
+    /// An enumerated type, laid out as `int`. Like typedefs, it is replaced by `int` when the
+    /// typedefs are resolved.
+    Enum {
+        /// The tag of the type, if any
+        name: Option<String>,
+
+        /// The enumerators with their values, if the type is defined here
+        enumerators: Option<Vec<(String, i128)>>,
+
+        /// TODO(document)
+        is_const: bool,
     },
 }
 
+/// A bit-field of a struct, e.g., `unsigned mode : 3`, stored in the bits of the integer of its
+/// type at the offset of the field, i.e., its storage unit.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct BitField {
+    /// The number of bits of the field
+    pub width: usize,
+    /// The offset of the first bit of the field in its storage unit, from the least significant
+    /// bit. It is 0 until the layout of the struct is computed.
+    pub bit_offset: usize,
+}
+
 impl BaseDtype {
     /// Apply `StorageClassSpecifier` to `BaseDtype`.
     ///
@@ -130,16 +211,26 @@ impl BaseDtype {
         &mut self,
         storage_class: &ast::StorageClassSpecifier,
     ) -> Result<(), DtypeError> {
-        match storage_class {
-            ast::StorageClassSpecifier::Typedef => {
-                // duplicate `typedef` is allowed
-                self.is_typedef = true;
-                Ok(())
-            }
-            scs => Err(DtypeError::Misc {
-                message: format!("unsupported storage class specifier: {scs:#?}"),
-            }),
+        let storage = match storage_class {
+            ast::StorageClassSpecifier::Typedef => DeclarationStorage::Typedef,
+            // The linkage is not a part of the type, see `Irgen::add_declaration`.
+            ast::StorageClassSpecifier::Extern => DeclarationStorage::Extern,
+            ast::StorageClassSpecifier::Static => DeclarationStorage::Static,
+            scs => {
+                return Err(DtypeError::Misc {
+                    message: format!("unsupported storage class specifier: {scs:#?}"),
+                })
+            }
+        };
+
+        // a duplicate storage class is allowed, but not two different ones
+        if self.storage != DeclarationStorage::Unspecified && self.storage != storage {
+            return Err(DtypeError::Misc {
+                message: "multiple storage classes in declaration specifiers".to_string(),
+            });
         }
+        self.storage = storage;
+        Ok(())
     }
 
     /// Apply `TypeSpecifier` to `BaseDtype`.
@@ -197,7 +288,25 @@ impl BaseDtype {
                 }
                 self.struct_type = Some(struct_type.node.clone());
             }
-            _ => todo!("apply_type_specifier: support {:?}", type_specifier),
+            ast::TypeSpecifier::Enum(enum_type) => {
+                if self.enum_type.is_some() {
+                    return Err(DtypeError::Misc {
+                        message: "two or more enum types in declaration specifiers".to_string(),
+                    });
+                }
+                self.enum_type = Some(enum_type.node.clone());
+            }
+            ast::TypeSpecifier::Complex => {
+                return Err(DtypeError::Misc {
+                    message: "`_Complex` is not supported: complex types are not implemented"
+                        .to_string(),
+                });
+            }
+            ts => {
+                return Err(DtypeError::Misc {
+                    message: format!("unsupported type specifier: {ts:#?}"),
+                })
+            }
         }
 
         Ok(())
@@ -222,6 +331,10 @@ impl BaseDtype {
                 // duplicate `const` is allowed
                 self.is_const = true;
             }
+            ast::TypeQualifier::Restrict => {
+                // duplicate `restrict` is allowed
+                self.is_restrict = true;
+            }
             tq => {
                 return Err(DtypeError::Misc {
                     message: format!("unsupported typq qualifier: {tq:#?}"),
@@ -266,6 +379,9 @@ impl BaseDtype {
             ast::DeclarationSpecifier::TypeQualifier(type_qualifier) => {
                 self.apply_type_qualifier(&type_qualifier.node)?
             }
+            // The alignment is not a part of the base type, but of the declared object, see
+            // `Dtype::align_of_ast_declaration_specifiers`.
+            ast::DeclarationSpecifier::Alignment(_) => (),
             ds => {
                 return Err(DtypeError::Misc {
                     message: format!("unsupported declaration qualifier: {ds:#?}"),
@@ -338,28 +454,34 @@ impl TryFrom<BaseDtype> for Dtype {
     /// For declaration is `const unsigned int * p`, `specifiers` is `const unsigned int`, and the
     /// result is `Dtype::Int { width: 4, is_signed: false, is_const: true }`.
     fn try_from(spec: BaseDtype) -> Result<Self, DtypeError> {
-        assert!(
-            !(spec.scalar.is_none()
-                && spec.size_modifiers.is_empty()
-                && spec.signed_option.is_none()
-                && spec.typedef_name.is_none()
-                && spec.struct_type.is_none()
-                && !spec.is_const),
-            "BaseDtype is empty"
-        );
+        if spec.scalar.is_none()
+            && spec.size_modifiers.is_empty()
+            && spec.signed_option.is_none()
+            && spec.typedef_name.is_none()
+            && spec.struct_type.is_none()
+            && spec.enum_type.is_none()
+            && !spec.is_const
+        {
+            return Err(DtypeError::Misc {
+                message: "no type specifier in declaration specifiers".to_string(),
+            });
+        }
 
         if let Some(name) = spec.typedef_name {
             if !(spec.scalar.is_none()
                 && spec.size_modifiers.is_empty()
                 && spec.signed_option.is_none()
-                && spec.struct_type.is_none())
+                && spec.struct_type.is_none()
+                && spec.enum_type.is_none())
             {
                 return Err(DtypeError::Misc {
                     message: "`typedef` can only be used with `const`".to_string(),
                 });
             }
 
-            let dtype = Self::typedef(name).set_const(spec.is_const);
+            let dtype = Self::typedef(name)
+                .set_const(spec.is_const)
+                .set_restrict(spec.is_restrict);
 
             return Ok(dtype);
         }
@@ -368,7 +490,8 @@ impl TryFrom<BaseDtype> for Dtype {
             if !(spec.scalar.is_none()
                 && spec.size_modifiers.is_empty()
                 && spec.signed_option.is_none()
-                && spec.typedef_name.is_none())
+                && spec.typedef_name.is_none()
+                && spec.enum_type.is_none())
             {
                 return Err(DtypeError::Misc {
                     message: "`struct` can only be used with `const`".to_string(),
@@ -376,30 +499,57 @@ impl TryFrom<BaseDtype> for Dtype {
             }
 
             assert!(struct_type.identifier.is_some() || struct_type.declarations.is_some());
-            assert_eq!(struct_type.kind.node, ast::StructKind::Struct);
+            let is_union = struct_type.kind.node == ast::StructKind::Union;
             let struct_name = struct_type.identifier.map(|i| i.node.name);
-            let fields = if let Some(declarations) = struct_type.declarations {
-                let fields = declarations
+            let (fields, bit_widths) = if let Some(declarations) = struct_type.declarations {
+                let (fields, bit_widths) = declarations
                     .iter()
                     .map(|d| Self::try_from_ast_struct_declaration(&d.node))
                     .collect::<Result<Vec<_>, _>>()?
-                    .concat();
+                    .concat()
+                    .into_iter()
+                    .unzip();
 
-                Some(fields)
+                (Some(fields), bit_widths)
             } else {
-                None
+                (None, Vec::new())
             };
 
             if let Some(fields) = &fields {
-                let mut field_names = HashSet::new();
-                if !check_no_duplicate_field(fields, &mut field_names) {
+                if let Some(name) = duplicate_field(fields, &mut HashSet::new()) {
                     return Err(DtypeError::Misc {
-                        message: "struct has duplicate field name".to_string(),
+                        message: format!("struct has duplicate member `{name}`"),
                     });
                 }
             }
 
-            let dtype = Self::structure(struct_name, fields).set_const(spec.is_const);
+            let dtype = Self::aggregate(is_union, struct_name, fields)
+                .with_bit_widths(bit_widths)
+                .set_const(spec.is_const);
+
+            return Ok(dtype);
+        }
+
+        if let Some(enum_type) = spec.enum_type {
+            if !(spec.scalar.is_none()
+                && spec.size_modifiers.is_empty()
+                && spec.signed_option.is_none()
+                && spec.typedef_name.is_none())
+            {
+                return Err(DtypeError::Misc {
+                    message: "`enum` can only be used with `const`".to_string(),
+                });
+            }
+
+            let enum_name = enum_type.identifier.as_ref().map(|i| i.node.name.clone());
+            // An enum type is defined by a nonempty list of enumerators.
+            let enumerators = if enum_type.enumerators.is_empty() {
+                None
+            } else {
+                Some(enumerator_values(&enum_type)?)
+            };
+
+            let dtype = Self::enumeration(enum_name, enumerators).set_const(spec.is_const);
 
             return Ok(dtype);
         }
@@ -409,7 +559,10 @@ impl TryFrom<BaseDtype> for Dtype {
             match t {
                 ast::TypeSpecifier::Void => Self::unit(),
                 ast::TypeSpecifier::Bool => Self::BOOL,
-                ast::TypeSpecifier::Char => Self::CHAR,
+                // `signed` and `unsigned` below override the signedness of plain `char`.
+                ast::TypeSpecifier::Char => {
+                    Self::CHAR.set_signed(char_signedness() == CharSignedness::Signed)
+                }
                 ast::TypeSpecifier::Int => Self::INT,
                 ast::TypeSpecifier::Float => Self::FLOAT,
                 ast::TypeSpecifier::Double => Self::DOUBLE,
@@ -422,9 +575,31 @@ impl TryFrom<BaseDtype> for Dtype {
         let number_of_modifier = spec.size_modifiers.len();
         dtype = match number_of_modifier {
             0 => dtype,
+            // `long double` is the only floating point type with a size modifier.
+            _ if dtype.get_float_width().is_some() => {
+                if dtype != Self::DOUBLE || spec.size_modifiers != [ast::TypeSpecifier::Long] {
+                    let name = spec
+                        .size_modifiers
+                        .iter()
+                        .map(|modifier| match modifier {
+                            ast::TypeSpecifier::Short => "short",
+                            _ => "long",
+                        })
+                        .chain([if dtype == Self::FLOAT {
+                            "float"
+                        } else {
+                            "double"
+                        }])
+                        .join(" ");
+                    return Err(DtypeError::Misc {
+                        message: format!("`{name}` is not a floating point type"),
+                    });
+                }
+                Self::LONGDOUBLE
+            }
             1 => match spec.size_modifiers[0] {
                 ast::TypeSpecifier::Short => Self::SHORT,
-                ast::TypeSpecifier::Long => Self::LONG,
+                ast::TypeSpecifier::Long => Self::long(),
                 _ => panic!(
                     "Dtype::try_from::<BaseDtype>: {:?} is not a size modifier",
                     spec.size_modifiers
@@ -466,7 +641,10 @@ impl TryFrom<BaseDtype> for Dtype {
             dtype = dtype.set_signed(is_signed);
         }
 
-        dtype = dtype.set_const(spec.is_const);
+        // `restrict` only qualifies pointers, e.g., `restrict intptr p` for a typedef of `int *`.
+        dtype = dtype
+            .set_const(spec.is_const)
+            .set_restrict(spec.is_restrict);
 
         Ok(dtype)
     }
@@ -500,14 +678,7 @@ impl TryFrom<&ast::ParameterDeclaration> for Dtype {
         if let Some(declarator) = &parameter_decl.declarator {
             dtype = dtype.with_ast_declarator(&declarator.node)?.into_inner();
 
-            // A function call with an array argument performs array-to-pointer conversion. For this
-            // reason, when `declarator` is from function parameter declaration and `base_dtype` is
-            // `Dtype::Array`, `base_dtype` is transformed to pointer type.
-            //
-            // For more information: <https://www.eskimo.com/~scs/cclass/notes/sx10f.html>
-            if let Some(inner) = dtype.get_array_inner() {
-                dtype = Self::pointer(inner.clone());
-            }
+            dtype = dtype.adjust_parameter();
         }
         Ok(dtype)
     }
@@ -520,10 +691,6 @@ impl Dtype {
     /// TODO(document)
     pub const SIZE_OF_BYTE: usize = 1;
 
-    /// TODO(document)
-    // TODO: consider architecture dependency (current: 64-bit architecture)
-    pub const SIZE_OF_POINTER: usize = 8;
-
     /// TODO(document)
     pub const SIZE_OF_CHAR: usize = 1;
 
@@ -533,9 +700,6 @@ impl Dtype {
     /// TODO(document)
     pub const SIZE_OF_INT: usize = 4;
 
-    /// TODO(document)
-    pub const SIZE_OF_LONG: usize = 8;
-
     /// TODO(document)
     pub const SIZE_OF_LONGLONG: usize = 8;
 
@@ -545,6 +709,10 @@ impl Dtype {
     /// TODO(document)
     pub const SIZE_OF_DOUBLE: usize = 8;
 
+    /// The size of `long double`, which is that of `double`, as the 128-bit floating point numbers
+    /// of RISC-V are not supported.
+    pub const SIZE_OF_LONGDOUBLE: usize = 8;
+
     /// TODO(document)
     /// A boolean value cannot be signed.
     pub const BOOL: Self = Self::Int {
@@ -562,8 +730,9 @@ impl Dtype {
     /// TODO(document)
     pub const INT: Self = Self::int(Self::SIZE_OF_INT * Self::BITS_OF_BYTE);
 
-    /// TODO(document)
-    pub const LONG: Self = Self::int(Self::SIZE_OF_LONG * Self::BITS_OF_BYTE);
+    /// The 64-bit integer, e.g., of the offsets of `getelementptr`. Unlike it, `long` in the C
+    /// source follows the target data (see [`Dtype::long`]).
+    pub const LONG: Self = Self::int(Self::SIZE_OF_LONGLONG * Self::BITS_OF_BYTE);
 
     /// TODO(document)
     pub const LONGLONG: Self = Self::int(Self::SIZE_OF_LONGLONG * Self::BITS_OF_BYTE);
@@ -574,6 +743,16 @@ impl Dtype {
     /// TODO(document)
     pub const DOUBLE: Self = Self::float(Self::SIZE_OF_DOUBLE * Self::BITS_OF_BYTE);
 
+    /// `long double`, which is as wide as `double` (see [`Dtype::SIZE_OF_LONGDOUBLE`]).
+    pub const LONGDOUBLE: Self = Self::float(Self::SIZE_OF_LONGDOUBLE * Self::BITS_OF_BYTE);
+
+    /// Returns `long` of the target data of the current thread, e.g., 32 bits on RV32 (see
+    /// [`TargetData`](crate::TargetData)).
+    #[inline]
+    pub fn long() -> Self {
+        Self::int(target_data().long_size * Self::BITS_OF_BYTE)
+    }
+
     /// TODO(document)
     #[inline]
     pub const fn unit() -> Self {
@@ -603,8 +782,9 @@ impl Dtype {
     #[inline]
     pub fn pointer(inner: Dtype) -> Self {
         Self::Pointer {
-            inner: Box::new(inner),
+            inner: Arc::new(inner),
             is_const: false,
+            is_restrict: false,
         }
     }
 
@@ -617,36 +797,119 @@ impl Dtype {
     /// the order of `2` and `3`.  In the recursive translation of a declaration into Dtype, we
     /// need to insert `3` inside `[2 * int*]`.
     pub fn array(base_dtype: Dtype, size: usize) -> Self {
-        match base_dtype {
+        Self::array_of(base_dtype, Some(size))
+    }
+
+    /// Returns the incomplete array type of `base_dtype`, e.g., `[? x i8]` of `extern char
+    /// table[];`, which is completed by [`Dtype::with_array_size`].
+    #[inline]
+    pub fn incomplete_array(base_dtype: Dtype) -> Self {
+        Self::array_of(base_dtype, None)
+    }
+
+    /// Returns the array type of `size` elements of `base_dtype`, or the incomplete one if `size`
+    /// is `None` (see [`Dtype::array`]).
+    fn array_of(base_dtype: Dtype, size: Option<usize>) -> Self {
+        // Iterative, since `base_dtype` may be a deeply nested array.
+        let mut sizes = Vec::new();
+        let mut inner = base_dtype;
+        while let Self::Array {
+            inner: next,
+            size: old_size,
+            align,
+        } = inner
+        {
+            sizes.push((old_size, align));
+            inner = Arc::unwrap_or_clone(next);
+        }
+
+        if let Self::Function { .. } = inner {
+            panic!("array size cannot be applied to function type");
+        }
+
+        sizes.into_iter().rev().fold(
             Self::Array {
-                inner,
-                size: old_size,
-            } => {
-                let inner = Self::array(*inner, size);
-                Self::Array {
-                    inner: Box::new(inner),
-                    size: old_size,
-                }
-            }
-            Self::Function { .. } => panic!("array size cannot be applied to function type"),
-            inner => Self::Array {
-                inner: Box::new(inner),
+                inner: Arc::new(inner),
                 size,
+                align: None,
             },
-        }
+            |inner, (size, align)| Self::Array {
+                inner: Arc::new(inner),
+                size,
+                align,
+            },
+        )
     }
 
     /// TODO(document)
     #[inline]
     pub fn structure(name: Option<String>, fields: Option<Vec<Named<Self>>>) -> Self {
+        Self::aggregate(false, name, fields)
+    }
+
+    /// Returns the union type `name` with the members `fields`.
+    #[inline]
+    pub fn union(name: Option<String>, fields: Option<Vec<Named<Self>>>) -> Self {
+        Self::aggregate(true, name, fields)
+    }
+
+    /// Returns the struct type, or the union type if `is_union`, `name` with the members `fields`.
+    #[inline]
+    pub fn aggregate(
+        is_union: bool,
+        name: Option<String>,
+        fields: Option<Vec<Named<Self>>>,
+    ) -> Self {
         Self::Struct {
             name,
             fields,
+            is_union,
+            bit_fields: Vec::new(),
             is_const: false,
             size_align_offsets: None,
         }
     }
 
+    /// Declares the fields of the struct type given a width in `bit_widths`, by their indices, as
+    /// bit-fields of that many bits.
+    #[must_use]
+    pub fn with_bit_widths(self, bit_widths: Vec<Option<usize>>) -> Self {
+        if let Self::Struct {
+            name,
+            fields,
+            is_union,
+            is_const,
+            size_align_offsets,
+            ..
+        } = self
+        {
+            let bit_fields = if bit_widths.iter().all(Option::is_none) {
+                Vec::new()
+            } else {
+                bit_widths
+                    .into_iter()
+                    .map(|width| {
+                        width.map(|width| BitField {
+                            width,
+                            bit_offset: 0,
+                        })
+                    })
+                    .collect()
+            };
+
+            Self::Struct {
+                name,
+                fields,
+                is_union,
+                bit_fields,
+                is_const,
+                size_align_offsets,
+            }
+        } else {
+            panic!("bit-fields can only be declared in `Dtype::Struct`")
+        }
+    }
+
     pub fn fill_size_align_offsets_of_struct(
         self,
         structs: &HashMap<String, Option<Dtype>>,
@@ -654,6 +917,8 @@ impl Dtype {
         if let Self::Struct {
             name,
             fields,
+            is_union,
+            mut bit_fields,
             is_const,
             size_align_offsets,
         } = self
@@ -663,46 +928,98 @@ impl Dtype {
             );
 
             let fields = fields.unwrap();
+            if let Some(field) = fields.iter().find(|field| !field.is_complete(structs)) {
+                return Err(DtypeError::IncompleteType {
+                    dtype: field.deref().clone(),
+                    usage: format!("field `{}`", field.name().map_or("%anon", String::as_str)),
+                });
+            }
+            for (field, bit_field) in izip!(&fields, &bit_fields) {
+                let bit_field = some_or!(bit_field, continue);
+                check_bit_field(field, bit_field.width)?;
+            }
+
             if fields.is_empty() {
                 return Ok(Self::Struct {
                     name,
                     fields: Some(fields),
+                    is_union,
+                    bit_fields,
                     is_const,
                     size_align_offsets: Some((0, 1, Vec::new())),
                 });
             }
 
-            let align_of = fields
-                .iter()
-                .map(|f| f.size_align_of(structs))
+            // The unnamed bit-fields, which only pad the struct, do not align it.
+            let align_of = izip!(0.., &fields)
+                .filter(|(index, field)| {
+                    field.name().is_some() || bit_fields.get(*index).map_or(true, Option::is_none)
+                })
+                .map(|(_, f)| f.size_align_of(structs))
                 .collect::<Result<Vec<_>, _>>()?
                 .into_iter()
                 .map(|(_, a)| a)
                 .max()
-                .unwrap_or(0);
+                .unwrap_or(1);
+
+            // The members of a union overlap, so that it is as large as its largest member.
+            if is_union {
+                let size_of = fields
+                    .iter()
+                    .map(|f| f.size_align_of(structs).map(|(size, _)| size))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .into_iter()
+                    .max()
+                    .unwrap_or(0);
+                let size_of = size_of.div_ceil(align_of) * align_of;
+
+                return Ok(Self::Struct {
+                    name,
+                    size_align_offsets: Some((size_of, align_of, vec![0; fields.len()])),
+                    fields: Some(fields),
+                    is_union,
+                    bit_fields,
+                    is_const,
+                });
+            }
 
             let mut offsets = Vec::new();
-            let mut offset = 0;
-            for field in &fields {
+            // The end of the previous field in bits, as a bit-field may end within a byte.
+            let mut end = 0;
+            for (index, field) in fields.iter().enumerate() {
                 let (size_of_dtype, align_of_dtype) = field.size_align_of(structs)?;
 
-                let pad = if (offset % align_of_dtype) != 0 {
-                    align_of_dtype - (offset % align_of_dtype)
-                } else {
-                    0
-                };
+                if let Some(bit_field) = bit_fields.get_mut(index).and_then(Option::as_mut) {
+                    // A bit-field is packed right after the previous field, unless it would
+                    // straddle two storage units of its type. A zero-width one closes the unit.
+                    let unit_bits = size_of_dtype * Self::BITS_OF_BYTE;
+                    let mut start = end;
+                    if bit_field.width == 0
+                        || start / unit_bits != (start + bit_field.width - 1) / unit_bits
+                    {
+                        start = start.div_ceil(unit_bits) * unit_bits;
+                    }
 
-                offset += pad;
-                offsets.push(offset);
+                    let offset = start / unit_bits * size_of_dtype;
+                    offsets.push(offset);
+                    bit_field.bit_offset = start - offset * Self::BITS_OF_BYTE;
+                    end = start + bit_field.width;
+                    continue;
+                }
 
-                offset += size_of_dtype;
+                let offset =
+                    end.div_ceil(Self::BITS_OF_BYTE).div_ceil(align_of_dtype) * align_of_dtype;
+                offsets.push(offset);
+                end = (offset + size_of_dtype) * Self::BITS_OF_BYTE;
             }
 
-            let size_of = ((offset - 1) / align_of + 1) * align_of;
+            let size_of = end.div_ceil(Self::BITS_OF_BYTE).div_ceil(align_of) * align_of;
 
             Ok(Self::Struct {
                 name,
                 fields: Some(fields),
+                is_union,
+                bit_fields,
                 is_const,
                 size_align_offsets: Some((size_of, align_of, offsets)),
             })
@@ -716,7 +1033,7 @@ impl Dtype {
     #[inline]
     pub fn function(ret: Dtype, params: Vec<Dtype>) -> Self {
         Self::Function {
-            ret: Box::new(ret),
+            ret: Arc::new(ret),
             params,
         }
     }
@@ -726,6 +1043,16 @@ impl Dtype {
         Self::Typedef {
             name,
             is_const: false,
+            is_restrict: false,
+        }
+    }
+
+    #[inline]
+    pub fn enumeration(name: Option<String>, enumerators: Option<Vec<(String, i128)>>) -> Self {
+        Self::Enum {
+            name,
+            enumerators,
+            is_const: false,
         }
     }
 
@@ -765,6 +1092,37 @@ impl Dtype {
         }
     }
 
+    /// Returns `true` if `self` is an array type of unknown size (see [`Dtype::incomplete_array`]).
+    #[inline]
+    pub fn is_incomplete_array(&self) -> bool {
+        matches!(self, Self::Array { size: None, .. })
+    }
+
+    /// Returns the array type with `size` elements, e.g., to complete the type of `a` in `int a[]
+    /// = {1, 2, 3}` by the length of its initializer, or `self` if it is not an array.
+    #[inline]
+    pub fn with_array_size(self, size: usize) -> Self {
+        if let Self::Array { inner, align, .. } = self {
+            Self::Array {
+                inner,
+                size: Some(size),
+                align,
+            }
+        } else {
+            self
+        }
+    }
+
+    /// Returns the alignment `_Alignas` requires of the array type, if it is overridden.
+    #[inline]
+    pub fn get_array_align(&self) -> Option<usize> {
+        if let Self::Array { align, .. } = self {
+            *align
+        } else {
+            None
+        }
+    }
+
     #[inline]
     pub fn get_struct_name(&self) -> Option<&Option<String>> {
         if let Self::Struct { name, .. } = self {
@@ -774,6 +1132,21 @@ impl Dtype {
         }
     }
 
+    #[inline]
+    pub fn is_union(&self) -> bool {
+        matches!(self, Self::Struct { is_union: true, .. })
+    }
+
+    /// Returns the keyword introducing the struct type in C, i.e., `union` for a union.
+    #[inline]
+    pub fn get_struct_keyword(&self) -> Option<&'static str> {
+        if let Self::Struct { is_union, .. } = self {
+            Some(if *is_union { "union" } else { "struct" })
+        } else {
+            None
+        }
+    }
+
     #[inline]
     pub fn get_struct_fields(&self) -> Option<&Option<Vec<Named<Self>>>> {
         if let Self::Struct { fields, .. } = self {
@@ -795,6 +1168,26 @@ impl Dtype {
         }
     }
 
+    /// Returns the bit-field declared by the `index`th field of the struct type, if any.
+    #[inline]
+    pub fn get_struct_bit_field(&self, index: usize) -> Option<BitField> {
+        if let Self::Struct { bit_fields, .. } = self {
+            bit_fields.get(index).copied().flatten()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the enumerators of the enum type with their values, if it is defined here.
+    #[inline]
+    pub fn get_enumerators(&self) -> Option<&Vec<(String, i128)>> {
+        if let Self::Enum { enumerators, .. } = self {
+            enumerators.as_ref()
+        } else {
+            None
+        }
+    }
+
     #[inline]
     pub fn get_function_inner(&self) -> Option<(&Self, &Vec<Self>)> {
         if let Self::Function { ret, params } = self {
@@ -821,59 +1214,229 @@ impl Dtype {
         }
     }
 
-    pub fn is_const(&self) -> bool {
-        match self {
-            Self::Unit { is_const }
-            | Self::Int { is_const, .. }
-            | Self::Float { is_const, .. }
-            | Self::Typedef { is_const, .. }
-            | Self::Pointer { is_const, .. }
-            | Self::Struct { is_const, .. } => *is_const,
-            Self::Function { .. } | Self::Array { .. } => true,
+    /// Returns the type of the integer `self` after the integer promotions: `int` for the integers
+    /// narrower than `int`, including `_Bool`, as `int` holds all their values whether they are
+    /// signed or not, and `self` for the others.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is not `Dtype::Int`.
+    pub fn promote_integer(&self) -> Self {
+        let width = self
+            .get_int_width()
+            .expect("only `Dtype::Int` can be promoted");
+        if width < Self::SIZE_OF_INT * Self::BITS_OF_BYTE {
+            Self::INT
+        } else {
+            self.clone()
         }
     }
 
-    #[inline]
-    /// Check if `Dtype` is constant. if it is constant, the variable of `Dtype` is not assignable.
-    pub fn is_immutable(&self, structs: &HashMap<String, Option<Dtype>>) -> bool {
+    /// Checks whether the size of the type is known, given the struct definitions in `structs`.
+    ///
+    /// `void`, functions, and the structs only declared by `struct Foo;` are incomplete, as well as
+    /// the arrays of them. Pointers to incomplete types are complete.
+    pub fn is_complete(&self, structs: &HashMap<String, Option<Dtype>>) -> bool {
         match self {
-            Self::Unit { is_const }
-            | Self::Int { is_const, .. }
-            | Self::Float { is_const, .. }
-            | Self::Pointer { is_const, .. } => *is_const,
-            Self::Array { .. } | Self::Function { .. } => true,
-            Self::Struct { name, is_const, .. } => {
-                let name = name.as_ref().expect("`name` must be exist");
-                let struct_type = structs
-                    .get(name)
-                    .expect("struct type matched with `name` must exist")
-                    .as_ref()
-                    .expect("`struct_type` must have its definition");
-                let fields = struct_type
-                    .get_struct_fields()
-                    .expect("`struct_type` must be struct type")
-                    .as_ref()
-                    .expect("`fields` must be `Some`");
-
-                *is_const
-                    // If any of the fields in the structure type is constant, return `true`.
-                    || fields
-                        .iter()
-                        .any(|f| {
-                        // If an array is wrapped in a struct and the array's inner type is not
-                        // constant, it is assignable to another object of the same struct type.
-                        if let Self::Array { inner, .. } = f.deref() {
-                            inner.is_immutable_for_array_struct_field_inner(structs)
-                        } else {
-                            f.deref().is_immutable(structs)
-                        }
-                    })
+            Self::Int { .. } | Self::Float { .. } | Self::Pointer { .. } | Self::Enum { .. } => {
+                true
+            }
+            Self::Unit { .. } | Self::Function { .. } | Self::Typedef { .. } => false,
+            Self::Array { inner, size, .. } => size.is_some() && inner.is_complete(structs),
+            Self::Struct { name, fields, .. } => {
+                fields.is_some()
+                    || name
+                        .as_ref()
+                        .and_then(|name| structs.get(name))
+                        .map_or(false, Option::is_some)
             }
-            Self::Typedef { .. } => panic!("typedef should be replaced by real dtype"),
         }
     }
 
-    fn is_immutable_for_array_struct_field_inner(
+    /// Returns the number of types nested in the type, e.g., 3 for `int **`.
+    ///
+    /// Computed iteratively, so that it can check whether a type is too deeply nested to be
+    /// processed recursively.
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut stack = vec![(self, 1)];
+        while let Some((dtype, level)) = stack.pop() {
+            depth = depth.max(level);
+            stack.extend(
+                dtype
+                    .inner_dtypes()
+                    .into_iter()
+                    .map(|inner| (inner, level + 1)),
+            );
+        }
+        depth
+    }
+
+    /// Returns the types directly nested in the type: the pointee, the element, the fields, or the
+    /// return type followed by the parameters. A struct referring to the struct table by its tag
+    /// has no fields here.
+    pub fn inner_dtypes(&self) -> Vec<&Self> {
+        match self {
+            Self::Pointer { inner, .. } | Self::Array { inner, .. } => vec![inner.deref()],
+            Self::Struct {
+                fields: Some(fields),
+                ..
+            } => fields.iter().map(Deref::deref).collect(),
+            Self::Function { ret, params } => iter::once(ret.deref()).chain(params).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the type with the types directly nested in it replaced by `f`, in the order of
+    /// [`Dtype::inner_dtypes`], keeping the qualifiers, the sizes and the names of the fields. The
+    /// layout of a struct whose fields are replaced is dropped, as it may change.
+    ///
+    /// A transformation of every nested type calls itself in `f`, e.g., `resolve_typedefs`.
+    pub fn map_inner<E>(self, mut f: impl FnMut(Self) -> Result<Self, E>) -> Result<Self, E> {
+        let dtype = match self {
+            Self::Pointer {
+                inner,
+                is_const,
+                is_restrict,
+            } => Self::Pointer {
+                inner: Arc::new(f(Arc::unwrap_or_clone(inner))?),
+                is_const,
+                is_restrict,
+            },
+            Self::Array { inner, size, align } => Self::Array {
+                inner: Arc::new(f(Arc::unwrap_or_clone(inner))?),
+                size,
+                align,
+            },
+            Self::Struct {
+                name,
+                fields: Some(fields),
+                is_union,
+                bit_fields,
+                is_const,
+                ..
+            } => {
+                let fields = fields
+                    .into_iter()
+                    .map(|field| {
+                        let (dtype, name) = field.destruct();
+                        Ok(Named::new(name, f(dtype)?))
+                    })
+                    .collect::<Result<_, _>>()?;
+                Self::Struct {
+                    name,
+                    fields: Some(fields),
+                    is_union,
+                    bit_fields,
+                    is_const,
+                    size_align_offsets: None,
+                }
+            }
+            Self::Function { ret, params } => {
+                let ret = f(Arc::unwrap_or_clone(ret))?;
+                let params = params.into_iter().map(&mut f).collect::<Result<_, _>>()?;
+                Self::function(ret, params)
+            }
+            dtype => dtype,
+        };
+
+        Ok(dtype)
+    }
+
+    /// Returns `true` if `predicate` holds for the type or a type nested in it, without following
+    /// the tags of the structs to the struct table.
+    pub fn any_nested(&self, mut predicate: impl FnMut(&Self) -> bool) -> bool {
+        let mut stack = vec![self];
+        while let Some(dtype) = stack.pop() {
+            if predicate(dtype) {
+                return true;
+            }
+            stack.extend(dtype.inner_dtypes());
+        }
+        false
+    }
+
+    /// Returns `true` if the type is or contains a struct or union, e.g., `struct S *[4]`.
+    pub fn contains_struct(&self) -> bool {
+        self.any_nested(|dtype| matches!(dtype, Self::Struct { .. }))
+    }
+
+    /// Returns `true` if the type is or contains a pointer to a function, e.g., a parameter of type
+    /// `int (*)(int)`.
+    pub fn contains_function_pointer(&self) -> bool {
+        self.any_nested(|dtype| {
+            dtype
+                .get_pointer_inner()
+                .map_or(false, |inner| matches!(inner, Self::Function { .. }))
+        })
+    }
+
+    pub fn is_const(&self) -> bool {
+        match self {
+            Self::Unit { is_const }
+            | Self::Int { is_const, .. }
+            | Self::Float { is_const, .. }
+            | Self::Typedef { is_const, .. }
+            | Self::Enum { is_const, .. }
+            | Self::Pointer { is_const, .. }
+            | Self::Struct { is_const, .. } => *is_const,
+            Self::Function { .. } | Self::Array { .. } => true,
+        }
+    }
+
+    /// Returns `true` if the type is a `restrict`-qualified pointer.
+    pub fn is_restrict(&self) -> bool {
+        matches!(
+            self,
+            Self::Pointer {
+                is_restrict: true,
+                ..
+            }
+        )
+    }
+
+    #[inline]
+    /// Check if `Dtype` is constant. if it is constant, the variable of `Dtype` is not assignable.
+    pub fn is_immutable(&self, structs: &HashMap<String, Option<Dtype>>) -> bool {
+        match self {
+            Self::Unit { is_const }
+            | Self::Int { is_const, .. }
+            | Self::Float { is_const, .. }
+            | Self::Pointer { is_const, .. }
+            | Self::Enum { is_const, .. } => *is_const,
+            Self::Array { .. } | Self::Function { .. } => true,
+            Self::Struct { name, is_const, .. } => {
+                let name = name.as_ref().expect("`name` must be exist");
+                let struct_type = structs
+                    .get(name)
+                    .expect("struct type matched with `name` must exist")
+                    .as_ref()
+                    .expect("`struct_type` must have its definition");
+                let fields = struct_type
+                    .get_struct_fields()
+                    .expect("`struct_type` must be struct type")
+                    .as_ref()
+                    .expect("`fields` must be `Some`");
+
+                *is_const
+                    // If any of the fields in the structure type is constant, return `true`.
+                    || fields
+                        .iter()
+                        .any(|f| {
+                        // If an array is wrapped in a struct and the array's inner type is not
+                        // constant, it is assignable to another object of the same struct type.
+                        if let Self::Array { inner, .. } = f.deref() {
+                            inner.is_immutable_for_array_struct_field_inner(structs)
+                        } else {
+                            f.deref().is_immutable(structs)
+                        }
+                    })
+            }
+            Self::Typedef { .. } => panic!("typedef should be replaced by real dtype"),
+        }
+    }
+
+    fn is_immutable_for_array_struct_field_inner(
         &self,
         structs: &HashMap<String, Option<Dtype>>,
     ) -> bool {
@@ -896,21 +1459,284 @@ impl Dtype {
                 is_const,
             },
             Self::Float { width, .. } => Self::Float { width, is_const },
-            Self::Pointer { inner, .. } => Self::Pointer { inner, is_const },
+            Self::Pointer {
+                inner, is_restrict, ..
+            } => Self::Pointer {
+                inner,
+                is_const,
+                is_restrict,
+            },
             Self::Array { .. } => self,
             Self::Struct {
                 name,
                 fields,
+                is_union,
+                bit_fields,
                 size_align_offsets,
                 ..
             } => Self::Struct {
                 name,
                 fields,
+                is_union,
+                bit_fields,
                 is_const,
                 size_align_offsets,
             },
             Self::Function { .. } => self,
-            Self::Typedef { name, .. } => Self::Typedef { name, is_const },
+            Self::Typedef {
+                name, is_restrict, ..
+            } => Self::Typedef {
+                name,
+                is_const,
+                is_restrict,
+            },
+            Self::Enum {
+                name, enumerators, ..
+            } => Self::Enum {
+                name,
+                enumerators,
+                is_const,
+            },
+        }
+    }
+
+    /// Returns the type `const`-qualified if `is_const` holds, keeping the qualifiers it already
+    /// has. Unlike [`Dtype::set_const`], which only replaces the qualifier of the type itself, the
+    /// qualifier of an array type applies to its elements (see C17 6.7.3p10), e.g., `const A` is
+    /// `[3 x const i32]` for `typedef int A[3]`.
+    #[must_use]
+    pub fn merge_const(self, is_const: bool) -> Self {
+        if !is_const {
+            return self;
+        }
+
+        match self {
+            Self::Array { inner, size, align } => Self::Array {
+                inner: Arc::new(Arc::unwrap_or_clone(inner).merge_const(true)),
+                size,
+                align,
+            },
+            Self::Function { .. } => self,
+            _ => self.set_const(true),
+        }
+    }
+
+    /// Returns the unqualified version of the type, i.e., without the `const` and `restrict`
+    /// qualifiers of the type itself but with those of the types it is derived from, e.g., `const
+    /// i32*` for `const i32*const restrict`. An array type is returned as it is, since its
+    /// qualifiers are the ones of its elements.
+    #[must_use]
+    pub fn strip_outer_const(self) -> Self {
+        self.set_const(false).set_restrict(false)
+    }
+
+    /// Returns the type `restrict`-qualified if `is_restrict` holds and it is a pointer, which
+    /// `restrict` only applies to, or a typedef name possibly resolved to a pointer. The other
+    /// types are returned unchanged.
+    #[must_use]
+    pub fn set_restrict(self, is_restrict: bool) -> Self {
+        match self {
+            Self::Pointer {
+                inner, is_const, ..
+            } => Self::Pointer {
+                inner,
+                is_const,
+                is_restrict,
+            },
+            Self::Typedef { name, is_const, .. } => Self::Typedef {
+                name,
+                is_const,
+                is_restrict,
+            },
+            _ => self,
+        }
+    }
+
+    /// Returns `true` if the type is compatible with `other` in the sense of C17 6.2.7, i.e., if
+    /// both may be the types of the declarations of the same object or function. Unlike `==`, it
+    /// ignores the alignments and the layouts computed for the types:
+    ///
+    /// - Qualified types are compatible only if they are identically qualified, except the
+    ///   parameters of functions, whose qualifiers are ignored, e.g., `void (const int)` and
+    ///   `void (int)`.
+    /// - Integers and floats are compatible if they have the same width and signedness. An enum is
+    ///   compatible with `int`, which it is laid out as, and with the enums of the same tag.
+    /// - Pointers are compatible if their pointees are.
+    /// - Arrays are compatible if their elements are and their sizes are the same, unless either
+    ///   is incomplete, e.g., `int[]` and `int[3]`.
+    /// - Functions are compatible if their return types are and their parameters are pairwise.
+    /// - Structs and unions are matched by their tags, without comparing their fields, which a
+    ///   declaration like `struct Node;` does not have. Untagged ones have no tag to match, and are
+    ///   compatible if their members have the same names and compatible types.
+    /// - Typedef names are only compatible with the same names. They should be resolved first.
+    pub fn is_compatible(&self, other: &Self) -> bool {
+        self.is_const() == other.is_const()
+            && self.is_restrict() == other.is_restrict()
+            && self.is_compatible_unqualified(other)
+    }
+
+    /// Returns `true` if the unqualified versions of the types are compatible.
+    fn is_compatible_unqualified(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Unit { .. }, Self::Unit { .. }) => true,
+            (
+                Self::Int {
+                    width, is_signed, ..
+                },
+                Self::Int {
+                    width: other_width,
+                    is_signed: other_is_signed,
+                    ..
+                },
+            ) => width == other_width && is_signed == other_is_signed,
+            (
+                Self::Float { width, .. },
+                Self::Float {
+                    width: other_width, ..
+                },
+            ) => width == other_width,
+            (
+                Self::Enum { name, .. },
+                Self::Enum {
+                    name: other_name, ..
+                },
+            ) => name == other_name,
+            (Self::Enum { .. }, int @ Self::Int { .. })
+            | (int @ Self::Int { .. }, Self::Enum { .. }) => {
+                int.clone().set_const(false) == Self::INT
+            }
+            (
+                Self::Pointer { inner, .. },
+                Self::Pointer {
+                    inner: other_inner, ..
+                },
+            ) => inner.is_compatible(other_inner),
+            (
+                Self::Array { inner, size, .. },
+                Self::Array {
+                    inner: other_inner,
+                    size: other_size,
+                    ..
+                },
+            ) => {
+                inner.is_compatible(other_inner)
+                    && (size.is_none() || other_size.is_none() || size == other_size)
+            }
+            (
+                Self::Struct {
+                    name,
+                    fields,
+                    is_union,
+                    ..
+                },
+                Self::Struct {
+                    name: other_name,
+                    fields: other_fields,
+                    is_union: other_is_union,
+                    ..
+                },
+            ) => {
+                if is_union != other_is_union {
+                    return false;
+                }
+                if name.is_some() || other_name.is_some() {
+                    return name == other_name;
+                }
+                match (fields, other_fields) {
+                    (Some(fields), Some(other_fields)) => {
+                        fields.len() == other_fields.len()
+                            && fields.iter().zip(other_fields).all(|(field, other_field)| {
+                                field.name() == other_field.name()
+                                    && field.deref().is_compatible(other_field.deref())
+                            })
+                    }
+                    _ => false,
+                }
+            }
+            (
+                Self::Function { ret, params },
+                Self::Function {
+                    ret: other_ret,
+                    params: other_params,
+                },
+            ) => {
+                ret.is_compatible(other_ret)
+                    && params.len() == other_params.len()
+                    && params
+                        .iter()
+                        .zip(other_params)
+                        .all(|(param, other_param)| param.is_compatible_unqualified(other_param))
+            }
+            (
+                Self::Typedef { name, .. },
+                Self::Typedef {
+                    name: other_name, ..
+                },
+            ) => name == other_name,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the type is the same as `other`. Unlike `==`, which compares the fields
+    /// of the structs and unions as they are filled in, e.g., none in `struct Point` referring to
+    /// the struct table but all in the type of a struct value, the named structs and unions are the
+    /// same if they have the same tags and qualifiers, as a tag refers to a single definition in a
+    /// translation unit. The anonymous ones have no tag, and are compared by `==`.
+    pub fn is_same_type(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::Pointer {
+                    inner,
+                    is_const,
+                    is_restrict,
+                },
+                Self::Pointer {
+                    inner: other_inner,
+                    is_const: other_is_const,
+                    is_restrict: other_is_restrict,
+                },
+            ) => {
+                is_const == other_is_const
+                    && is_restrict == other_is_restrict
+                    && inner.is_same_type(other_inner)
+            }
+            (
+                Self::Array { inner, size, align },
+                Self::Array {
+                    inner: other_inner,
+                    size: other_size,
+                    align: other_align,
+                },
+            ) => size == other_size && align == other_align && inner.is_same_type(other_inner),
+            (
+                Self::Struct {
+                    name: Some(name),
+                    is_union,
+                    is_const,
+                    ..
+                },
+                Self::Struct {
+                    name: Some(other_name),
+                    is_union: other_is_union,
+                    is_const: other_is_const,
+                    ..
+                },
+            ) => name == other_name && is_union == other_is_union && is_const == other_is_const,
+            (
+                Self::Function { ret, params },
+                Self::Function {
+                    ret: other_ret,
+                    params: other_params,
+                },
+            ) => {
+                ret.is_same_type(other_ret)
+                    && params.len() == other_params.len()
+                    && params
+                        .iter()
+                        .zip(other_params)
+                        .all(|(param, other_param)| param.is_same_type(other_param))
+            }
+            _ => self == other,
         }
     }
 
@@ -922,17 +1748,24 @@ impl Dtype {
             Self::Unit { .. } => Ok((0, 1)),
             Self::Int { width, .. } | Self::Float { width, .. } => {
                 let size_of = (*width + Self::BITS_OF_BYTE - 1) / Self::BITS_OF_BYTE;
-                let align_of = size_of;
+                let align_of = size_of.min(target_data().max_align);
 
                 Ok((size_of, align_of))
             }
-            Self::Pointer { .. } => Ok((Self::SIZE_OF_POINTER, Self::SIZE_OF_POINTER)),
-            Self::Array { inner, size, .. } => {
+            Self::Pointer { .. } => {
+                let size_of = target_data().pointer_size;
+                Ok((size_of, size_of))
+            }
+            Self::Array { inner, size, align } => {
+                let size = size.ok_or_else(|| DtypeError::IncompleteType {
+                    dtype: self.clone(),
+                    usage: "a type whose size is required".to_string(),
+                })?;
                 let (size_of_inner, align_of_inner) = inner.size_align_of(structs)?;
 
                 Ok((
                     size * std::cmp::max(size_of_inner, align_of_inner),
-                    align_of_inner,
+                    align.unwrap_or(align_of_inner),
                 ))
             }
             Self::Struct { name, .. } => {
@@ -943,7 +1776,10 @@ impl Dtype {
                         message: format!("unknown struct name `{name}`"),
                     })?
                     .as_ref()
-                    .expect("`struct_type` must have its definition");
+                    .ok_or_else(|| DtypeError::IncompleteType {
+                        dtype: self.clone(),
+                        usage: "a type whose size is required".to_string(),
+                    })?;
                 let (size_of, align_of, _) = struct_type
                     .get_struct_size_align_offsets()
                     .expect("`struct_type` must be struct type")
@@ -956,52 +1792,88 @@ impl Dtype {
             Self::Typedef { .. } => Err(DtypeError::Misc {
                 message: "typedef should be replaced by real dtype".to_string(),
             }),
+            Self::Enum { .. } => Self::INT.size_align_of(structs),
         }
     }
 
+    /// Returns the offset of the field `field_name` of the struct type, looking into its
+    /// anonymous members, together with the type of the field.
     pub fn get_offset_struct_field(
         &self,
         field_name: &str,
         structs: &HashMap<String, Option<Dtype>>,
-    ) -> Option<(usize, Self)> {
-        if let Self::Struct { name, .. } = self {
-            let struct_name = name.as_ref().expect("`self` must have its name");
-            let struct_type = structs
-                .get(struct_name)
-                .expect("`structs` must have value matched with `struct_name`")
-                .as_ref()
-                .expect("`struct_type` must have its definition");
-            let fields = struct_type
-                .get_struct_fields()
-                .expect("`struct_type` must be struct type")
-                .as_ref()
-                .expect("`fields` must be `Some`");
-            let (_, _, offsets) = struct_type
-                .get_struct_size_align_offsets()
-                .expect("`struct_type` must be struct type")
-                .as_ref()
-                .expect("`offsets` must be `Some`");
-
-            assert_eq!(fields.len(), offsets.len());
-            for (field, &offset) in izip!(fields, offsets) {
-                if let Some(name) = field.name() {
-                    if name == field_name {
-                        return Some((offset, field.deref().clone()));
-                    }
-                } else {
-                    let field_dtype = field.deref();
-                    let (offset_inner, dtype) = some_or!(
-                        field_dtype.get_offset_struct_field(field_name, structs),
-                        continue
-                    );
-                    return Some((offset + offset_inner, dtype));
+    ) -> Result<(usize, Self), DtypeError> {
+        let struct_name = if let Self::Struct { name, .. } = self {
+            name.as_ref().expect("`self` must have its name")
+        } else {
+            return Err(DtypeError::Misc {
+                message: format!("request for member `{field_name}` in `{self}`, not a struct"),
+            });
+        };
+        let struct_type = structs
+            .get(struct_name)
+            .ok_or_else(|| DtypeError::Misc {
+                message: format!("unknown struct name `{struct_name}`"),
+            })?
+            .as_ref()
+            .ok_or_else(|| DtypeError::IncompleteType {
+                dtype: self.clone(),
+                usage: format!("the struct of member `{field_name}`"),
+            })?;
+        let fields = struct_type
+            .get_struct_fields()
+            .expect("`struct_type` must be struct type")
+            .as_ref()
+            .expect("`fields` must be `Some`");
+        let (_, _, offsets) = struct_type
+            .get_struct_size_align_offsets()
+            .expect("`struct_type` must be struct type")
+            .as_ref()
+            .expect("`offsets` must be `Some`");
+
+        assert_eq!(fields.len(), offsets.len());
+        for (field, &offset) in izip!(fields, offsets) {
+            if let Some(name) = field.name() {
+                if name == field_name {
+                    return Ok((offset, field.deref().clone()));
+                }
+            } else if field.get_struct_name().is_some() {
+                match field.get_offset_struct_field(field_name, structs) {
+                    Ok((offset_inner, dtype)) => return Ok((offset + offset_inner, dtype)),
+                    Err(DtypeError::NoSuchMember { .. }) => continue,
+                    Err(dtype_error) => return Err(dtype_error),
                 }
             }
+        }
 
-            None
-        } else {
-            None
+        Err(DtypeError::NoSuchMember {
+            dtype: self.clone(),
+            field: field_name.to_string(),
+        })
+    }
+
+    /// Returns the bit-field declared by the field `field_name` of the struct type, looking into
+    /// its anonymous members, if it is a bit-field.
+    pub fn get_struct_field_bit_field(
+        &self,
+        field_name: &str,
+        structs: &HashMap<String, Option<Dtype>>,
+    ) -> Option<BitField> {
+        let name = self.get_struct_name()?.as_ref()?;
+        let struct_type = structs.get(name)?.as_ref()?;
+        let fields = struct_type.get_struct_fields()?.as_ref()?;
+
+        for (index, field) in fields.iter().enumerate() {
+            if let Some(name) = field.name() {
+                if name == field_name {
+                    return struct_type.get_struct_bit_field(index);
+                }
+            } else if field.get_offset_struct_field(field_name, structs).is_ok() {
+                return field.get_struct_field_bit_field(field_name, structs);
+            }
         }
+
+        None
     }
 
     #[must_use]
@@ -1018,22 +1890,110 @@ impl Dtype {
         }
     }
 
-    /// Derive a data type from declaration specifiers.
+    /// Derive a data type and the storage class of the declaration from declaration specifiers.
     pub fn try_from_ast_declaration_specifiers(
         specifiers: &[Node<ast::DeclarationSpecifier>],
-    ) -> Result<(Self, bool), DtypeError> {
+    ) -> Result<(Self, DeclarationStorage), DtypeError> {
         let mut spec = BaseDtype::default();
         BaseDtype::apply_declaration_specifiers(&mut spec, specifiers)?;
-        let is_typedef = spec.is_typedef;
+        let storage = spec.storage;
         let dtype = Self::try_from(spec)?;
 
-        Ok((dtype, is_typedef))
+        Ok((dtype, storage))
+    }
+
+    /// Returns the alignment the `_Alignas` specifiers among `specifiers` require, i.e., the
+    /// strictest one, or `None` if there is none.
+    ///
+    /// The operand of `_Alignas` is either an integer constant expression, e.g., `_Alignas(16)`,
+    /// or a type name whose alignment is required, e.g., `_Alignas(double)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DtypeError::Misc` if an alignment is neither zero nor a positive power of two.
+    pub fn align_of_ast_declaration_specifiers(
+        specifiers: &[Node<ast::DeclarationSpecifier>],
+        typedefs: &HashMap<String, Dtype>,
+        structs: &HashMap<String, Option<Dtype>>,
+    ) -> Result<Option<usize>, DtypeError> {
+        let mut result = None;
+        for specifier in specifiers {
+            let alignment = if let ast::DeclarationSpecifier::Alignment(alignment) = &specifier.node
+            {
+                &alignment.node
+            } else {
+                continue;
+            };
+            let align = match alignment {
+                ast::AlignmentSpecifier::Type(type_name) => {
+                    let dtype = Self::try_from(&type_name.node)?.resolve_typedefs(typedefs)?;
+                    let (_, align_of) = dtype.size_align_of(structs)?;
+                    align_of
+                }
+                ast::AlignmentSpecifier::Constant(expr) => {
+                    let constant =
+                        Constant::try_from(&expr.node).map_err(|_| DtypeError::Misc {
+                            message: "alignment is not an integer constant expression".to_string(),
+                        })?;
+                    let (value, _, is_signed) =
+                        constant.get_int().ok_or_else(|| DtypeError::Misc {
+                            message: "alignment is not an integer constant expression".to_string(),
+                        })?;
+                    // An alignment of zero has no effect (see C17 6.7.5p6).
+                    if value == 0 {
+                        continue;
+                    }
+                    if (is_signed && (value as i128) < 0) || !value.is_power_of_two() {
+                        return Err(DtypeError::Misc {
+                            message: format!(
+                                "requested alignment {} is not a positive power of two",
+                                value as i128
+                            ),
+                        });
+                    }
+                    value as usize
+                }
+            };
+            result = Some(result.map_or(align, |result: usize| result.max(align)));
+        }
+
+        Ok(result)
+    }
+
+    /// Returns the array type aligned to `align` bytes as `_Alignas` requires, e.g., `[64 x i8
+    /// align 16]` for `_Alignas(16) char buf[64]`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DtypeError::Misc` if `align` is less strict than the natural alignment of the type
+    /// or the type is not an array, the only types whose alignment may be overridden.
+    pub fn with_align(
+        self,
+        align: usize,
+        structs: &HashMap<String, Option<Dtype>>,
+    ) -> Result<Self, DtypeError> {
+        let (_, align_of) = self.size_align_of(structs)?;
+        match self {
+            Self::Array { inner, size, .. } if align >= align_of => Ok(Self::Array {
+                inner,
+                size,
+                align: Some(align),
+            }),
+            Self::Array { .. } => Err(DtypeError::Misc {
+                message: format!(
+                    "requested alignment {align} is less than the alignment {align_of} of `{self}`"
+                ),
+            }),
+            _ => Err(DtypeError::Misc {
+                message: format!("`_Alignas` is only supported for arrays, not for `{self}`"),
+            }),
+        }
     }
 
     /// Derive a data type and its name from the struct declaration.
     pub fn try_from_ast_struct_declaration(
         declaration: &ast::StructDeclaration,
-    ) -> Result<Vec<Named<Self>>, DtypeError> {
+    ) -> Result<Vec<(Named<Self>, Option<usize>)>, DtypeError> {
         let field_decl = if let ast::StructDeclaration::Field(field_decl) = declaration {
             &field_decl.node
         } else {
@@ -1050,9 +2010,19 @@ impl Dtype {
             .declarators
             .iter()
             .map(|d| {
-                dtype
-                    .clone()
-                    .with_ast_declarator(&d.node.declarator.as_ref().unwrap().node)
+                let field = if let Some(declarator) = &d.node.declarator {
+                    dtype.clone().with_ast_declarator(&declarator.node)?
+                } else {
+                    // An unnamed bit-field, e.g., `int : 0;`, only pads the fields.
+                    Named::new(None, dtype.clone())
+                };
+                let width = d
+                    .node
+                    .bit_width
+                    .as_ref()
+                    .map(|bit_width| bit_field_width(&field, &bit_width.node))
+                    .transpose()?;
+                Ok((field, width))
             })
             .collect::<Result<Vec<_>, _>>()?;
 
@@ -1065,7 +2035,7 @@ impl Dtype {
             if let Self::Struct { name, .. } = &dtype {
                 if name.is_none() {
                     // Note that `const` qualifier has no effect in this time.
-                    return Ok(vec![Named::new(None, dtype.set_const(false))]);
+                    return Ok(vec![(Named::new(None, dtype.set_const(false)), None)]);
                 }
             }
 
@@ -1086,22 +2056,70 @@ impl Dtype {
     /// # Arguments
     ///
     /// * `declarator` - Parts requiring conversion to 'Dtype' on the declaration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DtypeError::TooComplex` if `declarator` has more derived declarators than
+    /// [`max_nesting_depth`](crate::max_nesting_depth), before building the type.
     pub fn with_ast_declarator(
         mut self,
-        declarator: &ast::Declarator,
+        mut declarator: &ast::Declarator,
     ) -> Result<Named<Self>, DtypeError> {
-        for derived_decl in &declarator.derived {
+        // Iterative over the nested declarators, which may be deeply parenthesized.
+        let mut derived = 0;
+        loop {
+            derived += declarator.derived.len();
+            if derived > max_nesting_depth() {
+                return Err(DtypeError::TooComplex {
+                    limit: max_nesting_depth(),
+                });
+            }
+
+            self = self.with_ast_derived_declarators(&declarator.derived)?;
+
+            match &declarator.kind.node {
+                ast::DeclaratorKind::Abstract => return Ok(Named::new(None, self)),
+                ast::DeclaratorKind::Identifier(identifier) => {
+                    return Ok(Named::new(Some(identifier.node.name.clone()), self))
+                }
+                ast::DeclaratorKind::Declarator(inner) => declarator = &inner.node,
+            }
+        }
+    }
+
+    fn with_ast_derived_declarators(
+        mut self,
+        derived: &[Node<ast::DerivedDeclarator>],
+    ) -> Result<Self, DtypeError> {
+        // The number of the array declarators just applied, e.g., 1 for `[3]` of `a[2][3]`. The
+        // next size goes inside of theirs, but outside of the arrays `self` started with, e.g.,
+        // those of a typedef of an array type.
+        let mut arrays = 0;
+        for derived_decl in derived {
+            if !matches!(derived_decl.node, ast::DerivedDeclarator::Array(_)) {
+                arrays = 0;
+            }
             self = match &derived_decl.node {
                 ast::DerivedDeclarator::Pointer(pointer_qualifiers) => {
                     let mut specifier = BaseDtype::default();
                     for qualifier in pointer_qualifiers {
                         specifier.apply_pointer_qualifier(&qualifier.node)?;
                     }
-                    Self::pointer(self).set_const(specifier.is_const)
+                    Self::pointer(self)
+                        .set_const(specifier.is_const)
+                        .set_restrict(specifier.is_restrict)
                 }
                 ast::DerivedDeclarator::Array(array_decl) => {
-                    assert!(array_decl.node.qualifiers.is_empty());
-                    self.with_ast_array_size(&array_decl.node.size)?
+                    // E.g., `int a[const 3]` in a prototype.
+                    if !array_decl.node.qualifiers.is_empty() {
+                        return Err(DtypeError::Misc {
+                            message: "qualifiers in array declarators are not supported"
+                                .to_string(),
+                        });
+                    }
+                    let size = Self::size_of_ast_array_size(&array_decl.node.size)?;
+                    arrays += 1;
+                    self.with_array_size_below(size, arrays - 1)?
                 }
                 ast::DerivedDeclarator::Function(func_decl) => {
                     let mut params = func_decl
@@ -1120,23 +2138,23 @@ impl Dtype {
                 }
                 ast::DerivedDeclarator::KRFunction(kr_func_decl) => {
                     // K&R function is allowed only when it has no parameter
-                    assert!(kr_func_decl.is_empty());
+                    if !kr_func_decl.is_empty() {
+                        return Err(DtypeError::Misc {
+                            message: "K&R function declarators with parameters are not supported"
+                                .to_string(),
+                        });
+                    }
                     Self::function(self, Vec::new())
                 }
-                ast::DerivedDeclarator::Block(_) => panic!(),
+                ast::DerivedDeclarator::Block(_) => {
+                    return Err(DtypeError::Misc {
+                        message: "block pointers are not supported".to_string(),
+                    })
+                }
             };
         }
 
-        let declarator_kind = &declarator.kind;
-        match &declarator_kind.node {
-            ast::DeclaratorKind::Abstract => Ok(Named::new(None, self)),
-            ast::DeclaratorKind::Identifier(identifier) => {
-                Ok(Named::new(Some(identifier.node.name.clone()), self))
-            }
-            ast::DeclaratorKind::Declarator(declarator) => {
-                self.with_ast_declarator(&declarator.node)
-            }
-        }
+        Ok(self)
     }
 
     /// Generates `Dtype` based on declarator and `self` which has a scalar type.
@@ -1148,17 +2166,96 @@ impl Dtype {
     ///
     /// * `array_size` - the array size to add to `self`.
     pub fn with_ast_array_size(self, array_size: &ast::ArraySize) -> Result<Self, DtypeError> {
-        let expr = if let ast::ArraySize::VariableExpression(expr) = array_size {
-            &expr.node
-        } else {
-            return Err(DtypeError::Misc {
-                message: "`ArraySize` is unsupported except `ArraySize::VariableExpression`"
-                    .to_string(),
+        let size = Self::size_of_ast_array_size(array_size)?;
+        // `size` is inserted below the arrays of `self`, where it must be known.
+        if size.is_none() && self.get_array_inner().is_some() {
+            return Err(incomplete_element());
+        }
+        let mut element = &self;
+        while let Some(inner) = element.get_array_inner() {
+            element = inner;
+        }
+        if let Self::Function { .. } = element {
+            return Err(array_of_functions());
+        }
+        Ok(Self::array_of(self, size))
+    }
+
+    /// Returns the type with an array of `size` elements inserted below its outermost `depth`
+    /// array types, e.g., `[2 x [3 x i32]]` for `[2 x i32]`, 3 and 1.
+    fn with_array_size_below(self, size: Option<usize>, depth: usize) -> Result<Self, DtypeError> {
+        if depth == 0 {
+            if let Self::Function { .. } = self {
+                return Err(array_of_functions());
+            }
+            if self.is_incomplete_array() {
+                return Err(incomplete_element());
+            }
+            return Ok(Self::Array {
+                inner: Arc::new(self),
+                size,
+                align: None,
             });
+        }
+
+        // Only the outermost array may be incomplete, e.g., `int a[][3]` but not `int a[3][]`.
+        if size.is_none() {
+            return Err(incomplete_element());
+        }
+
+        match self {
+            Self::Array {
+                inner,
+                size: outer_size,
+                align,
+            } => Ok(Self::Array {
+                inner: Arc::new(
+                    Arc::unwrap_or_clone(inner).with_array_size_below(size, depth - 1)?,
+                ),
+                size: outer_size,
+                align,
+            }),
+            _ => panic!("`self` must have {depth} array types outermost"),
+        }
+    }
+
+    /// Returns the type of a parameter declared of type `self`, i.e., `self` with an array type
+    /// adjusted to a pointer to its elements and a function type to a pointer to the function,
+    /// e.g., `int *` for `int v[]` and `int (*)(int, int)` for `int cmp(int, int)`.
+    ///
+    /// A function call with an array or a function argument passes a pointer to it instead, so the
+    /// parameter holds that pointer (see C17 6.7.6.3p7-8).
+    ///
+    /// For more information: <https://www.eskimo.com/~scs/cclass/notes/sx10f.html>
+    fn adjust_parameter(self) -> Self {
+        if let Some(inner) = self.get_array_inner() {
+            return Self::pointer(inner.clone());
+        }
+        if let Self::Function { .. } = self {
+            return Self::pointer(self);
+        }
+        self
+    }
+
+    /// Returns the number of elements `array_size` declares, or `None` if it is unknown, e.g., in
+    /// `extern char table[];`.
+    fn size_of_ast_array_size(array_size: &ast::ArraySize) -> Result<Option<usize>, DtypeError> {
+        let expr = match array_size {
+            ast::ArraySize::VariableExpression(expr) => &expr.node,
+            ast::ArraySize::Unknown => return Ok(None),
+            // E.g., `int a[*]` in a prototype.
+            ast::ArraySize::VariableUnknown => return Err(variable_length()),
+            _ => {
+                return Err(DtypeError::Misc {
+                    message: "`ArraySize` is unsupported except `ArraySize::VariableExpression` \
+                              and `ArraySize::Unknown`"
+                        .to_string(),
+                })
+            }
         };
 
-        let constant = Constant::try_from(expr)
-            .expect("expression of `ArraySize::VariableExpression` must be constant value");
+        // E.g., `int buf[n]`.
+        let constant = Constant::try_from(expr).map_err(|_| variable_length())?;
 
         let (value, _, is_signed) = constant.get_int().ok_or_else(|| DtypeError::Misc {
             message: "expression is not an integer constant expression".to_string(),
@@ -1170,65 +2267,52 @@ impl Dtype {
             });
         }
 
-        Ok(Self::array(self, value as usize))
+        Ok(Some(value as usize))
     }
 
+    /// Replaces the typedef names in the type with the types they name.
+    ///
+    /// # Errors
+    ///
+    /// Returns `DtypeError::TooComplex` if the resolved type, e.g., a pointer to a typedef of a
+    /// pointer, is nested more deeply than [`max_nesting_depth`](crate::max_nesting_depth).
     pub fn resolve_typedefs(self, typedefs: &HashMap<String, Dtype>) -> Result<Self, DtypeError> {
-        let dtype = match self {
-            Self::Unit { .. } | Self::Int { .. } | Self::Float { .. } => self,
-            Self::Pointer { inner, is_const } => {
-                let inner = inner.resolve_typedefs(typedefs)?;
-                Self::pointer(inner).set_const(is_const)
-            }
-            Self::Array { inner, size } => {
-                let inner = inner.resolve_typedefs(typedefs)?;
-                Self::Array {
-                    inner: Box::new(inner),
-                    size,
-                }
-            }
-            Self::Struct {
+        let dtype = self.resolve_typedefs_inner(typedefs)?;
+        if dtype.depth() > max_nesting_depth() {
+            return Err(DtypeError::TooComplex {
+                limit: max_nesting_depth(),
+            });
+        }
+
+        Ok(dtype)
+    }
+
+    fn resolve_typedefs_inner(self, typedefs: &HashMap<String, Dtype>) -> Result<Self, DtypeError> {
+        let dtype = self.map_inner(|inner| inner.resolve_typedefs_inner(typedefs))?;
+        let dtype = match dtype {
+            // E.g., `f` of `typedef int fn(int); void apply(fn f);` is a function pointer.
+            Self::Function { ret, params } => Self::function(
+                Arc::unwrap_or_clone(ret),
+                params.into_iter().map(Self::adjust_parameter).collect(),
+            ),
+            Self::Typedef {
                 name,
-                fields,
                 is_const,
-                ..
+                is_restrict,
             } => {
-                let (name, fields) = if let Some(fields) = fields {
-                    let fields = fields
-                        .into_iter()
-                        .map(|f| {
-                            let (d, name) = f.destruct();
-                            let d = d.resolve_typedefs(typedefs).unwrap();
-                            Named::new(name, d)
-                        })
-                        .collect::<Vec<_>>();
-                    (name, Some(fields))
-                } else {
-                    assert!(name.is_some());
-                    (name, fields)
-                };
-                Self::structure(name, fields).set_const(is_const)
-            }
-            Self::Function { ret, params } => {
-                let ret = ret.resolve_typedefs(typedefs)?;
-                let params = params
-                    .into_iter()
-                    .map(|p| p.resolve_typedefs(typedefs))
-                    .collect::<Result<Vec<_>, _>>()?;
-
-                Self::function(ret, params)
-            }
-            Self::Typedef { name, is_const } => {
                 let dtype = typedefs
                     .get(&name)
                     .ok_or_else(|| DtypeError::Misc {
                         message: format!("unknown type name `{name}`"),
                     })?
                     .clone();
-                let is_const = dtype.is_const() || is_const;
+                let is_restrict = dtype.is_restrict() || is_restrict;
 
-                dtype.set_const(is_const)
+                dtype.merge_const(is_const).set_restrict(is_restrict)
             }
+            // The enumerators are constants of type `int`, registered by the caller beforehand.
+            Self::Enum { is_const, .. } => Self::INT.set_const(is_const),
+            dtype => dtype,
         };
 
         Ok(dtype)
@@ -1236,6 +2320,10 @@ impl Dtype {
 
     /// If the struct type has a definition, it is saved to the struct table
     /// and transformed to a struct type with no definition.
+    ///
+    /// `structs` holds the tags visible in the current scope, so that a tag defined there already
+    /// is a redefinition. A tag defined in a block shadows the definition of an outer scope, which
+    /// the caller removes from `structs` beforehand.
     pub fn resolve_structs(
         self,
         structs: &mut HashMap<String, Option<Dtype>>,
@@ -1243,7 +2331,11 @@ impl Dtype {
     ) -> Result<Self, DtypeError> {
         let dtype = match self {
             Self::Unit { .. } | Self::Int { .. } | Self::Float { .. } => self,
-            Self::Pointer { inner, is_const } => {
+            Self::Pointer {
+                inner,
+                is_const,
+                is_restrict,
+            } => {
                 // Pointer types can have an undeclared struct type as inner.
                 //
                 // For example, consider `struct A { struct B *p }`, even if `struct B` has not
@@ -1252,23 +2344,33 @@ impl Dtype {
                     if fields.is_none() {
                         let name = name.as_ref().expect("`name` must be `Some`");
                         let _ = structs.entry(name.to_string()).or_insert(None);
-                        return Ok(Self::pointer(*inner).set_const(is_const));
+                        return Ok(Self::Pointer {
+                            inner,
+                            is_const,
+                            is_restrict,
+                        });
                     }
                 }
 
-                let resolved_inner = inner.resolve_structs(structs, tempid_counter)?;
-                Self::pointer(resolved_inner).set_const(is_const)
+                let resolved_inner =
+                    Arc::unwrap_or_clone(inner).resolve_structs(structs, tempid_counter)?;
+                Self::pointer(resolved_inner)
+                    .set_const(is_const)
+                    .set_restrict(is_restrict)
             }
-            Self::Array { inner, size } => {
-                let inner = inner.resolve_structs(structs, tempid_counter)?;
+            Self::Array { inner, size, align } => {
+                let inner = Arc::unwrap_or_clone(inner).resolve_structs(structs, tempid_counter)?;
                 Self::Array {
-                    inner: Box::new(inner),
+                    inner: Arc::new(inner),
                     size,
+                    align,
                 }
             }
             Self::Struct {
                 name,
                 fields,
+                is_union,
+                bit_fields,
                 is_const,
                 ..
             } => {
@@ -1277,10 +2379,23 @@ impl Dtype {
                         .into_iter()
                         .map(|f| {
                             let (d, name) = f.destruct();
-                            let d = d.resolve_structs(structs, tempid_counter).unwrap();
-                            Named::new(name, d)
+                            let d =
+                                d.resolve_structs(structs, tempid_counter)
+                                    .map_err(|error| match error {
+                                        DtypeError::IncompleteType { dtype, .. } => {
+                                            DtypeError::IncompleteType {
+                                                dtype,
+                                                usage: format!(
+                                                    "field `{}`",
+                                                    name.as_deref().unwrap_or("%anon")
+                                                ),
+                                            }
+                                        }
+                                        error => error,
+                                    })?;
+                            Ok(Named::new(name, d))
                         })
-                        .collect::<Vec<_>>();
+                        .collect::<Result<Vec<_>, _>>()?;
 
                     let name = if let Some(name) = name {
                         name
@@ -1289,17 +2404,21 @@ impl Dtype {
                         *tempid_counter += 1;
                         format!("%t{tempid}")
                     };
-                    let resolved_struct = Self::structure(Some(name.clone()), Some(fields));
+                    // The first definition is kept, e.g., for the declarations after the error.
+                    if matches!(structs.get(&name), Some(Some(_))) {
+                        return Err(DtypeError::Misc {
+                            message: format!(
+                                "redefinition of `{}`",
+                                Self::aggregate(is_union, Some(name), None)
+                            ),
+                        });
+                    }
+                    let resolved_struct =
+                        Self::aggregate(is_union, Some(name.clone()), Some(fields))
+                            .with_bit_widths(bit_widths(&bit_fields));
                     let filled_struct =
                         resolved_struct.fill_size_align_offsets_of_struct(structs)?;
-
-                    if let Some(prev_dtype) = structs.insert(name.clone(), Some(filled_struct)) {
-                        if prev_dtype.is_some() {
-                            return Err(DtypeError::Misc {
-                                message: format!("redefinition of {name}"),
-                            });
-                        }
-                    }
+                    let _ = structs.insert(name.clone(), Some(filled_struct));
 
                     (name, None)
                 } else {
@@ -1308,17 +2427,18 @@ impl Dtype {
                         message: format!("unknown struct name `{name}`"),
                     })?;
                     if struct_type.is_none() {
-                        return Err(DtypeError::Misc {
-                            message: format!("variable has incomplete type 'struct {name}'"),
+                        return Err(DtypeError::IncompleteType {
+                            dtype: Self::structure(Some(name), None),
+                            usage: "a type whose size is required".to_string(),
                         });
                     }
 
                     (name, fields)
                 };
-                Self::structure(Some(name), fields).set_const(is_const)
+                Self::aggregate(is_union, Some(name), fields).set_const(is_const)
             }
             Self::Function { ret, params } => {
-                let ret = ret.resolve_structs(structs, tempid_counter)?;
+                let ret = Arc::unwrap_or_clone(ret).resolve_structs(structs, tempid_counter)?;
                 let params = params
                     .into_iter()
                     .map(|p| p.resolve_structs(structs, tempid_counter))
@@ -1327,12 +2447,15 @@ impl Dtype {
                 Self::function(ret, params)
             }
             Self::Typedef { .. } => panic!("typedef should be replaced by real dtype"),
+            Self::Enum { .. } => panic!("enum should be replaced by int"),
         };
 
         Ok(dtype)
     }
 }
 
+/// Writes the type in the syntax of the IR, which [`FromStr`](core::str::FromStr) parses back. The
+/// layouts of the structs are not written, so that they are computed again for the parsed types.
 impl fmt::Display for Dtype {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -1351,36 +2474,66 @@ impl fmt::Display for Dtype {
             Self::Float { width, is_const } => {
                 write!(f, "{}f{}", if *is_const { "const " } else { "" }, width)
             }
-            Self::Pointer { inner, is_const } => {
-                write!(f, "{}*{}", inner, if *is_const { "const" } else { "" })
+            Self::Pointer {
+                inner,
+                is_const,
+                is_restrict,
+            } => write!(
+                f,
+                "{}*{}{}",
+                inner,
+                if *is_const { "const" } else { "" },
+                match (is_const, is_restrict) {
+                    (true, true) => " restrict",
+                    (false, true) => "restrict",
+                    (_, false) => "",
+                }
+            ),
+            Self::Array { inner, size, align } => {
+                match size {
+                    Some(size) => write!(f, "[{size} x {inner}")?,
+                    None => write!(f, "[? x {inner}")?,
+                }
+                if let Some(align) = align {
+                    write!(f, " align {align}")?;
+                }
+                write!(f, "]")
             }
-            Self::Array { inner, size, .. } => write!(f, "[{size} x {inner}]",),
             Self::Struct {
                 name,
                 fields,
+                is_union,
                 is_const,
                 ..
             } => {
                 let fields = if let Some(fields) = fields {
-                    let fields = fields.iter().format_with(", ", |field, f| {
-                        f(&format_args!(
-                            "{}:{}",
-                            if let Some(name) = field.name() {
-                                name
-                            } else {
-                                "%anon"
-                            },
-                            field.deref()
-                        ))
-                    });
+                    let fields = fields
+                        .iter()
+                        .enumerate()
+                        .format_with(", ", |(i, field), f| {
+                            f(&format_args!(
+                                "{}:{}{}",
+                                if let Some(name) = field.name() {
+                                    name
+                                } else {
+                                    "%anon"
+                                },
+                                field.deref(),
+                                self.get_struct_bit_field(i).map_or(
+                                    String::new(),
+                                    |bit_field| format!(":{}", bit_field.width)
+                                )
+                            ))
+                        });
                     format!(":<{fields}>")
                 } else {
                     "".to_string()
                 };
                 write!(
                     f,
-                    "{}struct {}{}",
+                    "{}{} {}{}",
                     if *is_const { "const " } else { "" },
+                    if *is_union { "union" } else { "struct" },
                     if let Some(name) = name { name } else { "%anon" },
                     fields
                 )
@@ -1388,8 +2541,37 @@ impl fmt::Display for Dtype {
             Self::Function { ret, params } => {
                 write!(f, "[ret:{} params:({})]", ret, params.iter().format(", "))
             }
-            Self::Typedef { name, is_const } => {
-                write!(f, "{}{}", if *is_const { "const " } else { "" }, name)
+            Self::Typedef {
+                name,
+                is_const,
+                is_restrict,
+            } => write!(
+                f,
+                "{}typedef {}{}",
+                if *is_const { "const " } else { "" },
+                name,
+                if *is_restrict { " restrict" } else { "" }
+            ),
+            Self::Enum {
+                name,
+                enumerators,
+                is_const,
+            } => {
+                let enumerators = if let Some(enumerators) = enumerators {
+                    let enumerators = enumerators
+                        .iter()
+                        .format_with(", ", |(name, value), f| f(&format_args!("{name}={value}")));
+                    format!(":<{enumerators}>")
+                } else {
+                    "".to_string()
+                };
+                write!(
+                    f,
+                    "{}enum {}{}",
+                    if *is_const { "const " } else { "" },
+                    if let Some(name) = name { name } else { "%anon" },
+                    enumerators
+                )
             }
         }
     }
@@ -1402,25 +2584,140 @@ impl Default for Dtype {
     }
 }
 
-#[inline]
-fn check_no_duplicate_field(fields: &[Named<Dtype>], field_names: &mut HashSet<String>) -> bool {
+/// The error of a variable-length array type, e.g., of `int buf[n]`.
+fn variable_length() -> DtypeError {
+    DtypeError::Misc {
+        message: "variable-length arrays are not supported".to_string(),
+    }
+}
+
+/// The error of an array type whose elements are functions, e.g., of `int f[3](void)`.
+fn array_of_functions() -> DtypeError {
+    DtypeError::Misc {
+        message: "declared as an array of functions".to_string(),
+    }
+}
+
+/// The error of an array type whose elements are incomplete arrays, e.g., of `int a[3][]`.
+fn incomplete_element() -> DtypeError {
+    DtypeError::Misc {
+        message: "array type has incomplete element type".to_string(),
+    }
+}
+
+/// Returns the name of a member declared twice among `fields` and `field_names`, including the
+/// members of the anonymous structs and unions among `fields`, which share the namespace of the
+/// enclosing struct, e.g., `x` of `struct { struct { int x; }; int x; }`.
+fn duplicate_field(fields: &[Named<Dtype>], field_names: &mut HashSet<String>) -> Option<String> {
     for field in fields {
         if let Some(name) = field.name() {
             if !field_names.insert(name.clone()) {
-                return false;
+                return Some(name.clone());
             }
-        } else {
-            let field_dtype = field.deref();
-            let fields = field_dtype
-                .get_struct_fields()
-                .expect("`field_dtype` must be a struct type")
+        } else if let Some(fields) = field.get_struct_fields() {
+            let fields = fields
                 .as_ref()
                 .expect("struct type must have its definition");
-            if !check_no_duplicate_field(fields, field_names) {
-                return false;
+            if let Some(name) = duplicate_field(fields, field_names) {
+                return Some(name);
             }
         }
     }
 
-    true
+    None
+}
+
+/// Returns the value of each enumerator of `enum_type`. An explicit value must be an integer
+/// constant, and the others are one more than the previous enumerator, or 0 for the first one.
+fn enumerator_values(enum_type: &ast::EnumType) -> Result<Vec<(String, i128)>, DtypeError> {
+    let mut enumerators = Vec::<(String, i128)>::new();
+    let mut next = 0;
+    for enumerator in &enum_type.enumerators {
+        let name = enumerator.node.identifier.node.name.clone();
+        if enumerators.iter().any(|(prev, _)| *prev == name) {
+            return Err(DtypeError::Misc {
+                message: format!("redefinition of enumerator `{name}`"),
+            });
+        }
+
+        let value = if let Some(expression) = &enumerator.node.expression {
+            let (value, width, is_signed) = Constant::try_from(&expression.node)
+                .ok()
+                .and_then(|constant| constant.get_int())
+                .ok_or_else(|| DtypeError::Misc {
+                    message: format!("the value of enumerator `{name}` is not an integer constant"),
+                })?;
+            if is_signed {
+                sign_extension(value, width as u128) as i128
+            } else {
+                value as i128
+            }
+        } else {
+            next
+        };
+        if i32::try_from(value).is_err() {
+            return Err(DtypeError::Misc {
+                message: format!("the value of enumerator `{name}` is out of the range of `int`"),
+            });
+        }
+
+        enumerators.push((name, value));
+        next = value + 1;
+    }
+
+    Ok(enumerators)
+}
+
+/// Checks that the bit-field `field` of `width` bits has an integer type of at least `width` bits,
+/// and a name unless it is of zero width.
+fn check_bit_field(field: &Named<Dtype>, width: usize) -> Result<(), DtypeError> {
+    let invalid = |reason: String| DtypeError::InvalidBitField {
+        field: field.name().map_or("%anon", String::as_str).to_string(),
+        reason,
+    };
+
+    let max_width = field
+        .get_int_width()
+        .ok_or_else(|| invalid(format!("`{}` is not an integer type", field.deref())))?;
+    if width > max_width {
+        return Err(invalid(format!(
+            "width {width} exceeds the {max_width} bits of `{}`",
+            field.deref()
+        )));
+    }
+    if width == 0 && field.name().is_some() {
+        return Err(invalid("a named bit-field has zero width".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Returns the width of the bit-field `field` given by the expression `bit_width`, which must be a
+/// nonnegative integer constant.
+fn bit_field_width(field: &Named<Dtype>, bit_width: &ast::Expression) -> Result<usize, DtypeError> {
+    let (value, width, is_signed) = Constant::try_from(bit_width)
+        .ok()
+        .and_then(|constant| constant.get_int())
+        .ok_or_else(|| DtypeError::InvalidBitField {
+            field: field.name().map_or("%anon", String::as_str).to_string(),
+            reason: "the width is not an integer constant".to_string(),
+        })?;
+    let value = if is_signed {
+        sign_extension(value, width as u128) as i128
+    } else {
+        value as i128
+    };
+
+    usize::try_from(value).map_err(|_| DtypeError::InvalidBitField {
+        field: field.name().map_or("%anon", String::as_str).to_string(),
+        reason: format!("the width {value} is negative"),
+    })
+}
+
+/// Returns the widths of `bit_fields`, to declare them again with `Dtype::with_bit_widths`.
+fn bit_widths(bit_fields: &[Option<BitField>]) -> Vec<Option<usize>> {
+    bit_fields
+        .iter()
+        .map(|bit_field| bit_field.map(|bit_field| bit_field.width))
+        .collect()
 }
@@ -33,13 +33,23 @@ impl IsEquiv for Declaration {
     fn is_equiv(&self, other: &Self) -> bool {
         match (self, other) {
             (
-                Self::Variable { dtype, initializer },
+                Self::Variable {
+                    dtype,
+                    initializer,
+                    is_extern,
+                    is_static,
+                },
                 Self::Variable {
                     dtype: dtype_other,
                     initializer: initializer_other,
+                    is_extern: is_extern_other,
+                    is_static: is_static_other,
                 },
             ) => {
-                if dtype != dtype_other {
+                if dtype != dtype_other
+                    || is_extern != is_extern_other
+                    || is_static != is_static_other
+                {
                     return false;
                 }
 
@@ -49,13 +59,15 @@ impl IsEquiv for Declaration {
                 Self::Function {
                     signature,
                     definition,
+                    is_static,
                 },
                 Self::Function {
                     signature: signature_other,
                     definition: definition_other,
+                    is_static: is_static_other,
                 },
             ) => {
-                if signature != signature_other {
+                if signature != signature_other || is_static != is_static_other {
                     return false;
                 }
 
@@ -207,6 +219,33 @@ fn is_equiv_instruction(
                 && is_equiv_operand(offset, offset_other, map)
                 && dtype == dtype_other
         }
+        (
+            Instruction::Min { lhs, rhs, dtype },
+            Instruction::Min {
+                lhs: lhs_other,
+                rhs: rhs_other,
+                dtype: dtype_other,
+            },
+        )
+        | (
+            Instruction::Max { lhs, rhs, dtype },
+            Instruction::Max {
+                lhs: lhs_other,
+                rhs: rhs_other,
+                dtype: dtype_other,
+            },
+        ) => {
+            is_equiv_operand(lhs, lhs_other, map)
+                && is_equiv_operand(rhs, rhs_other, map)
+                && dtype == dtype_other
+        }
+        (
+            Instruction::Abs { operand, dtype },
+            Instruction::Abs {
+                operand: operand_other,
+                dtype: dtype_other,
+            },
+        ) => is_equiv_operand(operand, operand_other, map) && dtype == dtype_other,
         _ => false,
     }
 }
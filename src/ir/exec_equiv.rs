@@ -0,0 +1,277 @@
+//! Equivalence of two modules by executing them on the same inputs, e.g., to accept any correct
+//! optimization of a reference module.
+
+use ordered_float::OrderedFloat;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::ir::*;
+use crate::*;
+
+/// Generates random arguments of `main`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgGenerator {
+    /// Integers of `width` bits in `min..=max`, biased towards the bounds and small values, which
+    /// are the most likely to expose off-by-one errors.
+    Int {
+        width: usize,
+        is_signed: bool,
+        min: i128,
+        max: i128,
+    },
+    /// Floating-point numbers of `width` bits in `min..=max`.
+    Float { width: usize, min: f64, max: f64 },
+}
+
+impl ArgGenerator {
+    /// Returns the generator of the values of `dtype`, or `None` if it is neither an integer nor a
+    /// floating-point type.
+    pub fn from_dtype(dtype: &Dtype) -> Option<Self> {
+        match dtype {
+            Dtype::Int {
+                width, is_signed, ..
+            } => {
+                let (min, max) = if *is_signed {
+                    (-(1i128 << (width - 1)), (1i128 << (width - 1)) - 1)
+                } else {
+                    (0, (1i128 << width) - 1)
+                };
+                Some(Self::Int {
+                    width: *width,
+                    is_signed: *is_signed,
+                    min,
+                    max,
+                })
+            }
+            Dtype::Float { width, .. } => Some(Self::Float {
+                width: *width,
+                min: -1e3,
+                max: 1e3,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Derives the generators of the arguments of `main` of `ir` from its parameter types, or
+    /// returns `None` if `ir` has no `main` or a parameter has no generator.
+    pub fn for_main(ir: &TranslationUnit) -> Option<Vec<Self>> {
+        let (signature, _) = ir.decls.get("main")?.get_function()?;
+        signature.params.iter().map(Self::from_dtype).collect()
+    }
+
+    fn generate<R: Rng>(&self, rng: &mut R) -> Value {
+        match self {
+            Self::Int {
+                width,
+                is_signed,
+                min,
+                max,
+            } => {
+                let value = match rng.gen_range(0..4) {
+                    0 => {
+                        let edges = [*min, *max, 0, 1, -1];
+                        let edges = edges
+                            .iter()
+                            .filter(|edge| (*min..=*max).contains(*edge))
+                            .collect::<Vec<_>>();
+                        *edges[rng.gen_range(0..edges.len())]
+                    }
+                    1 => rng.gen_range((*min).max(-16)..=(*max).min(16)),
+                    _ => rng.gen_range(*min..=*max),
+                };
+                Value::Int {
+                    value: trim_unnecessary_bits(value as u128, *width as u128),
+                    width: *width,
+                    is_signed: *is_signed,
+                }
+            }
+            Self::Float { width, min, max } => {
+                let value = rng.gen_range(*min..=*max);
+                let value = if *width == Dtype::SIZE_OF_FLOAT * Dtype::BITS_OF_BYTE {
+                    value as f32 as f64
+                } else {
+                    value
+                };
+                Value::Float {
+                    value: OrderedFloat(value),
+                    width: *width,
+                }
+            }
+        }
+    }
+}
+
+/// The inputs two modules are run on by [`equiv_by_execution`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestVectors {
+    /// The arguments of `main`, one list per run.
+    pub inputs: Vec<Vec<Value>>,
+    /// The generators of the random arguments of `main`, one per parameter.
+    pub generators: Vec<ArgGenerator>,
+    /// The number of runs on random arguments, besides `inputs`.
+    pub random_inputs: usize,
+    /// The seed of the random arguments, so that a verdict is reproducible.
+    pub seed: u64,
+    /// The number of steps a module may take in a run before the run is given up.
+    pub max_steps: usize,
+    /// The semantics the modules are interpreted under.
+    pub options: CompileOptions,
+}
+
+impl Default for TestVectors {
+    /// A single run of `main` without arguments.
+    fn default() -> Self {
+        Self {
+            inputs: vec![Vec::new()],
+            generators: Vec::new(),
+            random_inputs: 0,
+            seed: 0,
+            max_steps: 1_000_000,
+            options: CompileOptions::default(),
+        }
+    }
+}
+
+impl TestVectors {
+    /// `count` runs of `main` of `ir` on random arguments generated from `seed`, with the
+    /// generators derived from its parameter types (see [`ArgGenerator::for_main`]).
+    pub fn random(ir: &TranslationUnit, count: usize, seed: u64) -> Option<Self> {
+        Some(Self {
+            inputs: Vec::new(),
+            generators: ArgGenerator::for_main(ir)?,
+            random_inputs: count,
+            seed,
+            ..Default::default()
+        })
+    }
+
+    fn all_inputs(&self) -> impl Iterator<Item = Vec<Value>> + '_ {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let random = (0..self.random_inputs).map(move |_| {
+            self.generators
+                .iter()
+                .map(|generator| generator.generate(&mut rng))
+                .collect()
+        });
+        self.inputs.iter().cloned().chain(random)
+    }
+}
+
+/// The verdict of [`equiv_by_execution`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    /// The modules behave the same on every input.
+    Equivalent,
+    /// The modules behave differently on `input`.
+    Different { input: Vec<Value>, reason: String },
+    /// No difference is found, but some runs are given up, e.g., on exceeding the step limit.
+    Inconclusive { reason: String },
+}
+
+/// Checks whether `actual` behaves like the reference module `expected` on `test_vectors`.
+///
+/// Each run compares the value `main` returns and the values of the global variables both modules
+/// declare. `actual` may behave arbitrarily on the inputs `expected` fails on, e.g., by an
+/// undefined behavior, and may refine the undefined values `expected` computes.
+pub fn equiv_by_execution(
+    expected: &TranslationUnit,
+    actual: &TranslationUnit,
+    test_vectors: &TestVectors,
+) -> Verdict {
+    let mut given_up = None;
+
+    for input in test_vectors.all_inputs() {
+        let run = |ir: &TranslationUnit| {
+            interp_bounded(
+                ir,
+                input.clone(),
+                test_vectors.options,
+                test_vectors.max_steps,
+            )
+        };
+
+        let expected_execution = match run(expected) {
+            Ok(Some(execution)) => execution,
+            Ok(None) => {
+                given_up = Some(format!("the reference does not return on {input:?}"));
+                continue;
+            }
+            Err(_) => continue,
+        };
+        let actual_execution = match run(actual) {
+            Ok(Some(execution)) => execution,
+            Ok(None) => {
+                given_up = Some(format!("the module does not return on {input:?}"));
+                continue;
+            }
+            Err(error) => {
+                return Verdict::Different {
+                    input,
+                    reason: format!("the module fails: {error}"),
+                }
+            }
+        };
+
+        if let Some(reason) = difference(&expected_execution, &actual_execution) {
+            return Verdict::Different { input, reason };
+        }
+    }
+
+    match given_up {
+        Some(reason) => Verdict::Inconclusive { reason },
+        None => Verdict::Equivalent,
+    }
+}
+
+fn difference(expected: &Execution, actual: &Execution) -> Option<String> {
+    if !refines(&expected.value, &actual.value) {
+        return Some(format!(
+            "`main` returns {:?} instead of {:?}",
+            actual.value, expected.value
+        ));
+    }
+
+    for (name, expected) in &expected.globals {
+        let actual = some_or!(actual.globals.get(name), continue);
+        if !refines(expected, actual) {
+            return Some(format!("@{name} is {actual:?} instead of {expected:?}"));
+        }
+    }
+
+    None
+}
+
+/// Whether `actual` is `expected` with its undefined parts possibly defined.
+fn refines(expected: &Value, actual: &Value) -> bool {
+    match (expected, actual) {
+        (Value::Undef { .. }, _) => true,
+        (
+            Value::Array { values, .. },
+            Value::Array {
+                values: actual_values,
+                ..
+            },
+        ) => {
+            values.len() == actual_values.len()
+                && values
+                    .iter()
+                    .zip(actual_values)
+                    .all(|(expected, actual)| refines(expected, actual))
+        }
+        (
+            Value::Struct { name, fields },
+            Value::Struct {
+                name: actual_name,
+                fields: actual_fields,
+            },
+        ) => {
+            name == actual_name
+                && fields.len() == actual_fields.len()
+                && fields
+                    .iter()
+                    .zip(actual_fields)
+                    .all(|(expected, actual)| refines(expected, actual))
+        }
+        _ => expected == actual,
+    }
+}
@@ -1,160 +1,17 @@
 use core::fmt;
 use core::iter;
 use core::mem;
-use ordered_float::OrderedFloat;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
 use thiserror::Error;
 
 use itertools::izip;
 
+use crate::ir::stdin::{parse_format, Conversion, Directive, Failure, Scanned, Stdin};
 use crate::ir::*;
 use crate::*;
 
-#[derive(Debug, PartialEq, Clone)]
-pub enum Value {
-    Undef {
-        dtype: Dtype,
-    },
-    Unit,
-    Int {
-        value: u128,
-        width: usize,
-        is_signed: bool,
-    },
-    Float {
-        /// `value` may be `f32`, but it is possible to consider it as `f64`.
-        ///
-        /// * Casting from an f32 to an f64 is perfect and lossless (f32 -> f64)
-        /// * Casting from an f64 to an f32 will produce the closest possible value (f64 -> f32)
-        ///
-        /// For more details: <https://doc.rust-lang.org/stable/reference/expressions/operator-expr.html#type-cast-expressions>
-        value: OrderedFloat<f64>,
-        width: usize,
-    },
-    Pointer {
-        bid: Option<usize>,
-        offset: isize,
-        dtype: Dtype,
-    },
-    Array {
-        inner_dtype: Dtype,
-        values: Vec<Value>,
-    },
-    Struct {
-        name: String,
-        fields: Vec<Named<Value>>,
-    },
-}
-
-impl TryFrom<Constant> for Value {
-    type Error = ();
-
-    fn try_from(constant: Constant) -> Result<Self, Self::Error> {
-        let value = match constant {
-            Constant::Undef { dtype } => Self::Undef { dtype },
-            Constant::Unit => Self::Unit,
-            Constant::Int {
-                value,
-                width,
-                is_signed,
-            } => Self::Int {
-                value,
-                width,
-                is_signed,
-            },
-            Constant::Float { value, width } => Self::Float { value, width },
-            _ => panic!(),
-        };
-
-        Ok(value)
-    }
-}
-
-impl HasDtype for Value {
-    fn dtype(&self) -> Dtype {
-        match self {
-            Self::Undef { dtype } => dtype.clone(),
-            Self::Unit => Dtype::unit(),
-            Self::Int {
-                width, is_signed, ..
-            } => Dtype::int(*width).set_signed(*is_signed),
-            Self::Float { width, .. } => Dtype::float(*width),
-            Self::Pointer { dtype, .. } => Dtype::pointer(dtype.clone()),
-            Self::Array {
-                inner_dtype,
-                values,
-            } => Dtype::array(inner_dtype.clone(), values.len()),
-            Self::Struct { name, fields } => {
-                let fields = fields
-                    .iter()
-                    .map(|f| Named::new(f.name().cloned(), f.deref().dtype()))
-                    .collect();
-                Dtype::structure(Some(name.clone()), Some(fields))
-            }
-        }
-    }
-}
-
 impl Value {
-    #[inline]
-    fn undef(dtype: Dtype) -> Self {
-        Self::Undef { dtype }
-    }
-
-    #[inline]
-    fn unit() -> Self {
-        Self::Unit
-    }
-
-    #[inline]
-    fn int(value: u128, width: usize, is_signed: bool) -> Self {
-        Self::Int {
-            value,
-            width,
-            is_signed,
-        }
-    }
-
-    #[inline]
-    fn float(value: f64, width: usize) -> Self {
-        Self::Float {
-            value: value.into(),
-            width,
-        }
-    }
-
-    #[inline]
-    fn pointer(bid: Option<usize>, offset: isize, dtype: Dtype) -> Self {
-        Self::Pointer { bid, offset, dtype }
-    }
-
-    #[inline]
-    fn array(inner_dtype: Dtype, values: Vec<Self>) -> Self {
-        Self::Array {
-            inner_dtype,
-            values,
-        }
-    }
-
-    #[inline]
-    fn structure(name: String, fields: Vec<Named<Value>>) -> Self {
-        Self::Struct { name, fields }
-    }
-
-    #[inline]
-    pub fn get_int(&self) -> Option<(u128, usize, bool)> {
-        if let Value::Int {
-            value,
-            width,
-            is_signed,
-        } = self
-        {
-            Some((*value, *width, *is_signed))
-        } else {
-            None
-        }
-    }
-
     #[inline]
     fn get_pointer(&self) -> Option<(&Option<usize>, &isize, &Dtype)> {
         if let Value::Pointer { bid, offset, dtype } = self {
@@ -163,137 +20,6 @@ impl Value {
             None
         }
     }
-
-    #[inline]
-    fn nullptr(dtype: Dtype) -> Self {
-        Self::Pointer {
-            bid: None,
-            offset: 0,
-            dtype,
-        }
-    }
-
-    #[inline]
-    fn default_from_dtype(
-        dtype: &Dtype,
-        structs: &HashMap<String, Option<Dtype>>,
-    ) -> Result<Self, ()> {
-        let value = match dtype {
-            Dtype::Unit { .. } => Self::unit(),
-            Dtype::Int {
-                width, is_signed, ..
-            } => Self::int(u128::default(), *width, *is_signed),
-            Dtype::Float { width, .. } => Self::float(f64::default(), *width),
-            Dtype::Pointer { inner, .. } => Self::nullptr(inner.deref().clone()),
-            Dtype::Array { inner, size } => {
-                let values = iter::repeat(Self::default_from_dtype(inner, structs))
-                    .take(*size)
-                    .collect::<Result<Vec<_>, _>>()?;
-                Self::array(inner.deref().clone(), values)
-            }
-            Dtype::Struct { name, .. } => {
-                let name = name.as_ref().expect("struct should have its name");
-                let struct_type = structs
-                    .get(name)
-                    .expect("struct type matched with `name` must exist")
-                    .as_ref()
-                    .expect("`struct_type` must have its definition");
-                let fields = struct_type
-                    .get_struct_fields()
-                    .expect("`struct_type` must be struct type")
-                    .as_ref()
-                    .expect("`fields` must be `Some`");
-
-                let fields = fields
-                    .iter()
-                    .map(|f| {
-                        let value = Self::default_from_dtype(f.deref(), structs)?;
-                        Ok(Named::new(f.name().cloned(), value))
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
-
-                Self::structure(name.clone(), fields)
-            }
-            Dtype::Function { .. } => panic!("function type does not have a default value"),
-            Dtype::Typedef { .. } => panic!("typedef should be replaced by real dtype"),
-        };
-
-        Ok(value)
-    }
-
-    #[allow(clippy::result_unit_err)]
-    pub fn try_from_initializer(
-        initializer: &ast::Initializer,
-        dtype: &Dtype,
-        structs: &HashMap<String, Option<Dtype>>,
-    ) -> Result<Self, ()> {
-        match initializer {
-            ast::Initializer::Expression(expr) => match dtype {
-                Dtype::Int { .. } | Dtype::Float { .. } | Dtype::Pointer { .. } => {
-                    let constant = Constant::try_from(&expr.node)?;
-                    let value = Self::try_from(constant)?;
-
-                    calculator::calculate_typecast(value, dtype.clone())
-                }
-                _ => Err(()),
-            },
-            ast::Initializer::List(items) => match dtype {
-                Dtype::Array { inner, size } => {
-                    let inner_dtype = inner.deref().clone();
-                    let num_of_items = items.len();
-                    let values = (0..*size)
-                        .map(|i| {
-                            if i < num_of_items {
-                                Self::try_from_initializer(
-                                    &items[i].node.initializer.node,
-                                    &inner_dtype,
-                                    structs,
-                                )
-                            } else {
-                                Self::default_from_dtype(&inner_dtype, structs)
-                            }
-                        })
-                        .collect::<Result<Vec<_>, _>>()?;
-
-                    Ok(Self::array(inner_dtype, values))
-                }
-                Dtype::Struct { name, .. } => {
-                    let name = name.as_ref().expect("struct should have its name");
-                    let struct_type = structs
-                        .get(name)
-                        .expect("struct type matched with `name` must exist")
-                        .as_ref()
-                        .expect("`struct_type` must have its definition");
-                    let fields = struct_type
-                        .get_struct_fields()
-                        .expect("`struct_type` must be struct type")
-                        .as_ref()
-                        .expect("`fields` must be `Some`");
-
-                    let fields = fields
-                        .iter()
-                        .enumerate()
-                        .map(|(i, f)| {
-                            let value = if let Some(item) = items.get(i) {
-                                Self::try_from_initializer(
-                                    &item.node.initializer.node,
-                                    f.deref(),
-                                    structs,
-                                )?
-                            } else {
-                                Self::default_from_dtype(f.deref(), structs)?
-                            };
-
-                            Ok(Named::new(f.name().cloned(), value))
-                        })
-                        .collect::<Result<Vec<_>, _>>()?;
-
-                    Ok(Self::structure(name.clone(), fields))
-                }
-                _ => Err(()),
-            },
-        }
-    }
 }
 
 #[derive(Debug, PartialEq, Eq, Error)]
@@ -304,6 +30,8 @@ pub enum InterpreterError {
     NoMainFunction,
     #[error("ir has no function definition of {} function", func_name)]
     NoFunctionDefinition { func_name: String },
+    #[error("cannot call {func_name}: {reason}")]
+    InvalidCall { func_name: String, reason: String },
     #[error("ir has no structure definition of {struct_name} structure")]
     NoStructureDefinition { struct_name: String },
     #[error("{func_name}:{pc} / {msg}")]
@@ -409,403 +137,6 @@ impl<'i> StackFrame<'i> {
     }
 }
 
-mod calculator {
-    use super::Value;
-    use crate::ir::*;
-    use lang_c::ast;
-    use std::cmp::Ordering;
-
-    fn calculate_integer_binary_operator_expression(
-        op: &ast::BinaryOperator,
-        lhs: u128,
-        rhs: u128,
-        width: usize,
-        is_signed: bool,
-    ) -> Result<Value, ()> {
-        let result = match op {
-            // TODO: explain why plus & minus do not need to consider `is_signed'
-            ast::BinaryOperator::Plus => (lhs as i128 + rhs as i128) as u128,
-            ast::BinaryOperator::Minus => (lhs as i128 - rhs as i128) as u128,
-            ast::BinaryOperator::Multiply => {
-                if is_signed {
-                    (lhs as i128 * rhs as i128) as u128
-                } else {
-                    lhs * rhs
-                }
-            }
-            ast::BinaryOperator::Divide => {
-                assert!(rhs != 0);
-                if is_signed {
-                    (lhs as i128 / rhs as i128) as u128
-                } else {
-                    lhs / rhs
-                }
-            }
-            ast::BinaryOperator::Modulo => {
-                assert!(rhs != 0);
-                if is_signed {
-                    (lhs as i128 % rhs as i128) as u128
-                } else {
-                    lhs % rhs
-                }
-            }
-            ast::BinaryOperator::ShiftLeft => {
-                let rhs = if is_signed {
-                    let rhs = rhs as i128;
-                    assert!(rhs >= 0);
-                    assert!(rhs < (width as i128));
-                    rhs as u128
-                } else {
-                    assert!(rhs < (width as u128));
-                    rhs
-                };
-
-                lhs << rhs
-            }
-            ast::BinaryOperator::ShiftRight => {
-                if is_signed {
-                    // arithmetic shift right
-                    let rhs = rhs as i128;
-                    assert!(rhs >= 0);
-                    assert!(rhs < (width as i128));
-                    ((lhs as i128) >> rhs) as u128
-                } else {
-                    // logical shift right
-                    assert!(rhs < (width as u128));
-                    let bit_mask = (1u128 << width as u128) - 1;
-                    let lhs = lhs & bit_mask;
-                    lhs >> rhs
-                }
-            }
-            ast::BinaryOperator::BitwiseAnd => lhs & rhs,
-            ast::BinaryOperator::BitwiseXor => lhs ^ rhs,
-            ast::BinaryOperator::BitwiseOr => lhs | rhs,
-            ast::BinaryOperator::Equals => {
-                let result = (lhs == rhs).into();
-                return Ok(Value::int(result, 1, false));
-            }
-            ast::BinaryOperator::NotEquals => {
-                let result = (lhs != rhs).into();
-                return Ok(Value::int(result, 1, false));
-            }
-            ast::BinaryOperator::Less => {
-                let condition = if is_signed {
-                    (lhs as i128) < (rhs as i128)
-                } else {
-                    lhs < rhs
-                };
-                let result = condition.into();
-                return Ok(Value::int(result, 1, false));
-            }
-            ast::BinaryOperator::Greater => {
-                let condition = if is_signed {
-                    (lhs as i128) > (rhs as i128)
-                } else {
-                    lhs > rhs
-                };
-                let result = condition.into();
-                return Ok(Value::int(result, 1, false));
-            }
-            ast::BinaryOperator::LessOrEqual => {
-                let condition = if is_signed {
-                    (lhs as i128) <= (rhs as i128)
-                } else {
-                    lhs <= rhs
-                };
-                let result = condition.into();
-                return Ok(Value::int(result, 1, false));
-            }
-            ast::BinaryOperator::GreaterOrEqual => {
-                let condition = if is_signed {
-                    (lhs as i128) >= (rhs as i128)
-                } else {
-                    lhs >= rhs
-                };
-                let result = condition.into();
-                return Ok(Value::int(result, 1, false));
-            }
-            _ => todo!(
-                "calculate_binary_operator_expression: not supported operator {:?}",
-                op
-            ),
-        };
-
-        let result = if is_signed {
-            sign_extension(result, width as u128)
-        } else {
-            trim_unnecessary_bits(result, width as u128)
-        };
-
-        Ok(Value::int(result, width, is_signed))
-    }
-
-    fn calculate_float_binary_operator_expression(
-        op: &ast::BinaryOperator,
-        lhs: OrderedFloat<f64>,
-        rhs: OrderedFloat<f64>,
-        width: usize,
-    ) -> Result<Value, ()> {
-        let (lhs, rhs) = if width == Dtype::SIZE_OF_FLOAT * Dtype::BITS_OF_BYTE {
-            (
-                lhs.into_inner() as f32 as f64,
-                rhs.into_inner() as f32 as f64,
-            )
-        } else {
-            (lhs.into_inner(), rhs.into_inner())
-        };
-
-        let result = match op {
-            ast::BinaryOperator::Plus => lhs + rhs,
-            ast::BinaryOperator::Minus => lhs - rhs,
-            ast::BinaryOperator::Multiply => lhs * rhs,
-            ast::BinaryOperator::Divide => {
-                assert!(rhs != 0.0);
-                lhs / rhs
-            }
-            ast::BinaryOperator::Equals => {
-                let order = lhs
-                    .partial_cmp(&rhs)
-                    .expect("`lhs` and `rhs` must be not NAN");
-                let result = (Ordering::Equal == order).into();
-                return Ok(Value::int(result, 1, false));
-            }
-            ast::BinaryOperator::NotEquals => {
-                let order = lhs
-                    .partial_cmp(&rhs)
-                    .expect("`lhs` and `rhs` must be not NAN");
-                let result = (Ordering::Equal != order).into();
-                return Ok(Value::int(result, 1, false));
-            }
-            ast::BinaryOperator::Less => {
-                let result = lhs.lt(&rhs).into();
-                return Ok(Value::int(result, 1, false));
-            }
-            ast::BinaryOperator::Greater => {
-                let result = lhs.gt(&rhs).into();
-                return Ok(Value::int(result, 1, false));
-            }
-            ast::BinaryOperator::LessOrEqual => {
-                let result = lhs.le(&rhs).into();
-                return Ok(Value::int(result, 1, false));
-            }
-            ast::BinaryOperator::GreaterOrEqual => {
-                let result = lhs.ge(&rhs).into();
-                return Ok(Value::int(result, 1, false));
-            }
-            _ => todo!(
-                "calculate_binary_operator_expression: not supported case for \
-                 {:?} {:?} {:?}",
-                op,
-                lhs,
-                rhs
-            ),
-        };
-
-        Ok(Value::float(result, width))
-    }
-
-    // TODO: change to template function in the future
-    pub fn calculate_binary_operator_expression(
-        op: &ast::BinaryOperator,
-        lhs: Value,
-        rhs: Value,
-    ) -> Result<Value, ()> {
-        match (lhs, rhs) {
-            (Value::Undef { dtype }, _) => Ok(Value::undef(dtype)),
-            (_, Value::Undef { dtype }) => Ok(Value::undef(dtype)),
-            (
-                Value::Int {
-                    value: lhs,
-                    width: lhs_w,
-                    is_signed: lhs_s,
-                },
-                Value::Int {
-                    value: rhs,
-                    width: rhs_w,
-                    is_signed: rhs_s,
-                },
-            ) => {
-                assert_eq!(lhs_w, rhs_w);
-                assert_eq!(lhs_s, rhs_s);
-
-                calculate_integer_binary_operator_expression(op, lhs, rhs, lhs_w, lhs_s)
-            }
-            (
-                Value::Float {
-                    value: lhs,
-                    width: lhs_w,
-                },
-                Value::Float {
-                    value: rhs,
-                    width: rhs_w,
-                },
-            ) => {
-                assert_eq!(lhs_w, rhs_w);
-
-                calculate_float_binary_operator_expression(op, lhs, rhs, lhs_w)
-            }
-            (
-                Value::Pointer { bid, offset, .. },
-                Value::Pointer {
-                    bid: other_bid,
-                    offset: other_offset,
-                    ..
-                },
-            ) => match op {
-                ast::BinaryOperator::Equals => {
-                    let result = (bid == other_bid && offset == other_offset).into();
-                    Ok(Value::int(result, 1, false))
-                }
-                ast::BinaryOperator::NotEquals => {
-                    let result = (!(bid == other_bid && offset == other_offset)).into();
-                    Ok(Value::int(result, 1, false))
-                }
-                _ => todo!(
-                    "calculate_binary_operator_expression: not supported case for \
-                     {:?} between pointer and integer value",
-                    op,
-                ),
-            },
-            (lhs, rhs) => todo!(
-                "calculate_binary_operator_expression: not supported case for {:?} {:?} {:?}",
-                op,
-                lhs,
-                rhs
-            ),
-        }
-    }
-
-    pub fn calculate_unary_operator_expression(
-        op: &ast::UnaryOperator,
-        operand: Value,
-    ) -> Result<Value, ()> {
-        match operand {
-            Value::Undef { dtype } => Ok(Value::undef(dtype)),
-            Value::Int {
-                value,
-                width,
-                is_signed,
-            } => {
-                match op {
-                    ast::UnaryOperator::Plus => Ok(Value::int(value, width, is_signed)),
-                    ast::UnaryOperator::Minus => {
-                        let result = if is_signed {
-                            (-(value as i128)) as u128
-                        } else {
-                            let value = (-(value as i128)) as u128;
-                            trim_unnecessary_bits(value, width as u128)
-                        };
-                        Ok(Value::int(result, width, is_signed))
-                    }
-                    ast::UnaryOperator::Negate => {
-                        // Check if it is boolean
-                        assert!(width == 1);
-                        let result = (value == 0).into();
-                        Ok(Value::int(result, width, is_signed))
-                    }
-                    _ => todo!(
-                        "calculate_unary_operator_expression: not supported case for {:?} {:?}",
-                        op,
-                        operand,
-                    ),
-                }
-            }
-            Value::Float { value, width } => match op {
-                ast::UnaryOperator::Plus => Ok(Value::float(value.into_inner(), width)),
-                ast::UnaryOperator::Minus => Ok(Value::float(-value.into_inner(), width)),
-                _ => todo!(
-                    "calculate_unary_operator_expression: not supported case for {:?} {:?}",
-                    op,
-                    operand,
-                ),
-            },
-            _ => todo!(
-                "calculate_unary_operator_expression: not supported case for {:?} {:?}",
-                op,
-                operand,
-            ),
-        }
-    }
-
-    pub fn calculate_typecast(value: Value, dtype: Dtype) -> Result<Value, ()> {
-        if value.dtype() == dtype {
-            return Ok(value);
-        }
-
-        match (value, dtype) {
-            (Value::Undef { .. }, dtype) => Ok(Value::undef(dtype)),
-            (
-                Value::Int { value, width, .. },
-                Dtype::Int {
-                    width: target_width,
-                    is_signed: target_signed,
-                    ..
-                },
-            ) => {
-                let result = if target_signed {
-                    if width >= target_width {
-                        // TODO: explain the logic in the future
-                        let value = trim_unnecessary_bits(value, target_width as u128);
-                        sign_extension(value, target_width as u128)
-                    } else {
-                        value
-                    }
-                } else {
-                    trim_unnecessary_bits(value, target_width as u128)
-                };
-
-                Ok(Value::int(result, target_width, target_signed))
-            }
-            (
-                Value::Int {
-                    value, is_signed, ..
-                },
-                Dtype::Float { width, .. },
-            ) => {
-                let size = (width - 1) / Dtype::BITS_OF_BYTE + 1;
-                let casted_value = match (is_signed, size) {
-                    (true, Dtype::SIZE_OF_FLOAT) => value as i128 as f32 as f64,
-                    (true, Dtype::SIZE_OF_DOUBLE) => value as i128 as f64,
-                    (false, Dtype::SIZE_OF_FLOAT) => value as f32 as f64,
-                    (false, Dtype::SIZE_OF_DOUBLE) => value as f64,
-                    _ => panic!(
-                        "calculate_typecast: not supported case \
-                            typecast int to float when `width` is {width}"
-                    ),
-                };
-                Ok(Value::float(casted_value, width))
-            }
-            (Value::Int { value, .. }, Dtype::Pointer { inner, .. }) => {
-                if value == 0 {
-                    Ok(Value::pointer(None, 0, *inner))
-                } else {
-                    panic!(
-                        "calculate_typecast: not support case \
-                            typecast int to pointer when `value` is {value}"
-                    )
-                }
-            }
-            (
-                Value::Float { value, .. },
-                Dtype::Int {
-                    width, is_signed, ..
-                },
-            ) => {
-                let casted_value = if is_signed {
-                    value.into_inner() as i128 as u128
-                } else {
-                    value.into_inner() as u128
-                };
-                Ok(Value::int(casted_value, width, is_signed))
-            }
-            (Value::Float { value, .. }, Dtype::Float { width, .. }) => {
-                Ok(Value::float(value.into_inner(), width))
-            }
-            (value, dtype) => todo!("calculate_typecast ({:?}) {:?}", value, dtype),
-        }
-    }
-}
-
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 enum Byte {
     Undef,
@@ -851,7 +182,58 @@ impl Byte {
 
     fn block_from_dtype(dtype: &Dtype, structs: &HashMap<String, Option<Dtype>>) -> Vec<Self> {
         let size = dtype.size_align_of(structs).unwrap().0;
-        iter::repeat(Self::Undef).take(size).collect()
+        let mut block = iter::repeat(Self::Undef).take(size).collect::<Vec<_>>();
+        Self::define_bit_field_units(dtype, structs, &mut block);
+        block
+    }
+
+    /// Defines the bytes of the storage units of the bit-fields in `bytes`, those of an object of
+    /// `dtype`, as zero. A bit-field is stored by loading and storing its whole unit, which would
+    /// otherwise be undefined until every field sharing it is stored.
+    fn define_bit_field_units(
+        dtype: &Dtype,
+        structs: &HashMap<String, Option<Dtype>>,
+        bytes: &mut [Self],
+    ) {
+        match dtype {
+            Dtype::Array { inner, size, .. } => {
+                let (inner_size, inner_align) = inner.size_align_of(structs).unwrap();
+                let stride = std::cmp::max(inner_size, inner_align);
+                for i in 0..size.expect("`dtype` must be complete") {
+                    Self::define_bit_field_units(inner, structs, &mut bytes[i * stride..]);
+                }
+            }
+            Dtype::Struct {
+                name: Some(name), ..
+            } => {
+                let struct_type = some_or!(structs.get(name).and_then(Option::as_ref), return);
+                let fields = some_or!(
+                    struct_type.get_struct_fields().and_then(Option::as_ref),
+                    return
+                );
+                let (_, _, offsets) = some_or!(
+                    struct_type
+                        .get_struct_size_align_offsets()
+                        .and_then(Option::as_ref),
+                    return
+                );
+
+                for (i, (f, o)) in izip!(fields, offsets).enumerate() {
+                    if struct_type.get_struct_bit_field(i).is_none() {
+                        Self::define_bit_field_units(f.deref(), structs, &mut bytes[*o..]);
+                        continue;
+                    }
+
+                    let (size, _) = f.size_align_of(structs).unwrap();
+                    for byte in &mut bytes[*o..*o + size] {
+                        if *byte == Self::Undef {
+                            *byte = Self::concrete(0);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
     }
 
     fn u128_to_bytes(mut value: u128, size: usize) -> Vec<u8> {
@@ -875,6 +257,46 @@ impl Byte {
         u128::from_le_bytes(array)
     }
 
+    /// Returns the value of the bit-field `bit_field` of type `dtype` in `bytes`, those of its
+    /// storage unit. Only the bytes holding the bits of the field need to be defined.
+    fn bytes_to_bit_field(bytes: &[Self], bit_field: BitField, dtype: &Dtype) -> Value {
+        let width = dtype.get_int_width().expect("`dtype` must be an integer");
+        let is_signed = dtype.is_int_signed();
+
+        let mut value = 0;
+        for bit in 0..bit_field.width {
+            let position = bit_field.bit_offset + bit;
+            let byte = some_or!(
+                bytes[position / Dtype::BITS_OF_BYTE].get_concrete(),
+                return Value::undef(dtype.clone())
+            );
+            let byte_bit = (byte >> (position % Dtype::BITS_OF_BYTE)) & 1;
+            value |= u128::from(byte_bit) << bit;
+        }
+        if is_signed && bit_field.width > 0 {
+            value = sign_extension(value, bit_field.width as u128);
+        }
+
+        Value::int(value, width, is_signed)
+    }
+
+    /// Writes the bit-field `bit_field` holding `value` into `bytes`, those of its storage unit,
+    /// keeping the bits of the other fields sharing the bytes.
+    fn bit_field_to_bytes(value: &Value, bit_field: BitField, bytes: &mut [Self]) {
+        let (value, _, _) = some_or!(value.get_int(), return);
+        for bit in 0..bit_field.width {
+            let position = bit_field.bit_offset + bit;
+            let byte = &mut bytes[position / Dtype::BITS_OF_BYTE];
+            let mask = 1 << (position % Dtype::BITS_OF_BYTE);
+            let old = byte.get_concrete().unwrap_or(0);
+            *byte = Self::concrete(if (value >> bit) & 1 == 1 {
+                old | mask
+            } else {
+                old & !mask
+            });
+        }
+    }
+
     fn bytes_to_value<'b, I>(
         bytes: &mut I,
         dtype: &Dtype,
@@ -911,18 +333,14 @@ impl Byte {
                     return Ok(Value::undef(dtype.clone()))
                 );
                 let value = Self::bytes_to_u128(&value, false);
-                let value = if size == Dtype::SIZE_OF_FLOAT {
-                    f32::from_bits(value as u32) as f64
-                } else {
-                    f64::from_bits(value as u64)
-                };
+                let value = float_value_from_bits(value as u64, *width);
 
                 Ok(Value::float(value, *width))
             }
             Dtype::Pointer { inner, .. } => {
                 let bytes = bytes
                     .by_ref()
-                    .take(Dtype::SIZE_OF_POINTER)
+                    .take(target_data().pointer_size)
                     .collect::<Vec<_>>();
                 let value = some_or!(
                     bytes
@@ -946,10 +364,10 @@ impl Byte {
                     },
                 )
             }
-            Dtype::Array { inner, size } => {
+            Dtype::Array { inner, size, .. } => {
                 let (inner_size, inner_align) = inner.size_align_of(structs).unwrap();
                 let padding = std::cmp::max(inner_size, inner_align) - inner_size;
-                let values = (0..*size)
+                let values = (0..size.expect("`dtype` must be complete"))
                     .map(|_| {
                         let value = Self::bytes_to_value(bytes, inner, structs)?;
                         if padding > 0 {
@@ -984,9 +402,14 @@ impl Byte {
 
                 assert_eq!(fields.len(), offsets.len());
                 let fields = izip!(fields, offsets)
-                    .map(|(f, o)| {
-                        let mut sub_bytes = bytes[*o..].iter();
-                        let value = Self::bytes_to_value(&mut sub_bytes, f.deref(), structs)?;
+                    .enumerate()
+                    .map(|(i, (f, o))| {
+                        let value = if let Some(bit_field) = struct_type.get_struct_bit_field(i) {
+                            Self::bytes_to_bit_field(&bytes[*o..], bit_field, f.deref())
+                        } else {
+                            let mut sub_bytes = bytes[*o..].iter();
+                            Self::bytes_to_value(&mut sub_bytes, f.deref(), structs)?
+                        };
                         Ok(Named::new(f.name().cloned(), value))
                     })
                     .collect::<Result<Vec<_>, InterpreterError>>()?;
@@ -998,6 +421,7 @@ impl Byte {
             }
             Dtype::Function { .. } => panic!("function value cannot be constructed from bytes"),
             Dtype::Typedef { .. } => panic!("typedef should be replaced by real dtype"),
+            Dtype::Enum { .. } => panic!("enum should be replaced by int"),
         }
     }
 
@@ -1015,21 +439,18 @@ impl Byte {
                     .collect::<Vec<_>>()
             }
             Value::Float {
-                value: float_value, ..
+                value: float_value,
+                width,
             } => {
                 let size = value.dtype().size_align_of(structs).unwrap().0;
-                let value_bits: u128 = match size {
-                    Dtype::SIZE_OF_FLOAT => (float_value.into_inner() as f32).to_bits() as u128,
-                    Dtype::SIZE_OF_DOUBLE => (float_value.into_inner()).to_bits() as u128,
-                    _ => panic!("value_to_bytes: {size} is not a valid float size"),
-                };
+                let value_bits = u128::from(float_value_to_bits(float_value.into_inner(), *width));
 
                 Self::u128_to_bytes(value_bits, size)
                     .iter()
                     .map(|b| Self::concrete(*b))
                     .collect::<Vec<_>>()
             }
-            Value::Pointer { bid, offset, .. } => (0..Dtype::SIZE_OF_POINTER)
+            Value::Pointer { bid, offset, .. } => (0..target_data().pointer_size)
                 .map(|i| Self::pointer(*bid, *offset, i))
                 .collect(),
             Value::Array {
@@ -1053,18 +474,30 @@ impl Byte {
                     .expect("struct type matched with `name` must exist")
                     .as_ref()
                     .expect("`struct_type` must have its definition");
-                let (size_of, _, offsets) = struct_type
+                let (_, _, offsets) = struct_type
                     .get_struct_size_align_offsets()
                     .expect("`struct_type` must be struct type")
                     .as_ref()
                     .expect("`offsets` must be `Some`");
-                let mut values = iter::repeat(Byte::Undef).take(*size_of).collect::<Vec<_>>();
+                let mut values =
+                    Self::block_from_dtype(&Dtype::structure(Some(name.clone()), None), structs);
 
                 assert_eq!(fields.len(), offsets.len());
-                izip!(fields, offsets).for_each(|(f, o)| {
+                // The members of a union overlap, and only the bytes defined by a member may
+                // overwrite those of another, e.g., the bytes of `int` under a larger `double`.
+                let is_union = struct_type.is_union();
+                izip!(fields, offsets).enumerate().for_each(|(i, (f, o))| {
+                    if let Some(bit_field) = struct_type.get_struct_bit_field(i) {
+                        Self::bit_field_to_bytes(f.deref(), bit_field, &mut values[*o..]);
+                        return;
+                    }
+
                     let result = Self::value_to_bytes(f.deref(), structs);
-                    let size_of_data = f.deref().dtype().size_align_of(structs).unwrap().0;
-                    let _unused = values.splice(*o..(*o + size_of_data), result);
+                    for (byte, value) in izip!(result, &mut values[*o..]) {
+                        if !is_union || byte != Byte::Undef {
+                            *value = byte;
+                        }
+                    }
                 });
 
                 values
@@ -1133,16 +566,102 @@ impl Memory {
         let bytes = Byte::value_to_bytes(value, structs);
         let block = self.inner[bid].as_mut().unwrap();
 
-        if 0 <= offset && end <= block.len() {
-            let _unused = block.splice(offset as usize..end, bytes);
-            Ok(())
-        } else {
-            Err(())
-        }
+        if 0 <= offset && end <= block.len() {
+            let _unused = block.splice(offset as usize..end, bytes);
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Copies `size` bytes from `(src_bid, src_offset)` to `(dst_bid, dst_offset)`.
+    fn copy(
+        &mut self,
+        dst_bid: usize,
+        dst_offset: isize,
+        src_bid: usize,
+        src_offset: isize,
+        size: usize,
+    ) -> Result<(), ()> {
+        let src_block = self.inner[src_bid].as_ref().ok_or(())?;
+        let src_start = usize::try_from(src_offset).map_err(|_| ())?;
+        let bytes = src_block
+            .get(src_start..src_start + size)
+            .ok_or(())?
+            .to_vec();
+        self.splice(dst_bid, dst_offset, bytes)
+    }
+
+    /// Sets `size` bytes from `(bid, offset)` to `byte`.
+    fn fill(&mut self, bid: usize, offset: isize, byte: u8, size: usize) -> Result<(), ()> {
+        self.splice(bid, offset, vec![Byte::concrete(byte); size])
+    }
+
+    fn splice(&mut self, bid: usize, offset: isize, bytes: Vec<Byte>) -> Result<(), ()> {
+        let block = self.inner[bid].as_mut().ok_or(())?;
+        let start = usize::try_from(offset).map_err(|_| ())?;
+        let end = start + bytes.len();
+        if end > block.len() {
+            return Err(());
+        }
+
+        let _unused = block.splice(start..end, bytes);
+        Ok(())
+    }
+}
+
+/// A function bound by [`Bindings::function`].
+type BoundFunction = Box<dyn FnMut(&[Value]) -> Result<Value, String>>;
+
+/// Definitions given by the host to the functions and the `extern` variables a module declares but
+/// does not define, e.g., those of the harness it is linked with, so that the module can be
+/// interpreted on its own.
+#[derive(Default)]
+pub struct Bindings {
+    functions: HashMap<String, BoundFunction>,
+    variables: HashMap<String, Value>,
+    stdin: Stdin,
+}
+
+impl fmt::Debug for Bindings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bindings")
+            .field("functions", &self.functions.keys().collect::<Vec<_>>())
+            .field("variables", &self.variables)
+            .field("stdin", &self.stdin.is_bound())
+            .finish()
+    }
+}
+
+impl Bindings {
+    /// Binds the function `name` to `function`, which is called with the arguments of each call and
+    /// returns its return value, or the reason why the call fails.
+    pub fn function<F>(mut self, name: &str, function: F) -> Self
+    where
+        F: FnMut(&[Value]) -> Result<Value, String> + 'static,
+    {
+        let _unused = self.functions.insert(name.to_string(), Box::new(function));
+        self
+    }
+
+    /// Binds the `extern` variable `name` to the initial value `value`.
+    ///
+    /// An `extern` variable that is not bound is left uninitialized.
+    pub fn variable(mut self, name: &str, value: Value) -> Self {
+        let _unused = self.variables.insert(name.to_string(), value);
+        self
+    }
+
+    /// Binds the standard input read by the [`StdinFunction`]s to `input`.
+    ///
+    /// The standard input is empty unless bound.
+    pub fn stdin<R: Read + 'static>(mut self, input: R) -> Self {
+        self.stdin = Stdin::new(Box::new(input));
+        self
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 struct State<'i> {
     /// Maps each global variable to a pointer value.
     ///
@@ -1152,10 +671,25 @@ struct State<'i> {
     pub stack: Vec<StackFrame<'i>>,
     pub memory: Memory,
     pub ir: &'i TranslationUnit,
+    pub options: CompileOptions,
+    /// Whether `main` is evaluated as a constant expression, which may neither call functions,
+    /// load from non-constant memory nor perform undefined operations.
+    pub constant_expression: bool,
+    /// The number of jumps taken, including those of conditional branches and switches.
+    pub branches: usize,
+    /// The number of calls to each function, by its name.
+    pub calls: BTreeMap<String, usize>,
+    /// The definitions of the functions and variables defined in another unit
+    pub bindings: Bindings,
 }
 
 impl<'i> State<'i> {
-    fn new(ir: &'i TranslationUnit, args: Vec<Value>) -> Result<State<'_>, InterpreterError> {
+    fn new(
+        ir: &'i TranslationUnit,
+        args: Vec<Value>,
+        options: CompileOptions,
+        bindings: Bindings,
+    ) -> Result<State<'_>, InterpreterError> {
         // Interpreter starts with the main function
         let func_name = String::from("main");
         let func = ir
@@ -1171,6 +705,19 @@ impl<'i> State<'i> {
                 func_name: func_name.clone(),
             })?;
 
+        Self::start(ir, func_name, func_def, args, options, bindings)
+    }
+
+    /// Creates the state calling `func_def`, the definition of the function `func_name`, with
+    /// `args`.
+    fn start(
+        ir: &'i TranslationUnit,
+        func_name: String,
+        func_def: &'i FunctionDefinition,
+        args: Vec<Value>,
+        options: CompileOptions,
+        bindings: Bindings,
+    ) -> Result<State<'i>, InterpreterError> {
         // Create State
         let mut state = State {
             global_map: GlobalMap::default(),
@@ -1178,11 +725,16 @@ impl<'i> State<'i> {
             stack: Vec::new(),
             memory: Default::default(),
             ir,
+            options,
+            constant_expression: false,
+            branches: 0,
+            calls: BTreeMap::new(),
+            bindings,
         };
 
         state.alloc_global_variables()?;
 
-        // Initialize state with main function and args
+        // Initialize state with the entry function and args
         state.write_args(func_def.bid_init, args)?;
         state.alloc_local_variables()?;
 
@@ -1190,24 +742,43 @@ impl<'i> State<'i> {
     }
 
     fn alloc_global_variables(&mut self) -> Result<(), InterpreterError> {
+        // Memory allocation, before any initialization since an initializer may refer to any
+        // global, e.g., `int (*fp)(void) = f;`
         for (name, decl) in &self.ir.decls {
-            // Memory allocation
             let bid = self.memory.alloc(&decl.dtype(), &self.ir.structs)?;
             self.global_map.insert(name.clone(), bid)?;
+        }
+
+        for (name, decl) in &self.ir.decls {
+            let bid = self
+                .global_map
+                .get_bid(name)
+                .expect("`name` must be allocated");
 
             // Initialize allocated memory space
             match decl {
-                Declaration::Variable { dtype, initializer } => {
-                    let value = if let Some(initializer) = initializer {
-                        Value::try_from_initializer(initializer, dtype, &self.ir.structs).map_err(
-                            |_| InterpreterError::Misc {
-                                func_name: self.stack_frame.func_name.clone(),
-                                pc: self.stack_frame.pc,
-                                msg: format!(
-                                    "fail to translate `Initializer` and `{dtype}` to `Value`"
-                                ),
-                            },
-                        )?
+                Declaration::Variable {
+                    dtype,
+                    initializer,
+                    is_extern,
+                    ..
+                } => {
+                    let value = if *is_extern {
+                        some_or!(self.bindings.variables.get(name).cloned(), continue)
+                    } else if let Some(initializer) = initializer {
+                        Value::try_from_initializer_with(
+                            initializer,
+                            dtype,
+                            &self.ir.structs,
+                            &|name| self.global_map.get_bid(name),
+                        )
+                        .map_err(|_| InterpreterError::Misc {
+                            func_name: self.stack_frame.func_name.clone(),
+                            pc: self.stack_frame.pc,
+                            msg: format!(
+                                "fail to translate `Initializer` and `{dtype}` to `Value`"
+                            ),
+                        })?
                     } else {
                         Value::default_from_dtype(dtype, &self.ir.structs)
                             .expect("default value must be derived from `dtype`")
@@ -1232,6 +803,19 @@ impl<'i> State<'i> {
         Ok(())
     }
 
+    fn global_values(&self) -> Result<BTreeMap<String, Value>, InterpreterError> {
+        self.ir
+            .decls
+            .iter()
+            .filter_map(|(name, decl)| {
+                let (dtype, _) = decl.get_variable()?;
+                let bid = self.global_map.get_bid(name)?;
+                let value = self.memory.load(bid, 0, dtype, &self.ir.structs);
+                Some(value.map(|value| (name.clone(), value)))
+            })
+            .collect()
+    }
+
     fn alloc_local_variables(&mut self) -> Result<(), InterpreterError> {
         // add alloc register
         for (id, allocation) in self.stack_frame.func_def.allocations.iter().enumerate() {
@@ -1291,8 +875,22 @@ impl<'i> State<'i> {
         let prev_stack_frame = some_or!(self.stack.pop(), return Ok(Some(return_value)));
         self.stack_frame = prev_stack_frame;
 
+        // A function called through a pointer to another function type returns its value in the
+        // register the call reads, e.g., an `u32` read as an `i32` keeps its bits.
+        let pc = self.stack_frame.pc;
+        let call = &self.stack_frame.func_def.blocks[&pc.bid].instructions[pc.iid];
+        let return_value = match (call.deref(), &return_value) {
+            (Instruction::Call { return_type, .. }, Value::Int { width, .. })
+                if return_type.get_int_width() == Some(*width) =>
+            {
+                calculator::calculate_typecast(return_value, return_type.clone())
+                    .expect("integers of the same width must be cast")
+            }
+            _ => return_value,
+        };
+
         // create temporary register to write return value
-        let register = RegisterId::temp(self.stack_frame.pc.bid, self.stack_frame.pc.iid);
+        let register = RegisterId::temp(pc.bid, pc.iid);
         self.stack_frame.registers.write(register, return_value);
         self.stack_frame.pc.increment();
         Ok(None)
@@ -1308,15 +906,23 @@ impl<'i> State<'i> {
 
     fn interp_args(
         &self,
+        func_name: &str,
         signature: &FunctionSignature,
         args: &[Operand],
     ) -> Result<Vec<Value>, InterpreterError> {
-        // Check that the dtype of each args matches the expected
+        // Check that the dtype of each args matches the expected, which a call through a pointer
+        // to another function type may violate.
         if !(args.len() == signature.params.len()
             && izip!(args, &signature.params)
-                .all(|(a, d)| a.dtype().set_const(false) == d.clone().set_const(false)))
+                .all(|(a, d)| a.dtype().strip_outer_const() == d.clone().strip_outer_const()))
         {
-            panic!("dtype of args and params must be compatible")
+            return Err(InterpreterError::InvalidCall {
+                func_name: func_name.to_string(),
+                reason: format!(
+                    "the arguments do not match the parameters of `{}`",
+                    signature.dtype()
+                ),
+            });
         }
 
         args.iter()
@@ -1334,7 +940,7 @@ impl<'i> State<'i> {
 
         assert_eq!(arg.args.len(), block.phinodes.len());
         for (a, d) in izip!(&arg.args, &block.phinodes) {
-            assert!(a.dtype().set_const(false) == d.deref().clone().set_const(false));
+            assert!(a.dtype().strip_outer_const() == d.deref().clone().strip_outer_const());
         }
 
         arg.args
@@ -1350,6 +956,7 @@ impl<'i> State<'i> {
             });
 
         self.stack_frame.pc = Pc::new(arg.bid);
+        self.branches += 1;
         Ok(None)
     }
 
@@ -1398,6 +1005,32 @@ impl<'i> State<'i> {
                 let lhs = self.interp_operand(lhs)?;
                 let rhs = self.interp_operand(rhs)?;
 
+                if self.options.overflow_policy != OverflowPolicy::Wrap
+                    && calculator::is_shift_out_of_range(op, &lhs, &rhs)
+                {
+                    return Err(self.shift_out_of_range());
+                }
+
+                if self.options.overflow_policy != OverflowPolicy::Wrap
+                    && calculator::is_division_by_zero(op, &rhs)
+                {
+                    return Err(self.division_by_zero());
+                }
+
+                if self.options.overflow_policy != OverflowPolicy::Wrap
+                    && calculator::has_signed_overflow(op, &lhs, &rhs)
+                {
+                    return Err(self.signed_overflow());
+                }
+
+                if self.constant_expression && !calculator::is_well_defined(op, &lhs, &rhs) {
+                    return Err(InterpreterError::Misc {
+                        func_name: self.stack_frame.func_name.clone(),
+                        pc: self.stack_frame.pc,
+                        msg: format!("undefined `{op:?}` in a constant expression"),
+                    });
+                }
+
                 calculator::calculate_binary_operator_expression(op, lhs, rhs).map_err(|_| {
                     InterpreterError::Misc {
                         func_name: self.stack_frame.func_name.clone(),
@@ -1409,6 +1042,13 @@ impl<'i> State<'i> {
             Instruction::UnaryOp { op, operand, .. } => {
                 let operand = self.interp_operand(operand)?;
 
+                if self.options.overflow_policy != OverflowPolicy::Wrap
+                    && *op == ast::UnaryOperator::Minus
+                    && calculator::is_signed_min(&operand)
+                {
+                    return Err(self.signed_overflow());
+                }
+
                 calculator::calculate_unary_operator_expression(op, operand).map_err(|_| {
                     InterpreterError::Misc {
                         func_name: self.stack_frame.func_name.clone(),
@@ -1418,9 +1058,22 @@ impl<'i> State<'i> {
                 })?
             }
             Instruction::Store { ptr, value, .. } => {
+                // A wider value would overwrite the bytes following the pointee (see `verify`).
+                if let Some(pointee) = mismatched_store(ptr, value) {
+                    return Err(InterpreterError::Misc {
+                        func_name: self.stack_frame.func_name.clone(),
+                        pc: self.stack_frame.pc,
+                        msg: format!(
+                            "store of `{}` into `{pointee}` needs an explicit `typecast`",
+                            value.dtype()
+                        ),
+                    });
+                }
                 let ptr = self.interp_operand(ptr)?;
                 let value = self.interp_operand(value)?;
                 let (bid, offset, _) = self.interp_ptr(&ptr)?;
+                self.check_frame_bounds(bid, offset, &value.dtype())?;
+                self.check_alignment(offset, &value.dtype())?;
                 self.memory
                     .store(bid, offset, &value, &self.ir.structs)
                     .map_err(|_| InterpreterError::Misc {
@@ -1435,58 +1088,110 @@ impl<'i> State<'i> {
             Instruction::Load { ptr, .. } => {
                 let ptr = self.interp_operand(ptr)?;
                 let (bid, offset, dtype) = self.interp_ptr(&ptr)?;
+
+                if self.constant_expression {
+                    let is_constant = self
+                        .global_map
+                        .get_var(bid)
+                        .and_then(|var| self.ir.decls.get(&var))
+                        .is_some_and(Declaration::is_constant);
+                    if !is_constant {
+                        return Err(InterpreterError::Misc {
+                            func_name: self.stack_frame.func_name.clone(),
+                            pc: self.stack_frame.pc,
+                            msg: "load from non-constant memory in a constant expression".into(),
+                        });
+                    }
+                }
+
+                self.check_frame_bounds(bid, offset, &dtype)?;
+                self.check_alignment(offset, &dtype)?;
                 self.memory.load(bid, offset, &dtype, &self.ir.structs)?
             }
             Instruction::Call { callee, args, .. } => {
+                if self.constant_expression {
+                    return Err(InterpreterError::Misc {
+                        func_name: self.stack_frame.func_name.clone(),
+                        pc: self.stack_frame.pc,
+                        msg: "function call in a constant expression".into(),
+                    });
+                }
+
                 let ptr = self.interp_operand(callee)?;
+                let callee_name = self.pointed_function(&ptr)?;
+                let (func_signature, func_def) = self.ir.decls[&callee_name]
+                    .get_function()
+                    .expect("`callee_name` must be a function");
 
-                // Get function name from pointer
-                let (bid, _, _) = ptr.get_pointer().expect("`ptr` must be `Value::Pointer`");
-                let bid = bid.expect("pointer for global variable must have bid value");
-                let callee_name = self
-                    .global_map
-                    .get_var(bid)
-                    .expect("bid must have relation with global variable");
+                // Calling a function through a pointer to an incompatible function type is
+                // undefined behavior (see C17 6.5.2.2p9).
+                let pointee = callee
+                    .dtype()
+                    .get_pointer_inner()
+                    .expect("`callee` must be a pointer")
+                    .clone();
+                if self.options.overflow_policy != OverflowPolicy::Wrap
+                    && !pointee.is_compatible(&func_signature.dtype())
+                {
+                    return Err(InterpreterError::InvalidCall {
+                        func_name: callee_name,
+                        reason: format!(
+                            "it is `{}`, but is called through `{}`",
+                            func_signature.dtype(),
+                            callee.dtype()
+                        ),
+                    });
+                }
 
-                let func = self
-                    .ir
-                    .decls
-                    .get(&callee_name)
-                    .expect("function must be declared before being called");
-                let (func_signature, func_def) = func
-                    .get_function()
-                    .expect("`func` must be function declaration");
-                let func_def =
-                    func_def
-                        .as_ref()
-                        .ok_or_else(|| InterpreterError::NoFunctionDefinition {
+                *self.calls.entry(callee_name.clone()).or_insert(0) += 1;
+
+                // Builtins are evaluated by the interpreter.
+                if let (None, Some(builtin)) = (func_def, Builtin::from_name(&callee_name)) {
+                    let args = self.interp_args(&callee_name, func_signature, args)?;
+                    self.interp_builtin(builtin, args)?
+                } else if func_def.is_none() && self.bindings.functions.contains_key(&callee_name) {
+                    let args = self.interp_args(&callee_name, func_signature, args)?;
+                    self.interp_bound_function(&callee_name, func_signature, &args)?
+                } else if let (None, Some(function)) =
+                    (func_def, StdinFunction::from_name(&callee_name))
+                {
+                    let args = self.interp_args(&callee_name, func_signature, args)?;
+                    self.interp_stdin_function(function, args)?
+                } else {
+                    let func_def = func_def.as_ref().ok_or_else(|| {
+                        InterpreterError::NoFunctionDefinition {
                             func_name: callee_name.clone(),
-                        })?;
+                        }
+                    })?;
 
-                let block_init = func_def
-                    .blocks
-                    .get(&func_def.bid_init)
-                    .expect("init block must exists");
+                    let block_init = func_def
+                        .blocks
+                        .get(&func_def.bid_init)
+                        .expect("init block must exists");
 
-                if !(args.len() == block_init.phinodes.len()
-                    && izip!(args, &block_init.phinodes).all(|(a, d)| {
-                        a.dtype().set_const(false) == d.deref().clone().set_const(false)
-                    }))
-                {
-                    panic!("dtype of args and phinodes of init block must be compatible");
-                }
+                    if !(args.len() == block_init.phinodes.len()
+                        && izip!(args, &block_init.phinodes).all(|(a, d)| {
+                            a.dtype().strip_outer_const() == d.deref().clone().strip_outer_const()
+                        }))
+                    {
+                        return Err(InterpreterError::InvalidCall {
+                            func_name: callee_name,
+                            reason: "the arguments do not match its parameters".to_string(),
+                        });
+                    }
 
-                let args = self.interp_args(func_signature, args)?;
+                    let args = self.interp_args(&callee_name, func_signature, args)?;
 
-                let stack_frame = StackFrame::new(func_def.bid_init, callee_name, func_def);
-                let prev_stack_frame = mem::replace(&mut self.stack_frame, stack_frame);
-                self.stack.push(prev_stack_frame);
+                    let stack_frame = StackFrame::new(func_def.bid_init, callee_name, func_def);
+                    let prev_stack_frame = mem::replace(&mut self.stack_frame, stack_frame);
+                    self.stack.push(prev_stack_frame);
 
-                // Initialize state with function obtained by callee and args
-                self.write_args(func_def.bid_init, args)?;
-                self.alloc_local_variables()?;
+                    // Initialize state with function obtained by callee and args
+                    self.write_args(func_def.bid_init, args)?;
+                    self.alloc_local_variables()?;
 
-                return Ok(());
+                    return Ok(());
+                }
             }
             Instruction::TypeCast {
                 value,
@@ -1522,6 +1227,34 @@ impl<'i> State<'i> {
 
                 Value::pointer(*bid, offset, inner_dtype.clone())
             }
+            Instruction::Min { lhs, rhs, .. } | Instruction::Max { lhs, rhs, .. } => {
+                let is_max = matches!(instruction, Instruction::Max { .. });
+                let lhs = self.interp_operand(lhs)?;
+                let rhs = self.interp_operand(rhs)?;
+
+                calculator::calculate_min_max(is_max, lhs, rhs).map_err(|_| {
+                    InterpreterError::Misc {
+                        func_name: self.stack_frame.func_name.clone(),
+                        pc: self.stack_frame.pc,
+                        msg: "calculate_min_max".into(),
+                    }
+                })?
+            }
+            Instruction::Abs { operand, .. } => {
+                let operand = self.interp_operand(operand)?;
+
+                if self.options.overflow_policy != OverflowPolicy::Wrap
+                    && calculator::is_signed_min(&operand)
+                {
+                    return Err(self.signed_overflow());
+                }
+
+                calculator::calculate_abs(operand).map_err(|_| InterpreterError::Misc {
+                    func_name: self.stack_frame.func_name.clone(),
+                    pc: self.stack_frame.pc,
+                    msg: "calculate_abs".into(),
+                })?
+            }
         };
 
         let register = RegisterId::temp(self.stack_frame.pc.bid, self.stack_frame.pc.iid);
@@ -1531,6 +1264,266 @@ impl<'i> State<'i> {
         Ok(())
     }
 
+    /// Returns the name of the function `ptr` points to, i.e., whose memory block it points to at
+    /// offset 0 (see [`Value::Pointer`]).
+    fn pointed_function(&self, ptr: &Value) -> Result<String, InterpreterError> {
+        let name = if let Value::Pointer {
+            bid: Some(bid),
+            offset: 0,
+            ..
+        } = ptr
+        {
+            self.global_map.get_var(*bid).filter(|name| {
+                self.ir
+                    .decls
+                    .get(name)
+                    .map_or(false, |decl| decl.get_function().is_some())
+            })
+        } else {
+            None
+        };
+
+        name.ok_or_else(|| InterpreterError::Misc {
+            func_name: self.stack_frame.func_name.clone(),
+            pc: self.stack_frame.pc,
+            msg: format!("call through {ptr:?}, which does not point to a function"),
+        })
+    }
+
+    fn interp_bound_function(
+        &mut self,
+        name: &str,
+        signature: &FunctionSignature,
+        args: &[Value],
+    ) -> Result<Value, InterpreterError> {
+        let function = self
+            .bindings
+            .functions
+            .get_mut(name)
+            .expect("`name` must be bound");
+        let result = function(args);
+        let msg = match result {
+            Ok(value)
+                if value.dtype().strip_outer_const()
+                    == signature.ret.clone().strip_outer_const() =>
+            {
+                return Ok(value)
+            }
+            Ok(value) => format!(
+                "the function bound to `{name}` returns {value:?} instead of a value of `{}`",
+                signature.ret
+            ),
+            Err(reason) => format!("the function bound to `{name}` fails: {reason}"),
+        };
+
+        Err(InterpreterError::Misc {
+            func_name: self.stack_frame.func_name.clone(),
+            pc: self.stack_frame.pc,
+            msg,
+        })
+    }
+
+    fn interp_builtin(
+        &mut self,
+        builtin: Builtin,
+        args: Vec<Value>,
+    ) -> Result<Value, InterpreterError> {
+        let misc = |state: &Self, msg: String| InterpreterError::Misc {
+            func_name: state.stack_frame.func_name.clone(),
+            pc: state.stack_frame.pc,
+            msg,
+        };
+
+        match builtin {
+            Builtin::Abs | Builtin::Labs => {
+                let (value, width, is_signed) =
+                    args[0].get_int().expect("`args[0]` must be `Value::Int`");
+                let is_negative = (value >> (width - 1)) & 1 == 1;
+                let value = if is_negative {
+                    value.wrapping_neg() & (u128::MAX >> (128 - width))
+                } else {
+                    value
+                };
+                Ok(Value::int(value, width, is_signed))
+            }
+            Builtin::Fabs => {
+                let (value, width) = if let Value::Float { value, width } = &args[0] {
+                    (value.into_inner(), *width)
+                } else {
+                    panic!("`args[0]` must be `Value::Float`")
+                };
+                Ok(Value::float(value.abs(), width))
+            }
+            Builtin::Memcpy | Builtin::Memset => {
+                let (dst_bid, dst_offset, _) = self.interp_ptr(&args[0])?;
+                let (size, _, _) = args[2].get_int().expect("`args[2]` must be `Value::Int`");
+                let size = size as usize;
+                self.check_frame_bounds(dst_bid, dst_offset, &Dtype::array(Dtype::CHAR, size))?;
+
+                let result = if builtin == Builtin::Memcpy {
+                    let (src_bid, src_offset, _) = self.interp_ptr(&args[1])?;
+                    self.memory
+                        .copy(dst_bid, dst_offset, src_bid, src_offset, size)
+                } else {
+                    let (byte, _, _) = args[1].get_int().expect("`args[1]` must be `Value::Int`");
+                    self.memory.fill(dst_bid, dst_offset, byte as u8, size)
+                };
+                result.map_err(|_| {
+                    misc(
+                        self,
+                        format!(
+                            "{builtin} out of bounds with bid: {dst_bid}, offset: {dst_offset}"
+                        ),
+                    )
+                })?;
+
+                Ok(args[0].clone())
+            }
+            Builtin::Strcmp => {
+                let lhs = self.load_string(&args[0])?;
+                let rhs = self.load_string(&args[1])?;
+                let (lhs, rhs) = lhs
+                    .zip(rhs)
+                    .ok_or_else(|| misc(self, format!("{builtin} of an uninitialized string")))?;
+                // The characters are compared as `unsigned char`, and a prefix is less than the
+                // string, as its null character is.
+                let result = lhs.cmp(&rhs) as i128;
+                Ok(Value::int(result as u128, 32, true))
+            }
+            Builtin::Unreachable => Err(InterpreterError::Unreachable),
+            Builtin::Trap => Err(misc(self, format!("{builtin} is called"))),
+        }
+    }
+
+    /// Returns the characters of the string `pointer` points to, without its null character, or
+    /// `None` if one of them is uninitialized.
+    fn load_string(&mut self, pointer: &Value) -> Result<Option<Vec<u8>>, InterpreterError> {
+        let (bid, offset, _) = self.interp_ptr(pointer)?;
+        let mut string = Vec::new();
+        loop {
+            let byte = self.memory.load(
+                bid,
+                offset + string.len() as isize,
+                &Dtype::CHAR,
+                &self.ir.structs,
+            )?;
+            let (byte, _, _) = some_or!(byte.get_int(), return Ok(None));
+            if byte == 0 {
+                return Ok(Some(string));
+            }
+            string.push(byte as u8);
+        }
+    }
+
+    fn interp_stdin_function(
+        &mut self,
+        function: StdinFunction,
+        args: Vec<Value>,
+    ) -> Result<Value, InterpreterError> {
+        let misc = |state: &Self, msg: String| InterpreterError::Misc {
+            func_name: state.stack_frame.func_name.clone(),
+            pc: state.stack_frame.pc,
+            msg,
+        };
+        // Signed integers are sign-extended.
+        let int = |value: i128| Value::int(value as u128, 32, true);
+
+        if function == StdinFunction::Getchar {
+            let byte = self.bindings.stdin.next_byte();
+            return Ok(int(byte.map_or(-1, i128::from)));
+        }
+
+        let format = self
+            .load_string(&args[0])?
+            .ok_or_else(|| misc(self, format!("{function} format is not a string")))?;
+        let directives =
+            parse_format(&format).map_err(|reason| misc(self, format!("{function} {reason}")))?;
+
+        // As in C, `EOF` is returned if the input ends before the first conversion.
+        let mut pointers = args[1..].iter();
+        let mut count = 0;
+        for directive in directives {
+            let (conversion, width) = match directive {
+                Directive::Whitespace => {
+                    self.bindings.stdin.skip_whitespace();
+                    continue;
+                }
+                Directive::Literal(byte) => match self.bindings.stdin.peek() {
+                    Some(next) if next == byte => {
+                        let _unused = self.bindings.stdin.next_byte();
+                        continue;
+                    }
+                    None if count == 0 => return Ok(int(-1)),
+                    _ => break,
+                },
+                Directive::Conversion { conversion, width } => (conversion, width),
+            };
+
+            let scanned = match self.bindings.stdin.scan(conversion, width) {
+                Ok(scanned) => scanned,
+                Err(Failure::Input) if count == 0 => return Ok(int(-1)),
+                Err(_) => break,
+            };
+            let pointer = pointers
+                .next()
+                .ok_or_else(|| misc(self, format!("{function} has too few arguments")))?;
+            let (bid, offset, dtype) = self.interp_ptr(pointer)?;
+
+            let stored = match (scanned, &dtype) {
+                (
+                    Scanned::Int(value),
+                    Dtype::Int {
+                        width, is_signed, ..
+                    },
+                ) => {
+                    let value = trim_unnecessary_bits(value as u128, *width as u128);
+                    let value = if *is_signed {
+                        sign_extension(value, *width as u128)
+                    } else {
+                        value
+                    };
+                    let value = Value::int(value, *width, *is_signed);
+                    self.check_frame_bounds(bid, offset, &dtype)?;
+                    self.memory.store(bid, offset, &value, &self.ir.structs)
+                }
+                (Scanned::Float(value), Dtype::Float { width, .. }) => {
+                    let value = if *width == Dtype::SIZE_OF_FLOAT * Dtype::BITS_OF_BYTE {
+                        f64::from(value as f32)
+                    } else {
+                        value
+                    };
+                    self.check_frame_bounds(bid, offset, &dtype)?;
+                    self.memory
+                        .store(bid, offset, &Value::float(value, *width), &self.ir.structs)
+                }
+                // `%s` and `%c` are checked against the bounds of the array they store into.
+                (Scanned::Bytes(mut bytes), Dtype::Int { width: 8, .. }) => {
+                    if conversion == Conversion::String {
+                        bytes.push(0);
+                    }
+                    self.check_frame_bounds(bid, offset, &Dtype::array(Dtype::CHAR, bytes.len()))?;
+                    self.memory
+                        .splice(bid, offset, bytes.into_iter().map(Byte::concrete).collect())
+                }
+                _ => {
+                    return Err(misc(
+                        self,
+                        format!("{function} cannot store a conversion into `{dtype}*`"),
+                    ))
+                }
+            };
+            stored.map_err(|_| {
+                misc(
+                    self,
+                    format!("{function} out of bounds with bid: {bid}, offset: {offset}"),
+                )
+            })?;
+            count += 1;
+        }
+
+        Ok(int(count))
+    }
+
     fn interp_operand(&self, operand: &Operand) -> Result<Value, InterpreterError> {
         match operand {
             Operand::Constant(value) => Ok(self.interp_constant(value.clone())),
@@ -1553,10 +1546,113 @@ impl<'i> State<'i> {
                     dtype,
                 }
             }
+            // `ZeroInit` reads as the default (i.e., zero) value of its dtype
+            Constant::ZeroInit { dtype } => Value::default_from_dtype(&dtype, &self.ir.structs)
+                .expect("default value must be derived from `dtype`"),
             constant => Value::try_from(constant).expect("constant must be transformed to value"),
         }
     }
 
+    /// Returns the error for a signed integer overflow, which is not allowed by the overflow
+    /// policy.
+    fn signed_overflow(&self) -> InterpreterError {
+        InterpreterError::Misc {
+            func_name: self.stack_frame.func_name.clone(),
+            pc: self.stack_frame.pc,
+            msg: format!(
+                "signed integer overflow (overflow policy: {})",
+                self.options.overflow_policy
+            ),
+        }
+    }
+
+    /// Returns the error for an integer division or remainder by zero, which is not allowed by
+    /// the overflow policy.
+    fn division_by_zero(&self) -> InterpreterError {
+        InterpreterError::Misc {
+            func_name: self.stack_frame.func_name.clone(),
+            pc: self.stack_frame.pc,
+            msg: format!(
+                "integer division by zero (overflow policy: {})",
+                self.options.overflow_policy
+            ),
+        }
+    }
+
+    fn shift_out_of_range(&self) -> InterpreterError {
+        InterpreterError::Misc {
+            func_name: self.stack_frame.func_name.clone(),
+            pc: self.stack_frame.pc,
+            msg: format!(
+                "shift amount out of range (overflow policy: {})",
+                self.options.overflow_policy
+            ),
+        }
+    }
+
+    /// Fails if accessing a value of `dtype` at `(bid, offset)` is out of the bounds of a local
+    /// allocation under `Instrumentation::StackProtect`, which would corrupt the canary of the
+    /// frame in the generated code.
+    fn check_frame_bounds(
+        &self,
+        bid: usize,
+        offset: isize,
+        dtype: &Dtype,
+    ) -> Result<(), InterpreterError> {
+        if self.options.instrumentation != Instrumentation::StackProtect {
+            return Ok(());
+        }
+
+        let is_local = iter::once(&self.stack_frame)
+            .chain(&self.stack)
+            .any(|frame| {
+                (0..frame.func_def.allocations.len()).any(|aid| {
+                    let pointer = frame.registers.read(RegisterId::local(aid)).get_pointer();
+                    pointer.and_then(|(local_bid, _, _)| *local_bid) == Some(bid)
+                })
+            });
+        let len = self.memory.inner[bid].as_ref().map_or(0, Vec::len);
+        let (size, _) = dtype
+            .size_align_of(&self.ir.structs)
+            .expect("`dtype` must have its size");
+
+        if is_local && (offset < 0 || offset as usize + size > len) {
+            return Err(InterpreterError::Misc {
+                func_name: self.stack_frame.func_name.clone(),
+                pc: self.stack_frame.pc,
+                msg: Instrumentation::STACK_FAIL_MESSAGE.to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Fails if accessing a scalar of `dtype` at `offset` is misaligned without
+    /// `CompileOptions::strict_align`, on which the target would trap. The memory blocks are
+    /// assumed to be aligned for any type.
+    fn check_alignment(&self, offset: isize, dtype: &Dtype) -> Result<(), InterpreterError> {
+        let is_scalar = matches!(
+            dtype,
+            Dtype::Int { .. } | Dtype::Float { .. } | Dtype::Pointer { .. }
+        );
+        if self.options.strict_align || !is_scalar {
+            return Ok(());
+        }
+
+        let (_, align) = dtype
+            .size_align_of(&self.ir.structs)
+            .expect("`dtype` must have its size");
+        if align > 1 && offset.rem_euclid(align as isize) != 0 {
+            return Err(InterpreterError::Misc {
+                func_name: self.stack_frame.func_name.clone(),
+                pc: self.stack_frame.pc,
+                msg: format!("misaligned access of {dtype} at offset {offset}"),
+            });
+        }
+
+        Ok(())
+    }
+
     fn interp_ptr(&mut self, pointer: &Value) -> Result<(usize, isize, Dtype), InterpreterError> {
         let (bid, offset, dtype) = pointer
             .get_pointer()
@@ -1578,6 +1674,148 @@ impl<'i> State<'i> {
 
 #[inline]
 pub fn interp(ir: &TranslationUnit, args: Vec<Value>) -> Result<Value, InterpreterError> {
-    let mut init_state = State::new(ir, args)?;
+    interp_with_options(ir, args, CompileOptions::default())
+}
+
+/// Interprets `ir` under the semantics given by `options`.
+pub fn interp_with_options(
+    ir: &TranslationUnit,
+    args: Vec<Value>,
+    options: CompileOptions,
+) -> Result<Value, InterpreterError> {
+    let mut init_state = State::new(ir, args, options, Bindings::default())?;
+    init_state.run()
+}
+
+/// Interprets `ir` like [`interp_with_options`], calling the functions and reading the `extern`
+/// variables it declares but does not define from `bindings`.
+pub fn interp_with_bindings(
+    ir: &TranslationUnit,
+    args: Vec<Value>,
+    options: CompileOptions,
+    bindings: Bindings,
+) -> Result<Value, InterpreterError> {
+    let mut init_state = State::new(ir, args, options, bindings)?;
     init_state.run()
 }
+
+/// Calls the function `name` of `ir` with `args`, e.g., `ir::call(&ir, "fib", vec![10.into()])`,
+/// as a harness calls the functions of a unit compiled with `--lib`, which needs no `main`.
+///
+/// The arguments are marshalled from Rust values by the `From` implementations of [`Value`], and
+/// must have the types of the parameters of the function. The global variables are initialized as
+/// for `main`.
+pub fn call(ir: &TranslationUnit, name: &str, args: Vec<Value>) -> Result<Value, InterpreterError> {
+    let invalid_call = |reason: String| InterpreterError::InvalidCall {
+        func_name: name.to_string(),
+        reason,
+    };
+    let (signature, func_def) = ir
+        .decls
+        .get(name)
+        .and_then(Declaration::get_function)
+        .ok_or_else(|| invalid_call("no such function".to_string()))?;
+    let func_def = func_def
+        .as_ref()
+        .ok_or_else(|| InterpreterError::NoFunctionDefinition {
+            func_name: name.to_string(),
+        })?;
+
+    if args.len() != signature.params.len() {
+        return Err(invalid_call(format!(
+            "expected {} arguments, but {} are given",
+            signature.params.len(),
+            args.len()
+        )));
+    }
+    for (i, (arg, param)) in izip!(&args, &signature.params).enumerate() {
+        if arg.dtype().strip_outer_const() != param.clone().strip_outer_const() {
+            return Err(invalid_call(format!(
+                "argument {i} of type `{}` does not match the parameter of type `{param}`",
+                arg.dtype()
+            )));
+        }
+    }
+
+    let mut state = State::start(
+        ir,
+        name.to_string(),
+        func_def,
+        args,
+        CompileOptions::default(),
+        Bindings::default(),
+    )?;
+    state.run()
+}
+
+/// The result of running `main` with [`interp_bounded`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Execution {
+    /// The value `main` returns.
+    pub value: Value,
+    /// The values of the global variables once `main` returns.
+    ///
+    /// Pointers are compared by the memory blocks they point into, which are the same for the
+    /// same global variable in two modules declaring the same global variables.
+    pub globals: BTreeMap<String, Value>,
+    /// The number of jumps taken, including those of conditional branches and switches, as a
+    /// measure of the cost of the control flow.
+    pub branches: usize,
+    /// The number of calls to each function, by its name, including the builtins and the
+    /// functions defined in another unit.
+    pub calls: BTreeMap<String, usize>,
+}
+
+/// Interprets `ir` like [`interp_with_options`], also returning the values of the global
+/// variables, or `None` if `main` does not return within `max_steps` steps.
+pub fn interp_bounded(
+    ir: &TranslationUnit,
+    args: Vec<Value>,
+    options: CompileOptions,
+    max_steps: usize,
+) -> Result<Option<Execution>, InterpreterError> {
+    let mut state = State::new(ir, args, options, Bindings::default())?;
+
+    for _ in 0..max_steps {
+        if let Some(value) = state.step()? {
+            let globals = state.global_values()?;
+            return Ok(Some(Execution {
+                value,
+                globals,
+                branches: state.branches,
+                calls: state.calls,
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Evaluates `main` of `ir`, which takes no arguments, as a constant expression.
+///
+/// The evaluation fails if `main` calls a function, loads from memory other than constant global
+/// variables, overflows a signed integer, divides by zero, shifts out of range, or does not
+/// return within `max_steps` steps.
+pub fn interp_constant_expression(
+    ir: &TranslationUnit,
+    max_steps: usize,
+) -> Result<Value, InterpreterError> {
+    let options = CompileOptions {
+        overflow_policy: OverflowPolicy::Trap,
+        ..Default::default()
+    };
+    let mut state = State::new(ir, Vec::new(), options, Bindings::default())?;
+    state.constant_expression = true;
+
+    for _ in 0..max_steps {
+        if let Some(value) = state.step()? {
+            return Ok(value);
+        }
+    }
+
+    Err(InterpreterError::Misc {
+        func_name: state.stack_frame.func_name.clone(),
+        pc: state.stack_frame.pc,
+        msg: format!("constant expression does not terminate within {max_steps} steps"),
+    })
+}
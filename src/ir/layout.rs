@@ -0,0 +1,764 @@
+//! Dump the memory layout of structs for ABI debugging.
+
+use core::fmt;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{Result, Write};
+
+use itertools::{izip, Itertools};
+
+use crate::ir::*;
+use crate::write_base::*;
+#[cfg(feature = "interp")]
+use crate::CompileOptions;
+use crate::{some_or, target_data};
+
+/// Memory layout of the structs in a translation unit.
+///
+/// Structs are printed in the order of their names, with the size and alignment of the struct,
+/// the offset and size of each field, and the padding between fields and at the end of the
+/// struct. E.g.,
+///
+/// ```text
+/// struct foo { // size: 16, align: 8
+///   a: i8 // offset: 0, size: 1
+///   // padding: 7 bytes
+///   b: i64 // offset: 8, size: 8
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Layout<'a> {
+    decls: &'a BTreeMap<String, Declaration>,
+    structs: &'a HashMap<String, Option<Dtype>>,
+}
+
+/// Size and alignment of a struct.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StructLayout {
+    pub name: String,
+    /// The tag of the struct in C, which is made up for anonymous structs, e.g., `__kecc_t0` for
+    /// `%t0`
+    pub tag: String,
+    /// The keyword of the struct in C, i.e., `struct` or `union`
+    pub keyword: &'static str,
+    pub size_of: usize,
+    pub align_of: usize,
+}
+
+/// Offset of a member of a struct.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MemberOffset {
+    /// The name of the struct in the IR
+    pub name: String,
+    /// The index of the member among the fields of the struct
+    pub index: usize,
+    /// The name of the member, which is made up for anonymous members, e.g., `__kecc_anon1`
+    pub member: String,
+    pub offset: usize,
+}
+
+impl MemberOffset {
+    /// Parses the output of the program of [`Layout::offset_probe`], one member per line.
+    pub fn parse_probe_output(output: &str) -> Option<Vec<Self>> {
+        output
+            .lines()
+            .map(|line| {
+                let (name, index, member, offset) = line.split_whitespace().collect_tuple()?;
+                Some(Self {
+                    name: name.to_string(),
+                    index: index.parse().ok()?,
+                    member: member.to_string(),
+                    offset: offset.parse().ok()?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A member of a struct whose offset differs between the layout computed by kecc and another
+/// source, e.g., the interpreter or a C compiler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutMismatch {
+    pub name: String,
+    pub member: String,
+    /// The offset computed by [`Dtype::size_align_of`]
+    pub recorded: usize,
+    /// The offsets found by each source, `None` if the source could not tell
+    pub found: Vec<(String, Option<usize>)>,
+}
+
+impl LayoutMismatch {
+    /// Returns the members whose offsets found by one of `sources`, given by their names, differ
+    /// from the `recorded` ones. A member missing from a source is not compared with it.
+    pub fn between(recorded: &[MemberOffset], sources: &[(&str, &[MemberOffset])]) -> Vec<Self> {
+        let sources = sources
+            .iter()
+            .map(|(source, offsets)| {
+                let offsets = offsets
+                    .iter()
+                    .map(|offset| ((offset.name.as_str(), offset.index), offset.offset))
+                    .collect::<HashMap<_, _>>();
+                (*source, offsets)
+            })
+            .collect::<Vec<_>>();
+
+        recorded
+            .iter()
+            .filter_map(|member| {
+                let key = (member.name.as_str(), member.index);
+                let found = sources
+                    .iter()
+                    .map(|(source, offsets)| (source.to_string(), offsets.get(&key).copied()))
+                    .collect::<Vec<_>>();
+                found
+                    .iter()
+                    .any(|(_, offset)| offset.is_some_and(|offset| offset != member.offset))
+                    .then(|| Self {
+                        name: member.name.clone(),
+                        member: member.member.clone(),
+                        recorded: member.offset,
+                        found,
+                    })
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for LayoutMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "error: the offset of `{}` in `{}` is {} by kecc",
+            self.member, self.name, self.recorded
+        )?;
+        for (source, offset) in &self.found {
+            match offset {
+                Some(offset) => write!(f, ", {offset} by {source}")?,
+                None => write!(f, ", unknown by {source}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Layout<'a> {
+    pub fn new(unit: &'a TranslationUnit) -> Self {
+        Self {
+            decls: &unit.decls,
+            structs: &unit.structs,
+        }
+    }
+
+    /// Returns the layouts of the structs in the interface of the translation unit, i.e., those
+    /// reachable from the types of its global variables and functions, in the order of their
+    /// names.
+    pub fn interface_structs(&self) -> Vec<StructLayout> {
+        let mut names = BTreeSet::new();
+        for decl in self.decls.values() {
+            self.collect_structs(&decl.dtype(), &mut names);
+        }
+
+        names
+            .into_iter()
+            .map(|name| {
+                let (size_of, align_of, _) = self.structs[name]
+                    .as_ref()
+                    .and_then(Dtype::get_struct_size_align_offsets)
+                    .and_then(Option::as_ref)
+                    .expect("`name` must be a complete struct");
+
+                StructLayout {
+                    name: name.to_string(),
+                    tag: c_tag(name),
+                    keyword: c_keyword(self.structs, name),
+                    size_of: *size_of,
+                    align_of: *align_of,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns a C file asserting the layout of the structs in the interface of the translation
+    /// unit with `_Static_assert`s, so that other compilers can check if they agree with it.
+    pub fn static_asserts(&self) -> String {
+        let layouts = self.interface_structs();
+        let mut source = format!(
+            "/* Layout of the interface structs computed by kecc for {} */\n",
+            target_data().triple()
+        );
+
+        // Structs are declared first, since fields may point to structs defined later.
+        for layout in &layouts {
+            source.push_str(&format!("{} {};\n", layout.keyword, layout.tag));
+        }
+
+        let mut defined = HashSet::new();
+        for layout in &layouts {
+            let (name, _) = self
+                .structs
+                .get_key_value(&layout.name)
+                .expect("`layout` must be of a struct");
+            self.define_struct(name, &mut defined, &mut source);
+        }
+
+        for layout in &layouts {
+            let StructLayout {
+                name,
+                tag,
+                keyword,
+                size_of,
+                align_of,
+            } = layout;
+            source.push_str(&format!(
+                "_Static_assert(sizeof({keyword} {tag}) == {size_of}, \"size of `{name}`\");\n\
+                 _Static_assert(_Alignof({keyword} {tag}) == {align_of}, \"alignment of `{name}`\");\n"
+            ));
+        }
+
+        source
+    }
+
+    /// Returns the offsets of the members of the complete structs, but the bit-fields, whose
+    /// addresses cannot be taken, in the order of the names of the structs and of the members.
+    pub fn member_offsets(&self) -> Vec<MemberOffset> {
+        let mut names = self
+            .structs
+            .iter()
+            .filter_map(|(name, struct_type)| Some((name, struct_type.as_ref()?)))
+            .collect::<Vec<_>>();
+        names.sort_by_key(|(name, _)| *name);
+
+        let mut members = Vec::new();
+        for (name, struct_type) in names {
+            let fields = struct_type
+                .get_struct_fields()
+                .and_then(Option::as_ref)
+                .expect("`struct_type` must be a complete struct");
+            let (_, _, offsets) = struct_type
+                .get_struct_size_align_offsets()
+                .and_then(Option::as_ref)
+                .expect("`struct_type` must be a complete struct");
+            for (index, (field, offset)) in izip!(fields, offsets).enumerate() {
+                if struct_type.get_struct_bit_field(index).is_some() {
+                    continue;
+                }
+                members.push(MemberOffset {
+                    name: name.clone(),
+                    index,
+                    member: member_name(field, index),
+                    offset: *offset,
+                });
+            }
+        }
+        members
+    }
+
+    /// Returns the offsets of the members of [`Layout::member_offsets`] where the interpreter
+    /// places them in memory, but those it cannot tell, e.g., pointers, whose bytes are not
+    /// readable as integers.
+    ///
+    /// A member is found by the first byte of the struct that is defined only if the member is,
+    /// the other members being undefined.
+    #[cfg(feature = "interp")]
+    pub fn interpreted_offsets(&self) -> std::result::Result<Vec<MemberOffset>, InterpreterError> {
+        let mut found = Vec::new();
+        let mut undefined = HashMap::new();
+        for member in self.member_offsets() {
+            let struct_type = self.structs[&member.name]
+                .as_ref()
+                .expect("`member` must be of a complete struct");
+            let fields = struct_type
+                .get_struct_fields()
+                .and_then(Option::as_ref)
+                .expect("`struct_type` must be a complete struct");
+            if !self.is_probed(fields[member.index].deref()) {
+                continue;
+            }
+
+            let bytes = self.interpreted_bytes(&member.name, Some(member.index))?;
+            if !undefined.contains_key(&member.name) {
+                let bytes = self.interpreted_bytes(&member.name, None)?;
+                let _unused = undefined.insert(member.name.clone(), bytes);
+            }
+            let offset = izip!(&bytes, &undefined[&member.name]).position(|(lhs, rhs)| lhs != rhs);
+            if let Some(offset) = offset {
+                found.push(MemberOffset { offset, ..member });
+            }
+        }
+        Ok(found)
+    }
+
+    /// Returns a C program printing the offsets of the members of [`Layout::member_offsets`],
+    /// computed by `(char *)&s.f - (char *)&s`, so that C compilers can be checked against them.
+    ///
+    /// The program prints the name of the struct, the index and the name of the member and its
+    /// offset in a line for each member (see [`MemberOffset::parse_probe_output`]).
+    pub fn offset_probe(&self) -> String {
+        let members = self.member_offsets();
+        let mut source = format!(
+            "/* Offsets of the struct members to check the layout computed by kecc for {} */\n\
+             #include <stdio.h>\n\n",
+            target_data().triple()
+        );
+
+        let names = members
+            .iter()
+            .map(|member| member.name.as_str())
+            .dedup()
+            .collect::<Vec<_>>();
+        for name in &names {
+            source.push_str(&format!(
+                "{} {};\n",
+                c_keyword(self.structs, name),
+                c_tag(name)
+            ));
+        }
+        let mut defined = HashSet::new();
+        for name in &names {
+            let (name, _) = self
+                .structs
+                .get_key_value(*name)
+                .expect("`name` must be a struct");
+            self.define_struct(name, &mut defined, &mut source);
+        }
+
+        source.push_str("\nint main(void) {\n");
+        for (i, member) in members.iter().enumerate() {
+            let MemberOffset {
+                name,
+                index,
+                member,
+                ..
+            } = member;
+            let offset = format!("(long)((char *)&s{i}.{member} - (char *)&s{i})");
+            source.push_str(&format!(
+                "  static {} {} s{i};\n",
+                c_keyword(self.structs, name),
+                c_tag(name),
+            ));
+            source.push_str(&format!(
+                "  printf(\"{name} {index} {member} %ld\\n\", {offset});\n"
+            ));
+        }
+        source.push_str("  return 0;\n}\n");
+        source
+    }
+
+    /// Returns `true` if the interpreter can tell where a member of `dtype` is placed, i.e., if its
+    /// first byte is an integer or a floating point number, which is defined by a zero value.
+    #[cfg(feature = "interp")]
+    fn is_probed(&self, dtype: &Dtype) -> bool {
+        match dtype {
+            Dtype::Int { .. } | Dtype::Float { .. } => true,
+            Dtype::Array { inner, size, .. } => {
+                size.is_some_and(|size| size > 0) && self.is_probed(inner)
+            }
+            Dtype::Struct {
+                name: Some(name), ..
+            } => {
+                let struct_type = some_or!(self.structs.get(name).and_then(Option::as_ref), {
+                    return false;
+                });
+                let fields = some_or!(
+                    struct_type.get_struct_fields().and_then(Option::as_ref),
+                    return false
+                );
+                // The storage units of bit-fields are defined even if the fields are not.
+                struct_type.get_struct_bit_field(0).is_none()
+                    && fields
+                        .first()
+                        .is_some_and(|field| self.is_probed(field.deref()))
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the bytes of the struct `name` in the memory of the interpreter, where the member
+    /// `defined` is zero if any, and the others are undefined. An undefined byte or a byte of a
+    /// pointer is `None`.
+    #[cfg(feature = "interp")]
+    fn interpreted_bytes(
+        &self,
+        name: &str,
+        defined: Option<usize>,
+    ) -> std::result::Result<Vec<Option<u128>>, InterpreterError> {
+        const PROBED: &str = "__kecc_probed";
+
+        let struct_type = self.structs[name]
+            .as_ref()
+            .expect("`name` must be a complete struct");
+        let fields = struct_type
+            .get_struct_fields()
+            .and_then(Option::as_ref)
+            .expect("`struct_type` must be a complete struct");
+        let (size_of, _, _) = struct_type
+            .get_struct_size_align_offsets()
+            .and_then(Option::as_ref)
+            .expect("`struct_type` must be a complete struct");
+
+        let fields = fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let value = if Some(i) == defined {
+                    Value::default_from_dtype(field.deref(), self.structs)
+                        .expect("`field` must have a default value")
+                } else {
+                    Value::Undef {
+                        dtype: field.deref().clone(),
+                    }
+                };
+                Named::new(field.name().cloned(), value)
+            })
+            .collect();
+        let value = Value::Struct {
+            name: name.to_string(),
+            fields,
+        };
+
+        // `main` loads the bytes of the struct bound to the `extern` variable `PROBED`.
+        let dtype = Dtype::structure(Some(name.to_string()), None);
+        let bytes = Dtype::array(Dtype::CHAR, *size_of);
+        let bid = BlockId(0);
+        let block = Block {
+            phinodes: Vec::new(),
+            instructions: vec![
+                Named::new(
+                    None,
+                    Instruction::GetElementPtr {
+                        ptr: Operand::constant(Constant::global_variable(
+                            PROBED.to_string(),
+                            dtype.clone(),
+                        )),
+                        offset: Operand::constant(Constant::int(0, Dtype::LONG)),
+                        dtype: Dtype::pointer(bytes.clone()),
+                    },
+                ),
+                Named::new(
+                    None,
+                    Instruction::Load {
+                        ptr: Operand::register(
+                            RegisterId::temp(bid, 0),
+                            Dtype::pointer(bytes.clone()),
+                        ),
+                    },
+                ),
+            ],
+            exit: BlockExit::Return {
+                value: Operand::register(RegisterId::temp(bid, 1), bytes.clone()),
+            },
+        };
+        let main = Declaration::Function {
+            signature: FunctionSignature::new(Dtype::function(bytes, Vec::new())),
+            definition: Some(FunctionDefinition {
+                allocations: Vec::new(),
+                blocks: [(bid, block)].into_iter().collect(),
+                bid_init: bid,
+            }),
+            is_static: false,
+        };
+        let probed = Declaration::Variable {
+            dtype,
+            initializer: None,
+            is_extern: true,
+            is_static: false,
+        };
+        let unit = TranslationUnit {
+            decls: [("main".to_string(), main), (PROBED.to_string(), probed)]
+                .into_iter()
+                .collect(),
+            structs: self.structs.clone(),
+        };
+
+        let bindings = Bindings::default().variable(PROBED, value);
+        let bytes = interp_with_bindings(&unit, Vec::new(), CompileOptions::default(), bindings)?;
+        let bytes = match bytes {
+            Value::Array { values, .. } => values,
+            value => panic!("`{value:?}` must be the bytes of `{name}`"),
+        };
+        Ok(bytes
+            .iter()
+            .map(|byte| byte.get_int().map(|(value, _, _)| value))
+            .collect())
+    }
+
+    /// Adds the complete structs reachable from `dtype` to `names`.
+    fn collect_structs(&self, dtype: &Dtype, names: &mut BTreeSet<&'a str>) {
+        match dtype {
+            Dtype::Pointer { inner, .. } | Dtype::Array { inner, .. } => {
+                self.collect_structs(inner, names)
+            }
+            Dtype::Function { ret, params } => {
+                self.collect_structs(ret, names);
+                for param in params {
+                    self.collect_structs(param, names);
+                }
+            }
+            Dtype::Struct {
+                name: Some(name), ..
+            } => {
+                let (name, struct_type) = some_or!(self.structs.get_key_value(name), return);
+                let struct_type = some_or!(struct_type.as_ref(), return);
+                if !names.insert(name.as_str()) {
+                    return;
+                }
+
+                let fields = struct_type
+                    .get_struct_fields()
+                    .expect("`struct_type` must be struct type")
+                    .as_ref()
+                    .expect("`fields` must be `Some`");
+                for field in fields {
+                    self.collect_structs(field.deref(), names);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Appends the C definition of the struct `name` to `source`, after those of the structs it
+    /// contains.
+    fn define_struct(&self, name: &'a str, defined: &mut HashSet<&'a str>, source: &mut String) {
+        if !defined.insert(name) {
+            return;
+        }
+
+        let struct_type = self.structs[name]
+            .as_ref()
+            .expect("`name` must be a complete struct");
+        let fields = struct_type
+            .get_struct_fields()
+            .and_then(Option::as_ref)
+            .expect("`name` must be a complete struct");
+
+        // Contained structs must be complete, unlike pointed ones.
+        for field in fields {
+            let mut dtype = field.deref();
+            while let Dtype::Array { inner, .. } = dtype {
+                dtype = inner;
+            }
+            if let Dtype::Struct {
+                name: Some(inner), ..
+            } = dtype
+            {
+                let (inner, _) = self
+                    .structs
+                    .get_key_value(inner)
+                    .expect("`inner` must be a struct");
+                self.define_struct(inner, defined, source);
+            }
+        }
+
+        source.push_str(&format!(
+            "{} {} {{\n",
+            c_keyword(self.structs, name),
+            c_tag(name)
+        ));
+        for (i, field) in fields.iter().enumerate() {
+            // Unnamed bit-fields do not align the struct, unlike named ones.
+            if let Some(bit_field) = struct_type.get_struct_bit_field(i) {
+                let field_name = field.name().cloned().unwrap_or_default();
+                source.push_str(&format!(
+                    "  {} : {};\n",
+                    c_declaration(field.deref(), field_name),
+                    bit_field.width
+                ));
+                continue;
+            }
+
+            // Anonymous members are named, which does not change the layout.
+            let declaration = c_declaration(field.deref(), member_name(field, i));
+            if let Some(align) = field.get_array_align() {
+                source.push_str(&format!("  _Alignas({align}) {declaration};\n"));
+            } else {
+                source.push_str(&format!("  {declaration};\n"));
+            }
+        }
+        source.push_str("};\n");
+    }
+}
+
+impl WriteLine for Layout<'_> {
+    fn write_line(&self, indent: usize, write: &mut dyn Write) -> Result<()> {
+        write_indent(indent, write)?;
+        writeln!(write, "// target: {}", target_data().triple())?;
+
+        let mut names = self.structs.keys().collect::<Vec<_>>();
+        names.sort();
+
+        for name in names {
+            writeln!(write)?;
+            write_indent(indent, write)?;
+
+            let struct_type = if let Some(struct_type) = &self.structs[name] {
+                struct_type
+            } else {
+                writeln!(write, "struct {name}; // incomplete")?;
+                continue;
+            };
+
+            let fields = struct_type
+                .get_struct_fields()
+                .expect("`struct_type` must be struct type")
+                .as_ref()
+                .expect("`fields` must be `Some`");
+            let (size_of, align_of, offsets) = struct_type
+                .get_struct_size_align_offsets()
+                .expect("`struct_type` must be struct type")
+                .as_ref()
+                .expect("`offsets` must be `Some`");
+
+            writeln!(
+                write,
+                "{} {name} {{ // size: {size_of}, align: {align_of}",
+                c_keyword(self.structs, name)
+            )?;
+
+            // The members of a union overlap, so that there is no padding between them.
+            let mut end = 0;
+            for (i, (field, offset)) in izip!(fields, offsets).enumerate() {
+                write_padding(offset.saturating_sub(end), "padding", indent + 1, write)?;
+
+                let (field_size_of, _) = field
+                    .size_align_of(self.structs)
+                    .expect("`field` must have its size");
+                let field_name = field.name().map(String::as_str).unwrap_or("%anon");
+
+                write_indent(indent + 1, write)?;
+                if let Some(bit_field) = struct_type.get_struct_bit_field(i) {
+                    let BitField { width, bit_offset } = bit_field;
+                    writeln!(
+                        write,
+                        "{field_name}: {} : {width} // offset: {offset}, bit offset: {bit_offset}",
+                        field.deref()
+                    )?;
+                    end = end.max(offset + (bit_offset + width).div_ceil(Dtype::BITS_OF_BYTE));
+                    continue;
+                }
+                writeln!(
+                    write,
+                    "{field_name}: {} // offset: {offset}, size: {field_size_of}",
+                    field.deref()
+                )?;
+                end = end.max(offset + field_size_of);
+            }
+            write_padding(size_of - end, "tail padding", indent + 1, write)?;
+
+            write_indent(indent, write)?;
+            writeln!(write, "}}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the keyword of the struct `name` in C, i.e., `union` for a union and `struct` for the
+/// others, including the incomplete ones.
+pub(crate) fn c_keyword(structs: &HashMap<String, Option<Dtype>>, name: &str) -> &'static str {
+    structs
+        .get(name)
+        .and_then(Option::as_ref)
+        .and_then(Dtype::get_struct_keyword)
+        .unwrap_or("struct")
+}
+
+/// Returns the tag of the struct `name` in C.
+pub(crate) fn c_tag(name: &str) -> String {
+    let is_identifier = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_identifier {
+        return name.to_string();
+    }
+
+    let name = name
+        .trim_start_matches('%')
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    format!("__kecc_{name}")
+}
+
+/// Returns the C declaration of `declarator` of `dtype` for the target, e.g., `int (*x)[4]`.
+///
+/// Qualifiers are omitted, since they do not change the layout.
+fn c_declaration(dtype: &Dtype, declarator: String) -> String {
+    match dtype {
+        Dtype::Unit { .. } => format!("void {declarator}"),
+        Dtype::Int {
+            width, is_signed, ..
+        } => {
+            let base = match width {
+                1 => "_Bool",
+                8 => "char",
+                16 => "short",
+                32 => "int",
+                64 => "long",
+                _ => panic!("`{width}` is not a width of integers"),
+            };
+            let sign = match (width, is_signed) {
+                (1, _) => "",
+                (8, true) => "signed ",
+                (_, true) => "",
+                (_, false) => "unsigned ",
+            };
+            format!("{sign}{base} {declarator}")
+        }
+        Dtype::Float { width, .. } => {
+            let base = if *width == Dtype::SIZE_OF_FLOAT * Dtype::BITS_OF_BYTE {
+                "float"
+            } else {
+                "double"
+            };
+            format!("{base} {declarator}")
+        }
+        Dtype::Pointer { inner, .. } => {
+            let declarator =
+                if matches!(inner.deref(), Dtype::Array { .. } | Dtype::Function { .. }) {
+                    format!("(*{declarator})")
+                } else {
+                    format!("*{declarator}")
+                };
+            c_declaration(inner, declarator)
+        }
+        Dtype::Array { inner, size, .. } => {
+            let size = size.map(|size| size.to_string()).unwrap_or_default();
+            c_declaration(inner, format!("{declarator}[{size}]"))
+        }
+        Dtype::Struct { name, .. } => {
+            let name = name.as_ref().expect("struct should have its name");
+            let keyword = dtype
+                .get_struct_keyword()
+                .expect("`dtype` must be a struct");
+            format!("{keyword} {} {declarator}", c_tag(name))
+        }
+        Dtype::Function { ret, params } => {
+            let params = if params.is_empty() {
+                "void".to_string()
+            } else {
+                params
+                    .iter()
+                    .map(|param| c_declaration(param, String::new()).trim_end().to_string())
+                    .join(", ")
+            };
+            c_declaration(ret, format!("{declarator}({params})"))
+        }
+        Dtype::Typedef { .. } => panic!("typedef should be replaced by real dtype"),
+        Dtype::Enum { .. } => panic!("enum should be replaced by int"),
+    }
+}
+
+/// Returns the name of the `index`th member `field` in C, which is made up if it is anonymous.
+fn member_name(field: &Named<Dtype>, index: usize) -> String {
+    field
+        .name()
+        .cloned()
+        .unwrap_or_else(|| format!("__kecc_anon{index}"))
+}
+
+fn write_padding(size: usize, kind: &str, indent: usize, write: &mut dyn Write) -> Result<()> {
+    if size == 0 {
+        return Ok(());
+    }
+
+    write_indent(indent, write)?;
+    writeln!(write, "// {kind}: {size} bytes")
+}
@@ -1,9 +1,20 @@
 //! The intermediate representation.
 
+mod bounds;
+mod builtin;
+mod diff;
 mod dtype;
 mod equiv;
+#[cfg(feature = "testing")]
+mod exec_equiv;
+#[cfg(feature = "interp")]
 mod interp;
+mod layout;
 mod parse;
+#[cfg(feature = "interp")]
+mod stdin;
+mod value;
+mod verify;
 mod visualize;
 mod write_ir;
 
@@ -14,30 +25,130 @@ use hexf_parse::{parse_hexf32, parse_hexf64};
 use itertools::Itertools;
 use lang_c::ast;
 use ordered_float::OrderedFloat;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::hash::{Hash, Hasher};
 
 use crate::write_base::*;
-pub use dtype::{Dtype, DtypeError, HasDtype};
-pub use interp::{interp, Value};
-pub use parse::Parse;
+use crate::{parallel_map, some_or};
+pub(crate) use bounds::initializer_overflows;
+pub use bounds::{check_access_bounds, check_bounds, BoundsWarning};
+pub use builtin::Builtin;
+pub use diff::diff;
+pub use dtype::{BitField, DeclarationStorage, Dtype, DtypeError, HasDtype};
+#[cfg(feature = "testing")]
+pub use exec_equiv::{equiv_by_execution, ArgGenerator, TestVectors, Verdict};
+#[cfg(feature = "interp")]
+pub use interp::{
+    call, interp, interp_bounded, interp_constant_expression, interp_with_bindings,
+    interp_with_options, Bindings, Execution, InterpreterError,
+};
+pub(crate) use layout::{c_keyword, c_tag};
+pub use layout::{Layout, LayoutMismatch, MemberOffset, StructLayout};
+pub use parse::{Error as ParseError, IrDiagnostic, IrMistake, Parse};
+#[cfg(feature = "interp")]
+pub use stdin::StdinFunction;
+pub(crate) use value::calculator;
+pub use value::Value;
+pub(crate) use verify::mismatched_store;
+pub use verify::{verify, verify_all, VerifierError};
 pub use visualize::Visualizer;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct TranslationUnit {
     pub decls: BTreeMap<String, Declaration>,
+    /// The definition of each struct and union by its tag, or `None` if it is only declared, e.g.,
+    /// by `struct Node;`.
     pub structs: HashMap<String, Option<Dtype>>,
 }
 
+impl TranslationUnit {
+    /// Counts the instructions and block exits of the function definitions by opcode.
+    ///
+    /// The functions are counted independently, in parallel with the `parallel` feature, and their
+    /// counts are summed up.
+    pub fn opcode_histogram(&self) -> BTreeMap<&'static str, usize> {
+        let definitions = self.definitions().collect::<Vec<_>>();
+        let mut histogram = BTreeMap::new();
+        for function in parallel_map(definitions, FunctionDefinition::opcode_histogram) {
+            for (opcode, count) in function {
+                *histogram.entry(opcode).or_insert(0) += count;
+            }
+        }
+        histogram
+    }
+
+    /// Counts the instructions and block exits of each function definition by opcode.
+    pub fn opcode_histograms(&self) -> BTreeMap<&str, BTreeMap<&'static str, usize>> {
+        self.decls
+            .iter()
+            .filter_map(|(name, decl)| {
+                let (_, definition) = decl.get_function()?;
+                Some((name.as_str(), definition.as_ref()?.opcode_histogram()))
+            })
+            .collect()
+    }
+
+    /// Returns the functions and variables referenced by the function definitions but defined in
+    /// another unit, i.e., the functions without definitions other than the builtins and the
+    /// `extern` variables. They are resolved when the unit is linked.
+    pub fn undefined_symbols(&self) -> BTreeSet<&str> {
+        let mut symbols = BTreeSet::new();
+        let mut visit = |operand: &Operand| {
+            let name = some_or!(
+                operand
+                    .get_constant()
+                    .and_then(Constant::get_global_variable_name),
+                return
+            );
+            let (name, decl) = some_or!(self.decls.get_key_value(&name), return);
+            let is_undefined = match decl {
+                Declaration::Variable { is_extern, .. } => *is_extern,
+                Declaration::Function { definition, .. } => {
+                    definition.is_none() && Builtin::from_name(name).is_none()
+                }
+            };
+            if is_undefined {
+                let _ = symbols.insert(name.as_str());
+            }
+        };
+
+        for definition in self.definitions() {
+            for block in definition.blocks.values() {
+                for instruction in &block.instructions {
+                    instruction.walk_operands(&mut visit);
+                }
+                block.exit.walk_operands(&mut visit);
+            }
+        }
+        symbols
+    }
+
+    fn definitions(&self) -> impl Iterator<Item = &FunctionDefinition> {
+        self.decls
+            .values()
+            .filter_map(|decl| decl.get_function()?.1.as_ref())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Declaration {
     Variable {
         dtype: Dtype,
         initializer: Option<ast::Initializer>,
+        /// Whether the variable is only declared by `extern`, and defined in another unit, e.g., a
+        /// harness the unit is linked with. Such a variable has neither storage nor an initializer
+        /// in this unit.
+        is_extern: bool,
+        /// Whether the variable has internal linkage, i.e., it is declared `static` at file scope
+        /// or in a function body, so that it is not visible to other units.
+        is_static: bool,
     },
     Function {
         signature: FunctionSignature,
         definition: Option<FunctionDefinition>,
+        /// Whether the function has internal linkage, i.e., it is declared `static`, so that it is
+        /// not visible to other units.
+        is_static: bool,
     },
 }
 
@@ -68,19 +179,26 @@ impl TryFrom<Dtype> for Declaration {
             | Dtype::Struct { .. } => Ok(Declaration::Variable {
                 dtype,
                 initializer: None,
+                is_extern: false,
+                is_static: false,
             }),
             Dtype::Function { .. } => Ok(Declaration::Function {
                 signature: FunctionSignature::new(dtype),
                 definition: None,
+                is_static: false,
             }),
             Dtype::Typedef { .. } => panic!("typedef should be replaced by real dtype"),
+            Dtype::Enum { .. } => panic!("enum should be replaced by int"),
         }
     }
 }
 
 impl Declaration {
     pub fn get_variable(&self) -> Option<(&Dtype, &Option<ast::Initializer>)> {
-        if let Self::Variable { dtype, initializer } = self {
+        if let Self::Variable {
+            dtype, initializer, ..
+        } = self
+        {
             Some((dtype, initializer))
         } else {
             None
@@ -113,10 +231,88 @@ impl Declaration {
         }
     }
 
+    /// Returns `true` if the declaration is a variable declared by `extern`, which is defined in
+    /// another unit.
+    pub fn is_extern(&self) -> bool {
+        matches!(
+            self,
+            Self::Variable {
+                is_extern: true,
+                ..
+            }
+        )
+    }
+
+    /// Returns `true` if the declaration is a variable or a function with internal linkage, which
+    /// is declared `static`.
+    pub fn is_static(&self) -> bool {
+        matches!(
+            self,
+            Self::Variable {
+                is_static: true,
+                ..
+            } | Self::Function {
+                is_static: true,
+                ..
+            }
+        )
+    }
+
+    /// Returns `true` if the declaration is a variable whose value is entirely zero.
+    ///
+    /// A variable without an initializer, or whose initializer only consists of zero constants
+    /// (elements missing from an initializer list are implicitly zero), is a `ZeroInit` value and
+    /// can be placed in the `.bss` section. The value of an `extern` variable is unknown.
+    pub fn is_zero_initialized(&self) -> bool {
+        match self {
+            Self::Variable {
+                is_extern: true, ..
+            } => false,
+            Self::Variable { initializer, .. } => initializer
+                .as_ref()
+                .map(is_zero_initializer)
+                .unwrap_or(true),
+            Self::Function { .. } => false,
+        }
+    }
+
+    /// Returns the names of the globals whose addresses initialize the variable, e.g., `f` of
+    /// `int (*fp)(void) = f;`.
+    pub fn initializer_references(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        if let Some((_, Some(initializer))) = self.get_variable() {
+            push_references(initializer, &mut names);
+        }
+        names
+    }
+
+    /// Returns `true` if the declaration is a variable whose value never changes, i.e., its dtype
+    /// (or the element dtype, for arrays) is `const` and it has internal linkage, so that no other
+    /// unit refers to it.
+    ///
+    /// Since writing to such a variable is undefined behavior, it can be placed in the `.rodata`
+    /// section and loads from it can be folded to its initializer.
+    pub fn is_constant(&self) -> bool {
+        if !self.is_static() || self.is_extern() {
+            return false;
+        }
+        let mut dtype = some_or!(self.get_variable(), return false).0;
+        while let Dtype::Array { inner, .. } = dtype {
+            dtype = inner.deref();
+        }
+
+        dtype.is_const()
+    }
+
     /// Check if type is conflicting for pre-declared one
     pub fn is_compatible(&self, other: &Declaration) -> bool {
         match (self, other) {
-            (Self::Variable { dtype, .. }, Self::Variable { dtype: other, .. }) => dtype == other,
+            // E.g., `extern int t[];` and `int t[3];`.
+            (Self::Variable { dtype, .. }, Self::Variable { dtype: other, .. }) => {
+                dtype == other
+                    || ((dtype.is_incomplete_array() || other.is_incomplete_array())
+                        && dtype.get_array_inner() == other.get_array_inner())
+            }
             (
                 Self::Function { signature, .. },
                 Self::Function {
@@ -128,6 +324,36 @@ impl Declaration {
     }
 }
 
+/// Pushes the names of the globals referred to by `initializer` into `names`.
+fn push_references<'a>(initializer: &'a ast::Initializer, names: &mut Vec<&'a str>) {
+    match initializer {
+        ast::Initializer::Expression(expr) => {
+            if let ast::Expression::Identifier(identifier) = &expr.node {
+                names.push(&identifier.node.name);
+            }
+        }
+        ast::Initializer::List(items) => {
+            for item in items {
+                push_references(&item.node.initializer.node, names);
+            }
+        }
+    }
+}
+
+/// Returns `true` if `initializer` only consists of zero constants, i.e., it initializes its
+/// variable to the `ZeroInit` value.
+#[inline]
+pub(crate) fn is_zero_initializer(initializer: &ast::Initializer) -> bool {
+    match initializer {
+        ast::Initializer::Expression(expr) => Constant::try_from(&expr.node)
+            .map(|constant| constant.is_zero())
+            .unwrap_or(false),
+        ast::Initializer::List(items) => items
+            .iter()
+            .all(|item| is_zero_initializer(&item.node.initializer.node)),
+    }
+}
+
 impl HasDtype for Declaration {
     fn dtype(&self) -> Dtype {
         match self {
@@ -174,6 +400,81 @@ pub struct FunctionDefinition {
     pub bid_init: BlockId,
 }
 
+impl FunctionDefinition {
+    /// Counts the instructions and block exits by opcode.
+    pub fn opcode_histogram(&self) -> BTreeMap<&'static str, usize> {
+        let mut histogram = BTreeMap::new();
+        for block in self.blocks.values() {
+            let opcodes = block
+                .instructions
+                .iter()
+                .map(|instruction| instruction.opcode_name())
+                .chain([block.exit.opcode_name()]);
+            for opcode in opcodes {
+                *histogram.entry(opcode).or_insert(0) += 1;
+            }
+        }
+        histogram
+    }
+
+    /// Returns the global variable each pointer register points into, along with the byte offset
+    /// from the start of the variable.
+    ///
+    /// A register is included if it is computed by a chain of `getelementptr` from a global
+    /// variable. The offset is `None` if some offset in the chain is not a constant.
+    pub fn global_pointers(&self) -> HashMap<RegisterId, (String, Option<isize>)> {
+        let mut pointers = HashMap::new();
+
+        loop {
+            let mut changed = false;
+            for (bid, block) in &self.blocks {
+                for (iid, instruction) in block.instructions.iter().enumerate() {
+                    let rid = RegisterId::temp(*bid, iid);
+                    if pointers.contains_key(&rid) {
+                        continue;
+                    }
+
+                    let (ptr, offset) = if let Instruction::GetElementPtr { ptr, offset, .. } =
+                        instruction.deref()
+                    {
+                        (ptr, offset)
+                    } else {
+                        continue;
+                    };
+
+                    let (name, base) = match ptr {
+                        Operand::Constant(Constant::GlobalVariable { name, .. }) => {
+                            (name.clone(), Some(0))
+                        }
+                        Operand::Register { rid, .. } => {
+                            some_or!(pointers.get(rid), continue).clone()
+                        }
+                        _ => continue,
+                    };
+                    let offset = offset.get_constant().and_then(|offset| {
+                        let (value, _, is_signed) = offset.get_int()?;
+                        Some(if is_signed {
+                            value as i128 as isize
+                        } else {
+                            value as isize
+                        })
+                    });
+                    let offset = base.zip(offset).map(|(base, offset)| base + offset);
+
+                    let _unused = pointers.insert(rid, (name, offset));
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        pointers
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct BlockId(pub usize);
 
@@ -228,6 +529,26 @@ pub enum Instruction {
         offset: Operand,
         dtype: Dtype,
     },
+    /// The minimum of `lhs` and `rhs`, compared as signed or unsigned integers by `dtype`.
+    Min {
+        lhs: Operand,
+        rhs: Operand,
+        dtype: Dtype,
+    },
+    /// The maximum of `lhs` and `rhs`, compared as signed or unsigned integers by `dtype`.
+    Max {
+        lhs: Operand,
+        rhs: Operand,
+        dtype: Dtype,
+    },
+    /// The absolute value of `operand`, a signed integer.
+    ///
+    /// The absolute value of the minimum integer overflows like its negation: it wraps around to
+    /// the minimum integer itself, or is an error, depending on the overflow policy.
+    Abs {
+        operand: Operand,
+        dtype: Dtype,
+    },
 }
 
 impl HasDtype for Instruction {
@@ -243,13 +564,16 @@ impl HasDtype for Instruction {
                 target_dtype: dtype,
                 ..
             }
-            | Self::GetElementPtr { dtype, .. } => dtype.clone(),
+            | Self::GetElementPtr { dtype, .. }
+            | Self::Min { dtype, .. }
+            | Self::Max { dtype, .. }
+            | Self::Abs { dtype, .. } => dtype.clone(),
             Self::Load { ptr } => ptr
                 .dtype()
                 .get_pointer_inner()
                 .expect("Load instruction must have pointer value as operand")
                 .clone()
-                .set_const(false),
+                .strip_outer_const(),
         }
     }
 }
@@ -258,48 +582,200 @@ impl Instruction {
     pub fn has_no_side_effects(&self) -> bool {
         !matches!(self, Self::Store { .. } | Self::Call { .. })
     }
+
+    /// The opcode of the instruction as written in the IR, e.g., `add` or `cmp lt`.
+    pub fn opcode_name(&self) -> &'static str {
+        match self {
+            Self::Nop => "nop",
+            Self::BinOp { op, .. } => binary_opcode_name(op),
+            Self::UnaryOp { op, .. } => unary_opcode_name(op),
+            Self::Store { .. } => "store",
+            Self::Load { .. } => "load",
+            Self::Call { .. } => "call",
+            Self::TypeCast { .. } => "typecast",
+            Self::GetElementPtr { .. } => "getelementptr",
+            Self::Min { .. } => "min",
+            Self::Max { .. } => "max",
+            Self::Abs { .. } => "abs",
+        }
+    }
+
+    /// Returns an iterator over the operands of the instruction, in order.
+    pub fn operands(&self) -> impl Iterator<Item = &Operand> {
+        let operands = match self {
+            Self::Nop => Vec::new(),
+            Self::BinOp { lhs, rhs, .. }
+            | Self::Min { lhs, rhs, .. }
+            | Self::Max { lhs, rhs, .. } => vec![lhs, rhs],
+            Self::UnaryOp { operand, .. } | Self::Abs { operand, .. } => vec![operand],
+            Self::Store { ptr, value } => vec![ptr, value],
+            Self::Load { ptr } => vec![ptr],
+            Self::Call { callee, args, .. } => {
+                let mut operands = vec![callee];
+                operands.extend(args);
+                operands
+            }
+            Self::TypeCast { value, .. } => vec![value],
+            Self::GetElementPtr { ptr, offset, .. } => vec![ptr, offset],
+        };
+        operands.into_iter()
+    }
+
+    /// Returns an iterator over the operands of the instruction, in order, allowing them to be
+    /// modified.
+    pub fn operands_mut(&mut self) -> impl Iterator<Item = &mut Operand> {
+        let operands = match self {
+            Self::Nop => Vec::new(),
+            Self::BinOp { lhs, rhs, .. }
+            | Self::Min { lhs, rhs, .. }
+            | Self::Max { lhs, rhs, .. } => vec![lhs, rhs],
+            Self::UnaryOp { operand, .. } | Self::Abs { operand, .. } => vec![operand],
+            Self::Store { ptr, value } => vec![ptr, value],
+            Self::Load { ptr } => vec![ptr],
+            Self::Call { callee, args, .. } => {
+                let mut operands = vec![callee];
+                operands.extend(args);
+                operands
+            }
+            Self::TypeCast { value, .. } => vec![value],
+            Self::GetElementPtr { ptr, offset, .. } => vec![ptr, offset],
+        };
+        operands.into_iter()
+    }
+
+    /// Applies `f` to each operand of the instruction.
+    pub fn walk_operands<F>(&self, f: F)
+    where
+        F: FnMut(&Operand),
+    {
+        self.operands().for_each(f)
+    }
+
+    /// Applies `f` to each operand of the instruction, allowing it to be modified.
+    pub fn walk_operands_mut<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut Operand),
+    {
+        self.operands_mut().for_each(f)
+    }
+}
+
+/// The semantics of the shift instructions `shl` and `shr`, which the interpreter, the constant
+/// folders and the RISC-V back-end implement alike.
+///
+/// C leaves undefined a shift by a negative amount or by the width of the promoted left operand
+/// or more, as well as a left shift of a negative signed value or into its sign bit, and leaves the
+/// right shift of a negative value implementation-defined. In the IR:
+///
+/// - the amount is masked by [`ShiftKind::amount_mask`], i.e., taken modulo the width of the
+///   operands, as RISC-V does for 32- and 64-bit shifts;
+/// - `shl` wraps around in two's complement;
+/// - `shr` is arithmetic for signed operands and logical for unsigned ones.
+///
+/// The shifts undefined in C are reported by the interpreter under `OverflowPolicy::Trap` and
+/// `OverflowPolicy::Ub` instead, since other compilers may not mask the amount likewise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShiftKind {
+    /// `shl`, filling the vacated bits with zeros
+    Left,
+    /// `shr` of a signed value, filling the vacated bits with its sign bit
+    ArithmeticRight,
+    /// `shr` of an unsigned value, filling the vacated bits with zeros
+    LogicalRight,
+}
+
+impl ShiftKind {
+    /// Returns the kind of the shift `op` of signed operands if `is_signed`, or `None` if `op` is
+    /// not a shift.
+    pub fn new(op: &ast::BinaryOperator, is_signed: bool) -> Option<Self> {
+        match op {
+            ast::BinaryOperator::ShiftLeft => Some(Self::Left),
+            ast::BinaryOperator::ShiftRight if is_signed => Some(Self::ArithmeticRight),
+            ast::BinaryOperator::ShiftRight => Some(Self::LogicalRight),
+            _ => None,
+        }
+    }
+
+    /// Returns the mask of the amounts of the shifts of `width`-bit integers, whose widths are
+    /// powers of two.
+    pub const fn amount_mask(width: usize) -> u128 {
+        width as u128 - 1
+    }
+
+    /// Returns `true` if shifting a `width`-bit integer by `amount` is defined in C, i.e., if
+    /// masking the amount does not change it. A negative `amount` is sign-extended, and thus out
+    /// of range.
+    pub fn is_in_range(amount: u128, width: usize) -> bool {
+        amount <= Self::amount_mask(width)
+    }
+
+    /// Returns the `width`-bit integer `value` shifted by `amount`, truncated to `width` bits.
+    pub fn apply(self, value: u128, amount: u128, width: usize) -> u128 {
+        let amount = amount & Self::amount_mask(width);
+        let mask = u128::MAX >> (u128::BITS as usize - width);
+        let value = value & mask;
+        let result = match self {
+            Self::Left => value << amount,
+            Self::LogicalRight => value >> amount,
+            Self::ArithmeticRight => {
+                // Moves the sign bit to the most significant bit to shift it in.
+                let padding = (u128::BITS as usize - width) as u128;
+                (((value << padding) as i128) >> (padding + amount)) as u128
+            }
+        };
+
+        result & mask
+    }
 }
 
 impl WriteOp for ast::BinaryOperator {
     fn write_operation(&self) -> String {
-        match self {
-            Self::Multiply => "mul",
-            Self::Divide => "div",
-            Self::Modulo => "mod",
-            Self::Plus => "add",
-            Self::Minus => "sub",
-            Self::ShiftLeft => "shl",
-            Self::ShiftRight => "shr",
-            Self::Equals => "cmp eq",
-            Self::NotEquals => "cmp ne",
-            Self::Less => "cmp lt",
-            Self::LessOrEqual => "cmp le",
-            Self::Greater => "cmp gt",
-            Self::GreaterOrEqual => "cmp ge",
-            Self::BitwiseAnd => "and",
-            Self::BitwiseXor => "xor",
-            Self::BitwiseOr => "or",
-            _ => todo!(
-                "ast::BinaryOperator::WriteOp: write operation for {:?} is needed",
-                self
-            ),
-        }
-        .to_string()
+        binary_opcode_name(self).to_string()
     }
 }
 
 impl WriteOp for ast::UnaryOperator {
     fn write_operation(&self) -> String {
-        match self {
-            Self::Plus => "plus",
-            Self::Minus => "minus",
-            Self::Negate => "negate",
-            _ => todo!(
-                "ast::UnaryOperator::WriteOp: write operation for {:?} is needed",
-                self
-            ),
-        }
-        .to_string()
+        unary_opcode_name(self).to_string()
+    }
+}
+
+/// The opcode of the binary operator `op` in the IR.
+fn binary_opcode_name(op: &ast::BinaryOperator) -> &'static str {
+    match op {
+        ast::BinaryOperator::Multiply => "mul",
+        ast::BinaryOperator::Divide => "div",
+        ast::BinaryOperator::Modulo => "mod",
+        ast::BinaryOperator::Plus => "add",
+        ast::BinaryOperator::Minus => "sub",
+        ast::BinaryOperator::ShiftLeft => "shl",
+        ast::BinaryOperator::ShiftRight => "shr",
+        ast::BinaryOperator::Equals => "cmp eq",
+        ast::BinaryOperator::NotEquals => "cmp ne",
+        ast::BinaryOperator::Less => "cmp lt",
+        ast::BinaryOperator::LessOrEqual => "cmp le",
+        ast::BinaryOperator::Greater => "cmp gt",
+        ast::BinaryOperator::GreaterOrEqual => "cmp ge",
+        ast::BinaryOperator::BitwiseAnd => "and",
+        ast::BinaryOperator::BitwiseXor => "xor",
+        ast::BinaryOperator::BitwiseOr => "or",
+        _ => todo!(
+            "ast::BinaryOperator::WriteOp: write operation for {:?} is needed",
+            op
+        ),
+    }
+}
+
+/// The opcode of the unary operator `op` in the IR.
+fn unary_opcode_name(op: &ast::UnaryOperator) -> &'static str {
+    match op {
+        ast::UnaryOperator::Plus => "plus",
+        ast::UnaryOperator::Minus => "minus",
+        ast::UnaryOperator::Negate => "negate",
+        _ => todo!(
+            "ast::UnaryOperator::WriteOp: write operation for {:?} is needed",
+            op
+        ),
     }
 }
 
@@ -331,6 +807,9 @@ impl fmt::Display for Instruction {
             Instruction::GetElementPtr { ptr, offset, .. } => {
                 write!(f, "getelementptr {ptr} offset {offset}")
             }
+            Instruction::Min { lhs, rhs, .. } => write!(f, "min {lhs} {rhs}"),
+            Instruction::Max { lhs, rhs, .. } => write!(f, "max {lhs} {rhs}"),
+            Instruction::Abs { operand, .. } => write!(f, "abs {operand}"),
         }
     }
 }
@@ -378,6 +857,89 @@ impl BlockExit {
             Self::Return { .. } | Self::Unreachable => {}
         }
     }
+
+    /// The opcode of the block exit as written in the IR, e.g., `br`.
+    pub fn opcode_name(&self) -> &'static str {
+        match self {
+            Self::Jump { .. } => "j",
+            Self::ConditionalJump { .. } => "br",
+            Self::Switch { .. } => "switch",
+            Self::Return { .. } => "ret",
+            Self::Unreachable => "unreachable",
+        }
+    }
+
+    /// Returns the jumps of the block exit, in order.
+    pub fn jump_args(&self) -> Vec<&JumpArg> {
+        match self {
+            Self::Jump { arg } => vec![arg],
+            Self::ConditionalJump {
+                arg_then, arg_else, ..
+            } => vec![arg_then, arg_else],
+            Self::Switch { default, cases, .. } => {
+                let mut args = vec![default];
+                args.extend(cases.iter().map(|(_, arg)| arg));
+                args
+            }
+            Self::Return { .. } | Self::Unreachable => Vec::new(),
+        }
+    }
+
+    /// Returns the blocks the block exit may jump to, in order and without duplicates.
+    pub fn successors(&self) -> Vec<BlockId> {
+        self.jump_args()
+            .into_iter()
+            .map(|arg| arg.bid)
+            .unique()
+            .collect()
+    }
+
+    /// Applies `f` to each operand of the block exit, including the arguments of jumps.
+    pub fn walk_operands<F>(&self, mut f: F)
+    where
+        F: FnMut(&Operand),
+    {
+        match self {
+            Self::Jump { arg } => arg.args.iter().for_each(f),
+            Self::ConditionalJump {
+                condition,
+                arg_then,
+                arg_else,
+            } => {
+                f(condition);
+                arg_then.args.iter().for_each(&mut f);
+                arg_else.args.iter().for_each(&mut f);
+            }
+            Self::Switch {
+                value,
+                default,
+                cases,
+            } => {
+                f(value);
+                default.args.iter().for_each(&mut f);
+                for (_, arg) in cases {
+                    arg.args.iter().for_each(&mut f);
+                }
+            }
+            Self::Return { value } => f(value),
+            Self::Unreachable => {}
+        }
+    }
+
+    /// Applies `f` to each operand of the block exit, allowing it to be modified.
+    pub fn walk_operands_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut Operand),
+    {
+        match self {
+            Self::ConditionalJump { condition, .. } => f(condition),
+            Self::Switch { value, .. } => f(value),
+            Self::Return { value } => f(value),
+            Self::Jump { .. } | Self::Unreachable => {}
+        }
+
+        self.walk_jump_args(|arg| arg.args.iter_mut().for_each(&mut f));
+    }
 }
 
 impl fmt::Display for BlockExit {
@@ -399,9 +961,8 @@ impl fmt::Display for BlockExit {
                 value,
                 default,
                 cases.iter().format_with("\n", |(v, b), f| f(&format_args!(
-                    "    {}:{} {}",
-                    v,
-                    v.dtype(),
+                    "    {} {}",
+                    Operand::constant(v.clone()),
                     b
                 )))
             ),
@@ -479,7 +1040,16 @@ impl Operand {
 impl fmt::Display for Operand {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Constant(value) => write!(f, "{}:{}", value, value.dtype()),
+            Self::Constant(value) => {
+                write!(f, "{}:{}", value, value.dtype())?;
+                if let Constant::Float { value, width } = value {
+                    write!(f, " /* {} */", float_decimal(value.into_inner(), *width))?;
+                }
+                if let Some(origin) = value.origin() {
+                    write!(f, " /* {origin} */")?;
+                }
+                Ok(())
+            }
             Self::Register { rid, dtype } => write!(f, "{rid}:{dtype}"),
         }
     }
@@ -598,11 +1168,20 @@ pub enum Constant {
     Undef {
         dtype: Dtype,
     },
+    /// Zero value of `dtype`, which may be an aggregate (array or struct) type.
+    ///
+    /// Aggregate zero values are represented by a single constant instead of materializing every
+    /// element, so that e.g. zero-filling a large array does not bloat the IR.
+    ZeroInit {
+        dtype: Dtype,
+    },
     Unit,
     Int {
         value: u128,
         width: usize,
         is_signed: bool,
+        /// The name the constant was written as, if any, e.g., an enumerator
+        origin: ConstantOrigin,
     },
     Float {
         /// `value` may be `f32`, but it is possible to consider it as `f64`.
@@ -620,6 +1199,26 @@ pub enum Constant {
     },
 }
 
+/// The name an integer constant was written as in the source, e.g., the enumerator `STATE_DONE`
+/// for `42`, which the IR is printed with as a comment, e.g., `42:i32 /* STATE_DONE */`.
+///
+/// It only makes the IR easier to read: all origins are equal and hash alike, so that a constant
+/// means the same with or without its origin.
+#[derive(Debug, Default, Clone)]
+pub struct ConstantOrigin(pub Option<String>);
+
+impl PartialEq for ConstantOrigin {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for ConstantOrigin {}
+
+impl Hash for ConstantOrigin {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
 impl TryFrom<&ast::Constant> for Constant {
     type Error = ();
 
@@ -628,7 +1227,7 @@ impl TryFrom<&ast::Constant> for Constant {
             ast::Constant::Integer(integer) => {
                 let dtype = match integer.suffix.size {
                     ast::IntegerSize::Int => Dtype::INT,
-                    ast::IntegerSize::Long => Dtype::LONG,
+                    ast::IntegerSize::Long => Dtype::long(),
                     ast::IntegerSize::LongLong => Dtype::LONGLONG,
                 };
 
@@ -758,6 +1357,11 @@ impl Constant {
         Self::Undef { dtype }
     }
 
+    #[inline]
+    pub fn zero_init(dtype: Dtype) -> Self {
+        Self::ZeroInit { dtype }
+    }
+
     #[inline]
     pub fn unit() -> Self {
         Self::Unit
@@ -772,6 +1376,41 @@ impl Constant {
             value,
             width,
             is_signed,
+            origin: ConstantOrigin::default(),
+        }
+    }
+
+    /// Returns the constant annotated with the name `origin` it was written as, if it is an
+    /// integer. The other constants are returned unchanged.
+    #[must_use]
+    pub fn with_origin(self, origin: String) -> Self {
+        match self {
+            Self::Int {
+                value,
+                width,
+                is_signed,
+                ..
+            } => Self::Int {
+                value,
+                width,
+                is_signed,
+                origin: ConstantOrigin(Some(origin)),
+            },
+            _ => self,
+        }
+    }
+
+    /// Returns the name the constant was written as, if any (see [`ConstantOrigin`]).
+    #[inline]
+    pub fn origin(&self) -> Option<&str> {
+        if let Self::Int {
+            origin: ConstantOrigin(Some(origin)),
+            ..
+        } = self
+        {
+            Some(origin.as_str())
+        } else {
+            None
         }
     }
 
@@ -787,6 +1426,30 @@ impl Constant {
         }
     }
 
+    /// Creates a floating-point constant of `dtype` from its bit pattern, e.g., `0x3DCCCCCD` for
+    /// `0.1f`.
+    #[inline]
+    pub fn float_from_bits(bits: u64, dtype: Dtype) -> Self {
+        let width = dtype
+            .get_float_width()
+            .expect("`dtype` must be `Dtype::Float`");
+
+        Self::Float {
+            value: float_value_from_bits(bits, width).into(),
+            width,
+        }
+    }
+
+    /// Returns the bit pattern of a floating-point constant in its own width.
+    #[inline]
+    pub fn get_float_bits(&self) -> Option<u64> {
+        if let Self::Float { value, width } = self {
+            Some(float_value_to_bits(value.into_inner(), *width))
+        } else {
+            None
+        }
+    }
+
     #[inline]
     pub fn global_variable(name: String, dtype: Dtype) -> Self {
         Self::GlobalVariable { name, dtype }
@@ -798,6 +1461,7 @@ impl Constant {
             value,
             width,
             is_signed,
+            ..
         } = self
         {
             Some((*value, *width, *is_signed))
@@ -822,6 +1486,7 @@ impl Constant {
                 value,
                 width,
                 is_signed,
+                ..
             } => {
                 assert!(is_signed);
                 let minus_value = -(value as i128);
@@ -829,6 +1494,7 @@ impl Constant {
                     value: minus_value as u128,
                     width,
                     is_signed,
+                    origin: ConstantOrigin::default(),
                 }
             }
             Self::Float { mut value, width } => {
@@ -846,6 +1512,22 @@ impl Constant {
         matches!(self, Self::Undef { .. })
     }
 
+    pub fn is_zero_init(&self) -> bool {
+        matches!(self, Self::ZeroInit { .. })
+    }
+
+    /// Returns `true` if the constant is a zero value, i.e., it is `ZeroInit` or a zero integer or
+    /// float.
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Self::ZeroInit { .. } => true,
+            Self::Int { value, .. } => *value == 0,
+            // `-0.0` compares equal to `0.0`, but its bits are not zero.
+            Self::Float { value, .. } => value.into_inner().to_bits() == 0,
+            _ => false,
+        }
+    }
+
     #[must_use]
     pub fn typecast(self, target_dtype: Dtype) -> Self {
         if self.dtype() == target_dtype {
@@ -900,6 +1582,8 @@ impl Constant {
             (Constant::Float { value, .. }, Dtype::Float { .. }) => {
                 Constant::float(value.into_inner(), target_dtype)
             }
+            (Constant::ZeroInit { .. }, Dtype::Int { .. }) => Constant::int(0, target_dtype),
+            (Constant::ZeroInit { .. }, Dtype::Float { .. }) => Constant::float(0.0, target_dtype),
             _ => todo!("typecast ({:?}) {:?}", self, target_dtype),
         }
     }
@@ -922,10 +1606,61 @@ pub fn trim_unnecessary_bits(value: u128, width: u128) -> u128 {
     value & bit_mask
 }
 
+/// Returns the value of the floating-point number of `width` bits with the bit pattern `bits`.
+///
+/// Unlike `f32::from_bits(bits) as f64`, the payload of a NaN `f32` is kept, so that
+/// [`float_value_to_bits`] recovers `bits`.
+pub fn float_value_from_bits(bits: u64, width: usize) -> f64 {
+    if width != Dtype::SIZE_OF_FLOAT * Dtype::BITS_OF_BYTE {
+        return f64::from_bits(bits);
+    }
+
+    let value = f32::from_bits(bits as u32);
+    if !value.is_nan() {
+        return f64::from(value);
+    }
+
+    let sign = (bits >> 31) & 1;
+    let payload = bits & 0x7F_FFFF;
+    f64::from_bits((sign << 63) | (0x7FF << 52) | (payload << 29))
+}
+
+/// Returns the bit pattern of `value` as a floating-point number of `width` bits, the inverse of
+/// [`float_value_from_bits`].
+pub fn float_value_to_bits(value: f64, width: usize) -> u64 {
+    if width != Dtype::SIZE_OF_FLOAT * Dtype::BITS_OF_BYTE {
+        return value.to_bits();
+    }
+
+    if !value.is_nan() {
+        return u64::from((value as f32).to_bits());
+    }
+
+    let bits = value.to_bits();
+    let sign = (bits >> 63) & 1;
+    let payload = (bits >> 29) & 0x7F_FFFF;
+    // A NaN whose payload is lost by the narrowing stays a quiet NaN.
+    let payload = if payload == 0 { 0x40_0000 } else { payload };
+    (sign << 31) | (0xFF << 23) | payload
+}
+
+/// Returns the shortest decimal representation of a floating-point number of `width` bits. It is
+/// only informative, e.g., NaNs are written `nan` regardless of their signs and payloads.
+fn float_decimal(value: f64, width: usize) -> String {
+    if value.is_nan() {
+        "nan".to_string()
+    } else if width == Dtype::SIZE_OF_FLOAT * Dtype::BITS_OF_BYTE {
+        format!("{:?}", value as f32)
+    } else {
+        format!("{value:?}")
+    }
+}
+
 impl fmt::Display for Constant {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Undef { .. } => write!(f, "undef"),
+            Self::ZeroInit { .. } => write!(f, "zeroinit"),
             Self::Unit => write!(f, "unit"),
             Self::Int {
                 value, is_signed, ..
@@ -938,13 +1673,12 @@ impl fmt::Display for Constant {
                     value.to_string()
                 }
             ),
-            Self::Float { value, .. } => {
-                let mut val_str = value.to_string();
-                // For floats with integral values, ".0" is added.
-                if !val_str.contains('.') {
-                    val_str += ".0"
-                };
-                write!(f, "{val_str}")
+            // The bit pattern, since a decimal may not be read back to the same float, e.g., a
+            // NaN with a payload.
+            Self::Float { value, width } => {
+                let digits = width / 4;
+                let bits = float_value_to_bits(value.into_inner(), *width);
+                write!(f, "0x{bits:0digits$X}")
             }
             Self::GlobalVariable { name, .. } => write!(f, "@{name}"),
         }
@@ -955,6 +1689,7 @@ impl HasDtype for Constant {
     fn dtype(&self) -> Dtype {
         match self {
             Self::Undef { dtype } => dtype.clone(),
+            Self::ZeroInit { dtype } => dtype.clone(),
             Self::Unit => Dtype::unit(),
             Self::Int {
                 width, is_signed, ..
@@ -1,7 +1,10 @@
+use core::str::FromStr;
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
 
 use lang_c::*;
+use thiserror::Error;
 
 use crate::ir::*;
 use crate::utils::AssertSupported;
@@ -16,8 +19,9 @@ peg::parser! {
 
         rule __() = whitespace()+
 
-        pub rule translation_unit() -> TranslationUnit
-            = _ named_structs:(named_struct() ** __) _ ds:(named_decl() ** __) _ {
+        // The comment first, if any, is the stamp of an artifact, e.g., `foo.opt.ir`.
+        pub rule translation_unit() -> (TranslationUnit, Vec<(String, Vec<ParsedBlock>)>)
+            = _ (comment() _)? named_structs:(named_struct() ** __) _ ds:(named_decl() ** __) _ {
                 let mut structs = HashMap::new();
                 for named_struct in &named_structs {
                     let name = named_struct.name.as_ref().unwrap();
@@ -36,12 +40,17 @@ peg::parser! {
                 }
 
                 let mut decls = BTreeMap::new();
-                for decl in ds {
-                    let result = decls.insert(decl.name.unwrap(), decl.inner);
+                let mut functions = Vec::new();
+                for (decl, blocks) in ds {
+                    let name = decl.name.unwrap();
+                    if decl.inner.get_function().map_or(false, |(_, definition)| definition.is_some()) {
+                        functions.push((name.clone(), blocks));
+                    }
+                    let result = decls.insert(name, decl.inner);
                     assert!(result.is_none());
                 }
 
-                TranslationUnit { decls, structs }
+                (TranslationUnit { decls, structs }, functions)
             }
 
         rule named_struct() -> Named<Option<Dtype>> =
@@ -50,54 +59,85 @@ peg::parser! {
             }
         /
             "struct" __ id:id() _ ":" _ "{" _ fields:(struct_field() ** (_ "," _)) _ "}"  {
-                let struct_type = Dtype::structure(Some(id.clone()), Some(fields));
+                let (fields, bit_widths) = fields.into_iter().unzip();
+                let struct_type =
+                    Dtype::structure(Some(id.clone()), Some(fields)).with_bit_widths(bit_widths);
                 Named::new(Some(id), Some(struct_type))
             }
+        /
+            "union" __ id:id() _ ":" _ "{" _ fields:(struct_field() ** (_ "," _)) _ "}"  {
+                let (fields, bit_widths) = fields.into_iter().unzip();
+                let union_type =
+                    Dtype::union(Some(id.clone()), Some(fields)).with_bit_widths(bit_widths);
+                Named::new(Some(id), Some(union_type))
+            }
         /
             "<named_struct>" {
                 todo!()
             }
 
-        rule struct_field() -> Named<Dtype> =
-            "%anon" _ ":" _ dtype:dtype() {
-                Named::new(None, dtype)
+        rule struct_field() -> (Named<Dtype>, Option<usize>) =
+            "%anon" _ ":" _ dtype:dtype() width:bit_width()? {
+                (Named::new(None, dtype), width)
             }
         /
-            id:id() _ ":" _ dtype:dtype() {
-                Named::new(Some(id), dtype)
+            id:id() _ ":" _ dtype:dtype() width:bit_width()? {
+                (Named::new(Some(id), dtype), width)
             }
         /
             "<struct_field>" {
                 todo!()
             }
 
-        rule named_decl() -> Named<Declaration> =
-            "var" __ dtype:dtype() __ var:global_variable() _ "=" _ initializer:initializer() {
-                Named::new(Some(var), Declaration::Variable {
+        rule bit_width() -> usize =
+            _ ":" _ n:number() { n }
+
+        // The blocks of a function definition are returned apart from it, to be checked.
+        rule named_decl() -> (Named<Declaration>, Vec<ParsedBlock>) =
+            "var" __ dtype:dtype() __ var:global_variable() _ "=" _ "extern" {
+                (Named::new(Some(var), Declaration::Variable {
+                    dtype,
+                    initializer: None,
+                    is_extern: true,
+                    is_static: false,
+                }), Vec::new())
+            }
+        /
+            "var" __ is_static:("static" __)? dtype:dtype() __ var:global_variable() _ "=" _ initializer:initializer() {
+                (Named::new(Some(var), Declaration::Variable {
                     dtype,
                     initializer,
-                })
+                    is_extern: false,
+                    is_static: is_static.is_some(),
+                }), Vec::new())
             }
         /
-            "fun" __ dtype:dtype() __ var:global_variable() _ "(" params:(dtype() ** (_ "," _)) _ ")" _ "{" _ fun_body:fun_body() _ "}" {
-                Named::new(Some(var), Declaration::Function {
+            "fun" __ is_static:("static" __)? dtype:dtype() __ var:global_variable() _ "(" params:(dtype() ** (_ "," _)) _ ")" _ "{" _ fun_body:fun_body() _ "}" {
+                let (definition, blocks) = fun_body;
+                (Named::new(Some(var), Declaration::Function {
                     signature: FunctionSignature::new(Dtype::function(dtype, params)),
-                    definition: Some(fun_body),
-                })
+                    definition: Some(definition),
+                    is_static: is_static.is_some(),
+                }), blocks)
             }
         /
-            "fun" __ dtype:dtype() __ var:global_variable() _ "(" params:(dtype() ** (_ "," _)) _ ")" {
-                Named::new(Some(var), Declaration::Function {
+            "fun" __ is_static:("static" __)? dtype:dtype() __ var:global_variable() _ "(" params:(dtype() ** (_ "," _)) _ ")" {
+                (Named::new(Some(var), Declaration::Function {
                     signature: FunctionSignature::new(Dtype::function(dtype, params)),
                     definition: None,
-                })
+                    is_static: is_static.is_some(),
+                }), Vec::new())
             }
 
+        pub rule standalone_dtype() -> Dtype = _ dtype:dtype() _ { dtype }
+
         rule dtype() -> Dtype =
-            inner:dtype_inner() is_consts:(is_const_of_pointer() ** _) {
+            inner:dtype_inner() qualifiers:(pointer_qualifiers() ** _) {
                 let mut inner = inner;
-                for is_const in is_consts {
-                    inner = Dtype::pointer(inner).set_const(is_const);
+                for (is_const, is_restrict) in qualifiers {
+                    inner = Dtype::pointer(inner)
+                        .set_const(is_const)
+                        .set_restrict(is_restrict);
                 }
                 inner
             }
@@ -111,26 +151,53 @@ peg::parser! {
         /
             "f" n:number() { Dtype::float(n) }
         /
-            "[" _ n:number() __ "x" __ inner:dtype() _ "]" {
-                Dtype::Array { inner: Box::new(inner), size: n }
+            "[" _ n:(n:number() { Some(n) } / "?" { None }) __ "x" __ inner:dtype()
+                align:(__ "align" __ a:number() { a })? _ "]" {
+                Dtype::Array { inner: Arc::new(inner), size: n, align }
             }
         /
             "[ret:" _ ret:dtype() __ "params:(" params:(dtype() ** (_ "," _)) _ ")]" {
-                Dtype::Function { ret: Box::new(ret), params }
+                Dtype::Function { ret: Arc::new(ret), params }
+            }
+        /
+            is_union:("struct" { false } / "union" { true }) __ name:tag()
+                fields:(":<" _ fields:(struct_field() ** (_ "," _)) _ ">" { fields })? {
+                match fields {
+                    Some(fields) => {
+                        let (fields, bit_widths) = fields.into_iter().unzip();
+                        Dtype::aggregate(is_union, name, Some(fields)).with_bit_widths(bit_widths)
+                    }
+                    None => Dtype::aggregate(is_union, name, None),
+                }
             }
         /
-            "struct" __ id:id() {
-                Dtype::structure(Some(id), None)
+            "enum" __ name:tag()
+                enumerators:(":<" _ e:(enumerator() ** (_ "," _)) _ ">" { e })? {
+                Dtype::enumeration(name, enumerators)
+            }
+        /
+            "typedef" __ name:id() is_restrict:(__ "restrict")? {
+                Dtype::typedef(name).set_restrict(is_restrict.is_some())
             }
         /
             "const" __ dtype:dtype_inner() { dtype.set_const(true) }
         /
             expected!("dtype")
 
-        rule is_const_of_pointer() -> bool =
-            _ "*" _ "const" { true }
+        rule tag() -> Option<String> =
+            "%anon" { None }
         /
-            _ "*" { false }
+            id:id() { Some(id) }
+
+        rule enumerator() -> (String, i128) =
+            name:id() _ "=" _ value:$("-"? ['0'..='9']+) {
+                (name, value.parse().unwrap())
+            }
+
+        rule pointer_qualifiers() -> (bool, bool) =
+            _ "*" is_const:(_ "const" { true })? is_restrict:(_ "restrict" { true })? {
+                (is_const.is_some(), is_restrict.is_some())
+            }
 
         rule id() -> String =
             n:$(['_' | 'a'..='z' | 'A'..='Z']['_' | 'a'..='z' | 'A'..='Z' | '0'..='9']*) {
@@ -154,13 +221,15 @@ peg::parser! {
                 todo!()
             }
 
-        rule fun_body() -> FunctionDefinition
-            = "init:" __ "bid:" _ bid_init:bid() _ "allocations:" _ allocations:(allocation() ** __) _ blocks:(block() ** __) {
-                FunctionDefinition {
+        rule fun_body() -> (FunctionDefinition, Vec<ParsedBlock>)
+            = "init:" __ "bid:" _ bid_init:bid() _ "allocations:" _ allocations:(allocation() ** __) _ blocks:(block() ** _) {
+                // The blocks are separated by optional whitespace, as a block missing its exit
+                // takes the whitespace before the next one.
+                (FunctionDefinition {
                     allocations: allocations.into_iter().map(|a| a.1).collect(),
-                    blocks: blocks.into_iter().collect(),
+                    blocks: BTreeMap::new(),
                     bid_init,
-                }
+                }, blocks)
             }
 
         rule allocation() -> (usize, Named<Dtype>)
@@ -168,22 +237,39 @@ peg::parser! {
                 (number, Named::new(Some(name), dtype))
             }
 
-        rule block() -> (BlockId, Block)
-            = "block" __ bid:bid() _ ":" _ phinodes:(phinode() ** __) _ instructions:(instruction() ** __) _ exit:exit() {
-                if !phinodes.iter().enumerate().all(|(i1, (bid2, i2, _))| bid == *bid2 && i1 == *i2) {
-                    panic!("Phinode id mismatches");
-                }
+        rule block() -> ParsedBlock
+            = offset:position!() "block" __ bid:bid() _ ":" _ phinodes:((offset:position!() phinode:phinode() { (offset, phinode) }) ** __) _ instructions:((offset:position!() instruction:instruction() { (offset, instruction) }) ** __) _ exit:(offset:position!() exit:exit() { (offset, exit) })? {
+                // The ids are checked against the positions with the label of the block.
+                let phinode_ids = phinodes
+                    .iter()
+                    .map(|(offset, (bid, aid, _))| (*offset, RegisterId::arg(*bid, *aid)))
+                    .collect();
+                let instruction_ids = instructions
+                    .iter()
+                    .map(|(offset, (bid, iid, _))| (*offset, RegisterId::temp(*bid, *iid)))
+                    .collect();
 
-                if !instructions.iter().enumerate().all(|(i1, (bid2, i2, _))| bid == *bid2 && i1 == *i2) {
-                    panic!("Instruction id mismatches");
+                let (instruction_offsets, instructions) = instructions
+                    .into_iter()
+                    .map(|(offset, (_, _, instr))| (offset, instr))
+                    .unzip();
+                let (exit_offset, exit) = match exit {
+                    Some((offset, exit)) => (Some(offset), exit),
+                    None => (None, BlockExit::Unreachable),
+                };
+                ParsedBlock {
+                    bid,
+                    block: Block {
+                        phinodes: phinodes.into_iter().map(|(_, (_, _, phi))| phi).collect(),
+                        instructions,
+                        exit,
+                    },
+                    offset,
+                    phinode_ids,
+                    instruction_ids,
+                    instruction_offsets,
+                    exit_offset,
                 }
-
-                (bid,
-                 Block {
-                     phinodes: phinodes.into_iter().map(|(_, _, phi)| phi).collect(),
-                     instructions: instructions.into_iter().map(|(_, _, instr)| instr).collect(),
-                     exit,
-                 })
             }
 
         rule number() -> usize
@@ -260,6 +346,23 @@ peg::parser! {
             "typecast" __ value:operand() __ "to" __ target_dtype:dtype() {
                 Instruction::TypeCast { value, target_dtype }
             }
+        /
+            "min" __ lhs:operand() __ rhs:operand() {
+                // The dtypes of the operands are checked by the verifier.
+                let dtype = lhs.dtype();
+                Instruction::Min { lhs, rhs, dtype }
+            }
+        /
+            "max" __ lhs:operand() __ rhs:operand() {
+                // The dtypes of the operands are checked by the verifier.
+                let dtype = lhs.dtype();
+                Instruction::Max { lhs, rhs, dtype }
+            }
+        /
+            "abs" __ operand:operand() {
+                let dtype = operand.dtype();
+                Instruction::Abs { operand, dtype }
+            }
         /
             op:unary_op() __ operand:operand() {
                 let dtype = operand.dtype();
@@ -389,8 +492,24 @@ peg::parser! {
             "unreachable" {
                 BlockExit::Unreachable
             }
+        /
+            // As written by `BlockExit`'s `Display`, e.g., after `Parse::fix` added it.
+            "<unreachable>" [' ' | '\t']* ";" [^'\n']* {
+                BlockExit::Unreachable
+            }
 
         rule constant() -> Constant =
+            "0x" bits:$(['0'..='9' | 'a'..='f' | 'A'..='F']+) {?
+                // The bit pattern of a float, whose width is given by the number of digits.
+                let width = bits.len() * 4;
+                match u64::from_str_radix(bits, 16) {
+                    Ok(bits) if width == 32 || width == 64 => {
+                        Ok(Constant::float_from_bits(bits, Dtype::float(width)))
+                    }
+                    _ => Err("8 or 16 hexadecimal digits"),
+                }
+            }
+        /
             f:float_number() {
                 Constant::float(f, Dtype::float(64)) // TODO: the right dtype
             }
@@ -410,6 +529,10 @@ peg::parser! {
             "undef" {
                 Constant::undef(Dtype::unit()) // TODO
             }
+        /
+            "zeroinit" {
+                Constant::zero_init(Dtype::unit())
+            }
         /
             "unit" {
                 Constant::unit()
@@ -440,25 +563,34 @@ peg::parser! {
                 RegisterId::temp(bid, id)
             }
 
+        rule comment() -> &'input str = "/*" text:$((!"*/" [_])*) "*/" {
+            text.trim()
+        }
+
         rule operand() -> Operand =
-            constant:constant() ":" dtype:dtype() {
+            constant:constant() ":" dtype:dtype() comment:(_ comment:comment() { comment })? {
                 let constant = match (&constant, &dtype) {
+                    // An integer is annotated with the identifier in its comment, if any.
                     (Constant::Int { value, .. }, Dtype::Int { width, is_signed, .. }) => {
+                        let origin = comment.filter(|comment| is_identifier(comment));
                         Constant::Int {
                             value: *value,
                             width: *width,
                             is_signed: *is_signed,
+                            origin: ConstantOrigin(origin.map(str::to_string)),
                         }
                     }
+                    // A decimal is rounded to `dtype`, whereas a bit pattern of `dtype` is kept.
                     (Constant::Float { value, .. }, Dtype::Float { width, .. }) => {
-                        Constant::Float {
-                            value: *value,
-                            width: *width,
-                        }
+                        let bits = float_value_to_bits(value.into_inner(), *width);
+                        Constant::float_from_bits(bits, dtype.clone())
                     }
                     (Constant::Undef { .. }, _) => {
                         Constant::undef(dtype.clone())
                     }
+                    (Constant::ZeroInit { .. }, _) => {
+                        Constant::zero_init(dtype.clone())
+                    }
                     (Constant::GlobalVariable { name, .. }, _) => {
                         let dtype_of_inner = dtype.get_pointer_inner().expect("`dtype` must be pointer type");
                         Constant::global_variable(name.clone(), dtype_of_inner.clone())
@@ -484,7 +616,7 @@ peg::parser! {
             }
 
         rule initializer() -> Option<ast::Initializer> =
-            "default" {
+            "default" !['_' | 'a'..='z' | 'A'..='Z' | '0'..='9'] {
                 None
             }
         /
@@ -541,6 +673,14 @@ peg::parser! {
 
                 ast::Expression::UnaryOperator(unary_expr)
             }
+        /
+            // A function or a variable whose address initializes a pointer, e.g., `f` of
+            // `int (*fp)(void) = f;`
+            name:$(['_' | 'a'..='z' | 'A'..='Z']['_' | 'a'..='z' | 'A'..='Z' | '0'..='9']*) {
+                let identifier = ast::Identifier { name: name.to_string() };
+                let identifier = Box::new(span::Node::new(identifier, span::Span::none()));
+                ast::Expression::Identifier(identifier)
+            }
         /
             "<ast_expression>" {
                 todo!()
@@ -661,23 +801,394 @@ peg::parser! {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Error)]
 pub enum Error {
+    #[error("{0}")]
     Io(std::io::Error),
+    #[error("{0}")]
     Parse(peg::error::ParseError<peg::str::LineCol>),
+    #[error("failed to resolve the structs")]
     Resolve,
+    /// The IR is well-formed text, but malformed IR, e.g., with undefined registers.
+    #[error("{}", .0.iter().format("\n"))]
+    Invalid(Vec<IrDiagnostic>),
+}
+
+/// A mistake in the IR text found after parsing it.
+#[derive(Debug, PartialEq, Eq, Error)]
+#[error("{line}: {mistake}")]
+pub struct IrDiagnostic {
+    /// The line of the mistake, from 1
+    pub line: usize,
+    pub mistake: IrMistake,
 }
 
+/// A mistake in IR that is well-formed text.
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum IrMistake {
+    /// The label of a block already labels another block. It is renumbered by [`Parse::fix`].
+    #[error("{func_name}: duplicate block label `{bid}`")]
+    DuplicateBlock { func_name: String, bid: BlockId },
+    /// A block has no exit. It gets `unreachable` by [`Parse::fix`].
+    #[error("{func_name}:{bid}: missing block exit")]
+    MissingExit { func_name: String, bid: BlockId },
+    #[error("{func_name}:{bid}: undefined register `{rid}`")]
+    UndefinedRegister {
+        func_name: String,
+        bid: BlockId,
+        rid: RegisterId,
+    },
+    #[error("{func_name}:{bid}: jump to undefined block `{target}`")]
+    UndefinedBlock {
+        func_name: String,
+        bid: BlockId,
+        target: BlockId,
+    },
+    #[error(
+        "{func_name}:{bid}: jump to `{target}` passes {found} argument(s) to its {expected} \
+         phinode(s)"
+    )]
+    JumpArgCount {
+        func_name: String,
+        bid: BlockId,
+        target: BlockId,
+        expected: usize,
+        found: usize,
+    },
+    #[error(
+        "{func_name}:{bid}: argument {index} of the jump to `{target}` is `{found}`, but its \
+         phinode is `{expected}`"
+    )]
+    JumpArgDtype {
+        func_name: String,
+        bid: BlockId,
+        target: BlockId,
+        index: usize,
+        expected: Dtype,
+        found: Dtype,
+    },
+    /// A phinode is not numbered after its block and its position in it, e.g., `%b1:p0` second
+    /// in `b0`.
+    #[error("{func_name}:{bid}: phinode `{found}` should be `{expected}`")]
+    PhinodeId {
+        func_name: String,
+        bid: BlockId,
+        expected: RegisterId,
+        found: RegisterId,
+    },
+    /// An instruction is not numbered after its block and its position in it.
+    #[error("{func_name}:{bid}: instruction `{found}` should be `{expected}`")]
+    InstructionId {
+        func_name: String,
+        bid: BlockId,
+        expected: RegisterId,
+        found: RegisterId,
+    },
+    #[error("{0}")]
+    Verifier(VerifierError),
+}
+
+/// Parses IR text, refusing malformed IR: duplicate block labels, blocks without exits, phinodes
+/// and instructions numbered out of their positions, undefined registers and blocks, jump
+/// arguments not matching the phinodes of their targets, and what [`verify`] rejects. All the mistakes are reported at once with their lines.
 #[derive(Default, Clone, Copy, Debug)]
-pub struct Parse {}
+pub struct Parse {
+    fix: bool,
+    skip_verifier: bool,
+}
+
+impl Parse {
+    /// Returns the parser fixing the mechanical mistakes instead of refusing them, e.g., when
+    /// mutating IR files in tests: a duplicate block label is renumbered to an unused block id,
+    /// including the registers of the block, and a block without exit gets `unreachable`.
+    pub fn fix(self) -> Self {
+        Self { fix: true, ..self }
+    }
+
+    /// Returns the parser not running [`verify`], e.g., to interpret IR it rejects.
+    pub fn skip_verifier(self) -> Self {
+        Self {
+            skip_verifier: true,
+            ..self
+        }
+    }
+
+    /// Parses `source` like [`Translate::translate`], also returning the mistakes fixed if
+    /// [`Parse::fix`].
+    pub fn translate_with_fixes<P: AsRef<Path>>(
+        &self,
+        source: &P,
+    ) -> Result<(TranslationUnit, Vec<IrDiagnostic>), Error> {
+        let ir = fs::read_to_string(source).map_err(Error::Io)?;
+        let (mut unit, functions) = ir_parse::translation_unit(&ir).map_err(Error::Parse)?;
+        let line = |offset: usize| ir[..offset].matches('\n').count() + 1;
+
+        let mut fixes = Vec::new();
+        let mut mistakes = Vec::new();
+        // The line of each instruction and exit, by function and block, for the verifier.
+        let mut lines = HashMap::new();
+        for (func_name, blocks) in functions {
+            let definition = unit
+                .decls
+                .get_mut(&func_name)
+                .and_then(|decl| decl.get_function_mut())
+                .and_then(|(_, definition)| definition.as_mut())
+                .expect("`func_name` must be defined");
+            let mut next_bid = blocks
+                .iter()
+                .map(|block| block.bid.0 + 1)
+                .max()
+                .unwrap_or(0);
+
+            let mut block_lines = BTreeMap::new();
+            for mut parsed in blocks {
+                check_ids(&func_name, &parsed, &line, &mut mistakes);
+                let mut report = |mistake: IrMistake, offset: usize| {
+                    let diagnostic = IrDiagnostic {
+                        line: line(offset),
+                        mistake,
+                    };
+                    if self.fix {
+                        fixes.push(diagnostic);
+                    } else {
+                        mistakes.push(diagnostic);
+                    }
+                };
+
+                if definition.blocks.contains_key(&parsed.bid) {
+                    report(
+                        IrMistake::DuplicateBlock {
+                            func_name: func_name.clone(),
+                            bid: parsed.bid,
+                        },
+                        parsed.offset,
+                    );
+                    let bid = BlockId(next_bid);
+                    next_bid += 1;
+                    renumber_block(&mut parsed.block, parsed.bid, bid);
+                    parsed.bid = bid;
+                }
+                if parsed.exit_offset.is_none() {
+                    report(
+                        IrMistake::MissingExit {
+                            func_name: func_name.clone(),
+                            bid: parsed.bid,
+                        },
+                        parsed.offset,
+                    );
+                }
+
+                let exit_line = line(parsed.exit_offset.unwrap_or(parsed.offset));
+                let instruction_lines = parsed
+                    .instruction_offsets
+                    .iter()
+                    .map(|offset| line(*offset))
+                    .collect::<Vec<_>>();
+                let _unused = block_lines.insert(parsed.bid, (instruction_lines, exit_line));
+                let _unused = definition.blocks.insert(parsed.bid, parsed.block);
+            }
+
+            check_definition(&func_name, definition, &block_lines, &mut mistakes);
+            let _unused = lines.insert(func_name, block_lines);
+        }
+
+        if mistakes.is_empty() && !self.skip_verifier {
+            mistakes.extend(verify_all(&unit).into_iter().map(|error| {
+                let (func_name, bid, iid) = error.location();
+                let (instruction_lines, exit_line) = &lines[func_name][&bid];
+                IrDiagnostic {
+                    line: instruction_lines.get(iid).copied().unwrap_or(*exit_line),
+                    mistake: IrMistake::Verifier(error),
+                }
+            }));
+        }
+
+        if !mistakes.is_empty() {
+            mistakes.sort_by_key(|diagnostic| diagnostic.line);
+            return Err(Error::Invalid(mistakes));
+        }
+        Ok((unit, fixes))
+    }
+}
 
 impl<P: AsRef<Path>> Translate<P> for Parse {
     type Target = TranslationUnit;
     type Error = Error;
 
     fn translate(&mut self, source: &P) -> Result<Self::Target, Self::Error> {
-        let ir = fs::read_to_string(source).map_err(Error::Io)?;
-        ir_parse::translation_unit(&ir).map_err(Error::Parse)
+        self.translate_with_fixes(source).map(|(unit, _)| unit)
+    }
+}
+
+impl FromStr for Dtype {
+    type Err = Error;
+
+    /// Parses a type as [`Dtype`] displays it, e.g., `const i32*` or `struct s:<x:i32, y:i32>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ir_parse::standalone_dtype(s).map_err(Error::Parse)
+    }
+}
+
+/// A block as written, before its label is checked to be unique.
+#[derive(Debug)]
+struct ParsedBlock {
+    bid: BlockId,
+    /// The block, whose exit is `unreachable` if it is missing
+    block: Block,
+    /// The offsets of the label, of the instructions and of the exit, if any, in the text
+    offset: usize,
+    /// The ids of the phinodes and of the instructions as written, at their offsets
+    phinode_ids: Vec<(usize, RegisterId)>,
+    instruction_ids: Vec<(usize, RegisterId)>,
+    instruction_offsets: Vec<usize>,
+    exit_offset: Option<usize>,
+}
+
+/// Renames the registers of `block` defined by the block `from` to those of the block `to`.
+fn renumber_block(block: &mut Block, from: BlockId, to: BlockId) {
+    let renumber = |operand: &mut Operand| {
+        if let Operand::Register { rid, .. } = operand {
+            match rid {
+                RegisterId::Arg { bid, .. } | RegisterId::Temp { bid, .. } if *bid == from => {
+                    *bid = to
+                }
+                _ => {}
+            }
+        }
+    };
+    for instruction in &mut block.instructions {
+        instruction.walk_operands_mut(renumber);
+    }
+    block.exit.walk_operands_mut(renumber);
+}
+
+/// Adds the phinodes and the instructions of `parsed` not numbered after their positions in it to
+/// `mistakes`, at their lines by `line`.
+fn check_ids<F: Fn(usize) -> usize>(
+    func_name: &str,
+    parsed: &ParsedBlock,
+    line: &F,
+    mistakes: &mut Vec<IrDiagnostic>,
+) {
+    let phinodes = parsed
+        .phinode_ids
+        .iter()
+        .enumerate()
+        .map(|(aid, (offset, found))| (*offset, RegisterId::arg(parsed.bid, aid), *found, true));
+    let instructions = parsed
+        .instruction_ids
+        .iter()
+        .enumerate()
+        .map(|(iid, (offset, found))| (*offset, RegisterId::temp(parsed.bid, iid), *found, false));
+    for (offset, expected, found, is_phinode) in phinodes.chain(instructions) {
+        if found == expected {
+            continue;
+        }
+
+        let (func_name, bid) = (func_name.to_string(), parsed.bid);
+        let mistake = if is_phinode {
+            IrMistake::PhinodeId {
+                func_name,
+                bid,
+                expected,
+                found,
+            }
+        } else {
+            IrMistake::InstructionId {
+                func_name,
+                bid,
+                expected,
+                found,
+            }
+        };
+        mistakes.push(IrDiagnostic {
+            line: line(offset),
+            mistake,
+        });
+    }
+}
+
+/// Adds the undefined registers and blocks, and the jump arguments not matching the phinodes of
+/// their targets, of `definition` to `mistakes`, at the lines of `lines`.
+fn check_definition(
+    func_name: &str,
+    definition: &FunctionDefinition,
+    lines: &BTreeMap<BlockId, (Vec<usize>, usize)>,
+    mistakes: &mut Vec<IrDiagnostic>,
+) {
+    let is_defined = |rid: &RegisterId| match rid {
+        RegisterId::Local { aid } => *aid < definition.allocations.len(),
+        RegisterId::Arg { bid, aid } => definition
+            .blocks
+            .get(bid)
+            .map_or(false, |block| *aid < block.phinodes.len()),
+        RegisterId::Temp { bid, iid } => definition
+            .blocks
+            .get(bid)
+            .map_or(false, |block| *iid < block.instructions.len()),
+    };
+
+    for (bid, block) in &definition.blocks {
+        let (instruction_lines, exit_line) = &lines[bid];
+        let mut check_operand = |operand: &Operand, line: usize| {
+            if let Operand::Register { rid, .. } = operand {
+                if !is_defined(rid) {
+                    mistakes.push(IrDiagnostic {
+                        line,
+                        mistake: IrMistake::UndefinedRegister {
+                            func_name: func_name.to_string(),
+                            bid: *bid,
+                            rid: *rid,
+                        },
+                    });
+                }
+            }
+        };
+        for (instruction, line) in block.instructions.iter().zip(instruction_lines) {
+            instruction.walk_operands(|operand| check_operand(operand, *line));
+        }
+        block
+            .exit
+            .walk_operands(|operand| check_operand(operand, *exit_line));
+
+        for arg in block.exit.jump_args() {
+            let mut report = |mistake: IrMistake| {
+                mistakes.push(IrDiagnostic {
+                    line: *exit_line,
+                    mistake,
+                })
+            };
+            let target = some_or!(definition.blocks.get(&arg.bid), {
+                report(IrMistake::UndefinedBlock {
+                    func_name: func_name.to_string(),
+                    bid: *bid,
+                    target: arg.bid,
+                });
+                continue;
+            });
+            if arg.args.len() != target.phinodes.len() {
+                report(IrMistake::JumpArgCount {
+                    func_name: func_name.to_string(),
+                    bid: *bid,
+                    target: arg.bid,
+                    expected: target.phinodes.len(),
+                    found: arg.args.len(),
+                });
+                continue;
+            }
+            for (index, (operand, phinode)) in arg.args.iter().zip(&target.phinodes).enumerate() {
+                if operand.dtype() != *phinode.deref() {
+                    report(IrMistake::JumpArgDtype {
+                        func_name: func_name.to_string(),
+                        bid: *bid,
+                        target: arg.bid,
+                        index,
+                        expected: phinode.deref().clone(),
+                        found: operand.dtype(),
+                    });
+                }
+            }
+        }
     }
 }
 
@@ -696,9 +1207,14 @@ fn resolve_structs(struct_type: Dtype, structs: &mut HashMap<String, Option<Dtyp
         .expect("`struct_type` must have fields");
 
     for field in fields {
-        if field.deref().get_struct_name().is_some() {
+        // The layout depends on those of the structs contained, also in arrays, but not on those
+        // pointed to, e.g., by `next:struct node*` in `struct node`.
+        let mut field = field.deref();
+        while let Dtype::Array { inner, .. } = field {
+            field = inner.deref();
+        }
+        if field.get_struct_name().is_some() {
             let name = field
-                .deref()
                 .get_struct_name()
                 .expect("`field` must be struct type")
                 .as_ref()
@@ -722,3 +1238,12 @@ fn resolve_structs(struct_type: Dtype, structs: &mut HashMap<String, Option<Dtyp
     let result = structs.insert(name, Some(filled_struct));
     assert!(result.is_some());
 }
+
+/// Returns `true` if `text` is a C identifier, e.g., the origin of a constant in its comment.
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map_or(false, |c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -0,0 +1,294 @@
+//! The standard input of the interpreted programs.
+
+use core::{fmt, str};
+use std::io::Read;
+
+use crate::some_or;
+
+/// Functions of the C library reading the standard input, which the interpreter evaluates when a
+/// module calls them without defining them.
+///
+/// Unlike the builtins, the calls to them are linked with the C library in the generated code.
+/// Since the IR has no variadic functions, `scanf` is declared with the parameters of each module's
+/// own calls, e.g., `fun i32 @scanf (i8*, i32*, i32*)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StdinFunction {
+    /// `int getchar(void)`
+    Getchar,
+    /// `int scanf(const char *, ...)`, supporting the conversions `%d`, `%u`, `%c`, `%s` and `%f`
+    /// with an optional width, and `%%`
+    Scanf,
+}
+
+impl StdinFunction {
+    const ALL: [Self; 2] = [Self::Getchar, Self::Scanf];
+
+    /// Returns the function named `name`, if any.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|function| function.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Getchar => "getchar",
+            Self::Scanf => "scanf",
+        }
+    }
+}
+
+impl fmt::Display for StdinFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A directive of a `scanf` format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Directive {
+    /// Whitespace, which skips any whitespace of the input
+    Whitespace,
+    /// A byte to be matched, including the `%` of `%%`
+    Literal(u8),
+    /// A conversion, reading at most `width` bytes if given
+    Conversion {
+        conversion: Conversion,
+        width: Option<usize>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Conversion {
+    /// `%d` and `%u`, which store into an integer
+    Int,
+    /// `%c`, which stores `width` bytes, 1 by default, without skipping whitespace
+    Char,
+    /// `%s`, which stores the bytes up to whitespace followed by a null terminator
+    String,
+    /// `%f`, which stores into a floating point number
+    Float,
+}
+
+/// The value read by a conversion.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Scanned {
+    Int(i128),
+    Bytes(Vec<u8>),
+    Float(f64),
+}
+
+/// Why a conversion fails, which ends `scanf`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Failure {
+    /// The input ends, so that `scanf` returns `EOF` if nothing has been converted.
+    Input,
+    /// The input does not match the conversion.
+    Matching,
+}
+
+/// Parses a `scanf` format, or returns the reason why it is not supported.
+pub(crate) fn parse_format(format: &[u8]) -> Result<Vec<Directive>, String> {
+    let mut directives = Vec::new();
+    let mut bytes = format.iter().copied().peekable();
+
+    while let Some(byte) = bytes.next() {
+        if byte.is_ascii_whitespace() {
+            while bytes.next_if(u8::is_ascii_whitespace).is_some() {}
+            directives.push(Directive::Whitespace);
+            continue;
+        }
+        if byte != b'%' {
+            directives.push(Directive::Literal(byte));
+            continue;
+        }
+
+        let mut width = None;
+        while let Some(digit) = bytes.next_if(u8::is_ascii_digit) {
+            width = Some(width.unwrap_or(0) * 10 + usize::from(digit - b'0'));
+        }
+        let conversion = match bytes.next() {
+            Some(b'%') if width.is_none() => {
+                directives.push(Directive::Literal(b'%'));
+                continue;
+            }
+            Some(b'd' | b'u') => Conversion::Int,
+            Some(b'c') => Conversion::Char,
+            Some(b's') => Conversion::String,
+            Some(b'f') => Conversion::Float,
+            Some(other) => return Err(format!("unsupported conversion `%{}`", other as char)),
+            None => return Err("incomplete conversion at the end of the format".to_string()),
+        };
+        if width == Some(0) {
+            return Err("conversion with a zero width".to_string());
+        }
+        directives.push(Directive::Conversion { conversion, width });
+    }
+
+    Ok(directives)
+}
+
+/// The standard input of an interpreted program, which is empty unless bound, with a byte of
+/// lookahead like `ungetc`.
+#[derive(Default)]
+pub(crate) struct Stdin {
+    input: Option<Box<dyn Read>>,
+    peeked: Option<Option<u8>>,
+}
+
+impl Stdin {
+    pub(crate) fn new(input: Box<dyn Read>) -> Self {
+        Self {
+            input: Some(input),
+            peeked: None,
+        }
+    }
+
+    pub(crate) fn is_bound(&self) -> bool {
+        self.input.is_some()
+    }
+
+    /// Returns the next byte without consuming it, or `None` at the end of the input. An error of
+    /// the input ends it, like the end-of-file indicator of a stream.
+    pub(crate) fn peek(&mut self) -> Option<u8> {
+        if let Some(byte) = self.peeked {
+            return byte;
+        }
+
+        let mut buffer = [0];
+        let byte = match self.input.as_mut().map(|input| input.read(&mut buffer)) {
+            Some(Ok(1)) => Some(buffer[0]),
+            _ => None,
+        };
+        self.peeked = Some(byte);
+        byte
+    }
+
+    /// Consumes the next byte, as `getchar`.
+    pub(crate) fn next_byte(&mut self) -> Option<u8> {
+        let byte = self.peek();
+        // The end of the input is sticky.
+        if byte.is_some() {
+            self.peeked = None;
+        }
+        byte
+    }
+
+    /// Consumes the next byte if it satisfies `predicate`.
+    fn next_if(&mut self, predicate: impl FnOnce(&u8) -> bool) -> Option<u8> {
+        let byte = self.peek()?;
+        if predicate(&byte) {
+            self.next_byte()
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn skip_whitespace(&mut self) {
+        while self.next_if(u8::is_ascii_whitespace).is_some() {}
+    }
+
+    /// Reads a conversion from the input. As in C, the bytes read before a matching failure are
+    /// consumed, except the one that does not match.
+    pub(crate) fn scan(
+        &mut self,
+        conversion: Conversion,
+        width: Option<usize>,
+    ) -> Result<Scanned, Failure> {
+        if conversion != Conversion::Char {
+            self.skip_whitespace();
+        }
+        if self.peek().is_none() {
+            return Err(Failure::Input);
+        }
+
+        let remaining = match conversion {
+            Conversion::Char => width.unwrap_or(1),
+            _ => width.unwrap_or(usize::MAX),
+        };
+        let mut field = Field {
+            stdin: self,
+            bytes: Vec::new(),
+            remaining,
+        };
+        match conversion {
+            Conversion::Int => {
+                let _unused = field.next_if(|byte| matches!(byte, b'+' | b'-'));
+                field.digits();
+
+                // An overflowing value is undefined, and wraps around here.
+                let is_negative = field.bytes.first() == Some(&b'-');
+                let digits = field
+                    .bytes
+                    .iter()
+                    .skip_while(|byte| matches!(byte, b'+' | b'-'))
+                    .collect::<Vec<_>>();
+                if digits.is_empty() {
+                    return Err(Failure::Matching);
+                }
+                let value = digits.into_iter().fold(0i128, |value, digit| {
+                    value
+                        .wrapping_mul(10)
+                        .wrapping_add(i128::from(digit - b'0'))
+                });
+                Ok(Scanned::Int(if is_negative {
+                    value.wrapping_neg()
+                } else {
+                    value
+                }))
+            }
+            Conversion::Char => {
+                while field.next_if(|_| true) {}
+                if field.bytes.len() < width.unwrap_or(1) {
+                    return Err(Failure::Input);
+                }
+                Ok(Scanned::Bytes(field.bytes))
+            }
+            Conversion::String => {
+                while field.next_if(|byte| !byte.is_ascii_whitespace()) {}
+                Ok(Scanned::Bytes(field.bytes))
+            }
+            Conversion::Float => {
+                let _unused = field.next_if(|byte| matches!(byte, b'+' | b'-'));
+                field.digits();
+                if field.next_if(|byte| *byte == b'.') {
+                    field.digits();
+                }
+                if field.next_if(|byte| matches!(byte, b'e' | b'E')) {
+                    let _unused = field.next_if(|byte| matches!(byte, b'+' | b'-'));
+                    field.digits();
+                }
+
+                str::from_utf8(&field.bytes)
+                    .ok()
+                    .and_then(|text| text.parse().ok())
+                    .map(Scanned::Float)
+                    .ok_or(Failure::Matching)
+            }
+        }
+    }
+}
+
+/// The bytes of the input a conversion reads, which are at most its width.
+struct Field<'a> {
+    stdin: &'a mut Stdin,
+    bytes: Vec<u8>,
+    remaining: usize,
+}
+
+impl Field<'_> {
+    /// Reads the next byte if it satisfies `predicate` and the width allows it.
+    fn next_if(&mut self, predicate: impl FnOnce(&u8) -> bool) -> bool {
+        if self.remaining == 0 {
+            return false;
+        }
+        let byte = some_or!(self.stdin.next_if(predicate), return false);
+        self.remaining -= 1;
+        self.bytes.push(byte);
+        true
+    }
+
+    fn digits(&mut self) {
+        while self.next_if(u8::is_ascii_digit) {}
+    }
+}
@@ -0,0 +1,892 @@
+//! The values of the IR, as the interpreter computes them, and the arithmetic on them shared with
+//! the constant folding of the optimizer and the IR generator.
+
+use core::iter;
+use ordered_float::OrderedFloat;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::ir::*;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    Undef {
+        dtype: Dtype,
+    },
+    Unit,
+    Int {
+        value: u128,
+        width: usize,
+        is_signed: bool,
+    },
+    Float {
+        /// `value` may be `f32`, but it is possible to consider it as `f64`.
+        ///
+        /// * Casting from an f32 to an f64 is perfect and lossless (f32 -> f64)
+        /// * Casting from an f64 to an f32 will produce the closest possible value (f64 -> f32)
+        ///
+        /// For more details: <https://doc.rust-lang.org/stable/reference/expressions/operator-expr.html#type-cast-expressions>
+        value: OrderedFloat<f64>,
+        width: usize,
+    },
+    /// A pointer `offset` bytes into the memory block `bid`, or the null pointer if `bid` is
+    /// `None`.
+    ///
+    /// Each function has a memory block of its own, of no bytes, so that a pointer to a function
+    /// points to its block at offset 0. Thus two function pointers are equal if and only if they
+    /// point to the same function, and they are never equal to the null pointer or to a pointer to
+    /// an object.
+    Pointer {
+        bid: Option<usize>,
+        offset: isize,
+        dtype: Dtype,
+    },
+    Array {
+        inner_dtype: Dtype,
+        values: Vec<Value>,
+    },
+    Struct {
+        name: String,
+        fields: Vec<Named<Value>>,
+    },
+}
+
+impl TryFrom<Constant> for Value {
+    type Error = ();
+
+    fn try_from(constant: Constant) -> Result<Self, Self::Error> {
+        let value = match constant {
+            Constant::Undef { dtype } => Self::Undef { dtype },
+            Constant::Unit => Self::Unit,
+            Constant::Int {
+                value,
+                width,
+                is_signed,
+                ..
+            } => Self::Int {
+                value,
+                width,
+                is_signed,
+            },
+            Constant::Float { value, width } => Self::Float { value, width },
+            _ => panic!(),
+        };
+
+        Ok(value)
+    }
+}
+
+macro_rules! value_from_int {
+    ($($int:ty => $is_signed:expr),*) => {
+        $(
+            impl From<$int> for Value {
+                /// Marshals the integer as a C integer of the same width and signedness.
+                fn from(value: $int) -> Self {
+                    // The signed integers are sign-extended, as the interpreter represents them.
+                    Self::int(value as u128, <$int>::BITS as usize, $is_signed)
+                }
+            }
+        )*
+    };
+}
+
+value_from_int!(
+    i8 => true,
+    i16 => true,
+    i32 => true,
+    i64 => true,
+    u8 => false,
+    u16 => false,
+    u32 => false,
+    u64 => false
+);
+
+impl From<bool> for Value {
+    /// Marshals the boolean as a `_Bool`.
+    fn from(value: bool) -> Self {
+        Self::int(value.into(), 1, false)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Self::float(value.into(), 32)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::float(value, 64)
+    }
+}
+
+impl HasDtype for Value {
+    fn dtype(&self) -> Dtype {
+        match self {
+            Self::Undef { dtype } => dtype.clone(),
+            Self::Unit => Dtype::unit(),
+            Self::Int {
+                width, is_signed, ..
+            } => Dtype::int(*width).set_signed(*is_signed),
+            Self::Float { width, .. } => Dtype::float(*width),
+            Self::Pointer { dtype, .. } => Dtype::pointer(dtype.clone()),
+            Self::Array {
+                inner_dtype,
+                values,
+            } => Dtype::array(inner_dtype.clone(), values.len()),
+            Self::Struct { name, fields } => {
+                let fields = fields
+                    .iter()
+                    .map(|f| Named::new(f.name().cloned(), f.deref().dtype()))
+                    .collect();
+                Dtype::structure(Some(name.clone()), Some(fields))
+            }
+        }
+    }
+}
+
+impl Value {
+    #[inline]
+    pub(crate) fn undef(dtype: Dtype) -> Self {
+        Self::Undef { dtype }
+    }
+
+    #[inline]
+    pub(crate) fn unit() -> Self {
+        Self::Unit
+    }
+
+    #[inline]
+    pub(crate) fn int(value: u128, width: usize, is_signed: bool) -> Self {
+        Self::Int {
+            value,
+            width,
+            is_signed,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn float(value: f64, width: usize) -> Self {
+        Self::Float {
+            value: value.into(),
+            width,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn pointer(bid: Option<usize>, offset: isize, dtype: Dtype) -> Self {
+        Self::Pointer { bid, offset, dtype }
+    }
+
+    #[inline]
+    pub(crate) fn array(inner_dtype: Dtype, values: Vec<Self>) -> Self {
+        Self::Array {
+            inner_dtype,
+            values,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn structure(name: String, fields: Vec<Named<Value>>) -> Self {
+        Self::Struct { name, fields }
+    }
+
+    #[inline]
+    pub fn get_int(&self) -> Option<(u128, usize, bool)> {
+        if let Value::Int {
+            value,
+            width,
+            is_signed,
+        } = self
+        {
+            Some((*value, *width, *is_signed))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the exit status of a process whose `main` returns the value, i.e., its low 8 bits,
+    /// or `None` if it is not an integer.
+    ///
+    /// The interpreter returns the full value, e.g., `-1`, `256` or `300`, which IR-level grading
+    /// compares. A native or an emulated run only reports the exit status, e.g., `255`, `0` or
+    /// `44`, so the differential tests compare the exit statuses instead.
+    pub fn exit_status(&self) -> Option<u8> {
+        let (value, _, _) = self.get_int()?;
+        Some((value & 0xff) as u8)
+    }
+
+    #[inline]
+    pub(crate) fn nullptr(dtype: Dtype) -> Self {
+        Self::Pointer {
+            bid: None,
+            offset: 0,
+            dtype,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn default_from_dtype(
+        dtype: &Dtype,
+        structs: &HashMap<String, Option<Dtype>>,
+    ) -> Result<Self, ()> {
+        let value = match dtype {
+            Dtype::Unit { .. } => Self::unit(),
+            Dtype::Int {
+                width, is_signed, ..
+            } => Self::int(u128::default(), *width, *is_signed),
+            Dtype::Float { width, .. } => Self::float(f64::default(), *width),
+            Dtype::Pointer { inner, .. } => Self::nullptr(inner.deref().clone()),
+            Dtype::Array { inner, size, .. } => {
+                let size = size.ok_or(())?;
+                let values = iter::repeat(Self::default_from_dtype(inner, structs))
+                    .take(size)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Self::array(inner.deref().clone(), values)
+            }
+            Dtype::Struct { name, .. } => {
+                let name = name.as_ref().expect("struct should have its name");
+                let struct_type = structs
+                    .get(name)
+                    .expect("struct type matched with `name` must exist")
+                    .as_ref()
+                    .expect("`struct_type` must have its definition");
+                let fields = struct_type
+                    .get_struct_fields()
+                    .expect("`struct_type` must be struct type")
+                    .as_ref()
+                    .expect("`fields` must be `Some`");
+
+                let fields = fields
+                    .iter()
+                    .map(|f| {
+                        let value = Self::default_from_dtype(f.deref(), structs)?;
+                        Ok(Named::new(f.name().cloned(), value))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Self::structure(name.clone(), fields)
+            }
+            Dtype::Function { .. } => panic!("function type does not have a default value"),
+            Dtype::Typedef { .. } => panic!("typedef should be replaced by real dtype"),
+            Dtype::Enum { .. } => panic!("enum should be replaced by int"),
+        };
+
+        Ok(value)
+    }
+
+    #[allow(clippy::result_unit_err)]
+    pub fn try_from_initializer(
+        initializer: &ast::Initializer,
+        dtype: &Dtype,
+        structs: &HashMap<String, Option<Dtype>>,
+    ) -> Result<Self, ()> {
+        Self::try_from_initializer_with(initializer, dtype, structs, &|_| None)
+    }
+
+    /// Converts `initializer` like [`Value::try_from_initializer`], where the memory block of a
+    /// global referred to by the initializer, e.g., `f` of `int (*fp)(void) = f;`, is given by
+    /// `global`.
+    #[allow(clippy::result_unit_err)]
+    pub fn try_from_initializer_with(
+        initializer: &ast::Initializer,
+        dtype: &Dtype,
+        structs: &HashMap<String, Option<Dtype>>,
+        global: &dyn Fn(&str) -> Option<usize>,
+    ) -> Result<Self, ()> {
+        match initializer {
+            ast::Initializer::Expression(expr) => match dtype {
+                Dtype::Pointer { inner, .. } => {
+                    if let ast::Expression::Identifier(identifier) = &expr.node {
+                        let bid = global(&identifier.node.name).ok_or(())?;
+                        return Ok(Self::pointer(Some(bid), 0, inner.deref().clone()));
+                    }
+
+                    let constant = Constant::try_from(&expr.node)?;
+                    let value = Self::try_from(constant)?;
+
+                    calculator::calculate_typecast(value, dtype.clone())
+                }
+                Dtype::Int { .. } | Dtype::Float { .. } => {
+                    let constant = Constant::try_from(&expr.node)?;
+                    let value = Self::try_from(constant)?;
+
+                    calculator::calculate_typecast(value, dtype.clone())
+                }
+                _ => Err(()),
+            },
+            ast::Initializer::List(items) => match dtype {
+                Dtype::Array { inner, size, .. } => {
+                    let inner_dtype = inner.deref().clone();
+                    let num_of_items = items.len();
+                    let values = (0..size.ok_or(())?)
+                        .map(|i| {
+                            if i < num_of_items {
+                                Self::try_from_initializer_with(
+                                    &items[i].node.initializer.node,
+                                    &inner_dtype,
+                                    structs,
+                                    global,
+                                )
+                            } else {
+                                Self::default_from_dtype(&inner_dtype, structs)
+                            }
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    Ok(Self::array(inner_dtype, values))
+                }
+                Dtype::Struct { name, .. } => {
+                    let name = name.as_ref().expect("struct should have its name");
+                    let struct_type = structs
+                        .get(name)
+                        .expect("struct type matched with `name` must exist")
+                        .as_ref()
+                        .expect("`struct_type` must have its definition");
+                    let fields = struct_type
+                        .get_struct_fields()
+                        .expect("`struct_type` must be struct type")
+                        .as_ref()
+                        .expect("`fields` must be `Some`");
+
+                    // The unnamed bit-fields, which only pad the struct, take no item.
+                    let mut items = items.iter();
+                    let fields = fields
+                        .iter()
+                        .enumerate()
+                        .map(|(i, f)| {
+                            let item = if f.name().is_none()
+                                && struct_type.get_struct_bit_field(i).is_some()
+                            {
+                                None
+                            } else {
+                                items.next()
+                            };
+                            let value = if let Some(item) = item {
+                                Self::try_from_initializer_with(
+                                    &item.node.initializer.node,
+                                    f.deref(),
+                                    structs,
+                                    global,
+                                )?
+                            } else {
+                                Self::default_from_dtype(f.deref(), structs)?
+                            };
+
+                            Ok(Named::new(f.name().cloned(), value))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    Ok(Self::structure(name.clone(), fields))
+                }
+                _ => Err(()),
+            },
+        }
+    }
+}
+
+// The interpreter computes all the operations, the constant folding of the optimizer only some.
+#[cfg_attr(not(feature = "interp"), allow(dead_code))]
+pub(crate) mod calculator {
+    use super::Value;
+    use crate::ir::*;
+    use crate::some_or;
+    use lang_c::ast;
+    use std::cmp::Ordering;
+
+    /// Returns `true` if `lhs op rhs` overflows the range of a signed integer.
+    ///
+    /// Only addition, subtraction, multiplication, division and remainder (`INT_MIN / -1` and
+    /// `INT_MIN % -1`, whose quotient is not representable) and left shift (of a negative value, or
+    /// into the sign bit) can overflow. Returns `false` for unsigned or non-integer operands.
+    pub fn has_signed_overflow(op: &ast::BinaryOperator, lhs: &Value, rhs: &Value) -> bool {
+        let (lhs, width, is_signed) = some_or!(lhs.get_int(), return false);
+        let (rhs, _, _) = some_or!(rhs.get_int(), return false);
+        if !is_signed {
+            return false;
+        }
+
+        let (lhs, rhs) = (lhs as i128, rhs as i128);
+        let result = match op {
+            ast::BinaryOperator::Plus => lhs.checked_add(rhs),
+            ast::BinaryOperator::Minus => lhs.checked_sub(rhs),
+            ast::BinaryOperator::Multiply => lhs.checked_mul(rhs),
+            ast::BinaryOperator::Divide | ast::BinaryOperator::Modulo if rhs != 0 => {
+                lhs.checked_div(rhs)
+            }
+            // A negative value may not be shifted left, and the amounts out of range are
+            // checked by `is_shift_out_of_range` instead.
+            ast::BinaryOperator::ShiftLeft if ShiftKind::is_in_range(rhs as u128, width) => {
+                if lhs < 0 {
+                    return true;
+                }
+                lhs.checked_mul(1 << rhs)
+            }
+            _ => return false,
+        };
+        let result = some_or!(result, return true);
+
+        let min = -(1i128 << (width - 1));
+        let max = (1i128 << (width - 1)) - 1;
+        result < min || result > max
+    }
+
+    /// Returns `true` if `value` is the minimum of a signed integer, whose negation and absolute
+    /// value overflow.
+    pub fn is_signed_min(value: &Value) -> bool {
+        let (value, width, is_signed) = some_or!(value.get_int(), return false);
+        is_signed && value as i128 == -(1i128 << (width - 1))
+    }
+
+    /// Returns the minimum or, if `is_max`, the maximum of `lhs` and `rhs`.
+    pub fn calculate_min_max(is_max: bool, lhs: Value, rhs: Value) -> Result<Value, ()> {
+        if let (Value::Undef { dtype }, _) | (_, Value::Undef { dtype }) = (&lhs, &rhs) {
+            return Ok(Value::undef(dtype.clone()));
+        }
+
+        let (lhs_value, _, is_signed) = lhs.get_int().ok_or(())?;
+        let (rhs_value, _, _) = rhs.get_int().ok_or(())?;
+        let ordering = if is_signed {
+            (lhs_value as i128).cmp(&(rhs_value as i128))
+        } else {
+            lhs_value.cmp(&rhs_value)
+        };
+
+        Ok(if (ordering == Ordering::Less) == is_max {
+            rhs
+        } else {
+            lhs
+        })
+    }
+
+    /// Returns the absolute value of `operand`, wrapping around for the minimum integer.
+    pub fn calculate_abs(operand: Value) -> Result<Value, ()> {
+        match operand {
+            Value::Undef { dtype } => Ok(Value::undef(dtype)),
+            Value::Int {
+                value,
+                width,
+                is_signed: true,
+            } => {
+                let result = sign_extension((value as i128).wrapping_abs() as u128, width as u128);
+                Ok(Value::int(result, width, true))
+            }
+            _ => Err(()),
+        }
+    }
+
+    /// Returns `true` if `lhs op rhs` is a shift by a negative amount or by the width of `lhs` or
+    /// more, whose amount is masked in the IR (see [`ShiftKind`]) but undefined in C.
+    pub fn is_shift_out_of_range(op: &ast::BinaryOperator, lhs: &Value, rhs: &Value) -> bool {
+        if ShiftKind::new(op, false).is_none() {
+            return false;
+        }
+        let (_, width, _) = some_or!(lhs.get_int(), return false);
+        let (amount, _, _) = some_or!(rhs.get_int(), return false);
+        !ShiftKind::is_in_range(amount, width)
+    }
+
+    /// Returns `true` if `lhs op rhs` is an integer division or remainder by zero.
+    ///
+    /// RISC-V does not trap on it: the division yields all ones, i.e., `-1` if signed, and the
+    /// remainder yields `lhs`, which [`calculate_binary_operator_expression`] mirrors.
+    pub fn is_division_by_zero(op: &ast::BinaryOperator, rhs: &Value) -> bool {
+        matches!(
+            (op, rhs),
+            (
+                ast::BinaryOperator::Divide | ast::BinaryOperator::Modulo,
+                Value::Int { value: 0, .. },
+            )
+        )
+    }
+
+    /// Returns `false` if `lhs op rhs` is a division by zero, a signed division or remainder whose
+    /// quotient overflows (`INT_MIN / -1` and `INT_MIN % -1`), or a shift by a negative amount or
+    /// by the width of `lhs` or more.
+    ///
+    /// Such expressions are never folded at compile time, whatever the overflow policy.
+    pub fn is_well_defined(op: &ast::BinaryOperator, lhs: &Value, rhs: &Value) -> bool {
+        match (op, rhs) {
+            (ast::BinaryOperator::Divide | ast::BinaryOperator::Modulo, Value::Int { .. }) => {
+                !is_division_by_zero(op, rhs) && !has_signed_overflow(op, lhs, rhs)
+            }
+            (ast::BinaryOperator::Divide, Value::Float { value, .. }) => value.into_inner() != 0.0,
+            _ => !is_shift_out_of_range(op, lhs, rhs),
+        }
+    }
+
+    fn calculate_integer_binary_operator_expression(
+        op: &ast::BinaryOperator,
+        lhs: u128,
+        rhs: u128,
+        width: usize,
+        is_signed: bool,
+    ) -> Result<Value, ()> {
+        let result = match op {
+            // TODO: explain why plus & minus do not need to consider `is_signed'
+            ast::BinaryOperator::Plus => (lhs as i128 + rhs as i128) as u128,
+            ast::BinaryOperator::Minus => (lhs as i128 - rhs as i128) as u128,
+            ast::BinaryOperator::Multiply => {
+                if is_signed {
+                    (lhs as i128 * rhs as i128) as u128
+                } else {
+                    lhs * rhs
+                }
+            }
+            // As on RISC-V, a division by zero yields all ones and a remainder by zero yields
+            // `lhs`. `INT_MIN / -1` wraps around to `INT_MIN` and `INT_MIN % -1` is 0, as `i128`
+            // does not overflow, and both truncate toward zero, e.g., `-7 / 2 == -3` and
+            // `-7 % 2 == -1`.
+            ast::BinaryOperator::Divide if rhs == 0 => u128::MAX,
+            ast::BinaryOperator::Modulo if rhs == 0 => lhs,
+            ast::BinaryOperator::Divide => {
+                if is_signed {
+                    (lhs as i128 / rhs as i128) as u128
+                } else {
+                    lhs / rhs
+                }
+            }
+            ast::BinaryOperator::Modulo => {
+                if is_signed {
+                    (lhs as i128 % rhs as i128) as u128
+                } else {
+                    lhs % rhs
+                }
+            }
+            ast::BinaryOperator::ShiftLeft | ast::BinaryOperator::ShiftRight => {
+                let kind = ShiftKind::new(op, is_signed).expect("`op` must be a shift");
+                kind.apply(lhs, rhs, width)
+            }
+            ast::BinaryOperator::BitwiseAnd => lhs & rhs,
+            ast::BinaryOperator::BitwiseXor => lhs ^ rhs,
+            ast::BinaryOperator::BitwiseOr => lhs | rhs,
+            ast::BinaryOperator::Equals => {
+                let result = (lhs == rhs).into();
+                return Ok(Value::int(result, 1, false));
+            }
+            ast::BinaryOperator::NotEquals => {
+                let result = (lhs != rhs).into();
+                return Ok(Value::int(result, 1, false));
+            }
+            ast::BinaryOperator::Less => {
+                let condition = if is_signed {
+                    (lhs as i128) < (rhs as i128)
+                } else {
+                    lhs < rhs
+                };
+                let result = condition.into();
+                return Ok(Value::int(result, 1, false));
+            }
+            ast::BinaryOperator::Greater => {
+                let condition = if is_signed {
+                    (lhs as i128) > (rhs as i128)
+                } else {
+                    lhs > rhs
+                };
+                let result = condition.into();
+                return Ok(Value::int(result, 1, false));
+            }
+            ast::BinaryOperator::LessOrEqual => {
+                let condition = if is_signed {
+                    (lhs as i128) <= (rhs as i128)
+                } else {
+                    lhs <= rhs
+                };
+                let result = condition.into();
+                return Ok(Value::int(result, 1, false));
+            }
+            ast::BinaryOperator::GreaterOrEqual => {
+                let condition = if is_signed {
+                    (lhs as i128) >= (rhs as i128)
+                } else {
+                    lhs >= rhs
+                };
+                let result = condition.into();
+                return Ok(Value::int(result, 1, false));
+            }
+            _ => todo!(
+                "calculate_binary_operator_expression: not supported operator {:?}",
+                op
+            ),
+        };
+
+        let result = if is_signed {
+            sign_extension(result, width as u128)
+        } else {
+            trim_unnecessary_bits(result, width as u128)
+        };
+
+        Ok(Value::int(result, width, is_signed))
+    }
+
+    fn calculate_float_binary_operator_expression(
+        op: &ast::BinaryOperator,
+        lhs: OrderedFloat<f64>,
+        rhs: OrderedFloat<f64>,
+        width: usize,
+    ) -> Result<Value, ()> {
+        let (lhs, rhs) = if width == Dtype::SIZE_OF_FLOAT * Dtype::BITS_OF_BYTE {
+            (
+                lhs.into_inner() as f32 as f64,
+                rhs.into_inner() as f32 as f64,
+            )
+        } else {
+            (lhs.into_inner(), rhs.into_inner())
+        };
+
+        let result = match op {
+            ast::BinaryOperator::Plus => lhs + rhs,
+            ast::BinaryOperator::Minus => lhs - rhs,
+            ast::BinaryOperator::Multiply => lhs * rhs,
+            ast::BinaryOperator::Divide => {
+                assert!(rhs != 0.0);
+                lhs / rhs
+            }
+            ast::BinaryOperator::Equals => {
+                let order = lhs
+                    .partial_cmp(&rhs)
+                    .expect("`lhs` and `rhs` must be not NAN");
+                let result = (Ordering::Equal == order).into();
+                return Ok(Value::int(result, 1, false));
+            }
+            ast::BinaryOperator::NotEquals => {
+                let order = lhs
+                    .partial_cmp(&rhs)
+                    .expect("`lhs` and `rhs` must be not NAN");
+                let result = (Ordering::Equal != order).into();
+                return Ok(Value::int(result, 1, false));
+            }
+            ast::BinaryOperator::Less => {
+                let result = lhs.lt(&rhs).into();
+                return Ok(Value::int(result, 1, false));
+            }
+            ast::BinaryOperator::Greater => {
+                let result = lhs.gt(&rhs).into();
+                return Ok(Value::int(result, 1, false));
+            }
+            ast::BinaryOperator::LessOrEqual => {
+                let result = lhs.le(&rhs).into();
+                return Ok(Value::int(result, 1, false));
+            }
+            ast::BinaryOperator::GreaterOrEqual => {
+                let result = lhs.ge(&rhs).into();
+                return Ok(Value::int(result, 1, false));
+            }
+            _ => todo!(
+                "calculate_binary_operator_expression: not supported case for \
+                 {:?} {:?} {:?}",
+                op,
+                lhs,
+                rhs
+            ),
+        };
+
+        Ok(Value::float(result, width))
+    }
+
+    // TODO: change to template function in the future
+    pub fn calculate_binary_operator_expression(
+        op: &ast::BinaryOperator,
+        lhs: Value,
+        rhs: Value,
+    ) -> Result<Value, ()> {
+        match (lhs, rhs) {
+            (Value::Undef { dtype }, _) => Ok(Value::undef(dtype)),
+            (_, Value::Undef { dtype }) => Ok(Value::undef(dtype)),
+            (
+                Value::Int {
+                    value: lhs,
+                    width: lhs_w,
+                    is_signed: lhs_s,
+                },
+                Value::Int {
+                    value: rhs,
+                    width: rhs_w,
+                    is_signed: rhs_s,
+                },
+            ) => {
+                assert_eq!(lhs_w, rhs_w);
+                assert_eq!(lhs_s, rhs_s);
+
+                calculate_integer_binary_operator_expression(op, lhs, rhs, lhs_w, lhs_s)
+            }
+            (
+                Value::Float {
+                    value: lhs,
+                    width: lhs_w,
+                },
+                Value::Float {
+                    value: rhs,
+                    width: rhs_w,
+                },
+            ) => {
+                assert_eq!(lhs_w, rhs_w);
+
+                calculate_float_binary_operator_expression(op, lhs, rhs, lhs_w)
+            }
+            (
+                Value::Pointer { bid, offset, .. },
+                Value::Pointer {
+                    bid: other_bid,
+                    offset: other_offset,
+                    ..
+                },
+            ) => match op {
+                ast::BinaryOperator::Equals => {
+                    let result = (bid == other_bid && offset == other_offset).into();
+                    Ok(Value::int(result, 1, false))
+                }
+                ast::BinaryOperator::NotEquals => {
+                    let result = (!(bid == other_bid && offset == other_offset)).into();
+                    Ok(Value::int(result, 1, false))
+                }
+                _ => todo!(
+                    "calculate_binary_operator_expression: not supported case for \
+                     {:?} between pointer and integer value",
+                    op,
+                ),
+            },
+            (lhs, rhs) => todo!(
+                "calculate_binary_operator_expression: not supported case for {:?} {:?} {:?}",
+                op,
+                lhs,
+                rhs
+            ),
+        }
+    }
+
+    pub fn calculate_unary_operator_expression(
+        op: &ast::UnaryOperator,
+        operand: Value,
+    ) -> Result<Value, ()> {
+        match operand {
+            Value::Undef { dtype } => Ok(Value::undef(dtype)),
+            Value::Int {
+                value,
+                width,
+                is_signed,
+            } => {
+                match op {
+                    ast::UnaryOperator::Plus => Ok(Value::int(value, width, is_signed)),
+                    ast::UnaryOperator::Minus => {
+                        let result = if is_signed {
+                            sign_extension((-(value as i128)) as u128, width as u128)
+                        } else {
+                            let value = (-(value as i128)) as u128;
+                            trim_unnecessary_bits(value, width as u128)
+                        };
+                        Ok(Value::int(result, width, is_signed))
+                    }
+                    ast::UnaryOperator::Negate => {
+                        // Check if it is boolean
+                        assert!(width == 1);
+                        let result = (value == 0).into();
+                        Ok(Value::int(result, width, is_signed))
+                    }
+                    _ => todo!(
+                        "calculate_unary_operator_expression: not supported case for {:?} {:?}",
+                        op,
+                        operand,
+                    ),
+                }
+            }
+            Value::Float { value, width } => match op {
+                ast::UnaryOperator::Plus => Ok(Value::float(value.into_inner(), width)),
+                ast::UnaryOperator::Minus => Ok(Value::float(-value.into_inner(), width)),
+                _ => todo!(
+                    "calculate_unary_operator_expression: not supported case for {:?} {:?}",
+                    op,
+                    operand,
+                ),
+            },
+            _ => todo!(
+                "calculate_unary_operator_expression: not supported case for {:?} {:?}",
+                op,
+                operand,
+            ),
+        }
+    }
+
+    pub fn calculate_typecast(value: Value, dtype: Dtype) -> Result<Value, ()> {
+        if value.dtype().is_same_type(&dtype) {
+            return Ok(value);
+        }
+
+        match (value, dtype) {
+            (Value::Undef { .. }, dtype) => Ok(Value::undef(dtype)),
+            (
+                Value::Int { value, width, .. },
+                Dtype::Int {
+                    width: target_width,
+                    is_signed: target_signed,
+                    ..
+                },
+            ) => {
+                let result = if target_signed {
+                    if width >= target_width {
+                        // TODO: explain the logic in the future
+                        let value = trim_unnecessary_bits(value, target_width as u128);
+                        sign_extension(value, target_width as u128)
+                    } else {
+                        value
+                    }
+                } else {
+                    trim_unnecessary_bits(value, target_width as u128)
+                };
+
+                Ok(Value::int(result, target_width, target_signed))
+            }
+            (
+                Value::Int {
+                    value, is_signed, ..
+                },
+                Dtype::Float { width, .. },
+            ) => {
+                let size = (width - 1) / Dtype::BITS_OF_BYTE + 1;
+                let casted_value = match (is_signed, size) {
+                    (true, Dtype::SIZE_OF_FLOAT) => value as i128 as f32 as f64,
+                    (true, Dtype::SIZE_OF_DOUBLE) => value as i128 as f64,
+                    (false, Dtype::SIZE_OF_FLOAT) => value as f32 as f64,
+                    (false, Dtype::SIZE_OF_DOUBLE) => value as f64,
+                    _ => panic!(
+                        "calculate_typecast: not supported case \
+                            typecast int to float when `width` is {width}"
+                    ),
+                };
+                Ok(Value::float(casted_value, width))
+            }
+            (Value::Int { value, .. }, Dtype::Pointer { inner, .. }) => {
+                if value == 0 {
+                    Ok(Value::pointer(None, 0, Arc::unwrap_or_clone(inner)))
+                } else {
+                    panic!(
+                        "calculate_typecast: not support case \
+                            typecast int to pointer when `value` is {value}"
+                    )
+                }
+            }
+            (
+                Value::Float { value, .. },
+                Dtype::Int {
+                    width, is_signed, ..
+                },
+            ) => {
+                let casted_value = if is_signed {
+                    value.into_inner() as i128 as u128
+                } else {
+                    value.into_inner() as u128
+                };
+                Ok(Value::int(casted_value, width, is_signed))
+            }
+            (Value::Float { value, .. }, Dtype::Float { width, .. }) => {
+                Ok(Value::float(value.into_inner(), width))
+            }
+            // E.g., a function pointer cast to another function pointer type, which still points
+            // to the same function.
+            (Value::Pointer { bid, offset, .. }, Dtype::Pointer { inner, .. }) => {
+                Ok(Value::pointer(bid, offset, Arc::unwrap_or_clone(inner)))
+            }
+            (value, dtype) => todo!("calculate_typecast ({:?}) {:?}", value, dtype),
+        }
+    }
+}
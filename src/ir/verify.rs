@@ -0,0 +1,243 @@
+//! Verify the well-formedness of IR.
+
+use core::ops::Deref;
+use thiserror::Error;
+
+use crate::ir::*;
+use crate::some_or;
+
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum VerifierError {
+    #[error("{func_name}:{bid}:{iid} / store to the constant global variable `@{name}`")]
+    StoreToConstant {
+        func_name: String,
+        bid: BlockId,
+        iid: usize,
+        name: String,
+    },
+    #[error("{func_name}:{bid}:{iid} / `{instruction}` requires {expected} operands of its dtype")]
+    InvalidOperands {
+        func_name: String,
+        bid: BlockId,
+        iid: usize,
+        instruction: String,
+        expected: String,
+    },
+    #[error(
+        "{func_name}:{bid}:{iid} / store of `{value}` into `{pointee}` needs an explicit `typecast`"
+    )]
+    StoreWidth {
+        func_name: String,
+        bid: BlockId,
+        iid: usize,
+        value: Dtype,
+        pointee: Dtype,
+    },
+    #[error(
+        "{func_name}:{bid}:{iid} / call through `{callee}` does not match its arguments or its \
+         return type"
+    )]
+    CallSignature {
+        func_name: String,
+        bid: BlockId,
+        iid: usize,
+        callee: Dtype,
+    },
+}
+
+impl VerifierError {
+    /// Returns the function, the block and the index of the instruction violating the rule.
+    pub fn location(&self) -> (&str, BlockId, usize) {
+        match self {
+            Self::StoreToConstant {
+                func_name,
+                bid,
+                iid,
+                ..
+            }
+            | Self::InvalidOperands {
+                func_name,
+                bid,
+                iid,
+                ..
+            }
+            | Self::StoreWidth {
+                func_name,
+                bid,
+                iid,
+                ..
+            }
+            | Self::CallSignature {
+                func_name,
+                bid,
+                iid,
+                ..
+            } => (func_name, *bid, *iid),
+        }
+    }
+}
+
+/// Verifies `unit`, returning the first violation found (see [`verify_all`]).
+///
+/// Rules:
+///
+/// - No store targets a constant global variable (see [`Declaration::is_constant`]), either
+///   directly or through a `getelementptr` chain.
+/// - The operands of `min` and `max` are integers, and the operand of `abs` is a signed integer,
+///   of the dtype of the instruction.
+/// - An integer or floating point value is stored through a pointer to its own dtype, so that a
+///   store writes exactly the bytes of the pointee, e.g., a value is narrowed to `i8` by a
+///   `typecast` before it is stored into a `char`.
+/// - A call through a function pointer, i.e., not to a function by its name, passes arguments of
+///   the parameter types of the pointee, which is a function type, and returns its return type.
+pub fn verify(unit: &TranslationUnit) -> Result<(), VerifierError> {
+    verify_all(unit).into_iter().next().map_or(Ok(()), Err)
+}
+
+/// Verifies `unit` like [`verify`], returning all the violations found, in order.
+pub fn verify_all(unit: &TranslationUnit) -> Vec<VerifierError> {
+    let mut errors = Vec::new();
+    let is_constant = |name: &str| some_or!(unit.decls.get(name), return false).is_constant();
+
+    for (func_name, decl) in &unit.decls {
+        let (_, definition) = some_or!(decl.get_function(), continue);
+        let definition = some_or!(definition, continue);
+        let global_pointers = definition.global_pointers();
+
+        for (bid, block) in &definition.blocks {
+            for (iid, instruction) in block.instructions.iter().enumerate() {
+                if let Some(expected) = invalid_operands(instruction) {
+                    errors.push(VerifierError::InvalidOperands {
+                        func_name: func_name.clone(),
+                        bid: *bid,
+                        iid,
+                        instruction: instruction.deref().to_string(),
+                        expected: expected.to_string(),
+                    });
+                    continue;
+                }
+
+                if let Some(callee) = mismatched_indirect_call(instruction) {
+                    errors.push(VerifierError::CallSignature {
+                        func_name: func_name.clone(),
+                        bid: *bid,
+                        iid,
+                        callee,
+                    });
+                    continue;
+                }
+
+                let (ptr, value) = if let Instruction::Store { ptr, value } = instruction.deref() {
+                    (ptr, value)
+                } else {
+                    continue;
+                };
+
+                if let Some(pointee) = mismatched_store(ptr, value) {
+                    errors.push(VerifierError::StoreWidth {
+                        func_name: func_name.clone(),
+                        bid: *bid,
+                        iid,
+                        value: value.dtype(),
+                        pointee,
+                    });
+                }
+
+                let name = match ptr {
+                    Operand::Constant(Constant::GlobalVariable { name, .. }) => name,
+                    Operand::Register { rid, .. } => {
+                        &some_or!(global_pointers.get(rid), continue).0
+                    }
+                    _ => continue,
+                };
+
+                if is_constant(name) {
+                    errors.push(VerifierError::StoreToConstant {
+                        func_name: func_name.clone(),
+                        bid: *bid,
+                        iid,
+                        name: name.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+/// Returns the operands `instruction` expects, if its operands are not such.
+fn invalid_operands(instruction: &Instruction) -> Option<&'static str> {
+    let (operands, dtype, is_abs) = match instruction {
+        Instruction::Min { lhs, rhs, dtype } | Instruction::Max { lhs, rhs, dtype } => {
+            (vec![lhs, rhs], dtype, false)
+        }
+        Instruction::Abs { operand, dtype } => (vec![operand], dtype, true),
+        _ => return None,
+    };
+
+    let is_valid = dtype.get_int_width().is_some()
+        && (!is_abs || dtype.is_int_signed())
+        && operands.iter().all(|operand| operand.dtype() == *dtype);
+    if is_valid {
+        None
+    } else if is_abs {
+        Some("signed integer")
+    } else {
+        Some("integer")
+    }
+}
+
+/// Returns the dtype of the callee if `instruction` calls through a function pointer whose
+/// pointee does not match the arguments and the return type of the call, other than by `const`.
+fn mismatched_indirect_call(instruction: &Instruction) -> Option<Dtype> {
+    let (callee, args, return_type) = if let Instruction::Call {
+        callee,
+        args,
+        return_type,
+    } = instruction
+    {
+        (callee, args, return_type)
+    } else {
+        return None;
+    };
+    if callee
+        .get_constant()
+        .and_then(Constant::get_global_variable_name)
+        .is_some()
+    {
+        return None;
+    }
+
+    let dtype = callee.dtype();
+    let (ret, params) = some_or!(
+        dtype
+            .get_pointer_inner()
+            .and_then(Dtype::get_function_inner),
+        return Some(dtype)
+    );
+    let same = |lhs: &Dtype, rhs: &Dtype| {
+        lhs.clone().strip_outer_const() == rhs.clone().strip_outer_const()
+    };
+    let matches = same(ret, return_type)
+        && args.len() == params.len()
+        && args
+            .iter()
+            .zip(params)
+            .all(|(arg, param)| same(&arg.dtype(), param));
+    (!matches).then_some(dtype)
+}
+
+/// Returns the pointee of `ptr` if the store of `value` through `ptr` would write other bytes than
+/// those of the pointee, i.e., if either is an integer or a floating point number and their dtypes
+/// differ other than by `const`.
+pub(crate) fn mismatched_store(ptr: &Operand, value: &Operand) -> Option<Dtype> {
+    let pointee = ptr.dtype().get_pointer_inner()?.clone();
+    let value = value.dtype();
+    let is_scalar = |dtype: &Dtype| matches!(dtype, Dtype::Int { .. } | Dtype::Float { .. });
+    if !(is_scalar(&pointee) || is_scalar(&value)) {
+        return None;
+    }
+
+    (pointee.clone().set_const(false) != value.set_const(false)).then_some(pointee)
+}
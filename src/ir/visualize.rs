@@ -29,6 +29,7 @@ impl Translate<TranslationUnit> for Visualizer {
                 Declaration::Function {
                     signature,
                     definition,
+                    ..
                 } => {
                     let definition = some_or!(definition, continue);
                     let subgraph = self.translate_function(name, signature, definition)?;
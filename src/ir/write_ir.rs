@@ -1,39 +1,71 @@
 use crate::ir::*;
 
-use std::io::{Result, Write};
+use std::io::{Error, ErrorKind, Result, Write};
 
 use crate::write_base::*;
 use crate::*;
 
 impl WriteLine for TranslationUnit {
     fn write_line(&self, indent: usize, write: &mut dyn Write) -> Result<()> {
-        // TODO: consider KECC IR parser in the future.
-        for (name, struct_type) in &self.structs {
+        // Types are written recursively, so those nested too deeply are rejected up front.
+        let limit = max_nesting_depth();
+        let allocations = self
+            .decls
+            .values()
+            .filter_map(|decl| decl.get_function()?.1.as_ref())
+            .flat_map(|definition| definition.allocations.iter().map(|alloc| alloc.deref()));
+        let too_complex = self
+            .structs
+            .values()
+            .flatten()
+            .chain(allocations)
+            .any(|dtype| dtype.depth() > limit)
+            || self.decls.values().any(|decl| decl.dtype().depth() > limit);
+        if too_complex {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                DtypeError::TooComplex { limit }.to_string(),
+            ));
+        }
+
+        // The struct table is written once, ordered by the tags so that the dumps are stable, and
+        // the structs are referred to by their tags elsewhere, which the parser resolves first.
+        for (name, struct_type) in self
+            .structs
+            .iter()
+            .sorted_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs))
+        {
+            let keyword = c_keyword(&self.structs, name);
             let definition = if let Some(struct_type) = struct_type {
-                let fields = struct_type
-                    .get_struct_fields()
-                    .expect("`struct_type` must be struct type")
-                    .as_ref()
-                    .expect("`fields` must be `Some`");
-
-                let fields = fields.iter().format_with(", ", |field, f| {
-                    f(&format_args!(
-                        "{}:{}",
-                        if let Some(name) = field.name() {
-                            name
-                        } else {
-                            "%anon"
-                        },
-                        field.deref()
-                    ))
-                });
-
-                format!("{{ {fields} }}")
+                if let Some(Some(fields)) = struct_type.get_struct_fields() {
+                    let fields = fields
+                        .iter()
+                        .enumerate()
+                        .format_with(", ", |(i, field), f| {
+                            f(&format_args!(
+                                "{}:{}{}",
+                                if let Some(name) = field.name() {
+                                    name
+                                } else {
+                                    "%anon"
+                                },
+                                field.deref(),
+                                struct_type.get_struct_bit_field(i).map_or(
+                                    String::new(),
+                                    |bit_field| format!(":{}", bit_field.width)
+                                )
+                            ))
+                        });
+
+                    format!("{{ {fields} }}")
+                } else {
+                    unsupported("struct definition without fields", None)
+                }
             } else {
                 "opaque".to_string()
             };
 
-            writeln!(write, "struct {name} : {definition}")?;
+            writeln!(write, "{keyword} {name} : {definition}")?;
         }
 
         for (name, decl) in &self.decls {
@@ -57,13 +89,21 @@ impl WriteLine for (&String, &Declaration) {
         let decl = self.1;
 
         match decl {
-            Declaration::Variable { dtype, initializer } => {
+            Declaration::Variable {
+                dtype,
+                initializer,
+                is_extern,
+                is_static,
+            } => {
                 writeln!(
                     write,
-                    "var {} @{} = {}",
+                    "var {}{} @{} = {}",
+                    if *is_static { "static " } else { "" },
                     dtype,
                     name,
-                    if let Some(init) = initializer {
+                    if *is_extern {
+                        "extern".to_string()
+                    } else if let Some(init) = initializer {
                         init.write_string()
                     } else {
                         "default".to_string()
@@ -73,12 +113,18 @@ impl WriteLine for (&String, &Declaration) {
             Declaration::Function {
                 signature,
                 definition,
+                is_static,
             } => {
                 let params = signature.params.iter().format(", ");
+                let storage = if *is_static { "static " } else { "" };
 
                 if let Some(definition) = definition.as_ref() {
                     // print function definition
-                    writeln!(write, "fun {} @{} ({}) {{", signature.ret, name, params)?;
+                    writeln!(
+                        write,
+                        "fun {storage}{} @{} ({}) {{",
+                        signature.ret, name, params
+                    )?;
                     // print meta data for function
                     writeln!(
                         write,
@@ -108,7 +154,11 @@ impl WriteLine for (&String, &Declaration) {
                     writeln!(write, "}}")?;
                 } else {
                     // print declaration line only
-                    writeln!(write, "fun {} @{} ({})", signature.ret, name, params)?;
+                    writeln!(
+                        write,
+                        "fun {storage}{} @{} ({})",
+                        signature.ret, name, params
+                    )?;
                     writeln!(write)?;
                 }
             }
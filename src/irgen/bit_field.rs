@@ -0,0 +1,204 @@
+//! Accesses to bit-fields, e.g., `f.mode` of `struct { unsigned ready : 1; unsigned mode : 3; } f`.
+//!
+//! A bit-field is accessed through the whole storage unit holding it, at the offset of the field:
+//! a load shifts the field to the most significant bits of the unit and back, arithmetically if
+//! the field is signed, so that `int x : 3` holding 7 reads as -1, and a store loads the unit,
+//! clears the bits of the field, ors in the new ones and stores the unit back. The bits of the
+//! adjacent fields are thus kept, and a volatile bit-field is accessed with the width of its unit.
+
+use lang_c::ast::BinaryOperator;
+
+use crate::ir::{self, HasDtype};
+
+use super::{Context, IrgenErrorMessage, IrgenFunc};
+
+impl IrgenFunc<'_> {
+    /// Returns the bit-field declared by the member `field` of the struct `ptr` points to, if it
+    /// is a bit-field.
+    pub(super) fn member_bit_field(&self, ptr: &ir::Operand, field: &str) -> Option<ir::BitField> {
+        ptr.dtype()
+            .get_pointer_inner()?
+            .get_struct_field_bit_field(field, self.structs)
+    }
+
+    /// Loads the bit-field `bit_field` from its storage unit `ptr` points to, i.e., the pointer to
+    /// the member at its offset in the struct.
+    pub(super) fn load_bit_field(
+        &self,
+        ptr: ir::Operand,
+        bit_field: ir::BitField,
+        context: &mut Context,
+    ) -> Result<ir::Operand, IrgenErrorMessage> {
+        let dtype = field_dtype(&ptr);
+        let ptr = self.unit_pointer(ptr, context)?;
+        let unit = context.insert_instruction(ir::Instruction::Load { ptr })?;
+        extract(unit, bit_field, dtype, context)
+    }
+
+    /// Stores `value` into the bit-field `bit_field` of its storage unit `ptr` points to, and
+    /// returns the value of the assignment, i.e., `value` converted to the width of the field.
+    pub(super) fn store_bit_field(
+        &self,
+        ptr: ir::Operand,
+        bit_field: ir::BitField,
+        value: ir::Operand,
+        context: &mut Context,
+    ) -> Result<ir::Operand, IrgenErrorMessage> {
+        let dtype = field_dtype(&ptr);
+        let ptr = self.unit_pointer(ptr, context)?;
+        let unit_dtype = ptr
+            .dtype()
+            .get_pointer_inner()
+            .expect("`ptr` must be a pointer")
+            .clone();
+        let unit = context.insert_instruction(ir::Instruction::Load { ptr: ptr.clone() })?;
+
+        let mask = ((1u128 << bit_field.width) - 1) << bit_field.bit_offset;
+        let cleared = binary(
+            BinaryOperator::BitwiseAnd,
+            unit,
+            constant(!mask & unit_mask(&unit_dtype), &unit_dtype),
+            context,
+        )?;
+        let bits = cast(value, &unit_dtype, context)?;
+        let bits = shift(
+            BinaryOperator::ShiftLeft,
+            bits,
+            bit_field.bit_offset,
+            context,
+        )?;
+        let bits = binary(
+            BinaryOperator::BitwiseAnd,
+            bits,
+            constant(mask, &unit_dtype),
+            context,
+        )?;
+        let unit = binary(BinaryOperator::BitwiseOr, cleared, bits, context)?;
+        let _unused = context.insert_instruction(ir::Instruction::Store {
+            ptr,
+            value: unit.clone(),
+        })?;
+
+        extract(unit, bit_field, dtype, context)
+    }
+
+    /// Returns the pointer to the storage unit of the bit-field `ptr` points to, as an unsigned
+    /// integer as large as the type of the field.
+    fn unit_pointer(
+        &self,
+        ptr: ir::Operand,
+        context: &mut Context,
+    ) -> Result<ir::Operand, IrgenErrorMessage> {
+        let dtype = field_dtype(&ptr);
+        let (size, _) = dtype
+            .size_align_of(self.structs)
+            .map_err(|dtype_error| IrgenErrorMessage::InvalidDtype { dtype_error })?;
+        let unit_dtype = ir::Dtype::int(size * ir::Dtype::BITS_OF_BYTE).set_signed(false);
+        if dtype == unit_dtype {
+            return Ok(ptr);
+        }
+
+        context.insert_instruction(ir::Instruction::TypeCast {
+            value: ptr,
+            target_dtype: ir::Dtype::pointer(unit_dtype),
+        })
+    }
+}
+
+/// Returns the type of the bit-field `ptr` points to.
+fn field_dtype(ptr: &ir::Operand) -> ir::Dtype {
+    ptr.dtype()
+        .get_pointer_inner()
+        .expect("`ptr` must be a pointer")
+        .clone()
+        .strip_outer_const()
+}
+
+/// Returns the value of the bit-field `bit_field` of type `dtype` in the storage unit `unit`, an
+/// unsigned integer.
+fn extract(
+    unit: ir::Operand,
+    bit_field: ir::BitField,
+    dtype: ir::Dtype,
+    context: &mut Context,
+) -> Result<ir::Operand, IrgenErrorMessage> {
+    let unit_dtype = unit.dtype();
+    let unit_width = unit_dtype
+        .get_int_width()
+        .expect("`unit` must be an integer");
+    let is_signed = dtype.is_int_signed();
+
+    let value = shift(
+        BinaryOperator::ShiftLeft,
+        unit,
+        unit_width - bit_field.bit_offset - bit_field.width,
+        context,
+    )?;
+    let value = if is_signed {
+        cast(value, &unit_dtype.set_signed(true), context)?
+    } else {
+        value
+    };
+    let value = shift(
+        BinaryOperator::ShiftRight,
+        value,
+        unit_width - bit_field.width,
+        context,
+    )?;
+    cast(value, &dtype, context)
+}
+
+/// Returns `lhs op rhs`, in the type of `lhs`.
+fn binary(
+    op: BinaryOperator,
+    lhs: ir::Operand,
+    rhs: ir::Operand,
+    context: &mut Context,
+) -> Result<ir::Operand, IrgenErrorMessage> {
+    let dtype = lhs.dtype();
+    context.insert_instruction(ir::Instruction::BinOp {
+        op,
+        lhs,
+        rhs,
+        dtype,
+    })
+}
+
+/// Returns `value` shifted by `amount` bits, or `value` itself if `amount` is 0.
+fn shift(
+    op: BinaryOperator,
+    value: ir::Operand,
+    amount: usize,
+    context: &mut Context,
+) -> Result<ir::Operand, IrgenErrorMessage> {
+    if amount == 0 {
+        return Ok(value);
+    }
+    let amount = constant(amount as u128, &value.dtype());
+    binary(op, value, amount, context)
+}
+
+/// Returns `value` converted to `dtype`, or `value` itself if it already has the type.
+fn cast(
+    value: ir::Operand,
+    dtype: &ir::Dtype,
+    context: &mut Context,
+) -> Result<ir::Operand, IrgenErrorMessage> {
+    if value.dtype() == *dtype {
+        return Ok(value);
+    }
+    context.insert_instruction(ir::Instruction::TypeCast {
+        value,
+        target_dtype: dtype.clone(),
+    })
+}
+
+fn constant(value: u128, dtype: &ir::Dtype) -> ir::Operand {
+    ir::Operand::constant(ir::Constant::int(value, dtype.clone()))
+}
+
+/// Returns the mask of all the bits of the integer type `dtype`.
+fn unit_mask(dtype: &ir::Dtype) -> u128 {
+    let width = dtype.get_int_width().expect("`dtype` must be an integer");
+    u128::MAX >> (u128::BITS as usize - width)
+}
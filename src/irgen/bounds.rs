@@ -0,0 +1,52 @@
+//! Accesses out of the bounds of arrays, e.g., `buf[4]` of `int buf[4]`.
+//!
+//! The initializers too long for their arrays are warned where the global variables are lowered,
+//! located at the initializer and at the declarator in the source. The constant indices out of
+//! the bounds of arrays, lowered to `getelementptr`s, and the calls to `__builtin_memcpy` and
+//! `__builtin_memset` with a constant size exceeding the destination are warned in the lowered
+//! functions by [`ir::check_access_bounds`], located at their instructions.
+
+use lang_c::ast::Initializer;
+use lang_c::span::{Node, Span};
+
+use crate::ir;
+
+use super::Irgen;
+
+impl Irgen {
+    /// Warns about `initializer` of a variable of `dtype`, whose declarator is at `decl_span`, if
+    /// it is too long for its array.
+    pub(super) fn check_initializer_bounds(
+        &mut self,
+        dtype: &ir::Dtype,
+        initializer: &Node<Initializer>,
+        decl_span: Span,
+    ) {
+        let mut messages = Vec::new();
+        ir::initializer_overflows(dtype, &initializer.node, &mut messages);
+        for message in messages {
+            let warning = self.bounds_warning(initializer.span, decl_span, message);
+            self.bounds_warnings.push(warning);
+        }
+    }
+
+    /// Warns about the accesses out of bounds in the functions lowered into `unit`.
+    pub(super) fn check_access_bounds(&mut self, unit: &ir::TranslationUnit) {
+        self.bounds_warnings.extend(ir::check_access_bounds(unit));
+    }
+
+    /// Returns the warning `message` about the access at `site` to the variable declared at
+    /// `decl`, located in the original source if its line map is known.
+    fn bounds_warning(&self, site: Span, decl: Span, message: String) -> ir::BoundsWarning {
+        let locate = |span: Span| match &self.line_map {
+            Some(line_map) => line_map.location(span.start).to_string(),
+            None => format!("offset {}", span.start),
+        };
+        ir::BoundsWarning {
+            site: locate(site),
+            decl: locate(decl),
+            message,
+            spans: Some((site, decl)),
+        }
+    }
+}
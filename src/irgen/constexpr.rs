@@ -0,0 +1,708 @@
+//! Evaluate the constant expressions of global initializers with the interpreter.
+//!
+//! An initializer that is not a literal, e.g., `1 << 4` or `sizeof(int) * 2`, is lowered into the
+//! body of a synthetic `main` function and evaluated by [`ir::interp_constant_expression`], which
+//! rejects function calls, loads from non-constant variables and undefined operations. The result
+//! replaces the initializer as a literal.
+
+use core::convert::TryFrom;
+use core::mem;
+use std::collections::BTreeMap;
+use std::ops::Deref;
+
+use lang_c::ast::*;
+use lang_c::span::{Node, Span};
+
+use crate::ir::{self, HasDtype, Named};
+use crate::some_or;
+
+use super::Irgen;
+
+/// Maximum number of steps the interpreter may take to evaluate a constant expression.
+const MAX_STEPS: usize = 10_000;
+
+impl Irgen {
+    /// Replaces the expressions in `initializer` of a variable of `dtype` that are not literals by
+    /// their values, or returns the reason why one of them is not a constant expression.
+    pub(super) fn fold_initializer(
+        &self,
+        initializer: &Initializer,
+        dtype: &ir::Dtype,
+    ) -> Result<Initializer, String> {
+        match initializer {
+            Initializer::Expression(expr) => {
+                if !matches!(dtype, ir::Dtype::Int { .. } | ir::Dtype::Float { .. })
+                    || ir::Constant::try_from(&expr.node).is_ok()
+                {
+                    return Ok(initializer.clone());
+                }
+
+                let value = self.evaluate(&expr.node, dtype)?;
+                Ok(Initializer::Expression(Box::new(Node::new(
+                    value, expr.span,
+                ))))
+            }
+            Initializer::List(items) => {
+                // A list of too many items is kept as it is, to be warned about.
+                let mut fits = true;
+                let inner_dtypes: Vec<ir::Dtype> = match dtype {
+                    ir::Dtype::Array { inner, size, .. } => {
+                        fits = size.map_or(false, |size| items.len() <= size);
+                        vec![inner.deref().clone(); items.len()]
+                    }
+                    ir::Dtype::Struct { name, .. } => {
+                        let fields = name
+                            .as_ref()
+                            .and_then(|name| self.structs.get(name))
+                            .and_then(Option::as_ref)
+                            .and_then(ir::Dtype::get_struct_fields)
+                            .and_then(Option::as_ref);
+                        let fields = some_or!(fields, return Ok(initializer.clone()));
+                        fits = items.len() <= fields.len();
+                        fields.iter().map(|field| field.deref().clone()).collect()
+                    }
+                    _ => return Ok(initializer.clone()),
+                };
+
+                let mut items: Vec<_> = items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        let inner_dtype = some_or!(inner_dtypes.get(i), return Ok(item.clone()));
+                        let initializer =
+                            self.fold_initializer(&item.node.initializer.node, inner_dtype)?;
+                        let folded = InitializerListItem {
+                            designation: item.node.designation.clone(),
+                            initializer: Box::new(Node::new(
+                                initializer,
+                                item.node.initializer.span,
+                            )),
+                        };
+                        Ok(Node::new(folded, item.span))
+                    })
+                    .collect::<Result<_, String>>()?;
+
+                // The zero items at the end are the `ZeroInit` tail of the aggregate, which the
+                // shorter list implies, e.g., `{1, 0, 0}` is folded to `{1}`. A designated item may
+                // override an earlier one, so that such lists are kept as they are.
+                if fits && items.iter().all(|item| item.node.designation.is_empty()) {
+                    let len = items
+                        .iter()
+                        .rposition(|item| !ir::is_zero_initializer(&item.node.initializer.node))
+                        .map_or(0, |i| i + 1);
+                    items.truncate(len);
+                }
+
+                Ok(Initializer::List(items))
+            }
+        }
+    }
+
+    /// Evaluates `expr` converted to `dtype`, returning its value as a literal, e.g., `-(3)`.
+    pub(super) fn evaluate(
+        &self,
+        expr: &Expression,
+        dtype: &ir::Dtype,
+    ) -> Result<Expression, String> {
+        let mut lowering = Lowering {
+            irgen: self,
+            decls: BTreeMap::new(),
+            blocks: BTreeMap::new(),
+            bid: Self::BID_INIT,
+            phinodes: Vec::new(),
+            instructions: Vec::new(),
+            bid_counter: Self::BID_COUNTER_INIT,
+        };
+        let value = lowering.translate(expr)?;
+        let dtype = dtype.clone().strip_outer_const();
+        let value = lowering.translate_typecast(value, dtype.clone())?;
+        lowering.end_block(ir::BlockExit::Return { value });
+
+        let mut decls = lowering.decls;
+        let _unused = decls.insert(
+            "main".to_string(),
+            ir::Declaration::Function {
+                signature: ir::FunctionSignature {
+                    ret: dtype,
+                    params: Vec::new(),
+                },
+                definition: Some(ir::FunctionDefinition {
+                    allocations: Vec::new(),
+                    blocks: lowering.blocks,
+                    bid_init: Self::BID_INIT,
+                }),
+                is_static: false,
+            },
+        );
+        let unit = ir::TranslationUnit {
+            decls,
+            structs: self.structs.clone(),
+        };
+
+        let value = ir::interp_constant_expression(&unit, MAX_STEPS).map_err(|e| e.to_string())?;
+        value_to_expression(&value)
+    }
+}
+
+/// Lowers a constant expression into the blocks of a function.
+struct Lowering<'i> {
+    irgen: &'i Irgen,
+    /// The global variables and functions referenced by the expression
+    ///
+    /// Functions are declared without their definitions, since calls are rejected anyway.
+    decls: BTreeMap<String, ir::Declaration>,
+    blocks: BTreeMap<ir::BlockId, ir::Block>,
+    /// The block being lowered
+    bid: ir::BlockId,
+    phinodes: Vec<Named<ir::Dtype>>,
+    instructions: Vec<Named<ir::Instruction>>,
+    bid_counter: usize,
+}
+
+impl Lowering<'_> {
+    fn alloc_bid(&mut self) -> ir::BlockId {
+        let bid = self.bid_counter;
+        self.bid_counter += 1;
+        ir::BlockId(bid)
+    }
+
+    fn insert_instruction(&mut self, instruction: ir::Instruction) -> ir::Operand {
+        let dtype = instruction.dtype();
+        self.instructions.push(Named::new(None, instruction));
+        ir::Operand::register(
+            ir::RegisterId::temp(self.bid, self.instructions.len() - 1),
+            dtype,
+        )
+    }
+
+    /// Ends the block being lowered with `exit`.
+    fn end_block(&mut self, exit: ir::BlockExit) {
+        let block = ir::Block {
+            phinodes: mem::take(&mut self.phinodes),
+            instructions: mem::take(&mut self.instructions),
+            exit,
+        };
+        let _unused = self.blocks.insert(self.bid, block);
+    }
+
+    /// Starts lowering into the block `bid`, returning the block being lowered so far.
+    fn switch_block(
+        &mut self,
+        bid: ir::BlockId,
+        phinodes: Vec<Named<ir::Dtype>>,
+        instructions: Vec<Named<ir::Instruction>>,
+    ) -> (
+        ir::BlockId,
+        Vec<Named<ir::Dtype>>,
+        Vec<Named<ir::Instruction>>,
+    ) {
+        (
+            mem::replace(&mut self.bid, bid),
+            mem::replace(&mut self.phinodes, phinodes),
+            mem::replace(&mut self.instructions, instructions),
+        )
+    }
+
+    fn translate(&mut self, expr: &Expression) -> Result<ir::Operand, String> {
+        match expr {
+            Expression::Constant(constant) => ir::Constant::try_from(&constant.node)
+                .map(ir::Operand::constant)
+                .map_err(|_| "unsupported constant".to_string()),
+            Expression::Identifier(identifier) => {
+                let name = &identifier.node.name;
+                if let Some(value) = self.irgen.enumerators.get(name) {
+                    return Ok(ir::Operand::constant(value.clone()));
+                }
+                let decl = self
+                    .irgen
+                    .decls
+                    .get(name)
+                    .ok_or_else(|| format!("`{name}` is undeclared"))?;
+                let ptr = ir::Operand::constant(ir::Constant::global_variable(
+                    name.clone(),
+                    decl.dtype(),
+                ));
+                let referenced = match decl {
+                    ir::Declaration::Function {
+                        signature,
+                        is_static,
+                        ..
+                    } => ir::Declaration::Function {
+                        signature: signature.clone(),
+                        definition: None,
+                        is_static: *is_static,
+                    },
+                    _ => decl.clone(),
+                };
+                let _unused = self.decls.insert(name.clone(), referenced);
+
+                match decl {
+                    ir::Declaration::Variable { dtype, .. } if is_arithmetic(dtype) => {
+                        Ok(self.insert_instruction(ir::Instruction::Load { ptr }))
+                    }
+                    ir::Declaration::Variable { dtype, .. } => Err(format!(
+                        "`{name}` of type `{dtype}` is not an arithmetic value"
+                    )),
+                    ir::Declaration::Function { .. } => Ok(ptr),
+                }
+            }
+            Expression::Call(call) => {
+                let callee = self.translate(&call.node.callee.node)?;
+                let (ret, params) = some_or!(
+                    callee
+                        .dtype()
+                        .get_pointer_inner()
+                        .and_then(ir::Dtype::get_function_inner)
+                        .map(|(ret, params)| (ret.clone(), params.clone())),
+                    return Err("called object is not a function".to_string())
+                );
+                if params.len() != call.node.arguments.len() {
+                    return Err("wrong number of arguments".to_string());
+                }
+
+                let args = call
+                    .node
+                    .arguments
+                    .iter()
+                    .zip(params)
+                    .map(|(arg, param)| {
+                        let arg = self.translate(&arg.node)?;
+                        self.translate_typecast(arg, param)
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                Ok(self.insert_instruction(ir::Instruction::Call {
+                    callee,
+                    args,
+                    return_type: ret,
+                }))
+            }
+            Expression::SizeOfTy(size_of) => {
+                self.translate_size_align_of(&size_of.node.0.node, true)
+            }
+            Expression::AlignOf(align_of) => {
+                self.translate_size_align_of(&align_of.node.0.node, false)
+            }
+            Expression::UnaryOperator(unary) => {
+                let operand = self.translate(&unary.node.operand.node)?;
+                self.translate_unary_op(&unary.node.operator.node, operand)
+            }
+            Expression::Cast(cast) => {
+                let dtype = ir::Dtype::try_from(&cast.node.type_name.node)
+                    .and_then(|dtype| dtype.resolve_typedefs(&self.irgen.typedefs))
+                    .map_err(|e| e.to_string())?;
+                let value = self.translate(&cast.node.expression.node)?;
+                self.translate_typecast(value, dtype.strip_outer_const())
+            }
+            Expression::BinaryOperator(binary) => {
+                let op = &binary.node.operator.node;
+                if matches!(op, BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr) {
+                    return self.translate_logical_op(
+                        op,
+                        &binary.node.lhs.node,
+                        &binary.node.rhs.node,
+                    );
+                }
+
+                let lhs = self.translate(&binary.node.lhs.node)?;
+                let rhs = self.translate(&binary.node.rhs.node)?;
+                self.translate_binary_op(op, lhs, rhs)
+            }
+            Expression::Conditional(conditional) => self.translate_conditional(
+                &conditional.node.condition.node,
+                &conditional.node.then_expression.node,
+                &conditional.node.else_expression.node,
+            ),
+            _ => Err("unsupported expression".to_string()),
+        }
+    }
+
+    fn translate_size_align_of(
+        &mut self,
+        type_name: &TypeName,
+        is_size: bool,
+    ) -> Result<ir::Operand, String> {
+        let dtype = ir::Dtype::try_from(type_name)
+            .and_then(|dtype| dtype.resolve_typedefs(&self.irgen.typedefs))
+            .map_err(|e| e.to_string())?;
+        if !dtype.is_complete(&self.irgen.structs) {
+            let operator = if is_size { "sizeof" } else { "_Alignof" };
+            return Err(ir::DtypeError::IncompleteType {
+                dtype,
+                usage: format!("the operand of `{operator}`"),
+            }
+            .to_string());
+        }
+        let (size_of, align_of) = dtype
+            .size_align_of(&self.irgen.structs)
+            .map_err(|e| e.to_string())?;
+        let value = if is_size { size_of } else { align_of };
+
+        Ok(ir::Operand::constant(ir::Constant::int(
+            value as u128,
+            ir::Dtype::long().set_signed(false),
+        )))
+    }
+
+    fn translate_unary_op(
+        &mut self,
+        op: &UnaryOperator,
+        operand: ir::Operand,
+    ) -> Result<ir::Operand, String> {
+        let dtype = operand.dtype().strip_outer_const();
+        if !is_arithmetic(&dtype) {
+            return Err(format!("invalid operand of type `{dtype}` to `{op:?}`"));
+        }
+
+        match op {
+            UnaryOperator::Plus => self.translate_typecast(operand, integer_promotion(dtype)),
+            UnaryOperator::Minus => {
+                let dtype = integer_promotion(dtype);
+                let operand = self.translate_typecast(operand, dtype.clone())?;
+                Ok(self.insert_instruction(ir::Instruction::UnaryOp {
+                    op: op.clone(),
+                    operand,
+                    dtype,
+                }))
+            }
+            UnaryOperator::Complement => {
+                let dtype = integer_promotion(dtype);
+                let width = some_or!(
+                    dtype.get_int_width(),
+                    return Err(format!("invalid operand of type `{dtype}` to `~`"))
+                );
+                let operand = self.translate_typecast(operand, dtype.clone())?;
+                // Signed integers are sign-extended, so all ones is `-1`.
+                let ones = if dtype.is_int_signed() {
+                    u128::MAX
+                } else {
+                    (1u128 << width) - 1
+                };
+                Ok(self.insert_instruction(ir::Instruction::BinOp {
+                    op: BinaryOperator::BitwiseXor,
+                    lhs: operand,
+                    rhs: ir::Operand::constant(ir::Constant::int(ones, dtype.clone())),
+                    dtype,
+                }))
+            }
+            UnaryOperator::Negate => {
+                let condition = self.translate_condition(operand, BinaryOperator::Equals)?;
+                self.translate_typecast(condition, ir::Dtype::INT)
+            }
+            _ => Err(format!("`{op:?}` is not allowed in a constant expression")),
+        }
+    }
+
+    fn translate_binary_op(
+        &mut self,
+        op: &BinaryOperator,
+        lhs: ir::Operand,
+        rhs: ir::Operand,
+    ) -> Result<ir::Operand, String> {
+        let lhs_dtype = lhs.dtype().strip_outer_const();
+        let rhs_dtype = rhs.dtype().strip_outer_const();
+        if !is_arithmetic(&lhs_dtype) || !is_arithmetic(&rhs_dtype) {
+            return Err(format!(
+                "invalid operands of types `{lhs_dtype}` and `{rhs_dtype}` to `{op:?}`"
+            ));
+        }
+
+        let (dtype, is_comparison) = match op {
+            BinaryOperator::Multiply
+            | BinaryOperator::Divide
+            | BinaryOperator::Plus
+            | BinaryOperator::Minus => (usual_arithmetic_conversion(lhs_dtype, rhs_dtype), false),
+            BinaryOperator::Modulo
+            | BinaryOperator::BitwiseAnd
+            | BinaryOperator::BitwiseXor
+            | BinaryOperator::BitwiseOr => {
+                let dtype = usual_arithmetic_conversion(lhs_dtype, rhs_dtype);
+                if dtype.get_int_width().is_none() {
+                    return Err(format!("invalid operands of type `{dtype}` to `{op:?}`"));
+                }
+                (dtype, false)
+            }
+            // The type of a shift is that of its promoted left operand.
+            BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight => {
+                if lhs_dtype.get_int_width().is_none() || rhs_dtype.get_int_width().is_none() {
+                    return Err(format!("invalid operands to `{op:?}`"));
+                }
+                (integer_promotion(lhs_dtype), false)
+            }
+            BinaryOperator::Less
+            | BinaryOperator::Greater
+            | BinaryOperator::LessOrEqual
+            | BinaryOperator::GreaterOrEqual
+            | BinaryOperator::Equals
+            | BinaryOperator::NotEquals => {
+                (usual_arithmetic_conversion(lhs_dtype, rhs_dtype), true)
+            }
+            _ => return Err(format!("`{op:?}` is not allowed in a constant expression")),
+        };
+
+        let lhs = self.translate_typecast(lhs, dtype.clone())?;
+        let rhs = self.translate_typecast(rhs, dtype.clone())?;
+        if !is_comparison {
+            return Ok(self.insert_instruction(ir::Instruction::BinOp {
+                op: op.clone(),
+                lhs,
+                rhs,
+                dtype,
+            }));
+        }
+
+        let condition = self.insert_instruction(ir::Instruction::BinOp {
+            op: op.clone(),
+            lhs,
+            rhs,
+            dtype: ir::Dtype::BOOL,
+        });
+        self.translate_typecast(condition, ir::Dtype::INT)
+    }
+
+    /// Lowers `lhs && rhs` or `lhs || rhs`, which evaluates `rhs` only if `lhs` does not decide
+    /// the result.
+    fn translate_logical_op(
+        &mut self,
+        op: &BinaryOperator,
+        lhs: &Expression,
+        rhs: &Expression,
+    ) -> Result<ir::Operand, String> {
+        let lhs = self.translate(lhs)?;
+        let lhs = self.translate_condition(lhs, BinaryOperator::NotEquals)?;
+
+        let bid_rhs = self.alloc_bid();
+        let bid_end = self.alloc_bid();
+        let arg_rhs = ir::JumpArg::new(bid_rhs, Vec::new());
+        let arg_end = ir::JumpArg::new(bid_end, vec![lhs.clone()]);
+        let (arg_then, arg_else) = if op == &BinaryOperator::LogicalAnd {
+            (arg_rhs, arg_end)
+        } else {
+            (arg_end, arg_rhs)
+        };
+        self.end_block(ir::BlockExit::ConditionalJump {
+            condition: lhs,
+            arg_then,
+            arg_else,
+        });
+
+        let _unused = self.switch_block(bid_rhs, Vec::new(), Vec::new());
+        let rhs = self.translate(rhs)?;
+        let rhs = self.translate_condition(rhs, BinaryOperator::NotEquals)?;
+        self.end_block(ir::BlockExit::Jump {
+            arg: ir::JumpArg::new(bid_end, vec![rhs]),
+        });
+
+        let _unused =
+            self.switch_block(bid_end, vec![Named::new(None, ir::Dtype::BOOL)], Vec::new());
+        let result = ir::Operand::register(ir::RegisterId::arg(bid_end, 0), ir::Dtype::BOOL);
+        self.translate_typecast(result, ir::Dtype::INT)
+    }
+
+    /// Lowers `condition ? then_expr : else_expr`, which evaluates only one of the branches.
+    fn translate_conditional(
+        &mut self,
+        condition: &Expression,
+        then_expr: &Expression,
+        else_expr: &Expression,
+    ) -> Result<ir::Operand, String> {
+        let condition = self.translate(condition)?;
+        let condition = self.translate_condition(condition, BinaryOperator::NotEquals)?;
+
+        let bid_then = self.alloc_bid();
+        let bid_else = self.alloc_bid();
+        let bid_end = self.alloc_bid();
+        self.end_block(ir::BlockExit::ConditionalJump {
+            condition,
+            arg_then: ir::JumpArg::new(bid_then, Vec::new()),
+            arg_else: ir::JumpArg::new(bid_else, Vec::new()),
+        });
+
+        let _unused = self.switch_block(bid_then, Vec::new(), Vec::new());
+        let then_value = self.translate(then_expr)?;
+        // The type of the result is known only after lowering both branches, so the conversion
+        // of `then_value` is inserted after lowering `else_expr`.
+        let (then_bid, then_phinodes, then_instructions) =
+            self.switch_block(bid_else, Vec::new(), Vec::new());
+        let else_value = self.translate(else_expr)?;
+
+        let then_dtype = then_value.dtype().strip_outer_const();
+        let else_dtype = else_value.dtype().strip_outer_const();
+        if !is_arithmetic(&then_dtype) || !is_arithmetic(&else_dtype) {
+            return Err(format!(
+                "invalid operands of types `{then_dtype}` and `{else_dtype}` to `?:`"
+            ));
+        }
+        let dtype = usual_arithmetic_conversion(then_dtype, else_dtype);
+
+        let else_value = self.translate_typecast(else_value, dtype.clone())?;
+        self.end_block(ir::BlockExit::Jump {
+            arg: ir::JumpArg::new(bid_end, vec![else_value]),
+        });
+
+        let _unused = self.switch_block(then_bid, then_phinodes, then_instructions);
+        let then_value = self.translate_typecast(then_value, dtype.clone())?;
+        self.end_block(ir::BlockExit::Jump {
+            arg: ir::JumpArg::new(bid_end, vec![then_value]),
+        });
+
+        let _unused = self.switch_block(bid_end, vec![Named::new(None, dtype.clone())], Vec::new());
+        Ok(ir::Operand::register(
+            ir::RegisterId::arg(bid_end, 0),
+            dtype,
+        ))
+    }
+
+    /// Compares `value` with zero by `op`, i.e., `Equals` or `NotEquals`.
+    fn translate_condition(
+        &mut self,
+        value: ir::Operand,
+        op: BinaryOperator,
+    ) -> Result<ir::Operand, String> {
+        let dtype = value.dtype().strip_outer_const();
+        let zero = match &dtype {
+            ir::Dtype::Int { .. } => ir::Constant::int(0, dtype),
+            ir::Dtype::Float { .. } => ir::Constant::float(0.0, dtype),
+            _ => return Err(format!("`{dtype}` is not an arithmetic type")),
+        };
+
+        Ok(self.insert_instruction(ir::Instruction::BinOp {
+            op,
+            lhs: value,
+            rhs: ir::Operand::constant(zero),
+            dtype: ir::Dtype::BOOL,
+        }))
+    }
+
+    fn translate_typecast(
+        &mut self,
+        value: ir::Operand,
+        dtype: ir::Dtype,
+    ) -> Result<ir::Operand, String> {
+        let source = value.dtype().strip_outer_const();
+        if source.is_same_type(&dtype) {
+            return Ok(value);
+        }
+        if !is_arithmetic(&source) || !is_arithmetic(&dtype) {
+            return Err(format!("cannot convert `{source}` to `{dtype}`"));
+        }
+
+        Ok(self.insert_instruction(ir::Instruction::TypeCast {
+            value,
+            target_dtype: dtype,
+        }))
+    }
+}
+
+fn is_arithmetic(dtype: &ir::Dtype) -> bool {
+    matches!(dtype, ir::Dtype::Int { .. } | ir::Dtype::Float { .. })
+}
+
+/// Promotes integers narrower than `int` to `int`, keeping the floats as they are.
+fn integer_promotion(dtype: ir::Dtype) -> ir::Dtype {
+    if dtype.get_int_width().is_some() {
+        dtype.promote_integer()
+    } else {
+        dtype
+    }
+}
+
+/// Returns the common type of arithmetic operands of types `lhs` and `rhs`.
+fn usual_arithmetic_conversion(lhs: ir::Dtype, rhs: ir::Dtype) -> ir::Dtype {
+    match (lhs.get_float_width(), rhs.get_float_width()) {
+        (Some(lhs_width), Some(rhs_width)) => ir::Dtype::float(lhs_width.max(rhs_width)),
+        (Some(_), None) => lhs,
+        (None, Some(_)) => rhs,
+        (None, None) => {
+            let lhs = integer_promotion(lhs);
+            let rhs = integer_promotion(rhs);
+            let lhs_width = lhs.get_int_width().expect("`lhs` must be an integer");
+            let rhs_width = rhs.get_int_width().expect("`rhs` must be an integer");
+
+            if lhs_width != rhs_width {
+                if lhs_width > rhs_width {
+                    lhs
+                } else {
+                    rhs
+                }
+            } else if !rhs.is_int_signed() {
+                rhs
+            } else {
+                lhs
+            }
+        }
+    }
+}
+
+/// Returns `value` as a literal, negated by `-` if it is negative.
+fn value_to_expression(value: &ir::Value) -> Result<Expression, String> {
+    let (constant, is_negative) = match value {
+        ir::Value::Int {
+            value,
+            width,
+            is_signed,
+        } => {
+            let shift = 128 - width;
+            let value = if *is_signed {
+                ((*value << shift) as i128) >> shift
+            } else {
+                ((*value << shift) >> shift) as i128
+            };
+
+            // `-9223372036854775808` is written as its two's complement, which is converted back
+            // to `long` by the initialization.
+            let (magnitude, is_negative) = if value == i64::MIN.into() {
+                (value as u64 as i128, false)
+            } else {
+                (value.abs(), value < 0)
+            };
+            let size = if magnitude <= i32::MAX.into() {
+                IntegerSize::Int
+            } else {
+                IntegerSize::Long
+            };
+
+            let integer = Integer {
+                base: IntegerBase::Decimal,
+                number: Box::from(magnitude.to_string()),
+                suffix: IntegerSuffix {
+                    size,
+                    unsigned: false,
+                    imaginary: false,
+                },
+            };
+            (Constant::Integer(integer), is_negative)
+        }
+        ir::Value::Float { value, .. } => {
+            let value = value.into_inner();
+            if !value.is_finite() {
+                return Err(format!("`{value}` is not a finite number"));
+            }
+
+            let float = Float {
+                base: FloatBase::Decimal,
+                number: Box::from(value.abs().to_string()),
+                suffix: FloatSuffix {
+                    format: FloatFormat::Double,
+                    imaginary: false,
+                },
+            };
+            (Constant::Float(float), value.is_sign_negative())
+        }
+        _ => return Err("the value is not arithmetic".to_string()),
+    };
+
+    let expr = Expression::Constant(Box::new(Node::new(constant, Span::none())));
+    if !is_negative {
+        return Ok(expr);
+    }
+
+    let unary = UnaryOperatorExpression {
+        operator: Node::new(UnaryOperator::Minus, Span::none()),
+        operand: Box::new(Node::new(expr, Span::none())),
+    };
+    Ok(Expression::UnaryOperator(Box::new(Node::new(
+        unary,
+        Span::none(),
+    ))))
+}
@@ -0,0 +1,175 @@
+//! Extension points observing the lowering of a translation unit and injecting IR.
+
+use core::fmt;
+use std::collections::HashMap;
+
+use lang_c::ast::*;
+
+use super::Context;
+use crate::ir::{HasDtype, Named};
+use crate::*;
+
+/// Callbacks invoked by [`Irgen`] while it lowers a translation unit, e.g., to instrument it.
+///
+/// The hooks observe the AST node being lowered and may insert instructions through the
+/// [`IrgenBuilder`] of the function being lowered, which appends them to the current block. The
+/// IR the hooks contribute is verified once the unit is lowered (see [`ir::verify`]), and a
+/// violation is reported as [`IrgenErrorMessage::InvalidHookOutput`].
+///
+/// Every hook does nothing by default.
+pub trait IrgenHooks: fmt::Debug {
+    /// Called once the parameters of the function `name` are stored, before its body is lowered.
+    fn on_function_start(
+        &mut self,
+        _name: &str,
+        _builder: &mut IrgenBuilder<'_>,
+    ) -> Result<(), IrgenErrorMessage> {
+        Ok(())
+    }
+
+    /// Called before `stmt` is lowered, i.e., the inserted instructions are executed each time
+    /// `stmt` is.
+    fn on_statement(
+        &mut self,
+        _stmt: &Statement,
+        _builder: &mut IrgenBuilder<'_>,
+    ) -> Result<(), IrgenErrorMessage> {
+        Ok(())
+    }
+
+    /// Called once `expr` is lowered to `value`.
+    fn on_expression_lowered(
+        &mut self,
+        _expr: &Expression,
+        _value: &ir::Operand,
+        _builder: &mut IrgenBuilder<'_>,
+    ) -> Result<(), IrgenErrorMessage> {
+        Ok(())
+    }
+
+    /// Called once the body of the function `name` is lowered, before its implicit return at the
+    /// end of the body.
+    fn on_function_end(
+        &mut self,
+        _name: &str,
+        _builder: &mut IrgenBuilder<'_>,
+    ) -> Result<(), IrgenErrorMessage> {
+        Ok(())
+    }
+
+    /// Called once the unit is lowered, before it is verified, e.g., to declare the global
+    /// variables the inserted instructions refer to.
+    fn on_unit_end(&mut self, _unit: &mut ir::TranslationUnit) {}
+}
+
+/// Inserts IR at the point of the function being lowered where a hook is invoked.
+#[derive(Debug)]
+pub struct IrgenBuilder<'a> {
+    pub(super) context: &'a mut Context,
+    pub(super) allocations: &'a mut Vec<Named<ir::Dtype>>,
+    pub(super) symbol_table: &'a [HashMap<String, ir::Operand>],
+}
+
+impl IrgenBuilder<'_> {
+    /// Appends `instr` to the current block, returning the register holding its result.
+    pub fn insert_instruction(
+        &mut self,
+        instr: ir::Instruction,
+    ) -> Result<ir::Operand, IrgenErrorMessage> {
+        self.context.insert_instruction(instr)
+    }
+
+    /// Adds a local allocation of `dtype`, returning the pointer to it.
+    pub fn insert_alloc(&mut self, name: Option<String>, dtype: ir::Dtype) -> ir::Operand {
+        self.allocations.push(Named::new(name, dtype.clone()));
+        ir::Operand::register(
+            ir::RegisterId::local(self.allocations.len() - 1),
+            ir::Dtype::pointer(dtype),
+        )
+    }
+
+    /// Returns the pointer to the variable `name` visible at the current point, if any.
+    pub fn lookup_symbol(&self, name: &str) -> Option<&ir::Operand> {
+        self.symbol_table
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+    }
+}
+
+/// Statement coverage instrumentation.
+///
+/// Each lowered statement is given a counter in the global array [`StatementCoverage::COUNTERS`],
+/// in the order the statements are lowered, incremented each time the statement is executed.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StatementCoverage {
+    statements: usize,
+}
+
+impl StatementCoverage {
+    /// The name of the array of counters.
+    pub const COUNTERS: &'static str = "__kecc_coverage";
+
+    /// The dtype of a counter.
+    pub const COUNTER_DTYPE: ir::Dtype = ir::Dtype::LONG;
+
+    /// The number of statements instrumented so far.
+    pub fn statements(&self) -> usize {
+        self.statements
+    }
+
+    /// Returns the pointer to the `index`-th counter.
+    fn counter(
+        index: usize,
+        builder: &mut IrgenBuilder<'_>,
+    ) -> Result<ir::Operand, IrgenErrorMessage> {
+        let counters = ir::Operand::constant(ir::Constant::global_variable(
+            Self::COUNTERS.to_string(),
+            Self::COUNTER_DTYPE,
+        ));
+        let offset = index * ir::Dtype::SIZE_OF_LONGLONG;
+        builder.insert_instruction(ir::Instruction::GetElementPtr {
+            ptr: counters.clone(),
+            offset: ir::Operand::constant(ir::Constant::int(offset as u128, ir::Dtype::LONG)),
+            dtype: counters.dtype(),
+        })
+    }
+}
+
+impl IrgenHooks for StatementCoverage {
+    fn on_statement(
+        &mut self,
+        _stmt: &Statement,
+        builder: &mut IrgenBuilder<'_>,
+    ) -> Result<(), IrgenErrorMessage> {
+        let ptr = Self::counter(self.statements, builder)?;
+        self.statements += 1;
+
+        let count = builder.insert_instruction(ir::Instruction::Load { ptr: ptr.clone() })?;
+        let value = builder.insert_instruction(ir::Instruction::BinOp {
+            op: BinaryOperator::Plus,
+            lhs: count,
+            rhs: ir::Operand::constant(ir::Constant::int(1, Self::COUNTER_DTYPE)),
+            dtype: Self::COUNTER_DTYPE,
+        })?;
+        let _unused = builder.insert_instruction(ir::Instruction::Store { ptr, value })?;
+
+        Ok(())
+    }
+
+    fn on_unit_end(&mut self, unit: &mut ir::TranslationUnit) {
+        if self.statements == 0 {
+            return;
+        }
+
+        let _unused = unit.decls.insert(
+            Self::COUNTERS.to_string(),
+            ir::Declaration::Variable {
+                dtype: ir::Dtype::array(Self::COUNTER_DTYPE, self.statements),
+                initializer: None,
+                is_extern: false,
+                is_static: false,
+            },
+        );
+    }
+}
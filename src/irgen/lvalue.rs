@@ -0,0 +1,609 @@
+//! Check the value categories of the operands of assignments, increments, decrements and `&`.
+//!
+//! An expression is either a modifiable lvalue, e.g., `x` or `*p`, a non-modifiable lvalue, e.g.,
+//! a `const` variable, an array or a string literal, or an rvalue, e.g., `5` or `a + b` (see C17
+//! 6.3.2.1). Assignments, increments and decrements need a modifiable lvalue, while `&` needs an
+//! lvalue or a function designator. The body of a function is checked before it is lowered, so
+//! that, e.g., `5 = x` is reported at the assignment instead of being lowered to a store to a
+//! temporary. The calls to unknown builtins, which need no declaration, are reported likewise.
+//!
+//! The typedefs and the struct types declared in a block are visible in it only, e.g., `typedef
+//! struct { int x, y; } pt;` in a function body names an anonymous struct given an internal tag
+//! that no other struct type reuses, and an inner typedef or variable shadows the outer typedef of
+//! the same name until the end of its block. Likewise, a struct tag defined in a block shadows the
+//! definition of the tag in an outer scope, e.g., at file scope.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use lang_c::ast::*;
+use lang_c::span::Node;
+
+use crate::c::name_of_declarator;
+use crate::ir::{self, HasDtype};
+use crate::some_or;
+
+use super::{Irgen, IrgenError, IrgenErrorMessage};
+
+impl Irgen {
+    /// Checks the operands in the body of `source`, whose parameters are `params`.
+    pub(super) fn check_lvalues(
+        &self,
+        source: &FunctionDefinition,
+        params: &[(String, ir::Dtype)],
+    ) -> Result<(), IrgenError> {
+        let params = params
+            .iter()
+            .map(|(name, dtype)| (name.clone(), Symbol::Variable(Some(dtype.clone()))))
+            .collect();
+        let mut checker = Checker {
+            irgen: self,
+            scopes: vec![params],
+            struct_scopes: vec![HashMap::new()],
+            structs: Cow::Borrowed(&self.structs),
+            struct_tempid_counter: self.struct_tempid_counter,
+        };
+        checker.check_stmt(&source.statement.node)
+    }
+
+    /// Checks the operands in `initializer` of a global variable.
+    pub(super) fn check_initializer_lvalues(
+        &self,
+        initializer: &Initializer,
+    ) -> Result<(), IrgenError> {
+        let mut checker = Checker {
+            irgen: self,
+            scopes: Vec::new(),
+            struct_scopes: Vec::new(),
+            structs: Cow::Borrowed(&self.structs),
+            struct_tempid_counter: self.struct_tempid_counter,
+        };
+        checker.check_initializer(initializer)
+    }
+}
+
+/// An expression designating an object.
+#[derive(Debug)]
+struct Lvalue {
+    /// The type of the object, or `None` if unknown
+    dtype: Option<ir::Dtype>,
+    /// Whether the object is a string literal or a part of one
+    in_string_literal: bool,
+}
+
+/// An ordinary identifier declared in a block, with its type or `None` if unknown.
+#[derive(Debug)]
+enum Symbol {
+    Variable(Option<ir::Dtype>),
+    Typedef(Option<ir::Dtype>),
+}
+
+/// Checks the operands of a function body, keeping track of the types of its local variables.
+struct Checker<'i> {
+    irgen: &'i Irgen,
+    /// The local variables and typedefs, innermost scope last
+    scopes: Vec<HashMap<String, Symbol>>,
+    /// The struct tags defined in each scope of `scopes` with the outer definitions they shadow,
+    /// which are restored at the end of the scope
+    struct_scopes: Vec<HashMap<String, Option<Option<ir::Dtype>>>>,
+    /// The struct types, including those defined in the body, which are only visible to the check
+    structs: Cow<'i, HashMap<String, Option<ir::Dtype>>>,
+    /// The next tag of the anonymous structs defined in the body, starting from that of the IR
+    /// generator. Like `structs`, it is dropped with the check, so that the IR generator resolves
+    /// the same tags when it lowers the body.
+    struct_tempid_counter: usize,
+}
+
+impl Checker<'_> {
+    fn check_stmt(&mut self, stmt: &Statement) -> Result<(), IrgenError> {
+        match stmt {
+            Statement::Labeled(stmt) => self.check_stmt(&stmt.node.statement.node),
+            Statement::Compound(items) => {
+                self.enter_scope();
+                let result = items
+                    .iter()
+                    .try_for_each(|item| self.check_block_item(&item.node));
+                self.exit_scope();
+                result
+            }
+            Statement::Expression(expr) => {
+                expr.as_ref().map_or(Ok(()), |expr| self.check_expr(expr))
+            }
+            Statement::If(stmt) => {
+                self.check_expr(&stmt.node.condition)?;
+                self.check_stmt(&stmt.node.then_statement.node)?;
+                stmt.node
+                    .else_statement
+                    .as_ref()
+                    .map_or(Ok(()), |stmt| self.check_stmt(&stmt.node))
+            }
+            Statement::Switch(stmt) => {
+                self.check_expr(&stmt.node.expression)?;
+                self.check_stmt(&stmt.node.statement.node)
+            }
+            Statement::While(stmt) => {
+                self.check_expr(&stmt.node.expression)?;
+                self.check_stmt(&stmt.node.statement.node)
+            }
+            Statement::DoWhile(stmt) => {
+                self.check_stmt(&stmt.node.statement.node)?;
+                self.check_expr(&stmt.node.expression)
+            }
+            Statement::For(stmt) => {
+                self.enter_scope();
+                let result = self.check_for(&stmt.node);
+                self.exit_scope();
+                result
+            }
+            Statement::Return(expr) => expr.as_ref().map_or(Ok(()), |expr| self.check_expr(expr)),
+            Statement::Goto(_) | Statement::Continue | Statement::Break | Statement::Asm(_) => {
+                Ok(())
+            }
+        }
+    }
+
+    fn enter_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+        self.struct_scopes.push(HashMap::new());
+    }
+
+    /// Leaves the innermost scope, where the struct tags it defines are no longer visible.
+    fn exit_scope(&mut self) {
+        let _unused = self.scopes.pop();
+        let shadowed = self
+            .struct_scopes
+            .pop()
+            .expect("a scope must have been entered");
+        if shadowed.is_empty() {
+            return;
+        }
+
+        let structs = self.structs.to_mut();
+        for (name, outer) in shadowed {
+            let _unused = match outer {
+                Some(outer) => structs.insert(name, outer),
+                None => structs.remove(&name),
+            };
+        }
+    }
+
+    fn check_for(&mut self, stmt: &ForStatement) -> Result<(), IrgenError> {
+        match &stmt.initializer.node {
+            ForInitializer::Expression(expr) => self.check_expr(expr)?,
+            ForInitializer::Declaration(decl) => self.declare(&decl.node)?,
+            ForInitializer::Empty | ForInitializer::StaticAssert(_) => (),
+        }
+        if let Some(condition) = &stmt.condition {
+            self.check_expr(condition)?;
+        }
+        if let Some(step) = &stmt.step {
+            self.check_expr(step)?;
+        }
+        self.check_stmt(&stmt.statement.node)
+    }
+
+    fn check_block_item(&mut self, item: &BlockItem) -> Result<(), IrgenError> {
+        match item {
+            BlockItem::Declaration(decl) => self.declare(&decl.node),
+            BlockItem::StaticAssert(_) => Ok(()),
+            BlockItem::Statement(stmt) => self.check_stmt(&stmt.node),
+        }
+    }
+
+    /// Adds the variables or the typedefs of `decl` to the innermost scope, and checks their
+    /// initializers.
+    ///
+    /// A variable whose type is invalid is added with an unknown type, so that it still shadows the
+    /// outer ones. The invalid type itself is reported while lowering.
+    fn declare(&mut self, decl: &Declaration) -> Result<(), IrgenError> {
+        let is_typedef = decl.specifiers.iter().any(|specifier| {
+            matches!(
+                &specifier.node,
+                DeclarationSpecifier::StorageClass(storage_class)
+                    if storage_class.node == StorageClassSpecifier::Typedef
+            )
+        });
+        let base_dtype = ir::Dtype::try_from_ast_declaration_specifiers(&decl.specifiers)
+            .ok()
+            .and_then(|(dtype, _)| self.resolve_base_dtype(dtype));
+
+        for init_decl in &decl.declarators {
+            let declarator = &init_decl.node.declarator.node;
+            let dtype = base_dtype.clone().and_then(|base_dtype| {
+                base_dtype
+                    .with_ast_declarator(declarator)
+                    .ok()?
+                    .into_inner()
+                    .resolve_typedefs(&self.typedefs())
+                    .ok()
+            });
+            let symbol = if is_typedef {
+                Symbol::Typedef(dtype)
+            } else {
+                Symbol::Variable(dtype)
+            };
+            let scope = self
+                .scopes
+                .last_mut()
+                .expect("a local variable must be declared in a scope");
+            let _unused = scope.insert(name_of_declarator(declarator), symbol);
+
+            if let Some(initializer) = &init_decl.node.initializer {
+                self.check_initializer(&initializer.node)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the base type of a declaration with its typedef names resolved in the current
+    /// scope, defining the struct type it defines if any, or `None` if it is invalid.
+    ///
+    /// A struct tag defined in a block shadows the definition of an outer scope until the end of
+    /// the block, and is only a redefinition if defined twice in the same block.
+    fn resolve_base_dtype(&mut self, dtype: ir::Dtype) -> Option<ir::Dtype> {
+        let dtype = dtype.resolve_typedefs(&self.typedefs()).ok()?;
+        let name = if let ir::Dtype::Struct {
+            name,
+            fields: Some(_),
+            ..
+        } = &dtype
+        {
+            name.clone()
+        } else {
+            return Some(dtype);
+        };
+
+        if let (Some(name), Some(shadowed)) = (name, self.struct_scopes.last_mut()) {
+            if !shadowed.contains_key(&name) {
+                let outer = self.structs.to_mut().remove(&name);
+                let _unused = shadowed.insert(name, outer);
+            }
+        }
+
+        dtype
+            .resolve_structs(self.structs.to_mut(), &mut self.struct_tempid_counter)
+            .ok()
+    }
+
+    /// Returns the typedef names visible in the current scope, where those declared in a block
+    /// shadow the outer ones, and so do the variables.
+    fn typedefs(&self) -> Cow<'_, HashMap<String, ir::Dtype>> {
+        let globals = &self.irgen.typedefs;
+        let is_shadowed = self.scopes.iter().flatten().any(|(name, symbol)| {
+            matches!(symbol, Symbol::Typedef(_)) || globals.contains_key(name)
+        });
+        if !is_shadowed {
+            return Cow::Borrowed(globals);
+        }
+
+        let mut typedefs = globals.clone();
+        for (name, symbol) in self.scopes.iter().flatten() {
+            if let Symbol::Typedef(Some(dtype)) = symbol {
+                let _unused = typedefs.insert(name.clone(), dtype.clone());
+            } else {
+                let _unused = typedefs.remove(name);
+            }
+        }
+        Cow::Owned(typedefs)
+    }
+
+    fn check_initializer(&mut self, initializer: &Initializer) -> Result<(), IrgenError> {
+        match initializer {
+            Initializer::Expression(expr) => self.check_expr(expr),
+            Initializer::List(items) => items
+                .iter()
+                .try_for_each(|item| self.check_initializer(&item.node.initializer.node)),
+        }
+    }
+
+    fn check_expr(&mut self, expr: &Node<Expression>) -> Result<(), IrgenError> {
+        match &expr.node {
+            Expression::Member(member) => self.check_expr(&member.node.expression),
+            Expression::Call(call) => {
+                // Builtins are called without being declared, so an unknown one is only found at
+                // its calls, e.g., `__builtin_bogus(1)`.
+                if let Expression::Identifier(identifier) = &call.node.callee.node {
+                    let name = &identifier.node.name;
+                    if ir::Builtin::is_reserved(name) && ir::Builtin::from_name(name).is_none() {
+                        let message = IrgenErrorMessage::UnknownBuiltin { name: name.clone() };
+                        return Err(error(&call.node.callee, message));
+                    }
+                }
+                self.check_expr(&call.node.callee)?;
+                call.node
+                    .arguments
+                    .iter()
+                    .try_for_each(|arg| self.check_expr(arg))
+            }
+            Expression::CompoundLiteral(literal) => literal
+                .node
+                .initializer_list
+                .iter()
+                .try_for_each(|item| self.check_initializer(&item.node.initializer.node)),
+            Expression::SizeOfVal(size_of_val) => self.check_expr(&size_of_val.node.0),
+            Expression::UnaryOperator(unary) => {
+                let operand = &unary.node.operand;
+                self.check_expr(operand)?;
+                match unary.node.operator.node {
+                    UnaryOperator::PreIncrement | UnaryOperator::PostIncrement => {
+                        self.require_modifiable(&operand.node, "increment operand", expr)
+                    }
+                    UnaryOperator::PreDecrement | UnaryOperator::PostDecrement => {
+                        self.require_modifiable(&operand.node, "decrement operand", expr)
+                    }
+                    UnaryOperator::Address => {
+                        let is_function = matches!(
+                            self.dtype_of(&operand.node),
+                            Some(ir::Dtype::Function { .. })
+                        );
+                        if is_function || self.classify(&operand.node).is_some() {
+                            Ok(())
+                        } else {
+                            Err(error(
+                                expr,
+                                IrgenErrorMessage::RequireLvalue {
+                                    message: "unary '&' operand".to_string(),
+                                },
+                            ))
+                        }
+                    }
+                    _ => Ok(()),
+                }
+            }
+            Expression::Cast(cast) => self.check_expr(&cast.node.expression),
+            Expression::BinaryOperator(binary) => {
+                self.check_expr(&binary.node.lhs)?;
+                self.check_expr(&binary.node.rhs)?;
+                if is_assignment(&binary.node.operator.node) {
+                    self.require_modifiable(
+                        &binary.node.lhs.node,
+                        "left operand of assignment",
+                        expr,
+                    )
+                } else {
+                    Ok(())
+                }
+            }
+            Expression::Conditional(conditional) => {
+                self.check_expr(&conditional.node.condition)?;
+                self.check_expr(&conditional.node.then_expression)?;
+                self.check_expr(&conditional.node.else_expression)
+            }
+            Expression::Comma(exprs) => exprs.iter().try_for_each(|expr| self.check_expr(expr)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that `operand` of `expr` is a modifiable lvalue, where `message` describes the role
+    /// of the operand, e.g., "increment operand".
+    fn require_modifiable(
+        &self,
+        operand: &Expression,
+        message: &str,
+        expr: &Node<Expression>,
+    ) -> Result<(), IrgenError> {
+        let lvalue = some_or!(
+            self.classify(operand),
+            return Err(error(
+                expr,
+                IrgenErrorMessage::RequireLvalue {
+                    message: message.to_string(),
+                },
+            ))
+        );
+
+        match self.read_only_reason(&lvalue) {
+            Some(reason) => Err(error(
+                expr,
+                IrgenErrorMessage::NonModifiableLvalue {
+                    message: message.to_string(),
+                    reason: reason.to_string(),
+                },
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns why `lvalue` may not be modified, or `None` if it is a modifiable lvalue.
+    fn read_only_reason(&self, lvalue: &Lvalue) -> Option<&'static str> {
+        if lvalue.in_string_literal {
+            return Some("a string literal");
+        }
+
+        let structs = &*self.structs;
+        match lvalue.dtype.as_ref()? {
+            ir::Dtype::Array { .. } => Some("an array"),
+            ir::Dtype::Function { .. } => Some("a function"),
+            dtype if dtype.is_const() => Some("`const`-qualified"),
+            dtype @ ir::Dtype::Struct { name: Some(_), .. }
+                if dtype.is_complete(structs) && dtype.is_immutable(structs) =>
+            {
+                Some("a struct with a `const` member")
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the lvalue `expr` designates, or `None` if it is an rvalue.
+    ///
+    /// The expressions of unknown types, e.g., the undeclared variables, are regarded as
+    /// modifiable lvalues so that only the definite errors are reported.
+    fn classify(&self, expr: &Expression) -> Option<Lvalue> {
+        let (dtype, in_string_literal) = match expr {
+            Expression::Identifier(identifier) => match self.lookup(&identifier.node.name) {
+                Some(Some(ir::Dtype::Function { .. })) => return None,
+                dtype => (dtype.flatten(), false),
+            },
+            Expression::StringLiteral(_) => (None, true),
+            Expression::Member(member) => {
+                // The member of an rvalue, e.g., `f().x`, is an rvalue as well.
+                if member.node.operator.node == MemberOperator::Direct
+                    && self.classify(&member.node.expression.node).is_none()
+                {
+                    return None;
+                }
+                (self.dtype_of(expr), false)
+            }
+            Expression::UnaryOperator(unary)
+                if unary.node.operator.node == UnaryOperator::Indirection =>
+            {
+                let operand = &unary.node.operand.node;
+                (self.dtype_of(expr), points_into_string_literal(operand))
+            }
+            Expression::BinaryOperator(binary)
+                if binary.node.operator.node == BinaryOperator::Index =>
+            {
+                let in_string_literal = points_into_string_literal(&binary.node.lhs.node)
+                    || points_into_string_literal(&binary.node.rhs.node);
+                (self.dtype_of(expr), in_string_literal)
+            }
+            Expression::CompoundLiteral(_) => (self.dtype_of(expr), false),
+            _ => return None,
+        };
+
+        Some(Lvalue {
+            dtype,
+            in_string_literal,
+        })
+    }
+
+    /// Returns the type of `expr` if it may designate an object or point to one, or `None` if
+    /// unknown.
+    fn dtype_of(&self, expr: &Expression) -> Option<ir::Dtype> {
+        match expr {
+            Expression::Identifier(identifier) => self.lookup(&identifier.node.name)?,
+            Expression::StringLiteral(_) => Some(ir::Dtype::pointer(ir::Dtype::CHAR)),
+            Expression::Member(member) => {
+                let base = self.dtype_of(&member.node.expression.node)?;
+                let base = match member.node.operator.node {
+                    MemberOperator::Direct => base,
+                    MemberOperator::Indirect => pointee(&base)?,
+                };
+                let structs = &*self.structs;
+                if !matches!(base, ir::Dtype::Struct { name: Some(_), .. })
+                    || !base.is_complete(structs)
+                {
+                    return None;
+                }
+                let (_, field) = base
+                    .get_offset_struct_field(&member.node.identifier.node.name, structs)
+                    .ok()?;
+                // The members of a `const` struct are `const` as well.
+                Some(field.merge_const(base.is_const()))
+            }
+            Expression::Call(call) => {
+                let callee = self.dtype_of(&call.node.callee.node)?;
+                let callee = pointee(&callee).unwrap_or(callee);
+                callee.get_function_inner().map(|(ret, _)| ret.clone())
+            }
+            Expression::CompoundLiteral(literal) => {
+                self.dtype_of_type_name(&literal.node.type_name)
+            }
+            Expression::UnaryOperator(unary) => {
+                let operand = &unary.node.operand.node;
+                match unary.node.operator.node {
+                    UnaryOperator::Indirection => pointee(&self.dtype_of(operand)?),
+                    UnaryOperator::Address => Some(ir::Dtype::pointer(self.dtype_of(operand)?)),
+                    _ => None,
+                }
+            }
+            Expression::Cast(cast) => self.dtype_of_type_name(&cast.node.type_name),
+            Expression::BinaryOperator(binary) => {
+                let lhs = &binary.node.lhs.node;
+                let rhs = &binary.node.rhs.node;
+                match &binary.node.operator.node {
+                    BinaryOperator::Index => {
+                        let base = self
+                            .dtype_of(lhs)
+                            .filter(|dtype| pointee(dtype).is_some())
+                            .or_else(|| self.dtype_of(rhs))?;
+                        pointee(&base)
+                    }
+                    BinaryOperator::Plus | BinaryOperator::Minus => {
+                        let base = self
+                            .dtype_of(lhs)
+                            .filter(|dtype| pointee(dtype).is_some())
+                            .or_else(|| self.dtype_of(rhs))?;
+                        pointee(&base).map(ir::Dtype::pointer)
+                    }
+                    operator if is_assignment(operator) => {
+                        self.dtype_of(lhs).map(|dtype| dtype.strip_outer_const())
+                    }
+                    _ => None,
+                }
+            }
+            Expression::Conditional(conditional) => {
+                self.dtype_of(&conditional.node.then_expression.node)
+            }
+            Expression::Comma(exprs) => self.dtype_of(&exprs.last()?.node),
+            _ => None,
+        }
+    }
+
+    fn dtype_of_type_name(&self, type_name: &Node<TypeName>) -> Option<ir::Dtype> {
+        ir::Dtype::try_from(&type_name.node)
+            .ok()?
+            .resolve_typedefs(&self.typedefs())
+            .ok()
+    }
+
+    /// Returns the type of the variable `name`, which is `Some(None)` if it is a local variable of
+    /// an unknown type, or `None` if it is not declared.
+    fn lookup(&self, name: &str) -> Option<Option<ir::Dtype>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .map(|symbol| match symbol {
+                Symbol::Variable(dtype) => dtype.clone(),
+                // A typedef name designates no object.
+                Symbol::Typedef(_) => None,
+            })
+            .or_else(|| self.irgen.decls.get(name).map(|decl| Some(decl.dtype())))
+    }
+}
+
+fn error(expr: &Node<Expression>, message: IrgenErrorMessage) -> IrgenError {
+    IrgenError::new(format!("{:#?}", expr.node), message).with_span(expr.span)
+}
+
+fn is_assignment(operator: &BinaryOperator) -> bool {
+    matches!(
+        operator,
+        BinaryOperator::Assign
+            | BinaryOperator::AssignMultiply
+            | BinaryOperator::AssignDivide
+            | BinaryOperator::AssignModulo
+            | BinaryOperator::AssignPlus
+            | BinaryOperator::AssignMinus
+            | BinaryOperator::AssignShiftLeft
+            | BinaryOperator::AssignShiftRight
+            | BinaryOperator::AssignBitwiseAnd
+            | BinaryOperator::AssignBitwiseXor
+            | BinaryOperator::AssignBitwiseOr
+    )
+}
+
+/// Returns the type pointed to by a value of `dtype`, which decays into a pointer if an array.
+fn pointee(dtype: &ir::Dtype) -> Option<ir::Dtype> {
+    dtype
+        .get_pointer_inner()
+        .or_else(|| dtype.get_array_inner())
+        .cloned()
+}
+
+/// Whether `expr` is a pointer into a string literal, e.g., `"str" + 1`.
+fn points_into_string_literal(expr: &Expression) -> bool {
+    match expr {
+        Expression::StringLiteral(_) => true,
+        Expression::BinaryOperator(binary) => {
+            matches!(
+                binary.node.operator.node,
+                BinaryOperator::Plus | BinaryOperator::Minus
+            ) && (points_into_string_literal(&binary.node.lhs.node)
+                || points_into_string_literal(&binary.node.rhs.node))
+        }
+        _ => false,
+    }
+}
@@ -26,6 +26,17 @@
 //! The skeleton code roughly consists of the code for the first two videos, but you should still
 //! watch them to have an idea of what the code is like.
 //!
+//! ### Evaluation Order
+//!
+//! C leaves unspecified the order in which the arguments of a call and the operands of most
+//! operators are evaluated, e.g., whether `g()` or `h()` is called first in `f(g(), h())` or
+//! `g() - h()`. KECC evaluates them from left to right: the callee before the arguments, the
+//! arguments in order, the left operand of a binary operator before the right one, including the
+//! lvalue of an assignment before its value, and the items of an initializer list in order. The
+//! interpreter follows the same order as it executes the generated IR. Since other compilers may
+//! choose another order, the tests depending on it compare with the interpreter only (see
+//! [`crate::test_irgen`]).
+//!
 //! [slides]: https://docs.google.com/presentation/d/1SqtU-Cn60Sd1jkbO0OSsRYKPMIkul0eZoYG9KpMugFE/edit?usp=sharing
 //! [ir]: https://youtu.be/7CY_lX5ZroI
 //! [irgen-overview]: https://youtu.be/YPtnXlKDSYo
@@ -35,36 +46,65 @@
 //! [irgen-stmt-2]: https://youtu.be/UkaXaNw462U
 //! [github-qna-irgen]: https://github.com/kaist-cp/cs420/labels/homework%20-%20irgen
 #![allow(dead_code)]
+mod bit_field;
+mod bounds;
+mod constexpr;
+mod hooks;
+mod lvalue;
+mod static_local;
+
 use core::convert::TryFrom;
 use core::fmt;
 use core::mem;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::ops::Deref;
 
 use lang_c::ast::*;
 use lang_c::driver::Parse;
-use lang_c::span::Node;
+use lang_c::span::{Node, Span};
 use thiserror::Error;
 
+use crate::c::name_of_declarator;
 use crate::ir::{DtypeError, HasDtype, Named};
 use crate::*;
 
 use itertools::izip;
 
+pub use hooks::{IrgenBuilder, IrgenHooks, StatementCoverage};
+
 #[derive(Debug)]
 pub struct IrgenError {
     pub code: String,
     pub message: IrgenErrorMessage,
+    /// The span of the offending expression in the source, if known
+    pub span: Option<Span>,
+    /// The location of the offending expression, or of the external declaration containing it, in
+    /// the original source, if the line map of the source is known (see [`Irgen::with_line_map`])
+    pub location: Option<Location>,
 }
 
 impl IrgenError {
     pub fn new(code: String, message: IrgenErrorMessage) -> Self {
-        Self { code, message }
+        Self {
+            code,
+            message,
+            span: None,
+            location: None,
+        }
+    }
+
+    /// Attaches the span of the offending expression.
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
     }
 }
 
 impl fmt::Display for IrgenError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(location) = &self.location {
+            write!(f, "{location}: ")?;
+        }
         write!(f, "error: {}\r\n\r\ncode: {}", self.message, self.code)
     }
 }
@@ -87,6 +127,14 @@ pub enum IrgenErrorMessage {
     InvalidDtype { dtype_error: DtypeError },
     #[error("l-value required as {message}")]
     RequireLvalue { message: String },
+    #[error("{message} is not a modifiable l-value, as it is {reason}")]
+    NonModifiableLvalue { message: String, reason: String },
+    #[error("unknown builtin `{name}`")]
+    UnknownBuiltin { name: String },
+    #[error("initializer element is not a constant expression: {reason}")]
+    NotConstantExpression { reason: String },
+    #[error("the IR inserted by the hooks is ill-formed: {error}")]
+    InvalidHookOutput { error: ir::VerifierError },
 }
 
 #[derive(Default, Debug)]
@@ -95,6 +143,20 @@ pub struct Irgen {
     typedefs: HashMap<String, ir::Dtype>,
     structs: HashMap<String, Option<ir::Dtype>>,
     struct_tempid_counter: usize,
+    /// The values of the enumerators declared at file scope, which are constants of type `int`
+    /// annotated with their names
+    enumerators: HashMap<String, ir::Constant>,
+    /// Functions whose bodies are not lowered, see [`Irgen::skip_definitions`]
+    skipped: HashSet<String>,
+    /// The globals holding the static local variables by the names of their functions and their
+    /// own, in the order of their declarations, see [`Irgen::add_static_locals`]
+    static_locals: BTreeMap<(String, String), Vec<String>>,
+    /// Callbacks observing the lowering, see [`Irgen::with_hooks`]
+    hooks: Option<Box<dyn IrgenHooks>>,
+    /// The line map of the source, locating the errors, see [`Irgen::with_line_map`]
+    line_map: Option<LineMap>,
+    /// The accesses out of the bounds of arrays found so far, see [`Irgen::take_bounds_warnings`]
+    bounds_warnings: Vec<ir::BoundsWarning>,
 }
 
 impl Translate<Parse> for Irgen {
@@ -111,41 +173,163 @@ impl Translate<TranslationUnit> for Irgen {
     type Error = IrgenError;
 
     fn translate(&mut self, source: &TranslationUnit) -> Result<Self::Target, Self::Error> {
+        let (unit, errors) = self.translate_keep_going(source);
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(unit),
+        }
+    }
+}
+
+impl Irgen {
+    const BID_INIT: ir::BlockId = ir::BlockId(0);
+    // `0` is used to create `BID_INIT`
+    const BID_COUNTER_INIT: usize = 1;
+    const TEMPID_COUNTER_INIT: usize = 0;
+
+    /// Skips lowering the bodies of the functions in `names`, which are only declared in the
+    /// result, e.g., because their definitions are reused from the incremental cache.
+    pub fn skip_definitions(mut self, names: HashSet<String>) -> Self {
+        self.skipped = names;
+        self
+    }
+
+    /// Invokes `hooks` while lowering, e.g., to instrument the result.
+    ///
+    /// The result is verified once lowered, so that ill-formed IR inserted by the hooks is reported
+    /// as an error instead of being silently miscompiled.
+    pub fn with_hooks(mut self, hooks: Box<dyn IrgenHooks>) -> Self {
+        self.hooks = Some(hooks);
+        self
+    }
+
+    /// Locates the errors in the original source by `line_map`, that of the preprocessed source
+    /// the spans of the AST are in (see [`crate::Parse::translate_with_line_map`]).
+    pub fn with_line_map(mut self, line_map: LineMap) -> Self {
+        self.line_map = Some(line_map);
+        self
+    }
+
+    /// Takes back the hooks given to [`Irgen::with_hooks`], e.g., to inspect their state.
+    pub fn take_hooks(&mut self) -> Option<Box<dyn IrgenHooks>> {
+        self.hooks.take()
+    }
+
+    /// Takes the warnings about the constant indices, builtins and initializers out of the bounds
+    /// of the arrays in the translated source. Those of the initializers are located in the
+    /// source (see [`Irgen::with_line_map`]), and those of the accesses at the instructions they
+    /// are lowered to.
+    pub fn take_bounds_warnings(&mut self) -> Vec<ir::BoundsWarning> {
+        mem::take(&mut self.bounds_warnings)
+    }
+
+    /// Translates `source` without stopping at the first error.
+    ///
+    /// External declarations are processed in order, so globals, typedefs and structs are visible
+    /// to every function defined after them. Each function definition is lowered independently:
+    /// its signature is registered before its body is translated, so a function whose body fails
+    /// to lower remains in the result as a declaration without a definition. A construct the IR
+    /// generator does not lower yet is reported as [`IrgenErrorMessage::Misc`] the same way.
+    ///
+    /// Returns the partially translated unit together with all errors, in source order.
+    pub fn translate_keep_going(
+        &mut self,
+        source: &TranslationUnit,
+    ) -> (ir::TranslationUnit, Vec<IrgenError>) {
+        let mut errors = Vec::new();
+
         for ext_decl in &source.0 {
-            match ext_decl.node {
-                ExternalDeclaration::Declaration(ref var) => {
-                    self.add_declaration(&var.node)?;
-                }
-                ExternalDeclaration::StaticAssert(_) => {
-                    panic!("ExternalDeclaration::StaticAssert is unsupported")
+            let result = match ext_decl.node {
+                ExternalDeclaration::Declaration(ref var) => self.add_declaration(&var.node, None),
+                ExternalDeclaration::StaticAssert(ref assert) => {
+                    self.add_static_assert(&assert.node)
                 }
                 ExternalDeclaration::FunctionDefinition(ref func) => {
-                    self.add_function_definition(&func.node)?;
+                    self.add_function_definition(&func.node)
                 }
+            };
+
+            if let Err(mut error) = result {
+                // An error without the span of its expression is located at its declaration.
+                let span = error.span.unwrap_or(ext_decl.span);
+                error.location = self
+                    .line_map
+                    .as_ref()
+                    .map(|line_map| line_map.location(span.start));
+                errors.push(error);
             }
         }
 
         let decls = mem::take(&mut self.decls);
         let structs = mem::take(&mut self.structs);
-        Ok(Self::Target { decls, structs })
+        let mut unit = ir::TranslationUnit { decls, structs };
+        self.check_access_bounds(&unit);
+
+        if let Some(hooks) = self.hooks.as_mut() {
+            hooks.on_unit_end(&mut unit);
+            if let Err(error) = ir::verify(&unit) {
+                errors.push(IrgenError::new(
+                    String::new(),
+                    IrgenErrorMessage::InvalidHookOutput { error },
+                ));
+            }
+        }
+
+        (unit, errors)
     }
-}
 
-impl Irgen {
-    const BID_INIT: ir::BlockId = ir::BlockId(0);
-    // `0` is used to create `BID_INIT`
-    const BID_COUNTER_INIT: usize = 1;
-    const TEMPID_COUNTER_INIT: usize = 0;
+    /// Checks the `_Static_assert` at file scope, whose expression must be a nonzero constant.
+    fn add_static_assert(&self, source: &StaticAssert) -> Result<(), IrgenError> {
+        let error = |message: IrgenErrorMessage| {
+            IrgenError::new(format!("{source:#?}"), message).with_span(source.expression.span)
+        };
+        let value = self
+            .evaluate(&source.expression.node, &ir::Dtype::INT)
+            .map_err(|reason| error(IrgenErrorMessage::NotConstantExpression { reason }))?;
+        let is_zero = ir::Constant::try_from(&value)
+            .ok()
+            .and_then(|constant| constant.get_int())
+            .map_or(false, |(value, _, _)| value == 0);
+        if is_zero {
+            return Err(error(IrgenErrorMessage::Misc {
+                message: format!("static assertion failed: {}", source.message.node.concat()),
+            }));
+        }
+
+        Ok(())
+    }
 
     /// Add a declaration. It can be either a struct, typedef, or a variable.
-    fn add_declaration(&mut self, source: &Declaration) -> Result<(), IrgenError> {
-        let (base_dtype, is_typedef) =
+    ///
+    /// If `function` is given, `source` declares static local variables of the function, which are
+    /// added as globals of their own (see [`Irgen::add_static_locals`]).
+    fn add_declaration(
+        &mut self,
+        source: &Declaration,
+        function: Option<&str>,
+    ) -> Result<(), IrgenError> {
+        let (base_dtype, storage) =
             ir::Dtype::try_from_ast_declaration_specifiers(&source.specifiers).map_err(|e| {
                 IrgenError::new(
                     format!("{source:#?}"),
                     IrgenErrorMessage::InvalidDtype { dtype_error: e },
                 )
             })?;
+        // The enumerators are registered before resolving the typedefs replaces the enum type.
+        if let Some(enumerators) = base_dtype.get_enumerators() {
+            for (name, value) in enumerators {
+                if self.decls.contains_key(name) || self.enumerators.contains_key(name) {
+                    return Err(IrgenError::new(
+                        format!("{source:#?}"),
+                        IrgenErrorMessage::Redefinition { name: name.clone() },
+                    ));
+                }
+                // The uses of the enumerator are annotated with its name in the IR dumps.
+                let constant =
+                    ir::Constant::int(*value as u128, ir::Dtype::INT).with_origin(name.clone());
+                let _unused = self.enumerators.insert(name.clone(), constant);
+            }
+        }
         let base_dtype = base_dtype.resolve_typedefs(&self.typedefs).map_err(|e| {
             IrgenError::new(
                 format!("{source:#?}"),
@@ -153,6 +337,10 @@ impl Irgen {
             )
         })?;
 
+        let is_typedef = storage == ir::DeclarationStorage::Typedef;
+        let is_extern = storage == ir::DeclarationStorage::Extern;
+        let is_static = storage == ir::DeclarationStorage::Static;
+
         let base_dtype = if let ir::Dtype::Struct { name, fields, .. } = &base_dtype {
             if let Some(name) = name {
                 let _ = self.structs.entry(name.to_string()).or_insert(None);
@@ -173,6 +361,17 @@ impl Irgen {
         } else {
             base_dtype
         };
+        let align = ir::Dtype::align_of_ast_declaration_specifiers(
+            &source.specifiers,
+            &self.typedefs,
+            &self.structs,
+        )
+        .map_err(|e| {
+            IrgenError::new(
+                format!("{source:#?}"),
+                IrgenErrorMessage::InvalidDtype { dtype_error: e },
+            )
+        })?;
 
         for init_decl in &source.declarators {
             let declarator = &init_decl.node.declarator.node;
@@ -185,6 +384,7 @@ impl Irgen {
                         format!("{source:#?}"),
                         IrgenErrorMessage::InvalidDtype { dtype_error: e },
                     )
+                    .with_span(init_decl.node.declarator.span)
                 })?
                 .deref()
                 .clone();
@@ -194,11 +394,40 @@ impl Irgen {
                     IrgenErrorMessage::InvalidDtype { dtype_error: e },
                 )
             })?;
-            if !is_typedef && is_invalid_structure(&dtype, &self.structs) {
+            // An array of unknown size is completed by its initializer, e.g., `int a[] = {1, 2}`.
+            let dtype = match init_decl.node.initializer.as_ref().map(|i| &i.node) {
+                Some(Initializer::List(items)) if dtype.is_incomplete_array() => {
+                    dtype.with_array_size(items.len())
+                }
+                _ => dtype,
+            };
+            let dtype = if let Some(align) = align {
+                dtype.with_align(align, &self.structs).map_err(|e| {
+                    IrgenError::new(
+                        format!("{source:#?}"),
+                        IrgenErrorMessage::InvalidDtype { dtype_error: e },
+                    )
+                })?
+            } else {
+                dtype
+            };
+            // `extern` declares a variable defined in another unit, unless it is initialized.
+            let is_extern = is_extern && init_decl.node.initializer.is_none();
+
+            // Only the variables defined here need a complete type, while `void` ones are rejected
+            // below.
+            if !is_typedef
+                && !is_extern
+                && !matches!(dtype, ir::Dtype::Function { .. } | ir::Dtype::Unit { .. })
+                && !dtype.is_complete(&self.structs)
+            {
                 return Err(IrgenError::new(
                     format!("{source:#?}"),
-                    IrgenErrorMessage::Misc {
-                        message: "incomplete struct type".to_string(),
+                    IrgenErrorMessage::InvalidDtype {
+                        dtype_error: DtypeError::IncompleteType {
+                            dtype,
+                            usage: format!("variable `{name}`"),
+                        },
                     },
                 ));
             }
@@ -210,7 +439,7 @@ impl Irgen {
                     .entry(name.clone())
                     .or_insert_with(|| dtype.clone());
 
-                if prev_dtype != &dtype {
+                if !prev_dtype.is_same_type(&dtype) {
                     return Err(IrgenError::new(
                         format!("{source:#?}"),
                         IrgenErrorMessage::ConflictingDtype {
@@ -230,10 +459,52 @@ impl Irgen {
                     IrgenErrorMessage::InvalidDtype { dtype_error: e },
                 )
             })?;
+            match &mut decl {
+                ir::Declaration::Variable {
+                    is_extern: is_extern_variable,
+                    is_static: is_static_variable,
+                    ..
+                } => {
+                    *is_extern_variable = is_extern;
+                    *is_static_variable = is_static;
+                }
+                ir::Declaration::Function {
+                    is_static: is_static_function,
+                    ..
+                } => *is_static_function = is_static,
+            }
+            let name = match (function, &decl) {
+                (Some(function), ir::Declaration::Variable { .. }) => {
+                    self.static_local_name(function, &name)
+                }
+                (Some(_), ir::Declaration::Function { .. }) => {
+                    return Err(IrgenError::new(
+                        format!("{source:#?}"),
+                        IrgenErrorMessage::Misc {
+                            message: format!("invalid storage class for function `{name}`"),
+                        },
+                    ));
+                }
+                (None, _) => name,
+            };
 
             // If `initializer` exists, convert initializer to a constant value
             if let Some(initializer) = init_decl.node.initializer.as_ref() {
-                if !is_valid_initializer(&initializer.node, &dtype, &self.structs) {
+                self.check_initializer_lvalues(&initializer.node)?;
+                let decl_span = init_decl.node.declarator.span;
+                self.check_initializer_bounds(&dtype, initializer, decl_span);
+
+                // Evaluates the expressions that are not literals, e.g., `1 << 4`.
+                let initializer =
+                    self.fold_initializer(&initializer.node, &dtype)
+                        .map_err(|reason| {
+                            IrgenError::new(
+                                format!("{source:#?}"),
+                                IrgenErrorMessage::NotConstantExpression { reason },
+                            )
+                            .with_span(initializer.span)
+                        })?;
+                if !is_valid_initializer(&initializer, &dtype, &self.structs, &self.decls) {
                     return Err(IrgenError::new(
                         format!("{source:#?}"),
                         IrgenErrorMessage::Misc {
@@ -241,6 +512,16 @@ impl Irgen {
                         },
                     ));
                 }
+                // An aggregate initialized to all zeros, whose list is folded to `{}`, has the
+                // `ZeroInit` value, which is also the value of a variable without an initializer,
+                // e.g., `int a[4] = {0};`.
+                let is_zero_init =
+                    matches!(&initializer, Initializer::List(items) if items.is_empty());
+                let initializer = if is_zero_init {
+                    None
+                } else {
+                    Some(initializer)
+                };
 
                 match &mut decl {
                     ir::Declaration::Variable {
@@ -253,7 +534,7 @@ impl Irgen {
                                 IrgenErrorMessage::Redefinition { name },
                             ));
                         }
-                        *var_initializer = Some(initializer.node.clone());
+                        *var_initializer = initializer;
                     }
                     ir::Declaration::Function { .. } => {
                         return Err(IrgenError::new(
@@ -283,15 +564,15 @@ impl Irgen {
         let name_of_params = name_of_params_from_function_declarator(declarator)
             .expect("declarator is not from function definition");
 
-        let (base_dtype, is_typedef) = ir::Dtype::try_from_ast_declaration_specifiers(specifiers)
+        let (base_dtype, storage) = ir::Dtype::try_from_ast_declaration_specifiers(specifiers)
             .map_err(|e| {
-            IrgenError::new(
-                format!("specs: {specifiers:#?}\ndecl: {declarator:#?}"),
-                IrgenErrorMessage::InvalidDtype { dtype_error: e },
-            )
-        })?;
+                IrgenError::new(
+                    format!("specs: {specifiers:#?}\ndecl: {declarator:#?}"),
+                    IrgenErrorMessage::InvalidDtype { dtype_error: e },
+                )
+            })?;
 
-        if is_typedef {
+        if storage == ir::DeclarationStorage::Typedef {
             return Err(IrgenError::new(
                 format!("specs: {specifiers:#?}\ndecl: {declarator:#?}"),
                 IrgenErrorMessage::Misc {
@@ -307,6 +588,7 @@ impl Irgen {
                     format!("specs: {specifiers:#?}\ndecl: {declarator:#?}"),
                     IrgenErrorMessage::InvalidDtype { dtype_error: e },
                 )
+                .with_span(source.declarator.span)
             })?
             .deref()
             .clone();
@@ -319,11 +601,45 @@ impl Irgen {
 
         let signature = ir::FunctionSignature::new(dtype.clone());
 
+        // Unlike a prototype, a definition needs the sizes of its parameters and return value.
+        let incomplete = izip!(&signature.params, &name_of_params)
+            .find(|(param, _)| !param.is_complete(&self.structs))
+            .map(|(param, name)| (param.clone(), format!("parameter `{name}`")))
+            .or_else(|| {
+                let ret = &signature.ret;
+                (!ret.is_complete(&self.structs) && !matches!(ret, ir::Dtype::Unit { .. }))
+                    .then(|| (ret.clone(), format!("the return type of `{name}`")))
+            });
+        if let Some((dtype, usage)) = incomplete {
+            return Err(IrgenError::new(
+                format!("specs: {specifiers:#?}\ndecl: {declarator:#?}"),
+                IrgenErrorMessage::InvalidDtype {
+                    dtype_error: DtypeError::IncompleteType { dtype, usage },
+                },
+            ));
+        }
+
         // Adds new declaration if nothing has been declared before
-        let decl = ir::Declaration::try_from(dtype).unwrap();
+        let mut decl = ir::Declaration::try_from(dtype).unwrap();
+        if let ir::Declaration::Function { is_static, .. } = &mut decl {
+            *is_static = storage == ir::DeclarationStorage::Static;
+        }
         self.add_decl(&name, decl)?;
 
-        // Prepare scope for global variable
+        // Rejects, e.g., `5 = x` before lowering it to a store to a temporary.
+        let params = izip!(&name_of_params, &signature.params)
+            .map(|(name, dtype)| (name.clone(), dtype.clone()))
+            .collect::<Vec<_>>();
+        self.check_lvalues(source, &params)?;
+
+        // The cached definitions of the skipped functions also refer to their static locals.
+        let static_locals = self.add_static_locals(&name, &source.statement.node)?;
+
+        if self.skipped.contains(&name) {
+            return Ok(());
+        }
+
+        // Prepare scope for global variable, where the enumerators stand for their values.
         let global_scope: HashMap<_, _> = self
             .decls
             .iter()
@@ -333,6 +649,11 @@ impl Irgen {
                 let operand = ir::Operand::constant(pointer);
                 (name.clone(), operand)
             })
+            .chain(
+                self.enumerators
+                    .iter()
+                    .map(|(name, value)| (name.clone(), ir::Operand::constant(value.clone()))),
+            )
             .collect();
 
         // Prepares for irgen pass.
@@ -348,6 +669,8 @@ impl Irgen {
             structs: &self.structs,
             // Initial symbol table has scope for global variable already
             symbol_table: vec![global_scope],
+            hooks: self.hooks.as_mut(),
+            static_locals,
         };
         let mut context = Context::new(irgen.bid_init);
 
@@ -360,12 +683,22 @@ impl Irgen {
             .map_err(|e| {
                 IrgenError::new(format!("specs: {specifiers:#?}\ndecl: {declarator:#?}"), e)
             })?;
+        irgen
+            .run_hooks(&mut context, |hooks, builder| {
+                hooks.on_function_start(&name, builder)
+            })
+            .map_err(|e| IrgenError::new(name.clone(), e))?;
 
         // Translates statement.
         irgen.translate_stmt(&source.statement.node, &mut context, None, None)?;
+        irgen
+            .run_hooks(&mut context, |hooks, builder| {
+                hooks.on_function_end(&name, builder)
+            })
+            .map_err(|e| IrgenError::new(name.clone(), e))?;
 
         // Creates the end block
-        let ret = signature.ret.set_const(false);
+        let ret = signature.ret.strip_outer_const();
         let value = if ret == ir::Dtype::unit() {
             ir::Operand::constant(ir::Constant::unit())
         } else if ret == ir::Dtype::INT {
@@ -416,8 +749,40 @@ impl Irgen {
 
     /// Adds a possibly existing declaration.
     ///
-    /// Returns error if the previous declearation is incompatible with `decl`.
-    fn add_decl(&mut self, name: &str, decl: ir::Declaration) -> Result<(), IrgenError> {
+    /// Returns error if the previous declearation is incompatible with `decl`, or `name` is
+    /// reserved for builtins but is not a supported one. An `extern` declaration of a variable
+    /// does not replace the previous one, which may be its definition, and a function declared
+    /// without `static` keeps the internal linkage of its previous declaration.
+    fn add_decl(&mut self, name: &str, mut decl: ir::Declaration) -> Result<(), IrgenError> {
+        if ir::Builtin::is_reserved(name) && ir::Builtin::from_name(name).is_none() {
+            return Err(IrgenError::new(
+                name.to_string(),
+                IrgenErrorMessage::UnknownBuiltin {
+                    name: name.to_string(),
+                },
+            ));
+        }
+
+        if self.enumerators.contains_key(name) {
+            return Err(IrgenError::new(
+                name.to_string(),
+                IrgenErrorMessage::Redefinition {
+                    name: name.to_string(),
+                },
+            ));
+        }
+
+        // E.g., `static int f(void); int f(void) { ... }`, where `f` is still `static`.
+        if let (
+            Some(ir::Declaration::Function {
+                is_static: true, ..
+            }),
+            ir::Declaration::Function { is_static, .. },
+        ) = (self.decls.get(name), &mut decl)
+        {
+            *is_static = true;
+        }
+
         let old_decl = some_or!(
             self.decls.insert(name.to_string(), decl.clone()),
             return Ok(())
@@ -434,6 +799,23 @@ impl Irgen {
             ));
         }
 
+        // E.g., `static int x; int x;`, while `static int x; extern int x;` declares the same `x`.
+        if !decl.is_extern() && old_decl.is_static() != decl.is_static() {
+            let message = if decl.is_static() {
+                format!("static declaration of `{name}` follows non-static declaration")
+            } else {
+                format!("non-static declaration of `{name}` follows static declaration")
+            };
+            return Err(IrgenError::new(
+                name.to_string(),
+                IrgenErrorMessage::Misc { message },
+            ));
+        }
+
+        if decl.is_extern() {
+            let _unused = self.decls.insert(name.to_string(), old_decl);
+        }
+
         Ok(())
     }
 }
@@ -495,6 +877,11 @@ struct IrgenFunc<'i> {
     structs: &'i HashMap<String, Option<ir::Dtype>>,
     /// Current symbol table. The initial symbol table has the global variables.
     symbol_table: Vec<HashMap<String, ir::Operand>>,
+    /// Callbacks observing the lowering, if any.
+    hooks: Option<&'i mut Box<dyn IrgenHooks>>,
+    /// The globals holding the static local variables by their names, each bound to the next one
+    /// when its declaration is lowered, see [`Irgen::add_static_locals`].
+    static_locals: HashMap<String, VecDeque<ir::Operand>>,
 }
 
 impl IrgenFunc<'_> {
@@ -572,16 +959,54 @@ impl IrgenFunc<'_> {
         Ok(())
     }
 
+    /// Invokes `hook` on the hooks, if any, inserting IR at the end of `context`.
+    fn run_hooks<F>(&mut self, context: &mut Context, hook: F) -> Result<(), IrgenErrorMessage>
+    where
+        F: FnOnce(&mut dyn IrgenHooks, &mut IrgenBuilder<'_>) -> Result<(), IrgenErrorMessage>,
+    {
+        let hooks = some_or!(self.hooks.as_deref_mut(), return Ok(()));
+        let mut builder = IrgenBuilder {
+            context,
+            allocations: &mut self.allocations,
+            symbol_table: &self.symbol_table,
+        };
+        hook(&mut **hooks, &mut builder)
+    }
+
+    /// Notifies the hooks that `expr` is lowered to `value` under the current block `context`.
+    ///
+    /// The translation of expressions, left to be implemented along with `translate_stmt`, must call
+    /// it with the value of each expression it lowers.
+    fn expression_lowered(
+        &mut self,
+        expr: &Expression,
+        value: &ir::Operand,
+        context: &mut Context,
+    ) -> Result<(), IrgenErrorMessage> {
+        self.run_hooks(context, |hooks, builder| {
+            hooks.on_expression_lowered(expr, value, builder)
+        })
+    }
+
     /// Transalte a C statement `stmt` under the current block `context`, with `continue` block
     /// `bid_continue` and break block `bid_break`.
     fn translate_stmt(
         &mut self,
-        _stmt: &Statement,
-        _context: &mut Context,
+        stmt: &Statement,
+        context: &mut Context,
         _bid_continue: Option<ir::BlockId>,
         _bid_break: Option<ir::BlockId>,
     ) -> Result<(), IrgenError> {
-        todo!("Homework: IR Generation")
+        self.run_hooks(context, |hooks, builder| hooks.on_statement(stmt, builder))
+            .map_err(|e| IrgenError::new(format!("{stmt:#?}"), e))?;
+
+        // Homework: IR Generation
+        Err(IrgenError::new(
+            format!("{stmt:#?}"),
+            IrgenErrorMessage::Misc {
+                message: "the lowering of statements is not implemented".to_string(),
+            },
+        ))
     }
 
     /// Translate parameter declaration of the functions to IR.
@@ -645,22 +1070,20 @@ impl IrgenFunc<'_> {
     /// [foo]: https://github.com/kaist-cp/kecc-public/blob/main/examples/c/foo.c
     fn translate_parameter_decl(
         &mut self,
-        _signature: &ir::FunctionSignature,
+        signature: &ir::FunctionSignature,
         _bid_init: ir::BlockId,
         _name_of_params: &[String],
         _context: &mut Context,
     ) -> Result<(), IrgenErrorMessage> {
-        todo!("Homework: IR Generation")
-    }
-}
+        // Without parameters, there is no argument to store.
+        if signature.params.is_empty() {
+            return Ok(());
+        }
 
-#[inline]
-fn name_of_declarator(declarator: &Declarator) -> String {
-    let declarator_kind = &declarator.kind;
-    match &declarator_kind.node {
-        DeclaratorKind::Abstract => panic!("DeclaratorKind::Abstract is unsupported"),
-        DeclaratorKind::Identifier(identifier) => identifier.node.name.clone(),
-        DeclaratorKind::Declarator(declarator) => name_of_declarator(&declarator.node),
+        // Homework: IR Generation
+        Err(IrgenErrorMessage::Misc {
+            message: "the lowering of parameters is not implemented".to_string(),
+        })
     }
 }
 
@@ -696,7 +1119,7 @@ fn name_of_params_from_derived_declarators(
                 return Some(name_of_params);
             }
             DerivedDeclarator::KRFunction(_kr_func_decl) => {
-                // K&R function is allowed only when it has no parameter
+                // The K&R functions with parameters are rewritten into prototypes by the parser.
                 return Some(Vec::new());
             }
             _ => (),
@@ -709,7 +1132,15 @@ fn name_of_params_from_derived_declarators(
 #[inline]
 fn name_of_parameter_declaration(parameter_declaration: &ParameterDeclaration) -> Option<String> {
     let declarator = some_or!(parameter_declaration.declarator.as_ref(), return None);
-    Some(name_of_declarator(&declarator.node))
+    // An unnamed parameter may have a nested abstract declarator, e.g., `int (*)(int)`.
+    let mut kind = &declarator.node.kind.node;
+    loop {
+        match kind {
+            DeclaratorKind::Abstract => return None,
+            DeclaratorKind::Identifier(identifier) => return Some(identifier.node.name.clone()),
+            DeclaratorKind::Declarator(declarator) => kind = &declarator.node.kind.node,
+        }
+    }
 }
 
 #[inline]
@@ -717,6 +1148,7 @@ fn is_valid_initializer(
     initializer: &Initializer,
     dtype: &ir::Dtype,
     structs: &HashMap<String, Option<ir::Dtype>>,
+    decls: &BTreeMap<String, ir::Declaration>,
 ) -> bool {
     match initializer {
         Initializer::Expression(expr) => match dtype {
@@ -727,6 +1159,14 @@ fn is_valid_initializer(
                         &unary.node.operator.node,
                         UnaryOperator::Minus | UnaryOperator::Plus
                     ),
+                    // A function designator, e.g., `f` of `int (*fp)(void) = f;`
+                    Expression::Identifier(identifier) => {
+                        dtype.get_pointer_inner().is_some()
+                            && matches!(
+                                decls.get(&identifier.node.name),
+                                Some(ir::Declaration::Function { .. })
+                            )
+                    }
                     _ => false,
                 }
             }
@@ -735,7 +1175,7 @@ fn is_valid_initializer(
         Initializer::List(items) => match dtype {
             ir::Dtype::Array { inner, .. } => items
                 .iter()
-                .all(|i| is_valid_initializer(&i.node.initializer.node, inner, structs)),
+                .all(|i| is_valid_initializer(&i.node.initializer.node, inner, structs, decls)),
             ir::Dtype::Struct { name, .. } => {
                 let name = name.as_ref().expect("struct should have its name");
                 let struct_type = structs
@@ -750,24 +1190,10 @@ fn is_valid_initializer(
                     .expect("`fields` must be `Some`");
 
                 izip!(fields, items).all(|(f, i)| {
-                    is_valid_initializer(&i.node.initializer.node, f.deref(), structs)
+                    is_valid_initializer(&i.node.initializer.node, f.deref(), structs, decls)
                 })
             }
             _ => false,
         },
     }
 }
-
-#[inline]
-fn is_invalid_structure(dtype: &ir::Dtype, structs: &HashMap<String, Option<ir::Dtype>>) -> bool {
-    // When `dtype` is `Dtype::Struct`, `structs` has real definition of `dtype`
-    if let ir::Dtype::Struct { name, fields, .. } = dtype {
-        assert!(name.is_some() && fields.is_none());
-        let name = name.as_ref().unwrap();
-        let struct_type = some_or!(structs.get(name), return true);
-
-        struct_type.is_none()
-    } else {
-        false
-    }
-}
@@ -0,0 +1,139 @@
+//! Static local variables, e.g., `hits` in `int f(void) { static int hits = 0; return ++hits; }`.
+//!
+//! A variable declared `static` in a function body has static storage duration: it is initialized
+//! once, before the program starts, and keeps its value across the calls of the function. It is
+//! thus lowered to a global with internal linkage instead of a local allocation, named after the
+//! function and the variable, e.g., `__static_f_hits`, so that each function has its own `static
+//! int x`. The globals are declared before the body is lowered, which binds the name of each
+//! variable to its global when its declaration is reached.
+
+use std::collections::{HashMap, VecDeque};
+
+use lang_c::ast::*;
+use lang_c::span::Node;
+
+use crate::ir::{self, HasDtype};
+
+use super::{Irgen, IrgenError, IrgenErrorMessage};
+
+impl Irgen {
+    /// The prefix of the names of the globals holding static local variables.
+    const STATIC_LOCAL_PREFIX: &str = "__static_";
+
+    /// Declares the globals holding the static local variables in `body` of the function
+    /// `function`, and returns them by the names of the variables, in the order of their
+    /// declarations.
+    pub(super) fn add_static_locals(
+        &mut self,
+        function: &str,
+        body: &Statement,
+    ) -> Result<HashMap<String, VecDeque<ir::Operand>>, IrgenError> {
+        let mut declarations = Vec::new();
+        static_declarations(body, &mut declarations)?;
+        for declaration in declarations {
+            self.add_declaration(declaration, Some(function))?;
+        }
+
+        let mut static_locals = HashMap::<_, VecDeque<_>>::new();
+        for ((_, name), globals) in self
+            .static_locals
+            .range((function.to_string(), String::new())..)
+            .take_while(|((owner, _), _)| owner == function)
+        {
+            let operands = globals.iter().map(|global| {
+                let dtype = self.decls[global].dtype();
+                ir::Operand::constant(ir::Constant::global_variable(global.clone(), dtype))
+            });
+            static_locals
+                .entry(name.clone())
+                .or_default()
+                .extend(operands);
+        }
+        Ok(static_locals)
+    }
+
+    /// Returns a fresh name for the global holding the static local variable `name` of the
+    /// function `function`, and records it.
+    pub(super) fn static_local_name(&mut self, function: &str, name: &str) -> String {
+        let base = format!("{}{function}_{name}", Self::STATIC_LOCAL_PREFIX);
+        let mut global = base.clone();
+        let mut suffix = 1;
+        // E.g., another `static int x` in an inner block of the same function.
+        while self.decls.contains_key(&global) {
+            global = format!("{base}_{suffix}");
+            suffix += 1;
+        }
+
+        self.static_locals
+            .entry((function.to_string(), name.to_string()))
+            .or_default()
+            .push(global.clone());
+        global
+    }
+}
+
+/// Appends the declarations of static variables in `stmt` to `declarations`, in source order.
+fn static_declarations<'a>(
+    stmt: &'a Statement,
+    declarations: &mut Vec<&'a Declaration>,
+) -> Result<(), IrgenError> {
+    match stmt {
+        Statement::Labeled(stmt) => static_declarations(&stmt.node.statement.node, declarations)?,
+        Statement::Compound(items) => {
+            for item in items {
+                match &item.node {
+                    BlockItem::Declaration(decl) if is_static(&decl.node) => {
+                        declarations.push(&decl.node)
+                    }
+                    BlockItem::Statement(stmt) => static_declarations(&stmt.node, declarations)?,
+                    BlockItem::Declaration(_) | BlockItem::StaticAssert(_) => (),
+                }
+            }
+        }
+        Statement::If(stmt) => {
+            static_declarations(&stmt.node.then_statement.node, declarations)?;
+            if let Some(stmt) = &stmt.node.else_statement {
+                static_declarations(&stmt.node, declarations)?;
+            }
+        }
+        Statement::Switch(stmt) => static_declarations(&stmt.node.statement.node, declarations)?,
+        Statement::While(stmt) => static_declarations(&stmt.node.statement.node, declarations)?,
+        Statement::DoWhile(stmt) => static_declarations(&stmt.node.statement.node, declarations)?,
+        Statement::For(stmt) => {
+            // Only `auto` and `register` variables may be declared there (see C17 6.8.5p3).
+            if let ForInitializer::Declaration(decl) = &stmt.node.initializer.node {
+                if is_static(&decl.node) {
+                    return Err(IrgenError::new(
+                        format!("{:#?}", decl.node),
+                        IrgenErrorMessage::Misc {
+                            message: "declaration of static variable in `for` loop initial \
+                                      declaration"
+                                .to_string(),
+                        },
+                    ));
+                }
+            }
+            static_declarations(&stmt.node.statement.node, declarations)?;
+        }
+        Statement::Expression(_)
+        | Statement::Return(_)
+        | Statement::Goto(_)
+        | Statement::Continue
+        | Statement::Break
+        | Statement::Asm(_) => (),
+    }
+
+    Ok(())
+}
+
+fn is_static(decl: &Declaration) -> bool {
+    decl.specifiers.iter().any(|specifier| {
+        matches!(
+            specifier.node,
+            DeclarationSpecifier::StorageClass(Node {
+                node: StorageClassSpecifier::Static,
+                ..
+            })
+        )
+    })
+}
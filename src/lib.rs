@@ -1,4 +1,8 @@
 //! KECC: KAIST Educational C Compiler.
+//!
+//! The C parser and the IR are always compiled. The IR generator, the optimizer, the interpreter,
+//! the RISC-V back-end and the test helpers are behind the cargo features `irgen`, `opt`,
+//! `interp`, `asmgen-riscv` and `testing`, respectively.
 
 #![deny(clippy::all)]
 // #![deny(rustdoc::all)]
@@ -46,29 +50,89 @@
 // Allowed for more flexible variants.
 // #![deny(variant_size_differences)]
 
+#[cfg(all(feature = "irgen", feature = "asmgen-riscv"))]
+mod artifacts;
+#[cfg(feature = "irgen")]
+mod cache;
+#[cfg(feature = "testing")]
+mod conformance;
+mod crash;
+mod memory;
+mod options;
+#[cfg(feature = "opt")]
+mod report;
+#[cfg(feature = "testing")]
 mod tests;
 mod utils;
 mod write_base;
 
+#[cfg(feature = "asmgen-riscv")]
 pub mod asm;
 mod c;
 pub mod ir;
 
+#[cfg(feature = "asmgen-riscv")]
 mod asmgen;
+mod cgen;
+#[cfg(feature = "irgen")]
 mod irgen;
+#[cfg(feature = "opt")]
 mod opt;
 
+#[cfg(all(feature = "irgen", feature = "asmgen-riscv"))]
+pub use artifacts::{
+    compile_artifacts, read_stamp, source_stamp, ArtifactError, ArtifactKind, CompileArtifacts,
+};
+#[cfg(feature = "irgen")]
+pub use cache::{Cache, CacheStats};
+#[cfg(feature = "testing")]
+pub use conformance::{
+    run_conformance_suite, run_conformance_test, ConformanceReport, ConformanceStatus, Manifest,
+    ManifestError, StatusChange,
+};
+pub use crash::{current_stage, install_crash_handler, set_stage, CrashReport};
+pub use memory::MemoryStats;
+pub use options::{
+    char_signedness, set_char_signedness, set_target_data, target_data, CharSignedness,
+    CompileOptions, Instrumentation, LanguageStd, OverflowPolicy, SwitchStrategy, SwitchThresholds,
+    TargetData,
+};
+#[cfg(feature = "opt")]
+pub use report::{FunctionReport, Report};
+#[cfg(feature = "testing")]
 pub use tests::*;
 pub use utils::*;
-pub use write_base::write;
+pub use write_base::{
+    try_write, write, OnUnsupported, UnsupportedConstruct, WriteError, WriteOpts,
+};
 
-pub use c::Parse;
+pub use c::{
+    check_language_std, check_restrict_aliasing, check_sequence_points, LineMap, Location,
+    Normalized, Parse, ParseError, RestrictWarning, SequenceWarning, Severity, StdDiagnostic,
+    StdViolation,
+};
 pub use ir::Parse as IrParse;
 pub use ir::Visualizer as IrVisualizer;
 
-pub use asmgen::Asmgen;
-pub use irgen::Irgen;
+#[cfg(feature = "asmgen-riscv")]
+pub use asmgen::{runtime, target, Asmgen};
+pub use cgen::{Cgen, CgenError};
+#[cfg(feature = "irgen")]
+pub use irgen::{
+    Irgen, IrgenBuilder, IrgenError, IrgenErrorMessage, IrgenHooks, StatementCoverage,
+};
+#[cfg(feature = "opt")]
+pub use opt::analysis::{BranchProb, CallGraph, Cfg, Dominators, LocalPointees, Loops};
+#[cfg(feature = "opt")]
+pub use opt::dataflow;
+#[cfg(feature = "opt")]
 pub use opt::{
-    Deadcode, FunctionPass, Gvn, Mem2reg, Optimize, Repeat, SimplifyCfg, SimplifyCfgConstProp,
-    SimplifyCfgEmpty, SimplifyCfgMerge, SimplifyCfgReach, O0, O1,
+    optimizer_budget, optimizer_out_of_time, set_optimizer_budget, take_budget_diagnostics,
+    BoxedPass, BudgetDiagnostic, DeadFunction, DeadStore, DeadStoreInner, Deadcode, FoldConstLoad,
+    ForwardPointers, ForwardPointersInner, FunctionPass, Gvn, LoopRotate, LoopRotateInner,
+    LowerSwitch, LowerSwitchInner, Measure, Mem2reg, MinMax, MinMaxInner, OptimizerBudget,
+    ParallelFunctionPass, PassRegistry, PassRegistryError, Repeat, SimplifyArith,
+    SimplifyArithInner, SimplifyCfg, SimplifyCfgConstProp, SimplifyCfgEmpty, SimplifyCfgMerge,
+    SimplifyCfgReach, Sink, SinkInner, SplitMisaligned, SplitMisalignedInner, StrcmpDispatch,
+    TailMerge, TailMergeInner, O0, O1,
 };
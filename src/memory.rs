@@ -0,0 +1,48 @@
+use core::fmt;
+use std::fs;
+
+/// Memory usage of the compiler, as reported by `/proc/self/status` on Linux.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// Peak resident set size in KiB, i.e., `VmHWM`
+    pub peak_rss: Option<u64>,
+    /// Resident set size in KiB, i.e., `VmRSS`
+    pub rss: Option<u64>,
+}
+
+impl MemoryStats {
+    /// Reads the memory usage of the current process, which is unknown if `/proc` is not
+    /// available.
+    pub fn current() -> Self {
+        fs::read_to_string("/proc/self/status")
+            .map(|status| Self::from_status(&status))
+            .unwrap_or_default()
+    }
+
+    /// Parses the contents of `/proc/<pid>/status`.
+    pub fn from_status(status: &str) -> Self {
+        let field = |name: &str| {
+            status.lines().find_map(|line| {
+                let value = line.strip_prefix(name)?.strip_prefix(':')?;
+                value.trim().strip_suffix("kB")?.trim().parse().ok()
+            })
+        };
+
+        Self {
+            peak_rss: field("VmHWM"),
+            rss: field("VmRSS"),
+        }
+    }
+}
+
+impl fmt::Display for MemoryStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kib = |value: Option<u64>| value.map_or("unknown".to_string(), |v| format!("{v} KiB"));
+        write!(
+            f,
+            "memory: peak RSS {}, RSS {}",
+            kib(self.peak_rss),
+            kib(self.rss)
+        )
+    }
+}
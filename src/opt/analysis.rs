@@ -0,0 +1,946 @@
+//! Analyses over the IR shared by multiple optimizations.
+
+use core::ops::Deref;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use lang_c::ast;
+
+use crate::ir::*;
+use crate::some_or;
+
+/// Control flow graph of a function.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cfg {
+    successors: BTreeMap<BlockId, Vec<BlockId>>,
+    predecessors: BTreeMap<BlockId, Vec<BlockId>>,
+}
+
+impl Cfg {
+    pub fn new(code: &FunctionDefinition) -> Self {
+        let mut cfg = Self::default();
+        for bid in code.blocks.keys() {
+            let _ = cfg.successors.insert(*bid, Vec::new());
+            let _ = cfg.predecessors.insert(*bid, Vec::new());
+        }
+
+        for (bid, block) in &code.blocks {
+            for succ in block.exit.successors() {
+                cfg.successors
+                    .get_mut(bid)
+                    .expect("`bid` must be a block")
+                    .push(succ);
+                cfg.predecessors
+                    .get_mut(&succ)
+                    .expect("`succ` must be a block")
+                    .push(*bid);
+            }
+        }
+
+        cfg
+    }
+
+    /// Returns the blocks `bid` may jump to.
+    pub fn successors(&self, bid: BlockId) -> &[BlockId] {
+        self.successors
+            .get(&bid)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Returns the blocks that may jump to `bid`.
+    pub fn predecessors(&self, bid: BlockId) -> &[BlockId] {
+        self.predecessors
+            .get(&bid)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// Dominator tree of a function.
+///
+/// Computed with the iterative algorithm of Cooper, Harvey, and Kennedy: "A Simple, Fast Dominance
+/// Algorithm". Blocks unreachable from the entry are neither dominated by nor dominate any block.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Dominators {
+    /// Reachable blocks in reverse postorder, starting with the entry.
+    reverse_postorder: Vec<BlockId>,
+    /// Immediate dominator of each reachable block, the entry being its own.
+    idoms: BTreeMap<BlockId, BlockId>,
+}
+
+impl Dominators {
+    pub fn new(code: &FunctionDefinition, cfg: &Cfg) -> Self {
+        let mut postorder = Vec::new();
+        let mut visited = BTreeSet::new();
+        let _ = visited.insert(code.bid_init);
+        let mut stack = vec![(code.bid_init, 0)];
+        while let Some((bid, next)) = stack.pop() {
+            if let Some(succ) = cfg.successors(bid).get(next) {
+                stack.push((bid, next + 1));
+                if visited.insert(*succ) {
+                    stack.push((*succ, 0));
+                }
+            } else {
+                postorder.push(bid);
+            }
+        }
+
+        let reverse_postorder = postorder.into_iter().rev().collect::<Vec<_>>();
+        let order = reverse_postorder
+            .iter()
+            .enumerate()
+            .map(|(i, bid)| (*bid, i))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut idoms = BTreeMap::new();
+        let _ = idoms.insert(code.bid_init, code.bid_init);
+        loop {
+            let mut changed = false;
+            for bid in reverse_postorder.iter().skip(1) {
+                let mut processed = cfg
+                    .predecessors(*bid)
+                    .iter()
+                    .filter(|pred| idoms.contains_key(*pred));
+                let first = *processed
+                    .next()
+                    .expect("a reachable block must have a processed predecessor");
+                let idom = processed.fold(first, |mut lhs, rhs| {
+                    let mut rhs = *rhs;
+                    while lhs != rhs {
+                        while order[&lhs] > order[&rhs] {
+                            lhs = idoms[&lhs];
+                        }
+                        while order[&rhs] > order[&lhs] {
+                            rhs = idoms[&rhs];
+                        }
+                    }
+                    lhs
+                });
+
+                if idoms.insert(*bid, idom) != Some(idom) {
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        Self {
+            reverse_postorder,
+            idoms,
+        }
+    }
+
+    /// Returns the reachable blocks in reverse postorder, starting with the entry.
+    pub fn reverse_postorder(&self) -> &[BlockId] {
+        &self.reverse_postorder
+    }
+
+    /// Returns `true` if `bid` is reachable from the entry.
+    pub fn is_reachable(&self, bid: BlockId) -> bool {
+        self.idoms.contains_key(&bid)
+    }
+
+    /// Returns the immediate dominator of `bid`, or `None` if it is the entry or unreachable.
+    pub fn idom(&self, bid: BlockId) -> Option<BlockId> {
+        let idom = *self.idoms.get(&bid)?;
+        if idom == bid {
+            None
+        } else {
+            Some(idom)
+        }
+    }
+
+    /// Returns `true` if every path from the entry to `bid` passes through `dom`.
+    ///
+    /// Every reachable block dominates itself.
+    pub fn dominates(&self, dom: BlockId, bid: BlockId) -> bool {
+        if !self.is_reachable(dom) || !self.is_reachable(bid) {
+            return false;
+        }
+
+        let mut current = bid;
+        loop {
+            if current == dom {
+                return true;
+            }
+            current = some_or!(self.idom(current), return false);
+        }
+    }
+
+    /// Returns the nearest block dominating both `lhs` and `rhs`, if both are reachable.
+    pub fn common_dominator(&self, lhs: BlockId, rhs: BlockId) -> Option<BlockId> {
+        let mut current = lhs;
+        loop {
+            if self.dominates(current, rhs) {
+                return Some(current);
+            }
+            current = self.idom(current)?;
+        }
+    }
+}
+
+/// Natural loops of a function.
+///
+/// A back edge is an edge to a block dominating its source, the header of the loop. The loop
+/// consists of the header and the blocks reaching the source of a back edge without passing
+/// through the header.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Loops {
+    /// Headers of the loops containing each block, which is absent if it is in no loop.
+    headers: BTreeMap<BlockId, BTreeSet<BlockId>>,
+}
+
+impl Loops {
+    pub fn new(cfg: &Cfg, dominators: &Dominators) -> Self {
+        let mut loops = Self::default();
+        for bid in dominators.reverse_postorder() {
+            for header in cfg.successors(*bid) {
+                if !dominators.dominates(*header, *bid) {
+                    continue;
+                }
+
+                let mut body = BTreeSet::new();
+                let _ = body.insert(*header);
+                let mut worklist = vec![*bid];
+                while let Some(member) = worklist.pop() {
+                    if body.insert(member) {
+                        worklist.extend(
+                            cfg.predecessors(member)
+                                .iter()
+                                .filter(|pred| dominators.is_reachable(**pred)),
+                        );
+                    }
+                }
+
+                for member in body {
+                    let _ = loops.headers.entry(member).or_default().insert(*header);
+                }
+            }
+        }
+
+        loops
+    }
+
+    /// Returns the headers of the loops containing `bid`.
+    pub fn headers(&self, bid: BlockId) -> BTreeSet<BlockId> {
+        self.headers.get(&bid).cloned().unwrap_or_default()
+    }
+
+    /// Returns `true` if every loop containing `bid` also contains `other`.
+    pub fn is_in_loops_of(&self, bid: BlockId, other: BlockId) -> bool {
+        self.headers(bid).is_subset(&self.headers(other))
+    }
+}
+
+/// Static estimates of the probabilities of the control flow edges of a function and of the
+/// frequencies of its blocks, for the passes wanting them without profile data.
+///
+/// The probability of the edges of a conditional jump follows the heuristics of Ball and Larus,
+/// "Branch Prediction for Free": a loop branch stays in the loop, e.g., by its back edge, a
+/// branch avoids the successor calling a function that never returns, e.g., `abort`, a pointer
+/// compared with null is not null, and a comparison against a constant in a loop does not exit
+/// it. The predictions of the heuristics applying to a branch are combined multiplicatively (by
+/// the Dempster-Shafer rule), and a branch without any is taken either way equally. The targets
+/// of a switch are taken equally.
+///
+/// The frequency of a block is the expected number of its executions per call of the function,
+/// propagated through the CFG as in Wu and Larus, "Static Branch Frequency and Program Profile
+/// Analysis": the loops are processed from the innermost, and the frequency of a loop header is
+/// scaled by the number of iterations its back edges imply. Unreachable blocks have frequency 0.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BranchProb {
+    /// The probability of each edge, by its source and target
+    probabilities: BTreeMap<(BlockId, BlockId), f64>,
+    frequencies: BTreeMap<BlockId, f64>,
+}
+
+impl BranchProb {
+    /// The probability that a loop branch stays in the loop.
+    pub const LOOP_BRANCH: f64 = 0.9;
+
+    /// The probability that a branch avoids the successor calling a function that never returns.
+    pub const NORETURN: f64 = 0.999;
+
+    /// The probability that a pointer compared with null is not null.
+    pub const POINTER: f64 = 0.6;
+
+    /// The probability that a comparison against a constant in a loop does not exit the loop.
+    pub const LOOP_EXIT: f64 = 0.8;
+
+    /// The bound of the probability that a loop header is reentered, so that the frequencies
+    /// stay finite even if a loop never exits.
+    const MAX_CYCLIC_PROBABILITY: f64 = 0.999;
+
+    /// The functions known not to return.
+    const NORETURN_FUNCTIONS: [&str; 4] = ["abort", "exit", "_exit", "__assert_fail"];
+
+    pub fn new(code: &FunctionDefinition) -> Self {
+        let cfg = Cfg::new(code);
+        let dominators = Dominators::new(code, &cfg);
+        let loops = Loops::new(&cfg, &dominators);
+
+        let mut result = Self::default();
+        for bid in dominators.reverse_postorder() {
+            let block = &code.blocks[bid];
+            let args = block.exit.jump_args();
+            let mut add = |target: BlockId, probability: f64| {
+                *result.probabilities.entry((*bid, target)).or_default() += probability;
+            };
+            match &block.exit {
+                BlockExit::ConditionalJump {
+                    condition,
+                    arg_then,
+                    arg_else,
+                } if arg_then.bid != arg_else.bid => {
+                    let then_probability = Self::predict(
+                        code,
+                        &dominators,
+                        &loops,
+                        *bid,
+                        condition,
+                        (arg_then.bid, arg_else.bid),
+                    );
+                    add(arg_then.bid, then_probability);
+                    add(arg_else.bid, 1.0 - then_probability);
+                }
+                _ => {
+                    for arg in &args {
+                        add(arg.bid, 1.0 / args.len() as f64);
+                    }
+                }
+            }
+        }
+
+        result.propagate_frequencies(code, &cfg, &dominators, &loops);
+        result
+    }
+
+    /// Returns the probability that the control flows from `from` to `to`, or 0 if there is no
+    /// such edge.
+    pub fn probability(&self, from: BlockId, to: BlockId) -> f64 {
+        self.probabilities
+            .get(&(from, to))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the expected number of executions of `bid` per call of the function.
+    pub fn frequency(&self, bid: BlockId) -> f64 {
+        self.frequencies.get(&bid).copied().unwrap_or_default()
+    }
+
+    /// Returns the expected number of times the control flows from `from` to `to` per call of
+    /// the function.
+    pub fn edge_frequency(&self, from: BlockId, to: BlockId) -> f64 {
+        self.frequency(from) * self.probability(from, to)
+    }
+
+    /// Returns the probability that the conditional jump of `bid` on `condition` takes the first
+    /// of `targets`.
+    fn predict(
+        code: &FunctionDefinition,
+        dominators: &Dominators,
+        loops: &Loops,
+        bid: BlockId,
+        condition: &Operand,
+        targets: (BlockId, BlockId),
+    ) -> f64 {
+        let (then_bid, else_bid) = targets;
+        let comparison = definition(code, condition).and_then(|instruction| match instruction {
+            Instruction::BinOp { op, lhs, rhs, .. } => Some((op, lhs, rhs)),
+            _ => None,
+        });
+        let mut predictions = Vec::new();
+        // Each heuristic predicts the first or the second of its targets with `probability`.
+        let mut predict = |is_then: bool, probability: f64| {
+            predictions.push(if is_then {
+                probability
+            } else {
+                1.0 - probability
+            });
+        };
+
+        let exits = |target: BlockId| !loops.is_in_loops_of(bid, target);
+        let stays = |target: BlockId| dominators.dominates(target, bid) || !exits(target);
+        if stays(then_bid) != stays(else_bid) {
+            predict(stays(then_bid), Self::LOOP_BRANCH);
+        }
+
+        if exits(then_bid) != exits(else_bid) {
+            let is_against_constant = comparison.is_some_and(|(op, lhs, rhs)| {
+                is_relational(op) && (lhs.get_constant().is_some() || rhs.get_constant().is_some())
+            });
+            if is_against_constant {
+                predict(!exits(then_bid), Self::LOOP_EXIT);
+            }
+        }
+
+        let calls_noreturn = |target: BlockId| {
+            code.blocks[&target].instructions.iter().any(|instruction| {
+                if let Instruction::Call { callee, .. } = instruction.deref() {
+                    callee
+                        .get_constant()
+                        .and_then(Constant::get_global_variable_name)
+                        .is_some_and(|name| Self::NORETURN_FUNCTIONS.iter().any(|f| *f == name))
+                } else {
+                    false
+                }
+            })
+        };
+        if calls_noreturn(then_bid) != calls_noreturn(else_bid) {
+            predict(!calls_noreturn(then_bid), Self::NORETURN);
+        }
+
+        if let Some((op, lhs, rhs)) = comparison {
+            let is_pointer = |operand: &Operand| operand.dtype().get_pointer_inner().is_some();
+            let is_null_check =
+                (is_pointer(lhs) && is_null(code, rhs)) || (is_pointer(rhs) && is_null(code, lhs));
+            match op {
+                ast::BinaryOperator::Equals if is_null_check => predict(false, Self::POINTER),
+                ast::BinaryOperator::NotEquals if is_null_check => predict(true, Self::POINTER),
+                _ => (),
+            }
+        }
+
+        predictions.into_iter().fold(0.5, |lhs, rhs| {
+            let taken = lhs * rhs;
+            taken / (taken + (1.0 - lhs) * (1.0 - rhs))
+        })
+    }
+
+    fn propagate_frequencies(
+        &mut self,
+        code: &FunctionDefinition,
+        cfg: &Cfg,
+        dominators: &Dominators,
+        loops: &Loops,
+    ) {
+        let mut bodies = BTreeMap::<BlockId, BTreeSet<BlockId>>::new();
+        for bid in dominators.reverse_postorder() {
+            for header in loops.headers(*bid) {
+                let _ = bodies.entry(header).or_default().insert(*bid);
+            }
+        }
+
+        // The probabilities that the back edges are taken per entry to their loops.
+        let mut back_edges = BTreeMap::new();
+        let mut headers = bodies.keys().copied().collect::<Vec<_>>();
+        headers.sort_by_key(|header| core::cmp::Reverse(loops.headers(*header).len()));
+        for header in headers {
+            self.propagate(header, &bodies[&header], cfg, dominators, &mut back_edges);
+        }
+
+        let reachable = dominators
+            .reverse_postorder()
+            .iter()
+            .copied()
+            .collect::<BTreeSet<_>>();
+        self.propagate(code.bid_init, &reachable, cfg, dominators, &mut back_edges);
+    }
+
+    /// Computes the frequencies of the blocks of `region` relative to `head`, and the
+    /// probabilities of the back edges to `head` if it is a loop header.
+    fn propagate(
+        &mut self,
+        head: BlockId,
+        region: &BTreeSet<BlockId>,
+        cfg: &Cfg,
+        dominators: &Dominators,
+        back_edges: &mut BTreeMap<(BlockId, BlockId), f64>,
+    ) {
+        for bid in dominators.reverse_postorder() {
+            if !region.contains(bid) {
+                continue;
+            }
+            if *bid == head {
+                let _ = self.frequencies.insert(head, 1.0);
+                continue;
+            }
+
+            let mut frequency = 0.0;
+            let mut cyclic_probability = 0.0;
+            for pred in cfg.predecessors(*bid) {
+                if let Some(probability) = back_edges.get(&(*pred, *bid)) {
+                    cyclic_probability += probability;
+                } else if region.contains(pred) && !dominators.dominates(*bid, *pred) {
+                    frequency += self.edge_frequency(*pred, *bid);
+                }
+            }
+            let cyclic_probability = f64::min(cyclic_probability, Self::MAX_CYCLIC_PROBABILITY);
+            let _ = self
+                .frequencies
+                .insert(*bid, frequency / (1.0 - cyclic_probability));
+        }
+
+        for pred in cfg.predecessors(head) {
+            if region.contains(pred) && dominators.dominates(head, *pred) {
+                let _ = back_edges.insert((*pred, head), self.edge_frequency(*pred, head));
+            }
+        }
+    }
+}
+
+/// Returns the instruction defining `operand`, `None` if it is not defined by an instruction.
+fn definition<'a>(code: &'a FunctionDefinition, operand: &Operand) -> Option<&'a Instruction> {
+    match operand.get_register()? {
+        (RegisterId::Temp { bid, iid }, _) => {
+            Some(code.blocks.get(bid)?.instructions.get(*iid)?.deref())
+        }
+        _ => None,
+    }
+}
+
+/// Whether `op` compares its operands.
+fn is_relational(op: &ast::BinaryOperator) -> bool {
+    matches!(
+        op,
+        ast::BinaryOperator::Less
+            | ast::BinaryOperator::Greater
+            | ast::BinaryOperator::LessOrEqual
+            | ast::BinaryOperator::GreaterOrEqual
+            | ast::BinaryOperator::Equals
+            | ast::BinaryOperator::NotEquals
+    )
+}
+
+/// Whether `operand` is a null pointer, i.e., 0 possibly cast to a pointer.
+fn is_null(code: &FunctionDefinition, operand: &Operand) -> bool {
+    let operand = match definition(code, operand) {
+        Some(Instruction::TypeCast { value, .. }) => value,
+        _ => operand,
+    };
+    matches!(operand.get_constant(), Some(Constant::Int { value: 0, .. }))
+}
+
+/// The local variable each local pointer variable of a function points to, for the pointer
+/// variables always pointing to the same one, e.g., `p` in `int x; int *p = &x; *p = 5;`.
+///
+/// A pointer variable is resolved if every store to it stores the address of the same local
+/// variable, and both its address and the values loaded from it are only used as the pointers of
+/// loads and stores. Since the pointer variable does not escape, a call cannot change it. It is
+/// not resolved if it may point to different variables, e.g., by a conditional, or to something
+/// else, e.g., by pointer arithmetic or a parameter.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LocalPointees {
+    /// The local variable each resolved pointer variable points to, by their allocation ids.
+    pointees: BTreeMap<usize, usize>,
+    /// The pointer variable each register holding a loaded pointer is loaded from.
+    copies: HashMap<RegisterId, usize>,
+}
+
+impl LocalPointees {
+    pub fn new(code: &FunctionDefinition) -> Self {
+        // The pointee of each pointer variable not ruled out yet, `None` until a store to it.
+        let mut candidates = code
+            .allocations
+            .iter()
+            .enumerate()
+            .filter(|(_, dtype)| dtype.get_pointer_inner().is_some())
+            .map(|(aid, _)| (aid, None))
+            .collect::<BTreeMap<usize, Option<usize>>>();
+        let local = |operand: &Operand| match operand.get_register()? {
+            (RegisterId::Local { aid }, _) => Some(*aid),
+            _ => None,
+        };
+
+        let mut copies = HashMap::new();
+        for (bid, block) in &code.blocks {
+            for (iid, instruction) in block.instructions.iter().enumerate() {
+                if let Instruction::Load { ptr } = instruction.deref() {
+                    let aid = some_or!(local(ptr), continue);
+                    if candidates.contains_key(&aid) {
+                        let _ = copies.insert(RegisterId::temp(*bid, iid), aid);
+                    }
+                }
+            }
+        }
+
+        // Rules out the pointer variables `operand` lets escape.
+        let escape = |operand: &Operand, candidates: &mut BTreeMap<usize, Option<usize>>| {
+            let aid = match operand.get_register() {
+                Some((RegisterId::Local { aid }, _)) => *aid,
+                Some((rid, _)) => *some_or!(copies.get(rid), return),
+                None => return,
+            };
+            let _ = candidates.remove(&aid);
+        };
+        for block in code.blocks.values() {
+            for instruction in &block.instructions {
+                match instruction.deref() {
+                    Instruction::Load { .. } => {}
+                    Instruction::Store { ptr, value } => {
+                        escape(value, &mut candidates);
+                        let aid = some_or!(local(ptr), continue);
+                        let pointee = *some_or!(candidates.get(&aid), continue);
+                        let target = local(value).filter(|target| {
+                            *target != aid
+                                && value.dtype() == *code.allocations[aid]
+                                && pointee.map_or(true, |pointee| pointee == *target)
+                        });
+                        if let Some(target) = target {
+                            let _ = candidates.insert(aid, Some(target));
+                        } else {
+                            let _ = candidates.remove(&aid);
+                        }
+                    }
+                    _ => instruction.walk_operands(|operand| escape(operand, &mut candidates)),
+                }
+            }
+            block
+                .exit
+                .walk_operands(|operand| escape(operand, &mut candidates));
+        }
+
+        let pointees = candidates
+            .into_iter()
+            .filter_map(|(aid, pointee)| Some((aid, pointee?)))
+            .collect::<BTreeMap<_, _>>();
+        copies.retain(|_, aid| pointees.contains_key(aid));
+        Self { pointees, copies }
+    }
+
+    /// Returns the resolved pointer variables, each with the variable it points to.
+    pub fn pointees(&self) -> &BTreeMap<usize, usize> {
+        &self.pointees
+    }
+
+    /// Returns the resolved pointer variable `rid` is loaded from, if any.
+    pub fn copy_of(&self, rid: &RegisterId) -> Option<usize> {
+        self.copies.get(rid).copied()
+    }
+
+    /// Returns `true` if no pointer variable is resolved.
+    pub fn is_empty(&self) -> bool {
+        self.pointees.is_empty()
+    }
+}
+
+/// The object a pointer of a function is derived from by `getelementptr` and typecasts, as far as
+/// it is known within the function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PointerBase {
+    /// A local variable, by its allocation id
+    Local(usize),
+    /// A global variable, by its name
+    Global(String),
+    /// A parameter, by its index
+    Param(usize),
+    /// Anything else, e.g., a loaded pointer or a phinode
+    Unknown,
+}
+
+impl PointerBase {
+    pub fn new(code: &FunctionDefinition, mut operand: &Operand) -> Self {
+        loop {
+            let rid = match operand {
+                Operand::Constant(constant) => {
+                    return constant
+                        .get_global_variable_name()
+                        .map_or(Self::Unknown, Self::Global)
+                }
+                Operand::Register { rid, .. } => rid,
+            };
+            operand = match rid {
+                RegisterId::Local { aid } => return Self::Local(*aid),
+                RegisterId::Arg { bid, aid } if *bid == code.bid_init => return Self::Param(*aid),
+                RegisterId::Arg { .. } => return Self::Unknown,
+                RegisterId::Temp { .. } => match definition(code, operand) {
+                    Some(Instruction::GetElementPtr { ptr, .. }) => ptr,
+                    Some(Instruction::TypeCast { value, .. }) => value,
+                    _ => return Self::Unknown,
+                },
+            };
+        }
+    }
+}
+
+/// Returns `true` if the pointers `lhs` and `rhs` of `code` may point into the same object.
+///
+/// The pointers derived from different variables never do, nor do a parameter and a local
+/// variable, which is allocated after the parameter is passed. A `restrict`-qualified parameter
+/// only accessed through the loads and stores of the pointers derived from it does not either
+/// with any pointer not derived from it, since another pointer accessing the object it points to
+/// is undefined behavior (see C17 6.7.3.1).
+pub fn may_alias(code: &FunctionDefinition, lhs: &Operand, rhs: &Operand) -> bool {
+    if lhs == rhs {
+        return true;
+    }
+
+    let is_restrict = |base: &PointerBase| match base {
+        PointerBase::Param(aid) => is_restrict_param(code, *aid),
+        _ => false,
+    };
+    match (PointerBase::new(code, lhs), PointerBase::new(code, rhs)) {
+        (PointerBase::Local(lhs), PointerBase::Local(rhs)) => lhs == rhs,
+        (PointerBase::Global(lhs), PointerBase::Global(rhs)) => lhs == rhs,
+        (PointerBase::Param(lhs), PointerBase::Param(rhs)) if lhs == rhs => true,
+        (PointerBase::Local(_), PointerBase::Global(_) | PointerBase::Param(_))
+        | (PointerBase::Global(_) | PointerBase::Param(_), PointerBase::Local(_)) => false,
+        (lhs, rhs) => !is_restrict(&lhs) && !is_restrict(&rhs),
+    }
+}
+
+/// Returns `true` if the parameter `aid` of `code` is a `restrict`-qualified pointer whose derived
+/// pointers are only used to load and store, so that no other pointer is derived from it.
+fn is_restrict_param(code: &FunctionDefinition, aid: usize) -> bool {
+    let param = some_or!(
+        code.blocks
+            .get(&code.bid_init)
+            .and_then(|block| block.phinodes.get(aid)),
+        return false
+    );
+    if !param.is_restrict() {
+        return false;
+    }
+
+    let is_derived = |operand: &Operand| PointerBase::new(code, operand) == PointerBase::Param(aid);
+    let mut escapes = false;
+    for block in code.blocks.values() {
+        for instruction in &block.instructions {
+            match instruction.deref() {
+                Instruction::Load { .. } | Instruction::GetElementPtr { .. } => {}
+                Instruction::Store { value, .. } => escapes |= is_derived(value),
+                Instruction::TypeCast {
+                    value,
+                    target_dtype,
+                } => escapes |= target_dtype.get_pointer_inner().is_none() && is_derived(value),
+                _ => instruction.walk_operands(|operand| escapes |= is_derived(operand)),
+            }
+        }
+        block
+            .exit
+            .walk_operands(|operand| escapes |= is_derived(operand));
+    }
+    !escapes
+}
+
+/// Static call graph of a translation unit.
+///
+/// Direct calls are resolved by the name of the callee. A function whose address is taken (i.e.,
+/// it is used other than as the callee of a direct call) may be called through a pointer, so
+/// every indirect call is conservatively considered to call every address-taken function. A
+/// function taking the address of another function also has an edge to it, so that the
+/// reachability of a function accounts for both calls and references. A function whose address
+/// initializes a global variable, e.g., `f` of `int (*fp)(void) = f;`, is also address-taken, and
+/// it is referenced by the global rather than by a function.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CallGraph {
+    /// Functions called or referenced by each function.
+    callees: BTreeMap<String, BTreeSet<String>>,
+    /// Functions whose address is taken.
+    address_taken: BTreeSet<String>,
+    /// Functions containing an indirect call.
+    indirect_callers: BTreeSet<String>,
+    /// Functions whose address initializes a global variable.
+    global_references: BTreeSet<String>,
+}
+
+impl CallGraph {
+    pub fn new(unit: &TranslationUnit) -> Self {
+        // Returns the name of the function `operand` refers to, if any.
+        let function_of = |operand: &Operand| {
+            let name = operand.get_constant()?.get_global_variable_name()?;
+            if let Some(Declaration::Function { .. }) = unit.decls.get(&name) {
+                Some(name)
+            } else {
+                None
+            }
+        };
+
+        let mut graph = Self::default();
+        for (name, decl) in &unit.decls {
+            let (_, definition) = some_or!(decl.get_function(), continue);
+            let mut callees = BTreeSet::new();
+            let mut references = Vec::new();
+
+            for block in definition.iter().flat_map(|d| d.blocks.values()) {
+                for instruction in &block.instructions {
+                    if let Instruction::Call { callee, args, .. } = instruction.deref() {
+                        if let Some(callee) = function_of(callee) {
+                            let _ = callees.insert(callee);
+                        } else {
+                            let _ = graph.indirect_callers.insert(name.clone());
+                        }
+                        references.extend(args.iter().filter_map(function_of));
+                    } else {
+                        instruction.walk_operands(|o| references.extend(function_of(o)));
+                    }
+                }
+                block
+                    .exit
+                    .walk_operands(|o| references.extend(function_of(o)));
+            }
+
+            graph.address_taken.extend(references.iter().cloned());
+            callees.extend(references);
+            let _ = graph.callees.insert(name.clone(), callees);
+        }
+
+        for decl in unit.decls.values() {
+            let references = decl
+                .initializer_references()
+                .into_iter()
+                .filter(|name| matches!(unit.decls.get(*name), Some(Declaration::Function { .. })));
+            graph.global_references.extend(references.map(String::from));
+        }
+        graph
+            .address_taken
+            .extend(graph.global_references.iter().cloned());
+
+        for caller in &graph.indirect_callers {
+            let callees = graph
+                .callees
+                .get_mut(caller)
+                .expect("`caller` must be a function");
+            callees.extend(graph.address_taken.iter().cloned());
+        }
+
+        graph
+    }
+
+    /// Returns the functions called or referenced by `name`.
+    pub fn callees(&self, name: &str) -> Option<&BTreeSet<String>> {
+        self.callees.get(name)
+    }
+
+    /// Returns the functions calling or referencing `name`.
+    pub fn callers(&self, name: &str) -> BTreeSet<String> {
+        self.callees
+            .iter()
+            .filter(|(_, callees)| callees.contains(name))
+            .map(|(caller, _)| caller.clone())
+            .collect()
+    }
+
+    /// Returns the functions whose address is taken.
+    pub fn address_taken(&self) -> &BTreeSet<String> {
+        &self.address_taken
+    }
+
+    /// Returns the functions whose address initializes a global variable, which are reachable
+    /// through the variable from any function.
+    pub fn global_references(&self) -> &BTreeSet<String> {
+        &self.global_references
+    }
+
+    /// Returns `true` if `name` contains an indirect call.
+    pub fn has_indirect_call(&self, name: &str) -> bool {
+        self.indirect_callers.contains(name)
+    }
+
+    /// Returns the functions reachable from `roots`, including `roots` themselves.
+    pub fn reachable_from<'a, I>(&self, roots: I) -> BTreeSet<String>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut reachable = BTreeSet::new();
+        let mut worklist = roots
+            .into_iter()
+            .filter(|root| self.callees.contains_key(*root))
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        while let Some(name) = worklist.pop() {
+            if !reachable.insert(name.clone()) {
+                continue;
+            }
+
+            worklist.extend(
+                self.callees[&name]
+                    .iter()
+                    .filter(|callee| !reachable.contains(*callee))
+                    .cloned(),
+            );
+        }
+
+        reachable
+    }
+
+    /// Returns the strongly connected components of the call graph in bottom-up order, i.e., a
+    /// component comes after all the components it calls.
+    pub fn sccs(&self) -> Vec<Vec<String>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            index: BTreeMap::new(),
+            lowlink: BTreeMap::new(),
+            stack: Vec::new(),
+            on_stack: BTreeSet::new(),
+            sccs: Vec::new(),
+        };
+
+        for name in self.callees.keys() {
+            if !tarjan.index.contains_key(name.as_str()) {
+                tarjan.visit(name);
+            }
+        }
+
+        tarjan.sccs
+    }
+
+    /// Returns the functions in bottom-up order, i.e., callees come before their callers except
+    /// for the functions in the same recursive cycle.
+    pub fn bottom_up_order(&self) -> Vec<String> {
+        self.sccs().into_iter().flatten().collect()
+    }
+
+    /// Returns `true` if `name` may call itself, directly or through other functions.
+    pub fn is_recursive(&self, name: &str) -> bool {
+        let callees = some_or!(self.callees.get(name), return false);
+        callees.contains(name)
+            || self
+                .sccs()
+                .iter()
+                .any(|scc| scc.len() > 1 && scc.iter().any(|n| n == name))
+    }
+}
+
+/// Tarjan's strongly connected components algorithm.
+#[derive(Debug)]
+struct Tarjan<'a> {
+    graph: &'a CallGraph,
+    index: BTreeMap<&'a str, usize>,
+    lowlink: BTreeMap<&'a str, usize>,
+    stack: Vec<&'a str>,
+    on_stack: BTreeSet<&'a str>,
+    sccs: Vec<Vec<String>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn visit(&mut self, name: &'a str) {
+        let index = self.index.len();
+        let _ = self.index.insert(name, index);
+        let _ = self.lowlink.insert(name, index);
+        self.stack.push(name);
+        let _ = self.on_stack.insert(name);
+
+        let graph = self.graph;
+        for callee in &graph.callees[name] {
+            let callee = callee.as_str();
+            if !self.index.contains_key(callee) {
+                self.visit(callee);
+                let lowlink = self.lowlink[name].min(self.lowlink[callee]);
+                let _ = self.lowlink.insert(name, lowlink);
+            } else if self.on_stack.contains(callee) {
+                let lowlink = self.lowlink[name].min(self.index[callee]);
+                let _ = self.lowlink.insert(name, lowlink);
+            }
+        }
+
+        if self.lowlink[name] == self.index[name] {
+            let mut scc = Vec::new();
+            loop {
+                let member = self.stack.pop().expect("`name` must be on the stack");
+                let _ = self.on_stack.remove(member);
+                scc.push(member.to_string());
+                if member == name {
+                    break;
+                }
+            }
+            scc.reverse();
+            self.sccs.push(scc);
+        }
+    }
+}
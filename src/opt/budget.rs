@@ -0,0 +1,315 @@
+//! Budgets bounding the optimizer, e.g., on fuzzed inputs on which the passes of a fixpoint group
+//! undo each other forever.
+//!
+//! The budgets are those of the current thread, as the target data, followed by the threads
+//! optimizing on its behalf (see [`ParallelFunctionPass`](crate::ParallelFunctionPass)), and
+//! checked by [`Repeat`] between the iterations of its group and by
+//! [`PassRegistry::run`](crate::PassRegistry::run) between its passes:
+//!
+//! - the wall-clock time limit stops optimizing once elapsed;
+//! - the iteration limit stops a group iterated too many times;
+//! - a group whose IR repeats, i.e., whose passes cycle through the same IR, is stopped at once;
+//! - the growth limit stops a group whose IR grew too large, e.g., by inlining or unrolling.
+//!
+//! Exceeding a budget is not an error: the optimizer stops, keeping the IR the last pass left,
+//! which is valid, and records a [`BudgetDiagnostic`] returned by [`take_budget_diagnostics`].
+
+use core::any::type_name;
+use core::cell::RefCell;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::time::Duration;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::ir;
+use crate::opt::Repeat;
+use crate::{some_or, Optimize};
+
+thread_local! {
+    /// The budgets of the optimizer running on the current thread.
+    static BUDGET: RefCell<State> = const {
+        RefCell::new(State {
+            limits: Limits {
+                budget: OptimizerBudget::DEFAULT,
+                deadline: None,
+            },
+            diagnostics: Vec::new(),
+        })
+    };
+}
+
+struct State {
+    limits: Limits,
+    diagnostics: Vec<BudgetDiagnostic>,
+}
+
+/// The budgets of a thread, to be followed by the threads optimizing on its behalf.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Limits {
+    budget: OptimizerBudget,
+    /// When the time limit of `budget` elapses
+    deadline: Option<Instant>,
+}
+
+/// The budgets of the optimizer, set by [`set_optimizer_budget`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OptimizerBudget {
+    /// The wall-clock time the optimizer may take from [`set_optimizer_budget`] on.
+    pub time_limit: Option<Duration>,
+    /// The maximum number of iterations of a fixpoint group.
+    pub max_iterations: Option<usize>,
+    /// The maximum number of instructions of the code a fixpoint group optimizes, in percent of
+    /// their number before the group, e.g., 200 for twice as many. Code smaller than
+    /// [`OptimizerBudget::GROWTH_FLOOR`] instructions is measured as that large.
+    pub max_growth_percent: Option<usize>,
+}
+
+impl Default for OptimizerBudget {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl OptimizerBudget {
+    /// The default budgets, which no fixpoint group converging on its own exceeds in practice.
+    pub const DEFAULT: Self = Self {
+        time_limit: None,
+        max_iterations: Some(Self::DEFAULT_MAX_ITERATIONS),
+        max_growth_percent: Some(Self::DEFAULT_MAX_GROWTH_PERCENT),
+    };
+
+    /// The default maximum number of iterations of a fixpoint group.
+    pub const DEFAULT_MAX_ITERATIONS: usize = 1000;
+
+    /// The default maximum size of the code a fixpoint group optimizes, in percent.
+    pub const DEFAULT_MAX_GROWTH_PERCENT: usize = 1000;
+
+    /// The size below which code is measured by the growth limit, so that tiny code, e.g., an
+    /// empty function, may still grow a few instructions.
+    pub const GROWTH_FLOOR: usize = 64;
+}
+
+/// A budget of the optimizer exceeded, upon which it stopped optimizing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BudgetDiagnostic {
+    /// The time limit elapsed.
+    TimeLimit { limit: Duration },
+    /// The fixpoint group `passes` left the same IR again after `period` more iterations.
+    Oscillation { passes: String, period: usize },
+    /// The fixpoint group `passes` was iterated `limit` times without converging.
+    IterationLimit { passes: String, limit: usize },
+    /// The fixpoint group `passes` grew the code beyond `percent` percent of its size.
+    GrowthLimit { passes: String, percent: usize },
+}
+
+impl fmt::Display for BudgetDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "warning: ")?;
+        match self {
+            Self::TimeLimit { limit } => {
+                write!(f, "optimizer stopped after its time limit of {limit:?}")
+            }
+            Self::Oscillation { passes, period } => write!(
+                f,
+                "passes `{passes}` oscillate, repeating the IR every {period} iteration(s); \
+                 stopped repeating them"
+            ),
+            Self::IterationLimit { passes, limit } => write!(
+                f,
+                "passes `{passes}` did not converge in {limit} iterations; stopped repeating them"
+            ),
+            Self::GrowthLimit { passes, percent } => write!(
+                f,
+                "passes `{passes}` grew the IR beyond {percent}% of its size; stopped repeating \
+                 them"
+            ),
+        }
+    }
+}
+
+/// Sets the budgets of the optimizer on the current thread, starting its time limit now.
+pub fn set_optimizer_budget(budget: OptimizerBudget) {
+    BUDGET.with(|state| {
+        state.borrow_mut().limits = Limits {
+            budget,
+            deadline: budget.time_limit.map(|limit| Instant::now() + limit),
+        };
+    });
+}
+
+/// Returns the budgets of the optimizer on the current thread.
+pub fn optimizer_budget() -> OptimizerBudget {
+    BUDGET.with(|state| state.borrow().limits.budget)
+}
+
+/// Returns whether the time limit of the optimizer elapsed, reporting it the first time.
+pub fn optimizer_out_of_time() -> bool {
+    let limits = BUDGET.with(|state| state.borrow().limits);
+    let deadline = some_or!(limits.deadline, return false);
+    if Instant::now() < deadline {
+        return false;
+    }
+
+    let limit = limits
+        .budget
+        .time_limit
+        .expect("`deadline` must come from the time limit");
+    report(BudgetDiagnostic::TimeLimit { limit });
+    true
+}
+
+/// Returns the budgets exceeded on the current thread since the last call, in the order they were
+/// exceeded.
+pub fn take_budget_diagnostics() -> Vec<BudgetDiagnostic> {
+    BUDGET.with(|state| core::mem::take(&mut state.borrow_mut().diagnostics))
+}
+
+/// Returns the budgets of the current thread, e.g., to pass them to [`with_limits`].
+pub(crate) fn limits() -> Limits {
+    BUDGET.with(|state| state.borrow().limits)
+}
+
+/// Runs `f` under `limits` on the current thread, e.g., a worker optimizing a function on behalf
+/// of the thread `limits` come from, and returns its result with the budgets it exceeded, to be
+/// reported to that thread by [`report_all`]. The budgets of the current thread are restored.
+pub(crate) fn with_limits<R>(limits: Limits, f: impl FnOnce() -> R) -> (R, Vec<BudgetDiagnostic>) {
+    let outer = BUDGET.with(|state| {
+        state.replace(State {
+            limits,
+            diagnostics: Vec::new(),
+        })
+    });
+    let result = f();
+    let inner = BUDGET.with(|state| state.replace(outer));
+    (result, inner.diagnostics)
+}
+
+/// Reports the budgets exceeded by the threads optimizing on behalf of the current thread.
+pub(crate) fn report_all(diagnostics: Vec<BudgetDiagnostic>) {
+    for diagnostic in diagnostics {
+        report(diagnostic);
+    }
+}
+
+/// Records `diagnostic`, except for the time limit, which is reported once.
+fn report(diagnostic: BudgetDiagnostic) {
+    BUDGET.with(|state| {
+        let diagnostics = &mut state.borrow_mut().diagnostics;
+        if matches!(diagnostic, BudgetDiagnostic::TimeLimit { .. })
+            && diagnostics.contains(&diagnostic)
+        {
+            return;
+        }
+        diagnostics.push(diagnostic);
+    });
+}
+
+/// The code a fixpoint group optimizes, as measured by the budgets.
+pub trait Measure {
+    /// Returns the hash of the code, the same for the same code.
+    fn fingerprint(&self) -> u64;
+
+    /// Returns the number of instructions of the code.
+    fn size(&self) -> usize;
+}
+
+impl Measure for ir::FunctionDefinition {
+    fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        format!("{self:?}").hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn size(&self) -> usize {
+        self.blocks
+            .values()
+            .map(|block| block.instructions.len())
+            .sum()
+    }
+}
+
+impl Measure for ir::TranslationUnit {
+    fn fingerprint(&self) -> u64 {
+        // The passes change the declarations, not the structs, whose map is not ordered.
+        let mut hasher = DefaultHasher::new();
+        format!("{:?}", self.decls).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn size(&self) -> usize {
+        self.decls
+            .values()
+            .filter_map(|decl| decl.get_function()?.1.as_ref())
+            .map(Measure::size)
+            .sum()
+    }
+}
+
+impl<T: Measure, O: Optimize<T>> Optimize<T> for Repeat<O> {
+    fn optimize(&mut self, code: &mut T) -> bool {
+        let size = code.size().max(OptimizerBudget::GROWTH_FLOOR);
+        if !self.inner.optimize(code) {
+            return false;
+        }
+
+        let budget = optimizer_budget();
+        // The iteration after which each IR was left.
+        let mut iterations = HashMap::from([(code.fingerprint(), 1)]);
+        let mut iteration = 1;
+        while !optimizer_out_of_time() {
+            if let Some(limit) = budget.max_iterations.filter(|limit| iteration >= *limit) {
+                report(BudgetDiagnostic::IterationLimit {
+                    passes: short_type_name::<O>(),
+                    limit,
+                });
+                break;
+            }
+
+            if !self.inner.optimize(code) {
+                break;
+            }
+            iteration += 1;
+
+            if let Some(percent) = budget
+                .max_growth_percent
+                .filter(|percent| code.size() * 100 > size * percent)
+            {
+                report(BudgetDiagnostic::GrowthLimit {
+                    passes: short_type_name::<O>(),
+                    percent,
+                });
+                break;
+            }
+
+            if let Some(previous) = iterations.insert(code.fingerprint(), iteration) {
+                report(BudgetDiagnostic::Oscillation {
+                    passes: short_type_name::<O>(),
+                    period: iteration - previous,
+                });
+                break;
+            }
+        }
+
+        true
+    }
+}
+
+/// Returns the name of the type `T` without the paths, e.g., `(Sink, Repeat<DeadcodeInner>)`.
+fn short_type_name<T>() -> String {
+    let segments = type_name::<T>().split("::").collect::<Vec<_>>();
+    let (last, paths) = segments
+        .split_last()
+        .expect("`split` must return a segment");
+    let mut name = String::new();
+    for path in paths {
+        // Only the characters before the path, e.g., `(` of `(kecc`, are kept.
+        let start = path
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map_or(0, |i| i + 1);
+        name.push_str(&path[..start]);
+    }
+    name.push_str(last);
+    name
+}
@@ -0,0 +1,426 @@
+//! Worklist-based dataflow analyses over the control flow graph of a function.
+//!
+//! An analysis is described by an [`Analysis`]: the direction the states flow, the lattice of the
+//! states, and how each part of a block transforms a state. [`solve`] computes its fixpoint, and
+//! [`Results`] gives the states at the start and the end of each block.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use lang_c::ast;
+
+use crate::ir::*;
+use crate::opt::analysis::{Cfg, Dominators};
+use crate::*;
+
+/// The direction the states of an analysis flow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From the entry along the edges, e.g., reaching definitions.
+    Forward,
+    /// From the returns against the edges, e.g., liveness.
+    Backward,
+}
+
+/// A dataflow analysis.
+///
+/// The states form a join-semilattice of finite height whose least element is `bottom`, and the
+/// transfer functions must be monotone for [`solve`] to terminate.
+pub trait Analysis {
+    /// The state at each point of the function.
+    type Domain: Clone + PartialEq;
+
+    const DIRECTION: Direction;
+
+    /// The least element, i.e., the state of the points that are not reached yet.
+    fn bottom(&self, code: &FunctionDefinition) -> Self::Domain;
+
+    /// The state at the start of the entry if forward, or at the end of returns if backward.
+    fn boundary(&self, code: &FunctionDefinition) -> Self::Domain;
+
+    /// Joins `other` into `state`.
+    fn join(&self, state: &mut Self::Domain, other: &Self::Domain);
+
+    /// Applies the phinodes of `bid`.
+    fn transfer_phinodes(
+        &self,
+        _state: &mut Self::Domain,
+        _bid: BlockId,
+        _phinodes: &[Named<Dtype>],
+    ) {
+    }
+
+    /// Applies `instruction`, whose result is held in `rid`.
+    fn transfer_instruction(
+        &self,
+        state: &mut Self::Domain,
+        rid: RegisterId,
+        instruction: &Instruction,
+    );
+
+    /// Applies the exit of `bid`.
+    fn transfer_exit(&self, state: &mut Self::Domain, bid: BlockId, exit: &BlockExit);
+
+    /// Applies the jump from `bid` along `arg`, e.g., to bind the phinodes of its target.
+    fn transfer_edge(&self, _state: &mut Self::Domain, _bid: BlockId, _arg: &JumpArg) {}
+}
+
+/// The fixpoint of an analysis.
+///
+/// Blocks unreachable from the entry are not analyzed, and their states are the bottom.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Results<D> {
+    /// The state at the start of each reachable block, i.e., before its phinodes.
+    states_in: BTreeMap<BlockId, D>,
+    /// The state at the end of each reachable block, i.e., after its exit.
+    states_out: BTreeMap<BlockId, D>,
+    bottom: D,
+}
+
+impl<D> Results<D> {
+    /// The state at the start of `bid`, before its phinodes.
+    pub fn state_in(&self, bid: BlockId) -> &D {
+        self.states_in.get(&bid).unwrap_or(&self.bottom)
+    }
+
+    /// The state at the end of `bid`, after its exit.
+    pub fn state_out(&self, bid: BlockId) -> &D {
+        self.states_out.get(&bid).unwrap_or(&self.bottom)
+    }
+}
+
+impl<D: Clone> Results<D> {
+    /// The states before each instruction of `bid`, followed by the state before its exit.
+    pub fn points<A>(&self, analysis: &A, code: &FunctionDefinition, bid: BlockId) -> Vec<D>
+    where
+        A: Analysis<Domain = D>,
+    {
+        let block = &code.blocks[&bid];
+        if !self.states_in.contains_key(&bid) {
+            return vec![self.bottom.clone(); block.instructions.len() + 1];
+        }
+
+        let mut points = Vec::with_capacity(block.instructions.len() + 1);
+        match A::DIRECTION {
+            Direction::Forward => {
+                let mut state = self.state_in(bid).clone();
+                analysis.transfer_phinodes(&mut state, bid, &block.phinodes);
+                for (iid, instruction) in block.instructions.iter().enumerate() {
+                    points.push(state.clone());
+                    analysis.transfer_instruction(
+                        &mut state,
+                        RegisterId::temp(bid, iid),
+                        instruction,
+                    );
+                }
+                points.push(state);
+            }
+            Direction::Backward => {
+                let mut state = self.state_out(bid).clone();
+                analysis.transfer_exit(&mut state, bid, &block.exit);
+                points.push(state.clone());
+                for (iid, instruction) in block.instructions.iter().enumerate().rev() {
+                    analysis.transfer_instruction(
+                        &mut state,
+                        RegisterId::temp(bid, iid),
+                        instruction,
+                    );
+                    points.push(state.clone());
+                }
+                points.reverse();
+            }
+        }
+        points
+    }
+}
+
+/// Computes the fixpoint of `analysis` over `code`.
+///
+/// Blocks are visited in reverse postorder if forward, or in its reverse if backward, and a block
+/// is visited again whenever the state flowing into it changes.
+pub fn solve<A: Analysis>(analysis: &A, code: &FunctionDefinition) -> Results<A::Domain> {
+    let cfg = Cfg::new(code);
+    let dominators = Dominators::new(code, &cfg);
+    let mut order = dominators.reverse_postorder().to_vec();
+    if A::DIRECTION == Direction::Backward {
+        order.reverse();
+    }
+    let positions = order
+        .iter()
+        .enumerate()
+        .map(|(position, bid)| (*bid, position))
+        .collect::<HashMap<_, _>>();
+
+    let bottom = analysis.bottom(code);
+    // Only the visited blocks have states, so that the blocks not visited yet, e.g., the latches
+    // of loops, are not joined.
+    let mut results = Results {
+        states_in: BTreeMap::new(),
+        states_out: BTreeMap::new(),
+        bottom,
+    };
+
+    // Positions in `order` of the blocks to visit, the earliest first.
+    let mut worklist = (0..order.len()).collect::<BTreeSet<_>>();
+    while let Some(position) = worklist.iter().next().copied() {
+        let _ = worklist.remove(&position);
+        let bid = order[position];
+        let block = &code.blocks[&bid];
+
+        let (changed, dependents) = match A::DIRECTION {
+            Direction::Forward => {
+                let mut state = results.bottom.clone();
+                if bid == code.bid_init {
+                    analysis.join(&mut state, &analysis.boundary(code));
+                }
+                for pred in cfg.predecessors(bid) {
+                    let pred_out = some_or!(results.states_out.get(pred), continue);
+                    for arg in code.blocks[pred].exit.jump_args() {
+                        if arg.bid == bid {
+                            let mut edge = pred_out.clone();
+                            analysis.transfer_edge(&mut edge, *pred, arg);
+                            analysis.join(&mut state, &edge);
+                        }
+                    }
+                }
+                let _ = results.states_in.insert(bid, state.clone());
+
+                analysis.transfer_phinodes(&mut state, bid, &block.phinodes);
+                for (iid, instruction) in block.instructions.iter().enumerate() {
+                    analysis.transfer_instruction(
+                        &mut state,
+                        RegisterId::temp(bid, iid),
+                        instruction,
+                    );
+                }
+                analysis.transfer_exit(&mut state, bid, &block.exit);
+
+                let changed = results.states_out.get(&bid) != Some(&state);
+                let _ = results.states_out.insert(bid, state);
+                (changed, cfg.successors(bid))
+            }
+            Direction::Backward => {
+                let mut state = results.bottom.clone();
+                if matches!(block.exit, BlockExit::Return { .. }) {
+                    analysis.join(&mut state, &analysis.boundary(code));
+                }
+                for arg in block.exit.jump_args() {
+                    let mut edge = some_or!(results.states_in.get(&arg.bid), continue).clone();
+                    analysis.transfer_edge(&mut edge, bid, arg);
+                    analysis.join(&mut state, &edge);
+                }
+                let _ = results.states_out.insert(bid, state.clone());
+
+                analysis.transfer_exit(&mut state, bid, &block.exit);
+                for (iid, instruction) in block.instructions.iter().enumerate().rev() {
+                    analysis.transfer_instruction(
+                        &mut state,
+                        RegisterId::temp(bid, iid),
+                        instruction,
+                    );
+                }
+                analysis.transfer_phinodes(&mut state, bid, &block.phinodes);
+
+                let changed = results.states_in.get(&bid) != Some(&state);
+                let _ = results.states_in.insert(bid, state);
+                (changed, cfg.predecessors(bid))
+            }
+        };
+
+        if changed {
+            worklist.extend(
+                dependents
+                    .iter()
+                    .filter_map(|dependent| positions.get(dependent)),
+            );
+        }
+    }
+
+    results
+}
+
+/// Live registers, i.e., the phinodes and instruction results that may be read later.
+///
+/// Local allocations are constant and are never live.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Liveness;
+
+impl Liveness {
+    fn gen(state: &mut HashSet<RegisterId>, operand: &Operand) {
+        if let Some((rid, _)) = operand.get_register() {
+            if !matches!(rid, RegisterId::Local { .. }) {
+                let _ = state.insert(*rid);
+            }
+        }
+    }
+}
+
+impl Analysis for Liveness {
+    type Domain = HashSet<RegisterId>;
+
+    const DIRECTION: Direction = Direction::Backward;
+
+    fn bottom(&self, _code: &FunctionDefinition) -> Self::Domain {
+        HashSet::new()
+    }
+
+    fn boundary(&self, _code: &FunctionDefinition) -> Self::Domain {
+        HashSet::new()
+    }
+
+    fn join(&self, state: &mut Self::Domain, other: &Self::Domain) {
+        state.extend(other.iter().copied());
+    }
+
+    fn transfer_phinodes(&self, state: &mut Self::Domain, bid: BlockId, phinodes: &[Named<Dtype>]) {
+        for aid in 0..phinodes.len() {
+            let _ = state.remove(&RegisterId::arg(bid, aid));
+        }
+    }
+
+    fn transfer_instruction(
+        &self,
+        state: &mut Self::Domain,
+        rid: RegisterId,
+        instruction: &Instruction,
+    ) {
+        let _ = state.remove(&rid);
+        instruction.walk_operands(|operand| Self::gen(state, operand));
+    }
+
+    fn transfer_exit(&self, state: &mut Self::Domain, _bid: BlockId, exit: &BlockExit) {
+        exit.walk_operands(|operand| Self::gen(state, operand));
+    }
+}
+
+/// The value a register holds along every path reaching a point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reaching {
+    /// The register holds the constant along every path.
+    Constant(Constant),
+    /// The register may hold different values.
+    Varying,
+}
+
+impl Reaching {
+    fn join(&mut self, other: &Self) {
+        if self != other {
+            *self = Self::Varying;
+        }
+    }
+}
+
+/// Registers holding a constant, i.e., sparse conditional constant propagation without pruning
+/// the branches.
+///
+/// A register defined along no path reaching a point is absent from its state. Only binary
+/// operators that cannot trap are folded, and never when a signed operation overflows.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ReachingConstants;
+
+impl ReachingConstants {
+    fn resolve(state: &HashMap<RegisterId, Reaching>, operand: &Operand) -> Reaching {
+        match operand {
+            Operand::Constant(constant) => Reaching::Constant(constant.clone()),
+            Operand::Register { rid, .. } => state.get(rid).cloned().unwrap_or(Reaching::Varying),
+        }
+    }
+
+    fn fold(state: &HashMap<RegisterId, Reaching>, instruction: &Instruction) -> Option<Constant> {
+        let (op, lhs, rhs, dtype) = if let Instruction::BinOp {
+            op,
+            lhs,
+            rhs,
+            dtype,
+        } = instruction
+        {
+            (op, lhs, rhs, dtype)
+        } else {
+            return None;
+        };
+        if !matches!(
+            op,
+            ast::BinaryOperator::Plus
+                | ast::BinaryOperator::Minus
+                | ast::BinaryOperator::Multiply
+                | ast::BinaryOperator::BitwiseAnd
+                | ast::BinaryOperator::BitwiseXor
+                | ast::BinaryOperator::BitwiseOr
+                | ast::BinaryOperator::Equals
+                | ast::BinaryOperator::NotEquals
+                | ast::BinaryOperator::Less
+                | ast::BinaryOperator::Greater
+                | ast::BinaryOperator::LessOrEqual
+                | ast::BinaryOperator::GreaterOrEqual
+        ) {
+            return None;
+        }
+
+        let (lhs, rhs) = match (Self::resolve(state, lhs), Self::resolve(state, rhs)) {
+            (Reaching::Constant(lhs), Reaching::Constant(rhs)) => (lhs, rhs),
+            _ => return None,
+        };
+        if !lhs.is_integer_constant() || !rhs.is_integer_constant() {
+            return None;
+        }
+
+        let lhs = Value::try_from(lhs).ok()?;
+        let rhs = Value::try_from(rhs).ok()?;
+        if calculator::has_signed_overflow(op, &lhs, &rhs) {
+            return None;
+        }
+        let result = calculator::calculate_binary_operator_expression(op, lhs, rhs).ok()?;
+        let (value, _, _) = result.get_int()?;
+        Some(Constant::int(value, dtype.clone()))
+    }
+}
+
+impl Analysis for ReachingConstants {
+    type Domain = HashMap<RegisterId, Reaching>;
+
+    const DIRECTION: Direction = Direction::Forward;
+
+    fn bottom(&self, _code: &FunctionDefinition) -> Self::Domain {
+        HashMap::new()
+    }
+
+    fn boundary(&self, code: &FunctionDefinition) -> Self::Domain {
+        let params = code.blocks[&code.bid_init].phinodes.len();
+        (0..params)
+            .map(|aid| (RegisterId::arg(code.bid_init, aid), Reaching::Varying))
+            .collect()
+    }
+
+    fn join(&self, state: &mut Self::Domain, other: &Self::Domain) {
+        for (rid, value) in other {
+            match state.get_mut(rid) {
+                Some(reaching) => reaching.join(value),
+                None => {
+                    let _ = state.insert(*rid, value.clone());
+                }
+            }
+        }
+    }
+
+    fn transfer_instruction(
+        &self,
+        state: &mut Self::Domain,
+        rid: RegisterId,
+        instruction: &Instruction,
+    ) {
+        let value = Self::fold(state, instruction).map_or(Reaching::Varying, Reaching::Constant);
+        let _ = state.insert(rid, value);
+    }
+
+    fn transfer_exit(&self, _state: &mut Self::Domain, _bid: BlockId, _exit: &BlockExit) {}
+
+    fn transfer_edge(&self, state: &mut Self::Domain, _bid: BlockId, arg: &JumpArg) {
+        let values = arg
+            .args
+            .iter()
+            .map(|operand| Self::resolve(state, operand))
+            .collect::<Vec<_>>();
+        for (aid, value) in values.into_iter().enumerate() {
+            let _ = state.insert(RegisterId::arg(arg.bid, aid), value);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use core::iter;
+
+use crate::ir::*;
+use crate::opt::analysis::CallGraph;
+use crate::*;
+
+/// Removes the functions unreachable from `main` and the functions in `keep`.
+///
+/// A function is reachable if it is called or its address is taken by a reachable function, or its
+/// address initializes a global variable, e.g., `int (*fp)(void) = f;`, which keeps the variable
+/// pointing to a function. See [`CallGraph`] for how indirect calls are handled.
+///
+/// A library, which has no `main`, is rooted at every function it defines instead, since they all
+/// have external linkage and may be called by the units it is linked with.
+#[derive(Default, Clone, Debug)]
+pub struct DeadFunction {
+    keep: Vec<String>,
+    is_library: bool,
+}
+
+impl DeadFunction {
+    const ENTRY: &'static str = "main";
+
+    pub fn new(keep: Vec<String>) -> Self {
+        Self {
+            keep,
+            is_library: false,
+        }
+    }
+
+    /// Creates the pass for a library, compiled with `--lib`.
+    pub fn library(keep: Vec<String>) -> Self {
+        Self {
+            keep,
+            is_library: true,
+        }
+    }
+}
+
+impl Optimize<TranslationUnit> for DeadFunction {
+    fn optimize(&mut self, code: &mut TranslationUnit) -> bool {
+        let call_graph = CallGraph::new(code);
+        let exported = code
+            .decls
+            .iter()
+            .filter(|(_, decl)| {
+                self.is_library && decl.get_function().is_some_and(|(_, def)| def.is_some())
+            })
+            .map(|(name, _)| name.as_str());
+        let roots = iter::once(Self::ENTRY)
+            .chain(exported)
+            .chain(call_graph.global_references().iter().map(String::as_str))
+            .chain(self.keep.iter().map(String::as_str));
+        let reachable = call_graph.reachable_from(roots);
+
+        let len = code.decls.len();
+        code.decls.retain(|name, decl| {
+            decl.get_function().is_none() || reachable.contains(name.as_str())
+        });
+
+        code.decls.len() != len
+    }
+}
@@ -0,0 +1,67 @@
+use core::ops::Deref;
+use std::collections::{BTreeSet, HashSet};
+
+use crate::ir::*;
+use crate::opt::analysis::may_alias;
+use crate::opt::opt_utils::*;
+use crate::opt::FunctionPass;
+use crate::*;
+
+pub type DeadStore = FunctionPass<DeadStoreInner>;
+
+/// Removes the stores overwritten by a later store to the same pointer in their block before
+/// anything may read them, e.g., the first store of `*p = 1; *p = 2;`.
+///
+/// A store may be read by a call, or by a load whose pointer may alias its pointer by
+/// [`may_alias`], which tells apart the pointers derived from a `restrict`-qualified parameter
+/// from the others. For example, the first store of `*p = 1; x = *q; *p = 2;` is removed only if
+/// `p` is a `restrict`-qualified parameter.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct DeadStoreInner {}
+
+impl Optimize<FunctionDefinition> for DeadStoreInner {
+    fn optimize(&mut self, code: &mut FunctionDefinition) -> bool {
+        let mut used = HashSet::new();
+        let mut add_used = |operand: &Operand| {
+            if let Some((rid, _)) = operand.get_register() {
+                let _ = used.insert(*rid);
+            }
+        };
+        for block in code.blocks.values() {
+            for instruction in &block.instructions {
+                instruction.walk_operands(&mut add_used);
+            }
+            block.exit.walk_operands(&mut add_used);
+        }
+
+        let mut dead = BTreeSet::new();
+        for (bid, block) in &code.blocks {
+            // The stores not read since they were executed, by their positions and pointers.
+            let mut pending = Vec::<(usize, &Operand)>::new();
+            for (iid, instruction) in block.instructions.iter().enumerate() {
+                match instruction.deref() {
+                    Instruction::Store { ptr, .. } => {
+                        if let Some(index) = pending.iter().position(|(_, other)| *other == ptr) {
+                            let (overwritten, _) = pending.remove(index);
+                            if !used.contains(&RegisterId::temp(*bid, overwritten)) {
+                                let _ = dead.insert((*bid, overwritten));
+                            }
+                        }
+                        pending.push((iid, ptr));
+                    }
+                    Instruction::Load { ptr } => {
+                        pending.retain(|(_, other)| !may_alias(code, other, ptr));
+                    }
+                    Instruction::Call { .. } => pending.clear(),
+                    _ => {}
+                }
+            }
+        }
+
+        if dead.is_empty() {
+            return false;
+        }
+        remove_instructions(code, &dead);
+        true
+    }
+}
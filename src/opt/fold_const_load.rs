@@ -0,0 +1,158 @@
+use core::ops::Deref;
+use std::collections::HashMap;
+
+use itertools::izip;
+
+use crate::ir::*;
+use crate::opt::opt_utils::*;
+use crate::*;
+
+/// Folds loads from constant global variables at constant offsets to their initializer values.
+///
+/// Writing to a constant global variable is undefined behavior (see
+/// [`Declaration::is_constant`]), so such a load always reads the initial value. The value of a
+/// scalar variable is annotated with its name (see [`ConstantOrigin`]).
+#[derive(Default, Clone, Copy, Debug)]
+pub struct FoldConstLoad {}
+
+impl Optimize<TranslationUnit> for FoldConstLoad {
+    fn optimize(&mut self, code: &mut TranslationUnit) -> bool {
+        let mut initial_values = HashMap::new();
+        for (name, decl) in &code.decls {
+            // The initial value of an `extern` variable is given by another unit.
+            if !decl.is_constant() || decl.is_extern() {
+                continue;
+            }
+
+            let (dtype, initializer) = decl.get_variable().expect("`decl` must be variable");
+            let value = if let Some(initializer) = initializer {
+                some_or!(
+                    Value::try_from_initializer(initializer, dtype, &code.structs).ok(),
+                    continue
+                )
+            } else {
+                // A variable without an initializer is zero-initialized.
+                some_or!(
+                    Value::default_from_dtype(dtype, &code.structs).ok(),
+                    continue
+                )
+            };
+            let _unused = initial_values.insert(name.clone(), value);
+        }
+
+        if initial_values.is_empty() {
+            return false;
+        }
+
+        let structs = &code.structs;
+        let mut changed = false;
+        for decl in code.decls.values_mut() {
+            let (_, definition) = some_or!(decl.get_function_mut(), continue);
+            let definition = some_or!(definition, continue);
+            let global_pointers = definition.global_pointers();
+
+            let mut replaces = HashMap::new();
+            for (bid, block) in &definition.blocks {
+                for (iid, instruction) in block.instructions.iter().enumerate() {
+                    let ptr = if let Instruction::Load { ptr } = instruction.deref() {
+                        ptr
+                    } else {
+                        continue;
+                    };
+
+                    // Whether the variable itself is loaded, e.g., `SIZE` of `const int SIZE = 4;`
+                    let (name, offset, is_whole) = match ptr {
+                        Operand::Constant(Constant::GlobalVariable { name, .. }) => (name, 0, true),
+                        Operand::Register { rid, .. } => {
+                            let (name, offset) = some_or!(global_pointers.get(rid), continue);
+                            (name, some_or!(*offset, continue), false)
+                        }
+                        _ => continue,
+                    };
+                    let value = some_or!(initial_values.get(name), continue);
+                    let offset = some_or!(usize::try_from(offset).ok(), continue);
+
+                    let dtype = instruction.dtype();
+                    let constant = some_or!(constant_at(value, offset, &dtype, structs), continue);
+                    // The constant is annotated with the variable it stands for in the IR dumps.
+                    let constant = if is_whole {
+                        constant.with_origin(name.clone())
+                    } else {
+                        constant
+                    };
+                    let rid = RegisterId::temp(*bid, iid);
+                    let _unused = replaces.insert(rid, Operand::constant(constant));
+                }
+            }
+
+            changed |= replace_operands(definition, &replaces);
+        }
+
+        changed
+    }
+}
+
+/// Returns the scalar of `dtype` stored at `offset` bytes in `value`, if any.
+fn constant_at(
+    value: &Value,
+    offset: usize,
+    dtype: &Dtype,
+    structs: &HashMap<String, Option<Dtype>>,
+) -> Option<Constant> {
+    match value {
+        Value::Int {
+            value,
+            width,
+            is_signed,
+        } if offset == 0 => {
+            let constant = Constant::Int {
+                value: *value,
+                width: *width,
+                is_signed: *is_signed,
+                origin: ConstantOrigin::default(),
+            };
+            (&constant.dtype() == dtype).then_some(constant)
+        }
+        Value::Float { value, width } if offset == 0 => {
+            let constant = Constant::Float {
+                value: *value,
+                width: *width,
+            };
+            (&constant.dtype() == dtype).then_some(constant)
+        }
+        Value::Array {
+            inner_dtype,
+            values,
+        } => {
+            let (size_of, _) = inner_dtype.size_align_of(structs).ok()?;
+            if size_of == 0 {
+                return None;
+            }
+
+            constant_at(
+                values.get(offset / size_of)?,
+                offset % size_of,
+                dtype,
+                structs,
+            )
+        }
+        Value::Struct { name, fields } => {
+            let struct_type = structs.get(name)?.as_ref()?;
+            let (_, _, offsets) = struct_type.get_struct_size_align_offsets()?.as_ref()?;
+
+            for (index, (field, field_offset)) in izip!(fields, offsets).enumerate() {
+                let (size_of, _) = field.dtype().size_align_of(structs).ok()?;
+                if *field_offset <= offset && offset < field_offset + size_of {
+                    // The storage unit of a bit-field also holds the adjacent bit-fields.
+                    if struct_type.get_struct_bit_field(index).is_some() {
+                        return None;
+                    }
+                    return constant_at(field, offset - field_offset, dtype, structs);
+                }
+            }
+
+            None
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,65 @@
+use core::ops::Deref;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::ir::*;
+use crate::opt::analysis::LocalPointees;
+use crate::opt::opt_utils::*;
+use crate::opt::FunctionPass;
+use crate::*;
+
+pub type ForwardPointers = FunctionPass<ForwardPointersInner>;
+
+/// Forwards the accesses through the local pointer variables always pointing to the same local
+/// variable to that variable, e.g., `*p = 5` to `x = 5` after `int *p = &x`, and removes the
+/// pointer variables, so that register promotion may promote the variables they pointed to.
+///
+/// The pointer variables are resolved by [`LocalPointees`]. The pointer of each load and store
+/// through a value loaded from such a variable is replaced by the address of its pointee, and the
+/// loads from and the stores to the variable are removed along with its allocation. The other
+/// pointer variables, e.g., those pointing to different variables or used in pointer arithmetic,
+/// are left intact.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct ForwardPointersInner {}
+
+impl Optimize<FunctionDefinition> for ForwardPointersInner {
+    fn optimize(&mut self, code: &mut FunctionDefinition) -> bool {
+        let pointees = LocalPointees::new(code);
+        if pointees.is_empty() {
+            return false;
+        }
+
+        let mut replaces = HashMap::new();
+        let mut removals = BTreeSet::new();
+        for (bid, block) in &code.blocks {
+            for (iid, instruction) in block.instructions.iter().enumerate() {
+                let ptr = match instruction.deref() {
+                    Instruction::Load { ptr } | Instruction::Store { ptr, .. } => ptr,
+                    _ => continue,
+                };
+                let aid = match ptr.get_register() {
+                    Some((RegisterId::Local { aid }, _)) => *aid,
+                    _ => continue,
+                };
+                let pointee = *some_or!(pointees.pointees().get(&aid), continue);
+
+                let _unused = removals.insert((*bid, iid));
+                let rid = RegisterId::temp(*bid, iid);
+                if pointees.copy_of(&rid).is_some() {
+                    let address = Operand::register(
+                        RegisterId::local(pointee),
+                        code.allocations[aid].deref().clone(),
+                    );
+                    let _unused = replaces.insert(rid, address);
+                }
+            }
+        }
+
+        // The loaded pointers are only used as the pointers of accesses, which now use the
+        // addresses of the pointees instead.
+        let _unused = replace_operands(code, &replaces);
+        remove_instructions(code, &removals);
+        remove_allocations(code, &pointees.pointees().keys().copied().collect());
+
+        true
+    }
+}
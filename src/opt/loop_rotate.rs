@@ -0,0 +1,402 @@
+use core::ops::Deref;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use crate::ir::*;
+use crate::opt::analysis::{Cfg, Dominators, Loops};
+use crate::opt::FunctionPass;
+use crate::*;
+
+pub type LoopRotate = FunctionPass<LoopRotateInner>;
+
+/// Rotates the loops testing their conditions at the top into the bottom-test form.
+///
+/// `while (cond) body` is lowered to a header testing `cond` and a body jumping back to the
+/// header, which takes two branches per iteration. Rotated into `if (cond) { do body while
+/// (cond); }`, it takes one, and the header left before the loop guards it, e.g., so that the
+/// computations hoisted out of the loop are skipped if it is never entered.
+///
+/// The header is duplicated at the end of the latch jumping back to it, or into a new block the
+/// latches jump to if there are several of them, e.g., because of `continue`. The values computed
+/// by the header and used in the loop or after it are passed to the body and the exit as block
+/// arguments. A loop is rotated only if its header has at most `max_header_size` instructions and
+/// no latch already exits the loop.
+#[derive(Clone, Copy, Debug)]
+pub struct LoopRotateInner {
+    max_header_size: usize,
+}
+
+impl Default for LoopRotateInner {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_MAX_HEADER_SIZE)
+    }
+}
+
+impl LoopRotateInner {
+    /// The default maximum number of instructions of the headers to duplicate.
+    pub const DEFAULT_MAX_HEADER_SIZE: usize = 8;
+
+    pub fn new(max_header_size: usize) -> Self {
+        Self { max_header_size }
+    }
+}
+
+/// A loop to rotate.
+#[derive(Debug)]
+struct Rotation {
+    header: BlockId,
+    /// The successor of the header in the loop, whose only predecessor is the header.
+    body: BlockId,
+    /// The successor of the header out of the loop.
+    exit: BlockId,
+    /// The blocks of the loop other than the header.
+    members: BTreeSet<BlockId>,
+    /// The blocks of the loop jumping back to the header.
+    latches: BTreeSet<BlockId>,
+    /// The registers of the header used in the loop.
+    used_in_loop: HashSet<RegisterId>,
+    /// The registers of the header used after the loop.
+    used_after_loop: HashSet<RegisterId>,
+}
+
+impl Optimize<FunctionDefinition> for LoopRotateInner {
+    fn optimize(&mut self, code: &mut FunctionDefinition) -> bool {
+        let mut changed = false;
+
+        // Rotating a loop changes the control flow graph, so the loops are found again.
+        while let Some(rotation) = self.candidate(code) {
+            rotate(code, &rotation);
+            changed = true;
+        }
+
+        changed
+    }
+}
+
+impl LoopRotateInner {
+    /// Returns a loop to rotate, if any.
+    fn candidate(&self, code: &FunctionDefinition) -> Option<Rotation> {
+        let cfg = Cfg::new(code);
+        let dominators = Dominators::new(code, &cfg);
+        let loops = Loops::new(&cfg, &dominators);
+
+        dominators
+            .reverse_postorder()
+            .iter()
+            .find_map(|header| self.rotation(code, *header, &cfg, &dominators, &loops))
+    }
+
+    /// Returns the rotation of the loop headed by `header`, if it is a loop to rotate.
+    fn rotation(
+        &self,
+        code: &FunctionDefinition,
+        header: BlockId,
+        cfg: &Cfg,
+        dominators: &Dominators,
+        loops: &Loops,
+    ) -> Option<Rotation> {
+        let in_loop = |bid: BlockId| loops.headers(bid).contains(&header);
+        if header == code.bid_init || !in_loop(header) {
+            return None;
+        }
+
+        let block = &code.blocks[&header];
+        if block.instructions.len() > self.max_header_size {
+            return None;
+        }
+        let (body, exit) = if let BlockExit::ConditionalJump {
+            arg_then, arg_else, ..
+        } = &block.exit
+        {
+            match (in_loop(arg_then.bid), in_loop(arg_else.bid)) {
+                (true, false) => (arg_then.bid, arg_else.bid),
+                (false, true) => (arg_else.bid, arg_then.bid),
+                _ => return None,
+            }
+        } else {
+            return None;
+        };
+
+        // The blocks unreachable from the entry may jump anywhere, and are ignored.
+        let predecessors = |bid: BlockId| {
+            cfg.predecessors(bid)
+                .iter()
+                .copied()
+                .filter(|pred| dominators.is_reachable(*pred))
+                .collect::<BTreeSet<_>>()
+        };
+        if body == header || predecessors(body).into_iter().any(|pred| pred != header) {
+            return None;
+        }
+
+        // A loop a latch of which exits it is already in the bottom-test form.
+        let latches = predecessors(header)
+            .into_iter()
+            .filter(|pred| in_loop(*pred))
+            .collect::<BTreeSet<_>>();
+        let exits = |bid: BlockId| cfg.successors(bid).iter().any(|succ| !in_loop(*succ));
+        if latches.iter().any(|latch| exits(*latch)) {
+            return None;
+        }
+
+        let members = code
+            .blocks
+            .keys()
+            .copied()
+            .filter(|bid| *bid != header && in_loop(*bid))
+            .collect::<BTreeSet<_>>();
+
+        let mut used_in_loop = HashSet::new();
+        let mut used_after_loop = HashSet::new();
+        for (bid, block) in &code.blocks {
+            if *bid == header || !dominators.is_reachable(*bid) {
+                continue;
+            }
+
+            let used = if members.contains(bid) {
+                &mut used_in_loop
+            } else {
+                &mut used_after_loop
+            };
+            let mut add_use = |operand: &Operand| {
+                if let Some((rid, _)) = operand.get_register() {
+                    if rid_bid(rid) == Some(header) {
+                        let _ = used.insert(*rid);
+                    }
+                }
+            };
+            for instruction in &block.instructions {
+                instruction.walk_operands(&mut add_use);
+            }
+            block.exit.walk_operands(&mut add_use);
+        }
+
+        // The values used after the loop are passed to the exit from every block leaving the loop,
+        // which therefore must be the only predecessors of the exit.
+        if !used_after_loop.is_empty() {
+            let leaves_elsewhere = members.iter().any(|member| {
+                cfg.successors(*member)
+                    .iter()
+                    .any(|succ| !in_loop(*succ) && *succ != exit)
+            });
+            if leaves_elsewhere || predecessors(exit).into_iter().any(|pred| !in_loop(pred)) {
+                return None;
+            }
+        }
+
+        Some(Rotation {
+            header,
+            body,
+            exit,
+            members,
+            latches,
+            used_in_loop,
+            used_after_loop,
+        })
+    }
+}
+
+/// Returns the block defining the register, if it is not a local allocation.
+fn rid_bid(rid: &RegisterId) -> Option<BlockId> {
+    match rid {
+        RegisterId::Local { .. } => None,
+        RegisterId::Arg { bid, .. } | RegisterId::Temp { bid, .. } => Some(*bid),
+    }
+}
+
+/// Rotates the loop of `rotation`.
+fn rotate(code: &mut FunctionDefinition, rotation: &Rotation) {
+    let Rotation {
+        header, body, exit, ..
+    } = *rotation;
+    let header_block = code.blocks[&header].clone();
+
+    // The values of the header used out of it: they are passed to the body, since the header
+    // or its copy may compute them, and to the exit if they are used after the loop.
+    let values = header_block
+        .phinodes
+        .iter()
+        .enumerate()
+        .map(|(i, phinode)| {
+            let dtype = phinode.deref().clone();
+            (RegisterId::arg(header, i), phinode.name().cloned(), dtype)
+        })
+        .chain(
+            header_block
+                .instructions
+                .iter()
+                .enumerate()
+                .map(|(i, instruction)| {
+                    let dtype = instruction.dtype();
+                    (
+                        RegisterId::temp(header, i),
+                        instruction.name().cloned(),
+                        dtype,
+                    )
+                }),
+        );
+    let live = values
+        .filter(|(rid, ..)| {
+            rotation.used_in_loop.contains(rid) || rotation.used_after_loop.contains(rid)
+        })
+        .collect::<Vec<_>>();
+    let live_after = live
+        .iter()
+        .filter(|(rid, ..)| rotation.used_after_loop.contains(rid))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let renames = |values: &[(RegisterId, Option<String>, Dtype)], bid: BlockId| {
+        let base = code.blocks[&bid].phinodes.len();
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, (rid, ..))| (*rid, RegisterId::arg(bid, base + i)))
+            .collect::<HashMap<_, _>>()
+    };
+    let in_loop_renames = renames(&live, body);
+    let after_loop_renames = renames(&live_after, exit);
+
+    for (bid, block) in &mut code.blocks {
+        if *bid == header {
+            continue;
+        }
+
+        let is_member = rotation.members.contains(bid);
+        let renames = if is_member {
+            &in_loop_renames
+        } else {
+            &after_loop_renames
+        };
+        let mut rename = |operand: &mut Operand| {
+            let (rid, _) = some_or!(operand.get_register_mut(), return);
+            if let Some(new_rid) = renames.get(rid) {
+                *rid = *new_rid;
+            }
+        };
+        for instruction in &mut block.instructions {
+            instruction.walk_operands_mut(&mut rename);
+        }
+        block.exit.walk_operands_mut(&mut rename);
+
+        // The blocks leaving the loop pass the values of the current iteration, and those
+        // unreachable from the entry pass undefined values.
+        block.exit.walk_jump_args(|arg| {
+            if arg.bid == body {
+                arg.args.extend(
+                    live.iter()
+                        .map(|(_, _, dtype)| Operand::constant(Constant::undef(dtype.clone()))),
+                );
+            } else if arg.bid == exit {
+                arg.args.extend(live_after.iter().map(|(rid, _, dtype)| {
+                    if is_member {
+                        Operand::register(in_loop_renames[rid], dtype.clone())
+                    } else {
+                        Operand::constant(Constant::undef(dtype.clone()))
+                    }
+                }));
+            }
+        });
+    }
+
+    let phinodes = |values: &[(RegisterId, Option<String>, Dtype)]| {
+        values
+            .iter()
+            .map(|(_, name, dtype)| Named::new(name.clone(), dtype.clone()))
+            .collect::<Vec<_>>()
+    };
+    let body_phinodes = phinodes(&live);
+    let exit_phinodes = phinodes(&live_after);
+    code.blocks
+        .get_mut(&body)
+        .expect("`body` must be a block")
+        .phinodes
+        .extend(body_phinodes);
+    code.blocks
+        .get_mut(&exit)
+        .expect("`exit` must be a block")
+        .phinodes
+        .extend(exit_phinodes);
+
+    // The only latch jumping to the header unconditionally tests the condition itself, and
+    // otherwise the latches jump to a new block testing it.
+    let merged_latch = rotation.latches.iter().copied().find(|latch| {
+        rotation.latches.len() == 1 && matches!(code.blocks[latch].exit, BlockExit::Jump { .. })
+    });
+    let mut copies = HashMap::new();
+    let (bottom, offset) = if let Some(latch) = merged_latch {
+        let latch_block = &code.blocks[&latch];
+        if let BlockExit::Jump { arg } = &latch_block.exit {
+            for (i, operand) in arg.args.iter().enumerate() {
+                let _ = copies.insert(RegisterId::arg(header, i), operand.clone());
+            }
+        }
+        (latch, latch_block.instructions.len())
+    } else {
+        let bid = BlockId(code.blocks.keys().next_back().map_or(0, |bid| bid.0 + 1));
+        for (i, phinode) in header_block.phinodes.iter().enumerate() {
+            let operand = Operand::register(RegisterId::arg(bid, i), phinode.deref().clone());
+            let _ = copies.insert(RegisterId::arg(header, i), operand);
+        }
+        (bid, 0)
+    };
+    for (i, instruction) in header_block.instructions.iter().enumerate() {
+        let operand = Operand::register(RegisterId::temp(bottom, offset + i), instruction.dtype());
+        let _ = copies.insert(RegisterId::temp(header, i), operand);
+    }
+
+    let mut copy = |operand: &mut Operand| {
+        let (rid, _) = some_or!(operand.get_register(), return);
+        if let Some(new_operand) = copies.get(rid) {
+            *operand = new_operand.clone();
+        }
+    };
+    let mut instructions = header_block.instructions.clone();
+    for instruction in &mut instructions {
+        instruction.walk_operands_mut(&mut copy);
+    }
+    let mut test = header_block.exit.clone();
+    test.walk_operands_mut(&mut copy);
+
+    // The header passes the values it computes, and the test their copies.
+    let header_exit = &mut code
+        .blocks
+        .get_mut(&header)
+        .expect("`header` must be a block")
+        .exit;
+    for (block_exit, is_copy) in [(header_exit, false), (&mut test, true)] {
+        block_exit.walk_jump_args(|arg| {
+            let values = if arg.bid == body { &live } else { &live_after };
+            arg.args.extend(values.iter().map(|(rid, _, dtype)| {
+                if is_copy {
+                    copies[rid].clone()
+                } else {
+                    Operand::register(*rid, dtype.clone())
+                }
+            }));
+        });
+    }
+
+    if merged_latch.is_some() {
+        let block = code
+            .blocks
+            .get_mut(&bottom)
+            .expect("`bottom` must be a block");
+        block.instructions.extend(instructions);
+        block.exit = test;
+    } else {
+        for latch in &rotation.latches {
+            let block = code.blocks.get_mut(latch).expect("`latch` must be a block");
+            block.exit.walk_jump_args(|arg| {
+                if arg.bid == header {
+                    arg.bid = bottom;
+                }
+            });
+        }
+        let block = Block {
+            phinodes: header_block.phinodes,
+            instructions,
+            exit: test,
+        };
+        let _ = code.blocks.insert(bottom, block);
+    }
+}
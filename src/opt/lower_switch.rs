@@ -0,0 +1,208 @@
+use lang_c::ast;
+
+use crate::ir::*;
+use crate::opt::FunctionPass;
+use crate::*;
+
+pub type LowerSwitch = FunctionPass<LowerSwitchInner>;
+
+/// Lowers the `switch`es into comparisons by the [`SwitchStrategy`] the `switch_thresholds` of
+/// `options` choose.
+///
+/// Under [`SwitchStrategy::Linear`], the value is compared with each case in turn. Under
+/// [`SwitchStrategy::Tree`], the sorted cases are bisected by `cmp lt` with the first case of the
+/// upper half until at most `max_linear_cases` of them are left, which are then compared in turn,
+/// so that the number of comparisons grows logarithmically with the number of cases. Each leaf
+/// jumps to the default target if none of its cases matches. Under [`SwitchStrategy::JumpTable`],
+/// the `switch` is kept for the back-end.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct LowerSwitchInner {
+    options: CompileOptions,
+}
+
+impl LowerSwitchInner {
+    pub fn new(options: CompileOptions) -> Self {
+        Self { options }
+    }
+}
+
+/// A case of a `switch`, with its value sign-extended if the value of the `switch` is signed.
+type Case = (i128, Constant, JumpArg);
+
+impl Optimize<FunctionDefinition> for LowerSwitchInner {
+    fn optimize(&mut self, code: &mut FunctionDefinition) -> bool {
+        let switches = code
+            .blocks
+            .iter()
+            .filter(|(_, block)| matches!(block.exit, BlockExit::Switch { .. }))
+            .map(|(bid, _)| *bid)
+            .collect::<Vec<_>>();
+
+        switches
+            .into_iter()
+            .map(|bid| self.lower(code, bid))
+            .fold(false, |l, r| l | r)
+    }
+}
+
+impl LowerSwitchInner {
+    fn lower(&self, code: &mut FunctionDefinition, bid: BlockId) -> bool {
+        let (value, default, cases) = if let BlockExit::Switch {
+            value,
+            default,
+            cases,
+        } = &code.blocks[&bid].exit
+        {
+            (value.clone(), default.clone(), cases.clone())
+        } else {
+            return false;
+        };
+
+        let mut cases = some_or!(
+            cases
+                .into_iter()
+                .map(|(constant, arg)| Some((case_value(&constant)?, constant, arg)))
+                .collect::<Option<Vec<Case>>>(),
+            return false
+        );
+        cases.sort_by_key(|(value, _, _)| *value);
+
+        let thresholds = self.options.switch_thresholds;
+        let values = cases.iter().map(|(value, _, _)| *value).collect::<Vec<_>>();
+        let leaf_size = match thresholds.strategy(&values) {
+            SwitchStrategy::Linear => cases.len(),
+            SwitchStrategy::Tree => thresholds.max_linear_cases.max(1),
+            SwitchStrategy::JumpTable => return false,
+        };
+
+        let mut lowering = Lowering {
+            code,
+            value,
+            default,
+            leaf_size,
+        };
+        lowering.tree(bid, &cases);
+        true
+    }
+}
+
+struct Lowering<'a> {
+    code: &'a mut FunctionDefinition,
+    value: Operand,
+    default: JumpArg,
+    leaf_size: usize,
+}
+
+impl Lowering<'_> {
+    /// Dispatches the value to `cases` from the end of the block `bid`.
+    fn tree(&mut self, bid: BlockId, cases: &[Case]) {
+        if cases.len() <= self.leaf_size {
+            self.chain(bid, cases);
+            return;
+        }
+
+        let (lower, upper) = cases.split_at(cases.len() / 2);
+        let condition = self.compare(bid, ast::BinaryOperator::Less, &upper[0].1);
+        let bid_lower = self.insert_block();
+        let bid_upper = self.insert_block();
+        self.set_exit(
+            bid,
+            BlockExit::ConditionalJump {
+                condition,
+                arg_then: JumpArg::new(bid_lower, Vec::new()),
+                arg_else: JumpArg::new(bid_upper, Vec::new()),
+            },
+        );
+
+        self.tree(bid_lower, lower);
+        self.tree(bid_upper, upper);
+    }
+
+    /// Compares the value with each of `cases` in turn from the end of the block `bid`, and jumps
+    /// to the default target if none of them matches.
+    fn chain(&mut self, mut bid: BlockId, cases: &[Case]) {
+        if cases.is_empty() {
+            let arg = self.default.clone();
+            self.set_exit(bid, BlockExit::Jump { arg });
+            return;
+        }
+
+        for (index, (_, constant, arg)) in cases.iter().enumerate() {
+            let condition = self.compare(bid, ast::BinaryOperator::Equals, constant);
+            let arg_else = if index + 1 < cases.len() {
+                JumpArg::new(self.insert_block(), Vec::new())
+            } else {
+                self.default.clone()
+            };
+            let next = arg_else.bid;
+            self.set_exit(
+                bid,
+                BlockExit::ConditionalJump {
+                    condition,
+                    arg_then: arg.clone(),
+                    arg_else,
+                },
+            );
+            bid = next;
+        }
+    }
+
+    /// Appends the comparison `value op constant` to the block `bid`, and returns its result.
+    fn compare(&mut self, bid: BlockId, op: ast::BinaryOperator, constant: &Constant) -> Operand {
+        let block = self
+            .code
+            .blocks
+            .get_mut(&bid)
+            .expect("`bid` must be a block");
+        let iid = block.instructions.len();
+        block.instructions.push(Named::new(
+            None,
+            Instruction::BinOp {
+                op,
+                lhs: self.value.clone(),
+                rhs: Operand::constant(constant.clone()),
+                dtype: Dtype::BOOL,
+            },
+        ));
+        Operand::register(RegisterId::temp(bid, iid), Dtype::BOOL)
+    }
+
+    fn set_exit(&mut self, bid: BlockId, exit: BlockExit) {
+        self.code
+            .blocks
+            .get_mut(&bid)
+            .expect("`bid` must be a block")
+            .exit = exit;
+    }
+
+    /// Inserts an empty block, whose exit is set later.
+    fn insert_block(&mut self) -> BlockId {
+        let bid = BlockId(
+            self.code
+                .blocks
+                .keys()
+                .next_back()
+                .map_or(0, |bid| bid.0 + 1),
+        );
+        let _unused = self.code.blocks.insert(
+            bid,
+            Block {
+                phinodes: Vec::new(),
+                instructions: Vec::new(),
+                exit: BlockExit::Unreachable,
+            },
+        );
+        bid
+    }
+}
+
+/// Returns the value of the case `constant` as the value of the `switch` is compared with it.
+fn case_value(constant: &Constant) -> Option<i128> {
+    let (value, width, is_signed) = constant.get_int()?;
+    let value = trim_unnecessary_bits(value, width as u128);
+    Some(if is_signed {
+        sign_extension(value, width as u128) as i128
+    } else {
+        value as i128
+    })
+}
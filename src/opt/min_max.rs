@@ -0,0 +1,366 @@
+use core::ops::Deref;
+use std::collections::BTreeSet;
+
+use itertools::izip;
+use lang_c::ast;
+
+use crate::ir::*;
+use crate::opt::analysis::Cfg;
+use crate::opt::opt_utils::*;
+use crate::opt::FunctionPass;
+use crate::*;
+
+pub type MinMax = FunctionPass<MinMaxInner>;
+
+/// Replaces the diamonds selecting the minimum, the maximum or the absolute value of integers by
+/// `min`, `max` and `abs` instructions.
+///
+/// A diamond is a conditional jump on a comparison, normalized to `l < r` or `l <= r`, whose edges
+/// both reach the same block, directly or through a block only jumping there. The arguments of the
+/// edges are selected as follows, the signedness of the comparison being that of `min` and `max`:
+///
+/// - `l < r ? l : r` is `min l r`, and `l < r ? r : l` is `max l r`;
+/// - `x < 0 ? -x : x` and `0 < x ? x : -x` are `abs x` for a signed `x`;
+/// - the clamp `x < lo ? lo : min x hi` is `max (min x hi) lo`, and `hi < x ? hi : max x lo` is
+///   `min (max x lo) hi`, for constants `lo <= hi`.
+///
+/// The conditional jump is replaced by a jump, and the blocks passed through are removed if they
+/// become unreachable. A block passed through only computes comparisons, negations, `min` and
+/// `max`, and those the selected values depend on are hoisted, so that nested conditionals, e.g.,
+/// clamps, are folded; the others are removed with the block. The comparison of the diamond is
+/// left to dead code elimination.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct MinMaxInner {}
+
+impl Optimize<FunctionDefinition> for MinMaxInner {
+    fn optimize(&mut self, code: &mut FunctionDefinition) -> bool {
+        let mut changed = false;
+
+        loop {
+            let bids = code.blocks.keys().copied().collect::<Vec<_>>();
+            if !bids.into_iter().any(|bid| fold_diamond(code, bid)) {
+                break;
+            }
+            changed = true;
+        }
+
+        changed
+    }
+}
+
+/// Folds the diamond ending `bid`, if any, or hoists the operands of the values it selects from
+/// the blocks it passes through.
+///
+/// Returns `true` if `code` is changed.
+fn fold_diamond(code: &mut FunctionDefinition, bid: BlockId) -> bool {
+    let cfg = Cfg::new(code);
+    let block = &code.blocks[&bid];
+    let (condition, arg_then, arg_else) = if let BlockExit::ConditionalJump {
+        condition,
+        arg_then,
+        arg_else,
+    } = &block.exit
+    {
+        (condition, arg_then, arg_else)
+    } else {
+        return false;
+    };
+
+    let (lhs, rhs) = some_or!(comparison(code, condition), return false);
+    let (arg_then, through_then) = forward(code, &cfg, bid, arg_then);
+    let (arg_else, through_else) = forward(code, &cfg, bid, arg_else);
+    if arg_then.bid != arg_else.bid {
+        return false;
+    }
+
+    let mut instructions = Vec::new();
+    let mut args = Vec::new();
+    for (value_then, value_else) in izip!(&arg_then.args, &arg_else.args) {
+        let instruction = some_or!(
+            select(code, (&lhs, &rhs), value_then, value_else),
+            return false
+        );
+        let instruction = some_or!(instruction, {
+            args.push(value_then.clone());
+            continue;
+        });
+
+        let iid = block.instructions.len() + instructions.len();
+        args.push(Operand::register(
+            RegisterId::temp(bid, iid),
+            instruction.dtype(),
+        ));
+        instructions.push(Named::new(None, instruction));
+    }
+
+    let mut through = vec![through_then, through_else]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    through.dedup();
+
+    // The operands of the selected values may be computed in the blocks passed through.
+    let hoisted = operands_through(code, &through, &instructions, &args);
+    if !hoisted.is_empty() {
+        let len = code.blocks[&bid].instructions.len();
+        for from in hoisted.into_iter().rev() {
+            move_instruction(code, from, (bid, len));
+        }
+        return true;
+    }
+
+    let block = code.blocks.get_mut(&bid).expect("`bid` must be a block");
+    block.instructions.extend(instructions);
+    block.exit = BlockExit::Jump {
+        arg: JumpArg::new(arg_then.bid, args),
+    };
+
+    for through_bid in through {
+        if cfg.predecessors(through_bid) == [bid] {
+            let _unused = code.blocks.remove(&through_bid);
+        }
+    }
+
+    true
+}
+
+/// Returns the positions of the instructions in the blocks `through` that the selected values,
+/// `instructions` and `args`, depend on, in order.
+fn operands_through(
+    code: &FunctionDefinition,
+    through: &[BlockId],
+    instructions: &[Named<Instruction>],
+    args: &[Operand],
+) -> BTreeSet<(BlockId, usize)> {
+    let mut positions = BTreeSet::new();
+    let mut operands = instructions
+        .iter()
+        .flat_map(|instruction| instruction.operands())
+        .chain(args)
+        .collect::<Vec<_>>();
+    while let Some(operand) = operands.pop() {
+        let (bid, iid) = match operand.get_register() {
+            Some((RegisterId::Temp { bid, iid }, _)) if through.contains(bid) => (*bid, *iid),
+            _ => continue,
+        };
+        if positions.insert((bid, iid)) {
+            operands.extend(code.blocks[&bid].instructions[iid].operands());
+        }
+    }
+    positions
+}
+
+/// Returns the operands `(l, r)` of the comparison `condition` normalized to `l < r` or `l <= r`,
+/// if it compares integers.
+fn comparison(code: &FunctionDefinition, condition: &Operand) -> Option<(Operand, Operand)> {
+    let (op, lhs, rhs) =
+        if let Instruction::BinOp { op, lhs, rhs, .. } = definition(code, condition)? {
+            (op, lhs, rhs)
+        } else {
+            return None;
+        };
+    if lhs.dtype().get_int_width().is_none() {
+        return None;
+    }
+
+    match op {
+        ast::BinaryOperator::Less | ast::BinaryOperator::LessOrEqual => {
+            Some((lhs.clone(), rhs.clone()))
+        }
+        ast::BinaryOperator::Greater | ast::BinaryOperator::GreaterOrEqual => {
+            Some((rhs.clone(), lhs.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the jump `arg` from `bid` ends up with, passing through a block only jumping, along
+/// with the block passed through, if any.
+///
+/// A block passed through has no phinodes, and its instructions, if any, can be hoisted to `bid`:
+/// `bid` is its only predecessor, and they are all hoistable by [`is_hoistable`].
+fn forward(
+    code: &FunctionDefinition,
+    cfg: &Cfg,
+    bid: BlockId,
+    arg: &JumpArg,
+) -> (JumpArg, Option<BlockId>) {
+    let block = &code.blocks[&arg.bid];
+    let is_hoistable = block.instructions.is_empty()
+        || (cfg.predecessors(arg.bid) == [bid]
+            && block
+                .instructions
+                .iter()
+                .all(|instruction| is_hoistable(instruction)));
+
+    match &block.exit {
+        BlockExit::Jump { arg: next }
+            if arg.bid != bid && block.phinodes.is_empty() && is_hoistable =>
+        {
+            (next.clone(), Some(arg.bid))
+        }
+        _ => (arg.clone(), None),
+    }
+}
+
+/// Returns `true` if `instruction` is a comparison, `min`, `max` or a negation.
+///
+/// They never fail, except for the negation of the minimum integer under the trap overflow
+/// policy, which is only folded into `abs` that fails as well.
+fn is_hoistable(instruction: &Instruction) -> bool {
+    match instruction {
+        Instruction::BinOp { op, lhs, .. } => match op {
+            ast::BinaryOperator::Less
+            | ast::BinaryOperator::LessOrEqual
+            | ast::BinaryOperator::Greater
+            | ast::BinaryOperator::GreaterOrEqual
+            | ast::BinaryOperator::Equals
+            | ast::BinaryOperator::NotEquals => true,
+            ast::BinaryOperator::Minus => is_zero(lhs),
+            _ => false,
+        },
+        Instruction::UnaryOp {
+            op: ast::UnaryOperator::Minus,
+            ..
+        }
+        | Instruction::Min { .. }
+        | Instruction::Max { .. } => true,
+        _ => false,
+    }
+}
+
+/// Returns the instruction selecting `then` if `l < r` and `otherwise` if not, or `None` inside if
+/// both are the same.
+fn select(
+    code: &FunctionDefinition,
+    (l, r): (&Operand, &Operand),
+    then: &Operand,
+    otherwise: &Operand,
+) -> Option<Option<Instruction>> {
+    if then == otherwise {
+        return Some(None);
+    }
+
+    let dtype = l.dtype();
+    if then.dtype() != dtype || otherwise.dtype() != dtype {
+        return None;
+    }
+
+    let min_max = |is_max: bool, lhs: &Operand, rhs: &Operand| {
+        let (lhs, rhs, dtype) = (lhs.clone(), rhs.clone(), dtype.clone());
+        Some(Some(if is_max {
+            Instruction::Max { lhs, rhs, dtype }
+        } else {
+            Instruction::Min { lhs, rhs, dtype }
+        }))
+    };
+
+    if (then, otherwise) == (l, r) {
+        return min_max(false, l, r);
+    }
+    if (then, otherwise) == (r, l) {
+        return min_max(true, l, r);
+    }
+
+    if dtype.is_int_signed() {
+        let abs = |operand: &Operand| {
+            Some(Some(Instruction::Abs {
+                operand: operand.clone(),
+                dtype: dtype.clone(),
+            }))
+        };
+        if is_zero(r) && otherwise == l && is_negation(code, then, l) {
+            return abs(l);
+        }
+        if is_zero(l) && then == r && is_negation(code, otherwise, r) {
+            return abs(r);
+        }
+    }
+
+    // Clamps, e.g., `x < lo ? lo : min x hi`.
+    if then == r && bound(code, otherwise, false, l).is_some_and(|hi| is_le(r, &hi)) {
+        return min_max(true, otherwise, r);
+    }
+    if then == l && bound(code, otherwise, true, r).is_some_and(|lo| is_le(&lo, l)) {
+        return min_max(false, otherwise, l);
+    }
+
+    None
+}
+
+/// Returns the instruction defining `operand`, if it is the result of an instruction.
+fn definition<'a>(code: &'a FunctionDefinition, operand: &Operand) -> Option<&'a Instruction> {
+    if let (RegisterId::Temp { bid, iid }, _) = operand.get_register()? {
+        code.blocks
+            .get(bid)?
+            .instructions
+            .get(*iid)
+            .map(|instruction| instruction.deref())
+    } else {
+        None
+    }
+}
+
+fn is_zero(operand: &Operand) -> bool {
+    operand
+        .get_constant()
+        .and_then(Constant::get_int)
+        .is_some_and(|(value, _, _)| value == 0)
+}
+
+/// Returns `true` if `operand` is `-x` or `0 - x`.
+fn is_negation(code: &FunctionDefinition, operand: &Operand, x: &Operand) -> bool {
+    match some_or!(definition(code, operand), return false) {
+        Instruction::UnaryOp {
+            op: ast::UnaryOperator::Minus,
+            operand,
+            ..
+        } => operand == x,
+        Instruction::BinOp {
+            op: ast::BinaryOperator::Minus,
+            lhs,
+            rhs,
+            ..
+        } => is_zero(lhs) && rhs == x,
+        _ => false,
+    }
+}
+
+/// Returns the constant bound `c` if `operand` is `max x c` if `is_max`, or `min x c` if not.
+fn bound(
+    code: &FunctionDefinition,
+    operand: &Operand,
+    is_max: bool,
+    x: &Operand,
+) -> Option<Operand> {
+    let (lhs, rhs) = match definition(code, operand)? {
+        Instruction::Min { lhs, rhs, .. } if !is_max => (lhs, rhs),
+        Instruction::Max { lhs, rhs, .. } if is_max => (lhs, rhs),
+        _ => return None,
+    };
+
+    let bound = if lhs == x {
+        rhs
+    } else if rhs == x {
+        lhs
+    } else {
+        return None;
+    };
+    if bound.get_constant().and_then(Constant::get_int).is_none() {
+        return None;
+    }
+    Some(bound.clone())
+}
+
+/// Returns `true` if the integer constants satisfy `lhs <= rhs`.
+fn is_le(lhs: &Operand, rhs: &Operand) -> bool {
+    let int = |operand: &Operand| operand.get_constant().and_then(Constant::get_int);
+    let ((lhs, width, is_signed), (rhs, _, _)) = some_or!(int(lhs).zip(int(rhs)), return false);
+
+    if is_signed {
+        let sign_extend = |value| sign_extension(value, width as u128) as i128;
+        sign_extend(lhs) <= sign_extend(rhs)
+    } else {
+        lhs <= rhs
+    }
+}
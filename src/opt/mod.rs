@@ -1,40 +1,80 @@
 use crate::*;
 
+pub mod analysis;
+mod budget;
+pub mod dataflow;
+mod dead_function;
+mod dead_store;
 mod deadcode;
+mod fold_const_load;
+mod forward_pointers;
 mod gvn;
+mod loop_rotate;
+mod lower_switch;
 mod mem2reg;
+mod min_max;
 pub mod opt_utils;
+mod registry;
+mod simplify_arith;
 mod simplify_cfg;
+mod sink;
+mod split_misaligned;
+mod strcmp_dispatch;
+mod tail_merge;
 
+pub use budget::{
+    optimizer_budget, optimizer_out_of_time, set_optimizer_budget, take_budget_diagnostics,
+    BudgetDiagnostic, Measure, OptimizerBudget,
+};
+pub use dead_function::DeadFunction;
+pub use dead_store::{DeadStore, DeadStoreInner};
 pub use deadcode::Deadcode;
+pub use fold_const_load::FoldConstLoad;
+pub use forward_pointers::{ForwardPointers, ForwardPointersInner};
 pub use gvn::Gvn;
+pub use loop_rotate::{LoopRotate, LoopRotateInner};
+pub use lower_switch::{LowerSwitch, LowerSwitchInner};
 pub use mem2reg::Mem2reg;
+pub use min_max::{MinMax, MinMaxInner};
+pub use registry::{BoxedPass, PassRegistry, PassRegistryError};
+pub use simplify_arith::{SimplifyArith, SimplifyArithInner};
 pub use simplify_cfg::{
     SimplifyCfg, SimplifyCfgConstProp, SimplifyCfgEmpty, SimplifyCfgMerge, SimplifyCfgReach,
 };
+pub use sink::{Sink, SinkInner};
+pub use split_misaligned::{SplitMisaligned, SplitMisalignedInner};
+pub use strcmp_dispatch::StrcmpDispatch;
+pub use tail_merge::{TailMerge, TailMergeInner};
 
 use crate::ir;
 
-pub trait Optimize<T> {
-    fn optimize(&mut self, code: &mut T) -> bool;
-}
-
 pub type O0 = Null;
 pub type O1 = Repeat<(SimplifyCfg, (Mem2reg, (Gvn, Deadcode)))>;
 
 #[derive(Default, Clone, Copy, Debug)]
 pub struct Null;
 
-#[derive(Default, Debug)]
+#[derive(Default, Clone, Debug)]
 pub struct Repeat<O> {
     inner: O,
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Clone, Debug)]
 pub struct FunctionPass<T: Optimize<ir::FunctionDefinition>> {
     inner: T,
 }
 
+/// Runs `T` on each function definition of a translation unit, in parallel with the `parallel`
+/// feature (see [`parallel_map`]).
+///
+/// Each function is optimized by its own copy of `T`, e.g., `ParallelFunctionPass<O1>` repeats
+/// the passes of [`O1`] on each function until it no longer changes. The functions are
+/// independent, so the result is the same as running `T` on the translation unit.
+#[derive(Default, Clone, Debug)]
+pub struct ParallelFunctionPass<T> {
+    inner: T,
+}
+
 impl Optimize<ir::TranslationUnit> for Null {
     fn optimize(&mut self, _code: &mut ir::TranslationUnit) -> bool {
         false
@@ -49,14 +89,12 @@ impl<T, O1: Optimize<T>, O2: Optimize<T>> Optimize<T> for (O1, O2) {
     }
 }
 
-impl<T, O: Optimize<T>> Optimize<T> for Repeat<O> {
-    fn optimize(&mut self, code: &mut T) -> bool {
-        if !self.inner.optimize(code) {
-            return false;
-        }
-
-        while self.inner.optimize(code) {}
-        true
+impl<T> FunctionPass<T>
+where
+    T: Optimize<ir::FunctionDefinition>,
+{
+    pub fn new(inner: T) -> Self {
+        Self { inner }
     }
 }
 
@@ -82,3 +120,45 @@ where
         self.inner.optimize(fdef)
     }
 }
+
+impl<T> Optimize<ir::FunctionDefinition> for FunctionPass<T>
+where
+    T: Optimize<ir::FunctionDefinition>,
+{
+    fn optimize(&mut self, code: &mut ir::FunctionDefinition) -> bool {
+        self.inner.optimize(code)
+    }
+}
+
+impl<T> ParallelFunctionPass<T>
+where
+    T: Optimize<ir::FunctionDefinition> + Clone + Sync,
+{
+    pub fn new(inner: T) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T> Optimize<ir::TranslationUnit> for ParallelFunctionPass<T>
+where
+    T: Optimize<ir::FunctionDefinition> + Clone + Sync,
+{
+    fn optimize(&mut self, code: &mut ir::TranslationUnit) -> bool {
+        let definitions = code
+            .decls
+            .values_mut()
+            .filter_map(|decl| decl.get_function_mut()?.1.as_mut())
+            .collect::<Vec<_>>();
+        let inner = &self.inner;
+        // The workers follow the budgets of this thread, and report to it what they exceeded.
+        let limits = budget::limits();
+        let mut changed = false;
+        for (result, diagnostics) in parallel_map(definitions, |definition| {
+            budget::with_limits(limits, || inner.clone().optimize(definition))
+        }) {
+            changed |= result;
+            budget::report_all(diagnostics);
+        }
+        changed
+    }
+}
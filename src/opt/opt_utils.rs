@@ -3,3 +3,144 @@
 //! You can add here utilities commonly used in the implementation of multiple optimizations.
 
 #![allow(dead_code)]
+use std::collections::{BTreeSet, HashMap};
+
+use crate::*;
+
+/// Replaces every use of the registers in `replaces` by the corresponding operand.
+///
+/// Returns `true` if an operand is replaced.
+pub fn replace_operands(
+    code: &mut ir::FunctionDefinition,
+    replaces: &HashMap<ir::RegisterId, ir::Operand>,
+) -> bool {
+    let mut changed = false;
+    let mut replace = |operand: &mut ir::Operand| {
+        let (rid, _) = some_or!(operand.get_register(), return);
+        if let Some(new_operand) = replaces.get(rid) {
+            *operand = new_operand.clone();
+            changed = true;
+        }
+    };
+
+    for block in code.blocks.values_mut() {
+        for instruction in &mut block.instructions {
+            instruction.walk_operands_mut(&mut replace);
+        }
+        block.exit.walk_operands_mut(&mut replace);
+    }
+
+    changed
+}
+
+/// Moves the instruction at `from` to the position `to`, renaming the registers of the
+/// instructions shifted by the move.
+///
+/// If both positions are in the same block, `to` is the position in the block without the
+/// instruction.
+pub fn move_instruction(
+    code: &mut ir::FunctionDefinition,
+    from: (ir::BlockId, usize),
+    to: (ir::BlockId, usize),
+) {
+    let (from_bid, from_iid) = from;
+    let (to_bid, to_iid) = to;
+
+    // The registers of the instructions of each block, in their order after the move.
+    let temps = |bid: ir::BlockId, len: usize| {
+        (0..len)
+            .map(|iid| ir::RegisterId::temp(bid, iid))
+            .collect::<Vec<_>>()
+    };
+    let mut from_temps = temps(from_bid, code.blocks[&from_bid].instructions.len());
+    let moved = from_temps.remove(from_iid);
+    let mut to_temps = if from_bid == to_bid {
+        from_temps.clone()
+    } else {
+        temps(to_bid, code.blocks[&to_bid].instructions.len())
+    };
+    to_temps.insert(to_iid, moved);
+
+    let mut renames = HashMap::new();
+    for (bid, temps) in [(from_bid, from_temps), (to_bid, to_temps)] {
+        for (iid, rid) in temps.into_iter().enumerate() {
+            let _ = renames.insert(rid, ir::RegisterId::temp(bid, iid));
+        }
+    }
+
+    let instruction = code
+        .blocks
+        .get_mut(&from_bid)
+        .expect("`from` must be in a block")
+        .instructions
+        .remove(from_iid);
+    code.blocks
+        .get_mut(&to_bid)
+        .expect("`to` must be in a block")
+        .instructions
+        .insert(to_iid, instruction);
+
+    rename_registers(code, &renames);
+}
+
+/// Removes the instructions at `positions`, whose results must be unused, renaming the registers
+/// of the instructions shifted by the removal.
+pub fn remove_instructions(
+    code: &mut ir::FunctionDefinition,
+    positions: &BTreeSet<(ir::BlockId, usize)>,
+) {
+    let mut renames = HashMap::new();
+    for (bid, block) in &mut code.blocks {
+        let instructions = block.instructions.drain(..).collect::<Vec<_>>();
+        for (iid, instruction) in instructions.into_iter().enumerate() {
+            if positions.contains(&(*bid, iid)) {
+                continue;
+            }
+            let _ = renames.insert(
+                ir::RegisterId::temp(*bid, iid),
+                ir::RegisterId::temp(*bid, block.instructions.len()),
+            );
+            block.instructions.push(instruction);
+        }
+    }
+
+    rename_registers(code, &renames);
+}
+
+/// Removes the local allocations `aids`, whose addresses must be unused, renaming the registers
+/// of the allocations shifted by the removal.
+pub fn remove_allocations(code: &mut ir::FunctionDefinition, aids: &BTreeSet<usize>) {
+    let mut renames = HashMap::new();
+    let allocations = code.allocations.drain(..).collect::<Vec<_>>();
+    for (aid, allocation) in allocations.into_iter().enumerate() {
+        if aids.contains(&aid) {
+            continue;
+        }
+        let _ = renames.insert(
+            ir::RegisterId::local(aid),
+            ir::RegisterId::local(code.allocations.len()),
+        );
+        code.allocations.push(allocation);
+    }
+
+    rename_registers(code, &renames);
+}
+
+/// Renames the registers in `renames` in every operand.
+fn rename_registers(
+    code: &mut ir::FunctionDefinition,
+    renames: &HashMap<ir::RegisterId, ir::RegisterId>,
+) {
+    let mut rename = |operand: &mut ir::Operand| {
+        let (rid, _) = some_or!(operand.get_register_mut(), return);
+        if let Some(new_rid) = renames.get(rid) {
+            *rid = *new_rid;
+        }
+    };
+    for block in code.blocks.values_mut() {
+        for instruction in &mut block.instructions {
+            instruction.walk_operands_mut(&mut rename);
+        }
+        block.exit.walk_operands_mut(&mut rename);
+    }
+}
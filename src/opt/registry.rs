@@ -0,0 +1,170 @@
+//! Named passes, e.g., for `--passes=mem2reg,mypass,simplify-cfg`.
+//!
+//! A crate depending on kecc may add its own pass without forking it, by implementing
+//! [`Optimize<ir::TranslationUnit>`] and registering it by name:
+//!
+//! ```ignore
+//! let mut registry = PassRegistry::new(CompileOptions::default());
+//! registry.register("mypass", Box::new(FunctionPass::new(MyPass::default())))?;
+//! registry.run("mem2reg,mypass,simplify-cfg", &mut unit)?;
+//! ```
+//!
+//! # The supported boundary
+//!
+//! A pass sees the translation unit only through the public items of [`ir`]: it may rewrite the
+//! declarations, the blocks, the instructions and the allocations of the functions, and may use
+//! the analyses of [`crate::opt::analysis`] and the helpers of [`crate::opt::opt_utils`]. The IR a
+//! pass leaves must pass [`ir::verify`], which [`PassRegistry::run`] checks after each pass, and
+//! its return value must tell whether it changed anything. The built-in passes are bound by the
+//! same rules, so a registered pass runs exactly like them.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use thiserror::Error;
+
+use crate::ir;
+use crate::opt::*;
+use crate::CompileOptions;
+
+/// A pass of [`PassRegistry`].
+pub type BoxedPass = Box<dyn Optimize<ir::TranslationUnit>>;
+
+/// An error in running the passes of [`PassRegistry`].
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum PassRegistryError {
+    #[error("pass `{name}` is already registered")]
+    Duplicate { name: String },
+    #[error("unknown pass `{name}` (known passes: {known})")]
+    Unknown { name: String, known: String },
+    #[error("pass `{name}` leaves invalid IR: {error}")]
+    InvalidIr {
+        name: String,
+        error: ir::VerifierError,
+    },
+}
+
+/// The passes by their names, the built-in ones and those registered by [`PassRegistry::register`].
+pub struct PassRegistry {
+    passes: BTreeMap<String, BoxedPass>,
+}
+
+impl fmt::Debug for PassRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PassRegistry")
+            .field("passes", &self.passes.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl PassRegistry {
+    /// Returns the registry of the built-in passes, named after their flags, e.g., `mem2reg` for
+    /// `--mem2reg`, configured by `options`.
+    pub fn new(options: CompileOptions) -> Self {
+        let builtins: [(&str, BoxedPass); 15] = [
+            ("O1", parallel(O1::default())),
+            ("simplify-cfg", parallel(SimplifyCfg::default())),
+            ("forward-pointers", parallel(ForwardPointers::default())),
+            ("mem2reg", parallel(Mem2reg::default())),
+            ("deadcode", parallel(Deadcode::default())),
+            ("gvn", parallel(Gvn::default())),
+            (
+                "simplify-arith",
+                parallel(SimplifyArith::new(SimplifyArithInner::new(options))),
+            ),
+            ("fold-const-load", Box::<FoldConstLoad>::default()),
+            ("sink", parallel(Sink::default())),
+            ("min-max", parallel(MinMax::default())),
+            ("loop-rotate", parallel(LoopRotate::default())),
+            (
+                "lower-switch",
+                parallel(LowerSwitch::new(LowerSwitchInner::new(options))),
+            ),
+            ("tail-merge", parallel(TailMerge::default())),
+            ("dead-store", parallel(DeadStore::default())),
+            ("strcmp-dispatch", Box::<StrcmpDispatch>::default()),
+        ];
+
+        Self {
+            passes: builtins
+                .into_iter()
+                .map(|(name, pass)| (name.to_string(), pass))
+                .collect(),
+        }
+    }
+
+    /// Registers `pass` by `name`, which must not name another pass.
+    pub fn register(&mut self, name: &str, pass: BoxedPass) -> Result<(), PassRegistryError> {
+        if self.passes.contains_key(name) {
+            return Err(PassRegistryError::Duplicate {
+                name: name.to_string(),
+            });
+        }
+
+        let _unused = self.passes.insert(name.to_string(), pass);
+        Ok(())
+    }
+
+    /// Returns the names of the passes, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.passes.keys().map(String::as_str)
+    }
+
+    /// Returns the pass `name`.
+    pub fn get_mut(&mut self, name: &str) -> Result<&mut BoxedPass, PassRegistryError> {
+        if !self.passes.contains_key(name) {
+            return Err(PassRegistryError::Unknown {
+                name: name.to_string(),
+                known: self.names().collect::<Vec<_>>().join(", "),
+            });
+        }
+
+        Ok(self
+            .passes
+            .get_mut(name)
+            .expect("`name` must be registered"))
+    }
+
+    /// Runs the passes named by `pipeline`, separated by commas, in turn on `unit`, verifying the
+    /// IR after each of them, and returns whether any of them changed `unit`.
+    ///
+    /// The names are all checked before any pass runs, and the passes after the time limit of the
+    /// optimizer elapsed are skipped (see [`OptimizerBudget`]).
+    pub fn run(
+        &mut self,
+        pipeline: &str,
+        unit: &mut ir::TranslationUnit,
+    ) -> Result<bool, PassRegistryError> {
+        let names = pipeline
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>();
+        for name in &names {
+            let _unused = self.get_mut(name)?;
+        }
+
+        let mut changed = false;
+        for name in names {
+            // The IR is valid after each pass, so the remaining ones are skipped if out of time.
+            if optimizer_out_of_time() {
+                break;
+            }
+            changed |= self.get_mut(name)?.optimize(unit);
+            ir::verify(unit).map_err(|error| PassRegistryError::InvalidIr {
+                name: name.to_string(),
+                error,
+            })?;
+        }
+        Ok(changed)
+    }
+}
+
+/// Returns the pass running the function pass `pass` on each function, in parallel with the
+/// `parallel` feature.
+fn parallel<T>(pass: T) -> BoxedPass
+where
+    T: Optimize<ir::FunctionDefinition> + Clone + Sync + 'static,
+{
+    Box::new(ParallelFunctionPass::new(pass))
+}
@@ -0,0 +1,373 @@
+use core::ops::{Deref, DerefMut};
+use std::collections::HashMap;
+
+use lang_c::ast;
+
+use crate::ir::*;
+use crate::opt::opt_utils::*;
+use crate::opt::FunctionPass;
+use crate::*;
+
+pub type SimplifyArith = FunctionPass<SimplifyArithInner>;
+
+/// Folds constant arithmetic and simplifies algebraic identities.
+///
+/// The equality comparisons of addresses are simplified only if the pointers are derived from the
+/// same object: `&x == &x` is 1, `&x == 0` is 0 since no object is at the null address, and
+/// `&a[i] == &a[j]` is `i == j`. The addresses of distinct objects are never compared, since the
+/// one-past-the-end address of an object may be the address of another, e.g., `&a[n] == &b[0]`
+/// for `int a[n], b[m]` holds if `b` happens to follow `a` in memory. Functions have no such
+/// addresses, so that the address of a function is unequal to that of any other object, e.g.,
+/// `&f == &g` is 0.
+///
+/// Signed integer overflow is handled according to the `OverflowPolicy` of `options`:
+///
+/// - `Wrap`: constant expressions are folded with two's complement wrap-around, and nothing is
+///   simplified by assuming the absence of overflow.
+/// - `Trap`: constant expressions that overflow, or shift by amounts out of range, are not folded,
+///   so that they still trap.
+/// - `Ub`: constant expressions are folded as in `Wrap`, and comparisons such as `x + 1 > x` are
+///   folded by assuming `x + 1` does not overflow.
+///
+/// Constant divisions and remainders are folded truncating toward zero, as in C, e.g., `-7 / 2` to
+/// `-3` and `-7 % 2` to `-1`, except those by zero and `INT_MIN / -1` or `INT_MIN % -1`, which are
+/// undefined and never folded.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct SimplifyArithInner {
+    options: CompileOptions,
+}
+
+impl SimplifyArithInner {
+    pub fn new(options: CompileOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Optimize<FunctionDefinition> for SimplifyArithInner {
+    fn optimize(&mut self, code: &mut FunctionDefinition) -> bool {
+        let mut replaces = HashMap::new();
+        let mut offset_comparisons = Vec::new();
+
+        for (bid, block) in &code.blocks {
+            for (iid, instruction) in block.instructions.iter().enumerate() {
+                if let Some(constant) = self.simplify(instruction, code) {
+                    let rid = RegisterId::temp(*bid, iid);
+                    let _unused = replaces.insert(rid, Operand::constant(constant));
+                } else if let Some(offsets) = same_object_offsets(instruction, code) {
+                    offset_comparisons.push((*bid, iid, offsets));
+                }
+            }
+        }
+
+        // `&a[i] == &a[j]` compares the offsets `i` and `j` from `a` instead.
+        let changed = !offset_comparisons.is_empty();
+        for (bid, iid, (lhs_offset, rhs_offset)) in offset_comparisons {
+            let block = code.blocks.get_mut(&bid).expect("`bid` must be a block");
+            if let Instruction::BinOp { lhs, rhs, .. } = block.instructions[iid].deref_mut() {
+                *lhs = lhs_offset;
+                *rhs = rhs_offset;
+            }
+        }
+
+        replace_operands(code, &replaces) || changed
+    }
+}
+
+impl SimplifyArithInner {
+    /// Returns the constant `instruction` evaluates to, if any.
+    fn simplify(&self, instruction: &Instruction, code: &FunctionDefinition) -> Option<Constant> {
+        let (op, lhs, rhs, dtype) = if let Instruction::BinOp {
+            op,
+            lhs,
+            rhs,
+            dtype,
+        } = instruction
+        {
+            (op, lhs, rhs, dtype)
+        } else {
+            return None;
+        };
+
+        if let (Some(lhs), Some(rhs)) = (lhs.get_constant(), rhs.get_constant()) {
+            return self.fold_binary_operator(op, lhs, rhs, dtype);
+        }
+
+        if let Some(constant) = simplify_address_comparison(op, lhs, rhs, dtype, code) {
+            return Some(constant);
+        }
+
+        if self.options.overflow_policy == OverflowPolicy::Ub {
+            return simplify_offset_comparison(op, lhs, rhs, dtype, code);
+        }
+
+        None
+    }
+
+    /// Folds `lhs op rhs` for integer constants.
+    fn fold_binary_operator(
+        &self,
+        op: &ast::BinaryOperator,
+        lhs: &Constant,
+        rhs: &Constant,
+        dtype: &Dtype,
+    ) -> Option<Constant> {
+        if !matches!(
+            op,
+            ast::BinaryOperator::Plus
+                | ast::BinaryOperator::Minus
+                | ast::BinaryOperator::Multiply
+                | ast::BinaryOperator::Divide
+                | ast::BinaryOperator::Modulo
+                | ast::BinaryOperator::ShiftLeft
+                | ast::BinaryOperator::ShiftRight
+                | ast::BinaryOperator::BitwiseAnd
+                | ast::BinaryOperator::BitwiseXor
+                | ast::BinaryOperator::BitwiseOr
+                | ast::BinaryOperator::Equals
+                | ast::BinaryOperator::NotEquals
+                | ast::BinaryOperator::Less
+                | ast::BinaryOperator::Greater
+                | ast::BinaryOperator::LessOrEqual
+                | ast::BinaryOperator::GreaterOrEqual
+        ) {
+            return None;
+        }
+
+        if !lhs.is_integer_constant() || !rhs.is_integer_constant() {
+            return None;
+        }
+
+        let lhs = Value::try_from(lhs.clone()).ok()?;
+        let rhs = Value::try_from(rhs.clone()).ok()?;
+        // Divisions by zero and `INT_MIN / -1` are left to runtime under every policy, where they
+        // trap or yield what the target yields (see `calculator::is_division_by_zero`).
+        if matches!(
+            op,
+            ast::BinaryOperator::Divide | ast::BinaryOperator::Modulo
+        ) && !calculator::is_well_defined(op, &lhs, &rhs)
+        {
+            return None;
+        }
+        if self.options.overflow_policy == OverflowPolicy::Trap
+            && (calculator::has_signed_overflow(op, &lhs, &rhs)
+                || calculator::is_shift_out_of_range(op, &lhs, &rhs))
+        {
+            return None;
+        }
+
+        let result = calculator::calculate_binary_operator_expression(op, lhs, rhs).ok()?;
+        let (value, _, _) = result.get_int()?;
+        Some(Constant::int(value, dtype.clone()))
+    }
+}
+
+/// Folds the comparison of `x + c` and `x` for a nonzero signed integer constant `c`, assuming
+/// `x + c` does not overflow.
+fn simplify_offset_comparison(
+    op: &ast::BinaryOperator,
+    lhs: &Operand,
+    rhs: &Operand,
+    dtype: &Dtype,
+    code: &FunctionDefinition,
+) -> Option<Constant> {
+    // Whether `lhs` is `x + c` and `rhs` is `x`, or the other way around
+    let (is_lhs_offset, offset) = if let Some(offset) = offset_of(lhs, rhs, code) {
+        (true, offset)
+    } else {
+        (false, offset_of(rhs, lhs, code)?)
+    };
+
+    // Whether the result holds when `x + c` is the greater operand
+    let holds_if_greater = match op {
+        ast::BinaryOperator::Greater | ast::BinaryOperator::GreaterOrEqual => is_lhs_offset,
+        ast::BinaryOperator::Less | ast::BinaryOperator::LessOrEqual => !is_lhs_offset,
+        _ => return None,
+    };
+
+    let result = holds_if_greater == (offset > 0);
+    Some(Constant::int(result.into(), dtype.clone()))
+}
+
+/// Folds `p == p` and `p == 0` for a pointer `p` into a known object, `&f == &x` for a function `f`
+/// and another object `x`, and their `!=` forms.
+fn simplify_address_comparison(
+    op: &ast::BinaryOperator,
+    lhs: &Operand,
+    rhs: &Operand,
+    dtype: &Dtype,
+    code: &FunctionDefinition,
+) -> Option<Constant> {
+    let is_equal = match op {
+        ast::BinaryOperator::Equals => true,
+        ast::BinaryOperator::NotEquals => false,
+        _ => return None,
+    };
+
+    // Only the address of the object itself is known not to be null, since the offset of a
+    // `getelementptr` is not known to stay in the object.
+    let equals = if lhs == rhs && points_into_object(lhs, code) {
+        true
+    } else if (is_object_address(lhs) && is_null(rhs, code))
+        || (is_null(lhs, code) && is_object_address(rhs))
+        || (lhs != rhs
+            && is_object_address(lhs)
+            && is_object_address(rhs)
+            && (is_function_address(lhs) || is_function_address(rhs)))
+    {
+        false
+    } else {
+        return None;
+    };
+
+    Some(Constant::int((equals == is_equal).into(), dtype.clone()))
+}
+
+/// Returns the offsets to compare instead if `instruction` compares two pointers of the same type
+/// for equality, derived from the same pointer by `getelementptr`, e.g., `i` and `j` for `&a[i]
+/// == &a[j]`, or the offset and 0 for `&a[i] == a`.
+fn same_object_offsets(
+    instruction: &Instruction,
+    code: &FunctionDefinition,
+) -> Option<(Operand, Operand)> {
+    let (lhs, rhs) = if let Instruction::BinOp {
+        op: ast::BinaryOperator::Equals | ast::BinaryOperator::NotEquals,
+        lhs,
+        rhs,
+        ..
+    } = instruction
+    {
+        (lhs, rhs)
+    } else {
+        return None;
+    };
+    if lhs.dtype().get_pointer_inner().is_none() || lhs.dtype() != rhs.dtype() {
+        return None;
+    }
+
+    // The base of a pointer not computed by `getelementptr` is the pointer itself.
+    let split = |operand: &Operand| match defining_instruction(operand, code) {
+        Some(Instruction::GetElementPtr { ptr, offset, .. }) => (ptr.clone(), Some(offset.clone())),
+        _ => (operand.clone(), None),
+    };
+    let (lhs_base, lhs_offset) = split(lhs);
+    let (rhs_base, rhs_offset) = split(rhs);
+    if lhs_base != rhs_base {
+        return None;
+    }
+    let zero = |offset: &Operand| Operand::constant(Constant::int(0, offset.dtype()));
+    match (lhs_offset, rhs_offset) {
+        (Some(lhs), Some(rhs)) if lhs.dtype() == rhs.dtype() => Some((lhs, rhs)),
+        (Some(lhs), None) => Some((lhs.clone(), zero(&lhs))),
+        (None, Some(rhs)) => Some((zero(&rhs), rhs)),
+        _ => None,
+    }
+}
+
+/// Whether `operand` points into a global variable or a local allocation, following
+/// `getelementptr`.
+fn points_into_object(operand: &Operand, code: &FunctionDefinition) -> bool {
+    let mut operand = operand;
+    loop {
+        if is_object_address(operand) {
+            return true;
+        }
+        match defining_instruction(operand, code) {
+            Some(Instruction::GetElementPtr { ptr, .. }) => operand = ptr,
+            _ => return false,
+        }
+    }
+}
+
+/// Whether `operand` is the address of a global variable or a local allocation.
+fn is_object_address(operand: &Operand) -> bool {
+    match operand {
+        Operand::Constant(constant) => constant.get_global_variable_name().is_some(),
+        Operand::Register { rid, .. } => matches!(rid, RegisterId::Local { .. }),
+    }
+}
+
+/// Whether `operand` is the address of a function.
+fn is_function_address(operand: &Operand) -> bool {
+    matches!(
+        operand.get_constant(),
+        Some(Constant::GlobalVariable {
+            dtype: Dtype::Function { .. },
+            ..
+        })
+    )
+}
+
+/// Whether `operand` is a null pointer, e.g., `typecast 0:i32 to i32*`.
+fn is_null(operand: &Operand, code: &FunctionDefinition) -> bool {
+    if operand.dtype().get_pointer_inner().is_none() {
+        return false;
+    }
+    if let Some(constant) = operand.get_constant() {
+        return constant.is_zero_init();
+    }
+    match defining_instruction(operand, code) {
+        Some(Instruction::TypeCast { value, .. }) => value
+            .get_constant()
+            .and_then(Constant::get_int)
+            .map_or(false, |(value, _, _)| value == 0),
+        _ => false,
+    }
+}
+
+/// Returns the instruction defining `operand`, if it is the result of an instruction.
+fn defining_instruction<'a>(
+    operand: &Operand,
+    code: &'a FunctionDefinition,
+) -> Option<&'a Instruction> {
+    let (rid, _) = operand.get_register()?;
+    let (bid, iid) = if let RegisterId::Temp { bid, iid } = rid {
+        (bid, iid)
+    } else {
+        return None;
+    };
+    code.blocks
+        .get(bid)?
+        .instructions
+        .get(*iid)
+        .map(|instruction| instruction.deref())
+}
+
+/// Returns `c` if `operand` is defined as `base + c` for a nonzero signed integer constant `c`.
+fn offset_of(operand: &Operand, base: &Operand, code: &FunctionDefinition) -> Option<i128> {
+    let (lhs, rhs, dtype) = if let Instruction::BinOp {
+        op: ast::BinaryOperator::Plus,
+        lhs,
+        rhs,
+        dtype,
+    } = defining_instruction(operand, code)?
+    {
+        (lhs, rhs, dtype)
+    } else {
+        return None;
+    };
+
+    if !matches!(
+        dtype,
+        Dtype::Int {
+            is_signed: true,
+            ..
+        }
+    ) {
+        return None;
+    }
+
+    let (value, _, is_signed) = if lhs == base {
+        rhs.get_constant()?.get_int()?
+    } else if rhs == base {
+        lhs.get_constant()?.get_int()?
+    } else {
+        return None;
+    };
+
+    let value = value as i128;
+    if !is_signed || value == 0 {
+        return None;
+    }
+
+    Some(value)
+}
@@ -0,0 +1,152 @@
+use core::ops::Deref;
+use std::collections::HashMap;
+
+use crate::ir::*;
+use crate::opt::analysis::{Cfg, Dominators, Loops};
+use crate::opt::opt_utils::*;
+use crate::opt::FunctionPass;
+use crate::*;
+
+pub type Sink = FunctionPass<SinkInner>;
+
+/// Sinks computations into the branch using them, so that the other branches skip them.
+///
+/// An instruction without side effects is moved out of its block if all its uses are in blocks
+/// dominated by a successor whose only predecessor is the block. It is moved to the nearest block
+/// dominating the uses, right before the first use there. Computations are never sunk into a
+/// loop not containing their block, which would execute them more often.
+///
+/// A load is sunk only into the successor itself, and only if no store or call, which may clobber
+/// the memory it reads, is executed in between.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct SinkInner {}
+
+/// The position of a use of a register: an instruction, or the exit of the block if `None`.
+type Use = (BlockId, Option<usize>);
+
+impl Optimize<FunctionDefinition> for SinkInner {
+    fn optimize(&mut self, code: &mut FunctionDefinition) -> bool {
+        // Sinking instructions does not change the control flow graph.
+        let cfg = Cfg::new(code);
+        let dominators = Dominators::new(code, &cfg);
+        let loops = Loops::new(&cfg, &dominators);
+
+        let mut changed = false;
+        while let Some((from, to)) = candidate(code, &cfg, &dominators, &loops) {
+            move_instruction(code, from, to);
+            changed = true;
+        }
+
+        changed
+    }
+}
+
+/// Returns an instruction to sink and its new position, if any.
+///
+/// Instructions are visited from the last one of each block, so that sinking an instruction may
+/// let the instructions computing its operands follow it.
+fn candidate(
+    code: &FunctionDefinition,
+    cfg: &Cfg,
+    dominators: &Dominators,
+    loops: &Loops,
+) -> Option<((BlockId, usize), (BlockId, usize))> {
+    let uses = uses(code);
+
+    for bid in dominators.reverse_postorder() {
+        let block = &code.blocks[bid];
+        for (iid, instruction) in block.instructions.iter().enumerate().rev() {
+            if !instruction.has_no_side_effects() {
+                continue;
+            }
+
+            let rid_uses = some_or!(uses.get(&RegisterId::temp(*bid, iid)), continue);
+            if let Some(to) = target(code, (*bid, iid), rid_uses, cfg, dominators, loops) {
+                return Some(((*bid, iid), to));
+            }
+        }
+    }
+
+    None
+}
+
+/// Returns the position to sink the instruction at `from` with `uses` into, if any.
+fn target(
+    code: &FunctionDefinition,
+    from: (BlockId, usize),
+    uses: &[Use],
+    cfg: &Cfg,
+    dominators: &Dominators,
+    loops: &Loops,
+) -> Option<(BlockId, usize)> {
+    let (bid, iid) = from;
+
+    let mut target = uses[0].0;
+    for (use_bid, _) in uses {
+        target = dominators.common_dominator(target, *use_bid)?;
+    }
+    if target == bid || !dominators.dominates(bid, target) {
+        return None;
+    }
+
+    // Sinking only pays off if some path from `bid` skips the target.
+    let successors = cfg.successors(bid);
+    let successor = *successors
+        .iter()
+        .find(|succ| dominators.dominates(**succ, target))?;
+    if successors.len() < 2 || cfg.predecessors(successor) != [bid] {
+        return None;
+    }
+
+    while !loops.is_in_loops_of(target, bid) {
+        target = dominators.idom(target)?;
+        if !dominators.dominates(successor, target) {
+            return None;
+        }
+    }
+
+    let position = uses
+        .iter()
+        .filter(|(use_bid, _)| *use_bid == target)
+        .filter_map(|(_, use_iid)| *use_iid)
+        .min()
+        .unwrap_or(code.blocks[&target].instructions.len());
+
+    if let Instruction::Load { .. } = code.blocks[&bid].instructions[iid].deref() {
+        let may_clobber = |instructions: &[Named<Instruction>]| {
+            instructions
+                .iter()
+                .any(|instruction| !instruction.has_no_side_effects())
+        };
+        if target != successor
+            || may_clobber(&code.blocks[&bid].instructions[iid + 1..])
+            || may_clobber(&code.blocks[&target].instructions[..position])
+        {
+            return None;
+        }
+    }
+
+    Some((target, position))
+}
+
+/// Returns the uses of the temporary registers.
+fn uses(code: &FunctionDefinition) -> HashMap<RegisterId, Vec<Use>> {
+    let mut uses = HashMap::<RegisterId, Vec<Use>>::new();
+
+    for (bid, block) in &code.blocks {
+        let mut add_use = |operand: &Operand, position: Use| {
+            if let Some((rid @ RegisterId::Temp { .. }, _)) = operand.get_register() {
+                uses.entry(*rid).or_default().push(position);
+            }
+        };
+
+        for (iid, instruction) in block.instructions.iter().enumerate() {
+            instruction.walk_operands(|operand| add_use(operand, (*bid, Some(iid))));
+        }
+        block
+            .exit
+            .walk_operands(|operand| add_use(operand, (*bid, None)));
+    }
+
+    uses
+}
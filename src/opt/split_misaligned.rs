@@ -0,0 +1,296 @@
+use core::ops::Deref;
+use std::collections::HashMap;
+
+use lang_c::ast;
+
+use crate::ir::*;
+use crate::opt::FunctionPass;
+use crate::*;
+
+pub type SplitMisaligned = FunctionPass<SplitMisalignedInner>;
+
+/// Splits the loads and stores of integers less aligned than their size into accesses of aligned
+/// pieces, for the targets trapping on misaligned accesses, i.e., under `strict-align`.
+///
+/// The alignment of a pointer is that of its pointee, lowered by the constant offsets of the
+/// `getelementptr`s computing it and kept through the casts between pointers. Each piece is as
+/// large as the alignment. A load loads the pieces as unsigned integers and assembles them by
+/// shifts and ors in little-endian order, and a store stores the shifted value piece by piece.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct SplitMisalignedInner {}
+
+/// The alignment assumed for the pointers to types whose layout is unknown without the structs.
+const MAX_ALIGN: usize = 16;
+
+impl Optimize<FunctionDefinition> for SplitMisalignedInner {
+    fn optimize(&mut self, code: &mut FunctionDefinition) -> bool {
+        // The size of the piece of each access to split, by its position.
+        let mut splits = HashMap::new();
+        for (bid, block) in &code.blocks {
+            for (iid, instruction) in block.instructions.iter().enumerate() {
+                let (ptr, dtype) = match instruction.deref() {
+                    Instruction::Load { ptr } => (
+                        ptr,
+                        some_or!(ptr.dtype().get_pointer_inner(), continue).clone(),
+                    ),
+                    Instruction::Store { ptr, value } => (ptr, value.dtype()),
+                    _ => continue,
+                };
+                let size = some_or!(int_size(&dtype), continue);
+                let piece = alignment(code, ptr);
+                if piece < size {
+                    let _unused = splits.insert((*bid, iid), piece);
+                }
+            }
+        }
+        if splits.is_empty() {
+            return false;
+        }
+
+        // The registers are renamed before the pieces are inserted, so that the instructions of
+        // the pieces are numbered from the start.
+        let mut renames = HashMap::new();
+        for (bid, block) in &code.blocks {
+            let mut next = 0;
+            for (iid, instruction) in block.instructions.iter().enumerate() {
+                next += match (splits.get(&(*bid, iid)), instruction.deref()) {
+                    (Some(piece), Instruction::Load { ptr }) => {
+                        5 * access_size(&ptr.dtype()) / piece - 1
+                    }
+                    (Some(piece), Instruction::Store { value, .. }) => {
+                        4 * int_size(&value.dtype()).expect("`value` must be an integer") / piece
+                    }
+                    _ => 1,
+                };
+                let _unused = renames.insert(
+                    RegisterId::temp(*bid, iid),
+                    RegisterId::temp(*bid, next - 1),
+                );
+            }
+        }
+        let mut rename = |operand: &mut Operand| {
+            let (rid, _) = some_or!(operand.get_register_mut(), return);
+            if let Some(new_rid) = renames.get(rid) {
+                *rid = *new_rid;
+            }
+        };
+        for block in code.blocks.values_mut() {
+            for instruction in &mut block.instructions {
+                instruction.walk_operands_mut(&mut rename);
+            }
+            block.exit.walk_operands_mut(&mut rename);
+        }
+
+        for (bid, block) in &mut code.blocks {
+            let instructions = block.instructions.drain(..).collect::<Vec<_>>();
+            let mut pieces = Pieces {
+                bid: *bid,
+                instructions: &mut block.instructions,
+            };
+            for (iid, instruction) in instructions.into_iter().enumerate() {
+                let piece = some_or!(splits.get(&(*bid, iid)), {
+                    pieces.instructions.push(instruction);
+                    continue;
+                });
+                let (instruction, name) = instruction.destruct();
+                match instruction {
+                    Instruction::Load { ptr } => pieces.load(ptr, *piece, name),
+                    Instruction::Store { ptr, value } => pieces.store(ptr, value, *piece, name),
+                    _ => unreachable!("only loads and stores are split"),
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// Returns the size of `dtype` if it is an integer of more than a byte.
+fn int_size(dtype: &Dtype) -> Option<usize> {
+    let width = dtype.get_int_width()?;
+    if width <= Dtype::BITS_OF_BYTE {
+        return None;
+    }
+    Some(width / Dtype::BITS_OF_BYTE)
+}
+
+/// Returns the size of the integer `ptr` points to.
+fn access_size(ptr: &Dtype) -> usize {
+    ptr.get_pointer_inner()
+        .and_then(int_size)
+        .expect("`ptr` must point to an integer")
+}
+
+/// Returns the alignment statically known of the pointer `operand`.
+fn alignment(code: &FunctionDefinition, operand: &Operand) -> usize {
+    if let Some((RegisterId::Temp { bid, iid }, _)) = operand.get_register() {
+        match code.blocks[bid].instructions[*iid].deref() {
+            Instruction::GetElementPtr { ptr, offset, .. } => {
+                let align = alignment(code, ptr);
+                return match offset.get_constant().and_then(Constant::get_int) {
+                    Some((value, width, _)) => {
+                        let value = sign_extension(value, width as u128);
+                        if value == 0 {
+                            align
+                        } else {
+                            align.min(1 << value.trailing_zeros().min(MAX_ALIGN.trailing_zeros()))
+                        }
+                    }
+                    None => 1,
+                };
+            }
+            Instruction::TypeCast { value, .. } if value.dtype().get_pointer_inner().is_some() => {
+                return alignment(code, value);
+            }
+            _ => {}
+        }
+    }
+
+    operand
+        .dtype()
+        .get_pointer_inner()
+        .and_then(|inner| inner.size_align_of(&HashMap::new()).ok())
+        .map_or(MAX_ALIGN, |(_, align)| align.max(1))
+}
+
+/// The instructions of a block being rebuilt with the pieces of the split accesses.
+struct Pieces<'a> {
+    bid: BlockId,
+    instructions: &'a mut Vec<Named<Instruction>>,
+}
+
+impl Pieces<'_> {
+    /// Pushes `instruction`, and returns its result.
+    fn push(&mut self, instruction: Instruction, name: Option<String>) -> Operand {
+        let dtype = instruction.dtype();
+        let rid = RegisterId::temp(self.bid, self.instructions.len());
+        self.instructions.push(Named::new(name, instruction));
+        Operand::register(rid, dtype)
+    }
+
+    /// Returns the pointer to the `index`th piece of `piece` bytes from `ptr`.
+    fn piece_ptr(&mut self, ptr: &Operand, piece: usize, index: usize) -> Operand {
+        self.push(
+            Instruction::GetElementPtr {
+                ptr: ptr.clone(),
+                offset: Operand::constant(Constant::int((index * piece) as u128, Dtype::LONG)),
+                dtype: Dtype::pointer(unsigned(piece)),
+            },
+            None,
+        )
+    }
+
+    fn load(&mut self, ptr: Operand, piece: usize, name: Option<String>) {
+        let dtype = ptr
+            .dtype()
+            .get_pointer_inner()
+            .expect("`ptr` must be a pointer")
+            .clone();
+        let size = access_size(&ptr.dtype());
+
+        let mut value = None;
+        for index in 0..size / piece {
+            let piece_ptr = self.piece_ptr(&ptr, piece, index);
+            let loaded = self.push(Instruction::Load { ptr: piece_ptr }, None);
+            let extended = self.push(
+                Instruction::TypeCast {
+                    value: loaded,
+                    target_dtype: unsigned(size),
+                },
+                None,
+            );
+            value = Some(if let Some(value) = value {
+                let shifted = self.push(
+                    Instruction::BinOp {
+                        op: ast::BinaryOperator::ShiftLeft,
+                        lhs: extended,
+                        rhs: shift(size, piece, index),
+                        dtype: unsigned(size),
+                    },
+                    None,
+                );
+                self.push(
+                    Instruction::BinOp {
+                        op: ast::BinaryOperator::BitwiseOr,
+                        lhs: value,
+                        rhs: shifted,
+                        dtype: unsigned(size),
+                    },
+                    None,
+                )
+            } else {
+                extended
+            });
+        }
+
+        let _unused = self.push(
+            Instruction::TypeCast {
+                value: value.expect("an integer has a piece"),
+                target_dtype: dtype,
+            },
+            name,
+        );
+    }
+
+    fn store(&mut self, ptr: Operand, value: Operand, piece: usize, name: Option<String>) {
+        let size = int_size(&value.dtype()).expect("`value` must be an integer");
+        let bits = self.push(
+            Instruction::TypeCast {
+                value,
+                target_dtype: unsigned(size),
+            },
+            None,
+        );
+
+        let count = size / piece;
+        for index in 0..count {
+            let shifted = if index == 0 {
+                bits.clone()
+            } else {
+                self.push(
+                    Instruction::BinOp {
+                        op: ast::BinaryOperator::ShiftRight,
+                        lhs: bits.clone(),
+                        rhs: shift(size, piece, index),
+                        dtype: unsigned(size),
+                    },
+                    None,
+                )
+            };
+            let truncated = self.push(
+                Instruction::TypeCast {
+                    value: shifted,
+                    target_dtype: unsigned(piece),
+                },
+                None,
+            );
+            let piece_ptr = self.piece_ptr(&ptr, piece, index);
+            // The last store stands for the split one.
+            let name = if index + 1 == count {
+                name.clone()
+            } else {
+                None
+            };
+            let _unused = self.push(
+                Instruction::Store {
+                    ptr: piece_ptr,
+                    value: truncated,
+                },
+                name,
+            );
+        }
+    }
+}
+
+/// Returns the unsigned integer type of `size` bytes.
+fn unsigned(size: usize) -> Dtype {
+    Dtype::int(size * Dtype::BITS_OF_BYTE).set_signed(false)
+}
+
+/// Returns the amount to shift the `index`th piece of `piece` bytes of an integer of `size` bytes.
+fn shift(size: usize, piece: usize, index: usize) -> Operand {
+    Operand::constant(Constant::int(
+        (index * piece * Dtype::BITS_OF_BYTE) as u128,
+        unsigned(size),
+    ))
+}
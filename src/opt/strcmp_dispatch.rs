@@ -0,0 +1,558 @@
+use core::ops::Deref;
+use std::collections::{HashMap, HashSet};
+
+use lang_c::ast;
+
+use crate::ir::*;
+use crate::opt::analysis::Cfg;
+use crate::*;
+
+/// The number of distinct literals a chain must compare the string with to be dispatched, as a
+/// chain of two already takes at most two calls.
+const MIN_LITERALS: usize = 3;
+
+/// Rewrites the chains comparing the same string with string literals by `strcmp`, e.g.,
+/// `if (strcmp(cmd, "add") == 0) ... else if (strcmp(cmd, "sub") == 0) ...`, into `switch`es on
+/// its characters.
+///
+/// A link of a chain compares the result of `strcmp` (see [`Builtin::from_library_name`]) of the
+/// string and a constant global character array, e.g., `static const char ADD[] = "add";`, with 0
+/// by `cmp eq` or `cmp ne`, and branches on it to its target or to the next link. The next link is
+/// a block with no other predecessor nor phinodes and nothing else, so that a link with any other
+/// instruction, e.g., a call with side effects between two comparisons, ends the chain. The string
+/// is then dispatched by a `switch` on its first character, then, among the literals sharing it,
+/// on its second, and so on, down to a single literal confirmed by a single `strcmp`, or ending
+/// with the null character. The literal compared first wins if it is repeated, as in the chain.
+///
+/// The length of the string is not checked first, as it is only known by scanning the string, and
+/// a character is only loaded once the string is known to be at least as long.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct StrcmpDispatch {}
+
+/// A string literal, a null-terminated array of characters in a constant global variable.
+#[derive(Debug, Clone)]
+struct Literal {
+    name: String,
+    /// The dtype of the global variable
+    dtype: Dtype,
+    offset: isize,
+    /// The dtype of the pointer to the literal as passed to `strcmp`
+    pointer_dtype: Dtype,
+    /// The characters, ending with the null character
+    chars: Vec<Constant>,
+}
+
+/// A block ending with the comparison of a string with a literal by `strcmp`.
+#[derive(Debug, Clone)]
+struct Link {
+    /// The position of the first instruction of the comparison in the block, all of those after
+    /// which are part of it
+    start: usize,
+    callee: Operand,
+    return_type: Dtype,
+    string: Operand,
+    literal: Literal,
+    /// The jump if the string is equal to the literal
+    arg_equal: JumpArg,
+    /// The jump if not, to the next link or the default target
+    arg_unequal: JumpArg,
+}
+
+impl Optimize<TranslationUnit> for StrcmpDispatch {
+    fn optimize(&mut self, code: &mut TranslationUnit) -> bool {
+        let mut strings = HashMap::new();
+        for (name, decl) in &code.decls {
+            // The initial value of an `extern` variable is given by another unit.
+            if !decl.is_constant() || decl.is_extern() {
+                continue;
+            }
+
+            let (dtype, initializer) = decl.get_variable().expect("`decl` must be variable");
+            let initializer = some_or!(initializer, continue);
+            let chars = some_or!(
+                Value::try_from_initializer(initializer, dtype, &code.structs).ok(),
+                continue
+            );
+            if let Value::Array { values, .. } = chars {
+                let _unused = strings.insert(name.clone(), (dtype.clone(), values));
+            }
+        }
+
+        // The functions declared without definitions comparing strings as `strcmp` does.
+        let strcmps = code
+            .decls
+            .iter()
+            .filter(|(name, decl)| {
+                matches!(
+                    decl,
+                    Declaration::Function {
+                        definition: None,
+                        ..
+                    }
+                ) && Builtin::from_library_name(name) == Some(Builtin::Strcmp)
+            })
+            .map(|(name, _)| name.clone())
+            .collect::<HashSet<_>>();
+        if strings.is_empty() || strcmps.is_empty() {
+            return false;
+        }
+
+        let mut changed = false;
+        for decl in code.decls.values_mut() {
+            let (_, definition) = some_or!(decl.get_function_mut(), continue);
+            let definition = some_or!(definition, continue);
+            let chains = Chains::new(definition, &strings, &strcmps).collect();
+            for (head, links) in chains {
+                rewrite(definition, head, links);
+                changed = true;
+            }
+        }
+
+        changed
+    }
+}
+
+/// Finds the chains of a function.
+struct Chains<'a> {
+    code: &'a FunctionDefinition,
+    strings: &'a HashMap<String, (Dtype, Vec<Value>)>,
+    strcmps: &'a HashSet<String>,
+    cfg: Cfg,
+    global_pointers: HashMap<RegisterId, (String, Option<isize>)>,
+    /// The positions of the uses of each register, `None` for the exit of the block.
+    uses: HashMap<RegisterId, Vec<(BlockId, Option<usize>)>>,
+}
+
+impl<'a> Chains<'a> {
+    fn new(
+        code: &'a FunctionDefinition,
+        strings: &'a HashMap<String, (Dtype, Vec<Value>)>,
+        strcmps: &'a HashSet<String>,
+    ) -> Self {
+        let mut uses = HashMap::<_, Vec<_>>::new();
+        for (bid, block) in &code.blocks {
+            for (iid, instruction) in block.instructions.iter().enumerate() {
+                instruction.walk_operands(|operand| {
+                    if let Some((rid, _)) = operand.get_register() {
+                        uses.entry(*rid).or_default().push((*bid, Some(iid)));
+                    }
+                });
+            }
+            block.exit.walk_operands(|operand| {
+                if let Some((rid, _)) = operand.get_register() {
+                    uses.entry(*rid).or_default().push((*bid, None));
+                }
+            });
+        }
+
+        Self {
+            code,
+            strings,
+            strcmps,
+            cfg: Cfg::new(code),
+            global_pointers: code.global_pointers(),
+            uses,
+        }
+    }
+
+    /// Returns the chains of the function, by their first blocks and links.
+    fn collect(&self) -> Vec<(BlockId, Vec<Link>)> {
+        let mut chains = Vec::new();
+        for bid in self.code.blocks.keys() {
+            let head = some_or!(self.link(*bid), continue);
+            // A link continuing another is in the chain of the other.
+            if let [pred] = self.cfg.predecessors(*bid) {
+                if self.link(*pred).map_or(false, |link| {
+                    link.arg_unequal.bid == *bid && self.continues(&link, *pred).is_some()
+                }) {
+                    continue;
+                }
+            }
+
+            let mut links = vec![head];
+            while let Some(next) = self.continues(links.last().expect("`links` is not empty"), *bid)
+            {
+                links.push(next);
+            }
+
+            if distinct_literals(&links).len() >= MIN_LITERALS {
+                chains.push((*bid, links));
+            }
+        }
+
+        chains
+    }
+
+    /// Returns the link following `link` in the chain starting from the block `head`, if any. As
+    /// the other links have a single predecessor, only a jump back to `head` closes a loop.
+    fn continues(&self, link: &Link, head: BlockId) -> Option<Link> {
+        let next = link.arg_unequal.bid;
+        if next == head || next == link.arg_equal.bid || self.cfg.predecessors(next).len() != 1 {
+            return None;
+        }
+
+        let next_link = self.link(next)?;
+        let block = &self.code.blocks[&next];
+        (next_link.start == 0 && block.phinodes.is_empty() && next_link.string == link.string)
+            .then_some(next_link)
+    }
+
+    /// Returns the comparison of a string with a literal by `strcmp` the block `bid` ends with,
+    /// if any.
+    fn link(&self, bid: BlockId) -> Option<Link> {
+        let block = &self.code.blocks[&bid];
+        let (condition, arg_then, arg_else) = if let BlockExit::ConditionalJump {
+            condition,
+            arg_then,
+            arg_else,
+        } = &block.exit
+        {
+            (condition, arg_then, arg_else)
+        } else {
+            return None;
+        };
+
+        let len = block.instructions.len();
+        let cmp_iid = len.checked_sub(1)?;
+        let call_iid = len.checked_sub(2)?;
+        if condition.get_register()?.0 != &RegisterId::temp(bid, cmp_iid)
+            || !self.is_only_used_at(bid, cmp_iid, None)
+            || !self.is_only_used_at(bid, call_iid, Some(cmp_iid))
+        {
+            return None;
+        }
+
+        let (op, lhs, rhs) =
+            if let Instruction::BinOp { op, lhs, rhs, .. } = block.instructions[cmp_iid].deref() {
+                (op, lhs, rhs)
+            } else {
+                return None;
+            };
+        let is_zero = |operand: &Operand| {
+            operand
+                .get_constant()
+                .and_then(Constant::get_int)
+                .map_or(false, |(value, _, _)| value == 0)
+        };
+        if !is_zero(lhs) && !is_zero(rhs) {
+            return None;
+        }
+        let (arg_equal, arg_unequal) = match op {
+            ast::BinaryOperator::Equals => (arg_then, arg_else),
+            ast::BinaryOperator::NotEquals => (arg_else, arg_then),
+            _ => return None,
+        };
+
+        let (callee, args, return_type) = if let Instruction::Call {
+            callee,
+            args,
+            return_type,
+        } = block.instructions[call_iid].deref()
+        {
+            (callee, args, return_type)
+        } else {
+            return None;
+        };
+        let name = callee.get_constant()?.get_global_variable_name()?;
+        if !self.strcmps.contains(&name) {
+            return None;
+        }
+        let (string, literal) = match args.as_slice() {
+            [lhs, rhs] => {
+                if let Some(literal) = self.literal(rhs) {
+                    (lhs, literal)
+                } else {
+                    (rhs, self.literal(lhs)?)
+                }
+            }
+            _ => return None,
+        };
+        let char_dtype = string
+            .dtype()
+            .get_pointer_inner()?
+            .clone()
+            .strip_outer_const();
+        if literal.chars.iter().any(|c| c.dtype() != char_dtype) {
+            return None;
+        }
+
+        // The instructions computing the pointer to the literal only for the call.
+        let mut start = call_iid;
+        while let Some(iid) = start.checked_sub(1) {
+            if !matches!(
+                block.instructions[iid].deref(),
+                Instruction::GetElementPtr { .. }
+            ) {
+                break;
+            }
+            let (use_bid, use_iid) = some_or!(self.only_use(RegisterId::temp(bid, iid)), break);
+            if use_bid != bid || use_iid.map_or(true, |use_iid| use_iid < start) {
+                break;
+            }
+            start = iid;
+        }
+        if let Some((
+            RegisterId::Temp {
+                bid: string_bid,
+                iid,
+            },
+            _,
+        )) = string.get_register()
+        {
+            if *string_bid == bid && *iid >= start {
+                return None;
+            }
+        }
+
+        Some(Link {
+            start,
+            callee: callee.clone(),
+            return_type: return_type.clone(),
+            string: string.clone(),
+            literal,
+            arg_equal: arg_equal.clone(),
+            arg_unequal: arg_unequal.clone(),
+        })
+    }
+
+    /// Returns the only use of the register, if it is used once.
+    fn only_use(&self, rid: RegisterId) -> Option<(BlockId, Option<usize>)> {
+        match self.uses.get(&rid).map(Vec::as_slice) {
+            Some([position]) => Some(*position),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the result of the instruction `iid` of the block `bid` is only used by
+    /// its instruction `user`, or its exit if `None`.
+    fn is_only_used_at(&self, bid: BlockId, iid: usize, user: Option<usize>) -> bool {
+        self.only_use(RegisterId::temp(bid, iid)) == Some((bid, user))
+    }
+
+    /// Returns the string literal `operand` points to, if any.
+    fn literal(&self, operand: &Operand) -> Option<Literal> {
+        let (name, offset) = match operand {
+            Operand::Constant(Constant::GlobalVariable { name, .. }) => (name.clone(), 0),
+            Operand::Register { rid, .. } => {
+                let (name, offset) = self.global_pointers.get(rid)?;
+                (name.clone(), (*offset)?)
+            }
+            _ => return None,
+        };
+        let (dtype, values) = self.strings.get(&name)?;
+
+        let mut chars = Vec::new();
+        for value in values.get(usize::try_from(offset).ok()?..)? {
+            let (value, width, is_signed) = value.get_int()?;
+            if width != 8 {
+                return None;
+            }
+            chars.push(Constant::Int {
+                value,
+                width,
+                is_signed,
+                origin: ConstantOrigin::default(),
+            });
+            if value == 0 {
+                return Some(Literal {
+                    name,
+                    dtype: dtype.clone(),
+                    offset,
+                    pointer_dtype: operand.dtype(),
+                    chars,
+                });
+            }
+        }
+
+        // The literal is not null-terminated.
+        None
+    }
+}
+
+/// Returns the literals of `links` with their targets, without those compared before.
+fn distinct_literals(links: &[Link]) -> Vec<(Literal, JumpArg)> {
+    let mut literals = Vec::<(Literal, JumpArg)>::new();
+    for link in links {
+        if literals
+            .iter()
+            .all(|(literal, _)| literal.chars != link.literal.chars)
+        {
+            literals.push((link.literal.clone(), link.arg_equal.clone()));
+        }
+    }
+    literals
+}
+
+/// Replaces the chain of `links` starting from the block `head` by the dispatch of the string by
+/// its characters.
+fn rewrite(code: &mut FunctionDefinition, head: BlockId, links: Vec<Link>) {
+    let first = &links[0];
+    let last = links.last().expect("`links` must not be empty");
+
+    // The links after the first are only reached from the chain.
+    for link in &links[..links.len() - 1] {
+        let _unused = code.blocks.remove(&link.arg_unequal.bid);
+    }
+    code.blocks
+        .get_mut(&head)
+        .expect("`head` must be a block")
+        .instructions
+        .truncate(first.start);
+
+    let mut dispatch = Dispatch {
+        code,
+        callee: first.callee.clone(),
+        return_type: first.return_type.clone(),
+        string: first.string.clone(),
+        default: last.arg_unequal.clone(),
+    };
+    dispatch.dispatch(head, &distinct_literals(&links), 0);
+}
+
+struct Dispatch<'a> {
+    code: &'a mut FunctionDefinition,
+    callee: Operand,
+    return_type: Dtype,
+    string: Operand,
+    /// The jump if the string is equal to none of the literals
+    default: JumpArg,
+}
+
+impl Dispatch<'_> {
+    /// Dispatches the string to the target of the one of `literals` it is equal to from the end of
+    /// the block `bid`, knowing that it starts with their first `index` characters.
+    fn dispatch(&mut self, bid: BlockId, literals: &[(Literal, JumpArg)], index: usize) {
+        if let [(literal, arg)] = literals {
+            self.confirm(bid, literal, arg);
+            return;
+        }
+
+        // The string is at least `index` characters long, none of which is null.
+        let pointer = if index == 0 {
+            self.string.clone()
+        } else {
+            self.push(
+                bid,
+                Instruction::GetElementPtr {
+                    ptr: self.string.clone(),
+                    offset: Operand::constant(Constant::int(index as u128, Dtype::LONG)),
+                    dtype: self.string.dtype(),
+                },
+            )
+        };
+        let value = self.push(bid, Instruction::Load { ptr: pointer });
+
+        let mut buckets = Vec::<(Constant, Vec<(Literal, JumpArg)>)>::new();
+        for (literal, arg) in literals {
+            let char = &literal.chars[index];
+            if let Some((_, bucket)) = buckets.iter_mut().find(|(other, _)| other == char) {
+                bucket.push((literal.clone(), arg.clone()));
+            } else {
+                buckets.push((char.clone(), vec![(literal.clone(), arg.clone())]));
+            }
+        }
+
+        let mut cases = Vec::new();
+        for (char, bucket) in buckets {
+            let is_null = char.get_int().map_or(false, |(value, _, _)| value == 0);
+            let arg = if is_null {
+                // The string is equal to the only literal ending here, as the others differ.
+                bucket[0].1.clone()
+            } else {
+                let bid_bucket = self.insert_block();
+                self.dispatch(bid_bucket, &bucket, index + 1);
+                JumpArg::new(bid_bucket, Vec::new())
+            };
+            cases.push((char, arg));
+        }
+        self.set_exit(
+            bid,
+            BlockExit::Switch {
+                value,
+                default: self.default.clone(),
+                cases,
+            },
+        );
+    }
+
+    /// Compares the string with `literal` by `strcmp` from the end of the block `bid`, jumping to
+    /// `arg` if they are equal.
+    fn confirm(&mut self, bid: BlockId, literal: &Literal, arg: &JumpArg) {
+        let pointer = self.push(
+            bid,
+            Instruction::GetElementPtr {
+                ptr: Operand::constant(Constant::global_variable(
+                    literal.name.clone(),
+                    literal.dtype.clone(),
+                )),
+                offset: Operand::constant(Constant::int(literal.offset as u128, Dtype::LONG)),
+                dtype: literal.pointer_dtype.clone(),
+            },
+        );
+        let result = self.push(
+            bid,
+            Instruction::Call {
+                callee: self.callee.clone(),
+                args: vec![self.string.clone(), pointer],
+                return_type: self.return_type.clone(),
+            },
+        );
+        let condition = self.push(
+            bid,
+            Instruction::BinOp {
+                op: ast::BinaryOperator::Equals,
+                lhs: result,
+                rhs: Operand::constant(Constant::int(0, self.return_type.clone())),
+                dtype: Dtype::BOOL,
+            },
+        );
+        self.set_exit(
+            bid,
+            BlockExit::ConditionalJump {
+                condition,
+                arg_then: arg.clone(),
+                arg_else: self.default.clone(),
+            },
+        );
+    }
+
+    /// Appends `instruction` to the block `bid`, and returns its result.
+    fn push(&mut self, bid: BlockId, instruction: Instruction) -> Operand {
+        let block = self
+            .code
+            .blocks
+            .get_mut(&bid)
+            .expect("`bid` must be a block");
+        let dtype = instruction.dtype();
+        block.instructions.push(Named::new(None, instruction));
+        Operand::register(RegisterId::temp(bid, block.instructions.len() - 1), dtype)
+    }
+
+    fn set_exit(&mut self, bid: BlockId, exit: BlockExit) {
+        self.code
+            .blocks
+            .get_mut(&bid)
+            .expect("`bid` must be a block")
+            .exit = exit;
+    }
+
+    /// Inserts an empty block, whose exit is set later.
+    fn insert_block(&mut self) -> BlockId {
+        let bid = BlockId(
+            self.code
+                .blocks
+                .keys()
+                .next_back()
+                .map_or(0, |bid| bid.0 + 1),
+        );
+        let _unused = self.code.blocks.insert(
+            bid,
+            Block {
+                phinodes: Vec::new(),
+                instructions: Vec::new(),
+                exit: BlockExit::Unreachable,
+            },
+        );
+        bid
+    }
+}
@@ -0,0 +1,247 @@
+use core::ops::Deref;
+use std::collections::HashMap;
+
+use crate::ir::*;
+use crate::opt::analysis::Cfg;
+use crate::opt::opt_utils::*;
+use crate::opt::FunctionPass;
+use crate::*;
+
+pub type TailMerge = FunctionPass<TailMergeInner>;
+
+/// Merges the identical tails of the blocks jumping to the same block, e.g., the code common to
+/// both branches of an `if`, into that block.
+///
+/// If every predecessor of a block ends with an unconditional jump to it, and their last
+/// instructions are the same but for their operands, they are replaced by a single instruction at
+/// the beginning of the block. The operands differing between the predecessors are passed as
+/// block arguments, reusing a phinode already receiving them if any. Repeating this merges the
+/// longest common suffix, one instruction at a time.
+///
+/// Since only the last instructions are moved, and only past the jumps, the order of the side
+/// effects on every path is kept. The pointer of a load or a store and the callee of a call must
+/// be the same in every predecessor, so that different memory accesses are never merged.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct TailMergeInner {}
+
+/// A merge of the last instructions of the predecessors of a block.
+#[derive(Debug)]
+struct Merge {
+    bid: BlockId,
+    predecessors: Vec<BlockId>,
+    /// The merged instruction, whose differing operands are replaced by the phinodes of `bid`
+    instruction: Named<Instruction>,
+    /// The phinodes to be appended to `bid`, with their argument from each predecessor
+    phinodes: Vec<(Dtype, Vec<Operand>)>,
+    /// The phinodes receiving the result of the instruction from every predecessor
+    forwarded: Vec<usize>,
+}
+
+impl Optimize<FunctionDefinition> for TailMergeInner {
+    fn optimize(&mut self, code: &mut FunctionDefinition) -> bool {
+        // Merging instructions does not change the control flow graph.
+        let cfg = Cfg::new(code);
+
+        let mut changed = false;
+        while let Some(merge) = code
+            .blocks
+            .keys()
+            .find_map(|bid| candidate(code, &cfg, *bid))
+        {
+            apply(code, merge);
+            changed = true;
+        }
+
+        changed
+    }
+}
+
+/// Returns the merge of the last instructions of the predecessors of `bid`, if they can be merged.
+fn candidate(code: &FunctionDefinition, cfg: &Cfg, bid: BlockId) -> Option<Merge> {
+    let predecessors = cfg.predecessors(bid);
+    if bid == code.bid_init || predecessors.len() < 2 || predecessors.contains(&bid) {
+        return None;
+    }
+
+    let mut lasts = Vec::new();
+    let mut args = Vec::new();
+    for pred in predecessors {
+        let block = &code.blocks[pred];
+        if let BlockExit::Jump { arg } = &block.exit {
+            lasts.push(block.instructions.last()?);
+            args.push(&arg.args);
+        } else {
+            return None;
+        }
+    }
+
+    let first = lasts[0];
+    if lasts[1..].iter().any(|last| shape(last) != shape(first)) {
+        return None;
+    }
+
+    let num_phinodes = code.blocks[&bid].phinodes.len();
+    let operands = lasts
+        .iter()
+        .map(|last| last.operands().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let mut instruction = first.deref().clone();
+    let mut phinodes = Vec::<(Dtype, Vec<Operand>)>::new();
+    for (index, operand) in instruction.operands_mut().enumerate() {
+        let values = operands
+            .iter()
+            .map(|operands| operands[index].clone())
+            .collect::<Vec<_>>();
+
+        if values.iter().all(|value| *value == values[0]) {
+            // The registers of `bid` hold other values at its beginning.
+            if let Some((RegisterId::Arg { bid: b, .. } | RegisterId::Temp { bid: b, .. }, _)) =
+                values[0].get_register()
+            {
+                if *b == bid {
+                    return None;
+                }
+            }
+            continue;
+        }
+
+        if is_address(first, index) {
+            return None;
+        }
+
+        let existing = (0..num_phinodes).find(|aid| {
+            args.iter()
+                .zip(&values)
+                .all(|(args, value)| args[*aid] == *value)
+        });
+        let aid = if let Some(aid) = existing {
+            aid
+        } else if let Some(position) = phinodes.iter().position(|(_, column)| *column == values) {
+            num_phinodes + position
+        } else {
+            phinodes.push((operand.dtype(), values));
+            num_phinodes + phinodes.len() - 1
+        };
+        *operand = Operand::register(RegisterId::arg(bid, aid), operand.dtype());
+    }
+
+    // The result of the instructions may only be passed to `bid` by every predecessor at once.
+    let results = predecessors
+        .iter()
+        .map(|pred| RegisterId::temp(*pred, code.blocks[pred].instructions.len() - 1))
+        .collect::<Vec<_>>();
+    let mut forwarded = Vec::new();
+    for aid in 0..num_phinodes {
+        let count = args
+            .iter()
+            .zip(&results)
+            .filter(|(args, result)| args[aid].get_register().map(|(rid, _)| rid) == Some(*result))
+            .count();
+        if count == results.len() {
+            forwarded.push(aid);
+        } else if count > 0 {
+            return None;
+        }
+    }
+
+    let name = if lasts.iter().all(|last| last.name() == first.name()) {
+        first.name().cloned()
+    } else {
+        None
+    };
+    Some(Merge {
+        bid,
+        predecessors: predecessors.to_vec(),
+        instruction: Named::new(name, instruction),
+        phinodes,
+        forwarded,
+    })
+}
+
+fn apply(code: &mut FunctionDefinition, merge: Merge) {
+    let Merge {
+        bid,
+        predecessors,
+        instruction,
+        phinodes,
+        forwarded,
+    } = merge;
+    let dtype = instruction.dtype();
+
+    let block = code.blocks.get_mut(&bid).expect("`bid` must be a block");
+    block.phinodes.extend(
+        phinodes
+            .iter()
+            .map(|(dtype, _)| Named::new(None, dtype.clone())),
+    );
+    let num_phinodes = block.phinodes.len();
+    for (index, pred) in predecessors.iter().enumerate() {
+        let block = code.blocks.get_mut(pred).expect("`pred` must be a block");
+        if let BlockExit::Jump { arg } = &mut block.exit {
+            arg.args
+                .extend(phinodes.iter().map(|(_, column)| column[index].clone()));
+        }
+    }
+
+    // Moving the first instruction renumbers the instructions of `bid`, and the others are dropped.
+    let first = predecessors[0];
+    let last = code.blocks[&first].instructions.len() - 1;
+    move_instruction(code, (first, last), (bid, 0));
+    let block = code.blocks.get_mut(&bid).expect("`bid` must be a block");
+    block.instructions[0] = instruction;
+    for pred in &predecessors {
+        let block = code.blocks.get_mut(pred).expect("`pred` must be a block");
+        if *pred != first {
+            let _unused = block.instructions.pop();
+        }
+        if let BlockExit::Jump { arg } = &mut block.exit {
+            for aid in forwarded.iter().rev() {
+                let _unused = arg.args.remove(*aid);
+            }
+        }
+    }
+
+    // The forwarded phinodes are replaced by the result, and the others are renumbered.
+    let mut replaces = HashMap::new();
+    let mut next = 0;
+    for aid in 0..num_phinodes {
+        let rid = RegisterId::arg(bid, aid);
+        if forwarded.contains(&aid) {
+            let _unused = replaces.insert(
+                rid,
+                Operand::register(RegisterId::temp(bid, 0), dtype.clone()),
+            );
+        } else {
+            if aid != next {
+                let dtype = code.blocks[&bid].phinodes[aid].deref().clone();
+                let _unused =
+                    replaces.insert(rid, Operand::register(RegisterId::arg(bid, next), dtype));
+            }
+            next += 1;
+        }
+    }
+    let block = code.blocks.get_mut(&bid).expect("`bid` must be a block");
+    for aid in forwarded.iter().rev() {
+        let _unused = block.phinodes.remove(*aid);
+    }
+    let _unused = replace_operands(code, &replaces);
+}
+
+/// Returns the instruction with every operand replaced by `undef` of its type, to compare the
+/// instructions but for their operands.
+fn shape(instruction: &Instruction) -> Instruction {
+    let mut shape = instruction.clone();
+    shape.walk_operands_mut(|operand| {
+        *operand = Operand::constant(Constant::undef(operand.dtype()));
+    });
+    shape
+}
+
+/// Returns `true` if the operand at `index` of `instruction` is the memory it accesses.
+fn is_address(instruction: &Instruction, index: usize) -> bool {
+    index == 0
+        && matches!(
+            instruction,
+            Instruction::Store { .. } | Instruction::Load { .. } | Instruction::Call { .. }
+        )
+}
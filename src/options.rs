@@ -0,0 +1,393 @@
+//! Options that change the semantics or the output of the compiler.
+
+use core::cell::Cell;
+use core::fmt;
+use core::str::FromStr;
+
+thread_local! {
+    /// The target data of the C source lowered by the current thread.
+    static TARGET_DATA: Cell<TargetData> = const { Cell::new(TargetData::RV64) };
+}
+
+/// Semantics of signed integer overflow.
+///
+/// The policy is consulted by:
+///
+/// - the interpreter, which reports signed overflow, including `INT_MIN / -1` and `INT_MIN % -1`,
+///   as well as the shifts by amounts out of range (see `ir::ShiftKind`) and the integer
+///   divisions by zero, as an error under `Trap` and `Ub`. Under `Wrap`, it yields what RISC-V
+///   yields: `INT_MIN / -1` is `INT_MIN`, `INT_MIN % -1` is 0, a division by zero is all ones and
+///   a remainder by zero is the dividend. Likewise, it reports the calls through pointers to
+///   incompatible function types, e.g., an `int (*)(int)` pointing to `unsigned f(int)`, under
+///   `Trap` and `Ub`, while under `Wrap` it only rejects those passing mismatched arguments;
+/// - `SimplifyArith`, which folds constant expressions with two's complement wrap-around under
+///   `Wrap` and `Ub`, keeps overflowing expressions and shifts out of range as-is under `Trap`, and
+///   only under `Ub` folds comparisons by assuming no overflow, e.g. `x + 1 > x` to `1`. It never
+///   folds the divisions by zero, `INT_MIN / -1` and `INT_MIN % -1`.
+///
+/// Passes that reason about trip counts or value ranges must only assume the absence of signed
+/// overflow under `Ub`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// Signed overflow wraps around in two's complement, i.e., `-fwrapv`.
+    #[default]
+    Wrap,
+    /// Signed overflow aborts the execution.
+    Trap,
+    /// Signed overflow is undefined behavior, which optimizations may assume never happens.
+    Ub,
+}
+
+impl fmt::Display for OverflowPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Wrap => "wrap",
+                Self::Trap => "trap",
+                Self::Ub => "ub",
+            }
+        )
+    }
+}
+
+impl FromStr for OverflowPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wrap" => Ok(Self::Wrap),
+            "trap" => Ok(Self::Trap),
+            "ub" => Ok(Self::Ub),
+            _ => Err(format!(
+                "unknown overflow policy `{s}` (expected `wrap`, `trap` or `ub`)"
+            )),
+        }
+    }
+}
+
+/// Instrumentation of the generated code for debugging.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instrumentation {
+    #[default]
+    None,
+    /// Guards each stack frame with a canary checked before returning (see
+    /// `asm::Function::canary_check`). The interpreter checks the bounds of the accesses to local
+    /// allocations instead.
+    StackProtect,
+}
+
+impl Instrumentation {
+    /// The message reported when the stack is found corrupted.
+    pub const STACK_FAIL_MESSAGE: &'static str = "*** stack smashing detected ***: terminated";
+
+    /// The canary stored in each stack frame under `StackProtect`.
+    pub const STACK_CANARY: u64 = 0x4b45_4343_c0de_ff00;
+}
+
+impl fmt::Display for Instrumentation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::None => "none",
+                Self::StackProtect => "stack-protect",
+            }
+        )
+    }
+}
+
+impl FromStr for Instrumentation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "stack-protect" => Ok(Self::StackProtect),
+            _ => Err(format!(
+                "unknown instrumentation `{s}` (expected `none` or `stack-protect`)"
+            )),
+        }
+    }
+}
+
+/// The revision of the C standard the source is checked against.
+///
+/// Every behavior depending on the revision is decided by the methods of this type, so that a
+/// check never compares revisions itself.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LanguageStd {
+    /// ANSI C, i.e., C90.
+    C89,
+    C99,
+    #[default]
+    C11,
+    C17,
+}
+
+impl LanguageStd {
+    /// Whether a declaration may follow a statement in a block (see C17 6.8.2p1).
+    pub fn allows_mixed_declarations(self) -> bool {
+        self >= Self::C99
+    }
+
+    /// Whether the first clause of a `for` statement may be a declaration (see C17 6.8.5p1).
+    pub fn allows_for_declarations(self) -> bool {
+        self >= Self::C99
+    }
+}
+
+impl fmt::Display for LanguageStd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::C89 => "c89",
+                Self::C99 => "c99",
+                Self::C11 => "c11",
+                Self::C17 => "c17",
+            }
+        )
+    }
+}
+
+impl FromStr for LanguageStd {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "c89" | "c90" => Ok(Self::C89),
+            "c99" => Ok(Self::C99),
+            "c11" => Ok(Self::C11),
+            "c17" | "c18" => Ok(Self::C17),
+            _ => Err(format!(
+                "unknown language standard `{s}` (expected `c89`, `c99`, `c11` or `c17`)"
+            )),
+        }
+    }
+}
+
+/// The signedness of plain `char`, which C leaves to the implementation (see C17 6.2.5p15).
+///
+/// The RISC-V Linux ABI makes it unsigned, as for `gcc -funsigned-char`, whereas x86 makes it
+/// signed. `signed char` and `unsigned char` are not affected. Plain `char` is lowered into `i8`
+/// or `u8` accordingly, so that the interpreter and the back-end, e.g., `lb` or `lbu`, follow the
+/// IR without consulting the option again.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CharSignedness {
+    #[default]
+    Signed,
+    Unsigned,
+}
+
+impl fmt::Display for CharSignedness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Signed => "signed",
+                Self::Unsigned => "unsigned",
+            }
+        )
+    }
+}
+
+impl FromStr for CharSignedness {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "signed" => Ok(Self::Signed),
+            "unsigned" => Ok(Self::Unsigned),
+            _ => Err(format!(
+                "unknown char signedness `{s}` (expected `signed` or `unsigned`)"
+            )),
+        }
+    }
+}
+
+/// Returns the signedness of plain `char` in the C source lowered by the current thread.
+pub fn char_signedness() -> CharSignedness {
+    target_data().char_signedness
+}
+
+/// Sets the signedness of plain `char` in the C source lowered by the current thread, overriding
+/// that of the target data.
+pub fn set_char_signedness(signedness: CharSignedness) {
+    set_target_data(TargetData {
+        char_signedness: signedness,
+        ..target_data()
+    });
+}
+
+/// The sizes and alignments of the types that depend on the target, e.g., 4-byte pointers and
+/// `long` on RV32 (ILP32) and 8-byte ones on RV64 (LP64).
+///
+/// They are consulted by `Dtype::size_align_of` and in lowering the C types, e.g., `long`, and
+/// must thus be set by [`set_target_data`] before lowering a unit, as the layouts of its structs
+/// are computed once. The interpreter and the back-end then follow the IR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TargetData {
+    /// The size of pointers in bytes
+    pub pointer_size: usize,
+    /// The size of `long` in bytes
+    pub long_size: usize,
+    /// The signedness of plain `char`, signed by default as on the hosts running the tests,
+    /// unlike the RISC-V Linux ABI
+    pub char_signedness: CharSignedness,
+    /// The largest alignment of a scalar in bytes, to which the wider ones are aligned
+    pub max_align: usize,
+}
+
+impl Default for TargetData {
+    fn default() -> Self {
+        Self::RV64
+    }
+}
+
+impl TargetData {
+    /// 64-bit RISC-V, whose pointers and `long` are 8 bytes.
+    pub const RV64: Self = Self {
+        pointer_size: 8,
+        long_size: 8,
+        char_signedness: CharSignedness::Signed,
+        max_align: 16,
+    };
+
+    /// 32-bit RISC-V, whose pointers and `long` are 4 bytes.
+    pub const RV32: Self = Self {
+        pointer_size: 4,
+        long_size: 4,
+        char_signedness: CharSignedness::Signed,
+        max_align: 16,
+    };
+
+    /// Returns the target triple of the target data, e.g., to name the target in the layouts
+    /// computed for it.
+    pub fn triple(&self) -> &'static str {
+        if self.pointer_size == 4 {
+            "riscv32-linux-gnu"
+        } else {
+            "riscv64-linux-gnu"
+        }
+    }
+}
+
+/// Returns the target data of the C source lowered by the current thread.
+pub fn target_data() -> TargetData {
+    TARGET_DATA.with(Cell::get)
+}
+
+/// Sets the target data of the C source lowered by the current thread, e.g., to
+/// [`TargetData::RV32`] before lowering it for RV32.
+pub fn set_target_data(target_data: TargetData) {
+    TARGET_DATA.with(|cell| cell.set(target_data));
+}
+
+/// Strategies to lower a `switch` with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwitchStrategy {
+    /// Compares the value with each case in turn.
+    Linear,
+    /// Keeps the `switch` for the back-end to index a table of the targets with the value.
+    JumpTable,
+    /// Bisects the sorted cases by comparisons down to linear chains of the remaining cases.
+    Tree,
+}
+
+/// Thresholds choosing the [`SwitchStrategy`] of a `switch` by the number of its cases and their
+/// density, i.e., the ratio of the number of cases to the range of their values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SwitchThresholds {
+    /// The maximum number of cases compared in turn, for the whole `switch` or for a leaf of the
+    /// tree.
+    pub max_linear_cases: usize,
+    /// The minimum density of the cases in percent for a jump table.
+    pub min_table_density: usize,
+}
+
+impl Default for SwitchThresholds {
+    fn default() -> Self {
+        Self {
+            max_linear_cases: Self::DEFAULT_MAX_LINEAR_CASES,
+            min_table_density: Self::DEFAULT_MIN_TABLE_DENSITY,
+        }
+    }
+}
+
+impl SwitchThresholds {
+    /// The default maximum number of cases compared in turn.
+    pub const DEFAULT_MAX_LINEAR_CASES: usize = 4;
+
+    /// The default minimum density of the cases in percent for a jump table.
+    pub const DEFAULT_MIN_TABLE_DENSITY: usize = 40;
+
+    /// Chooses the strategy for a `switch` with the case values `values`.
+    ///
+    /// A few cases are compared in turn. The others are dispatched by a jump table if they are
+    /// dense enough, and by a tree of comparisons otherwise.
+    pub fn strategy(&self, values: &[i128]) -> SwitchStrategy {
+        if values.len() <= self.max_linear_cases {
+            return SwitchStrategy::Linear;
+        }
+
+        let (min, max) = match (values.iter().min(), values.iter().max()) {
+            (Some(min), Some(max)) => (*min, *max),
+            _ => return SwitchStrategy::Linear,
+        };
+        let range = max.abs_diff(min) + 1;
+        if values.len() as u128 * 100 >= self.min_table_density as u128 * range {
+            SwitchStrategy::JumpTable
+        } else {
+            SwitchStrategy::Tree
+        }
+    }
+}
+
+/// Options shared by the front-end, the optimizer, the interpreter and the back-end.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompileOptions {
+    /// Semantics of signed integer overflow.
+    pub overflow_policy: OverflowPolicy,
+    /// Instrumentation of the generated code.
+    pub instrumentation: Instrumentation,
+    /// Thresholds choosing how to lower each `switch`.
+    pub switch_thresholds: SwitchThresholds,
+    /// Whether the loads and stores less aligned than their size are split into aligned pieces,
+    /// for the targets trapping on misaligned accesses. Without it, the interpreter reports the
+    /// misaligned accesses as the target would trap on them.
+    pub strict_align: bool,
+    /// The revision of the C standard the source is checked against.
+    pub language_std: LanguageStd,
+    /// The sizes of the types and the signedness of plain `char` on the target, which the thread
+    /// lowering the source follows (see [`set_target_data`]).
+    pub target_data: TargetData,
+}
+
+impl CompileOptions {
+    /// Serializes the options into a JSON object.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"overflow_policy\": \"{}\", \"instrumentation\": \"{}\", \
+             \"switch_thresholds\": {{\"max_linear_cases\": {}, \"min_table_density\": {}}}, \
+             \"strict_align\": {}, \"language_std\": \"{}\", \
+             \"target\": {{\"triple\": \"{}\", \"pointer_size\": {}, \"long_size\": {}, \
+             \"char_signedness\": \"{}\", \"max_align\": {}}}}}",
+            self.overflow_policy,
+            self.instrumentation,
+            self.switch_thresholds.max_linear_cases,
+            self.switch_thresholds.min_table_density,
+            self.strict_align,
+            self.language_std,
+            self.target_data.triple(),
+            self.target_data.pointer_size,
+            self.target_data.long_size,
+            self.target_data.char_signedness,
+            self.target_data.max_align
+        )
+    }
+}
@@ -0,0 +1,185 @@
+//! Per-function size and stack usage report.
+
+use core::fmt;
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::ir;
+use crate::opt::analysis::CallGraph;
+
+peg::parser! {
+    grammar report_parse() for str {
+        rule whitespace() = quiet!{[' ' | '\n' | '\t']}
+
+        rule _() = whitespace()*
+
+        pub rule report() -> Report
+            = _ "[" _ functions:(function() ** (_ "," _)) _ "]" _ {
+                Report { functions }
+            }
+
+        rule function() -> FunctionReport
+            = "{" _
+              "\"name\"" _ ":" _ name:string() _ "," _
+              "\"instructions\"" _ ":" _ instructions:number() _ "," _
+              "\"blocks\"" _ ":" _ blocks:number() _ "," _
+              "\"frame_size\"" _ ":" _ frame_size:number() _ "," _
+              "\"call_depth\"" _ ":" _ call_depth:(n:number() { Some(n) } / "null" { None }) _
+              "}" {
+                FunctionReport {
+                    name,
+                    instructions,
+                    blocks,
+                    frame_size,
+                    call_depth,
+                }
+            }
+
+        rule string() -> String
+            = "\"" s:$([^ '"' | '\\']*) "\"" { s.to_string() }
+
+        rule number() -> usize
+            = n:$(['0'..='9']+) {? n.parse().or(Err("usize")) }
+    }
+}
+
+/// Size and stack usage of a function.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FunctionReport {
+    pub name: String,
+    /// Number of instructions, including block exits
+    pub instructions: usize,
+    /// Number of blocks
+    pub blocks: usize,
+    /// Size in bytes of the local allocations, laid out in order with their alignments
+    pub frame_size: usize,
+    /// Maximum depth of the calls made by the function, or `None` if it may be unbounded due to
+    /// recursion
+    ///
+    /// A function calling no function has depth 0. Functions referenced by the function are
+    /// counted as called (see [`CallGraph`]).
+    pub call_depth: Option<usize>,
+}
+
+/// Report of the functions defined in a translation unit, in the order of their names.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Report {
+    pub functions: Vec<FunctionReport>,
+}
+
+impl Report {
+    pub fn new(unit: &ir::TranslationUnit) -> Self {
+        let call_depths = call_depths(&CallGraph::new(unit));
+
+        let functions = unit
+            .decls
+            .iter()
+            .filter_map(|(name, decl)| {
+                let (_, definition) = decl.get_function()?;
+                let definition = definition.as_ref()?;
+
+                let instructions = definition
+                    .blocks
+                    .values()
+                    .map(|block| block.instructions.len() + 1)
+                    .sum();
+
+                let mut frame_size: usize = 0;
+                for allocation in &definition.allocations {
+                    let (size_of, align_of) = allocation
+                        .size_align_of(&unit.structs)
+                        .expect("`allocation` must have its size");
+                    if align_of != 0 {
+                        frame_size = frame_size.div_ceil(align_of) * align_of;
+                    }
+                    frame_size += size_of;
+                }
+
+                Some(FunctionReport {
+                    name: name.clone(),
+                    instructions,
+                    blocks: definition.blocks.len(),
+                    frame_size,
+                    call_depth: call_depths.get(name.as_str()).copied().flatten(),
+                })
+            })
+            .collect();
+
+        Self { functions }
+    }
+
+    /// Serializes the report into a JSON array of objects, one per function.
+    pub fn to_json(&self) -> String {
+        let functions = self.functions.iter().format_with(",\n", |function, f| {
+            f(&format_args!(
+                "  {{\"name\": \"{}\", \"instructions\": {}, \"blocks\": {}, \
+                 \"frame_size\": {}, \"call_depth\": {}}}",
+                function.name,
+                function.instructions,
+                function.blocks,
+                function.frame_size,
+                function
+                    .call_depth
+                    .map_or_else(|| "null".to_string(), |depth| depth.to_string()),
+            ))
+        });
+
+        format!("[\n{functions}\n]\n")
+    }
+
+    /// Deserializes a report written by [`Report::to_json`].
+    pub fn from_json(json: &str) -> Result<Self, peg::error::ParseError<peg::str::LineCol>> {
+        report_parse::report(json)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:>12} {:>8} {:>10} {:>10}",
+            "function", "instructions", "blocks", "frame_size", "call_depth"
+        )?;
+
+        for function in &self.functions {
+            writeln!(
+                f,
+                "{:<24} {:>12} {:>8} {:>10} {:>10}",
+                function.name,
+                function.instructions,
+                function.blocks,
+                function.frame_size,
+                function
+                    .call_depth
+                    .map_or_else(|| "unbounded".to_string(), |depth| depth.to_string()),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Computes the maximum call depth of each function, or `None` if it may be unbounded.
+fn call_depths(call_graph: &CallGraph) -> HashMap<String, Option<usize>> {
+    let mut depths = HashMap::<String, Option<usize>>::new();
+
+    // Callees are visited before their callers.
+    for scc in call_graph.sccs() {
+        let is_recursive = scc.len() > 1 || call_graph.is_recursive(&scc[0]);
+        for name in &scc {
+            let depth = if is_recursive {
+                None
+            } else {
+                let callees = call_graph.callees(name).expect("`name` must be a function");
+                callees.iter().try_fold(0, |depth, callee| {
+                    let callee_depth = depths[callee.as_str()]?;
+                    Some(depth.max(callee_depth + 1))
+                })
+            };
+            let _unused = depths.insert(name.clone(), depth);
+        }
+    }
+
+    depths
+}
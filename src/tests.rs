@@ -1,7 +1,8 @@
 use lang_c::*;
 use rand::Rng;
+use std::collections::HashMap;
 use std::fs::{self, File};
-use std::io::{stderr, Read, Write};
+use std::io::{stderr, Cursor, Read, Write};
 use std::path::Path;
 use std::process::{Command, Stdio};
 use tempfile::tempdir;
@@ -11,6 +12,26 @@ use crate::*;
 
 const NONCE_NAME: &str = "nonce";
 
+/// The marker of the tests depending on the order of evaluation KECC chooses, followed by the exit
+/// status they are expected to return, e.g., `// kecc: order-dependent, returns 1`.
+const ORDER_DEPENDENT_MARKER: &str = "// kecc: order-dependent, returns ";
+
+/// Interprets `main` of `ir`, which must return `int`, reading `stdin` as the standard input, and
+/// returns the exit status of the run.
+///
+/// The full value `main` returns is not observable by the native and the emulated runs the
+/// interpreter is compared to, whose exit statuses are its low 8 bits.
+fn interp_main(ir: &ir::TranslationUnit, stdin: &[u8]) -> u8 {
+    let bindings = ir::Bindings::default().stdin(Cursor::new(stdin.to_vec()));
+    let result = ir::interp_with_bindings(ir, Vec::new(), CompileOptions::default(), bindings)
+        .unwrap_or_else(|interp_error| panic!("{}", interp_error));
+    // We only allow a main function whose return type is `int`
+    let (_, width, is_signed) = result.get_int().expect("non-integer value occurs");
+    assert_eq!(width, 32);
+    assert!(is_signed);
+    result.exit_status().expect("`result` must be an integer")
+}
+
 fn modify_c(path: &Path, rand_num: i32) -> String {
     let mut src = File::open(path).expect("`path` must exist");
     let mut data = String::new();
@@ -24,6 +45,18 @@ fn modify_c(path: &Path, rand_num: i32) -> String {
     data.replace(&from, &to)
 }
 
+/// Returns the exit status declared by the marker of `source` if it depends on the order of
+/// evaluation, which other compilers may not share.
+fn order_dependent_status(source: &str) -> Option<u8> {
+    source.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix(ORDER_DEPENDENT_MARKER)?
+            .trim()
+            .parse()
+            .ok()
+    })
+}
+
 fn ast_initializer(number: i32) -> ast::Initializer {
     let expr = ast::Expression::Constant(Box::new(span::Node::new(
         ast::Constant::Integer(ast::Integer {
@@ -49,6 +82,8 @@ fn modify_ir(unit: &mut ir::TranslationUnit, rand_num: i32) {
             let new_decl = ir::Declaration::Variable {
                 dtype: dtype.clone(),
                 initializer: Some(initializer),
+                is_extern: false,
+                is_static: false,
             };
 
             *decl = new_decl;
@@ -94,6 +129,39 @@ pub fn test_write_c(path: &Path) {
     temp_dir.close().expect("temp dir deletion failed");
 }
 
+/// Tests the normalized C writer: the C source at `path` is normalized to the one at
+/// `normal_path`, which is normalized to itself and parses back to the same AST.
+pub fn test_normalize(path: &Path, normal_path: &Path) {
+    let normalize = |path: &Path| {
+        let unit = Parse
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+        let mut normalized = Vec::new();
+        write(&Normalized(&unit), &mut normalized).unwrap();
+        (unit, String::from_utf8(normalized).unwrap())
+    };
+
+    let expected = fs::read_to_string(normal_path).unwrap();
+    let (_, normalized) = normalize(path);
+    assert_eq!(normalized, expected, "{} is not normalized", path.display());
+    let (unit, normalized) = normalize(normal_path);
+    assert_eq!(
+        normalized,
+        expected,
+        "{} is not normalized",
+        normal_path.display()
+    );
+
+    let temp_dir = tempdir().expect("temp dir creation failed");
+    let temp_file_path = temp_dir.path().join("temp.c");
+    fs::write(&temp_file_path, normalized).unwrap();
+    let new_unit = Parse
+        .translate(&temp_file_path.as_path())
+        .expect("parse failed while parsing the normalized output");
+    c::assert_ast_equiv(&unit, &new_unit);
+    temp_dir.close().expect("temp dir deletion failed");
+}
+
 /// Tests irgen.
 pub fn test_irgen(path: &Path) {
     // Check if the file has .c extension
@@ -110,6 +178,14 @@ pub fn test_irgen(path: &Path) {
     let new_c = modify_c(path, rand_num);
     modify_ir(&mut ir, rand_num);
 
+    // The tests depending on the order of evaluation are compared with their declared status.
+    if let Some(expected) = order_dependent_status(&new_c) {
+        let value = interp_main(&ir, &[]);
+        println!("declared (expected): {expected}, kecc: {value}");
+        assert_eq!(expected, value);
+        return;
+    }
+
     // compile recolved c example
     let temp_dir = tempdir().expect("temp dir creation failed");
     let temp_file_path = temp_dir.path().join("temp.c");
@@ -175,19 +251,14 @@ pub fn test_irgen(path: &Path) {
     temp_dir.close().expect("temp dir deletion failed");
 
     // Interpret resolved ir
-    let args = Vec::new();
-    let result = ir::interp(&ir, args).unwrap_or_else(|interp_error| panic!("{}", interp_error));
-    // We only allow a main function whose return type is `int`
-    let (value, width, is_signed) = result.get_int().expect("non-integer value occurs");
-    assert_eq!(width, 32);
-    assert!(is_signed);
+    let value = interp_main(&ir, &[]);
 
     // When obtaining status from `clang` executable process, the status value is truncated to byte
     // size. For this reason, we make `fuzzer` generate the C source code which returns values
     // typecasted to `unsigned char`. However, during `creduce` to reduce the code, typecasting may
-    // be nullified. So, we truncate the result value to byte size one more time here.
-    println!("clang (expected): {}, kecc: {}", status as u8, value as u8);
-    assert_eq!(status as u8, value as u8);
+    // be nullified. So, we compare the exit status of the result value here.
+    println!("clang (expected): {}, kecc: {}", status as u8, value);
+    assert_eq!(status as u8, value);
 }
 
 /// Tests irparse.
@@ -251,6 +322,165 @@ fn test_irparse_for_optimized_ir<O: Optimize<ir::TranslationUnit>>(
     optimized_ir
 }
 
+/// Tests if gcc agrees with the layout of the interface structs of an IR file.
+pub fn test_layout_asserts(path: &Path) {
+    // Check if the file has .ir extension
+    assert_eq!(path.extension(), Some(std::ffi::OsStr::new("ir")));
+    let ir = ir::Parse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    let temp_dir = tempdir().expect("temp dir creation failed");
+    let temp_file_path = temp_dir.path().join("layout.c");
+    fs::write(&temp_file_path, ir::Layout::new(&ir).static_asserts()).unwrap();
+
+    let output = Command::new("gcc")
+        .args(["-std=c11", "-fsyntax-only"])
+        .arg(&temp_file_path)
+        .output()
+        .expect("failed to execute gcc");
+    assert!(
+        output.status.success(),
+        "gcc disagrees with the layout of the structs in {}:\n{}",
+        path.display(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    temp_dir.close().expect("temp dir deletion failed");
+}
+
+/// Returns the offsets of the struct members in `ir` printed by the probe program of
+/// [`ir::Layout::offset_probe`] compiled by gcc.
+pub fn compiled_offsets(ir: &ir::TranslationUnit) -> Vec<ir::MemberOffset> {
+    let temp_dir = tempdir().expect("temp dir creation failed");
+    let temp_file_path = temp_dir.path().join("probe.c");
+    let bin_path = temp_dir.path().join("probe");
+    fs::write(&temp_file_path, ir::Layout::new(ir).offset_probe()).unwrap();
+
+    let output = Command::new("gcc")
+        .arg(&temp_file_path)
+        .arg("-o")
+        .arg(&bin_path)
+        .output()
+        .expect("failed to execute gcc");
+    assert!(
+        output.status.success(),
+        "gcc fails to compile the offset probe:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let output = Command::new(&bin_path)
+        .output()
+        .expect("failed to execute the offset probe");
+    assert!(output.status.success());
+
+    temp_dir.close().expect("temp dir deletion failed");
+    ir::MemberOffset::parse_probe_output(&String::from_utf8_lossy(&output.stdout))
+        .expect("the offset probe must print the offsets")
+}
+
+/// Returns the values of the integer globals `names` of the C file `path` compiled by gcc with
+/// plain `char` of `signedness`, or `None` if gcc is missing.
+pub fn compiled_global_values(
+    path: &Path,
+    names: &[&str],
+    signedness: CharSignedness,
+) -> Option<Vec<i128>> {
+    let temp_dir = tempdir().expect("temp dir creation failed");
+    let temp_file_path = temp_dir.path().join("probe.c");
+    let bin_path = temp_dir.path().join("probe");
+    let mut probe = format!(
+        "#include <stdio.h>\n#include \"{}\"\nint main(void) {{\n",
+        fs::canonicalize(path).unwrap().display()
+    );
+    for name in names {
+        probe.push_str(&format!("  printf(\"%lld\\n\", (long long){name});\n"));
+    }
+    probe.push_str("  return 0;\n}\n");
+    fs::write(&temp_file_path, probe).unwrap();
+
+    let output = Command::new("gcc")
+        .arg(format!("-f{signedness}-char"))
+        .arg(&temp_file_path)
+        .arg("-o")
+        .arg(&bin_path)
+        .output()
+        .ok()?;
+    assert!(
+        output.status.success(),
+        "gcc fails to compile the probe of {}:\n{}",
+        path.display(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let output = Command::new(&bin_path)
+        .output()
+        .expect("failed to execute the probe");
+    assert!(output.status.success());
+
+    temp_dir.close().expect("temp dir deletion failed");
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.parse().expect("the probe must print integers"))
+            .collect(),
+    )
+}
+
+/// Returns the symbols defined by the assembly `asm` with their types printed by nm, e.g., `T` for
+/// a global function and `t` for a local one, or `None` if the RISC-V toolchain is missing.
+pub fn assembled_symbols(asm: &str) -> Option<HashMap<String, char>> {
+    let temp_dir = tempdir().expect("temp dir creation failed");
+    let asm_path = temp_dir.path().join("symbols.s");
+    let object_path = asm_path.with_extension("o");
+    fs::write(&asm_path, asm).unwrap();
+
+    let output = Command::new("riscv64-linux-gnu-as")
+        .arg(&asm_path)
+        .arg("-o")
+        .arg(&object_path)
+        .output()
+        .ok()?;
+    assert!(
+        output.status.success(),
+        "riscv64-linux-gnu-as rejects the assembly:\n{}\n{}",
+        String::from_utf8_lossy(&output.stderr),
+        asm
+    );
+    let output = Command::new("riscv64-linux-gnu-nm")
+        .arg("--defined-only")
+        .arg(&object_path)
+        .output()
+        .ok()?;
+    assert!(output.status.success());
+
+    temp_dir.close().expect("temp dir deletion failed");
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                // E.g., `0000000000000000 T main`.
+                let mut fields = line.split_whitespace().skip(1);
+                let symbol_type = fields.next()?.chars().next()?;
+                Some((fields.next()?.to_string(), symbol_type))
+            })
+            .collect(),
+    )
+}
+
+/// Returns `unit` with `copies` copies of the function `name`, named `{name}_0`, `{name}_1`, ...,
+/// e.g., to compile a translation unit of many functions.
+pub fn replicate_function(
+    unit: &ir::TranslationUnit,
+    name: &str,
+    copies: usize,
+) -> ir::TranslationUnit {
+    let mut unit = unit.clone();
+    let decl = unit.decls[name].clone();
+    for i in 0..copies {
+        let _unused = unit.decls.insert(format!("{name}_{i}"), decl.clone());
+    }
+    unit
+}
+
 /// Tests optimizations.
 pub fn test_opt<P1: AsRef<Path>, P2: AsRef<Path>, O: Optimize<ir::TranslationUnit>>(
     from: &P1,
@@ -306,12 +536,7 @@ pub fn test_asmgen(path: &Path) {
     modify_asm(&mut asm, rand_num);
 
     // Execute IR
-    let args = Vec::new();
-    let result = ir::interp(&ir, args).unwrap_or_else(|interp_error| panic!("{}", interp_error));
-    // We only allow main function whose return type is `int`
-    let (value, width, is_signed) = result.get_int().expect("non-integer value occurs");
-    assert_eq!(width, 32);
-    assert!(is_signed);
+    let value = interp_main(&ir, &[]);
 
     let temp_dir = tempdir().expect("temp dir creation failed");
     let asm_path = temp_dir.path().join("temp.S");
@@ -373,9 +598,148 @@ pub fn test_asmgen(path: &Path) {
 
     println!(
         "kecc interp (expected): {}, qemu: {}",
-        value as u8, qemu_status as u8
+        value, qemu_status as u8
+    );
+    assert_eq!(value, qemu_status as u8);
+}
+
+/// Tests the assembly of the library `path`, compiled with `--lib`, linked with the C `harness`,
+/// whose `main` must exit with `expected`.
+pub fn test_lib_asmgen(path: &Path, harness: &Path, expected: u8) {
+    assert_eq!(path.extension(), Some(std::ffi::OsStr::new("ir")));
+    let mut ir = ir::Parse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let _ = DeadFunction::library(Vec::new()).optimize(&mut ir);
+
+    let mut asm = Asmgen::default()
+        .translate(&ir)
+        .expect("fail to create riscv assembly code");
+    asm.unit.export_functions();
+
+    let temp_dir = tempdir().expect("temp dir creation failed");
+    let asm_path = temp_dir.path().join("lib.S");
+    let bin_path = temp_dir.path().join("lib.asmgen");
+    let mut buffer = File::create(&asm_path).expect("need to success creating file");
+    write(&asm, &mut buffer).unwrap();
+
+    // Link the library with the harness
+    if !Command::new("riscv64-linux-gnu-gcc")
+        .arg("-static")
+        .arg(harness)
+        .arg(&asm_path)
+        .arg("-o")
+        .arg(&bin_path)
+        .stderr(Stdio::null())
+        .status()
+        .unwrap()
+        .success()
+    {
+        ::std::process::exit(SKIP_TEST);
+    }
+
+    let mut child = Command::new("qemu-riscv64-static")
+        .arg(&bin_path)
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to execute the compiled executable");
+    let status = some_or!(
+        child
+            .wait_timeout_ms(1000)
+            .expect("failed to obtain exit status from child process"),
+        {
+            println!("timeout occurs");
+            child.kill().unwrap();
+            let _ = child.wait().unwrap();
+            ::std::process::exit(SKIP_TEST);
+        }
     );
-    assert_eq!(value as u8, qemu_status as u8);
+    let qemu_status = some_or_exit!(status.code(), SKIP_TEST);
+    drop(buffer);
+    temp_dir.close().expect("temp dir deletion failed");
+
+    assert_eq!(expected, qemu_status as u8);
+}
+
+/// Tests if the C generated from an IR file behaves like the IR under gcc.
+pub fn test_cgen(path: &Path) {
+    test_cgen_with_stdin(path, &[]);
+}
+
+/// Tests if the C generated from an IR file behaves like the IR under gcc, both reading `stdin` as
+/// the standard input.
+pub fn test_cgen_with_stdin(path: &Path, stdin: &[u8]) {
+    // Check if the file has .ir extension
+    assert_eq!(path.extension(), Some(std::ffi::OsStr::new("ir")));
+    let ir = ir::Parse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    // Execute IR
+    let value = interp_main(&ir, stdin);
+
+    let c = Cgen::default()
+        .translate(&ir)
+        .unwrap_or_else(|cgen_error| panic!("{}", cgen_error));
+
+    let temp_dir = tempdir().expect("temp dir creation failed");
+    let c_path = temp_dir.path().join("temp.c");
+    let bin_path = c_path.with_extension("cgen");
+    fs::write(&c_path, &c).unwrap();
+
+    // Compile the C code: If gcc is missing, test is vacuously success
+    let output = some_or_exit!(
+        Command::new("gcc")
+            .args(["-std=c11", "-w"])
+            .arg(&c_path)
+            .arg("-o")
+            .arg(&bin_path)
+            .output()
+            .ok(),
+        SKIP_TEST
+    );
+    assert!(
+        output.status.success(),
+        "gcc rejects the C generated from {}:\n{}\n{}",
+        path.display(),
+        String::from_utf8_lossy(&output.stderr),
+        c
+    );
+
+    // Execute compiled executable
+    let mut child = Command::new(&bin_path)
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to execute the compiled executable");
+    // The standard input is closed once written, so that the executable reads its end.
+    child
+        .stdin
+        .take()
+        .expect("`stdin` must be piped")
+        .write_all(stdin)
+        .expect("failed to write the standard input");
+
+    let status = some_or!(
+        child
+            .wait_timeout_ms(1000)
+            .expect("failed to obtain exit status from child process"),
+        {
+            println!("timeout occurs");
+            child.kill().unwrap();
+            let _ = child.wait().unwrap();
+            ::std::process::exit(SKIP_TEST);
+        }
+    );
+
+    let gcc_status = some_or_exit!(status.code(), SKIP_TEST);
+    temp_dir.close().expect("temp dir deletion failed");
+
+    println!(
+        "kecc interp (expected): {}, gcc: {}",
+        value, gcc_status as u8
+    );
+    assert_eq!(value, gcc_status as u8);
 }
 
 /// Tests end-to-end translation.
@@ -454,22 +818,17 @@ pub fn test_end_to_end(path: &Path) {
         .translate(&unit)
         .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
     let _ = O1::default().optimize(&mut ir);
-    let args = Vec::new();
-    let result = ir::interp(&ir, args).unwrap_or_else(|interp_error| panic!("{}", interp_error));
-    // We only allow a main function whose return type is `int`
-    let (value, width, is_signed) = result.get_int().expect("non-integer value occurs");
-    assert_eq!(width, 32);
-    assert!(is_signed);
+    let value = interp_main(&ir, &[]);
 
     // When obtaining status from `clang` executable process, the status value is truncated to byte
     // size. For this reason, we make `fuzzer` generate the C source code which returns values
     // typecasted to `unsigned char`. However, during `creduce` to reduce the code, typecasting may
-    // be nullified. So, we truncate the result value to byte size one more time here.
+    // be nullified. So, we compare the exit status of the result value here.
     println!(
         "clang (expected): {}, kecc interp: {}",
-        clang_status as u8, value as u8
+        clang_status as u8, value
     );
-    assert_eq!(clang_status as u8, value as u8);
+    assert_eq!(clang_status as u8, value);
 
     // Generate RISC-V assembly from IR
     let asm = Asmgen::default()
@@ -1,7 +1,63 @@
 use itertools::izip;
 
+use core::cell::Cell;
 use core::ops::Deref;
 
+/// The default of [`max_nesting_depth`].
+pub const DEFAULT_MAX_NESTING_DEPTH: usize = 256;
+
+thread_local! {
+    /// The maximum nesting depth of the types and expressions processed by the current thread.
+    static MAX_NESTING_DEPTH: Cell<usize> = const { Cell::new(DEFAULT_MAX_NESTING_DEPTH) };
+}
+
+/// Returns the maximum nesting depth of the types and expressions the current thread processes.
+///
+/// Deeper types and expressions, e.g., generated by a fuzzer, are reported as too complex instead
+/// of overflowing the stack of the recursive algorithms processing them.
+pub fn max_nesting_depth() -> usize {
+    MAX_NESTING_DEPTH.with(Cell::get)
+}
+
+/// Sets the maximum nesting depth of the types and expressions the current thread processes.
+pub fn set_max_nesting_depth(depth: usize) {
+    MAX_NESTING_DEPTH.with(|cell| cell.set(depth));
+}
+
+/// Returns `f` applied to each of `items`, in the order of `items`.
+///
+/// With the `parallel` feature, the items are processed on the threads of the current rayon
+/// thread pool, e.g., the function definitions of a translation unit, which are independent after
+/// irgen. The results are still in order, so that the output does not depend on the scheduling.
+/// The threads follow the [`TargetData`](crate::TargetData) and the [`max_nesting_depth`] of the
+/// calling thread.
+pub fn parallel_map<I, R, F>(items: Vec<I>, f: F) -> Vec<R>
+where
+    I: Send,
+    R: Send,
+    F: Fn(I) -> R + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        let target_data = crate::target_data();
+        let max_nesting_depth = max_nesting_depth();
+        items
+            .into_par_iter()
+            .map(|item| {
+                crate::set_target_data(target_data);
+                set_max_nesting_depth(max_nesting_depth);
+                f(item)
+            })
+            .collect()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.into_iter().map(f).collect()
+    }
+}
+
 #[macro_export]
 /// Ok or executing the given expression.
 macro_rules! ok_or {
@@ -61,6 +117,12 @@ pub trait Translate<S> {
     fn translate(&mut self, source: &S) -> Result<Self::Target, Self::Error>;
 }
 
+/// A pass transforming `T` in place, e.g., an IR optimization or an assembly rewrite, returning
+/// `true` if it changed `T`.
+pub trait Optimize<T> {
+    fn optimize(&mut self, code: &mut T) -> bool;
+}
+
 /// TODO(document)
 pub trait AssertSupported {
     /// TODO(document)
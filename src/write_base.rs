@@ -1,4 +1,93 @@
-use std::io::{Result, Write};
+use core::cell::RefCell;
+use core::fmt;
+use std::io::{self, Result, Write};
+
+use lang_c::span::Span;
+use thiserror::Error;
+
+thread_local! {
+    /// The first unsupported construct found since the innermost `try_write*` call being run
+    /// started, or `None` outside such calls.
+    static UNSUPPORTED: RefCell<Option<Option<UnsupportedConstruct>>> =
+        const { RefCell::new(None) };
+}
+
+/// What a writer does on a construct it cannot write yet.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OnUnsupported {
+    /// The write fails with [`WriteError::Unsupported`].
+    #[default]
+    Fail,
+    /// The construct is written as a `/* UNSUPPORTED: construct */` comment, so that the rest of
+    /// the output is still written, e.g., for reducing a test case or reporting a crash.
+    Placeholder,
+}
+
+/// Options of [`WriteString::try_write_string`] and [`WriteLine::try_write_line`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WriteOpts {
+    pub on_unsupported: OnUnsupported,
+}
+
+/// A construct a writer cannot write yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedConstruct {
+    /// The description of the construct, e.g., `string literal`
+    pub construct: &'static str,
+    /// The span of the construct in the source, if it has one
+    pub span: Option<Span>,
+}
+
+impl fmt::Display for UnsupportedConstruct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unsupported construct: {}", self.construct)?;
+        if let Some(span) = self.span {
+            write!(f, " at {}..{}", span.start, span.end)?;
+        }
+        Ok(())
+    }
+}
+
+/// An error in writing with [`WriteOpts`].
+#[derive(Debug, Error)]
+pub enum WriteError {
+    #[error("{0}")]
+    Unsupported(UnsupportedConstruct),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Returns the placeholder of the unsupported construct `construct` at `span`, recording it for
+/// the innermost `try_write*` call.
+///
+/// # Panics
+///
+/// Panics outside `try_write*` calls, as the writers did before they had options.
+pub(crate) fn unsupported(construct: &'static str, span: Option<Span>) -> String {
+    UNSUPPORTED.with(|state| {
+        let mut state = state.borrow_mut();
+        let found = state
+            .as_mut()
+            .unwrap_or_else(|| panic!("not yet implemented: {construct}"));
+        let _unused = found.get_or_insert(UnsupportedConstruct { construct, span });
+    });
+    format!("/* UNSUPPORTED: {construct} */")
+}
+
+/// Runs `f` recording the unsupported constructs it writes, and fails on the first one unless
+/// `opts` asks for placeholders.
+fn with_opts<R>(opts: &WriteOpts, f: impl FnOnce() -> R) -> core::result::Result<R, WriteError> {
+    let outer = UNSUPPORTED.with(|state| state.replace(Some(None)));
+    let result = f();
+    let found = UNSUPPORTED.with(|state| state.replace(outer));
+
+    match found {
+        Some(Some(unsupported)) if opts.on_unsupported == OnUnsupported::Fail => {
+            Err(WriteError::Unsupported(unsupported))
+        }
+        _ => Ok(result),
+    }
+}
 
 /// TODO(document)
 #[inline]
@@ -10,12 +99,30 @@ pub fn write_indent(indent: usize, write: &mut dyn Write) -> Result<()> {
 pub trait WriteLine {
     /// TODO(document)
     fn write_line(&self, indent: usize, write: &mut dyn Write) -> Result<()>;
+
+    /// Writes `self` like [`WriteLine::write_line`], handling the unsupported constructs by
+    /// `opts` instead of panicking.
+    fn try_write_line(
+        &self,
+        indent: usize,
+        write: &mut dyn Write,
+        opts: &WriteOpts,
+    ) -> core::result::Result<(), WriteError> {
+        with_opts(opts, || self.write_line(indent, write))??;
+        Ok(())
+    }
 }
 
 /// TODO(document)
 pub trait WriteString {
     /// TODO(document)
     fn write_string(&self) -> String;
+
+    /// Writes `self` like [`WriteString::write_string`], handling the unsupported constructs by
+    /// `opts` instead of panicking.
+    fn try_write_string(&self, opts: &WriteOpts) -> core::result::Result<String, WriteError> {
+        with_opts(opts, || self.write_string())
+    }
 }
 
 /// TODO(document)
@@ -28,3 +135,12 @@ pub trait WriteOp {
 pub fn write<T: WriteLine>(t: &T, write: &mut dyn Write) -> Result<()> {
     t.write_line(0, write)
 }
+
+/// Writes `t` like [`write`], handling the constructs the writer does not support by `opts`.
+pub fn try_write<T: WriteLine>(
+    t: &T,
+    write: &mut dyn Write,
+    opts: &WriteOpts,
+) -> core::result::Result<(), WriteError> {
+    t.try_write_line(0, write, opts)
+}
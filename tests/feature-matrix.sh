@@ -0,0 +1,32 @@
+#!/usr/bin/env bash
+
+# Checks that the crate compiles under each combination of the cargo features gating its
+# components, from the C parser and the IR alone to the whole pipeline, and runs the test suite
+# with all of them.
+#
+# Usage: tests/feature-matrix.sh
+
+# Exit when any command fails.
+set -e
+
+COMPONENTS=(irgen opt interp asmgen-riscv)
+
+# Every subset of the components, by the bits of `mask`.
+for ((mask = 0; mask < 1 << ${#COMPONENTS[@]}; mask++)); do
+    features=()
+    for i in "${!COMPONENTS[@]}"; do
+        if ((mask & 1 << i)); then
+            features+=("${COMPONENTS[$i]}")
+        fi
+    done
+    features=$(IFS=,; echo "${features[*]}")
+    echo "features: ${features:-(none)}"
+    cargo check --lib --no-default-features --features "$features"
+done
+
+echo "features: testing"
+cargo check --all-targets --no-default-features --features testing
+echo "features: build-bin,parallel"
+cargo check --all-targets --features build-bin,parallel
+
+RUST_MIN_STACK=33554432 cargo test --release
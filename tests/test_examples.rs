@@ -1,8 +1,14 @@
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
 use std::path::Path;
+use std::rc::Rc;
 
 use kecc::*;
 
+#[path = "../examples/custom_pass/pass.rs"]
+mod custom_pass;
+
 fn test_dir<F>(path: &Path, ext: &OsStr, f: F)
 where
     F: Fn(&Path),
@@ -46,12 +52,13 @@ fn test_opt_between_dirs<O: Optimize<ir::TranslationUnit>>(from: &Path, to: &Pat
     }
 }
 
-const IRGEN_SMALL_TEST_IGNORE_LIST: [&str; 12] = [
+const IRGEN_SMALL_TEST_IGNORE_LIST: [&str; 13] = [
     "examples/c/array.c",
     "examples/c/array2.c",
     "examples/c/array3.c",
     "examples/c/array4.c",
     "examples/c/array5.c",
+    "examples/c/bitfield.c",
     "examples/c/float.c",
     "examples/c/sizeof2.c",
     "examples/c/struct.c",
@@ -89,6 +96,14 @@ fn test_examples_write_c() {
     test_dir(Path::new("examples/c"), OsStr::new("c"), test_write_c);
 }
 
+#[test]
+fn test_examples_normalize() {
+    // Differently formatted sources of the same program are normalized to the same bytes.
+    let normal_path = Path::new("examples/normalize/normal.c");
+    test_normalize(Path::new("examples/normalize/compact.c"), normal_path);
+    test_normalize(Path::new("examples/normalize/verbose.c"), normal_path);
+}
+
 #[test]
 fn test_examples_irgen_small() {
     test_dir(Path::new("examples/c"), OsStr::new("c"), |path| {
@@ -114,6 +129,108 @@ fn test_examples_irparse() {
     test_dir(Path::new("examples/c"), OsStr::new("c"), test_irparse);
 }
 
+#[test]
+fn test_float_constants_round_trip() {
+    let returned = |ir: &ir::TranslationUnit, name: &str| {
+        let (_, definition) = ir.decls[name].get_function().unwrap();
+        let block = &definition.as_ref().unwrap().blocks[&ir::BlockId(0)];
+        if let ir::BlockExit::Return { value } = &block.exit {
+            value.get_constant().and_then(ir::Constant::get_float_bits)
+        } else {
+            panic!("`{name}` must return")
+        }
+    };
+
+    // Both forms are accepted, but the bit pattern is authoritative over the comment.
+    let path = Path::new("examples/float_bits/constants.ir");
+    let ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    assert_eq!(returned(&ir, "tenth_decimal"), Some(0x3DCC_CCCD));
+    assert_eq!(returned(&ir, "tenth_hex"), Some(0x3DCC_CCCD));
+    assert_eq!(returned(&ir, "hex_is_authoritative"), Some(1f64.to_bits()));
+
+    // The interpreter keeps the payload of a NaN through the memory.
+    let value = ir::interp(&ir, Vec::new()).unwrap();
+    let (value, width) = if let ir::Value::Float { value, width } = value {
+        (value.into_inner(), width)
+    } else {
+        panic!("`main` must return a float")
+    };
+    assert_eq!(ir::float_value_to_bits(value, width), 0x7FC0_0001);
+
+    // Awkward floats are written and read back bit-exactly, and written the same way again.
+    let awkward = [
+        (
+            ir::Dtype::FLOAT,
+            vec![
+                0x3DCC_CCCD, // 0.1
+                0x8000_0000, // -0.0
+                0x7FC0_0000, // NaN
+                0xFFC0_0001, // negative NaN with a payload
+                0x7F80_0001, // signaling NaN
+                0x7F80_0000, // infinity
+                0xFF80_0000, // negative infinity
+                0x0000_0001, // the smallest subnormal
+                0x7F7F_FFFF, // the largest finite
+            ],
+        ),
+        (
+            ir::Dtype::DOUBLE,
+            vec![
+                0x3FB9_9999_9999_999A, // 0.1
+                0x8000_0000_0000_0000, // -0.0
+                0x7FF8_0000_0000_0000, // NaN
+                0xFFF8_0000_0000_0001, // negative NaN with a payload
+                0x7FF0_0000_0000_0001, // signaling NaN
+                0x7FF0_0000_0000_0000, // infinity
+                0x0000_0000_0000_0001, // the smallest subnormal
+                0x7FEF_FFFF_FFFF_FFFF, // the largest finite
+                0x7E37_E43C_8800_759C, // 1e300
+            ],
+        ),
+    ];
+    let constants = awkward
+        .iter()
+        .flat_map(|(dtype, bits)| {
+            bits.iter()
+                .map(move |bits| (*bits, ir::Constant::float_from_bits(*bits, dtype.clone())))
+        })
+        .collect::<Vec<_>>();
+    let source = constants
+        .iter()
+        .enumerate()
+        .map(|(i, (_, constant))| {
+            let operand = ir::Operand::constant(constant.clone());
+            format!(
+                "fun {} @c{i} () {{\ninit:\n  bid: b0\n  allocations: \n\n\nblock b0:\n  ret {operand}\n}}\n",
+                operand.dtype(),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let dir = tempfile::tempdir().expect("temp dir creation failed");
+    let compile = |source: &[u8], name: &str| {
+        let path = dir.path().join(name);
+        std::fs::write(&path, source).unwrap();
+        let ir = IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+        let mut written = Vec::new();
+        write(&ir, &mut written).unwrap();
+        (ir, written)
+    };
+    let (ir, written) = compile(source.as_bytes(), "source.ir");
+    let (ir_again, written_again) = compile(&written, "written.ir");
+    for (i, (bits, constant)) in constants.iter().enumerate() {
+        assert_eq!(constant.get_float_bits(), Some(*bits));
+        assert_eq!(returned(&ir, &format!("c{i}")), Some(*bits));
+        assert_eq!(returned(&ir_again, &format!("c{i}")), Some(*bits));
+    }
+    assert_eq!(written, written_again);
+}
+
 #[test]
 fn test_examples_simplify_cfg() {
     test_opt(
@@ -147,6 +264,124 @@ fn test_examples_simplify_cfg() {
     );
 }
 
+/// Appends a `nop` to the initial block unless it already ends with one.
+#[derive(Default, Clone)]
+struct AppendNop;
+
+impl Optimize<ir::FunctionDefinition> for AppendNop {
+    fn optimize(&mut self, code: &mut ir::FunctionDefinition) -> bool {
+        let block = code.blocks.get_mut(&code.bid_init).unwrap();
+        if ends_with_nop(block) {
+            return false;
+        }
+        block
+            .instructions
+            .push(ir::Named::new(None, ir::Instruction::Nop));
+        true
+    }
+}
+
+/// Removes the `nop` the initial block ends with, undoing [`AppendNop`].
+#[derive(Default, Clone)]
+struct RemoveNop;
+
+impl Optimize<ir::FunctionDefinition> for RemoveNop {
+    fn optimize(&mut self, code: &mut ir::FunctionDefinition) -> bool {
+        let block = code.blocks.get_mut(&code.bid_init).unwrap();
+        if !ends_with_nop(block) {
+            return false;
+        }
+        let _unused = block.instructions.pop();
+        true
+    }
+}
+
+/// Appends a `nop` to the initial block every time.
+#[derive(Default)]
+struct GrowNop;
+
+impl Optimize<ir::FunctionDefinition> for GrowNop {
+    fn optimize(&mut self, code: &mut ir::FunctionDefinition) -> bool {
+        let block = code.blocks.get_mut(&code.bid_init).unwrap();
+        block
+            .instructions
+            .push(ir::Named::new(None, ir::Instruction::Nop));
+        true
+    }
+}
+
+fn ends_with_nop(block: &ir::Block) -> bool {
+    block
+        .instructions
+        .last()
+        .is_some_and(|instruction| matches!(**instruction, ir::Instruction::Nop))
+}
+
+#[test]
+fn test_examples_optimizer_budget() {
+    let parse = |path: &str| {
+        IrParse::default()
+            .translate(&Path::new(path))
+            .unwrap_or_else(|_| panic!("parse failed {path}"))
+    };
+
+    // The passes converging on their own never exceed the default budgets.
+    test_dir(Path::new("examples/ir0"), OsStr::new("ir"), |path| {
+        let mut ir = IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+        let _ = Repeat::<(SimplifyArith, (Sink, TailMerge))>::default().optimize(&mut ir);
+        assert_eq!(ir::verify(&ir), Ok(()), "{}", path.display());
+    });
+    assert_eq!(take_budget_diagnostics(), []);
+
+    // Two passes undoing each other are stopped as soon as the IR repeats, leaving valid IR.
+    let mut ir = parse("examples/ir0/fibonacci.ir");
+    let mut oscillating = FunctionPass::new(Repeat::<(AppendNop, RemoveNop)>::default());
+    assert!(oscillating.optimize(&mut ir));
+    assert_eq!(ir::verify(&ir), Ok(()));
+    let diagnostics = take_budget_diagnostics();
+    assert!(!diagnostics.is_empty());
+    for diagnostic in diagnostics {
+        assert_eq!(
+            diagnostic,
+            BudgetDiagnostic::Oscillation {
+                passes: "(AppendNop, RemoveNop)".to_string(),
+                period: 1,
+            }
+        );
+        assert_eq!(
+            diagnostic.to_string(),
+            "warning: passes `(AppendNop, RemoveNop)` oscillate, repeating the IR every 1 \
+             iteration(s); stopped repeating them"
+        );
+    }
+
+    // The budgets exceeded by the functions optimized on behalf of this thread, e.g., on the
+    // threads of the `parallel` feature, are reported to it.
+    let mut ir = parse("examples/ir0/fibonacci.ir");
+    let mut oscillating = ParallelFunctionPass::new(Repeat::<(AppendNop, RemoveNop)>::default());
+    assert!(oscillating.optimize(&mut ir));
+    let diagnostics = take_budget_diagnostics();
+    assert!(!diagnostics.is_empty());
+    assert!(diagnostics
+        .iter()
+        .all(|diagnostic| matches!(diagnostic, BudgetDiagnostic::Oscillation { period: 1, .. })));
+
+    // A pass growing the IR forever is stopped by the growth limit.
+    let mut ir = parse("examples/ir0/fibonacci.ir");
+    let size = ir.size().max(OptimizerBudget::GROWTH_FLOOR);
+    let _ = Repeat::<FunctionPass<GrowNop>>::default().optimize(&mut ir);
+    assert!(ir.size() * 100 > size * OptimizerBudget::DEFAULT_MAX_GROWTH_PERCENT);
+    assert_eq!(
+        take_budget_diagnostics(),
+        [BudgetDiagnostic::GrowthLimit {
+            passes: "FunctionPass<GrowNop>".to_string(),
+            percent: OptimizerBudget::DEFAULT_MAX_GROWTH_PERCENT,
+        }]
+    );
+}
+
 #[test]
 fn test_examples_mem2reg() {
     test_opt(
@@ -162,6 +397,63 @@ fn test_examples_mem2reg() {
     );
 }
 
+#[test]
+fn test_examples_forward_pointers() {
+    test_opt(
+        &Path::new("examples/forward_pointers/forward.input.ir"),
+        &Path::new("examples/forward_pointers/forward.output.ir"),
+        &mut ForwardPointers::default(),
+    );
+
+    let parse = |path: &str| {
+        IrParse::default()
+            .translate(&Path::new(path))
+            .unwrap_or_else(|_| panic!("parse failed {path}"))
+    };
+    let input = parse("examples/forward_pointers/forward.input.ir");
+    let mut output = parse("examples/forward_pointers/forward.output.ir");
+    assert_eq!(ir::verify(&output), Ok(()));
+    let result = ir::interp(&output, Vec::new()).unwrap();
+    assert_eq!(result.get_int(), Some((14, 32, true)));
+    assert_eq!(ir::interp(&input, Vec::new()), Ok(result));
+
+    // `p` points to `x` on both paths of `@consistent`, but to `x` or `y` in `@different`, and the
+    // pointers loaded from it are offset in `@arith` and passed to a call in `@escape`.
+    let pointees = |name: &str| {
+        let (_, definition) = input.decls[name].get_function().unwrap();
+        let pointees = LocalPointees::new(definition.as_ref().unwrap());
+        pointees.pointees().clone().into_iter().collect::<Vec<_>>()
+    };
+    assert_eq!(pointees("single"), [(1, 0)]);
+    assert_eq!(pointees("consistent"), [(1, 0)]);
+    for name in ["different", "arith", "escape"] {
+        assert!(pointees(name).is_empty(), "{name}");
+    }
+    assert!(!ForwardPointers::default().optimize(&mut output));
+
+    // Forwarding preserves the behavior of the programs.
+    let run = |ir: &ir::TranslationUnit| {
+        ir::interp_bounded(ir, Vec::new(), CompileOptions::default(), 1_000_000)
+    };
+    for dir in ["examples/ir0", "examples/opt"] {
+        test_dir(Path::new(dir), OsStr::new("ir"), |path| {
+            let ir = IrParse::default()
+                .translate(&path)
+                .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+            let expected = some_or!(ok_or!(run(&ir), return), return);
+
+            let mut forwarded = ir.clone();
+            let _ = ForwardPointers::default().optimize(&mut forwarded);
+            assert_eq!(ir::verify(&forwarded), Ok(()), "{}", path.display());
+            let actual = run(&forwarded)
+                .unwrap()
+                .unwrap_or_else(|| panic!("{} diverges", path.display()));
+            assert_eq!(actual.value, expected.value);
+            assert_eq!(actual.globals, expected.globals);
+        });
+    }
+}
+
 #[test]
 fn test_examples_deadcode() {
     test_opt(
@@ -233,7 +525,4879 @@ fn test_examples_asmgen_large() {
     }
 }
 
+#[test]
+fn test_examples_cgen() {
+    for dir in ASMGEN_TEST_DIR_LIST.iter() {
+        test_dir(Path::new(dir), OsStr::new("ir"), test_cgen);
+    }
+    for dir in [
+        "examples/opt",
+        "examples/exit_status",
+        "examples/shift",
+        "examples/division",
+    ] {
+        test_dir(Path::new(dir), OsStr::new("ir"), test_cgen);
+    }
+}
+
+#[test]
+fn test_examples_exit_status() {
+    // The interpreter returns the full value of `main`, and the runs compared to it only observe
+    // its low 8 bits, e.g., by `test_examples_cgen`.
+    for (name, value, exit_status) in [
+        ("negative", -1i128, 255),
+        ("wrap", 256, 0),
+        ("truncate", 300, 44),
+        ("store_width", 15, 15),
+    ] {
+        let path = Path::new("examples/exit_status").join(format!("{name}.ir"));
+        let ir = IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+        let result = ir::interp(&ir, Vec::new()).unwrap();
+        assert_eq!(result.get_int(), Some((value as u128, 32, true)));
+        assert_eq!(result.exit_status(), Some(exit_status));
+    }
+
+    let unit = ir::Value::Unit;
+    assert_eq!(unit.exit_status(), None);
+}
+
+#[test]
+fn test_examples_store_width() {
+    // A narrowing store needs an explicit `typecast`, instead of writing the bytes of the value.
+    let path = Path::new("examples/store_width/wide_store.ir");
+    let error = IrParse::default().translate(&path).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "8: main:b0:0 / store of `i32` into `i8` needs an explicit `typecast`"
+    );
+    let ir = IrParse::default()
+        .skip_verifier()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let error = ir::verify(&ir).unwrap_err();
+    assert!(matches!(error, ir::VerifierError::StoreWidth { .. }));
+    assert_eq!(
+        error.to_string(),
+        "main:b0:0 / store of `i32` into `i8` needs an explicit `typecast`"
+    );
+    assert!(matches!(
+        ir::interp(&ir, Vec::new()),
+        Err(ir::InterpreterError::Misc { .. })
+    ));
+
+    // The value stored into a `char` or a `short` reads back truncated, sign- or zero-extended
+    // by the signedness of the load.
+    let path = Path::new("examples/exit_status/store_width.ir");
+    let ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    assert_eq!(ir::verify(&ir), Ok(()));
+    test_cgen(path);
+}
+
+#[test]
+fn test_examples_broken_ir() {
+    // All the mistakes of each file are reported with their lines, as in the `.err` file.
+    test_dir(Path::new("examples/broken_ir"), OsStr::new("ir"), |path| {
+        let expected = std::fs::read_to_string(path.with_extension("err")).unwrap();
+        let error = IrParse::default().translate(&path).unwrap_err();
+        assert!(matches!(error, ir::ParseError::Invalid(_)));
+        assert_eq!(error.to_string(), expected.trim_end(), "{}", path.display());
+    });
+
+    // The mechanical mistakes are fixed instead, and the fixed IR parses as is.
+    for (name, fixed) in [
+        ("duplicate_block", "block b2:"),
+        ("missing_exit", "<unreachable>"),
+    ] {
+        let path = Path::new("examples/broken_ir").join(format!("{name}.ir"));
+        let expected = std::fs::read_to_string(path.with_extension("err")).unwrap();
+        let (ir, fixes) = IrParse::default()
+            .fix()
+            .translate_with_fixes(&path)
+            .unwrap_or_else(|error| panic!("{error}"));
+        let fixes = fixes.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(fixes, expected.lines().collect::<Vec<_>>());
+        assert_eq!(ir::verify(&ir), Ok(()));
+
+        let mut buffer = Vec::new();
+        write(&ir, &mut buffer).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        assert!(text.contains(fixed), "{text}");
+        let temp_dir = tempfile::tempdir().expect("temp dir creation failed");
+        let temp_file_path = temp_dir.path().join(format!("{name}.ir"));
+        std::fs::write(&temp_file_path, text).unwrap();
+        let reparsed = IrParse::default()
+            .translate(&temp_file_path)
+            .unwrap_or_else(|error| panic!("{error}"));
+        assert_eq!(reparsed.decls["main"], ir.decls["main"]);
+        temp_dir.close().expect("temp dir deletion failed");
+    }
+
+    // Only the mechanical mistakes are fixed.
+    let path = Path::new("examples/broken_ir/jump_args.ir");
+    assert!(IrParse::default().fix().translate(&path).is_err());
+}
+
+#[test]
+fn test_examples_stdin() {
+    let run = |name: &str, stdin: &[u8]| {
+        let path = Path::new("examples/stdin").join(format!("{name}.ir"));
+        let ir = IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+        let bindings = ir::Bindings::default().stdin(std::io::Cursor::new(stdin.to_vec()));
+        ir::interp_with_bindings(&ir, Vec::new(), CompileOptions::default(), bindings)
+    };
+    let int = |value: i128| ir::Value::Int {
+        value: value as u128,
+        width: 32,
+        is_signed: true,
+    };
+
+    // `scanf` skips whitespace, stops at a matching failure, and returns `EOF` at the end of the
+    // input before the first conversion.
+    for (stdin, sum) in [
+        (&b"3\n10 20\n-5\n"[..], 25),
+        (b"  4 +1\t2", 3),
+        (b"2 7 x 8", 7),
+        (b"", -1),
+        (b"x", 0),
+    ] {
+        assert_eq!(run("sum", stdin), Ok(int(sum)));
+        test_cgen_with_stdin(Path::new("examples/stdin/sum.ir"), stdin);
+    }
+
+    // `getchar` returns `EOF` at the end of the input, and so does `scanf` afterwards.
+    for (stdin, lines) in [(&b"a\nbb\n\nccc"[..], 3), (b"", 0), (b"\n", 1)] {
+        assert_eq!(run("lines", stdin), Ok(int(lines * 2 + 1)));
+        test_cgen_with_stdin(Path::new("examples/stdin/lines.ir"), stdin);
+    }
+
+    // `%s` stores a null terminator, and fails beyond the array it stores into.
+    assert_eq!(run("word", b" abc def"), Ok(int(i128::from(b'c') + 1)));
+    test_cgen_with_stdin(Path::new("examples/stdin/word.ir"), b" abc def");
+    assert!(matches!(
+        run("word", b"abcd"),
+        Err(ir::InterpreterError::Misc { msg, .. }) if msg.contains("out of bounds")
+    ));
+}
+
 #[test]
 fn test_examples_end_to_end() {
     test_dir(Path::new("examples/c"), OsStr::new("c"), test_end_to_end);
 }
+
+/// Runs the conformance suite checked out at `KECC_CONFORMANCE_DIR`, if any, failing on the tests
+/// failing at an earlier stage than `examples/conformance/manifest.txt` records. The assembly is
+/// run on QEMU if `KECC_CONFORMANCE_QEMU` is set, and the manifest is regenerated instead if
+/// `KECC_CONFORMANCE_UPDATE` is set.
+#[test]
+fn test_conformance() {
+    let dir = some_or!(std::env::var_os("KECC_CONFORMANCE_DIR"), return);
+    let checks_asm = std::env::var_os("KECC_CONFORMANCE_QEMU").is_some();
+    let manifest_path = Path::new("examples/conformance/manifest.txt");
+    let manifest = Manifest::parse(&std::fs::read_to_string(manifest_path).unwrap())
+        .unwrap_or_else(|error| panic!("{}: {error}", manifest_path.display()));
+
+    let results = run_conformance_suite(Path::new(&dir), checks_asm).unwrap();
+    if std::env::var_os("KECC_CONFORMANCE_UPDATE").is_some() {
+        let updated = manifest.updated(&results, checks_asm);
+        std::fs::write(manifest_path, updated.to_string()).unwrap();
+        return;
+    }
+
+    let report = ConformanceReport::new(&manifest, &results, checks_asm);
+    print!("{report}");
+    assert!(
+        !report.has_regressions(),
+        "conformance tests regressed:\n{report}"
+    );
+}
+
+#[test]
+fn test_conformance_manifest() {
+    use ConformanceStatus::*;
+
+    let manifest = Manifest::parse(
+        "# comment\n\
+         b.c xfail-asm\n\
+         a.c pass\n\
+         c.c xfail-parse\n\
+         gone.c pass\n",
+    )
+    .unwrap();
+    assert_eq!(
+        Manifest::parse("a.c pass\na.c pass\n"),
+        Err(ManifestError::Duplicate {
+            line: 2,
+            name: "a.c".to_string()
+        })
+    );
+    assert_eq!(
+        Manifest::parse("a.c passes\n"),
+        Err(ManifestError::UnknownStatus {
+            line: 1,
+            status: "passes".to_string()
+        })
+    );
+
+    let results = [
+        ("a.c", XfailIrgen),
+        ("b.c", Pass),
+        ("c.c", XfailIrgen),
+        ("new.c", Pass),
+    ]
+    .into_iter()
+    .map(|(name, status)| (name.to_string(), status))
+    .collect();
+
+    // Without QEMU, passing the interpreter does not tell whether `b.c` passes.
+    let report = ConformanceReport::new(&manifest, &results, false);
+    let describe = |changes: &[StatusChange]| {
+        changes
+            .iter()
+            .map(|change| change.to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        describe(&report.newly_passing),
+        ["c.c (xfail-parse -> xfail-irgen)"]
+    );
+    assert_eq!(
+        describe(&report.newly_failing),
+        ["a.c (pass -> xfail-irgen)"]
+    );
+    assert_eq!(report.untracked, [("new.c".to_string(), Pass)]);
+    assert_eq!(report.missing, ["gone.c"]);
+    assert!(report.has_regressions());
+    let report = ConformanceReport::new(&manifest, &results, true);
+    assert_eq!(
+        describe(&report.newly_passing),
+        [
+            "b.c (xfail-asm -> pass)",
+            "c.c (xfail-parse -> xfail-irgen)"
+        ]
+    );
+
+    // The regenerated manifest is sorted, and parses back to itself.
+    let updated = manifest.updated(&results, false);
+    assert_eq!(
+        updated
+            .to_string()
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect::<Vec<_>>(),
+        [
+            "a.c xfail-irgen",
+            "b.c xfail-asm",
+            "c.c xfail-irgen",
+            "new.c pass"
+        ]
+    );
+    assert_eq!(Manifest::parse(&updated.to_string()), Ok(updated.clone()));
+    assert_eq!(manifest.updated(&results, false), updated);
+}
+
+#[test]
+fn test_examples_irgen_keep_going() {
+    let path = Path::new("examples/keep_going/partial.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    // Each failing declaration is reported, and the declarations after it are still translated.
+    let (ir, errors) = Irgen::default().translate_keep_going(&unit);
+    let messages = errors
+        .into_iter()
+        .map(|error| match error.message {
+            IrgenErrorMessage::NotConstantExpression { .. } => "not constant".to_string(),
+            IrgenErrorMessage::InvalidDtype {
+                dtype_error: ir::DtypeError::IncompleteType { usage, .. },
+            } => usage,
+            message => message.to_string(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            "static assertion failed: \"long is 4 bytes\"",
+            "variable `m`",
+            "not constant",
+        ]
+    );
+    for name in ["g", "last"] {
+        assert!(ir.decls.contains_key(name), "`{name}` must be declared");
+    }
+
+    // A bad function is reported alone, and the declarations after it are still translated. The
+    // body of `get` is not lowered by this IR generator yet, which is reported as an error of its
+    // own, so `get` is only declared.
+    let path = Path::new("examples/keep_going/unsupported.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let (ir, errors) = Irgen::default().translate_keep_going(&unit);
+    assert_eq!(errors.len(), 2);
+    assert!(matches!(
+        &errors[0].message,
+        IrgenErrorMessage::InvalidDtype {
+            dtype_error: ir::DtypeError::IncompleteType { usage, .. },
+        } if usage == "the return type of `bad`"
+    ));
+    assert!(matches!(errors[1].message, IrgenErrorMessage::Misc { .. }));
+    assert!(!ir.decls.contains_key("bad"));
+    let (_, definition) = ir.decls["get"]
+        .get_function()
+        .expect("`get` must be a function");
+    assert!(definition.is_none());
+    for name in ["before", "after"] {
+        let (_, initializer) = ir.decls[name]
+            .get_variable()
+            .unwrap_or_else(|| panic!("`{name}` must be a variable"));
+        assert!(initializer.is_some(), "`{name}` must be initialized");
+    }
+}
+
+#[test]
+fn test_examples_zero_init() {
+    let path = Path::new("examples/zeroinit/zeroinit.ir");
+    let ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    assert!(ir.decls["big"].is_zero_initialized());
+    assert!(ir.decls["partial"].is_zero_initialized());
+    assert!(!ir.decls["tail"].is_zero_initialized());
+
+    // The printed IR keeps a single `zeroinit` constant instead of materializing each element.
+    let mut buffer = Vec::new();
+    write(&ir, &mut buffer).unwrap();
+    let printed = String::from_utf8(buffer).unwrap();
+    assert!(printed.contains("store zeroinit:[4 x i32] %l0:[4 x i32]*"));
+    assert!(printed.len() < 1024);
+
+    let result = ir::interp(&ir, Vec::new()).unwrap_or_else(|e| panic!("{}", e));
+    let (value, _, _) = result.get_int().expect("non-integer value occurs");
+    assert_eq!(value, 7);
+
+    // The all-zero aggregates are folded to `ZeroInit`, written as `default`, as well as the zero
+    // tail of a partial initializer, while `-0.0` is not a zero value.
+    let path = "examples/zeroinit/zeroinit.c";
+    let parse = lang_c::driver::parse(&lang_c::driver::Config::default(), path)
+        .unwrap_or_else(|_| panic!("parse failed {path}"));
+    let ir = Irgen::default()
+        .translate(&parse.unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+    let mut buffer = Vec::new();
+    write(&ir, &mut buffer).unwrap();
+    let printed = String::from_utf8(buffer).unwrap();
+    assert!(printed.contains("var static [100000 x i32] @big = default"));
+    assert!(printed.contains("var [4 x i32] @zeros = default"));
+    assert!(printed.len() < 1024);
+    for name in ["big", "zeros", "origin"] {
+        assert!(ir.decls[name].is_zero_initialized(), "{name}");
+    }
+    for name in ["tail", "p", "negative_zero"] {
+        assert!(!ir.decls[name].is_zero_initialized(), "{name}");
+    }
+
+    let global = |name: &str| {
+        asm::Section::global(asm::Label(name.to_string()), &ir.decls[name], &ir.structs)
+            .unwrap_or_else(|| panic!("`{name}` has no data directives"))
+    };
+    let big = global("big");
+    assert!(big
+        .header
+        .contains(&asm::Directive::Section(asm::SectionType::Bss)));
+    assert!(!big
+        .header
+        .contains(&asm::Directive::Globl(asm::Label("big".to_string()))));
+    assert_eq!(big.body.directives, vec![asm::Directive::Zero(400_000)]);
+    assert!(global("origin")
+        .header
+        .contains(&asm::Directive::Section(asm::SectionType::Bss)));
+
+    let data = |name: &str| {
+        let section = global(name);
+        assert!(section
+            .header
+            .contains(&asm::Directive::Section(asm::SectionType::Data)));
+        section.body.directives
+    };
+    assert_eq!(
+        data("tail"),
+        vec![asm::Directive::Word(1), asm::Directive::Zero(12)]
+    );
+    assert_eq!(
+        data("p"),
+        vec![
+            asm::Directive::Byte(1),
+            asm::Directive::Zero(7),
+            asm::Directive::Quad(2)
+        ]
+    );
+    assert_eq!(
+        data("negative_zero"),
+        vec![asm::Directive::Quad((-0.0f64).to_bits())]
+    );
+}
+
+#[test]
+fn test_examples_overflow_policy() {
+    let path = Path::new("examples/overflow/overflow.ir");
+    let ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    let run = |ir: &ir::TranslationUnit, overflow_policy| {
+        let options = CompileOptions {
+            overflow_policy,
+            ..Default::default()
+        };
+        ir::interp_with_options(ir, Vec::new(), options).map(|result| {
+            let (value, _, _) = result.get_int().expect("non-integer value occurs");
+            value
+        })
+    };
+
+    // `x + 1 > x` is false when `x + 1` wraps around, and the interpreter flags it otherwise.
+    assert_eq!(run(&ir, OverflowPolicy::Wrap), Ok(0));
+    assert!(run(&ir, OverflowPolicy::Trap).is_err());
+    assert!(run(&ir, OverflowPolicy::Ub).is_err());
+
+    // The comparison is folded only when signed overflow is undefined behavior.
+    for (overflow_policy, expected) in [
+        (OverflowPolicy::Wrap, 0),
+        (OverflowPolicy::Trap, 0),
+        (OverflowPolicy::Ub, 1),
+    ] {
+        let mut ir = ir.clone();
+        let options = CompileOptions {
+            overflow_policy,
+            ..Default::default()
+        };
+        let changed = SimplifyArith::new(SimplifyArithInner::new(options)).optimize(&mut ir);
+        assert_eq!(changed, overflow_policy == OverflowPolicy::Ub);
+        assert_eq!(run(&ir, OverflowPolicy::Wrap), Ok(expected));
+    }
+}
+
+#[test]
+fn test_examples_shift() {
+    let parse = |path: &str| {
+        IrParse::default()
+            .translate(&Path::new(path))
+            .unwrap_or_else(|_| panic!("parse failed {path}"))
+    };
+    let run = |ir: &ir::TranslationUnit, overflow_policy| {
+        let options = CompileOptions {
+            overflow_policy,
+            ..Default::default()
+        };
+        ir::interp_with_options(ir, Vec::new(), options).map(|result| {
+            let (value, _, _) = result.get_int().expect("non-integer value occurs");
+            value
+        })
+    };
+
+    // Each shift of the matrix, over the widths, the signedness and the amounts 0, 1, width - 1,
+    // width and width + 3, counts 1 unless it gives the value of `ir::ShiftKind`. The back-end is
+    // checked against the interpreter by `test_examples_asmgen_small`.
+    let matrix = parse("examples/shift/matrix.ir");
+    assert_eq!(run(&matrix, OverflowPolicy::Wrap), Ok(0));
+
+    // The constant folder agrees with the interpreter.
+    let mut folded = matrix.clone();
+    assert!(SimplifyArith::default().optimize(&mut folded));
+    let (_, definition) = folded.decls["main"].get_function().unwrap();
+    let block = &definition.as_ref().unwrap().blocks[&ir::BlockId(0)];
+    assert!(block
+        .instructions
+        .iter()
+        .all(|instruction| !matches!(instruction.opcode_name(), "shl" | "shr")));
+    assert_eq!(run(&folded, OverflowPolicy::Wrap), Ok(0));
+
+    // The shifts undefined in C, by an amount out of range or into the sign bit, are flagged
+    // under the other policies, and kept by the folder under `Trap` so that they still trap.
+    let out_of_range = parse("examples/shift/out_of_range.ir");
+    let sign_bit = parse("examples/shift/sign_bit.ir");
+    assert_eq!(run(&out_of_range, OverflowPolicy::Wrap), Ok(2));
+    assert_eq!(run(&sign_bit, OverflowPolicy::Wrap), Ok(1));
+    for ir in [&matrix, &out_of_range, &sign_bit] {
+        assert!(run(ir, OverflowPolicy::Trap).is_err());
+        assert!(run(ir, OverflowPolicy::Ub).is_err());
+    }
+    let options = CompileOptions {
+        overflow_policy: OverflowPolicy::Trap,
+        ..Default::default()
+    };
+    for ir in [&out_of_range, &sign_bit] {
+        let mut kept = ir.clone();
+        assert!(!SimplifyArith::new(SimplifyArithInner::new(options)).optimize(&mut kept));
+    }
+}
+
+#[test]
+fn test_examples_division() {
+    let parse = |path: &str| {
+        IrParse::default()
+            .translate(&Path::new(path))
+            .unwrap_or_else(|_| panic!("parse failed {path}"))
+    };
+    let run = |ir: &ir::TranslationUnit, overflow_policy| {
+        let options = CompileOptions {
+            overflow_policy,
+            ..Default::default()
+        };
+        ir::interp_with_options(ir, Vec::new(), options).map(|result| {
+            let (value, _, _) = result.get_int().expect("non-integer value occurs");
+            value
+        })
+    };
+
+    // Each division and remainder of the matrix, over the widths, the signedness and the signs of
+    // the operands, counts 1 unless it truncates toward zero, e.g., `-7 / 2 == -3` and
+    // `-7 % 2 == -1`. The back-end is checked against the interpreter by
+    // `test_examples_asmgen_small`.
+    let matrix = parse("examples/division/matrix.ir");
+    for overflow_policy in [
+        OverflowPolicy::Wrap,
+        OverflowPolicy::Trap,
+        OverflowPolicy::Ub,
+    ] {
+        assert_eq!(run(&matrix, overflow_policy), Ok(0));
+    }
+
+    // The constant folder agrees with the interpreter.
+    let mut folded = matrix.clone();
+    assert!(SimplifyArith::default().optimize(&mut folded));
+    let (_, definition) = folded.decls["main"].get_function().unwrap();
+    let block = &definition.as_ref().unwrap().blocks[&ir::BlockId(0)];
+    assert!(block
+        .instructions
+        .iter()
+        .all(|instruction| !matches!(instruction.opcode_name(), "div" | "mod")));
+    assert_eq!(run(&folded, OverflowPolicy::Wrap), Ok(0));
+
+    // The divisions undefined in C, by zero and of `INT_MIN` by -1, yield what RISC-V yields under
+    // `Wrap`: -1 and the dividend by zero, and `INT_MIN` and 0 for `INT_MIN`. They are flagged
+    // under the other policies, and never folded.
+    let by_zero = parse("examples/division/by_zero.ir");
+    let int_min = parse("examples/division/int_min.ir");
+    assert_eq!(run(&by_zero, OverflowPolicy::Wrap), Ok(6));
+    assert_eq!(run(&int_min, OverflowPolicy::Wrap), Ok(1));
+    for overflow_policy in [
+        OverflowPolicy::Wrap,
+        OverflowPolicy::Trap,
+        OverflowPolicy::Ub,
+    ] {
+        for ir in [&by_zero, &int_min] {
+            if overflow_policy != OverflowPolicy::Wrap {
+                assert!(run(ir, overflow_policy).is_err());
+            }
+            let options = CompileOptions {
+                overflow_policy,
+                ..Default::default()
+            };
+            let mut kept = ir.clone();
+            assert!(!SimplifyArith::new(SimplifyArithInner::new(options)).optimize(&mut kept));
+        }
+    }
+
+    // The constant expressions of the initializers agree with the interpreter.
+    let path = Path::new("examples/constexpr/division.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|e| panic!("{}", e));
+    let values_of = |name: &str| {
+        let (dtype, initializer) = ir.decls[name].get_variable().unwrap();
+        let value =
+            ir::Value::try_from_initializer(initializer.as_ref().unwrap(), dtype, &ir.structs)
+                .unwrap_or_else(|_| panic!("`{name}` is not initialized with a literal"));
+        let values = if let ir::Value::Array { values, .. } = value {
+            values
+        } else {
+            panic!("`{name}` must be an array")
+        };
+        values
+            .into_iter()
+            .map(|value| value.get_int().expect("non-integer value occurs").0 as i128)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(values_of("quotients"), [-3, -3, 3, -(1 << 30), -42]);
+    assert_eq!(values_of("remainders"), [-1, 1, -1, 0, -2]);
+
+    // The undefined ones are not constant expressions.
+    let path = Path::new("examples/constexpr/undefined_division.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let (_, errors) = Irgen::default().translate_keep_going(&unit);
+    let reasons = errors
+        .into_iter()
+        .map(|error| match error.message {
+            IrgenErrorMessage::NotConstantExpression { reason } => reason,
+            message => panic!("unexpected error: {message}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(reasons.len(), 4);
+    assert!(reasons[0].contains("integer division by zero"));
+    assert!(reasons[1].contains("integer division by zero"));
+    assert!(reasons[2].contains("signed integer overflow"));
+    assert!(reasons[3].contains("signed integer overflow"));
+}
+
+#[test]
+fn test_examples_char_signedness() {
+    let path = Path::new("examples/char_signedness/compare.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let names = [
+        "stored",
+        "greater",
+        "all_ones",
+        "explicit_signed",
+        "explicit_unsigned",
+        "negative",
+        "explicit_negative",
+    ];
+    let values_of = |signedness: CharSignedness| {
+        set_char_signedness(signedness);
+        let ir = Irgen::default()
+            .translate(&unit)
+            .unwrap_or_else(|e| panic!("{}", e));
+        let value_of = |name: &str| {
+            let (dtype, initializer) = ir.decls[name].get_variable().unwrap();
+            let value =
+                ir::Value::try_from_initializer(initializer.as_ref().unwrap(), dtype, &ir.structs)
+                    .unwrap_or_else(|_| panic!("`{name}` is not initialized with a literal"));
+            let (value, width, is_signed) = value.get_int().expect("non-integer value occurs");
+            let shift = 128 - width;
+            if is_signed {
+                ((value << shift) as i128) >> shift
+            } else {
+                ((value << shift) >> shift) as i128
+            }
+        };
+        names.map(value_of)
+    };
+
+    // Plain `char` follows the option, and `signed char` and `unsigned char` do not.
+    assert_eq!(
+        values_of(CharSignedness::Unsigned),
+        [200, 1, 1, -56, 200, 0, 1]
+    );
+    assert_eq!(
+        values_of(CharSignedness::Signed),
+        [-56, 0, 0, -56, 200, 1, 1]
+    );
+    assert_eq!(char_signedness(), CharSignedness::Signed);
+    assert_eq!("unsigned".parse(), Ok(CharSignedness::Unsigned));
+    assert!("neither".parse::<CharSignedness>().is_err());
+
+    // gcc agrees under both, e.g., riscv64-linux-gnu-gcc defaulting to `-funsigned-char`.
+    for signedness in [CharSignedness::Unsigned, CharSignedness::Signed] {
+        if let Some(values) = compiled_global_values(path, &names, signedness) {
+            assert_eq!(values, values_of(signedness), "under {signedness} char");
+        }
+    }
+}
+
+#[test]
+fn test_examples_target_data() {
+    let path = Path::new("examples/target_data/node.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let translate = |target: TargetData| {
+        set_target_data(target);
+        Irgen::default()
+            .translate(&unit)
+            .unwrap_or_else(|e| panic!("{}", e))
+    };
+    let values_of = |ir: &ir::TranslationUnit| {
+        ["size_of_node", "align_of_node", "long_is_pointer"].map(|name| {
+            let (dtype, initializer) = ir.decls[name].get_variable().unwrap();
+            let value =
+                ir::Value::try_from_initializer(initializer.as_ref().unwrap(), dtype, &ir.structs)
+                    .unwrap_or_else(|_| panic!("`{name}` is not initialized with a literal"));
+            value.get_int().expect("non-integer value occurs").0
+        })
+    };
+
+    // The struct holding a pointer and a `long` is half as large on RV32, and `long` is as wide as
+    // a pointer on both.
+    let ir = translate(TargetData::RV32);
+    assert_eq!(values_of(&ir), [12, 4, 1]);
+    let (dtype, _) = ir.decls["size_of_node"].get_variable().unwrap();
+    assert_eq!(dtype.get_int_width(), Some(32));
+    let node = ir::Dtype::structure(Some("node".to_string()), None);
+    assert_eq!(node.size_align_of(&ir.structs).unwrap(), (12, 4));
+    let (bytes, _) = ir::Dtype::pointer(ir::Dtype::INT)
+        .size_align_of(&ir.structs)
+        .unwrap();
+    assert_eq!(bytes, 4);
+    assert_eq!(ir::Dtype::long(), ir::Dtype::int(32));
+
+    // The layout names the target it is computed for.
+    let mut layout = Vec::new();
+    write(&ir::Layout::new(&ir), &mut layout).unwrap();
+    assert!(String::from_utf8(layout)
+        .unwrap()
+        .starts_with("// target: riscv32-linux-gnu\n"));
+    assert!(ir::Layout::new(&ir).static_asserts().starts_with(
+        "/* Layout of the interface structs computed by kecc for riscv32-linux-gnu */"
+    ));
+
+    let ir = translate(TargetData::RV64);
+    assert_eq!(values_of(&ir), [24, 8, 1]);
+    let (dtype, _) = ir.decls["size_of_node"].get_variable().unwrap();
+    assert_eq!(dtype.get_int_width(), Some(64));
+    assert_eq!(node.size_align_of(&ir.structs).unwrap(), (24, 8));
+    assert_eq!(ir::Dtype::long(), ir::Dtype::LONG);
+    assert_eq!(target_data(), TargetData::default());
+}
+
+#[test]
+fn test_examples_diff() {
+    let path = Path::new("examples/diff/fold.ir");
+    let ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    // Only the use of the folded `add` changes, and the other registers are the same.
+    let mut folded = ir.clone();
+    assert!(SimplifyArith::default().optimize(&mut folded));
+    assert_eq!(
+        ir::diff(&ir, &folded),
+        "@@ @main @@\n \
+         block b0\n\
+         -  %b0:i1:i32 = mul %b0:i0:i32 4:i32\n\
+         +  %b0:i1:i32 = mul 3:i32 4:i32\n"
+    );
+
+    let mut merged = folded.clone();
+    assert!(!TailMerge::default().optimize(&mut merged));
+    assert_eq!(ir::diff(&folded, &merged), "");
+}
+
+#[test]
+fn test_examples_dead_function() {
+    let path = Path::new("examples/dead_function/dead_function.ir");
+    let mut ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    let call_graph = CallGraph::new(&ir);
+    assert!(call_graph.has_indirect_call("apply"));
+    assert!(call_graph.address_taken().contains("callback"));
+    assert!(call_graph.is_recursive("even"));
+    assert!(call_graph.is_recursive("odd"));
+    assert!(!call_graph.is_recursive("main"));
+
+    // Callees come before their callers, and mutually recursive functions form a single SCC.
+    let order = call_graph.bottom_up_order();
+    let position = |name: &str| order.iter().position(|n| n == name).unwrap();
+    assert!(position("callback") < position("apply"));
+    assert!(position("apply") < position("main"));
+    assert!(position("helper") < position("unused"));
+    assert!(position("even") < position("unused"));
+    assert!(call_graph
+        .sccs()
+        .contains(&vec!["even".to_string(), "odd".to_string()]));
+
+    // `helper`, `even` and `odd` are only used by the unused function.
+    assert!(DeadFunction::default().optimize(&mut ir));
+    let names = ir.decls.keys().map(String::as_str).collect::<Vec<_>>();
+    assert_eq!(names, ["apply", "callback", "main"]);
+
+    let result = ir::interp(&ir, Vec::new()).unwrap_or_else(|e| panic!("{}", e));
+    let (value, _, _) = result.get_int().expect("non-integer value occurs");
+    assert_eq!(value, 3);
+
+    // Functions in the keep list are retained along with their callees.
+    let mut ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    assert!(!DeadFunction::new(vec!["unused".to_string()]).optimize(&mut ir));
+    assert_eq!(ir.decls.len(), 7);
+
+    // A function whose address initializes a global variable is a root, e.g., `handler` of
+    // `int (*fp)(void) = handler;`, called through `fp` by `main`.
+    let path = Path::new("examples/dead_function/global_pointer.ir");
+    let mut ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    assert_eq!(ir.decls["fp"].initializer_references(), ["handler"]);
+    let call_graph = CallGraph::new(&ir);
+    assert!(call_graph.global_references().contains("handler"));
+    assert!(call_graph.address_taken().contains("handler"));
+
+    assert!(DeadFunction::default().optimize(&mut ir));
+    let names = ir.decls.keys().map(String::as_str).collect::<Vec<_>>();
+    assert_eq!(names, ["fp", "handler", "main"]);
+
+    let result = ir::interp(&ir, Vec::new()).unwrap_or_else(|e| panic!("{}", e));
+    let (value, _, _) = result.get_int().expect("non-integer value occurs");
+    assert_eq!(value, 5);
+
+    // The initializer of such a variable is lowered from C, and written as the function name.
+    let temp_dir = tempfile::tempdir().expect("temp dir creation failed");
+    let path = temp_dir.path().join("global_pointer.c");
+    std::fs::write(&path, "int handler(void);\nint (*fp)(void) = handler;\n").unwrap();
+    let unit = Parse
+        .translate(&path.as_path())
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+    assert_eq!(ir.decls["fp"].initializer_references(), ["handler"]);
+    let mut buffer = Vec::new();
+    write(&ir, &mut buffer).unwrap();
+    let printed = String::from_utf8(buffer).unwrap();
+    assert!(printed.contains("var [ret:i32 params:()]* @fp = handler"));
+    temp_dir.close().expect("temp dir deletion failed");
+}
+
+#[test]
+fn test_examples_const_global() {
+    let path = Path::new("examples/const_global/const_global.ir");
+    let mut ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    // Only a `const` variable with internal linkage is placed in `.rodata`, and an `extern` one is
+    // defined in another unit.
+    assert_eq!(
+        asm::SectionType::of_variable(&ir.decls["table"]),
+        Some(asm::SectionType::Rodata)
+    );
+    assert_eq!(
+        asm::SectionType::of_variable(&ir.decls["index"]),
+        Some(asm::SectionType::Data)
+    );
+    assert!(!ir.decls["exported"].is_constant());
+    assert_eq!(
+        asm::SectionType::of_variable(&ir.decls["exported"]),
+        Some(asm::SectionType::Data)
+    );
+    assert!(!ir.decls["imported"].is_constant());
+    assert_eq!(asm::SectionType::of_variable(&ir.decls["imported"]), None);
+    assert_eq!(ir::verify(&ir), Ok(()));
+
+    // Only the load at a constant offset is folded.
+    let mut folded = ir.clone();
+    assert!(FoldConstLoad::default().optimize(&mut folded));
+    assert!(!FoldConstLoad::default().optimize(&mut folded));
+
+    let result = ir::interp(&folded, Vec::new()).unwrap_or_else(|e| panic!("{}", e));
+    let (value, _, _) = result.get_int().expect("non-integer value occurs");
+    assert_eq!(value, 70);
+
+    // Storing to a constant global variable through a pointer is rejected.
+    let (_, definition) = ir
+        .decls
+        .get_mut("main")
+        .unwrap()
+        .get_function_mut()
+        .unwrap();
+    let block = definition
+        .as_mut()
+        .unwrap()
+        .blocks
+        .get_mut(&ir::BlockId(0))
+        .unwrap();
+    let ptr = if let ir::Instruction::Load { ptr } = &*block.instructions[2] {
+        ptr.clone()
+    } else {
+        panic!("`%b0:i2` must be a load");
+    };
+    block.instructions.push(ir::Named::new(
+        None,
+        ir::Instruction::Store {
+            ptr,
+            value: ir::Operand::constant(ir::Constant::int(0, ir::Dtype::INT)),
+        },
+    ));
+    assert!(matches!(
+        ir::verify(&ir),
+        Err(ir::VerifierError::StoreToConstant { name, .. }) if name == "table"
+    ));
+
+    // The storage unit of two bit-fields is not folded into the first of them, unlike the field
+    // after it.
+    let path = Path::new("examples/const_global/bit_fields.ir");
+    let ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let mut folded = ir.clone();
+    assert!(FoldConstLoad::default().optimize(&mut folded));
+    let (_, definition) = folded.decls["main"].get_function().unwrap();
+    let block = &definition.as_ref().unwrap().blocks[&ir::BlockId(0)];
+    assert!(matches!(
+        &*block.instructions[1],
+        ir::Instruction::Load { .. }
+    ));
+    for ir in [&ir, &folded] {
+        let result = ir::interp(ir, Vec::new()).unwrap_or_else(|e| panic!("{}", e));
+        assert_eq!(result.get_int(), Some((13, 32, true)));
+    }
+}
+
+#[test]
+fn test_examples_constant_origin() {
+    let path = Path::new("examples/constant_origin/state.ir");
+    let ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let print = |ir: &ir::TranslationUnit| {
+        let mut buffer = Vec::new();
+        write(ir, &mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    };
+
+    // The enumerators of the cases are attached to their constants and printed back as they were.
+    let source = std::fs::read_to_string(path).unwrap();
+    assert_eq!(print(&ir), source);
+    let (_, definition) = ir.decls["main"].get_function().unwrap();
+    let exit = &definition.as_ref().unwrap().blocks[&ir::BlockId(0)].exit;
+    let origins = if let ir::BlockExit::Switch { cases, .. } = exit {
+        cases
+            .iter()
+            .map(|(constant, _)| constant.origin())
+            .collect::<Vec<_>>()
+    } else {
+        panic!("`b0` must end with a switch")
+    };
+    assert_eq!(origins, [Some("STATE_IDLE"), Some("STATE_DONE")]);
+
+    // The annotations do not change the meaning of the constants.
+    let stripped = ["STATE_IDLE", "STATE_DONE", "STATE_BUSY"]
+        .iter()
+        .fold(source, |source, name| {
+            source.replace(&format!(" /* {name} */"), "")
+        });
+    let temp_dir = tempfile::tempdir().expect("temp dir creation failed");
+    let temp_file_path = temp_dir.path().join("stripped.ir");
+    std::fs::write(&temp_file_path, &stripped).unwrap();
+    let parsed = IrParse::default()
+        .translate(&temp_file_path)
+        .unwrap_or_else(|_| panic!("parse failed {}", temp_file_path.display()));
+    assert_eq!(parsed, ir);
+    assert_eq!(print(&parsed), stripped);
+    assert_eq!(
+        ir::Constant::int(2, ir::Dtype::INT).with_origin("STATE_DONE".to_string()),
+        ir::Constant::int(2, ir::Dtype::INT)
+    );
+    temp_dir.close().expect("temp dir deletion failed");
+
+    // A constant global variable folded into its uses is named after it.
+    let mut folded = ir.clone();
+    assert!(FoldConstLoad::default().optimize(&mut folded));
+    assert!(print(&folded).contains("ret 3:i32 /* LIMIT */"));
+}
+
+#[test]
+fn test_examples_layout() {
+    let path = Path::new("examples/layout/layout.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+
+    let mut layout = Vec::new();
+    write(&ir::Layout::new(&ir), &mut layout).unwrap();
+    let layout = String::from_utf8(layout).unwrap();
+
+    let expected = std::fs::read_to_string("examples/layout/layout.txt").unwrap();
+    assert_eq!(layout, expected);
+}
+
+#[test]
+fn test_examples_layout_members() {
+    let path = Path::new("examples/layout/members.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+
+    let point = ir::Dtype::structure(Some("point".to_string()), None);
+    let offset = |field: &str| {
+        point
+            .get_offset_struct_field(field, &ir.structs)
+            .map(|(offset, dtype)| (offset, dtype.to_string()))
+    };
+
+    // The members of the anonymous struct and union are found at their offsets in `point`.
+    assert_eq!(offset("tag").unwrap(), (0, "i32".to_string()));
+    assert_eq!(offset("c").unwrap(), (8, "i8".to_string()));
+    assert_eq!(offset("l").unwrap(), (16, "i64".to_string()));
+    assert_eq!(offset("s").unwrap(), (24, "i16".to_string()));
+    assert_eq!(offset("d").unwrap(), (24, "f64".to_string()));
+    assert!(matches!(
+        offset("x"),
+        Err(ir::DtypeError::NoSuchMember { field, .. }) if field == "x"
+    ));
+    assert!(matches!(
+        ir::Dtype::INT.get_offset_struct_field("tag", &ir.structs),
+        Err(ir::DtypeError::Misc { .. })
+    ));
+
+    // The offsets accumulate through the nested anonymous members.
+    let word = ir::Dtype::structure(Some("word".to_string()), None);
+    let offset = |field: &str| word.get_offset_struct_field(field, &ir.structs).unwrap().0;
+    assert_eq!(
+        ["c", "lo", "hi", "value"].map(offset),
+        [0, 4, 6, 4],
+        "offsets of the members of `struct word`"
+    );
+
+    // A member of a nested anonymous member conflicts with the members of the enclosing struct.
+    let path = Path::new("examples/layout/duplicate_members.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let irgen_error = Irgen::default()
+        .translate(&unit)
+        .expect_err("`x` must be a duplicate member");
+    assert_eq!(
+        irgen_error.message.to_string(),
+        "struct has duplicate member `x`"
+    );
+}
+
+#[test]
+fn test_examples_restrict() {
+    use kecc::ir::HasDtype;
+
+    let path = Path::new("examples/restrict/copy.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+
+    let dtype = |name: &str| ir.decls[name].dtype().to_string();
+    assert_eq!(
+        dtype("copy"),
+        "[ret:unit params:(i32*restrict, const i32*restrict, i32)]"
+    );
+    assert_eq!(dtype("first"), "i32*const restrict");
+    assert_eq!(dtype("second"), "i32*restrict");
+
+    // The qualifiers survive a round trip through the IR.
+    let temp_dir = tempfile::tempdir().expect("temp dir creation failed");
+    let temp_file_path = temp_dir.path().join("copy.ir");
+    let mut buffer = Vec::new();
+    write(&ir, &mut buffer).unwrap();
+    std::fs::write(&temp_file_path, buffer).unwrap();
+    let parsed = ir::Parse::default()
+        .translate(&temp_file_path)
+        .unwrap_or_else(|_| panic!("parse failed {}", temp_file_path.display()));
+    for name in ["copy", "first", "second"] {
+        assert_eq!(parsed.decls[name].dtype(), ir.decls[name].dtype());
+    }
+    temp_dir.close().expect("temp dir deletion failed");
+}
+
+#[test]
+fn test_examples_restrict_aliasing() {
+    let path = "examples/restrict/self_copy.c";
+    let source = lang_c::driver::parse(&lang_c::driver::Config::default(), path)
+        .unwrap_or_else(|_| panic!("parse failed {path}"));
+
+    let calls = check_restrict_aliasing(&source.unit)
+        .into_iter()
+        .map(|warning| {
+            assert_eq!(warning.function, "main");
+            let [lhs, rhs] = warning
+                .spans
+                .map(|span| source.source[span.start..span.end].to_string());
+            (warning.callee, warning.args, lhs, rhs)
+        })
+        .collect::<Vec<_>>();
+    let expected = [
+        ("memcpy", [0, 1], "a", "a"),
+        ("memcpy", [0, 1], "(void *)p", "&a[0]"),
+        ("copy", [0, 1], "b", "(int *)b"),
+    ]
+    .map(|(callee, args, lhs, rhs)| (callee.to_string(), args, lhs.to_string(), rhs.to_string()));
+    assert_eq!(calls, expected);
+}
+
+#[test]
+fn test_examples_kr_definition() {
+    use lang_c::ast::ExternalDeclaration;
+
+    let path = Path::new("examples/kr_definition/add.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    // The definitions have the same types as their prototypes.
+    let mut prototypes = HashMap::new();
+    let mut definitions = HashMap::new();
+    for decl in &unit.0 {
+        let (specifiers, declarator, dtypes) = match &decl.node {
+            ExternalDeclaration::Declaration(decl) => (
+                &decl.node.specifiers,
+                &decl.node.declarators[0].node.declarator.node,
+                &mut prototypes,
+            ),
+            ExternalDeclaration::FunctionDefinition(definition) => {
+                assert!(definition.node.declarations.is_empty());
+                (
+                    &definition.node.specifiers,
+                    &definition.node.declarator.node,
+                    &mut definitions,
+                )
+            }
+            ExternalDeclaration::StaticAssert(_) => continue,
+        };
+        let (dtype, _) = ir::Dtype::try_from_ast_declaration_specifiers(specifiers).unwrap();
+        let (dtype, name) = dtype.with_ast_declarator(declarator).unwrap().destruct();
+        let _unused = dtypes.insert(name.unwrap(), dtype.to_string());
+    }
+    assert_eq!(prototypes["add"], "[ret:i32 params:(i32, i32)]");
+    assert_eq!(prototypes["scale"], "[ret:i64 params:(i32, i64)]");
+    for name in ["add", "scale"] {
+        assert_eq!(definitions[name], prototypes[name]);
+    }
+
+    let path = Path::new("examples/kr_definition/not_a_parameter.c");
+    match Parse.translate(&path) {
+        Err(ParseError::KRDefinition { function, message }) => {
+            assert_eq!(function, "add");
+            assert_eq!(message, "`c` is declared, but is not a parameter");
+        }
+        result => panic!("unexpected result {:?}", result.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_examples_dead_store() {
+    // The load through `src` may read the first store through `dst`, unless `dst` is `restrict`.
+    test_opt(
+        &Path::new("examples/restrict/dead_store.input.ir"),
+        &Path::new("examples/restrict/dead_store.output.ir"),
+        &mut DeadStore::default(),
+    );
+}
+
+#[test]
+fn test_examples_alignas() {
+    use kecc::ir::HasDtype;
+
+    let path = Path::new("examples/alignas/layout.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+
+    // The alignment is overridden, while the size is not.
+    let expected = [
+        ("buf", "[64 x i8 align 16]", (64, 16)),
+        ("scratch", "[3 x i8 align 8]", (3, 8)),
+        ("plain", "[3 x i16]", (6, 2)),
+        ("table", "[4 x i32 align 32]", (16, 32)),
+        ("blocks", "[2 x [16 x i8 align 16]]", (32, 16)),
+        ("packet", "struct packet", (32, 16)),
+    ];
+    for (name, dtype, size_align) in expected {
+        let decl_dtype = ir.decls[name].dtype();
+        assert_eq!(decl_dtype.to_string(), dtype, "`{name}`");
+        assert_eq!(
+            decl_dtype.size_align_of(&ir.structs).unwrap(),
+            size_align,
+            "`{name}`"
+        );
+    }
+
+    // An over-aligned field is placed at its alignment.
+    let (_, _, offsets) = ir.structs["packet"]
+        .as_ref()
+        .unwrap()
+        .get_struct_size_align_offsets()
+        .unwrap()
+        .as_ref()
+        .unwrap();
+    assert_eq!(offsets, &vec![0, 16]);
+
+    // The alignments survive a round trip through the IR.
+    let temp_dir = tempfile::tempdir().expect("temp dir creation failed");
+    let temp_file_path = temp_dir.path().join("layout.ir");
+    let mut buffer = Vec::new();
+    write(&ir, &mut buffer).unwrap();
+    std::fs::write(&temp_file_path, buffer).unwrap();
+    let parsed = ir::Parse::default()
+        .translate(&temp_file_path)
+        .unwrap_or_else(|_| panic!("parse failed {}", temp_file_path.display()));
+    for name in ["buf", "table", "blocks"] {
+        assert_eq!(parsed.decls[name].dtype(), ir.decls[name].dtype());
+    }
+    temp_dir.close().expect("temp dir deletion failed");
+
+    let path = Path::new("examples/alignas/invalid.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let (ir, errors) = Irgen::default().translate_keep_going(&unit);
+    let expected = [
+        "requested alignment 3 is not a positive power of two",
+        "requested alignment -8 is not a positive power of two",
+        "requested alignment 2 is less than the alignment 4 of `[4 x i32]`",
+        "`_Alignas` is only supported for arrays, not for `i32`",
+    ];
+    assert_eq!(errors.len(), expected.len(), "{errors:#?}");
+    for (error, message) in errors.iter().zip(expected) {
+        assert!(
+            matches!(
+                &error.message,
+                IrgenErrorMessage::InvalidDtype {
+                    dtype_error: ir::DtypeError::Misc { .. }
+                }
+            ),
+            "{error:#?}"
+        );
+        assert_eq!(error.message.to_string(), message);
+    }
+    assert!(ir.decls.is_empty());
+}
+
+#[test]
+fn test_examples_long_double() {
+    let path = Path::new("examples/long_double/long_double.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+    assert_eq!(ir.decls["scale"].dtype(), ir::Dtype::LONGDOUBLE);
+    assert_eq!(ir.decls["ratio"].dtype(), ir::Dtype::LONGDOUBLE);
+    assert_eq!(
+        ir.decls["pi"].dtype(),
+        ir::Dtype::LONGDOUBLE.set_const(true)
+    );
+    let (size, align) = ir.decls["samples"]
+        .dtype()
+        .size_align_of(&ir.structs)
+        .unwrap();
+    assert_eq!((size, align), (32, ir::Dtype::SIZE_OF_LONGDOUBLE));
+
+    let path = Path::new("examples/long_double/invalid.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let (ir, errors) = Irgen::default().translate_keep_going(&unit);
+    let expected = [
+        "`long float` is not a floating point type",
+        "`long long double` is not a floating point type",
+        "`short double` is not a floating point type",
+        "`signed` and `unsigned` only be applied to `Dtype::Int`",
+    ];
+    assert_eq!(errors.len(), expected.len(), "{errors:#?}");
+    for (error, message) in errors.iter().zip(expected) {
+        assert!(
+            matches!(
+                &error.message,
+                IrgenErrorMessage::InvalidDtype {
+                    dtype_error: ir::DtypeError::Misc { .. }
+                }
+            ),
+            "{error:#?}"
+        );
+        assert_eq!(error.message.to_string(), message);
+    }
+    assert!(ir.decls.is_empty());
+}
+
+#[test]
+fn test_examples_complex() {
+    let path = Path::new("examples/complex/complex.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let (ir, errors) = Irgen::default().translate_keep_going(&unit);
+    assert_eq!(errors.len(), 2, "{errors:#?}");
+    for error in &errors {
+        assert!(
+            matches!(
+                &error.message,
+                IrgenErrorMessage::InvalidDtype {
+                    dtype_error: ir::DtypeError::Misc { .. }
+                }
+            ),
+            "{error:#?}"
+        );
+        assert_eq!(
+            error.message.to_string(),
+            "`_Complex` is not supported: complex types are not implemented"
+        );
+    }
+    assert!(ir.decls.contains_key("real"));
+    assert!(ir.decls.contains_key("count"));
+}
+
+#[test]
+fn test_examples_union() {
+    let path = Path::new("examples/union/union.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+
+    let union_type = ir.structs["U"].as_ref().expect("`U` must be defined");
+    assert!(union_type.is_union());
+    assert_eq!(
+        union_type.get_struct_size_align_offsets(),
+        Some(&Some((8, 8, vec![0, 0, 0])))
+    );
+    assert_eq!(
+        ir::Dtype::union(Some("U".to_string()), None).size_align_of(&ir.structs),
+        Ok((8, 8))
+    );
+
+    // The keyword survives a round trip through the IR.
+    let temp_dir = tempfile::tempdir().expect("temp dir creation failed");
+    let temp_file_path = temp_dir.path().join("union.ir");
+    let mut buffer = Vec::new();
+    write(&ir, &mut buffer).unwrap();
+    std::fs::write(&temp_file_path, buffer).unwrap();
+    let parsed = ir::Parse::default()
+        .translate(&temp_file_path)
+        .unwrap_or_else(|_| panic!("parse failed {}", temp_file_path.display()));
+    assert!(parsed.structs["U"]
+        .as_ref()
+        .is_some_and(ir::Dtype::is_union));
+    temp_dir.close().expect("temp dir deletion failed");
+}
+
+#[test]
+fn test_examples_write_c_unsupported() {
+    let path = "examples/write_c/unsupported.c";
+    let source = lang_c::driver::parse(&lang_c::driver::Config::default(), path)
+        .unwrap_or_else(|_| panic!("parse failed {path}"));
+
+    // The unsupported constructs are marked, and the rest of the program is still written.
+    let opts = WriteOpts {
+        on_unsupported: OnUnsupported::Placeholder,
+    };
+    let mut output = Vec::new();
+    try_write(&source.unit, &mut output, &opts).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    for marker in ["`sizeof` of a type", "`->` member access"] {
+        assert!(
+            output.contains(&format!("/* UNSUPPORTED: {marker} */")),
+            "{marker} must be marked in:\n{output}"
+        );
+    }
+    assert!(output.contains("main"));
+
+    // The first of them fails the write, located in the source.
+    let write_error = try_write(&source.unit, &mut Vec::new(), &WriteOpts::default())
+        .expect_err("`sizeof` must be unsupported");
+    let unsupported = match write_error {
+        WriteError::Unsupported(unsupported) => unsupported,
+        WriteError::Io(error) => panic!("unexpected I/O error: {error}"),
+    };
+    assert_eq!(unsupported.construct, "`sizeof` of a type");
+    let span = unsupported.span.expect("`sizeof` must have its span");
+    assert!(source.source[span.start..span.end].contains("struct point"));
+}
+
+#[test]
+fn test_examples_write_c_type_names() {
+    // The casts to the pointers to arrays and functions are written with their parentheses.
+    test_write_c(Path::new("examples/type_names/casts.c"));
+}
+
+#[test]
+fn test_examples_sequence() {
+    let path = Path::new("examples/sequence/unsequenced.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    let names = check_sequence_points(&unit)
+        .into_iter()
+        .map(|warning| {
+            assert_eq!(warning.function, "main");
+            warning.name
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["i", "j", "k"]);
+}
+
+#[test]
+fn test_examples_language_std() {
+    let path = Path::new("examples/language_std/mixed.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    for std in [LanguageStd::C99, LanguageStd::C11, LanguageStd::C17] {
+        assert_eq!(check_language_std(&unit, std, Severity::Warning), []);
+    }
+
+    let diagnostics = check_language_std(&unit, LanguageStd::C89, Severity::Warning);
+    let violations = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.function.as_str(), diagnostic.violation))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        violations,
+        [
+            ("main", StdViolation::MixedDeclaration),
+            ("main", StdViolation::ForDeclaration),
+            ("main", StdViolation::MixedDeclaration),
+        ]
+    );
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "main: warning: mixed declarations and statements not allowed in c89"
+    );
+
+    let errors = check_language_std(&unit, LanguageStd::C89, Severity::Error);
+    assert_eq!(errors.len(), diagnostics.len());
+    assert!(errors
+        .iter()
+        .zip(&diagnostics)
+        .all(|(error, warning)| error.severity == Severity::Error && error.span == warning.span));
+    assert_eq!("c90".parse(), Ok(LanguageStd::C89));
+}
+
+#[test]
+fn test_examples_bitfield() {
+    let path = Path::new("examples/bitfield/flags.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+
+    let bit_fields = |name: &str| {
+        let struct_type = ir.structs[name].as_ref().expect("struct must be defined");
+        let (size, align, offsets) = struct_type
+            .get_struct_size_align_offsets()
+            .and_then(Option::as_ref)
+            .expect("struct must have its layout");
+        let bit_fields = (0..offsets.len())
+            .map(|i| {
+                struct_type
+                    .get_struct_bit_field(i)
+                    .map(|bit_field| (bit_field.width, bit_field.bit_offset))
+            })
+            .collect::<Vec<_>>();
+        (*size, *align, offsets.clone(), bit_fields)
+    };
+
+    // Adjacent bit-fields share a storage unit of their type.
+    assert_eq!(
+        bit_fields("Flags"),
+        (4, 4, vec![0, 0], vec![Some((1, 0)), Some((3, 1))])
+    );
+    // A zero-width bit-field closes the unit, and a bit-field never straddles two units.
+    assert_eq!(
+        bit_fields("Mixed"),
+        (
+            12,
+            4,
+            vec![0, 0, 4, 4, 8],
+            vec![
+                None,
+                Some((4, 8)),
+                Some((0, 0)),
+                Some((30, 0)),
+                Some((4, 0))
+            ]
+        )
+    );
+
+    // The widths survive a round trip through the IR.
+    let temp_dir = tempfile::tempdir().expect("temp dir creation failed");
+    let temp_file_path = temp_dir.path().join("flags.ir");
+    let mut buffer = Vec::new();
+    write(&ir, &mut buffer).unwrap();
+    std::fs::write(&temp_file_path, buffer).unwrap();
+    let parsed = ir::Parse::default()
+        .translate(&temp_file_path)
+        .unwrap_or_else(|_| panic!("parse failed {}", temp_file_path.display()));
+    assert_eq!(parsed.structs["Mixed"], ir.structs["Mixed"]);
+    temp_dir.close().expect("temp dir deletion failed");
+
+    let path = Path::new("examples/bitfield/errors.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let (_, errors) = Irgen::default().translate_keep_going(&unit);
+    let fields = errors
+        .into_iter()
+        .map(|error| match error.message {
+            IrgenErrorMessage::InvalidDtype {
+                dtype_error: ir::DtypeError::InvalidBitField { field, .. },
+            } => field,
+            message => panic!("unexpected error: {message}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(fields, ["x", "y", "z", "w"]);
+}
+
+#[test]
+fn test_examples_struct_table() {
+    let path = Path::new("examples/struct_table/linked_list.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+    let mut buffer = Vec::new();
+    write(&ir, &mut buffer).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+
+    // Each struct is defined once at the top, and referred to by its tag elsewhere.
+    let definitions = text
+        .lines()
+        .take_while(|line| line.starts_with("struct "))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        definitions,
+        [
+            "struct list : { head:struct node*, pool:struct pool*, len:i32 }",
+            "struct node : { next:struct node*, val:i32 }",
+            "struct pool : { nodes:[8 x struct node], free:struct node* }",
+        ]
+    );
+    assert_eq!(text.matches("next:").count(), 1);
+    assert!(text.contains("var [4 x struct list] @lists"));
+
+    // The table is rebuilt first, whatever the order of the tags, and the dump is the same.
+    let temp_dir = tempfile::tempdir().expect("temp dir creation failed");
+    let temp_file_path = temp_dir.path().join("linked_list.ir");
+    std::fs::write(&temp_file_path, &text).unwrap();
+    let parsed = ir::Parse::default()
+        .translate(&temp_file_path)
+        .unwrap_or_else(|_| panic!("parse failed {}", temp_file_path.display()));
+    assert_eq!(parsed.structs, ir.structs);
+    let mut buffer = Vec::new();
+    write(&parsed, &mut buffer).unwrap();
+    assert_eq!(String::from_utf8(buffer).unwrap(), text);
+    temp_dir.close().expect("temp dir deletion failed");
+}
+
+#[test]
+fn test_examples_struct_nominal() {
+    use kecc::ir::HasDtype;
+
+    let path = Path::new("examples/struct_table/nominal.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    // The typedef repeated after the definition is the same.
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+    let early = ir.decls["early"].dtype();
+    let late = ir.decls["late"].dtype();
+    assert!(early.is_same_type(&late));
+
+    // The type of a struct value has its fields, unlike the references to the struct table.
+    let fields = ir.structs["S"]
+        .as_ref()
+        .and_then(|struct_type| struct_type.get_struct_fields()?.clone())
+        .expect("`S` must be defined");
+    let reference = ir::Dtype::structure(Some("S".to_string()), None);
+    let filled = ir::Dtype::structure(Some("S".to_string()), Some(fields.clone()));
+    assert_eq!(ir.decls["value"].dtype(), reference);
+    assert_ne!(reference, filled);
+    assert!(reference.is_same_type(&filled));
+    assert!(ir::Dtype::pointer(reference.clone()).is_same_type(&ir::Dtype::pointer(filled)));
+    assert!(!reference.is_same_type(&ir::Dtype::structure(Some("U".to_string()), None)));
+    assert!(!reference.is_same_type(&reference.clone().set_const(true)));
+
+    // The anonymous structs are compared by their fields.
+    let anonymous = ir::Dtype::structure(None, Some(fields.clone()));
+    assert!(anonymous.is_same_type(&ir::Dtype::structure(None, Some(fields))));
+    assert!(!anonymous.is_same_type(&ir::Dtype::structure(None, Some(Vec::new()))));
+}
+
+#[test]
+fn test_examples_bitfield_access() {
+    let path = Path::new("examples/bitfield/access.ir");
+    let ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    // The fields of a struct value are read from and written to the bits of their units, and a
+    // signed field is sign-extended, so that `x : 3` holding 7 reads as -1.
+    assert_eq!(ir::call(&ir, "copy", Vec::new()), Ok(123u8.into()));
+    assert_eq!(ir::call(&ir, "signed", Vec::new()), Ok((-1i32).into()));
+    // Storing a field by loading and storing its whole unit keeps the adjacent fields.
+    assert_eq!(ir::call(&ir, "adjacent", Vec::new()), Ok(13u32.into()));
+}
+
+#[test]
+fn test_examples_enum() {
+    use kecc::ir::HasDtype;
+
+    let path = Path::new("examples/enum/enum.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+
+    // The enum types are `int`, and the enumerators are replaced by their values.
+    let value_of = |name: &str| match &ir.decls[name] {
+        ir::Declaration::Variable {
+            dtype,
+            initializer: Some(lang_c::ast::Initializer::Expression(expr)),
+            ..
+        } => {
+            assert_eq!(dtype.clone().strip_outer_const(), ir::Dtype::INT);
+            let (value, _, _) = ir::Constant::try_from(&expr.node)
+                .ok()
+                .and_then(|constant| constant.get_int())
+                .unwrap_or_else(|| panic!("`{name}` is not initialized by an integer"));
+            value as i128
+        }
+        decl => panic!("unexpected declaration of `{name}`: {decl:?}"),
+    };
+    assert_eq!(value_of("red"), 0);
+    assert_eq!(value_of("blue"), 6);
+    assert_eq!(value_of("green"), 5);
+    assert_eq!(value_of("sum"), 8);
+    assert!(ir.decls["green"].dtype().is_const());
+}
+
+#[test]
+fn test_examples_layout_asserts() {
+    test_dir(
+        Path::new("examples/ir0"),
+        OsStr::new("ir"),
+        test_layout_asserts,
+    );
+
+    let path = Path::new("examples/layout_asserts/interface.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+
+    // Structs not reachable from global variables or functions are not in the interface.
+    let names = ir::Layout::new(&ir)
+        .interface_structs()
+        .into_iter()
+        .map(|layout| layout.name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["inner", "outer"]);
+}
+
+#[test]
+fn test_examples_verify_layout() {
+    let path = Path::new("examples/verify_layout/structs.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let mut ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+
+    let layout = ir::Layout::new(&ir);
+    let recorded = layout.member_offsets();
+    let interpreted = layout
+        .interpreted_offsets()
+        .unwrap_or_else(|interp_error| panic!("{}", interp_error));
+    let compiled = compiled_offsets(&ir);
+    // Bit-fields are not probed, nor pointers by the interpreter.
+    assert!(!recorded.iter().any(|member| member.member == "ready"));
+    assert!(!interpreted.iter().any(|member| member.member == "next"));
+    assert_eq!(compiled, recorded);
+    let sources = [
+        ("interpreter", interpreted.as_slice()),
+        ("gcc", compiled.as_slice()),
+    ];
+    assert_eq!(ir::LayoutMismatch::between(&recorded, &sources), []);
+
+    // A broken layout is followed by the interpreter, but not by gcc.
+    if let Some(ir::Dtype::Struct {
+        size_align_offsets: Some((_, _, offsets)),
+        ..
+    }) = ir.structs.get_mut("point").unwrap()
+    {
+        offsets[1] = 2;
+    }
+    let layout = ir::Layout::new(&ir);
+    let recorded = layout.member_offsets();
+    let interpreted = layout
+        .interpreted_offsets()
+        .unwrap_or_else(|interp_error| panic!("{}", interp_error));
+    let sources = [
+        ("interpreter", interpreted.as_slice()),
+        ("gcc", compiled.as_slice()),
+    ];
+    let mismatches = ir::LayoutMismatch::between(&recorded, &sources);
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(
+        mismatches[0].to_string(),
+        "error: the offset of `x` in `point` is 2 by kecc, 2 by interpreter, 4 by gcc"
+    );
+}
+
+#[test]
+fn test_examples_line_marker() {
+    let path = Path::new("examples/line_marker/preprocessed.i");
+    let location = match Parse.translate(&path) {
+        Err(ParseError::SyntaxError { location, .. }) => location,
+        result => panic!("unexpected parse result: {result:?}"),
+    };
+    assert_eq!(location.file, "util.h");
+    assert_eq!(location.line, 3);
+
+    // Markers with flags, `#line` directives and markers without a file name are recognized.
+    let source = "# 1 \"main.c\"\nint x;\n# 10 \"foo.h\" 1 3 4\nint y;\n#line 20\nint z;\n";
+    let line_map = LineMap::new("source.i", source);
+    let offset_of = |needle: &str| source.find(needle).unwrap();
+    assert_eq!(line_map.location(offset_of("x")).to_string(), "main.c:1:5");
+    assert_eq!(line_map.location(offset_of("y")).to_string(), "foo.h:10:5");
+    assert_eq!(line_map.location(offset_of("z")).to_string(), "foo.h:20:5");
+    assert_eq!(LineMap::strip(source), "\nint x;\n\nint y;\n\nint z;\n");
+
+    // The errors of the IR generator are located in the original source likewise.
+    let path = Path::new("examples/line_marker/irgen.i");
+    let (unit, line_map) = Parse
+        .translate_with_line_map(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let (ir, errors) = Irgen::default()
+        .with_line_map(line_map)
+        .translate_keep_going(&unit);
+    assert!(ir.decls.contains_key("table"));
+    assert_eq!(errors.len(), 1, "{errors:#?}");
+    let error = &errors[0];
+    let location = error.location.as_ref().expect("`error` must be located");
+    assert_eq!((location.file.as_str(), location.line), ("config.h", 3));
+    assert!(error.to_string().starts_with("config.h:3:"));
+    assert!(matches!(
+        error.message,
+        IrgenErrorMessage::NotConstantExpression { .. }
+    ));
+}
+
+#[test]
+fn test_examples_report() {
+    let path = Path::new("examples/report/report.ir");
+    let ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    let report = Report::new(&ir);
+    let function = |name: &str, instructions, blocks, frame_size, call_depth| FunctionReport {
+        name: name.to_string(),
+        instructions,
+        blocks,
+        frame_size,
+        call_depth,
+    };
+    assert_eq!(
+        report.functions,
+        [
+            function("leaf", 2, 1, 0, Some(0)),
+            // `i8` at 0, `i64` at 8, and `i32` at 16
+            function("main", 7, 3, 20, None),
+            function("middle", 2, 1, 0, Some(1)),
+            function("recursive", 6, 3, 0, None),
+        ]
+    );
+
+    let json = report.to_json();
+    assert_eq!(Report::from_json(&json).unwrap(), report);
+}
+
+#[test]
+fn test_examples_builtin() {
+    let path = Path::new("examples/builtin/builtin.ir");
+    let ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let result = ir::interp(&ir, Vec::new()).unwrap_or_else(|e| panic!("{}", e));
+    let (value, _, _) = result.get_int().expect("non-integer value occurs");
+    assert_eq!(value, 24);
+
+    // `__builtin_trap` stops the execution before `__builtin_unreachable` is reached.
+    let path = Path::new("examples/builtin/trap.ir");
+    let mut ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    assert!(matches!(
+        ir::interp(&ir, Vec::new()),
+        Err(ir::InterpreterError::Misc { msg, .. }) if msg.contains("__builtin_trap")
+    ));
+
+    let (_, definition) = ir
+        .decls
+        .get_mut("main")
+        .unwrap()
+        .get_function_mut()
+        .unwrap();
+    let block = definition
+        .as_mut()
+        .unwrap()
+        .blocks
+        .get_mut(&ir::BlockId(0))
+        .unwrap();
+    let _unused = block.instructions.remove(0);
+    assert_eq!(
+        ir::interp(&ir, Vec::new()),
+        Err(ir::InterpreterError::Unreachable)
+    );
+
+    // Declaring an unknown builtin is an error.
+    let path = Path::new("examples/builtin/unknown.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let irgen_error = Irgen::default().translate(&unit).unwrap_err();
+    assert_eq!(
+        irgen_error.message,
+        IrgenErrorMessage::UnknownBuiltin {
+            name: "__builtin_bogus".to_string()
+        }
+    );
+
+    // So is calling one without declaring it, reported at the callee.
+    let path = Path::new("examples/builtin/unknown_call.c");
+    let (unit, line_map) = Parse
+        .translate_with_line_map(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let irgen_error = Irgen::default()
+        .with_line_map(line_map)
+        .translate(&unit)
+        .unwrap_err();
+    assert_eq!(
+        irgen_error.message,
+        IrgenErrorMessage::UnknownBuiltin {
+            name: "__builtin_frobnicate".to_string()
+        }
+    );
+    let location = irgen_error.location.expect("`irgen_error` must be located");
+    assert_eq!((location.line, location.column), (3, 10));
+}
+
+#[test]
+fn test_crash_report() {
+    let dir = tempfile::tempdir().expect("temp dir creation failed");
+    let path = Path::new("examples/c/simple.c");
+    let options = CompileOptions {
+        overflow_policy: OverflowPolicy::Trap,
+        ..Default::default()
+    };
+
+    install_crash_handler(path.to_path_buf(), options, dir.path().to_path_buf());
+    set_stage("test:deliberate-panic");
+    let result = std::panic::catch_unwind(|| panic!("deliberate panic"));
+    let _hook = std::panic::take_hook();
+    assert!(result.is_err());
+
+    let report = dir
+        .path()
+        .read_dir()
+        .expect("read_dir call failed")
+        .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+        .find(|report| report.contains("deliberate panic"))
+        .expect("crash report must be written");
+
+    let input = std::fs::read_to_string(path).unwrap();
+    for section in [
+        "== kecc crash report ==",
+        &format!("version: {}", env!("CARGO_PKG_VERSION")),
+        "stage: test:deliberate-panic",
+        "options: {\"overflow_policy\": \"trap\", \"instrumentation\": \"none\", \
+         \"switch_thresholds\": {\"max_linear_cases\": 4, \"min_table_density\": 40}, \
+         \"strict_align\": false, \"language_std\": \"c11\", \
+         \"target\": {\"triple\": \"riscv64-linux-gnu\", \"pointer_size\": 8, \
+         \"long_size\": 8, \"char_signedness\": \"signed\", \"max_align\": 16}}",
+        "== panic ==",
+        "== input: examples/c/simple.c ==",
+        &input,
+        "== backtrace ==",
+    ] {
+        assert!(
+            report.contains(section),
+            "missing `{section}` in:\n{report}"
+        );
+    }
+}
+
+#[test]
+fn test_examples_bounds() {
+    let path = Path::new("examples/bounds/bounds.ir");
+    let ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    // In-range accesses, accesses through parameters and copies fitting in the destination are
+    // not warned.
+    let warnings = ir::check_bounds(&ir)
+        .into_iter()
+        .map(|warning| (warning.site, warning.decl))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        warnings,
+        [
+            ("@global", "@global"),
+            ("main:b0:4", "%l0:[4 x i32]:buf"),
+            ("main:b0:8", "%l1:struct pair:p"),
+            ("main:b0:16", "%l0:[4 x i32]:buf"),
+            ("main:b0:20", "@global"),
+        ]
+        .map(|(site, decl)| (site.to_string(), decl.to_string()))
+    );
+
+    // Those of a C source are found in the source, and located at the access and the declarator.
+    let path = Path::new("examples/bounds/bounds.c");
+    let (unit, line_map) = Parse
+        .translate_with_line_map(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let mut irgen = Irgen::default().with_line_map(line_map);
+    let _unused = irgen.translate_keep_going(&unit);
+
+    // The initializers too long for their arrays are warned where the variables, including the
+    // static local ones, are lowered. The accesses are only warned in the IR lowered from the
+    // function bodies, which this IR generator does not lower yet.
+    let at = |line: usize, column: usize| format!("{}:{line}:{column}", path.display());
+    let warnings = irgen
+        .take_bounds_warnings()
+        .into_iter()
+        .map(|warning| (warning.site, warning.decl, warning.message, warning.spans))
+        .collect::<Vec<_>>();
+    assert_eq!(warnings.len(), 2, "{warnings:#?}");
+    for ((site, decl, message, spans), expected) in warnings.into_iter().zip([
+        (
+            at(5, 14),
+            at(5, 6),
+            "initializer of 5 elements overflows the array of 4",
+        ),
+        (
+            at(29, 26),
+            at(29, 17),
+            "initializer of 3 elements overflows the array of 2",
+        ),
+    ]) {
+        assert_eq!((site, decl, message.as_str()), expected);
+        assert!(spans.is_some());
+    }
+}
+
+#[test]
+fn test_examples_const_levels() {
+    use kecc::ir::HasDtype;
+
+    let parse = |path: &str| {
+        lang_c::driver::parse(&lang_c::driver::Config::default(), path)
+            .unwrap_or_else(|_| panic!("parse failed {path}"))
+    };
+
+    // The qualifiers of the typedefs and of the declarators merge at every level, and those of an
+    // array type qualify its elements.
+    let levels = parse("examples/const_levels/levels.c");
+    let ir = Irgen::default()
+        .skip_definitions(HashSet::from(["writable".to_string()]))
+        .translate(&levels.unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+    let expected = [
+        ("d1", "i32*const"),
+        ("d2", "const i32*const"),
+        ("d3", "i32*const"),
+        ("d4", "i32*const*"),
+        ("d5", "i32*const*const"),
+        ("d6", "[3 x const i32*const*const]"),
+        ("d7", "[3 x const i32]"),
+        ("d8", "[3 x const i32]"),
+        ("d9", "[2 x [3 x const i32]]"),
+        ("d10", "[2 x i32*const]"),
+        ("d11", "[3 x const i32]*"),
+        ("d12", "[3 x i32]*const"),
+        ("d13", "[2 x const i32*const]"),
+        ("d14", "[2 x [3 x const i32*]]"),
+        ("d15", "[4 x i32*const*]"),
+        ("d16", "[2 x const i32*const]*"),
+        ("d17", "[3 x const i32]*const*"),
+        ("d18", "[ret:i32 params:(i32*const)]*const"),
+        ("d19", "[2 x [3 x const i32]*]"),
+        ("d20", "const i32*const*const"),
+        ("d21", "i32*const*"),
+    ];
+    for (name, dtype) in expected {
+        assert_eq!(ir.decls[name].dtype().to_string(), dtype, "`{name}`");
+    }
+
+    // Without typedefs, the qualifiers after an asterisk qualify that pointer, and the others the
+    // pointee. The levels are listed from the outermost, i.e., the declared object.
+    let pointers = parse("examples/const_levels/pointers.c");
+    let pointers_ir = Irgen::default()
+        .translate(&pointers.unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+    let expected = [
+        ("p1", "const i32*", [false, true].as_slice()),
+        ("p2", "i32*const", &[true, false]),
+        ("p3", "const i32*const", &[true, true]),
+        ("p4", "i32**const", &[true, false, false]),
+        ("p5", "i32*const*", &[false, true, false]),
+        ("q", "const i32*const*", &[false, true, true]),
+    ];
+    for (name, display, levels) in expected {
+        let dtype = pointers_ir.decls[name].dtype();
+        assert_eq!(dtype.to_string(), display, "`{name}`");
+        let mut level = Some(&dtype);
+        let mut is_const = Vec::new();
+        while let Some(dtype) = level {
+            is_const.push(dtype.is_const());
+            level = dtype.get_pointer_inner();
+        }
+        assert_eq!(is_const, levels, "`{name}`");
+    }
+
+    let stripped = ir.decls["d6"]
+        .dtype()
+        .get_array_inner()
+        .unwrap()
+        .clone()
+        .strip_outer_const();
+    assert_eq!(stripped.to_string(), "const i32*const*");
+    assert_eq!(
+        ir.decls["d7"].dtype().strip_outer_const(),
+        ir.decls["d7"].dtype()
+    );
+
+    // A write is rejected at every `const` level, including the elements of a `const` array typedef
+    // and of an array member of a `const` struct.
+    let source = parse("examples/const_levels/writes.c");
+    let (_, errors) = Irgen::default().translate_keep_going(&source.unit);
+    let not_modifiable = "left operand of assignment is not a modifiable l-value, as it is";
+    let expected = [
+        ("p = 0", "`const`-qualified"),
+        ("a[1] = 1", "`const`-qualified"),
+        ("aa[1][2] = 1", "`const`-qualified"),
+        ("ap[0] = 0", "`const`-qualified"),
+        ("r = 0", "an array"),
+        ("r[0] = 0", "`const`-qualified"),
+        ("*r[0] = 0", "`const`-qualified"),
+        ("**r[0] = 1", "`const`-qualified"),
+        ("(*pa)[0] = 1", "`const`-qualified"),
+        ("box.values[0] = 1", "`const`-qualified"),
+    ];
+    assert_eq!(errors.len(), expected.len(), "{errors:#?}");
+
+    for (error, (snippet, reason)) in errors.iter().zip(expected) {
+        assert_eq!(
+            error.message.to_string(),
+            format!("{not_modifiable} {reason}")
+        );
+        let span = error
+            .span
+            .expect("the span of the expression must be known");
+        let text = &source.source[span.start..span.end];
+        assert!(
+            text.contains(snippet) && !text.contains(';'),
+            "`{text}` is not the span of `{snippet}`"
+        );
+    }
+}
+
+#[test]
+fn test_examples_constexpr() {
+    let path = Path::new("examples/constexpr/constexpr.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|e| panic!("{}", e));
+
+    let value_of = |name: &str| {
+        let (dtype, initializer) = ir.decls[name].get_variable().unwrap();
+        ir::Value::try_from_initializer(initializer.as_ref().unwrap(), dtype, &ir.structs)
+            .unwrap_or_else(|_| panic!("`{name}` is not initialized with a literal"))
+    };
+    let int_of = |value: ir::Value| value.get_int().expect("non-integer value occurs").0 as i128;
+
+    assert_eq!(int_of(value_of("shift")), 16);
+    assert_eq!(int_of(value_of("negated")), -15);
+    assert_eq!(int_of(value_of("scaled")), 16);
+    assert_eq!(int_of(value_of("big")), 1 << 40);
+    assert_eq!(int_of(value_of("narrowed")), 44);
+    assert_eq!(int_of(value_of("logical")), 0);
+    // The branch dividing by zero is not evaluated.
+    assert_eq!(int_of(value_of("chosen")), 10);
+    assert!(
+        matches!(value_of("half"), ir::Value::Float { value, .. } if value.into_inner() == 0.5)
+    );
+    let values = if let ir::Value::Array { values, .. } = value_of("table") {
+        values
+    } else {
+        panic!("`table` must be an array")
+    };
+    assert_eq!(
+        values.into_iter().map(int_of).collect::<Vec<_>>(),
+        [4, 8, -5]
+    );
+
+    // Calls and loads from non-constant variables are not constant expressions.
+    let path = Path::new("examples/constexpr/not_constant.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let (_, errors) = Irgen::default().translate_keep_going(&unit);
+    let reasons = errors
+        .into_iter()
+        .map(|error| match error.message {
+            IrgenErrorMessage::NotConstantExpression { reason } => reason,
+            message => panic!("unexpected error: {message}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(reasons.len(), 2);
+    assert!(reasons[0].contains("function call in a constant expression"));
+    assert!(reasons[1].contains("load from non-constant memory"));
+}
+
+#[test]
+fn test_examples_sink() {
+    test_opt(
+        &Path::new("examples/sink/if_use.input.ir"),
+        &Path::new("examples/sink/if_use.output.ir"),
+        &mut Sink::default(),
+    );
+
+    // `%b0:i0` is only used in the loop, which would compute it on every iteration.
+    let path = Path::new("examples/sink/loop.ir");
+    let mut ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let (_, definition) = ir.decls["loop"].get_function().unwrap();
+    let definition = definition.as_ref().unwrap();
+    let cfg = Cfg::new(definition);
+    let dominators = Dominators::new(definition, &cfg);
+    let loops = Loops::new(&cfg, &dominators);
+    assert!(dominators.dominates(ir::BlockId(1), ir::BlockId(2)));
+    assert!(!dominators.dominates(ir::BlockId(1), ir::BlockId(3)));
+    assert_eq!(loops.headers(ir::BlockId(1)).len(), 1);
+    assert!(loops.headers(ir::BlockId(2)).is_empty());
+    assert!(!Sink::default().optimize(&mut ir));
+
+    // Sinking preserves the behavior of the programs.
+    test_dir(Path::new("examples/opt"), OsStr::new("ir"), |path| {
+        let ir = IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+        let expected = ok_or!(ir::interp(&ir, Vec::new()), return);
+
+        let mut sunk = ir.clone();
+        let _ = Sink::default().optimize(&mut sunk);
+        assert_eq!(ir::interp(&sunk, Vec::new()), Ok(expected));
+    });
+}
+
+#[test]
+fn test_examples_branch_prob() {
+    let parse = |path: &Path| {
+        IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()))
+    };
+    let b = ir::BlockId;
+
+    // The inner loop `b2` runs in the outer loop `b1`, and both compare against constants.
+    let ir = parse(Path::new("examples/branch_prob/nested.ir"));
+    let (_, definition) = ir.decls["nested"].get_function().unwrap();
+    let prob = BranchProb::new(definition.as_ref().unwrap());
+    assert!(prob.probability(b(1), b(2)) > BranchProb::LOOP_BRANCH);
+    assert!((prob.probability(b(1), b(2)) + prob.probability(b(1), b(4)) - 1.0).abs() < 1e-9);
+    assert_eq!(prob.probability(b(0), b(1)), 1.0);
+    assert_eq!(prob.probability(b(0), b(2)), 0.0);
+    assert_eq!(prob.frequency(b(0)), 1.0);
+    assert!(prob.frequency(b(1)) > prob.frequency(b(0)));
+    assert!(prob.frequency(b(2)) > prob.frequency(b(1)));
+    assert!(prob.frequency(b(3)) > prob.frequency(b(5)));
+    assert!((prob.frequency(b(4)) - 1.0).abs() < 1e-9);
+    assert!((prob.frequency(b(5)) - prob.frequency(b(1)) + 1.0).abs() < 1e-6);
+
+    // `ptr` is not null, and the block calling `abort` is almost never reached.
+    let ir = parse(Path::new("examples/branch_prob/guards.ir"));
+    let (_, definition) = ir.decls["guards"].get_function().unwrap();
+    let prob = BranchProb::new(definition.as_ref().unwrap());
+    assert!((prob.probability(b(0), b(2)) - BranchProb::POINTER).abs() < 1e-9);
+    assert!(prob.frequency(b(1)) < prob.frequency(b(2)));
+    assert!((prob.probability(b(2), b(3)) - (1.0 - BranchProb::NORETURN)).abs() < 1e-9);
+    assert!(prob.frequency(b(3)) < 0.001);
+    // The cases of a switch are taken equally.
+    assert!((prob.probability(b(4), b(6)) - 2.0 / 3.0).abs() < 1e-9);
+    assert!((prob.edge_frequency(b(4), b(5)) * 2.0 - prob.frequency(b(6))).abs() < 1e-9);
+}
+
+#[test]
+fn test_examples_strict_align() {
+    // `@packed` holds a packed `struct { char; unsigned int; short; }`.
+    let path = Path::new("examples/strict_align/packed.ir");
+    let ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let expected = ir::Value::Int {
+        value: 17,
+        width: 32,
+        is_signed: true,
+    };
+
+    // The misaligned accesses trap on the target without `strict-align`.
+    let error = ir::interp(&ir, Vec::new()).unwrap_err();
+    assert!(format!("{error:?}").contains("misaligned"), "{error:?}");
+    let options = CompileOptions {
+        strict_align: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        ir::interp_with_options(&ir, Vec::new(), options),
+        Ok(expected.clone())
+    );
+
+    // Split into bytes, the accesses are aligned.
+    let mut split = ir.clone();
+    assert!(SplitMisaligned::default().optimize(&mut split));
+    assert_eq!(ir::interp(&split, Vec::new()), Ok(expected));
+    assert!(!SplitMisaligned::default().optimize(&mut split));
+}
+
+#[test]
+fn test_examples_pass_registry() {
+    let parse = |path: &str| {
+        IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {path}"))
+    };
+
+    let mut registry = PassRegistry::new(CompileOptions::default());
+    registry
+        .register("strip-names", Box::<custom_pass::StripNames>::default())
+        .unwrap();
+    assert_eq!(
+        registry.register("gvn", Box::<custom_pass::StripNames>::default()),
+        Err(PassRegistryError::Duplicate {
+            name: "gvn".to_string()
+        })
+    );
+    assert!(registry.names().any(|name| name == "strip-names"));
+
+    // A registered pass runs along with the built-in ones.
+    let mut ir = parse("examples/ir0/fibonacci.ir");
+    let mut expected = ir.clone();
+    assert!(registry.run("tail-merge,strip-names", &mut ir).unwrap());
+    let _unused = TailMerge::default().optimize(&mut expected);
+    let _unused = custom_pass::StripNames::default().optimize(&mut expected);
+    assert_eq!(ir, expected);
+    let (_, definition) = ir.decls["fibonacci"].get_function().unwrap();
+    let definition = definition.as_ref().unwrap();
+    assert!(definition
+        .allocations
+        .iter()
+        .all(|allocation| allocation.name().is_none()));
+    assert!(!registry.run("strip-names", &mut ir).unwrap());
+
+    // The names are checked before any pass runs.
+    let mut ir = parse("examples/ir0/fibonacci.ir");
+    let before = ir.clone();
+    assert!(matches!(
+        registry.run("strip-names,mypass", &mut ir),
+        Err(PassRegistryError::Unknown { name, .. }) if name == "mypass"
+    ));
+    assert_eq!(ir, before);
+}
+
+#[test]
+fn test_examples_tail_merge() {
+    test_opt(
+        &Path::new("examples/tail_merge/if_else.input.ir"),
+        &Path::new("examples/tail_merge/if_else.output.ir"),
+        &mut TailMerge::default(),
+    );
+
+    // The stores to different variables are not merged.
+    let path = Path::new("examples/tail_merge/clobber.ir");
+    let mut ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    assert!(!TailMerge::default().optimize(&mut ir));
+
+    // Merging tails preserves the behavior of the programs.
+    test_dir(Path::new("examples/opt"), OsStr::new("ir"), |path| {
+        let ir = IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+        let expected = ok_or!(ir::interp(&ir, Vec::new()), return);
+
+        let mut merged = ir.clone();
+        let _ = TailMerge::default().optimize(&mut merged);
+        assert_eq!(ir::interp(&merged, Vec::new()), Ok(expected));
+    });
+}
+
+#[test]
+fn test_examples_loop_rotate() {
+    let parse = |path: &str| {
+        let path = Path::new(path);
+        IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()))
+    };
+
+    // `@odd_sum` has two latches because of `continue`, and `@count` is a `for` loop.
+    test_opt(
+        &Path::new("examples/loop_rotate/loops.input.ir"),
+        &Path::new("examples/loop_rotate/loops.output.ir"),
+        &mut LoopRotate::default(),
+    );
+    let mut ir = parse("examples/loop_rotate/loops.output.ir");
+    assert!(!LoopRotate::default().optimize(&mut ir));
+    let mut ir = parse("examples/loop_rotate/loops.input.ir");
+    assert!(!LoopRotate::new(LoopRotateInner::new(0)).optimize(&mut ir));
+
+    let run = |ir: &ir::TranslationUnit| {
+        ir::interp_bounded(ir, Vec::new(), CompileOptions::default(), 1_000_000)
+    };
+
+    // An iteration of `@sum` takes one branch instead of two, and of `@count` two instead of
+    // three, as the jump from the body to the step remains.
+    let mut rotated = ir.clone();
+    assert!(LoopRotate::default().optimize(&mut rotated));
+    let (expected, actual) = (run(&ir).unwrap().unwrap(), run(&rotated).unwrap().unwrap());
+    assert_eq!(actual.value, expected.value);
+    assert_eq!((expected.branches, actual.branches), (806, 606));
+
+    // Rotating preserves the behavior of the programs, and never takes more branches.
+    for dir in ["examples/ir0", "examples/opt"] {
+        test_dir(Path::new(dir), OsStr::new("ir"), |path| {
+            let ir = IrParse::default()
+                .translate(&path)
+                .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+            let expected = some_or!(ok_or!(run(&ir), return), return);
+
+            let mut rotated = ir.clone();
+            let _ = LoopRotate::default().optimize(&mut rotated);
+            let actual = run(&rotated)
+                .unwrap()
+                .unwrap_or_else(|| panic!("{} diverges", path.display()));
+            assert_eq!(actual.value, expected.value);
+            assert_eq!(actual.globals, expected.globals);
+            assert!(actual.branches <= expected.branches);
+        });
+    }
+}
+
+#[test]
+fn test_examples_lower_switch() {
+    let parse = |path: &str| {
+        let path = Path::new(path);
+        IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()))
+    };
+
+    let thresholds = SwitchThresholds::default();
+    assert_eq!(thresholds.strategy(&[]), SwitchStrategy::Linear);
+    assert_eq!(thresholds.strategy(&[1, 100, 5000]), SwitchStrategy::Linear);
+    assert_eq!(
+        thresholds.strategy(&[0, 1, 2, 3, 4, 5]),
+        SwitchStrategy::JumpTable
+    );
+    assert_eq!(
+        thresholds.strategy(&[1, 100, 5000, 70000, 100000]),
+        SwitchStrategy::Tree
+    );
+    // Five cases are dense enough in a range of 12 values, but not of 13.
+    assert_eq!(
+        thresholds.strategy(&[0, 3, 6, 9, 11]),
+        SwitchStrategy::JumpTable
+    );
+    assert_eq!(thresholds.strategy(&[0, 3, 6, 9, 12]), SwitchStrategy::Tree);
+
+    // `@few` is compared in turn, `@dense` is kept for a jump table, and `@sparse` is bisected
+    // into two chains of four comparisons.
+    test_opt(
+        &Path::new("examples/switch_lowering/strategies.input.ir"),
+        &Path::new("examples/switch_lowering/strategies.output.ir"),
+        &mut LowerSwitch::default(),
+    );
+    let mut ir = parse("examples/switch_lowering/strategies.output.ir");
+    assert!(!LowerSwitch::default().optimize(&mut ir));
+
+    let ir = parse("examples/switch_lowering/sparse.ir");
+    let lower = |switch_thresholds: SwitchThresholds| {
+        let options = CompileOptions {
+            switch_thresholds,
+            ..Default::default()
+        };
+        let mut lowered = ir.clone();
+        let changed = LowerSwitch::new(LowerSwitchInner::new(options)).optimize(&mut lowered);
+        assert_eq!(ir::verify(&lowered), Ok(()));
+        (changed, lowered)
+    };
+    let (changed, tree) = lower(thresholds);
+    assert!(changed);
+    let (changed, linear) = lower(SwitchThresholds {
+        max_linear_cases: 50,
+        ..thresholds
+    });
+    assert!(changed);
+    let (changed, _) = lower(SwitchThresholds {
+        min_table_density: 0,
+        ..thresholds
+    });
+    assert!(!changed);
+
+    // Every case, the values next to them and the extremes are dispatched as by the `switch`.
+    let run = |ir: &ir::TranslationUnit, value: i128| {
+        let arg = ir::Value::Int {
+            value: value as u128,
+            width: 32,
+            is_signed: true,
+        };
+        ir::interp_bounded(ir, vec![arg], CompileOptions::default(), 1_000)
+            .unwrap()
+            .unwrap()
+    };
+    let (_, definition) = ir.decls["main"].get_function().unwrap();
+    let cases = match &definition.as_ref().unwrap().blocks[&ir::BlockId(0)].exit {
+        ir::BlockExit::Switch { cases, .. } => cases
+            .iter()
+            .map(|(case, _)| ir::sign_extension(case.get_int().unwrap().0, 32) as i128)
+            .collect::<Vec<_>>(),
+        exit => panic!("unexpected exit: {exit}"),
+    };
+    assert_eq!(cases.len(), 50);
+    let mut values = vec![i32::MIN as i128, i32::MAX as i128];
+    for case in &cases {
+        values.extend([case - 1, *case, case + 1]);
+    }
+
+    let (mut tree_branches, mut linear_branches) = (0, 0);
+    for value in values {
+        let expected = run(&ir, value);
+        let (actual_tree, actual_linear) = (run(&tree, value), run(&linear, value));
+        assert_eq!(actual_tree.value, expected.value, "dispatching {value}");
+        assert_eq!(actual_linear.value, expected.value, "dispatching {value}");
+        tree_branches = tree_branches.max(actual_tree.branches);
+        linear_branches = linear_branches.max(actual_linear.branches);
+    }
+
+    // The tree of the 50 cases takes at most 4 `cmp lt` down to a leaf of at most 4 `cmp eq`,
+    // where the chain compares with every case before the default.
+    assert_eq!((tree_branches, linear_branches), (8, 50));
+}
+
+#[test]
+fn test_examples_strcmp_dispatch() {
+    let parse = |path: &str| {
+        let path = Path::new(path);
+        IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()))
+    };
+    let run = |ir: &ir::TranslationUnit| {
+        ir::interp_bounded(ir, Vec::new(), CompileOptions::default(), 1_000_000)
+            .unwrap()
+            .unwrap()
+    };
+    let strcmps = |execution: &ir::Execution| execution.calls["__builtin_strcmp"];
+
+    // The ten commands are dispatched by their first characters, then by their second ones if
+    // they share them, e.g., `push` and `pop`, and `q` is told from `quit` by its null character.
+    test_opt(
+        &Path::new("examples/strcmp_dispatch/commands.input.ir"),
+        &Path::new("examples/strcmp_dispatch/commands.output.ir"),
+        &mut StrcmpDispatch::default(),
+    );
+    let mut dispatched = parse("examples/strcmp_dispatch/commands.output.ir");
+    assert!(!StrcmpDispatch::default().optimize(&mut dispatched));
+
+    // `@main` dispatches each command and seven other strings, e.g., `""`, `addx` and `qu`.
+    let ir = parse("examples/strcmp_dispatch/commands.input.ir");
+    let (expected, actual) = (run(&ir), run(&dispatched));
+    assert_eq!(actual.value, expected.value);
+    assert_eq!(actual.globals, expected.globals);
+    let results = match &actual.globals["results"] {
+        ir::Value::Array { values, .. } => values
+            .iter()
+            .map(|value| ir::sign_extension(value.get_int().unwrap().0, 32) as i32)
+            .collect::<Vec<_>>(),
+        value => panic!("unexpected value: {value:?}"),
+    };
+    assert_eq!(
+        results,
+        [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, -1, -1, -1, -1, -1, -1, -1]
+    );
+
+    // The chain compares a string with up to all the ten commands, where the dispatch compares it
+    // with at most one of them, or none if it matches no prefix or is `q`.
+    assert_eq!((strcmps(&expected), strcmps(&actual)), (125, 14));
+
+    // The call to `@log` between the comparisons with `sub` and `mul` cuts the chain: the first
+    // two comparisons are kept, and the others are dispatched after the call.
+    let ir = parse("examples/strcmp_dispatch/side_effect.ir");
+    let mut dispatched = ir.clone();
+    assert!(StrcmpDispatch::default().optimize(&mut dispatched));
+    assert_eq!(ir::verify(&dispatched), Ok(()));
+    let (_, definition) = dispatched.decls["dispatch"].get_function().unwrap();
+    let definition = definition.as_ref().unwrap();
+    for bid in [0, 2, 4] {
+        assert!(definition.blocks.contains_key(&ir::BlockId(bid)));
+    }
+    assert!(!definition.blocks.contains_key(&ir::BlockId(6)));
+    assert!(matches!(
+        &*definition.blocks[&ir::BlockId(4)].instructions[0],
+        ir::Instruction::Call { callee, .. }
+            if callee.get_constant().and_then(ir::Constant::get_global_variable_name)
+                == Some("log".to_string())
+    ));
+
+    let (expected, actual) = (run(&ir), run(&dispatched));
+    assert_eq!(actual.value, expected.value);
+    assert_eq!(actual.globals, expected.globals);
+    assert_eq!(actual.calls["log"], expected.calls["log"]);
+    assert_eq!((strcmps(&expected), strcmps(&actual)), (20, 14));
+
+    // Dispatching preserves the behavior of the programs without such chains.
+    for dir in ["examples/ir0", "examples/opt"] {
+        test_dir(Path::new(dir), OsStr::new("ir"), |path| {
+            let ir = IrParse::default()
+                .translate(&path)
+                .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+            let run = |ir: &ir::TranslationUnit| {
+                ir::interp_bounded(ir, Vec::new(), CompileOptions::default(), 1_000_000)
+            };
+            let expected = some_or!(ok_or!(run(&ir), return), return);
+
+            let mut dispatched = ir.clone();
+            let _ = StrcmpDispatch::default().optimize(&mut dispatched);
+            let actual = run(&dispatched).unwrap().unwrap();
+            assert_eq!(actual.value, expected.value);
+            assert_eq!(actual.globals, expected.globals);
+        });
+    }
+}
+
+#[test]
+fn test_examples_dataflow() {
+    use dataflow::{Analysis, Liveness, Reaching, ReachingConstants};
+
+    // `b1` heads a loop, and the unreachable `b4` jumps to it.
+    let path = Path::new("examples/dataflow/loop.ir");
+    let ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let (_, definition) = ir.decls["loop"].get_function().unwrap();
+    let definition = definition.as_ref().unwrap();
+    let (b0, b1, b2, b3, b4) = (
+        ir::BlockId(0),
+        ir::BlockId(1),
+        ir::BlockId(2),
+        ir::BlockId(3),
+        ir::BlockId(4),
+    );
+
+    let liveness = dataflow::solve(&Liveness, definition);
+    let live = |rids: &[ir::RegisterId]| rids.iter().copied().collect::<HashSet<_>>();
+    let (i, step) = (ir::RegisterId::arg(b1, 0), ir::RegisterId::arg(b1, 1));
+    assert_eq!(liveness.state_in(b0), &live(&[]));
+    assert_eq!(liveness.state_out(b0), &live(&[]));
+    assert_eq!(liveness.state_in(b1), &live(&[]));
+    assert_eq!(liveness.state_out(b1), &live(&[i, step]));
+    assert_eq!(liveness.state_in(b2), &live(&[i, step]));
+    assert_eq!(liveness.state_in(b3), &live(&[i]));
+    assert_eq!(liveness.state_in(b4), &live(&[]));
+    assert_eq!(
+        liveness.points(&Liveness, definition, b2),
+        vec![live(&[i, step]), live(&[ir::RegisterId::temp(b2, 0), step])],
+    );
+
+    // `step` is 3 along every path reaching the loop, whereas `i` varies along the latch.
+    let constants = dataflow::solve(&ReachingConstants, definition);
+    let three = Reaching::Constant(ir::Constant::int(3, ir::Dtype::INT));
+    assert_eq!(
+        constants.state_out(b0).get(&ir::RegisterId::temp(b0, 0)),
+        Some(&three),
+    );
+    assert_eq!(constants.state_in(b1).get(&step), Some(&three));
+    assert_eq!(constants.state_in(b1).get(&i), Some(&Reaching::Varying));
+    assert_eq!(
+        constants.state_out(b2).get(&ir::RegisterId::temp(b2, 0)),
+        Some(&Reaching::Varying),
+    );
+    assert!(constants.state_in(b4).is_empty());
+    assert_eq!(
+        ReachingConstants.bottom(definition),
+        *constants.state_out(b4)
+    );
+}
+
+#[test]
+fn test_examples_opcode_histogram() {
+    let path = Path::new("examples/sink/loop.ir");
+    let compile = || {
+        IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()))
+    };
+
+    let ir = compile();
+    let expected = [
+        ("add", 2),
+        ("br", 2),
+        ("cmp gt", 1),
+        ("cmp lt", 1),
+        ("mul", 1),
+        ("ret", 2),
+    ];
+    assert_eq!(ir.opcode_histogram(), expected.into_iter().collect());
+    assert_eq!(ir.opcode_histograms()["loop"], ir.opcode_histogram());
+    assert_eq!(compile().opcode_histogram(), ir.opcode_histogram());
+}
+
+#[test]
+fn test_nesting_limits() {
+    use lang_c::ast;
+    use lang_c::span::{Node, Span};
+
+    fn node<T>(node: T) -> Node<T> {
+        Node::new(node, Span::none())
+    }
+
+    fn declarator(derived: Vec<Node<ast::DerivedDeclarator>>) -> ast::Declarator {
+        ast::Declarator {
+            kind: node(ast::DeclaratorKind::Identifier(node(ast::Identifier {
+                name: "x".to_string(),
+            }))),
+            derived,
+            extensions: Vec::new(),
+        }
+    }
+
+    fn one() -> ast::Expression {
+        ast::Expression::Constant(Box::new(node(ast::Constant::Integer(ast::Integer {
+            base: ast::IntegerBase::Decimal,
+            number: Box::from("1"),
+            suffix: ast::IntegerSuffix {
+                size: ast::IntegerSize::Int,
+                unsigned: false,
+                imaginary: false,
+            },
+        }))))
+    }
+
+    fn too_complex<T>(result: Result<T, ir::DtypeError>) -> bool {
+        matches!(result, Err(ir::DtypeError::TooComplex { limit }) if limit == max_nesting_depth())
+    }
+
+    // `int **...*x` and `int x[1][1]...[1]`, rejected before their types are built.
+    let pointers = (0..10_000)
+        .map(|_| node(ast::DerivedDeclarator::Pointer(Vec::new())))
+        .collect();
+    assert!(too_complex(
+        ir::Dtype::INT.with_ast_declarator(&declarator(pointers))
+    ));
+    let arrays = (0..10_000)
+        .map(|_| {
+            node(ast::DerivedDeclarator::Array(node(ast::ArrayDeclarator {
+                qualifiers: Vec::new(),
+                size: ast::ArraySize::VariableExpression(Box::new(node(one()))),
+            })))
+        })
+        .collect();
+    assert!(too_complex(
+        ir::Dtype::INT.with_ast_declarator(&declarator(arrays))
+    ));
+
+    // Pointers to a typedef of pointers, each within the limit but not together.
+    let mut pointer = ir::Dtype::INT;
+    for _ in 0..max_nesting_depth() {
+        pointer = ir::Dtype::pointer(pointer);
+    }
+    let typedefs = [("T".to_string(), pointer.clone())].into_iter().collect();
+    let typedef = ir::Dtype::pointer(ir::Dtype::typedef("T".to_string()));
+    assert!(too_complex(typedef.resolve_typedefs(&typedefs)));
+
+    // The IR writer rejects the types nested too deeply.
+    let ir = ir::TranslationUnit {
+        decls: [(
+            "x".to_string(),
+            ir::Declaration::Variable {
+                dtype: ir::Dtype::pointer(pointer),
+                initializer: None,
+                is_extern: false,
+                is_static: false,
+            },
+        )]
+        .into_iter()
+        .collect(),
+        structs: Default::default(),
+    };
+    assert!(write(&ir, &mut Vec::new()).is_err());
+
+    // The C writer rejects the expressions nested too deeply, i.e., `int x = --...-1;`.
+    let unit = |depth| {
+        let mut expression = one();
+        for _ in 0..depth {
+            expression =
+                ast::Expression::UnaryOperator(Box::new(node(ast::UnaryOperatorExpression {
+                    operator: node(ast::UnaryOperator::Minus),
+                    operand: Box::new(node(expression)),
+                })));
+        }
+        let declaration = ast::Declaration {
+            specifiers: vec![node(ast::DeclarationSpecifier::TypeSpecifier(node(
+                ast::TypeSpecifier::Int,
+            )))],
+            declarators: vec![node(ast::InitDeclarator {
+                declarator: node(declarator(Vec::new())),
+                initializer: Some(node(ast::Initializer::Expression(Box::new(node(
+                    expression,
+                ))))),
+            })],
+        };
+        ast::TranslationUnit(vec![node(ast::ExternalDeclaration::Declaration(node(
+            declaration,
+        )))])
+    };
+    assert!(write(&unit(10), &mut Vec::new()).is_ok());
+    assert!(write(&unit(1_000), &mut Vec::new()).is_err());
+
+    // The limit is that of the current thread.
+    set_max_nesting_depth(8);
+    assert!(write(&unit(10), &mut Vec::new()).is_err());
+    let depth = std::thread::spawn(max_nesting_depth).join().unwrap();
+    assert_eq!(depth, DEFAULT_MAX_NESTING_DEPTH);
+    set_max_nesting_depth(DEFAULT_MAX_NESTING_DEPTH);
+
+    // Types within the limit are unaffected, e.g., `int *x[2][3]` is `[2 x [3 x i32*]]`.
+    let size = |size: &str| {
+        let mut expression = one();
+        if let ast::Expression::Constant(constant) = &mut expression {
+            if let ast::Constant::Integer(integer) = &mut constant.node {
+                integer.number = Box::from(size);
+            }
+        }
+        node(ast::DerivedDeclarator::Array(node(ast::ArrayDeclarator {
+            qualifiers: Vec::new(),
+            size: ast::ArraySize::VariableExpression(Box::new(node(expression))),
+        })))
+    };
+    let derived = vec![
+        node(ast::DerivedDeclarator::Pointer(Vec::new())),
+        size("2"),
+        size("3"),
+    ];
+    let dtype = ir::Dtype::INT
+        .with_ast_declarator(&declarator(derived))
+        .unwrap()
+        .into_inner();
+    let expected = ir::Dtype::array(ir::Dtype::array(ir::Dtype::pointer(ir::Dtype::INT), 2), 3);
+    assert_eq!(dtype, expected);
+    assert_eq!(dtype.to_string(), "[2 x [3 x i32*]]");
+}
+
+#[test]
+fn test_dtype_compatibility() {
+    let int = ir::Dtype::INT;
+    let const_int = ir::Dtype::INT.set_const(true);
+    let named = |name: &str, fields| ir::Dtype::structure(Some(name.to_string()), fields);
+    let field = |name: &str, dtype| ir::Named::new(Some(name.to_string()), dtype);
+    let node_fields = Some(vec![field("next", ir::Dtype::pointer(named("Node", None)))]);
+    let anonymous = |name: &str| ir::Dtype::structure(None, Some(vec![field(name, int.clone())]));
+    let function = |params| ir::Dtype::function(int.clone(), params);
+
+    for (lhs, rhs, expected) in [
+        (int.clone(), int.clone(), true),
+        // Qualifiers matter, except at the top level of parameters.
+        (int.clone(), const_int.clone(), false),
+        (
+            function(vec![const_int.clone()]),
+            function(vec![int.clone()]),
+            true,
+        ),
+        (
+            ir::Dtype::pointer(const_int.clone()),
+            ir::Dtype::pointer(int.clone()),
+            false,
+        ),
+        (
+            function(vec![ir::Dtype::pointer(int.clone()).set_restrict(true)]),
+            function(vec![ir::Dtype::pointer(int.clone())]),
+            true,
+        ),
+        (int.clone(), ir::Dtype::LONG, false),
+        (int.clone(), int.set_signed(false), false),
+        (ir::Dtype::FLOAT, ir::Dtype::DOUBLE, false),
+        (ir::Dtype::enumeration(None, None), int.clone(), true),
+        (ir::Dtype::enumeration(None, None), ir::Dtype::CHAR, false),
+        // Arrays of the same size, or of an unknown one.
+        (
+            ir::Dtype::pointer(ir::Dtype::array(int.clone(), 3)),
+            ir::Dtype::pointer(ir::Dtype::array(int.clone(), 3)),
+            true,
+        ),
+        (
+            ir::Dtype::pointer(ir::Dtype::array(int.clone(), 3)),
+            ir::Dtype::pointer(ir::Dtype::array(int.clone(), 4)),
+            false,
+        ),
+        (
+            ir::Dtype::incomplete_array(int.clone()),
+            ir::Dtype::array(int.clone(), 3),
+            true,
+        ),
+        // The same return type and parameters.
+        (
+            function(vec![int.clone(), ir::Dtype::DOUBLE]),
+            function(vec![int.clone(), ir::Dtype::DOUBLE]),
+            true,
+        ),
+        (function(vec![int.clone()]), function(Vec::new()), false),
+        (
+            function(vec![int.clone()]),
+            ir::Dtype::function(ir::Dtype::unit(), vec![int.clone()]),
+            false,
+        ),
+        // Structs by their tags, declared or defined, and untagged ones by their members.
+        (
+            named("Node", None),
+            named("Node", node_fields.clone()),
+            true,
+        ),
+        (named("Node", node_fields), named("List", None), false),
+        (
+            named("Node", None),
+            ir::Dtype::union(Some("Node".to_string()), None),
+            false,
+        ),
+        (anonymous("x"), anonymous("x"), true),
+        (anonymous("x"), anonymous("y"), false),
+    ] {
+        assert_eq!(lhs.is_compatible(&rhs), expected, "`{lhs}` and `{rhs}`");
+        assert_eq!(rhs.is_compatible(&lhs), expected, "`{rhs}` and `{lhs}`");
+    }
+}
+
+#[test]
+fn test_dtype_promote_integer() {
+    let promoted = |dtype: ir::Dtype| {
+        let dtype = dtype.promote_integer();
+        (dtype.get_int_width().unwrap(), dtype.is_int_signed())
+    };
+
+    // The integers narrower than `int` are promoted to `int` whether they are signed or not.
+    assert_eq!(promoted(ir::Dtype::BOOL), (32, true));
+    assert_eq!(promoted(ir::Dtype::CHAR), (32, true));
+    assert_eq!(promoted(ir::Dtype::CHAR.set_signed(false)), (32, true));
+    assert_eq!(promoted(ir::Dtype::SHORT), (32, true));
+    assert_eq!(promoted(ir::Dtype::SHORT.set_signed(false)), (32, true));
+
+    // The others are kept.
+    assert_eq!(promoted(ir::Dtype::INT), (32, true));
+    assert_eq!(promoted(ir::Dtype::INT.set_signed(false)), (32, false));
+    assert_eq!(promoted(ir::Dtype::LONG), (64, true));
+    assert_eq!(promoted(ir::Dtype::LONG.set_signed(false)), (64, false));
+    assert!(std::panic::catch_unwind(|| ir::Dtype::FLOAT.promote_integer()).is_err());
+
+    // The operands of the unary operators and the shifts are promoted in constant expressions.
+    let path = Path::new("examples/constexpr/promotion.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|e| panic!("{}", e));
+    let value_of = |name: &str| {
+        let (dtype, initializer) = ir.decls[name].get_variable().unwrap();
+        let value =
+            ir::Value::try_from_initializer(initializer.as_ref().unwrap(), dtype, &ir.structs)
+                .unwrap_or_else(|_| panic!("`{name}` is not initialized with a literal"));
+        value.get_int().expect("non-integer value occurs").0 as u32 as i32
+    };
+    assert_eq!(value_of("negated"), -1);
+    assert_eq!(value_of("complemented"), -1);
+    assert_eq!(value_of("shifted"), -1);
+    assert_eq!(value_of("widened"), 0xff00);
+    assert_eq!(value_of("compared"), 0);
+}
+
+#[test]
+fn test_dtype_from_str() {
+    let point = ir::Dtype::structure(
+        Some("point".to_string()),
+        Some(vec![
+            ir::Named::new(Some("x".to_string()), ir::Dtype::INT),
+            ir::Named::new(Some("y".to_string()), ir::Dtype::INT.set_const(true)),
+        ]),
+    );
+    let flags = ir::Dtype::structure(
+        None,
+        Some(vec![
+            ir::Named::new(Some("mode".to_string()), ir::Dtype::INT.set_signed(false)),
+            ir::Named::new(None, ir::Dtype::union(Some("u".to_string()), None)),
+            ir::Named::new(Some("next".to_string()), ir::Dtype::pointer(point.clone())),
+        ]),
+    )
+    .with_bit_widths(vec![Some(3), None, None]);
+    let function = ir::Dtype::function(
+        ir::Dtype::pointer(ir::Dtype::CHAR.set_const(true)),
+        vec![ir::Dtype::INT, ir::Dtype::DOUBLE, ir::Dtype::unit()],
+    );
+    let dtypes = [
+        ir::Dtype::unit(),
+        ir::Dtype::BOOL,
+        ir::Dtype::LONG.set_signed(false).set_const(true),
+        ir::Dtype::FLOAT.set_const(true),
+        // The qualifiers of the pointers follow their asterisks, with or without each other.
+        ir::Dtype::pointer(ir::Dtype::pointer(ir::Dtype::INT).set_const(true)).set_restrict(true),
+        ir::Dtype::pointer(ir::Dtype::INT.set_const(true))
+            .set_const(true)
+            .set_restrict(true),
+        ir::Dtype::array(ir::Dtype::array(ir::Dtype::SHORT, 4), 3),
+        ir::Dtype::Array {
+            inner: std::sync::Arc::new(ir::Dtype::pointer(ir::Dtype::CHAR)),
+            size: None,
+            align: Some(16),
+        },
+        point.clone(),
+        point.set_const(true),
+        flags.clone(),
+        ir::Dtype::union(None, Some(Vec::new())),
+        ir::Dtype::array(flags, 2),
+        function.clone(),
+        ir::Dtype::pointer(ir::Dtype::function(function, Vec::new())),
+        // Typedef names are told apart from the types of the IR they may be named after.
+        ir::Dtype::typedef("u8".to_string()),
+        ir::Dtype::pointer(
+            ir::Dtype::typedef("T".to_string())
+                .set_restrict(true)
+                .set_const(true),
+        ),
+        ir::Dtype::enumeration(
+            Some("color".to_string()),
+            Some(vec![
+                ("RED".to_string(), -1),
+                ("GREEN".to_string(), 1 << 40),
+            ]),
+        ),
+        ir::Dtype::enumeration(None, None).set_const(true),
+    ];
+    for dtype in dtypes {
+        let text = dtype.to_string();
+        let parsed = text
+            .parse::<ir::Dtype>()
+            .unwrap_or_else(|e| panic!("`{text}` is not parsed: {e}"));
+        assert_eq!(parsed, dtype, "`{text}`");
+    }
+
+    assert_eq!(
+        " i32 *const ".parse::<ir::Dtype>().unwrap(),
+        ir::Dtype::pointer(ir::Dtype::INT).set_const(true)
+    );
+    assert!("i32 i32".parse::<ir::Dtype>().is_err());
+    assert!("struct s:<x>".parse::<ir::Dtype>().is_err());
+}
+
+#[test]
+fn test_dtype_map_inner() {
+    // Widens every `int` nested in the type.
+    fn widen(dtype: ir::Dtype) -> Result<ir::Dtype, ()> {
+        if dtype == ir::Dtype::INT {
+            return Ok(ir::Dtype::LONG);
+        }
+        dtype.map_inner(widen)
+    }
+
+    let point = |x: ir::Dtype| {
+        ir::Dtype::structure(
+            None,
+            Some(vec![
+                ir::Named::new(Some("x".to_string()), x),
+                ir::Named::new(Some("y".to_string()), ir::Dtype::CHAR),
+            ]),
+        )
+    };
+    let function = |int: ir::Dtype| {
+        ir::Dtype::function(
+            ir::Dtype::pointer(int.clone()).set_const(true),
+            vec![ir::Dtype::array(int.clone(), 4), point(int)],
+        )
+    };
+    assert_eq!(
+        widen(function(ir::Dtype::INT)),
+        Ok(function(ir::Dtype::LONG))
+    );
+    // A struct referring to the struct table has no fields to replace.
+    let named = ir::Dtype::structure(Some("s".to_string()), None);
+    assert_eq!(widen(named.clone()), Ok(named.clone()));
+
+    let signature = ir::Dtype::function(ir::Dtype::CHAR, vec![ir::Dtype::INT, ir::Dtype::DOUBLE]);
+    assert_eq!(
+        signature.inner_dtypes(),
+        [&ir::Dtype::CHAR, &ir::Dtype::INT, &ir::Dtype::DOUBLE]
+    );
+    assert_eq!(
+        ir::Dtype::pointer(ir::Dtype::pointer(ir::Dtype::INT)).depth(),
+        3
+    );
+
+    assert!(ir::Dtype::array(ir::Dtype::pointer(named), 4).contains_struct());
+    assert!(!signature.contains_struct());
+    assert!(!signature.contains_function_pointer());
+    assert!(ir::Dtype::function(
+        ir::Dtype::unit(),
+        vec![ir::Dtype::pointer(signature.clone())]
+    )
+    .contains_function_pointer());
+
+    // The parameters of the functions are adjusted after their typedefs are resolved.
+    let typedefs = HashMap::from([
+        ("fn".to_string(), signature.clone()),
+        ("row".to_string(), ir::Dtype::array(ir::Dtype::INT, 4)),
+    ]);
+    let apply = ir::Dtype::function(
+        ir::Dtype::unit(),
+        vec![
+            ir::Dtype::typedef("fn".to_string()),
+            ir::Dtype::typedef("row".to_string()),
+        ],
+    );
+    assert_eq!(
+        apply.resolve_typedefs(&typedefs).unwrap(),
+        ir::Dtype::function(
+            ir::Dtype::unit(),
+            vec![
+                ir::Dtype::pointer(signature),
+                ir::Dtype::pointer(ir::Dtype::INT),
+            ],
+        )
+    );
+}
+
+#[test]
+fn test_type_names() {
+    use lang_c::ast::{Expression, ExternalDeclaration, Initializer};
+
+    // The type names of casts and `sizeof`, their types, and their sizes on RISC-V 64.
+    let type_names = [
+        ("int (*)[4]", "[4 x i32]*", 8),
+        ("int *[3]", "[3 x i32*]", 24),
+        ("void (*)(int, char *)", "[ret:unit params:(i32, i8*)]*", 8),
+        ("int [2][3]", "[2 x [3 x i32]]", 24),
+        ("int (*[2])[3]", "[2 x [3 x i32]*]", 16),
+        ("int (*(*)(void))[4]", "[ret:[4 x i32]* params:()]*", 8),
+        (
+            "char *(*)(const char *, int)",
+            "[ret:i8* params:(const i8*, i32)]*",
+            8,
+        ),
+        ("void (*[4])(void)", "[4 x [ret:unit params:()]*]", 32),
+        ("int (**)(int)", "[ret:i32 params:(i32)]**", 8),
+        ("const int *const", "const i32*const", 8),
+        ("short (*)[2][5]", "[2 x [5 x i16]]*", 8),
+        (
+            "long (*)(int [], void (*)(void))",
+            "[ret:i64 params:(i32*, [ret:unit params:()]*)]*",
+            8,
+        ),
+        ("double *(*[3])[2]", "[3 x [2 x f64*]*]", 24),
+        ("char (*(*)[3])(int)", "[3 x [ret:i8 params:(i32)]*]*", 8),
+        ("unsigned char [5]", "[5 x u8]", 5),
+    ];
+    let source = format!(
+        "unsigned long sizes[{}] = {{{}}};\n",
+        type_names.len(),
+        type_names
+            .iter()
+            .map(|(type_name, _, _)| format!("sizeof({type_name})"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let dir = tempfile::tempdir().expect("temp dir creation failed");
+    let path = dir.path().join("type_names.c");
+    std::fs::write(&path, source).unwrap();
+    let unit = Parse
+        .translate(&path.as_path())
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    let items = match &unit.0[0].node {
+        ExternalDeclaration::Declaration(decl) => {
+            match &decl.node.declarators[0]
+                .node
+                .initializer
+                .as_ref()
+                .unwrap()
+                .node
+            {
+                Initializer::List(items) => items,
+                initializer => panic!("unexpected initializer {initializer:?}"),
+            }
+        }
+        decl => panic!("unexpected declaration {decl:?}"),
+    };
+    assert_eq!(items.len(), type_names.len());
+    for ((type_name, expected, _), item) in type_names.iter().zip(items) {
+        let size_of = match &item.node.initializer.node {
+            Initializer::Expression(expr) => match &expr.node {
+                Expression::SizeOfTy(size_of) => &size_of.node.0,
+                expr => panic!("unexpected expression {expr:?}"),
+            },
+            initializer => panic!("unexpected initializer {initializer:?}"),
+        };
+        let dtype = ir::Dtype::try_from(&size_of.node)
+            .unwrap_or_else(|e| panic!("`{type_name}` is not a type: {e}"));
+        assert_eq!(dtype.to_string(), *expected, "`{type_name}`");
+    }
+
+    // `sizeof` is evaluated with the same types.
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|e| panic!("{}", e));
+    let (dtype, initializer) = ir.decls["sizes"].get_variable().unwrap();
+    let values =
+        match ir::Value::try_from_initializer(initializer.as_ref().unwrap(), dtype, &ir.structs) {
+            Ok(ir::Value::Array { values, .. }) => values,
+            _ => panic!("`sizes` must be initialized with an array"),
+        };
+    for ((type_name, _, size), value) in type_names.iter().zip(values) {
+        let (value, _, _) = value.get_int().expect("non-integer value occurs");
+        assert_eq!(value, *size, "`sizeof({type_name})`");
+    }
+}
+
+#[test]
+fn test_dtype_errors() {
+    use lang_c::ast;
+    use lang_c::driver::{parse_preprocessed, Config, Flavor};
+
+    // The type of the first declarator of the declaration `source`, bypassing the checks of
+    // `Parse`, which rejects some of them before their types are derived.
+    let declared_dtype = |source: &str, flavor: Flavor| -> Result<ir::Dtype, ir::DtypeError> {
+        let config = Config {
+            flavor,
+            ..Config::default()
+        };
+        let parse = parse_preprocessed(&config, source.to_string())
+            .unwrap_or_else(|e| panic!("`{source}` is not parsed: {e:?}"));
+        let declaration = match &parse.unit.0[0].node {
+            ast::ExternalDeclaration::Declaration(declaration) => &declaration.node,
+            _ => panic!("`{source}` must be a declaration"),
+        };
+        let (dtype, _) = ir::Dtype::try_from_ast_declaration_specifiers(&declaration.specifiers)?;
+        dtype
+            .with_ast_declarator(&declaration.declarators[0].node.declarator.node)
+            .map(ir::Named::into_inner)
+    };
+
+    let cases = [
+        (
+            declared_dtype("_Thread_local int x;", Flavor::GnuC11),
+            "unsupported storage class specifier",
+        ),
+        (
+            declared_dtype("__typeof__(int) x;", Flavor::GnuC11),
+            "unsupported type specifier",
+        ),
+        (
+            declared_dtype("int *__attribute__((aligned(8))) p;", Flavor::GnuC11),
+            "unsupported pointer qualifier",
+        ),
+        (
+            declared_dtype("int f(a, b);", Flavor::GnuC11),
+            "K&R function declarators with parameters are not supported",
+        ),
+        (
+            declared_dtype("void f(int a[const 3]);", Flavor::GnuC11),
+            "qualifiers in array declarators are not supported",
+        ),
+        (
+            declared_dtype("int (^b)(void);", Flavor::ClangC11),
+            "block pointers are not supported",
+        ),
+        (
+            ir::Dtype::try_from_ast_declaration_specifiers(&[]).map(|(dtype, _)| dtype),
+            "no type specifier in declaration specifiers",
+        ),
+        (
+            ir::Dtype::function(ir::Dtype::INT, Vec::new())
+                .with_ast_array_size(&ast::ArraySize::Unknown),
+            "declared as an array of functions",
+        ),
+    ];
+    for (result, expected) in cases {
+        match result {
+            Err(ir::DtypeError::Misc { message }) => {
+                assert!(message.starts_with(expected), "{message}")
+            }
+            result => panic!("expected `{expected}`, got {result:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_examples_equiv_by_execution() {
+    let parse = |path: &str| {
+        let path = Path::new(path);
+        IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()))
+    };
+    let reference = parse("examples/equiv/reference.ir");
+    let optimized = parse("examples/equiv/optimized.ir");
+    let mutant = parse("examples/equiv/mutant.ir");
+
+    let vectors = ir::TestVectors::random(&reference, 200, 42).unwrap();
+    assert_eq!(
+        ir::equiv_by_execution(&reference, &optimized, &vectors),
+        ir::Verdict::Equivalent,
+    );
+
+    // The mutant compares `n >= 0` instead of `n > 0`, which only 0 distinguishes.
+    let input = match ir::equiv_by_execution(&reference, &mutant, &vectors) {
+        ir::Verdict::Different { input, .. } => input,
+        verdict => panic!("unexpected verdict: {verdict:?}"),
+    };
+    let zero = ir::Value::Int {
+        value: 0,
+        width: 32,
+        is_signed: true,
+    };
+    assert_eq!(input, vec![zero]);
+    assert_ne!(
+        ir::interp(&reference, input.clone()),
+        ir::interp(&mutant, input)
+    );
+
+    let vectors = ir::TestVectors {
+        max_steps: 1,
+        ..vectors
+    };
+    assert!(matches!(
+        ir::equiv_by_execution(&reference, &optimized, &vectors),
+        ir::Verdict::Inconclusive { .. }
+    ));
+}
+
+#[test]
+fn test_examples_incomplete_types() {
+    let parse = |path: &str| {
+        let path = Path::new(path);
+        Parse
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()))
+    };
+
+    // Incomplete types may be pointed to, and be the parameters of prototypes.
+    let ir = Irgen::default()
+        .translate(&parse("examples/incomplete/opaque.c"))
+        .unwrap();
+    for name in [
+        "open_handle",
+        "close_handle",
+        "read_handle",
+        "handles",
+        "raw",
+    ] {
+        assert!(ir.decls.contains_key(name), "`{name}` is not declared");
+    }
+
+    // A struct may point to itself, since the pointer is complete before the struct is.
+    let node = ir::Dtype::structure(Some("Node".to_string()), None);
+    assert_eq!(node.size_align_of(&ir.structs), Ok((16, 8)));
+    let (offset, next) = node.get_offset_struct_field("next", &ir.structs).unwrap();
+    assert_eq!((offset, next.to_string()), (0, "struct Node*".to_string()));
+
+    // A tag is defined once, and its first definition is kept.
+    let (ir, errors) =
+        Irgen::default().translate_keep_going(&parse("examples/incomplete/redefinition.c"));
+    let messages = errors
+        .into_iter()
+        .map(|error| error.message.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(messages, ["redefinition of `struct Node`"]);
+    let node = ir.structs["Node"].as_ref().unwrap();
+    let fields = node.get_struct_fields().unwrap().as_ref().unwrap();
+    assert_eq!(fields.len(), 2);
+    assert!(ir.decls.contains_key("node_size"));
+
+    // A tag defined in a block shadows the outer definition until the end of the block, without
+    // redefining it.
+    let path = "examples/incomplete/scoped.c";
+    let scoped = lang_c::driver::parse(&lang_c::driver::Config::default(), path)
+        .unwrap_or_else(|_| panic!("parse failed {path}"));
+    let (ir, errors) = Irgen::default().translate_keep_going(&scoped.unit);
+    let expected = ["inner.value = 3", "outer.id = 5"];
+    assert_eq!(errors.len(), expected.len(), "{errors:#?}");
+    for (error, snippet) in errors.iter().zip(expected) {
+        assert!(matches!(
+            error.message,
+            IrgenErrorMessage::NonModifiableLvalue { .. }
+        ));
+        let span = error
+            .span
+            .expect("the span of the expression must be known");
+        let text = &scoped.source[span.start..span.end];
+        assert!(
+            text.contains(snippet),
+            "`{text}` is not the span of `{snippet}`"
+        );
+    }
+    let pair = ir.structs["pair"].as_ref().unwrap();
+    let fields = pair.get_struct_fields().unwrap().as_ref().unwrap();
+    assert_eq!(fields.len(), 2);
+
+    // But not where their sizes are required.
+    let (_, errors) = Irgen::default().translate_keep_going(&parse("examples/incomplete/errors.c"));
+    let usages = errors
+        .into_iter()
+        .map(|error| match error.message {
+            IrgenErrorMessage::NotConstantExpression { reason } => reason,
+            IrgenErrorMessage::InvalidDtype {
+                dtype_error: ir::DtypeError::IncompleteType { dtype, usage },
+            } => {
+                assert_eq!(dtype.to_string(), "struct Handle");
+                usage
+            }
+            message => panic!("unexpected error: {message}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        usages,
+        [
+            "incomplete type `struct Handle` used as the operand of `sizeof`",
+            "field `inner`",
+            "variable `current`",
+            "parameter `handle`",
+            "the return type of `make_handle`",
+        ]
+    );
+}
+
+#[test]
+fn test_examples_lvalue() {
+    // String literals are rejected by `Parse`, so the sources are parsed as they are.
+    let parse = |path: &str| {
+        lang_c::driver::parse(&lang_c::driver::Config::default(), path)
+            .unwrap_or_else(|_| panic!("parse failed {path}"))
+    };
+
+    // The body is only checked, since lowering it is left as the homework.
+    let valid = parse("examples/lvalue/valid.c");
+    let ir = Irgen::default()
+        .skip_definitions(HashSet::from(["valid".to_string()]))
+        .translate(&valid.unit)
+        .unwrap();
+    assert!(ir.decls.contains_key("valid"));
+
+    let source = parse("examples/lvalue/errors.c");
+    let (_, errors) = Irgen::default().translate_keep_going(&source.unit);
+    let not_modifiable = "left operand of assignment is not a modifiable l-value, as it is";
+    let expected = [
+        (
+            "5 = x",
+            "l-value required as left operand of assignment".to_string(),
+        ),
+        (
+            "a + b) = 3",
+            "l-value required as left operand of assignment".to_string(),
+        ),
+        (
+            r#""str"[0] = 'c'"#,
+            format!("{not_modifiable} a string literal"),
+        ),
+        ("*p = 1", format!("{not_modifiable} `const`-qualified")),
+        ("copy = values", format!("{not_modifiable} an array")),
+        (
+            "*p = q",
+            format!("{not_modifiable} a struct with a `const` member"),
+        ),
+        (
+            "limit++",
+            "increment operand is not a modifiable l-value, as it is `const`-qualified".to_string(),
+        ),
+        (
+            "next_value() += 1",
+            "l-value required as left operand of assignment".to_string(),
+        ),
+        (
+            "&(a + 1",
+            "l-value required as unary '&' operand".to_string(),
+        ),
+    ];
+    assert_eq!(errors.len(), expected.len(), "{errors:#?}");
+
+    for (error, (snippet, message)) in errors.iter().zip(expected) {
+        assert_eq!(error.message.to_string(), message);
+
+        // The span is the one of the offending expression, not of the whole statement.
+        let span = error
+            .span
+            .expect("the span of the expression must be known");
+        let text = &source.source[span.start..span.end];
+        assert!(
+            text.contains(snippet) && !text.contains(';'),
+            "`{text}` is not the span of `{snippet}`"
+        );
+    }
+}
+
+#[test]
+fn test_examples_local_typedef() {
+    use kecc::ir::HasDtype;
+
+    let parse = |path: &str| {
+        lang_c::driver::parse(&lang_c::driver::Config::default(), path)
+            .unwrap_or_else(|_| panic!("parse failed {path}"))
+    };
+
+    // The typedefs of a block are used in declarations, casts and `sizeof` of the block, and the
+    // outer typedef is visible again after it.
+    let valid = parse("examples/local_typedef/valid.c");
+    let ir = Irgen::default()
+        .skip_definitions(HashSet::from(["valid".to_string()]))
+        .translate(&valid.unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+
+    // Neither leaks to the file scope, nor do the tags the check gives to their anonymous structs.
+    assert_eq!(ir.decls["after"].dtype().to_string(), "struct %t0");
+    assert_eq!(ir.structs.keys().collect::<Vec<_>>(), ["%t0"]);
+
+    let source = parse("examples/local_typedef/errors.c");
+    let (_, errors) = Irgen::default().translate_keep_going(&source.unit);
+    let not_modifiable = "left operand of assignment is not a modifiable l-value, as it is";
+    let expected = ["a = 2", "e.id = 1", "*(cint *)&x = 1", "outer = 1"];
+    assert_eq!(errors.len(), expected.len(), "{errors:#?}");
+
+    for (error, snippet) in errors.iter().zip(expected) {
+        assert_eq!(
+            error.message.to_string(),
+            format!("{not_modifiable} `const`-qualified")
+        );
+        let span = error
+            .span
+            .expect("the span of the expression must be known");
+        let text = &source.source[span.start..span.end];
+        assert!(
+            text.contains(snippet) && !text.contains(';'),
+            "`{text}` is not the span of `{snippet}`"
+        );
+    }
+}
+
+#[test]
+fn test_examples_lib() {
+    let path = Path::new("examples/lib/fib.ir");
+    let mut ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+
+    // A library has no `main`, and exports every function it defines.
+    assert_eq!(
+        ir::interp(&ir, Vec::new()),
+        Err(ir::InterpreterError::NoMainFunction)
+    );
+    assert!(DeadFunction::library(Vec::new()).optimize(&mut ir));
+    let names = ir.decls.keys().map(String::as_str).collect::<Vec<_>>();
+    assert_eq!(names, ["fib", "square"]);
+
+    let int = |value: i32| ir::Value::from(value);
+    assert_eq!(ir::call(&ir, "fib", vec![10i32.into()]), Ok(int(55)));
+    assert_eq!(ir::call(&ir, "square", vec![(-3i32).into()]), Ok(int(9)));
+
+    // The arguments are checked against the parameters.
+    for args in [vec![], vec![10i64.into()], vec![1i32.into(), 2i32.into()]] {
+        assert!(matches!(
+            ir::call(&ir, "fib", args),
+            Err(ir::InterpreterError::InvalidCall { .. })
+        ));
+    }
+    assert!(matches!(
+        ir::call(&ir, "main", vec![]),
+        Err(ir::InterpreterError::InvalidCall { .. })
+    ));
+
+    let expected = [("fib", 10), ("square", 3)]
+        .into_iter()
+        .map(|(name, arg)| {
+            let (value, _, _) = ir::call(&ir, name, vec![int(arg)])
+                .unwrap_or_else(|e| panic!("{}", e))
+                .get_int()
+                .expect("non-integer value occurs");
+            value
+        })
+        .sum::<u128>();
+    test_lib_asmgen(path, Path::new("examples/lib/harness.c"), expected as u8);
+}
+
+#[test]
+fn test_examples_extern() {
+    let parse = |path: &str| {
+        let path = Path::new(path);
+        Parse
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()))
+    };
+
+    // `extern` declarations do not replace the definitions, and need no complete types.
+    let ir = Irgen::default()
+        .translate(&parse("examples/extern/declarations.c"))
+        .unwrap();
+    assert!(ir.decls["shared_counter"].is_extern());
+    assert!(ir.decls["current"].is_extern());
+    assert!(!ir.decls["defined"].is_extern());
+    assert!(ir.decls["defined"].get_variable().unwrap().1.is_some());
+    assert!(ir.decls["helper"].get_function().unwrap().1.is_none());
+    assert!(ir.decls["errno_shadow"].is_extern());
+    assert!(ir.decls["errno_shadow"].get_variable().unwrap().1.is_none());
+
+    // `extern` and `typedef` cannot be combined, while a repeated `extern` is allowed.
+    let (ir, errors) = Irgen::default().translate_keep_going(&parse("examples/extern/storage.c"));
+    assert_eq!(errors.len(), 2, "{errors:#?}");
+    for error in &errors {
+        assert!(matches!(
+            &error.message,
+            IrgenErrorMessage::InvalidDtype {
+                dtype_error: ir::DtypeError::Misc { .. }
+            }
+        ));
+    }
+    assert!(ir.decls["twice"].is_extern());
+
+    let (_, errors) = Irgen::default().translate_keep_going(&parse("examples/extern/conflict.c"));
+    assert!(matches!(
+        errors.as_slice(),
+        [IrgenError {
+            message: IrgenErrorMessage::ConflictingDtype { .. },
+            ..
+        }]
+    ));
+
+    let ir = IrParse::default()
+        .translate(&Path::new("examples/extern/harness.ir"))
+        .unwrap();
+    assert_eq!(
+        ir.undefined_symbols().into_iter().collect::<Vec<_>>(),
+        ["helper", "shared_counter"]
+    );
+    let mut buffer = Vec::new();
+    write(&ir, &mut buffer).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    assert!(text.contains("var i32 @shared_counter = extern"));
+
+    // Without the harness, `helper` cannot be called.
+    assert_eq!(
+        ir::interp(&ir, Vec::new()),
+        Err(ir::InterpreterError::NoFunctionDefinition {
+            func_name: "helper".to_string()
+        })
+    );
+
+    let int = |value| ir::Value::Int {
+        value,
+        width: 32,
+        is_signed: true,
+    };
+    let calls = Rc::new(Cell::new(0));
+    let bindings = ir::Bindings::default()
+        .variable("shared_counter", int(20))
+        .function("helper", {
+            let calls = calls.clone();
+            move |args: &[ir::Value]| -> Result<ir::Value, String> {
+                calls.set(calls.get() + 1);
+                let (value, _, _) = args[0].get_int().ok_or("not an integer")?;
+                Ok(int(value * 2 + 1))
+            }
+        });
+    let value =
+        ir::interp_with_bindings(&ir, Vec::new(), CompileOptions::default(), bindings).unwrap();
+    assert_eq!(value, int(41));
+    assert_eq!(calls.get(), 1);
+
+    // The call is left to the linker, which resolves it to the definition in the harness.
+    let (_, definition) = ir.decls["helper"].get_function().unwrap();
+    assert!(definition.is_none());
+}
+
+#[test]
+fn test_examples_static() {
+    let parse = |path: &str| {
+        let path = Path::new(path);
+        Parse
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()))
+    };
+
+    // The static locals are globals of their own, declared even if the bodies are not lowered.
+    let ir = Irgen::default()
+        .skip_definitions(HashSet::from(["next".to_string(), "reset".to_string()]))
+        .translate(&parse("examples/static/storage.c"))
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+    let initializer = |name: &str| match ir.decls[name].get_variable() {
+        Some((_, Some(lang_c::ast::Initializer::Expression(expr)))) => {
+            let (value, _, _) = ir::Constant::try_from(&expr.node)
+                .ok()
+                .and_then(|constant| constant.get_int())
+                .unwrap_or_else(|| panic!("`{name}` is not initialized by an integer"));
+            Some(value)
+        }
+        Some((_, None)) => None,
+        decl => panic!("unexpected declaration of `{name}`: {decl:?}"),
+    };
+    for name in ["counter", "table", "declared_twice"] {
+        assert!(ir.decls[name].is_static(), "{name}");
+        assert!(!ir.decls[name].is_extern(), "{name}");
+    }
+    assert!(!ir.decls["shared"].is_static());
+    assert_eq!(initializer("declared_twice"), Some(4));
+    assert!(!ir.decls.contains_key("hits"));
+    assert!(ir.decls["__static_next_hits"].is_static());
+    assert_eq!(initializer("__static_next_hits"), Some(0));
+    assert_eq!(initializer("__static_reset_hits"), Some(10));
+    assert_eq!(initializer("__static_reset_hits_1"), None);
+
+    // The internal linkage survives a round trip through the IR.
+    let mut buffer = Vec::new();
+    write(&ir, &mut buffer).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    assert!(text.contains("var static i32 @counter = 0"));
+    let temp_dir = tempfile::tempdir().expect("temp dir creation failed");
+    let temp_file_path = temp_dir.path().join("storage.ir");
+    std::fs::write(&temp_file_path, text).unwrap();
+    let parsed = ir::Parse::default()
+        .translate(&temp_file_path)
+        .unwrap_or_else(|_| panic!("parse failed {}", temp_file_path.display()));
+    assert!(parsed.decls["__static_reset_hits_1"].is_static());
+    assert!(!parsed.decls["shared"].is_static());
+    temp_dir.close().expect("temp dir deletion failed");
+
+    let (_, errors) = Irgen::default().translate_keep_going(&parse("examples/static/errors.c"));
+    let messages = errors
+        .iter()
+        .map(|error| error.message.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            "static declaration of `visible` follows non-static declaration",
+            "non-static declaration of `hidden` follows static declaration",
+            "declaration of static variable in `for` loop initial declaration",
+            "invalid storage class for function `helper`",
+        ]
+    );
+}
+
+#[test]
+fn test_examples_symbols() {
+    let path = Path::new("examples/symbols/linkage.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let functions = ["get", "helper", "main"].map(String::from);
+    let ir = Irgen::default()
+        .skip_definitions(HashSet::from(functions))
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+
+    // `helper` keeps the internal linkage of its prototype, also through the IR.
+    for (name, is_static) in [
+        ("shared", false),
+        ("counter", true),
+        ("table", true),
+        ("zeros", false),
+        ("get", false),
+        ("helper", true),
+        ("main", false),
+    ] {
+        assert_eq!(ir.decls[name].is_static(), is_static, "{name}");
+    }
+    let mut buffer = Vec::new();
+    write(&ir, &mut buffer).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    assert!(text.contains("fun static i32 @helper ()"));
+    let temp_dir = tempfile::tempdir().expect("temp dir creation failed");
+    let temp_file_path = temp_dir.path().join("linkage.ir");
+    std::fs::write(&temp_file_path, text).unwrap();
+    let parsed = IrParse::default()
+        .translate(&temp_file_path)
+        .unwrap_or_else(|_| panic!("parse failed {}", temp_file_path.display()));
+    assert!(parsed.decls["helper"].is_static());
+    assert!(!parsed.decls["get"].is_static());
+    temp_dir.close().expect("temp dir deletion failed");
+
+    let mut unit = asm::TranslationUnit {
+        functions: Vec::new(),
+        variables: Vec::new(),
+    };
+    for (name, decl) in &ir.decls {
+        let label = asm::Label(name.clone());
+        match decl {
+            ir::Declaration::Function { .. } => {
+                let ret = vec![asm::Instruction::Pseudo(asm::Pseudo::Ret)];
+                let body = asm::Function::new(vec![asm::Block::new(Some(label.clone()), ret)]);
+                let function = asm::Section::function(label, decl.is_static(), false, body);
+                unit.functions.push(function);
+            }
+            ir::Declaration::Variable { dtype, .. } => {
+                let (size, align) = dtype.size_align_of(&ir.structs).unwrap();
+                unit.variables.push(asm::Section::object(
+                    asm::Variable::zero(label, size),
+                    decl.is_static(),
+                    asm::SectionType::of_variable(decl).expect("`decl` must be defined here"),
+                    align.trailing_zeros() as usize,
+                ));
+            }
+        }
+    }
+    let mut buffer = Vec::new();
+    write(&unit, &mut buffer).unwrap();
+    let asm = String::from_utf8(buffer).unwrap();
+    let expected = std::fs::read_to_string("examples/symbols/linkage.s").unwrap();
+    assert_eq!(asm, expected);
+
+    // Only the symbols with external linkage are `.globl`, and every symbol has a type and a size.
+    let mut directives = HashMap::<&str, Vec<&str>>::new();
+    for line in asm.lines() {
+        let (directive, operands) = some_or!(line.trim().split_once('\t'), continue);
+        let symbol = operands.split(',').next().unwrap();
+        directives.entry(symbol).or_default().push(directive);
+    }
+    for (name, decl) in &ir.decls {
+        let expected = if decl.is_static() {
+            vec![".type", ".size"]
+        } else {
+            vec![".globl", ".type", ".size"]
+        };
+        assert_eq!(directives[name.as_str()], expected, "{name}");
+    }
+    assert_eq!(asm.matches(".p2align\t2").count(), 3);
+    assert!(asm.contains(".size\ttable, 16"));
+    assert!(asm.contains(".size\tmain, .-main"));
+
+    let symbols = some_or!(assembled_symbols(&asm), return);
+    for (name, symbol_type) in [
+        ("shared", 'D'),
+        ("counter", 'd'),
+        ("table", 'r'),
+        ("zeros", 'B'),
+        ("get", 'T'),
+        ("helper", 't'),
+        ("main", 'T'),
+    ] {
+        assert_eq!(symbols[name], symbol_type, "{name}");
+    }
+}
+
+#[test]
+fn test_asm_compressed() {
+    use asm::{
+        DataSize, IType, Immediate, Instruction, Pseudo, RType, Register, RegisterType, SType,
+    };
+
+    // A loop body adding the counter to a sum stored through a pointer, in the given registers.
+    let body = |registers: &[Register]| {
+        let (i, sum, n, ptr) = (registers[0], registers[1], registers[2], registers[3]);
+        vec![
+            Instruction::Pseudo(Pseudo::Li { rd: sum, imm: 0 }),
+            Instruction::IType {
+                instr: IType::LD,
+                rd: n,
+                rs1: ptr,
+                imm: Immediate::Value(8),
+            },
+            Instruction::RType {
+                instr: RType::Add(DataSize::Double),
+                rd: sum,
+                rs1: sum,
+                rs2: Some(i),
+            },
+            Instruction::IType {
+                instr: IType::ADDI,
+                rd: i,
+                rs1: i,
+                imm: Immediate::Value(1),
+            },
+            Instruction::RType {
+                instr: RType::Sub(DataSize::Double),
+                rd: n,
+                rs1: n,
+                rs2: Some(i),
+            },
+            Instruction::SType {
+                instr: SType::SD,
+                rs1: ptr,
+                rs2: sum,
+                imm: Immediate::Value(0),
+            },
+            Instruction::Pseudo(Pseudo::Mv {
+                rd: Register::A0,
+                rs: sum,
+            }),
+            Instruction::Pseudo(Pseudo::Ret),
+        ]
+    };
+
+    // Preferring `x8`-`x15` compresses the loads, the stores and the subtraction as well.
+    let order = Register::allocation_order(RegisterType::Integer, false);
+    assert_eq!(order[0], Register::T0);
+    let estimate = asm::CompressionEstimate::new(&body(&order));
+    assert_eq!((estimate.compressible, estimate.total), (5, 8));
+    let order = Register::allocation_order(RegisterType::Integer, true);
+    assert!(order[..8].iter().all(Register::is_compressible));
+    assert!(!order[8].is_compressible());
+    let estimate = asm::CompressionEstimate::new(&body(&order));
+    assert_eq!((estimate.compressible, estimate.total), (8, 8));
+    assert_eq!(estimate.saved_bytes(), 16);
+
+    // `c.ldsp` reaches 504 bytes above the stack pointer.
+    let load = |offset| Instruction::IType {
+        instr: IType::LD,
+        rd: Register::T0,
+        rs1: Register::Sp,
+        imm: Immediate::Value(offset),
+    };
+    assert!(load(504).is_compressible());
+    assert!(!load(508).is_compressible());
+    assert!(!load(512).is_compressible());
+
+    let label = asm::Label("f".to_string());
+    let block = asm::Block::new(Some(label.clone()), body(&order));
+    let mut unit = asm::TranslationUnit {
+        functions: vec![asm::Section::function(
+            label,
+            false,
+            false,
+            asm::Function::new(vec![block]),
+        )],
+        variables: Vec::new(),
+    };
+    unit.enable_compression();
+    assert_eq!(unit.compression_estimate(), estimate);
+    let mut buffer = Vec::new();
+    write(&unit, &mut buffer).unwrap();
+    let asm = String::from_utf8(buffer).unwrap();
+    assert!(asm.contains(".option\trvc"));
+    assert!(asm.contains(".p2align\t1"));
+    if let Some(symbols) = assembled_symbols(&asm) {
+        assert_eq!(symbols["f"], 'T');
+    }
+}
+
+#[test]
+fn test_examples_stack_protect() {
+    let options = CompileOptions {
+        instrumentation: Instrumentation::StackProtect,
+        ..Default::default()
+    };
+
+    // Writing past the end of `buf` aborts with the message of `__kecc_stack_fail`.
+    let path = Path::new("examples/stack_protect/overflow.ir");
+    let ir = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let error = ir::interp_with_options(&ir, Vec::new(), options).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains(Instrumentation::STACK_FAIL_MESSAGE));
+
+    let mut buffer = Vec::new();
+    write(&asm::Section::stack_fail(), &mut buffer).unwrap();
+    write(&asm::Section::stack_fail_message(), &mut buffer).unwrap();
+    let stub = String::from_utf8(buffer).unwrap();
+    assert!(stub.contains("__kecc_stack_fail:"));
+    assert!(stub.contains("call\twrite"));
+
+    // Programs staying in the bounds of their frames are unaffected.
+    test_dir(Path::new("examples/opt"), OsStr::new("ir"), |path| {
+        let ir = IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+        let expected = ok_or!(ir::interp(&ir, Vec::new()), return);
+        assert_eq!(
+            ir::interp_with_options(&ir, Vec::new(), options),
+            Ok(expected)
+        );
+    });
+}
+
+#[test]
+fn test_examples_cache() {
+    let dir = tempfile::tempdir().expect("temp dir creation failed");
+    let compile = |path: &Path, context: &str, unit_keys: bool| {
+        let unit = Parse
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+        let mut cache = Cache::new(dir.path(), context.to_string()).unwrap();
+        if unit_keys {
+            cache = cache.with_unit_keys();
+        }
+        let mut ir = cache
+            .skip_cached(&unit, Irgen::default())
+            .translate(&unit)
+            .unwrap();
+        let _ = O1::default().optimize(&mut ir);
+        cache.complete(&mut ir).unwrap();
+
+        let mut output = Vec::new();
+        write(&ir, &mut output).unwrap();
+        (output, cache.stats())
+    };
+    let stats = |hits, misses| CacheStats { hits, misses };
+
+    let base = Path::new("examples/cache/base.c");
+    let (output, first) = compile(base, "", false);
+    assert_eq!(first, stats(0, 3));
+    let (cached, second) = compile(base, "", false);
+    assert_eq!(second, stats(3, 0));
+
+    // The cached functions are the same as the uncached ones.
+    let unit = Parse.translate(&base).unwrap();
+    let mut ir = Irgen::default().translate(&unit).unwrap();
+    let _ = O1::default().optimize(&mut ir);
+    let mut uncached = Vec::new();
+    write(&ir, &mut uncached).unwrap();
+    assert_eq!(output, uncached);
+    assert_eq!(cached, uncached);
+
+    // Only `square` is edited.
+    let edited = Path::new("examples/cache/edited.c");
+    let (_, stats_edited) = compile(edited, "", false);
+    assert_eq!(stats_edited, stats(2, 1));
+
+    // Another context, e.g., other options or another target, misses every function.
+    let (_, other) = compile(base, "riscv32", false);
+    assert_eq!(other, stats(0, 3));
+
+    // Keyed on the whole unit, editing `square` misses every function.
+    let (_, first) = compile(base, "", true);
+    assert_eq!(first, stats(0, 3));
+    let (_, second) = compile(base, "", true);
+    assert_eq!(second, stats(3, 0));
+    let (_, edited) = compile(edited, "", true);
+    assert_eq!(edited, stats(0, 3));
+}
+
+#[test]
+fn test_examples_artifacts() {
+    let path = Path::new("examples/c/fibonacci.c");
+    let options = CompileOptions::default();
+    let artifacts = compile_artifacts(path, &options, &mut O1::default(), &ArtifactKind::ALL)
+        .unwrap_or_else(|error| panic!("{error}"));
+    for kind in ArtifactKind::ALL {
+        let artifact = artifacts
+            .get(kind)
+            .unwrap_or_else(|| panic!("`{kind}` is missing"));
+        assert_eq!(read_stamp(artifact), Some(artifacts.stamp.as_str()));
+    }
+
+    // The artifacts are written side by side, and the IR parses back with its stamp.
+    let dir = tempfile::tempdir().expect("temp dir creation failed");
+    let paths = artifacts.write_files(path, Some(dir.path())).unwrap();
+    assert_eq!(
+        paths,
+        ["fibonacci.ir", "fibonacci.opt.ir", "fibonacci.s"].map(|name| dir.path().join(name))
+    );
+    let opt_ir = ir::Parse::default()
+        .translate(&paths[1])
+        .unwrap_or_else(|_| panic!("parse failed {}", paths[1].display()));
+    dir.close().expect("temp dir deletion failed");
+
+    // Every function of the assembly is defined in the optimized IR.
+    let functions = artifacts
+        .asm
+        .as_deref()
+        .unwrap()
+        .lines()
+        .filter_map(|line| {
+            line.trim()
+                .strip_prefix(".type\t")?
+                .strip_suffix(", @function")
+        })
+        .collect::<Vec<_>>();
+    assert!(functions.contains(&"main"));
+    for function in functions {
+        let definition = opt_ir
+            .decls
+            .get(function)
+            .and_then(|decl| decl.get_function()?.1.as_ref());
+        assert!(
+            definition.is_some(),
+            "`{function}` is not defined in the IR"
+        );
+    }
+
+    // The stamp changes with the source and the options.
+    let source = std::fs::read(path).unwrap();
+    assert_eq!(source_stamp(&source, &options), artifacts.stamp);
+    let strict_align = CompileOptions {
+        strict_align: true,
+        ..CompileOptions::default()
+    };
+    assert_ne!(source_stamp(&source, &strict_align), artifacts.stamp);
+    let rv32 = CompileOptions {
+        target_data: TargetData::RV32,
+        ..CompileOptions::default()
+    };
+    assert_ne!(source_stamp(&source, &rv32), artifacts.stamp);
+    assert_ne!(
+        source_stamp(b"int main() { return 0; }", &options),
+        artifacts.stamp
+    );
+}
+
+#[test]
+fn test_examples_min_max() {
+    test_opt(
+        &Path::new("examples/min_max/select.input.ir"),
+        &Path::new("examples/min_max/select.output.ir"),
+        &mut MinMax::default(),
+    );
+
+    let parse = |path: &str| {
+        IrParse::default()
+            .translate(&Path::new(path))
+            .unwrap_or_else(|_| panic!("parse failed {path}"))
+    };
+    let input = parse("examples/min_max/select.input.ir");
+    let output = parse("examples/min_max/select.output.ir");
+    assert_eq!(ir::verify(&output), Ok(()));
+    let result = ir::interp(&output, Vec::new()).unwrap();
+    assert_eq!(result.get_int(), Some((318, 32, true)));
+    assert_eq!(ir::interp(&input, Vec::new()), Ok(result));
+
+    // `abs` of the minimum integer wraps around, unless signed overflow is an error.
+    let abs_min = parse("examples/min_max/abs_min.ir");
+    let (value, _, _) = ir::interp(&abs_min, Vec::new()).unwrap().get_int().unwrap();
+    assert_eq!(value as i32, i32::MIN);
+    let options = CompileOptions {
+        overflow_policy: OverflowPolicy::Trap,
+        ..Default::default()
+    };
+    assert!(ir::interp_with_options(&abs_min, Vec::new(), options).is_err());
+
+    // Folding preserves the behavior of the programs.
+    test_dir(Path::new("examples/opt"), OsStr::new("ir"), |path| {
+        let ir = IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+        let expected = ok_or!(ir::interp(&ir, Vec::new()), return);
+
+        let mut folded = ir.clone();
+        let _ = MinMax::default().optimize(&mut folded);
+        assert_eq!(ir::verify(&folded), Ok(()));
+        assert_eq!(ir::interp(&folded, Vec::new()), Ok(expected));
+    });
+}
+
+#[test]
+fn test_examples_coverage_hooks() {
+    use kecc::ir::HasDtype;
+
+    // The statement hook runs before a statement is lowered, so the body of `main` is counted even
+    // while the statements in it are not lowered yet.
+    let unit = Parse
+        .translate(&Path::new("examples/coverage/loop.c"))
+        .expect("parse failed");
+    let mut irgen = Irgen::default().with_hooks(Box::<StatementCoverage>::default());
+    let (ir, errors) = irgen.translate_keep_going(&unit);
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(errors[0].message, IrgenErrorMessage::Misc { .. }));
+    assert_eq!(
+        ir.decls[StatementCoverage::COUNTERS].dtype(),
+        ir::Dtype::array(StatementCoverage::COUNTER_DTYPE, 1)
+    );
+
+    // Without hooks, no counter is declared.
+    let (ir, _) = Irgen::default().translate_keep_going(&unit);
+    assert!(!ir.decls.contains_key(StatementCoverage::COUNTERS));
+}
+
+#[test]
+#[ignore = "needs the statements and the expressions to be lowered by the IR generator"]
+fn test_examples_coverage() {
+    use kecc::ir::HasDtype;
+
+    let unit = Parse
+        .translate(&Path::new("examples/coverage/loop.c"))
+        .expect("parse failed");
+    let mut irgen = Irgen::default().with_hooks(Box::<StatementCoverage>::default());
+    let ir = irgen.translate(&unit).unwrap();
+    assert!(irgen.take_hooks().is_some());
+    assert_eq!(
+        ir::interp(&ir, Vec::new()).unwrap().get_int(),
+        Some((20, 32, true))
+    );
+
+    // The executions of the statements in source order: the body, the loop and its body, the
+    // conditional, the even branch and its expression, the odd branch and its `continue`, and the
+    // return.
+    let expected = [1, 1, 10, 10, 5, 5, 5, 5, 1];
+    let decl = &ir.decls[StatementCoverage::COUNTERS];
+    assert_eq!(
+        decl.dtype(),
+        ir::Dtype::array(StatementCoverage::COUNTER_DTYPE, expected.len())
+    );
+
+    // Reads each counter by returning it from `main` instead of its value.
+    for (index, expected) in expected.into_iter().enumerate() {
+        let mut counted = ir.clone();
+        let (_, definition) = counted
+            .decls
+            .get_mut("main")
+            .and_then(ir::Declaration::get_function_mut)
+            .unwrap();
+        for (bid, block) in &mut definition.as_mut().unwrap().blocks {
+            if !matches!(block.exit, ir::BlockExit::Return { .. }) {
+                continue;
+            }
+
+            let counter = ir::Operand::constant(ir::Constant::global_variable(
+                StatementCoverage::COUNTERS.to_string(),
+                StatementCoverage::COUNTER_DTYPE,
+            ));
+            let offset = (index * ir::Dtype::SIZE_OF_LONGLONG) as u128;
+            let instructions = [
+                ir::Instruction::GetElementPtr {
+                    ptr: counter.clone(),
+                    offset: ir::Operand::constant(ir::Constant::int(offset, ir::Dtype::LONG)),
+                    dtype: counter.dtype(),
+                },
+                ir::Instruction::Load {
+                    ptr: ir::Operand::register(
+                        ir::RegisterId::temp(*bid, block.instructions.len()),
+                        counter.dtype(),
+                    ),
+                },
+                ir::Instruction::TypeCast {
+                    value: ir::Operand::register(
+                        ir::RegisterId::temp(*bid, block.instructions.len() + 1),
+                        StatementCoverage::COUNTER_DTYPE,
+                    ),
+                    target_dtype: ir::Dtype::INT,
+                },
+            ];
+            let value = ir::Operand::register(
+                ir::RegisterId::temp(*bid, block.instructions.len() + 2),
+                ir::Dtype::INT,
+            );
+            block
+                .instructions
+                .extend(instructions.map(|instruction| ir::Named::new(None, instruction)));
+            block.exit = ir::BlockExit::Return { value };
+        }
+
+        let count = ir::interp(&counted, Vec::new()).unwrap();
+        assert_eq!(
+            count.get_int(),
+            Some((expected, 32, true)),
+            "statement {index}"
+        );
+    }
+
+    // Without hooks, no counter is declared.
+    let ir = Irgen::default().translate(&unit).unwrap();
+    assert!(!ir.decls.contains_key(StatementCoverage::COUNTERS));
+}
+
+#[test]
+fn test_examples_split_cold() {
+    let label = |name: &str| asm::Label(name.to_string());
+    let branch = |instr, rs1, rs2, target| asm::Instruction::BType {
+        instr,
+        rs1,
+        rs2,
+        imm: label(target),
+    };
+    let li = |imm| {
+        asm::Instruction::Pseudo(asm::Pseudo::Li {
+            rd: asm::Register::A0,
+            imm,
+        })
+    };
+    let call = |callee| {
+        asm::Instruction::Pseudo(asm::Pseudo::Call {
+            offset: label(callee),
+        })
+    };
+
+    // The error path calling `abort` is branched to, and the failure of the canary check falls
+    // through from the epilogue.
+    let blocks = vec![
+        asm::Block::new(
+            Some(label("check")),
+            vec![branch(
+                asm::BType::Beq,
+                asm::Register::A0,
+                asm::Register::Zero,
+                ".check_L1",
+            )],
+        ),
+        asm::Block::new(
+            Some(label(".check_L2")),
+            vec![
+                li(0),
+                branch(
+                    asm::BType::Beq,
+                    asm::Register::T5,
+                    asm::Register::T6,
+                    ".check_L3",
+                ),
+                call(asm::Function::STACK_FAIL),
+            ],
+        ),
+        asm::Block::new(
+            Some(label(".check_L3")),
+            vec![asm::Instruction::Pseudo(asm::Pseudo::Ret)],
+        ),
+        asm::Block::new(Some(label(".check_L1")), vec![li(1), call("abort")]),
+    ];
+    let header = vec![
+        asm::Directive::Globl(label("check")),
+        asm::Directive::Section(asm::SectionType::Text),
+        asm::Directive::Type(label("check"), asm::SymbolType::Function),
+    ];
+    let mut unit = asm::TranslationUnit {
+        functions: vec![asm::Section::new(header, asm::Function::new(blocks))],
+        variables: Vec::new(),
+    };
+    assert_eq!(unit.functions[0].body.instruction_counts(), (7, 0));
+
+    assert!(asm::SplitCold::default().optimize(&mut unit));
+    let mut buffer = Vec::new();
+    write(&unit, &mut buffer).unwrap();
+    let expected = std::fs::read_to_string("examples/split_cold/check.s").unwrap();
+    assert_eq!(String::from_utf8(buffer).unwrap(), expected);
+
+    // The hot path has one instruction less, despite the added jumps.
+    assert_eq!(unit.functions[0].body.instruction_counts(), (6, 3));
+    assert!(!asm::SplitCold::default().optimize(&mut unit));
+
+    // A function without a cold block, or only with its entry block, is left as is.
+    let mut unit = asm::TranslationUnit {
+        functions: vec![asm::Section::stack_fail()],
+        variables: Vec::new(),
+    };
+    assert!(!asm::SplitCold::default().optimize(&mut unit));
+    assert!(unit.functions[0].body.cold_blocks.is_empty());
+
+    // The functions given as never returning are also taken into account.
+    let blocks = vec![
+        asm::Block::new(
+            Some(label("f")),
+            vec![branch(
+                asm::BType::Bne,
+                asm::Register::A0,
+                asm::Register::Zero,
+                ".f_L1",
+            )],
+        ),
+        asm::Block::new(None, vec![call("panic_handler")]),
+        asm::Block::new(
+            Some(label(".f_L1")),
+            vec![asm::Instruction::Pseudo(asm::Pseudo::Ret)],
+        ),
+    ];
+    let mut unit = asm::TranslationUnit {
+        functions: vec![asm::Section::new(Vec::new(), asm::Function::new(blocks))],
+        variables: Vec::new(),
+    };
+    assert!(!asm::SplitCold::default().optimize(&mut unit.clone()));
+    assert!(asm::SplitCold::new(vec!["panic_handler".to_string()]).optimize(&mut unit));
+    let function = &unit.functions[0].body;
+    assert_eq!(function.cold_blocks[0].label, Some(label(".f_split1")));
+    assert_eq!(
+        function.blocks[0].instructions.last(),
+        Some(&asm::Instruction::Pseudo(asm::Pseudo::J {
+            offset: label(".f_split1")
+        }))
+    );
+}
+
+#[test]
+fn test_examples_rvalue_struct() {
+    use kecc::ir::HasDtype;
+
+    // The reference lowering of the members of struct rvalues, agreeing with gcc on the result.
+    let ir = IrParse::default()
+        .translate(&Path::new("examples/rvalue_struct/nested.ir"))
+        .expect("parse failed");
+    assert_eq!(ir::verify(&ir), Ok(()));
+    assert_eq!(
+        ir::interp(&ir, Vec::new()).unwrap().get_int(),
+        Some((1, 32, true))
+    );
+
+    // Only the members are copied out of the temporaries, whose allocations are reused by the
+    // later full expressions.
+    let (_, main) = ir.decls["main"].get_function().unwrap();
+    let main = main.as_ref().unwrap();
+    let temporaries = main
+        .allocations
+        .iter()
+        .filter(|alloc| alloc.name().is_some_and(|name| name.starts_with('t')))
+        .map(|alloc| (**alloc).to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        temporaries,
+        ["struct outer", "struct inner", "struct outer"]
+    );
+
+    // The body is only checked, since lowering it is left as the homework.
+    let source = Parse
+        .translate(&Path::new("examples/rvalue_struct/nested.c"))
+        .expect("parse failed");
+    let names = ["make", "value_of", "second", "main"].map(String::from);
+    let lowered = Irgen::default()
+        .skip_definitions(HashSet::from(names.clone()))
+        .translate(&source)
+        .unwrap();
+    for name in names {
+        assert_eq!(
+            lowered.decls[&name].dtype().to_string(),
+            ir.decls[&name].dtype().to_string(),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn test_examples_target_check() {
+    // Every type of the examples is supported by the 64-bit target.
+    test_dir(Path::new("examples/ir0"), OsStr::new("ir"), |path| {
+        let ir = IrParse::default()
+            .translate(&path)
+            .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+        assert_eq!(target::check_target(&ir, &target::Riscv64), Vec::new());
+    });
+
+    // `double` has no place in the ABI of the 32-bit target, which supports `float` only.
+    let ir = IrParse::default()
+        .translate(&Path::new("examples/ir0/float.ir"))
+        .expect("parse failed");
+    let unsupported = target::check_target(&ir, &target::Riscv32);
+    assert_eq!(
+        unsupported
+            .iter()
+            .map(|error| error.site.as_str())
+            .collect::<Vec<_>>(),
+        [
+            "@average",
+            "average:b5:1",
+            "@custom_abs",
+            "custom_abs:%l0",
+            "@custom_max",
+            "custom_max:%l0",
+            "@is_close",
+            "is_close:%l0",
+            "main:b5:2",
+        ]
+    );
+    assert_eq!(
+        unsupported[1].to_string(),
+        "average:b5:1: error: `f64` is not supported by the target `riscv32`"
+    );
+    assert!(unsupported.iter().all(|error| error.target == "riscv32"));
+
+    let ir = IrParse::default()
+        .translate(&Path::new("examples/ir0/fibonacci.ir"))
+        .expect("parse failed");
+    assert_eq!(target::check_target(&ir, &target::Riscv32), Vec::new());
+}
+
+#[test]
+fn test_examples_runtime_helper() {
+    let parse = |path: &str| {
+        IrParse::default()
+            .translate(&Path::new(path))
+            .unwrap_or_else(|_| panic!("parse failed {path}"))
+    };
+    let word = |value: u64, width| ir::Value::Int {
+        value: value as u128,
+        width,
+        is_signed: false,
+    };
+    let values = [
+        0,
+        1,
+        3,
+        7,
+        0xffff_ffff,
+        0x1_0000_0000,
+        0x1234_5678_9abc_def0,
+        i64::MAX as u64,
+        i64::MIN as u64,
+        -1i64 as u64,
+        -7i64 as u64,
+        -0x1_0000_0001i64 as u64,
+    ];
+
+    // Each helper computes on the words as the 64-bit operation does.
+    let binary = parse("examples/runtime_helper/binary.ir");
+    let helpers: [(asm::RuntimeHelper, fn(u64, u64) -> Option<u64>); 5] = [
+        (asm::RuntimeHelper::Mul, |lhs, rhs| {
+            Some(lhs.wrapping_mul(rhs))
+        }),
+        (asm::RuntimeHelper::Udiv, u64::checked_div),
+        (asm::RuntimeHelper::Umod, u64::checked_rem),
+        (asm::RuntimeHelper::Div, |lhs, rhs| {
+            (lhs as i64)
+                .checked_div(rhs as i64)
+                .map(|value| value as u64)
+        }),
+        (asm::RuntimeHelper::Mod, |lhs, rhs| {
+            (lhs as i64)
+                .checked_rem(rhs as i64)
+                .map(|value| value as u64)
+        }),
+    ];
+    for (helper, expected) in helpers {
+        let mut ir = binary.clone();
+        let _unused = ir
+            .decls
+            .insert("helper".to_string(), runtime::declaration(helper));
+        assert_eq!(ir::verify(&ir), Ok(()), "{helper}");
+        for lhs in values {
+            for rhs in values {
+                let expected = some_or!(expected(lhs, rhs), continue);
+                let result = ir::interp(&ir, vec![word(lhs, 64), word(rhs, 64)]).unwrap();
+                assert_eq!(result, word(expected, 64), "{helper}({lhs:#x}, {rhs:#x})");
+            }
+        }
+    }
+
+    let shift = parse("examples/runtime_helper/shift.ir");
+    let helpers: [(asm::RuntimeHelper, fn(u64, u32) -> u64); 3] = [
+        (asm::RuntimeHelper::Shl, |value, amount| value << amount),
+        (asm::RuntimeHelper::Lshr, |value, amount| value >> amount),
+        (asm::RuntimeHelper::Ashr, |value, amount| {
+            ((value as i64) >> amount) as u64
+        }),
+    ];
+    for (helper, expected) in helpers {
+        let mut ir = shift.clone();
+        let _unused = ir
+            .decls
+            .insert("helper".to_string(), runtime::declaration(helper));
+        assert_eq!(ir::verify(&ir), Ok(()), "{helper}");
+        for value in values {
+            for amount in 0..64 {
+                let result =
+                    ir::interp(&ir, vec![word(value, 64), word(amount as u64, 32)]).unwrap();
+                assert_eq!(
+                    result,
+                    word(expected(value, amount), 64),
+                    "{helper}({value:#x}, {amount})"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_examples_debug_lines() {
+    let location = |file: &str, line| Location {
+        file: file.to_string(),
+        line,
+        column: 1,
+    };
+    let path = "examples/debug_lines/lines.c";
+    let locations = Parse::function_locations(path).expect("parse failed");
+    assert_eq!(
+        locations,
+        HashMap::from([
+            ("square".to_string(), location(path, 1)),
+            ("cube".to_string(), location("generated.c", 100)),
+            ("main".to_string(), location("generated.c", 104)),
+        ])
+    );
+
+    let function = |name: &str, imm| {
+        let label = asm::Label(name.to_string());
+        let header = vec![
+            asm::Directive::Globl(label.clone()),
+            asm::Directive::Section(asm::SectionType::Text),
+            asm::Directive::Type(label.clone(), asm::SymbolType::Function),
+        ];
+        let instructions = vec![
+            asm::Instruction::Pseudo(asm::Pseudo::Li {
+                rd: asm::Register::A0,
+                imm,
+            }),
+            asm::Instruction::Pseudo(asm::Pseudo::Ret),
+        ];
+        asm::Section::new(
+            header,
+            asm::Function::new(vec![asm::Block::new(Some(label), instructions)]),
+        )
+    };
+    let mut unit = asm::TranslationUnit {
+        functions: vec![
+            function("square", 4),
+            function("cube", 8),
+            function("main", 0),
+        ],
+        variables: Vec::new(),
+    };
+    assert!(asm::DebugLines::new(locations).optimize(&mut unit));
+
+    let mut buffer = Vec::new();
+    write(&unit, &mut buffer).unwrap();
+    let expected = std::fs::read_to_string("examples/debug_lines/lines.s").unwrap();
+    assert_eq!(String::from_utf8(buffer).unwrap(), expected);
+
+    // Functions of unknown locations, e.g., from an IR input, are left as they are.
+    let mut unit = asm::TranslationUnit {
+        functions: vec![function("square", 4)],
+        variables: Vec::new(),
+    };
+    assert!(!asm::DebugLines::default().optimize(&mut unit));
+    assert!(unit.functions[0].body.entry_directives.is_empty());
+}
+
+#[test]
+fn test_memory_stats() {
+    let status = "Name:\tkecc\nVmPeak:\t  20000 kB\nVmHWM:\t    8192 kB\nVmRSS:\t    4096 kB\n";
+    let stats = MemoryStats::from_status(status);
+    assert_eq!((stats.peak_rss, stats.rss), (Some(8192), Some(4096)));
+    assert_eq!(stats.to_string(), "memory: peak RSS 8192 KiB, RSS 4096 KiB");
+
+    let stats = MemoryStats::from_status("Name:\tkecc\n");
+    assert_eq!(stats.to_string(), "memory: peak RSS unknown, RSS unknown");
+
+    // The peak is never below the current usage.
+    let stats = MemoryStats::current();
+    if let (Some(peak_rss), Some(rss)) = (stats.peak_rss, stats.rss) {
+        assert!(peak_rss >= rss);
+    }
+}
+
+#[test]
+fn test_examples_incomplete_array() {
+    let path = Path::new("examples/incomplete_array/arrays.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let ir = Irgen::default()
+        .translate(&unit)
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+    assert_eq!(ir.decls["a"].dtype(), ir::Dtype::array(ir::Dtype::INT, 3));
+    assert_eq!(
+        ir.decls["m"].dtype(),
+        ir::Dtype::array(ir::Dtype::array(ir::Dtype::INT, 2), 3)
+    );
+    assert_eq!(
+        ir.decls["late"].dtype(),
+        ir::Dtype::array(ir::Dtype::INT, 4)
+    );
+    assert!(!ir.decls["late"].is_extern());
+
+    let table = ir.decls["table"].dtype();
+    assert_eq!(table, ir::Dtype::incomplete_array(ir::Dtype::CHAR));
+    assert_eq!(table.to_string(), "[? x i8]");
+    assert!(matches!(
+        table.size_align_of(&ir.structs),
+        Err(ir::DtypeError::IncompleteType { .. })
+    ));
+
+    let f = ir.decls["f"].dtype();
+    let (_, params) = f.get_function_inner().unwrap();
+    assert_eq!(params, &[ir::Dtype::pointer(ir::Dtype::INT)]);
+
+    let path = Path::new("examples/incomplete_array/errors.c");
+    let unit = Parse
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let (_, errors) = Irgen::default().translate_keep_going(&unit);
+    let expected = [
+        "array type has incomplete element type",
+        "incomplete type `[? x i32]` used as variable `missing`",
+    ];
+    assert_eq!(errors.len(), expected.len(), "{errors:#?}");
+    for (error, message) in errors.iter().zip(expected) {
+        assert_eq!(error.message.to_string(), message);
+    }
+}
+
+#[test]
+fn test_examples_parallel() {
+    let path = Path::new("examples/ir0/fibonacci.ir");
+    let unit = IrParse::default()
+        .translate(&path)
+        .unwrap_or_else(|_| panic!("parse failed {}", path.display()));
+    let unit = replicate_function(&unit, "fibonacci", 200);
+    let pipeline = (
+        SimplifyArith::default(),
+        (
+            Sink::default(),
+            (LoopRotate::default(), TailMerge::default()),
+        ),
+    );
+
+    // The functions optimized in parallel are the same as those optimized one by one.
+    let mut sequential = unit.clone();
+    let mut parallel = unit.clone();
+    assert_eq!(
+        pipeline.clone().optimize(&mut sequential),
+        ParallelFunctionPass::new(pipeline).optimize(&mut parallel)
+    );
+    let mut expected = Vec::new();
+    write(&sequential, &mut expected).unwrap();
+    let mut buffer = Vec::new();
+    write(&parallel, &mut buffer).unwrap();
+    assert_eq!(buffer, expected);
+
+    // The results are merged in the order of the functions.
+    let histograms = parallel.opcode_histograms();
+    assert_eq!(histograms.len(), 202);
+    assert_eq!(histograms["fibonacci_123"], histograms["fibonacci"]);
+
+    // The counts of the functions are summed up.
+    let histogram = parallel.opcode_histogram();
+    for (opcode, count) in &histograms["fibonacci"] {
+        assert_eq!(
+            histogram[opcode],
+            count * 201 + histograms["main"].get(opcode).unwrap_or(&0)
+        );
+    }
+}
+
+#[test]
+fn test_examples_vla() {
+    let path = "examples/vla/vla.c";
+    let unit = Parse
+        .translate(&Path::new(path))
+        .unwrap_or_else(|_| panic!("parse failed {path}"));
+    let source = lang_c::driver::parse(&lang_c::driver::Config::default(), path)
+        .unwrap_or_else(|_| panic!("parse failed {path}"));
+
+    // Variable-length arrays are rejected with the span of their declarators instead of panicking.
+    let (ir, errors) = Irgen::default().translate_keep_going(&unit);
+    let expected = ["buf[n]", "a[*]", "counts[n]"];
+    assert_eq!(errors.len(), expected.len(), "{errors:#?}");
+    for (error, snippet) in errors.iter().zip(expected) {
+        assert!(
+            matches!(
+                &error.message,
+                IrgenErrorMessage::InvalidDtype {
+                    dtype_error: ir::DtypeError::Misc { .. }
+                }
+            ),
+            "{error:#?}"
+        );
+        assert_eq!(
+            error.message.to_string(),
+            "variable-length arrays are not supported"
+        );
+        let span = error
+            .span
+            .expect("the span of the declarator must be known");
+        let text = &source.source[span.start..span.end];
+        assert!(
+            text.contains(snippet) && !text.contains(';'),
+            "`{text}` is not the span of `{snippet}`"
+        );
+    }
+    assert!(ir.decls.contains_key("n"));
+}
+
+#[test]
+fn test_examples_function_pointer() {
+    let parse = |path: &str| {
+        Parse
+            .translate(&Path::new(path))
+            .unwrap_or_else(|_| panic!("parse failed {path}"))
+    };
+    let int = ir::Dtype::INT;
+    let function = |params: Vec<ir::Dtype>| ir::Dtype::function(ir::Dtype::INT, params);
+    let pointer = ir::Dtype::pointer;
+
+    let ir = Irgen::default()
+        .translate(&parse("examples/function_pointer/declarators.c"))
+        .unwrap_or_else(|irgen_error| panic!("{}", irgen_error));
+    let binary = function(vec![int.clone(), int.clone()]);
+    let unary = function(vec![int.clone()]);
+    assert_eq!(ir.decls["cmp"].dtype(), pointer(binary.clone()));
+    assert_eq!(
+        ir.decls["table"].dtype(),
+        ir::Dtype::array(pointer(unary.clone()), 4)
+    );
+    assert_eq!(
+        ir.decls["getter"].dtype(),
+        pointer(ir::Dtype::function(pointer(unary.clone()), Vec::new()))
+    );
+
+    // Parameters of function types are adjusted to function pointers, as those of array types.
+    assert_eq!(
+        ir.decls["sort"].dtype(),
+        function(vec![pointer(int.clone()), int.clone(), pointer(binary)])
+    );
+    for name in ["apply", "apply_typedef", "apply_abstract"] {
+        assert_eq!(
+            ir.decls[name].dtype(),
+            function(vec![pointer(unary.clone()), int.clone()]),
+            "{name}"
+        );
+    }
+
+    // The calls through function pointers are typed by the return types of the functions.
+    let (_, errors) =
+        Irgen::default().translate_keep_going(&parse("examples/function_pointer/calls.c"));
+    assert_eq!(errors.len(), 3, "{errors:#?}");
+    for error in errors {
+        assert_eq!(
+            error.message.to_string(),
+            "left operand of assignment is not a modifiable l-value, as it is `const`-qualified"
+        );
+    }
+}
+
+#[test]
+fn test_examples_function_pointer_dispatch() {
+    let parse = |path: &str| {
+        IrParse::default()
+            .translate(&Path::new(path))
+            .unwrap_or_else(|error| panic!("{error}"))
+    };
+    let run = |ir: &ir::TranslationUnit, overflow_policy| {
+        let options = CompileOptions {
+            overflow_policy,
+            ..Default::default()
+        };
+        ir::interp_with_options(ir, Vec::new(), options)
+            .map(|value| value.get_int().expect("non-integer value occurs").0)
+    };
+
+    // A table of function pointers is indexed at runtime, and a function pointer loaded from a
+    // struct compares equal to the same function only, never to null or to an object.
+    let path = "examples/function_pointer/dispatch.ir";
+    let ir = parse(path);
+    assert_eq!(run(&ir, OverflowPolicy::Wrap), Ok(126));
+    test_cgen(Path::new(path));
+
+    // Calling a function through a pointer to an incompatible function type is reported unless
+    // the overflow policy is `wrap`, under which the result is read as the return type.
+    let path = "examples/function_pointer/mismatch.ir";
+    let ir = parse(path);
+    assert_eq!(run(&ir, OverflowPolicy::Wrap), Ok(42));
+    test_cgen(Path::new(path));
+    for policy in [OverflowPolicy::Trap, OverflowPolicy::Ub] {
+        assert!(
+            matches!(
+                run(&ir, policy),
+                Err(ir::InterpreterError::InvalidCall { func_name, .. })
+                    if func_name == "unsigned_twice"
+            ),
+            "{policy}"
+        );
+    }
+
+    // The addresses of distinct functions are folded as unequal.
+    let ir = parse("examples/function_pointer/compare.ir");
+    let mut folded = ir.clone();
+    assert!(SimplifyArith::default().optimize(&mut folded));
+    assert_eq!(run(&ir, OverflowPolicy::Wrap), Ok(0));
+    assert_eq!(run(&folded, OverflowPolicy::Wrap), Ok(0));
+    let (_, definition) = folded.decls["main"].get_function().unwrap();
+    assert!(!definition.as_ref().unwrap().blocks[&ir::BlockId(0)]
+        .instructions
+        .iter()
+        .any(|instruction| matches!(
+            **instruction,
+            ir::Instruction::BinOp {
+                op: lang_c::ast::BinaryOperator::Equals | lang_c::ast::BinaryOperator::NotEquals,
+                ..
+            }
+        )));
+}
+
+#[test]
+fn test_examples_address_comparison() {
+    let parse = |path: &str| {
+        IrParse::default()
+            .translate(&Path::new(path))
+            .unwrap_or_else(|_| panic!("parse failed {path}"))
+    };
+    let run = |ir: &ir::TranslationUnit| {
+        let result = ir::interp(ir, Vec::new()).unwrap();
+        let (value, _, _) = result.get_int().expect("non-integer value occurs");
+        value
+    };
+    let instruction = |ir: &ir::TranslationUnit, iid: usize| {
+        let (_, definition) = ir.decls["main"].get_function().unwrap();
+        let block = &definition.as_ref().unwrap().blocks[&ir::BlockId(0)];
+        (*block.instructions[iid]).clone()
+    };
+
+    // `&x == &x`, `&x == 0` and `0 != &l` are folded, and `&a[i] == &a[j]` and `&a[i] != a`
+    // compare the offsets from `a` instead.
+    let ir = parse("examples/address_comparison/fold.ir");
+    let mut folded = ir.clone();
+    assert!(SimplifyArith::default().optimize(&mut folded));
+    assert_eq!(run(&ir), 29);
+    assert_eq!(run(&folded), 29);
+    let bit = |value| ir::Operand::constant(ir::Constant::int(value, ir::Dtype::BOOL));
+    for (iid, folded_iid, value) in [(15, 0, 1), (16, 2, 0), (18, 3, 1)] {
+        assert_eq!(
+            instruction(&folded, iid),
+            ir::Instruction::TypeCast {
+                value: bit(value),
+                target_dtype: ir::Dtype::INT,
+            },
+            "%b0:i{folded_iid}"
+        );
+    }
+    let offset =
+        |iid| ir::Operand::register(ir::RegisterId::temp(ir::BlockId(0), iid), ir::Dtype::LONG);
+    let compare = |op, lhs, rhs| ir::Instruction::BinOp {
+        op,
+        lhs,
+        rhs,
+        dtype: ir::Dtype::BOOL,
+    };
+    assert_eq!(
+        instruction(&folded, 13),
+        compare(lang_c::ast::BinaryOperator::Equals, offset(6), offset(9))
+    );
+    assert_eq!(
+        instruction(&folded, 14),
+        compare(
+            lang_c::ast::BinaryOperator::NotEquals,
+            offset(6),
+            ir::Operand::constant(ir::Constant::int(0, ir::Dtype::LONG))
+        )
+    );
+
+    // The addresses of distinct objects are kept, since one may be one past the end of the other,
+    // and so is the null comparison of an address computed by `getelementptr`.
+    let ir = parse("examples/address_comparison/kept.ir");
+    let mut kept = ir.clone();
+    assert!(!SimplifyArith::default().optimize(&mut kept));
+    assert_eq!(kept, ir);
+    assert_eq!(run(&kept), 0);
+}